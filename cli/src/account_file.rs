@@ -0,0 +1,179 @@
+use std::{fmt::Write, fs, str::FromStr};
+
+use anchor_lang::{prelude::Pubkey, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sablier_network_program::state::{
+    Config, Delegation, Fee, Penalty, Pool, Registry, Snapshot, SnapshotEntry, SnapshotFrame,
+    Unstake, Worker,
+};
+use sablier_thread_program::state::Thread;
+use serde::Deserialize;
+use solana_account_decoder::UiAccount;
+use solana_sdk::account::Account;
+
+use crate::errors::CliError;
+
+/// Where the data of a decoded account is read from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DecodeSource {
+    /// A JSON account file, or a file of the raw account data.
+    File(String),
+    Base64(String),
+    Address(Pubkey),
+}
+
+/// An account as printed by `solana account --output json`, or as returned by the `getAccountInfo` RPC method.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AccountFile {
+    Keyed { pubkey: String, account: UiAccount },
+    Unkeyed(UiAccount),
+}
+
+/// The data of an account, with its address when it is known.
+#[derive(Debug, PartialEq)]
+pub struct AccountData {
+    pub address: Option<Pubkey>,
+    pub data: Vec<u8>,
+}
+
+/// Reads the data of an account from a JSON account file, or from a file of the raw account data.
+pub fn read_account_file(path: &str) -> Result<AccountData, CliError> {
+    let bytes =
+        fs::read(path).map_err(|err| CliError::InvalidAccountFile(format!("{}: {}", path, err)))?;
+    parse_account_file(bytes)
+        .map_err(|err| CliError::InvalidAccountFile(format!("{}: {}", path, err)))
+}
+
+/// Parses an account file, the files starting with a `{` are JSON and the others raw data.
+fn parse_account_file(bytes: Vec<u8>) -> Result<AccountData, String> {
+    if bytes.iter().find(|byte| !byte.is_ascii_whitespace()) != Some(&b'{') {
+        return Ok(AccountData {
+            address: None,
+            data: bytes,
+        });
+    }
+    let (address, account) = match serde_json::from_slice::<AccountFile>(&bytes)
+        .map_err(|_err| "neither a JSON account nor a JSON keyed account".to_string())?
+    {
+        AccountFile::Keyed { pubkey, account } => (
+            Some(
+                Pubkey::from_str(&pubkey)
+                    .map_err(|err| format!("pubkey: invalid pubkey `{}`: {}", pubkey, err))?,
+            ),
+            account,
+        ),
+        AccountFile::Unkeyed(account) => (None, account),
+    };
+    let account = account
+        .decode::<Account>()
+        .ok_or("data: only the base58, base64 and base64+zstd encodings are supported")?;
+    Ok(AccountData {
+        address,
+        data: account.data,
+    })
+}
+
+pub fn decode_base64(data: &str) -> Result<Vec<u8>, CliError> {
+    STANDARD
+        .decode(data.trim())
+        .map_err(|err| CliError::BadParameter(format!("base64: {}", err)))
+}
+
+/// Describes the discriminator of account data, with the Sablier account type it belongs to when there is one.
+pub fn describe_discriminator(data: &[u8]) -> String {
+    let Some(discriminator) = data.get(..8) else {
+        return format!(
+            "only {} bytes of data, too short for a discriminator",
+            data.len()
+        );
+    };
+    let hex = discriminator.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{:02x}", byte);
+        hex
+    });
+    let known = [
+        ("Config", Config::DISCRIMINATOR),
+        ("Delegation", Delegation::DISCRIMINATOR),
+        ("Fee", Fee::DISCRIMINATOR),
+        ("Penalty", Penalty::DISCRIMINATOR),
+        ("Pool", Pool::DISCRIMINATOR),
+        ("Registry", Registry::DISCRIMINATOR),
+        ("Snapshot", Snapshot::DISCRIMINATOR),
+        ("SnapshotEntry", SnapshotEntry::DISCRIMINATOR),
+        ("SnapshotFrame", SnapshotFrame::DISCRIMINATOR),
+        ("Thread", Thread::DISCRIMINATOR),
+        ("Unstake", Unstake::DISCRIMINATOR),
+        ("Worker", Worker::DISCRIMINATOR),
+    ];
+    match known
+        .iter()
+        .find(|(_, known)| known.as_slice() == discriminator)
+    {
+        Some((name, _)) => format!("the discriminator {} of a {} account", hex, name),
+        None => format!("the unknown discriminator {}", hex),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_solana_cli_json_accounts() {
+        let file = r#"{
+            "pubkey": "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi",
+            "account": {
+                "lamports": 1000,
+                "data": ["AQID", "base64"],
+                "owner": "11111111111111111111111111111111",
+                "executable": false,
+                "rentEpoch": 18446744073709551615,
+                "space": 3
+            }
+        }"#;
+        assert_eq!(
+            parse_account_file(file.as_bytes().to_vec()).unwrap(),
+            AccountData {
+                address: Some(Pubkey::new_from_array([1; 32])),
+                data: vec![1, 2, 3],
+            }
+        );
+
+        let file = r#"{"lamports": 1, "data": ["Ldp", "base58"], "owner": "11111111111111111111111111111111", "executable": false, "rentEpoch": 0}"#;
+        assert_eq!(
+            parse_account_file(file.as_bytes().to_vec()).unwrap(),
+            AccountData {
+                address: None,
+                data: vec![1, 2, 3],
+            }
+        );
+        assert!(parse_account_file(b"{\"data\": 1}".to_vec()).is_err());
+    }
+
+    #[test]
+    fn raw_files_are_the_account_data() {
+        assert_eq!(
+            parse_account_file(vec![0, 1, 2]).unwrap(),
+            AccountData {
+                address: None,
+                data: vec![0, 1, 2],
+            }
+        );
+    }
+
+    #[test]
+    fn discriminators_are_named_after_their_account_type() {
+        let worker = describe_discriminator(&[Worker::DISCRIMINATOR.as_slice(), &[0; 4]].concat());
+        assert!(worker.starts_with("the discriminator "));
+        assert!(worker.ends_with(" of a Worker account"));
+        assert_eq!(
+            describe_discriminator(&[0xff; 8]),
+            "the unknown discriminator ffffffffffffffff"
+        );
+        assert_eq!(
+            describe_discriminator(&[1, 2]),
+            "only 2 bytes of data, too short for a discriminator"
+        );
+    }
+}
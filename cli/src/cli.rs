@@ -1,8 +1,14 @@
-use clap::{crate_version, Arg, ArgGroup, Command};
-use sablier_thread_program::state::{SerializableInstruction, Trigger};
+use chrono_tz::Tz;
+use clap::{crate_version, Arg, ArgAction, ArgGroup, Command};
+use sablier_thread_program::state::{Equality, SerializableInstruction, Trigger};
 use solana_sdk::{pubkey::Pubkey, signature::Keypair};
 
-use crate::parser::ProgramInfo;
+use crate::{
+    account_file::DecodeSource,
+    export::{ExportField, ExportFormat},
+    manifest::{BatchAction, BatchThread},
+    parser::ProgramInfo,
+};
 
 #[derive(Debug, PartialEq)]
 pub enum CliCommand {
@@ -12,11 +18,15 @@ pub enum CliCommand {
         admin: Option<Pubkey>,
         epoch_thread: Option<Pubkey>,
         hasher_thread: Option<Pubkey>,
+        mint: Option<Pubkey>,
     },
 
     // Crontab
     Crontab {
         schedule: String,
+        count: usize,
+        timezone: Option<Tz>,
+        after: Option<i64>,
     },
 
     // Delegation
@@ -24,7 +34,7 @@ pub enum CliCommand {
         worker_id: u64,
     },
     DelegationDeposit {
-        amount: u64,
+        amount: String,
         delegation_id: u64,
         worker_id: u64,
     },
@@ -33,13 +43,14 @@ pub enum CliCommand {
         worker_id: u64,
     },
     DelegationWithdraw {
-        amount: u64,
+        amount: String,
         delegation_id: u64,
         worker_id: u64,
     },
 
     ExplorerGetThread {
         id: Option<String>,
+        domain: Option<String>,
         address: Option<Pubkey>,
     },
 
@@ -51,11 +62,13 @@ pub enum CliCommand {
     Localnet {
         force_init: bool,
         clone_addresses: Vec<Pubkey>,
+        clone_programs: Vec<Pubkey>,
         network_url: Option<String>,
         program_infos: Vec<ProgramInfo>,
         solana_archive: Option<String>,
         sablier_archive: Option<String>,
         dev: bool,
+        keep_ledger: bool,
     },
 
     // Pool commands
@@ -73,48 +86,166 @@ pub enum CliCommand {
     ThreadCrateInfo,
 
     // Thread commands
+    ThreadBatch {
+        action: BatchAction,
+        threads: Vec<BatchThread>,
+        continue_on_error: bool,
+        dry_run: bool,
+    },
+    ThreadPauseAll {
+        /// Pauses the threads, or resumes them.
+        pause: bool,
+        pattern: Option<String>,
+    },
+    ThreadClone {
+        from_id: Option<String>,
+        from_domain: Option<String>,
+        from_address: Option<Pubkey>,
+        id: String,
+        domain: Option<String>,
+        paused: bool,
+    },
     ThreadCreate {
         id: String,
-        domain: String,
-        kickoff_instruction: SerializableInstruction,
-        trigger: Trigger,
+        domain: Option<String>,
+        instructions: Vec<SerializableInstruction>,
+        trigger: TriggerArg,
+    },
+    ThreadDecode {
+        source: DecodeSource,
     },
     ThreadDelete {
-        id: String,
+        id: Option<String>,
+        domain: Option<String>,
+        address: Option<Pubkey>,
+    },
+    ThreadExport {
+        format: ExportFormat,
+        out: Option<String>,
+        fields: Vec<ExportField>,
+    },
+    ThreadFund {
+        id: Option<String>,
+        domain: Option<String>,
+        address: Option<Pubkey>,
+        lamports: u64,
     },
     ThreadGet {
         id: Option<String>,
+        domain: Option<String>,
         address: Option<Pubkey>,
     },
+    ThreadList {
+        limit: Option<usize>,
+        json: bool,
+    },
+    ThreadLogs {
+        id: Option<String>,
+        domain: Option<String>,
+        address: Option<Pubkey>,
+        limit: usize,
+        json: bool,
+    },
+    ThreadPubkey {
+        id: Vec<u8>,
+        domain: Option<String>,
+    },
     ThreadPause {
-        id: String,
+        id: Option<String>,
+        domain: Option<String>,
+        address: Option<Pubkey>,
     },
     ThreadResume {
-        id: String,
+        id: Option<String>,
+        domain: Option<String>,
+        address: Option<Pubkey>,
     },
     ThreadReset {
-        id: String,
+        id: Option<String>,
+        domain: Option<String>,
+        address: Option<Pubkey>,
+    },
+    ThreadSetInstruction {
+        id: Option<String>,
+        domain: Option<String>,
+        address: Option<Pubkey>,
+        /// The index of the instruction replaced, or `None` to append the instruction.
+        index: Option<usize>,
+        instruction: SerializableInstruction,
+    },
+    ThreadSimulate {
+        id: Option<String>,
+        domain: Option<String>,
+        address: Option<Pubkey>,
+        worker_id: u64,
+    },
+    ThreadWatch {
+        id: Option<String>,
+        domain: Option<String>,
+        address: Option<Pubkey>,
+        interval: Option<u64>,
+    },
+    ThreadWithdraw {
+        id: Option<String>,
+        domain: Option<String>,
+        address: Option<Pubkey>,
+        lamports: u64,
+        to: Option<Pubkey>,
     },
     ThreadUpdate {
         id: String,
+        domain: Option<String>,
+        fee: Option<u64>,
+        name: Option<String>,
         rate_limit: Option<u64>,
-        schedule: Option<String>,
+        instructions: Option<Vec<SerializableInstruction>>,
+        trigger: Option<TriggerArg>,
+        /// Overrides the skippability of the new or the current cron trigger.
+        skippable: Option<bool>,
     },
 
     // Registry
     RegistryGet,
     RegistryUnlock,
 
+    Version {
+        cluster: Option<String>,
+    },
+
     // Worker commands
     WorkerCreate {
         signatory: Keypair,
     },
+    WorkerClaim {
+        address: Pubkey,
+        lamports: Option<u64>,
+        to: Option<Pubkey>,
+    },
     WorkerGet {
-        id: u64,
+        address: Pubkey,
     },
     WorkerUpdate {
-        id: u64,
-        signatory: Option<Keypair>,
+        address: Pubkey,
+        commission_rate: Option<u64>,
+        signatory: Option<Pubkey>,
+    },
+    WorkerBalances {
+        min_signatory_balance: Option<u64>,
+        json: bool,
+    },
+}
+
+/// The trigger of the thread commands, parsed from the trigger flags.
+#[derive(Debug, PartialEq)]
+pub enum TriggerArg {
+    Trigger(Trigger),
+    /// A pyth trigger, its limit is a decimal price until it is scaled by the exponent of the feed.
+    Pyth {
+        feed_id: [u8; 32],
+        equality: Equality,
+        price: String,
+        /// The exponent of the prices of the feed, read from its price account when it isn't set.
+        exponent: Option<i32>,
     },
 }
 
@@ -124,6 +255,121 @@ pub fn app() -> Command {
         .about("An automation engine for the Solana blockchain")
         .version(crate_version!())
         .arg_required_else_help(true)
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .global(true)
+                .value_parser(["text", "json"])
+                .default_value("text")
+                .help("The format of the printed accounts"),
+        )
+        .arg(
+            Arg::new("verbose")
+                .long("verbose")
+                .short('v')
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Print the causes of an error, and the program logs of a failed transaction"),
+        )
+        .arg(
+            Arg::new("url")
+                .long("url")
+                .short('u')
+                .global(true)
+                .value_name("URL_OR_MONIKER")
+                .help("The RPC URL of the cluster or its moniker (or its first letter): [mainnet-beta, devnet, testnet, localhost], \
+                    defaults to the SABLIER_RPC_URL environment variable, then to the Solana config"),
+        )
+        .arg(
+            Arg::new("ws")
+                .long("ws")
+                .global(true)
+                .value_name("URL")
+                .help("The websocket URL of the cluster, derived from the RPC URL by default"),
+        )
+        .arg(
+            Arg::new("keypair")
+                .long("keypair")
+                .global(true)
+                .value_name("KEYPAIR")
+                .help("The payer, a keypair path or a signer URI like usb://ledger, defaults to the keypair of the Solana config"),
+        )
+        .arg(
+            Arg::new("authority")
+                .long("authority")
+                .global(true)
+                .value_name("AUTHORITY")
+                .help("The authority of the threads and workers, defaults to the payer. \
+                    Either an address, or a keypair path or signer URI like usb://ledger which signs along with the payer"),
+        )
+        .arg(
+            Arg::new("dump_transaction")
+                .long("dump-transaction")
+                .global(true)
+                .value_name("ENCODING")
+                .num_args(0..=1)
+                .default_missing_value("base64")
+                .value_parser(["base64", "base58"])
+                .help("Print the unsigned transaction paid by the authority instead of sending it, \
+                    as a base64 transaction or a base58 message"),
+        )
+        .arg(
+            Arg::new("commitment")
+                .long("commitment")
+                .global(true)
+                .value_name("LEVEL")
+                .value_parser(["processed", "confirmed", "finalized"])
+                .default_value("confirmed")
+                .help("The commitment the sent transactions are confirmed at"),
+        )
+        .arg(
+            Arg::new("priority_fee")
+                .long("priority-fee")
+                .global(true)
+                .value_name("MICROLAMPORTS")
+                .value_parser(clap::value_parser!(u64))
+                .help("The compute unit price of the sent transactions"),
+        )
+        .arg(
+            Arg::new("auto_priority_fee")
+                .long("auto-priority-fee")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .conflicts_with("priority_fee")
+                .help("Price the compute units of the sent transactions with the recent prioritization fees of their writable accounts"),
+        )
+        .arg(
+            Arg::new("explorer")
+                .long("explorer")
+                .global(true)
+                .value_parser(["solana", "solscan", "solana.fm"])
+                .default_value("solana")
+                .help("The block explorer of the printed links, the localnet links always use the official explorer"),
+        )
+        .arg(
+            Arg::new("compute_units")
+                .long("compute-units")
+                .global(true)
+                .value_name("UNITS")
+                .value_parser(clap::value_parser!(u32))
+                .help("The compute unit limit of the sent transactions"),
+        )
+        .arg(
+            Arg::new("yes")
+                .long("yes")
+                .short('y')
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Skip the confirmation of the destructive commands, e.g. in scripts"),
+        )
+        .arg(
+            Arg::new("skip_version_check")
+                .long("skip-version-check")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Don't warn when the programs of the cluster are incompatible with this CLI, the versions are checked once a day"),
+        )
         .subcommand(
             Command::new("config")
                 .about("Manage the Sablier network config")
@@ -131,26 +377,46 @@ pub fn app() -> Command {
                 .subcommand(Command::new("get").about("Get a config value"))
                 .subcommand(
                     Command::new("set")
-                        .about("Set a config value")
+                        .about("Set a config value, signed by the current admin")
+                        .arg_required_else_help(true)
                         .arg(
                             Arg::new("admin")
                                 .long("admin")
                                 .value_name("ADDRESS")
+                                .requires("yes_i_know")
+                                .help("The new admin, it must sign to transfer the admin back")
                         )
                         .arg(
                             Arg::new("epoch_thread")
-                                .long("epoch_thread")
+                                .long("epoch-thread")
+                                .visible_alias("epoch_thread")
                                 .value_name("ADDRESS")
+                                .help("The thread incrementing the epochs")
                         )
                         .arg(
                             Arg::new("hasher_thread")
-                                .long("hasher_thread")
+                                .long("hasher-thread")
+                                .visible_alias("hasher_thread")
                                 .value_name("ADDRESS")
+                                .help("The thread hashing the registry nonce")
+                        )
+                        .arg(
+                            Arg::new("mint")
+                                .long("mint")
+                                .value_name("ADDRESS")
+                                .help("The mint of the tokens staked with the workers")
+                        )
+                        .arg(
+                            Arg::new("yes_i_know")
+                                .long("yes-i-know")
+                                .action(ArgAction::SetTrue)
+                                .help("Confirm the transfer of the admin, which is irreversible")
                         )
                         .group(
                             ArgGroup::new("config_settings")
-                                .args(["admin", "epoch_thread", "hasher_thread"])
-                                .multiple(true),
+                                .args(["admin", "epoch_thread", "hasher_thread", "mint"])
+                                .multiple(true)
+                                .required(true),
                         ),
                 ),
         )
@@ -163,93 +429,59 @@ pub fn app() -> Command {
                         .index(1)
                         .required(true)
                         .help("The schedule to generate a cron table for"),
+                )
+                .arg(
+                    Arg::new("count")
+                        .long("count")
+                        .short('n')
+                        .default_value("9")
+                        .help("The number of moments to print"),
+                )
+                .arg(
+                    Arg::new("timezone")
+                        .long("timezone")
+                        .value_name("TZ")
+                        .help("The IANA timezone to print the moments in, besides UTC"),
+                )
+                .arg(
+                    Arg::new("after")
+                        .long("after")
+                        .value_name("UNIX_TS|RFC3339")
+                        .help("The moment to start from, defaults to the cluster clock"),
                 ),
         )
         .subcommand(
             Command::new("delegation")
                 .about("Manage a stake delegation to a Sablier worker")
+                .arg_required_else_help(true)
                 .subcommand(
                     Command::new("create")
                         .about("Create a new delegation")
                         .arg_required_else_help(true)
-                        .arg(
-                            Arg::new("worker_id")
-                                .long("worker_id")
-                                .short('w')
-                                .required(false)
-                                .help("The ID of the worker to create a delegation with"),
-                        ),
+                        .arg(worker_id_arg().help("The ID of the worker to create a delegation with")),
                 )
                 .subcommand(
                     Command::new("deposit")
-                        .about("Deposit CLOCK to a delegation account")
+                        .about("Deposit tokens to a delegation account")
                         .arg_required_else_help(true)
-                        .arg(
-                            Arg::new("amount")
-                                .long("amount")
-                                .short('a')
-                                .required(false)
-                                .help("The number of tokens to deposit"),
-                        )
-                        .arg(
-                            Arg::new("delegation_id")
-                                .long("delegation_id")
-                                .short('i')
-                                .required(false)
-                                .help("The ID of the delegation to deposit into"),
-                        )
-                        .arg(
-                            Arg::new("worker_id")
-                                .long("worker_id")
-                                .short('w')
-                                .required(false)
-                                .help("The ID of the worker"),
-                        ),
+                        .arg(delegation_amount_arg().help("The amount of tokens to deposit"))
+                        .arg(delegation_id_arg().help("The ID of the delegation to deposit into"))
+                        .arg(worker_id_arg()),
                 )
                 .subcommand(
                     Command::new("get")
                         .about("Get a delegation")
                         .arg_required_else_help(true)
-                        .arg(
-                            Arg::new("delegation_id")
-                                .long("delegation_id")
-                                .short('i')
-                                .required(false)
-                                .help("The ID of the delegation"),
-                        )
-                        .arg(
-                            Arg::new("worker_id")
-                                .long("worker_id")
-                                .short('w')
-                                .required(false)
-                                .help("The ID of the worker"),
-                        ),
+                        .arg(delegation_id_arg())
+                        .arg(worker_id_arg()),
                 )
                 .subcommand(
                     Command::new("withdraw")
-                        .about("Withdraw CLOCK from a delegation account")
+                        .about("Withdraw tokens from a delegation account")
                         .arg_required_else_help(true)
-                        .arg(
-                            Arg::new("amount")
-                                .long("amount")
-                                .short('a')
-                                .required(false)
-                                .help("The number of tokens to withdraw"),
-                        )
-                        .arg(
-                            Arg::new("delegation_id")
-                                .long("delegation_id")
-                                .short('i')
-                                .required(false)
-                                .help("The ID of the delegation to withdraw from"),
-                        )
-                        .arg(
-                            Arg::new("worker_id")
-                                .long("worker_id")
-                                .short('w')
-                                .required(false)
-                                .help("The ID of the worker"),
-                        ),
+                        .arg(delegation_amount_arg().help("The amount of tokens to withdraw"))
+                        .arg(delegation_id_arg().help("The ID of the delegation to withdraw from"))
+                        .arg(worker_id_arg()),
                 ),
         )
         .subcommand(
@@ -260,19 +492,8 @@ pub fn app() -> Command {
                     Command::new("get")
                         .about("Prints thread explorer url")
                         .arg_required_else_help(true)
-                        .arg(
-                            Arg::new("id")
-                                .index(1)
-                                .required(false)
-                                .help("The label of the thread to lookup (only works if you \
-                                are the signer of that thread)")
-                        )
-                        .arg(
-                            Arg::new("address")
-                                .short('k')
-                                .long("address")
-                                .help("The address of the thread to lookup"),
-                        ),
+                        .args(thread_lookup_args("look up"))
+                        .group(thread_lookup_group()),
                 )
         )
         .subcommand(
@@ -308,8 +529,18 @@ pub fn app() -> Command {
                     .value_names(["ADDRESS"])
                     .num_args(1..)
                     .number_of_values(1)
+                    .requires("url")
                     .help("Copy an account from the cluster referenced by the --url argument the genesis configuration. If the ledger already exists then this parameter is silently ignored")
                 )
+                .arg(
+                    Arg::new("clone_program")
+                    .long("clone-program")
+                    .value_name("PROGRAM_ID")
+                    .action(ArgAction::Append)
+                    .requires("url")
+                    .help("Copy a program from the cluster referenced by the --url argument the genesis configuration, \
+                        with the program data of an upgradeable program")
+                )
                 .arg(
                     Arg::new("url")
                     .long("url")
@@ -317,10 +548,12 @@ pub fn app() -> Command {
                     .value_names(["URL_OR_MONIKER"])
                     .num_args(1)
                     .number_of_values(1)
-                    .help("URL for Solana's JSON RPC or moniker (or their first letter): [mainnet-beta, testnet, devnet, localhost]")
+                    .help("URL for Solana's JSON RPC or moniker (or their first letter): [mainnet-beta, testnet, devnet, localhost], \
+                        the cluster the accounts and programs are cloned from")
                 )
                 .arg(Arg::new("force_init")
                     .long("force-init")
+                    .action(ArgAction::SetTrue)
                     .help("Initializes and downloads localnet dependencies")
                 )
                 .arg(
@@ -342,24 +575,31 @@ pub fn app() -> Command {
                 .arg(
                     Arg::new("dev")
                         .long("dev")
+                        .action(ArgAction::SetTrue)
                         .help("Use development versions of sablier programs")
                     )
+                .arg(
+                    Arg::new("keep_ledger")
+                        .long("keep-ledger")
+                        .action(ArgAction::SetTrue)
+                        .help("Keep the ledger of the previous run rather than resetting it, \
+                            the network state and the worker already bootstrapped in it are reused")
+                    )
         )
         .subcommand(
             Command::new("pool")
                 .about("Manage the Sablier network worker pools")
                 .subcommand(
                     Command::new("get")
-                        .about("Get a pool")
-                        .arg_required_else_help(true)
+                        .about("Get a pool, with its workers in rotation order")
                         .arg(
                             Arg::new("id")
                                 .index(1)
-                                .required(false)
+                                .default_value("0")
                                 .help("The ID of the pool to lookup"),
                         ),
                 )
-                .subcommand(Command::new("list").about("List the pools"))
+                .subcommand(Command::new("list").about("List the pools, by ID until the first missing one"))
                 .subcommand(
                     Command::new("update")
                         .about("Update a pool")
@@ -464,6 +704,76 @@ pub fn app() -> Command {
             Command::new("thread")
                 .about("Manage your transaction threads")
                 .arg_required_else_help(true)
+                .subcommand(
+                    Command::new("batch")
+                        .about("Pause, resume, delete or update the threads of a manifest, in as few transactions as fit")
+                        .arg_required_else_help(true)
+                        .arg(
+                            Arg::new("action")
+                                .index(1)
+                                .value_parser(["pause", "resume", "delete", "update"])
+                                .required(true)
+                                .help("The action applied to every thread of the manifest"),
+                        )
+                        .arg(
+                            Arg::new("file")
+                                .long("file")
+                                .short('f')
+                                .value_name("PATH")
+                                .required(true)
+                                .help("The YAML or JSON manifest listing the threads by id or address, \
+                                    with their fee, name, rate_limit, schedule or skippable settings for an update"),
+                        )
+                        .arg(
+                            Arg::new("continue_on_error")
+                                .long("continue-on-error")
+                                .action(ArgAction::SetTrue)
+                                .help("Send the next transactions after a failed one rather than stopping"),
+                        )
+                        .arg(
+                            Arg::new("dry_run")
+                                .long("dry-run")
+                                .action(ArgAction::SetTrue)
+                                .help("Print the planned transactions without sending them"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("clone")
+                        .about("Create a thread with the instructions, trigger and settings of another one, under a new id")
+                        .arg_required_else_help(true)
+                        .arg(
+                            Arg::new("from")
+                                .long("from")
+                                .value_name("ID_OR_ADDRESS")
+                                .required(true)
+                                .help("The id of the authority's thread to clone, or the address of any thread"),
+                        )
+                        .arg(
+                            Arg::new("from_domain")
+                                .long("from-domain")
+                                .value_name("DOMAIN")
+                                .help("The domain of the thread to clone, when it is looked up by id"),
+                        )
+                        .arg(
+                            Arg::new("to_id")
+                                .long("to-id")
+                                .value_name("ID")
+                                .required(true)
+                                .help("The id of the clone, it must not exist yet"),
+                        )
+                        .arg(
+                            Arg::new("to_domain")
+                                .long("to-domain")
+                                .value_name("DOMAIN")
+                                .help("The domain of the clone"),
+                        )
+                        .arg(
+                            Arg::new("paused")
+                                .long("paused")
+                                .action(ArgAction::SetTrue)
+                                .help("Pause the clone in its create transaction, so it doesn't execute before it is resumed"),
+                        ),
+                )
                 .subcommand(
                     Command::new("crate-info")
                         .about("Crate Information")
@@ -471,6 +781,7 @@ pub fn app() -> Command {
                 .subcommand(
                     Command::new("create")
                         .about("Create an new thread")
+                        .arg(domain_arg())
                         .arg_required_else_help(true)
                         .arg(
                             Arg::new("id")
@@ -485,32 +796,52 @@ pub fn app() -> Command {
                                 .long("kickoff_instruction")
                                 .short('k')
                                 .value_name("FILEPATH")
-                                .required(true)
                                 .help("Filepath to a description of the kickoff instruction"),
                         )
                         .arg(
-                            Arg::new("account")
-                                .long("account")
-                                .short('a')
-                                .value_name("ADDRESS")
-                                .help("An account-based trigger"),
+                            Arg::new("instructions_file")
+                                .long("instructions-file")
+                                .value_name("FILEPATH")
+                                .help(
+                                    "Filepath to a JSON list of instructions, or base64 encoded bincode. \
+                                    Data is base58, or hex prefixed with 0x, and PAYER stands for the worker",
+                                ),
+                        )
+                        .group(
+                            ArgGroup::new("instructions")
+                                .args(["kickoff_instruction", "instructions_file"])
+                                .required(true),
                         )
+                        .args(trigger_args())
+                        .groups(trigger_groups(true)),
+                )
+                .subcommand(
+                    Command::new("decode")
+                        .about("Decode the data of a thread account, from a file, base64 or the cluster, and print it like `thread get`")
+                        .arg_required_else_help(true)
                         .arg(
-                            Arg::new("cron")
-                                .long("cron")
-                                .short('c')
-                                .value_name("SCHEDULE")
-                                .help("A cron-based trigger"),
+                            Arg::new("file")
+                                .long("file")
+                                .short('f')
+                                .value_name("PATH")
+                                .help("A JSON account file, as printed by `solana account --output json`, or a file of the raw account data"),
                         )
                         .arg(
-                            Arg::new("immediate")
-                                .long("immediate")
-                                .short('m')
-                                .help("An immediate trigger"),
+                            Arg::new("base64")
+                                .long("base64")
+                                .value_name("DATA")
+                                .help("The base64 encoded account data"),
+                        )
+                        .arg(
+                            Arg::new("address")
+                                .long("address")
+                                .short('k')
+                                .value_name("ADDRESS")
+                                .help("The address of the thread account to fetch"),
                         )
                         .group(
-                            ArgGroup::new("trigger")
-                                .args(["account", "cron", "immediate"])
+                            ArgGroup::new("source")
+                                .args(["file", "base64", "address"])
                                 .required(true),
                         ),
                 )
@@ -518,16 +849,32 @@ pub fn app() -> Command {
                     Command::new("delete")
                         .about("Delete a thread")
                         .arg_required_else_help(true)
+                        .args(thread_lookup_args("delete"))
+                        .group(thread_lookup_group()),
+                )
+                .subcommand(
+                    Command::new("fund")
+                        .about("Transfer SOL to a thread, to pay for its executions")
+                        .arg_required_else_help(true)
+                        .arg(domain_arg())
                         .arg(
-                        Arg::new("id")
-                            .index(1)
-                            .required(false)
-                            .help("The id of the thread to delete"),
-                    ),
+                            Arg::new("id")
+                                .index(1)
+                                .required(false)
+                                .help("The id of the thread to fund"),
+                        )
+                        .arg(
+                            Arg::new("address")
+                                .short('k')
+                                .long("address")
+                                .help("The address of the thread to fund"),
+                        )
+                        .args(amount_args()),
                 )
                 .subcommand(
                     Command::new("get")
                         .about("Lookup a thread")
+                        .arg(domain_arg())
                         .arg_required_else_help(true)
                         .arg(
                         Arg::new("id")
@@ -543,36 +890,224 @@ pub fn app() -> Command {
                                 .help("The address of the thread to lookup"),
                         )
                 )
+                .subcommand(
+                    Command::new("list")
+                        .about("List the threads owned by an authority")
+                        .arg(
+                            Arg::new("limit")
+                                .long("limit")
+                                .short('l')
+                                .value_name("COUNT")
+                                .help("The max number of threads to list"),
+                        )
+                        .arg(
+                            Arg::new("json")
+                                .long("json")
+                                .action(ArgAction::SetTrue)
+                                .help("Print the threads as JSON"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("export")
+                        .about("Export the threads owned by an authority, with their balances and last execs, for reporting")
+                        .arg(
+                            Arg::new("format")
+                                .long("format")
+                                .value_parser(["csv", "json"])
+                                .default_value("csv")
+                                .help("The format of the exported file"),
+                        )
+                        .arg(
+                            Arg::new("out")
+                                .long("out")
+                                .value_name("PATH")
+                                .help("The file the threads are written to, defaults to stdout"),
+                        )
+                        .arg(
+                            Arg::new("fields")
+                                .long("fields")
+                                .value_name("FIELDS")
+                                .value_delimiter(',')
+                                .value_parser(ExportField::ALL.map(|field| field.name()))
+                                .help("The comma separated columns to export, defaults to all of them in their order"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("watch")
+                        .about("Print the changes of a thread as they happen, until interrupted")
+                        .arg_required_else_help(true)
+                        .args(thread_lookup_args("watch"))
+                        .group(thread_lookup_group())
+                        .arg(
+                            Arg::new("interval")
+                                .long("interval")
+                                .value_name("SECONDS")
+                                .help("Poll the thread at this interval, for RPC endpoints without websockets"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("logs")
+                        .about("List the latest executions of a thread, with their outcome")
+                        .arg_required_else_help(true)
+                        .args(thread_lookup_args("list the executions of"))
+                        .group(thread_lookup_group())
+                        .arg(
+                            Arg::new("limit")
+                                .long("limit")
+                                .short('l')
+                                .value_name("COUNT")
+                                .default_value("20")
+                                .value_parser(clap::value_parser!(u64).range(1..=1000))
+                                .help("The max number of transactions of the thread to inspect"),
+                        )
+                        .arg(
+                            Arg::new("json")
+                                .long("json")
+                                .action(ArgAction::SetTrue)
+                                .help("Print the executions as JSON"),
+                        ),
+                )
                 .subcommand(
                     Command::new("pause")
                         .about("Pause a thread")
                         .arg_required_else_help(true)
+                        .args(thread_lookup_args("pause"))
+                        .group(thread_lookup_group()),
+                )
+                .subcommand(
+                    Command::new("pause-all")
+                        .about("Pause every thread of the authority, or those whose id matches, in as few transactions as fit")
+                        .arg(thread_match_arg("pause")),
+                )
+                .subcommand(
+                    Command::new("pubkey")
+                        .about("Print the address a thread is created at, and its bump, without any RPC call")
+                        .arg_required_else_help(true)
                         .arg(
-                        Arg::new("id")
-                            .index(1)
-                            .required(false)
-                            .help("The id of the thread to pause"),
-                    ),
+                            Arg::new("id")
+                                .long("id")
+                                .value_name("ID")
+                                .help("The id of the thread"),
+                        )
+                        .arg(
+                            Arg::new("raw_id_hex")
+                                .long("raw-id-hex")
+                                .value_name("HEX")
+                                .help("The id of the thread as hex encoded bytes, for the ids which aren't valid UTF-8"),
+                        )
+                        .arg(domain_arg())
+                        .group(
+                            ArgGroup::new("thread_id")
+                                .args(["id", "raw_id_hex"])
+                                .required(true),
+                        ),
                 )
                 .subcommand(
-                    Command::new("resume").about("Resume a thread").arg(
-                        Arg::new("id")
-                            .index(1)
-                            .required(false)
-                            .help("The id of the thread to resume"),
-                    ),
+                    Command::new("resume")
+                        .about("Resume a thread")
+                        .arg_required_else_help(true)
+                        .args(thread_lookup_args("resume"))
+                        .group(thread_lookup_group()),
                 )
                 .subcommand(
-                    Command::new("reset").about("Reset a thread").arg(
-                        Arg::new("id")
-                            .index(1)
-                            .required(false)
-                            .help("The id of the thread to stop"),
-                    ),
+                    Command::new("resume-all")
+                        .about("Resume every paused thread of the authority, or those whose id matches, in as few transactions as fit")
+                        .arg(thread_match_arg("resume")),
+                )
+                .subcommand(
+                    Command::new("reset")
+                        .about("Reset a thread")
+                        .arg_required_else_help(true)
+                        .args(thread_lookup_args("reset"))
+                        .group(thread_lookup_group()),
+                )
+                .subcommand(
+                    Command::new("simulate")
+                        .about("Simulate the next instruction of a thread, without sending anything")
+                        .arg_required_else_help(true)
+                        .arg(domain_arg())
+                        .arg(
+                            Arg::new("id")
+                                .index(1)
+                                .required(false)
+                                .help("The id of the thread to simulate"),
+                        )
+                        .arg(
+                            Arg::new("address")
+                                .short('k')
+                                .long("address")
+                                .help("The address of the thread to simulate"),
+                        )
+                        .arg(
+                            Arg::new("worker_id")
+                                .long("worker-id")
+                                .short('w')
+                                .default_value("0")
+                                .help("The ID of the worker whose accounts are derived"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("set-instruction")
+                        .about("Replace a single instruction of a thread, or append one, keeping the others as they are")
+                        .arg_required_else_help(true)
+                        .args(thread_lookup_args("update"))
+                        .group(thread_lookup_group())
+                        .arg(
+                            Arg::new("index")
+                                .long("index")
+                                .value_name("INDEX")
+                                .value_parser(clap::value_parser!(usize))
+                                .help("The 0-based index of the instruction to replace"),
+                        )
+                        .arg(
+                            Arg::new("append")
+                                .long("append")
+                                .action(ArgAction::SetTrue)
+                                .help("Add the instruction after the last one"),
+                        )
+                        .group(
+                            ArgGroup::new("position")
+                                .args(["index", "append"])
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("instructions_file")
+                                .long("instructions-file")
+                                .value_name("FILEPATH")
+                                .required(true)
+                                .help("Filepath to a JSON list of a single instruction, or base64 encoded bincode, \
+                                    in the format of the instructions file of thread create"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("withdraw")
+                        .about("Withdraw SOL from a thread, above its rent exemption")
+                        .arg_required_else_help(true)
+                        .arg(domain_arg())
+                        .arg(
+                            Arg::new("id")
+                                .index(1)
+                                .required(false)
+                                .help("The id of the thread to withdraw from"),
+                        )
+                        .arg(
+                            Arg::new("address")
+                                .short('k')
+                                .long("address")
+                                .help("The address of the thread to withdraw from"),
+                        )
+                        .args(amount_args())
+                        .arg(
+                            Arg::new("to")
+                                .long("to")
+                                .value_name("ADDRESS")
+                                .help("The account receiving the SOL, defaults to the payer"),
+                        ),
                 )
                 .subcommand(
                     Command::new("update")
                         .about("Update a property of a thread")
+                        .arg(domain_arg())
                         .arg_required_else_help(true)
                         .arg(
                             Arg::new("id")
@@ -590,19 +1125,31 @@ pub fn app() -> Command {
                                 ),
                         )
                         .arg(
-                            Arg::new("schedule")
-                                .long("schedule")
-                                .short('s')
-                                .required(false)
-                                .help("The cron schedule of the thread"),
-                        ),
+                            Arg::new("fee")
+                                .long("fee")
+                                .value_name("LAMPORTS")
+                                .help("The fee paid to the workers per execution"),
+                        )
+                        .arg(
+                            Arg::new("name")
+                                .long("name")
+                                .help("The name of the thread"),
+                        )
+                        .arg(
+                            Arg::new("instructions_file")
+                                .long("instructions-file")
+                                .value_name("FILEPATH")
+                                .help("Filepath to the new instructions, in the format of thread create"),
+                        )
+                        .args(trigger_args())
+                        .groups(trigger_groups(false)),
                 ),
         )
         .subcommand(
             Command::new("registry")
                 .about("Manage the Sablier network registry")
                 .arg_required_else_help(true)
-                .subcommand(Command::new("get").about("Lookup the registry"))
+                .subcommand(Command::new("get").about("Lookup the registry and the snapshot of its current epoch"))
                 .subcommand(Command::new("unlock").about("Manually unlock the registry")),
         )
         .subcommand(
@@ -662,6 +1209,16 @@ pub fn app() -> Command {
                         )
                 )
         )
+        .subcommand(
+            Command::new("version")
+                .about("Print the versions of the CLI and of the programs deployed on the cluster, and whether they are compatible")
+                .arg(
+                    Arg::new("cluster")
+                        .long("cluster")
+                        .value_name("URL")
+                        .help("The RPC URL of the cluster, defaults to the one of the Solana config"),
+                ),
+        )
         .subcommand(
             Command::new("worker")
                 .about("Manage your workers")
@@ -672,8 +1229,56 @@ pub fn app() -> Command {
                         .arg(
                             Arg::new("signatory_keypair")
                                 .index(1)
-                                .required(true)
                                 .help("Filepath to the worker's signatory keypair"),
+                        )
+                        .arg(
+                            Arg::new("signatory")
+                                .long("signatory")
+                                .value_name("KEYPAIR")
+                                .help("Filepath to the worker's signatory keypair"),
+                        )
+                        .group(
+                            ArgGroup::new("signatory_group")
+                                .args(["signatory_keypair", "signatory"])
+                                .required(true),
+                        ),
+                )
+                .subcommand(
+                    Command::new("balances")
+                        .about("Show the claimable fees, penalties and commissions of the workers of the authority, \
+                            with the balances of their signatories")
+                        .arg(
+                            Arg::new("min_signatory_balance")
+                                .long("min-signatory-balance")
+                                .value_name("SOL")
+                                .help("Exit with an error when a signatory holds less SOL, e.g. to monitor the workers"),
+                        )
+                        .arg(
+                            Arg::new("json")
+                                .long("json")
+                                .action(ArgAction::SetTrue)
+                                .help("Print the balances as JSON"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("claim")
+                        .about("Claim the commission a worker has earned")
+                        .arg(
+                            Arg::new("id")
+                                .index(1)
+                                .required(true)
+                                .help("The ID or the address of the worker"),
+                        )
+                        .args(amount_args())
+                        .mut_arg("amount", |arg| {
+                            arg.required(false)
+                                .help("The amount to claim, in SOL, defaults to the whole claimable balance")
+                        })
+                        .arg(
+                            Arg::new("to")
+                                .long("to")
+                                .value_name("ADDRESS")
+                                .help("The account paid, defaults to the payer"),
                         ),
                 )
                 .subcommand(
@@ -683,7 +1288,7 @@ pub fn app() -> Command {
                             Arg::new("id")
                                 .index(1)
                                 .required(true)
-                                .help("The ID of the worker to lookup"),
+                                .help("The ID or the address of the worker to lookup"),
                         ),
                 )
                 .subcommand(
@@ -693,7 +1298,21 @@ pub fn app() -> Command {
                             Arg::new("id")
                                 .index(1)
                                 .required(true)
-                                .help("The ID of the worker to edit"),
+                                .help("The ID or the address of the worker to edit"),
+                        )
+                        .arg(
+                            Arg::new("commission_rate")
+                                .long("commission-rate")
+                                .value_name("PERCENT")
+                                .value_parser(clap::value_parser!(u64).range(1..=100))
+                                .help("The percentage of the fees the worker keeps, from 1 to 100"),
+                        )
+                        .arg(
+                            Arg::new("signatory")
+                                .long("signatory")
+                                .value_name("ADDRESS")
+                                .conflicts_with("signatory_keypair")
+                                .help("The worker's new signatory"),
                         )
                         .arg(
                             Arg::new("signatory_keypair")
@@ -705,3 +1324,187 @@ pub fn app() -> Command {
                 ),
         )
 }
+
+/// The trigger flags of the thread commands, only one trigger kind may be set at once.
+fn trigger_args() -> Vec<Arg> {
+    vec![
+        Arg::new("cron")
+            .long("cron")
+            .short('c')
+            .alias("schedule")
+            .short_alias('s')
+            .value_name("SCHEDULE")
+            .help("A trigger on the moments of the cron schedule"),
+        Arg::new("skippable")
+            .long("skippable")
+            .action(ArgAction::SetTrue)
+            .help("Skip the cron or periodic moments missed, e.g. during a network outage, the default of a new thread"),
+        Arg::new("no_skippable")
+            .long("no-skippable")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("skippable")
+            .help("Execute the cron or periodic moments missed once the network is back"),
+        Arg::new("account")
+            .long("account")
+            .short('a')
+            .value_name("ADDRESS")
+            .help("A trigger on the changes of the data of the account"),
+        Arg::new("offset")
+            .long("offset")
+            .requires("account")
+            .default_value("0")
+            .help("The offset of the account data watched"),
+        Arg::new("size")
+            .long("size")
+            .requires("account")
+            .default_value("32")
+            .help("The size of the account data watched"),
+        Arg::new("now")
+            .long("now")
+            .alias("immediate")
+            .short_alias('m')
+            .action(ArgAction::SetTrue)
+            .help("An immediate trigger"),
+        Arg::new("slot")
+            .long("slot")
+            .help("A trigger on the slot"),
+        Arg::new("epoch")
+            .long("epoch")
+            .help("A trigger on the epoch"),
+        Arg::new("timestamp")
+            .long("timestamp")
+            .value_name("UNIX_TS|RFC3339")
+            .help("A trigger on the moment, a unix timestamp or a RFC 3339 date, e.g. 2030-01-01T00:00:00Z"),
+        Arg::new("pyth")
+            .long("pyth")
+            .alias("pyth-feed-id")
+            .value_name("FEED_ID_HEX")
+            .requires("pyth_limit")
+            .help("A trigger on the price of the pyth feed, against the --gte or --lte price"),
+        Arg::new("gte")
+            .long("gte")
+            .value_name("PRICE")
+            .requires("pyth")
+            .allow_negative_numbers(true)
+            .help("Trigger once the price is greater than or equal to this decimal price"),
+        Arg::new("lte")
+            .long("lte")
+            .value_name("PRICE")
+            .requires("pyth")
+            .allow_negative_numbers(true)
+            .help("Trigger once the price is lower than or equal to this decimal price"),
+        Arg::new("pyth_exponent")
+            .long("pyth-exponent")
+            .value_name("EXPONENT")
+            .requires("pyth")
+            .allow_negative_numbers(true)
+            .value_parser(clap::value_parser!(i32))
+            .help("The exponent of the prices of the feed, e.g. -8, read from its price account by default"),
+        Arg::new("periodic")
+            .long("periodic")
+            .value_name("SECONDS")
+            .help("A trigger every given number of seconds, from the creation of the thread"),
+    ]
+}
+
+/// At most one trigger kind, and exactly one price limit of a pyth trigger.
+fn trigger_groups(required: bool) -> Vec<ArgGroup> {
+    vec![
+        ArgGroup::new("trigger")
+            .args([
+                "cron",
+                "account",
+                "now",
+                "slot",
+                "epoch",
+                "timestamp",
+                "pyth",
+                "periodic",
+            ])
+            .required(required),
+        ArgGroup::new("pyth_limit").args(["gte", "lte"]),
+    ]
+}
+
+/// The thread a command operates on, either by its address or by the id it is derived from.
+fn thread_lookup_args(action: &str) -> Vec<Arg> {
+    vec![
+        Arg::new("id")
+            .index(1)
+            .help(format!("The id of the thread to {}", action)),
+        Arg::new("address")
+            .short('k')
+            .long("address")
+            .help(format!("The address of the thread to {}", action)),
+        domain_arg().conflicts_with("address"),
+    ]
+}
+
+/// Filters the threads of the authority a bulk command operates on.
+fn thread_match_arg(action: &str) -> Arg {
+    Arg::new("match")
+        .long("match")
+        .value_name("SUBSTRING")
+        .help(format!(
+            "Only {} the threads whose id contains the substring",
+            action
+        ))
+}
+
+fn thread_lookup_group() -> ArgGroup {
+    ArgGroup::new("thread")
+        .args(["id", "address"])
+        .required(true)
+}
+
+/// The amount of a transfer, in SOL unless `--lamports` is set.
+fn amount_args() -> Vec<Arg> {
+    vec![
+        Arg::new("amount")
+            .long("amount")
+            .required(true)
+            .help("The amount to transfer, in SOL"),
+        Arg::new("lamports")
+            .long("lamports")
+            .action(ArgAction::SetTrue)
+            .help("The amount is in lamports rather than SOL"),
+    ]
+}
+
+/// The amount of a delegation transfer, in tokens with the decimals of the mint.
+fn delegation_amount_arg() -> Arg {
+    Arg::new("amount")
+        .long("amount")
+        .short('a')
+        .value_name("TOKENS")
+        .required(true)
+}
+
+fn delegation_id_arg() -> Arg {
+    Arg::new("delegation_id")
+        .long("delegation-id")
+        .visible_alias("delegation_id")
+        .short('i')
+        .value_name("ID")
+        .required(true)
+        .help("The ID of the delegation")
+}
+
+fn worker_id_arg() -> Arg {
+    Arg::new("worker_id")
+        .long("worker-id")
+        .visible_alias("worker_id")
+        .short('w')
+        .value_name("ID")
+        .required(true)
+        .help("The ID of the worker")
+}
+
+/// The domain of the thread, which is part of its address.
+fn domain_arg() -> Arg {
+    Arg::new("domain")
+        .long("domain")
+        .short('d')
+        .value_name("DOMAIN")
+        .help("The domain of the thread, if it was created with one")
+}
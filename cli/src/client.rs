@@ -1,25 +1,41 @@
-use anchor_lang::{prelude::Clock, AccountDeserialize};
-use sablier_utils::ProgramLogsDeserializable;
+use anchor_lang::prelude::Clock;
+use sablier_utils::{
+    explorer::{Explorer, ExplorerSite},
+    ProgramLogsDeserializable,
+};
 use solana_client::{
-    client_error, rpc_client::RpcClient, rpc_response::RpcSimulateTransactionResult,
+    client_error::{self, ClientErrorKind},
+    rpc_client::RpcClient,
+    rpc_config::RpcSendTransactionConfig,
+    rpc_response::RpcSimulateTransactionResult,
 };
 use solana_sdk::{
-    commitment_config::CommitmentConfig,
+    commitment_config::{CommitmentConfig, CommitmentLevel},
+    compute_budget::ComputeBudgetInstruction,
     hash::Hash,
-    instruction::Instruction,
+    instruction::{Instruction, InstructionError},
+    message::Message,
+    native_token::lamports_to_sol,
+    packet::PACKET_DATA_SIZE,
     program_error::ProgramError,
     pubkey::Pubkey,
-    signature::{Keypair, Signature, Signer},
+    signature::{Signature, Signer},
     signers::Signers,
-    transaction::Transaction,
+    sysvar,
+    transaction::{Transaction, TransactionError},
 };
+use solana_transaction_status::TransactionStatus;
 use std::{
+    cell::OnceCell,
     fmt::Debug,
     ops::{Deref, DerefMut},
-    str::FromStr,
+    thread::sleep,
+    time::Duration,
 };
 use thiserror::Error;
 
+use crate::signer::LabeledSigner;
+
 #[derive(Debug, Error)]
 pub enum ClientError {
     #[error(transparent)]
@@ -30,29 +46,124 @@ pub enum ClientError {
 
     #[error("Failed to deserialize account data")]
     DeserializationError,
+
+    #[error("Transaction {signature} failed: {error}")]
+    TransactionFailed {
+        signature: Signature,
+        error: TransactionError,
+    },
+
+    #[error("Transaction {signature} was not confirmed after {attempts} attempts, its blockhash expired (last status: {status})")]
+    TransactionExpired {
+        signature: Signature,
+        attempts: usize,
+        status: String,
+    },
+}
+
+impl ClientError {
+    /// The custom program error a transaction failed with, in its preflight simulation or once landed.
+    pub fn custom_error(&self) -> Option<u32> {
+        let error = match self {
+            Self::Client(err) => err.get_transaction_error(),
+            Self::TransactionFailed { error, .. } => Some(error.clone()),
+            _ => None,
+        };
+        match error {
+            Some(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+                Some(code)
+            }
+            _ => None,
+        }
+    }
 }
 
 pub type ClientResult<T> = Result<T, ClientError>;
 
+/// The number of times a transaction is signed with a fresh blockhash before giving up.
+const MAX_SIGN_ATTEMPTS: usize = 3;
+
+/// The number of times an RPC request failing with a transient error is retried.
+const MAX_REQUEST_RETRIES: u32 = 4;
+
+/// The delay before the first retry of a request, doubled at each retry.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+
+/// The delay between the signature status polls.
+const STATUS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The maximum number of accounts `getRecentPrioritizationFees` accepts.
+const MAX_PRIORITIZATION_FEE_ACCOUNTS: usize = 128;
+
+/// The compute unit price of the sent transactions, in micro-lamports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriorityFee {
+    Fixed(u64),
+    /// The median of the recent prioritization fees of the writable accounts of the transaction.
+    Auto,
+}
+
+/// The compute budget instructions prepended to the sent transactions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ComputeBudget {
+    pub priority_fee: Option<PriorityFee>,
+    pub compute_units: Option<u32>,
+}
+
 pub struct Client {
     pub client: RpcClient,
-    pub payer: Keypair,
+    pub payer: LabeledSigner,
+    pub compute_budget: ComputeBudget,
+    /// The commitment the sent transactions are confirmed at.
+    pub confirmation: CommitmentLevel,
+    /// The block explorer the printed links point to.
+    pub explorer_site: ExplorerSite,
+    explorer: OnceCell<Explorer>,
 }
 
 impl Client {
-    pub fn new(payer: Keypair, url: String) -> Self {
+    pub fn new(payer: LabeledSigner, url: String) -> Self {
         let client = RpcClient::new_with_commitment::<String>(url, CommitmentConfig::processed());
-        Self { client, payer }
+        Self {
+            client,
+            payer,
+            compute_budget: ComputeBudget::default(),
+            confirmation: CommitmentLevel::Confirmed,
+            explorer_site: ExplorerSite::default(),
+            explorer: OnceCell::new(),
+        }
+    }
+
+    pub fn with_explorer_site(mut self, explorer_site: ExplorerSite) -> Self {
+        self.explorer_site = explorer_site;
+        self
+    }
+
+    /// The explorer of the cluster, detected once from its genesis hash,
+    /// or guessed from the RPC url when the hash can't be fetched.
+    pub fn explorer(&self) -> &Explorer {
+        self.explorer.get_or_init(|| {
+            let url = self.client.url();
+            match self.get_genesis_hash() {
+                Ok(genesis_hash) => Explorer::from_genesis_hash(&genesis_hash.to_string(), url),
+                Err(_err) => Explorer::from(url),
+            }
+            .with_site(self.explorer_site)
+        })
+    }
+
+    pub fn with_confirmation(mut self, confirmation: CommitmentLevel) -> Self {
+        self.confirmation = confirmation;
+        self
     }
 
-    pub fn get<T: AccountDeserialize>(&self, pubkey: &Pubkey) -> ClientResult<T> {
-        let data = self.client.get_account_data(pubkey)?;
-        T::try_deserialize(&mut data.as_slice()).map_err(|_| ClientError::DeserializationError)
+    pub fn with_compute_budget(mut self, compute_budget: ComputeBudget) -> Self {
+        self.compute_budget = compute_budget;
+        self
     }
 
     pub fn get_clock(&self) -> ClientResult<Clock> {
-        let clock_pubkey = Pubkey::from_str("SysvarC1ock11111111111111111111111111111111").unwrap();
-        let clock_data = self.client.get_account_data(&clock_pubkey)?;
+        let clock_data = self.client.get_account_data(&sysvar::clock::ID)?;
         bincode::deserialize::<Clock>(&clock_data).map_err(|_| ClientError::DeserializationError)
     }
 
@@ -80,7 +191,7 @@ impl Client {
         Ok(logs)
     }
 
-    pub fn payer(&self) -> &Keypair {
+    pub fn payer(&self) -> &dyn Signer {
         &self.payer
     }
 
@@ -99,13 +210,158 @@ impl Client {
         Ok(signature)
     }
 
+    /// Sends the instructions prefixed with the compute budget instructions,
+    /// and prints the signature and the fee paid once the transaction is confirmed.
     pub fn send_and_confirm<T: Signers>(
         &self,
         ixs: &[Instruction],
         signers: &T,
     ) -> ClientResult<Signature> {
-        let tx = self.transaction(ixs, signers)?;
-        Ok(self.send_and_confirm_transaction(&tx)?)
+        let ixs = self.with_compute_budget_instructions(ixs)?;
+        let mut tx = Transaction::new_with_payer(&ixs, Some(&self.payer_pubkey()));
+        let mut last_signature = None;
+        let mut last_status = None;
+        for _ in 0..MAX_SIGN_ATTEMPTS {
+            // Expired transactions are signed again with a fresh blockhash.
+            let (blockhash, last_valid_block_height) =
+                self.with_retries(|| self.get_latest_blockhash_with_commitment(self.commitment()))?;
+            tx.try_sign(signers, blockhash)
+                .map_err(client_error::ClientError::from)?;
+            let fee = self.with_retries(|| self.get_fee_for_message(&tx.message))?;
+            let signature = self.with_retries(|| {
+                self.send_transaction_with_config(
+                    &tx,
+                    RpcSendTransactionConfig {
+                        preflight_commitment: Some(self.commitment().commitment),
+                        ..RpcSendTransactionConfig::default()
+                    },
+                )
+            })?;
+            last_signature = Some(signature);
+
+            let mut expired = false;
+            loop {
+                let status = self
+                    .with_retries(|| self.get_signature_statuses(&[signature]))?
+                    .value
+                    .remove(0);
+                if let Some(status) = &status {
+                    if let Some(error) = &status.err {
+                        return Err(ClientError::TransactionFailed {
+                            signature,
+                            error: error.clone(),
+                        });
+                    }
+                    if status.satisfies_commitment(CommitmentConfig {
+                        commitment: self.confirmation,
+                    }) {
+                        // The confirmations go to stderr to keep the printed accounts parsable.
+                        eprintln!(
+                            "Signature: {} (fee: {} SOL)\nExplorer: {}",
+                            signature,
+                            lamports_to_sol(fee),
+                            self.explorer().tx_url(signature)
+                        );
+                        return Ok(signature);
+                    }
+                }
+                if expired && status.is_none() {
+                    break;
+                }
+                last_status = status.or(last_status);
+                if !expired
+                    && self.with_retries(|| self.get_block_height())? > last_valid_block_height
+                {
+                    // The transaction may have landed as its blockhash expired, its status is queried once more
+                    // and it is only signed again while unknown, not to be sent twice.
+                    expired = true;
+                    continue;
+                }
+                sleep(STATUS_POLL_INTERVAL);
+            }
+        }
+        Err(ClientError::TransactionExpired {
+            signature: last_signature.unwrap_or_default(),
+            attempts: MAX_SIGN_ATTEMPTS,
+            status: describe_status(last_status.as_ref()),
+        })
+    }
+
+    /// Retries the request with an exponential backoff while it fails with a transient error.
+    fn with_retries<T>(
+        &self,
+        request: impl Fn() -> client_error::Result<T>,
+    ) -> client_error::Result<T> {
+        let mut backoff = INITIAL_BACKOFF;
+        for _ in 0..MAX_REQUEST_RETRIES {
+            match request() {
+                Err(err) if is_transient(&err) => {
+                    sleep(backoff);
+                    backoff *= 2;
+                }
+                result => return result,
+            }
+        }
+        request()
+    }
+
+    /// Prepends the compute budget instructions of the client to the instructions.
+    pub fn with_compute_budget_instructions(
+        &self,
+        ixs: &[Instruction],
+    ) -> ClientResult<Vec<Instruction>> {
+        let mut budget_ixs = vec![];
+        if let Some(compute_units) = self.compute_budget.compute_units {
+            budget_ixs.push(ComputeBudgetInstruction::set_compute_unit_limit(
+                compute_units,
+            ));
+        }
+        let price = match self.compute_budget.priority_fee {
+            None => None,
+            Some(PriorityFee::Fixed(price)) => Some(price),
+            Some(PriorityFee::Auto) => Some(self.recent_priority_fee(ixs)?),
+        };
+        if let Some(price) = price.filter(|price| *price > 0) {
+            budget_ixs.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+        Ok(budget_ixs.into_iter().chain(ixs.iter().cloned()).collect())
+    }
+
+    /// Whether the instructions fit in a single transaction paid by the payer,
+    /// with the compute budget instructions prepended.
+    pub fn fits_in_transaction(&self, ixs: &[Instruction], payer: &Pubkey) -> bool {
+        let mut budget_ixs = vec![];
+        if let Some(compute_units) = self.compute_budget.compute_units {
+            budget_ixs.push(ComputeBudgetInstruction::set_compute_unit_limit(
+                compute_units,
+            ));
+        }
+        // The compute unit price instruction has the same size whatever the price.
+        if self.compute_budget.priority_fee.is_some() {
+            budget_ixs.push(ComputeBudgetInstruction::set_compute_unit_price(0));
+        }
+        let ixs = budget_ixs
+            .into_iter()
+            .chain(ixs.iter().cloned())
+            .collect::<Vec<_>>();
+        let tx = Transaction::new_unsigned(Message::new(&ixs, Some(payer)));
+        bincode::serialized_size(&tx).map_or(false, |size| size <= PACKET_DATA_SIZE as u64)
+    }
+
+    fn recent_priority_fee(&self, ixs: &[Instruction]) -> ClientResult<u64> {
+        let mut accounts = vec![];
+        for account in ixs.iter().flat_map(|ix| &ix.accounts) {
+            if account.is_writable && !accounts.contains(&account.pubkey) {
+                accounts.push(account.pubkey);
+            }
+        }
+        accounts.truncate(MAX_PRIORITIZATION_FEE_ACCOUNTS);
+        let fees = self
+            .get_recent_prioritization_fees(&accounts)?
+            .into_iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect();
+        Ok(median(fees))
     }
 
     pub fn simulate_transaction<T: Signers>(
@@ -133,6 +389,26 @@ impl Client {
     }
 }
 
+/// Whether the request failed before reaching the RPC node, so it can be retried.
+fn is_transient(err: &client_error::ClientError) -> bool {
+    matches!(
+        err.kind(),
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_)
+    )
+}
+
+fn describe_status(status: Option<&TransactionStatus>) -> String {
+    match status {
+        None => "not found".to_string(),
+        Some(status) => format!("{:?}", status.confirmation_status()).to_lowercase(),
+    }
+}
+
+fn median(mut values: Vec<u64>) -> u64 {
+    values.sort_unstable();
+    values.get(values.len() / 2).copied().unwrap_or_default()
+}
+
 impl Debug for Client {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "RPC client payer {}", self.payer_pubkey())
@@ -152,3 +428,57 @@ impl DerefMut for Client {
         &mut self.client
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{signature::Keypair, system_instruction};
+
+    use super::*;
+
+    #[test]
+    fn compute_budget_instructions_are_prepended() {
+        let ix = system_instruction::transfer(&Pubkey::new_unique(), &Pubkey::new_unique(), 1);
+        let client = Client::new(
+            LabeledSigner::new("payer", Box::new(Keypair::new())),
+            "http://127.0.0.1:1".into(),
+        );
+        assert_eq!(
+            client
+                .with_compute_budget_instructions(&[ix.clone()])
+                .unwrap(),
+            vec![ix.clone()]
+        );
+
+        let client = client.with_compute_budget(ComputeBudget {
+            priority_fee: Some(PriorityFee::Fixed(1_000)),
+            compute_units: Some(200_000),
+        });
+        assert_eq!(
+            client
+                .with_compute_budget_instructions(&[ix.clone()])
+                .unwrap(),
+            vec![
+                ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+                ComputeBudgetInstruction::set_compute_unit_price(1_000),
+                ix,
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_transactions_expose_their_custom_error() {
+        let err = ClientError::TransactionFailed {
+            signature: Signature::default(),
+            error: TransactionError::InstructionError(2, InstructionError::Custom(6003)),
+        };
+        assert_eq!(err.custom_error(), Some(6003));
+        assert_eq!(ClientError::DeserializationError.custom_error(), None);
+        assert_eq!(describe_status(None), "not found");
+    }
+
+    #[test]
+    fn median_of_no_fees_is_zero() {
+        assert_eq!(median(vec![]), 0);
+        assert_eq!(median(vec![5, 0, 0, 9, 7]), 5);
+    }
+}
@@ -1,8 +1,9 @@
 use {
     crate::deps::ToTagVersion,
     clap::crate_version,
+    solana_cli_config::ConfigInput,
     solana_sdk::commitment_config::CommitmentConfig,
-    std::{env, fs, path::PathBuf, time::Duration},
+    std::{env, fmt, fs, path::PathBuf, time::Duration},
 };
 
 pub const DEFAULT_RPC_TIMEOUT_SECONDS: Duration = Duration::from_secs(30);
@@ -18,6 +19,35 @@ pub const SABLIER_DEPS: &[&str] = &[
 ];
 pub const SOLANA_RELEASE_BASE_URL: &str = "https://github.com/solana-labs/solana/releases/download";
 pub const SOLANA_DEPS: &[&str] = &["solana-test-validator"];
+/// The environment variable of the RPC URL, overridden by `--url` and overriding the Solana config.
+pub const RPC_URL_ENV: &str = "SABLIER_RPC_URL";
+
+/// Where the RPC URL of the cluster was resolved from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClusterSource {
+    Flag,
+    Env,
+    #[default]
+    SolanaConfig,
+}
+
+impl fmt::Display for ClusterSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Flag => write!(f, "--url"),
+            Self::Env => write!(f, "{}", RPC_URL_ENV),
+            Self::SolanaConfig => write!(f, "the Solana config"),
+        }
+    }
+}
+
+/// The cluster and payer overrides of the command line, the URLs are either full URLs or monikers like `devnet`.
+#[derive(Debug, Default)]
+pub struct ClusterArgs {
+    pub url: Option<String>,
+    pub websocket_url: Option<String>,
+    pub keypair_path: Option<String>,
+}
 
 /// The combination of solana config file and our own config file
 #[derive(Debug, PartialEq)]
@@ -29,15 +59,19 @@ pub struct CliConfig {
     pub rpc_timeout: Duration,
     pub commitment: CommitmentConfig,
     pub confirm_transaction_initial_timeout: Duration,
+    pub cluster_source: ClusterSource,
 
     pub active_version: String,
     pub dev: bool,
 }
 
 impl CliConfig {
+    /// Loads the Solana CLI config, with the defaults of the Solana CLI when it has no config file.
     pub fn load() -> Self {
-        let solana_config_file = solana_cli_config::CONFIG_FILE.as_ref().unwrap().as_str();
-        let solana_config = solana_cli_config::Config::load(solana_config_file).unwrap();
+        let solana_config = solana_cli_config::CONFIG_FILE
+            .as_ref()
+            .and_then(|file| solana_cli_config::Config::load(file).ok())
+            .unwrap_or_default();
         CliConfig {
             json_rpc_url: solana_config.json_rpc_url,
             websocket_url: solana_config.websocket_url,
@@ -46,11 +80,43 @@ impl CliConfig {
             rpc_timeout: DEFAULT_RPC_TIMEOUT_SECONDS,
             commitment: CommitmentConfig::confirmed(),
             confirm_transaction_initial_timeout: DEFAULT_CONFIRM_TX_TIMEOUT_SECONDS,
+            cluster_source: ClusterSource::SolanaConfig,
             active_version: crate_version!().to_owned().to_tag_version(),
             dev: false,
         }
     }
 
+    /// Applies the overrides of the command line with the precedence of the Solana CLI:
+    /// the flags, then the `SABLIER_RPC_URL` environment variable, then the Solana config.
+    /// The websocket URL of an overridden cluster is derived from its RPC URL rather than read from the config,
+    /// so a `--url` never pairs with the websocket of another cluster.
+    pub fn with_overrides(mut self, args: &ClusterArgs, env_url: Option<String>) -> Self {
+        let (cluster_source, url) = match (&args.url, env_url.filter(|url| !url.is_empty())) {
+            (Some(url), _) => (ClusterSource::Flag, url.clone()),
+            (None, Some(url)) => (ClusterSource::Env, url),
+            (None, None) => (ClusterSource::SolanaConfig, String::new()),
+        };
+        let websocket_cfg_url = match cluster_source {
+            ClusterSource::SolanaConfig => self.websocket_url.as_str(),
+            _ => "",
+        };
+        self.websocket_url = ConfigInput::compute_websocket_url_setting(
+            args.websocket_url.as_deref().unwrap_or_default(),
+            websocket_cfg_url,
+            &url,
+            &self.json_rpc_url,
+        )
+        .1;
+        self.json_rpc_url = ConfigInput::compute_json_rpc_url_setting(&url, &self.json_rpc_url).1;
+        self.keypair_path = ConfigInput::compute_keypair_path_setting(
+            args.keypair_path.as_deref().unwrap_or_default(),
+            &self.keypair_path,
+        )
+        .1;
+        self.cluster_source = cluster_source;
+        self
+    }
+
     pub fn default_home() -> PathBuf {
         dirs_next::home_dir()
             .map(|mut path| {
@@ -66,6 +132,13 @@ impl CliConfig {
         path
     }
 
+    /// The accounts and programs cloned into the localnet, rewritten at every start.
+    pub fn clones_dir() -> PathBuf {
+        let mut path = Self::default_home();
+        path.extend(["localnet", "clones"]);
+        path
+    }
+
     pub fn active_runtime_dir(&self) -> PathBuf {
         Self::default_runtime_dir().join(&self.active_version)
     }
@@ -90,15 +163,21 @@ impl CliConfig {
         }
     }
 
-    /// This assumes the path for the signatory keypair created by solana-test-validator
-    /// is test-ledger/validator-keypair.json
+    /// The signatory keypair of the localnet worker, generated at the first start and reused after.
     pub fn signatory(&self) -> String {
+        let mut path = Self::default_home();
+        path.extend(["localnet", "signatory.json"]);
+        path.to_string()
+    }
+
+    /// The ledger of the localnet validator, in the working directory like solana-test-validator's default.
+    pub fn ledger(&self) -> String {
         env::current_dir()
             .map(|mut path| {
-                path.extend(["test-ledger", "validator-keypair.json"]);
+                path.push("test-ledger");
                 path
             })
-            .unwrap_or_else(|_| panic!("Unable to find location of validator-keypair.json"))
+            .unwrap_or_else(|_| panic!("Unable to find the working directory"))
             .to_string()
     }
 
@@ -173,3 +252,106 @@ impl CliConfig {
         format!("solana-release-{}.tar.bz2", target_triplet)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Solana config pointing at mainnet, with its own websocket.
+    fn mainnet_config() -> CliConfig {
+        CliConfig {
+            json_rpc_url: "https://api.mainnet-beta.solana.com".into(),
+            websocket_url: "wss://mainnet.example.com/".into(),
+            relayer_url: RELAYER_URL.to_owned(),
+            keypair_path: "/config/id.json".into(),
+            rpc_timeout: DEFAULT_RPC_TIMEOUT_SECONDS,
+            commitment: CommitmentConfig::confirmed(),
+            confirm_transaction_initial_timeout: DEFAULT_CONFIRM_TX_TIMEOUT_SECONDS,
+            cluster_source: ClusterSource::SolanaConfig,
+            active_version: "v0.0.0".into(),
+            dev: false,
+        }
+    }
+
+    fn resolve(url: Option<&str>, env_url: Option<&str>) -> CliConfig {
+        mainnet_config().with_overrides(
+            &ClusterArgs {
+                url: url.map(str::to_string),
+                ..ClusterArgs::default()
+            },
+            env_url.map(str::to_string),
+        )
+    }
+
+    #[test]
+    fn the_url_flag_overrides_the_environment_which_overrides_the_solana_config() {
+        let config = resolve(None, None);
+        assert_eq!(config.json_rpc_url, "https://api.mainnet-beta.solana.com");
+        assert_eq!(config.websocket_url, "wss://mainnet.example.com/");
+        assert_eq!(config.cluster_source, ClusterSource::SolanaConfig);
+
+        let config = resolve(None, Some("devnet"));
+        assert_eq!(config.json_rpc_url, "https://api.devnet.solana.com");
+        assert_eq!(config.cluster_source, ClusterSource::Env);
+
+        let config = resolve(Some("t"), Some("devnet"));
+        assert_eq!(config.json_rpc_url, "https://api.testnet.solana.com");
+        assert_eq!(config.cluster_source, ClusterSource::Flag);
+
+        let config = resolve(None, Some(""));
+        assert_eq!(config.json_rpc_url, "https://api.mainnet-beta.solana.com");
+        assert_eq!(config.cluster_source, ClusterSource::SolanaConfig);
+    }
+
+    #[test]
+    fn cluster_monikers_and_urls_are_resolved() {
+        for (url, expected) in [
+            ("m", "https://api.mainnet-beta.solana.com"),
+            ("mainnet-beta", "https://api.mainnet-beta.solana.com"),
+            ("d", "https://api.devnet.solana.com"),
+            ("devnet", "https://api.devnet.solana.com"),
+            ("t", "https://api.testnet.solana.com"),
+            ("testnet", "https://api.testnet.solana.com"),
+            ("l", "http://localhost:8899"),
+            ("localhost", "http://localhost:8899"),
+            ("https://rpc.example.com", "https://rpc.example.com"),
+        ] {
+            assert_eq!(resolve(Some(url), None).json_rpc_url, expected, "{}", url);
+        }
+    }
+
+    #[test]
+    fn the_websocket_follows_an_overridden_cluster() {
+        assert_eq!(
+            resolve(Some("devnet"), None).websocket_url,
+            "wss://api.devnet.solana.com/"
+        );
+        assert_eq!(
+            resolve(None, Some("l")).websocket_url,
+            "ws://localhost:8900/"
+        );
+        let config = mainnet_config().with_overrides(
+            &ClusterArgs {
+                url: Some("devnet".into()),
+                websocket_url: Some("wss://ws.example.com".into()),
+                ..ClusterArgs::default()
+            },
+            None,
+        );
+        assert_eq!(config.websocket_url, "wss://ws.example.com");
+    }
+
+    #[test]
+    fn the_keypair_flag_overrides_the_solana_config() {
+        assert_eq!(resolve(None, None).keypair_path, "/config/id.json");
+        let config = mainnet_config().with_overrides(
+            &ClusterArgs {
+                keypair_path: Some("/tmp/payer.json".into()),
+                ..ClusterArgs::default()
+            },
+            None,
+        );
+        assert_eq!(config.keypair_path, "/tmp/payer.json");
+        assert_eq!(config.json_rpc_url, "https://api.mainnet-beta.solana.com");
+    }
+}
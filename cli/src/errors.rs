@@ -1,30 +1,251 @@
+use solana_client::{
+    client_error::{self, ClientErrorKind},
+    rpc_request::{RpcError, RpcResponseErrorData},
+};
+use solana_sdk::pubkey::Pubkey;
 use thiserror::Error;
 
+use crate::{account_file::describe_discriminator, client::ClientError};
+
 #[derive(Debug, Error)]
 pub enum CliError {
-    #[error("Account not found: {0}")]
-    AccountNotFound(String),
-    #[error("Account data could not be parsed: {0}")]
-    AccountDataNotParsable(String),
+    #[error("The {account} account {pubkey} does not exist{}", hint_suffix(.hint))]
+    AccountNotFound {
+        /// The type of the account, e.g. `thread`.
+        account: &'static str,
+        pubkey: Pubkey,
+        hint: Option<String>,
+    },
+    #[error("{address} could not be deserialized as a {account} account, it has {discriminator}")]
+    DeserializationFailed {
+        account: &'static str,
+        /// The address of the account, or where its data was read from.
+        address: String,
+        discriminator: String,
+    },
     #[error("Bad client: {0}")]
     BadClient(String),
     #[error("Bad parameter: {0}")]
     BadParameter(String),
     #[error("This codepath hasn't been implemented yet")]
     NotImplemented,
+    #[error("Cancelled, nothing was sent")]
+    Cancelled,
+    #[error("Confirmation needed to {0}, but stdin is not a terminal, pass --yes to confirm")]
+    ConfirmationRequired(String),
+    #[error("Signatory balance too low: {0}")]
+    LowSignatoryBalance(String),
     #[error("Command not recognized: {0}")]
     CommandNotRecognized(String),
-    #[error("Transaction failed with error: {0}")]
-    FailedTransaction(String),
+    #[error("RPC request failed, the RPC node may be unreachable or overloaded, retry later: {0}")]
+    RpcTransient(#[source] client_error::ClientError),
+    #[error("Transaction failed: {message}")]
+    TransactionFailed {
+        message: String,
+        /// The program logs of the failed transaction, when the RPC node returned them.
+        logs: Vec<String>,
+    },
+    #[error("Transaction expired: {0}")]
+    TransactionExpired(String),
     #[error("Failed to start localnet with error: {0}")]
     FailedLocalnet(String),
+    #[error("Invalid account file: {0}")]
+    InvalidAccountFile(String),
+    #[error("Invalid instructions file: {0}")]
+    InvalidInstructionsFile(String),
+    #[error("Invalid manifest: {0}")]
+    InvalidManifest(String),
     #[error("Invalid address")]
     InvalidAddress,
     #[error("Program file does not exist")]
     InvalidProgramFile,
+    #[error("Invalid signer: {0}")]
+    InvalidSigner(String),
     #[error(
         "No default signer found in {0}, \
      run `solana-keygen new`, or `solana config set —keypair <FILEPATH>`"
     )]
     KeypairNotFound(String),
 }
+
+/// The prefix of the error of the node version query, sent before the first RPC request.
+static CLUSTER_VERSION_QUERY_FAILED: &str = "cluster version query failed";
+
+fn hint_suffix(hint: &Option<String>) -> String {
+    hint.as_ref()
+        .map_or(String::new(), |hint| format!(", {}", hint))
+}
+
+impl CliError {
+    pub fn account_not_found(account: &'static str, pubkey: Pubkey) -> Self {
+        Self::AccountNotFound {
+            account,
+            pubkey,
+            hint: None,
+        }
+    }
+
+    /// The error of account data which isn't of the expected type, named after its discriminator.
+    pub fn deserialization_failed(
+        account: &'static str,
+        address: impl ToString,
+        data: &[u8],
+    ) -> Self {
+        Self::DeserializationFailed {
+            account,
+            address: address.to_string(),
+            discriminator: describe_discriminator(data),
+        }
+    }
+
+    pub fn transaction_failed(message: impl ToString) -> Self {
+        Self::TransactionFailed {
+            message: message.to_string(),
+            logs: vec![],
+        }
+    }
+
+    /// Adds a hint to a missing account, on how to look it up or create it.
+    pub fn with_hint(self, hint: impl ToString) -> Self {
+        match self {
+            Self::AccountNotFound {
+                account, pubkey, ..
+            } => Self::AccountNotFound {
+                account,
+                pubkey,
+                hint: Some(hint.to_string()),
+            },
+            err => err,
+        }
+    }
+
+    /// The lines printed after the error with `--verbose`: the chain of its causes, then the program logs.
+    /// The causes already in the message of the error are skipped, the RPC errors mostly repeat their sources.
+    pub fn details(&self) -> Vec<String> {
+        let message = self.to_string();
+        let mut details = vec![];
+        let mut source = std::error::Error::source(self);
+        while let Some(err) = source {
+            let cause = err.to_string();
+            if !message.contains(&cause) {
+                details.push(format!("caused by: {}", cause));
+            }
+            source = err.source();
+        }
+        if let Self::TransactionFailed { logs, .. } = self {
+            details.extend(logs.iter().map(|log| format!("log: {}", log)));
+        }
+        details
+    }
+}
+
+impl From<client_error::ClientError> for CliError {
+    fn from(err: client_error::ClientError) -> Self {
+        match err.kind() {
+            ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => Self::RpcTransient(err),
+            // The version of the node is queried before the first request, a connection failure is only told by its message.
+            ClientErrorKind::RpcError(RpcError::RpcRequestError(message))
+                if message.starts_with(CLUSTER_VERSION_QUERY_FAILED) =>
+            {
+                Self::RpcTransient(err)
+            }
+            ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+                ..
+            }) => Self::TransactionFailed {
+                message: err.to_string(),
+                logs: result.logs.clone().unwrap_or_default(),
+            },
+            _ if err.get_transaction_error().is_some() => Self::transaction_failed(err),
+            _ => Self::BadClient(err.to_string()),
+        }
+    }
+}
+
+impl From<ClientError> for CliError {
+    fn from(err: ClientError) -> Self {
+        match err {
+            ClientError::Client(client_err) => client_err.into(),
+            ClientError::TransactionExpired { .. } => Self::TransactionExpired(err.to_string()),
+            ClientError::TransactionFailed { .. } => Self::transaction_failed(err),
+            _ => Self::BadClient(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::Discriminator;
+    use sablier_thread_program::state::Thread;
+    use solana_client::rpc_response::RpcSimulateTransactionResult;
+    use solana_sdk::transaction::TransactionError;
+
+    use super::*;
+
+    #[test]
+    fn missing_accounts_name_their_type_and_hint() {
+        let pubkey = Pubkey::new_from_array([1; 32]);
+        assert_eq!(
+            CliError::account_not_found("thread", pubkey).to_string(),
+            "The thread account 4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi does not exist"
+        );
+        assert_eq!(
+            CliError::account_not_found("thread", pubkey)
+                .with_hint("did you mean to pass --domain?")
+                .to_string(),
+            "The thread account 4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi does not exist, \
+            did you mean to pass --domain?"
+        );
+    }
+
+    #[test]
+    fn undeserializable_accounts_name_their_discriminator() {
+        let data = [Thread::DISCRIMINATOR.as_slice(), &[0; 4]].concat();
+        let err =
+            CliError::deserialization_failed("worker", Pubkey::new_from_array([1; 32]), &data);
+        assert!(err.to_string().starts_with(
+            "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi could not be deserialized as a worker account, \
+            it has the discriminator "
+        ));
+        assert!(err.to_string().ends_with(" of a Thread account"));
+    }
+
+    #[test]
+    fn rpc_errors_are_told_apart() {
+        let io = client_error::ClientError::from(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            "timed out",
+        ));
+        let err = CliError::from(io);
+        assert!(matches!(err, CliError::RpcTransient(_)));
+        assert_eq!(
+            err.to_string(),
+            "RPC request failed, the RPC node may be unreachable or overloaded, retry later: \
+            timed out"
+        );
+        assert!(err.details().is_empty());
+
+        let preflight = client_error::ClientError::from(RpcError::RpcResponseError {
+            code: -32002,
+            message: "Transaction simulation failed".into(),
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(
+                RpcSimulateTransactionResult {
+                    err: Some(TransactionError::AccountNotFound),
+                    logs: Some(vec!["Program log: out of funds".into()]),
+                    accounts: None,
+                    units_consumed: None,
+                    return_data: None,
+                    inner_instructions: None,
+                },
+            ),
+        });
+        let err = CliError::from(preflight);
+        assert!(err.to_string().starts_with(
+            "Transaction failed: RPC response error -32002: Transaction simulation failed"
+        ));
+        assert_eq!(err.details(), vec!["log: Program log: out of funds"]);
+
+        let other = client_error::ClientError::from(RpcError::ForUser("invalid".into()));
+        assert!(matches!(CliError::from(other), CliError::BadClient(_)));
+    }
+}
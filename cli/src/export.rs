@@ -0,0 +1,304 @@
+use std::{io::Write, str::FromStr};
+
+use chrono::DateTime;
+use serde_json::Value;
+
+use crate::errors::CliError;
+
+/// The format of the exported threads.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    /// A JSON array of objects, with the fields in the column order.
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = CliError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            _ => Err(CliError::BadParameter("format".into())),
+        }
+    }
+}
+
+/// The columns of the exported threads, in their order in the exported files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportField {
+    Address,
+    Id,
+    Domain,
+    Trigger,
+    Paused,
+    Fee,
+    RateLimit,
+    /// The lamports of the thread account.
+    Balance,
+    CreatedAt,
+    ExecIndex,
+    LastExecSlot,
+    LastExecTime,
+}
+
+impl ExportField {
+    pub const ALL: [ExportField; 12] = [
+        Self::Address,
+        Self::Id,
+        Self::Domain,
+        Self::Trigger,
+        Self::Paused,
+        Self::Fee,
+        Self::RateLimit,
+        Self::Balance,
+        Self::CreatedAt,
+        Self::ExecIndex,
+        Self::LastExecSlot,
+        Self::LastExecTime,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Address => "address",
+            Self::Id => "id",
+            Self::Domain => "domain",
+            Self::Trigger => "trigger",
+            Self::Paused => "paused",
+            Self::Fee => "fee",
+            Self::RateLimit => "rate_limit",
+            Self::Balance => "balance",
+            Self::CreatedAt => "created_at",
+            Self::ExecIndex => "exec_index",
+            Self::LastExecSlot => "last_exec_slot",
+            Self::LastExecTime => "last_exec_time",
+        }
+    }
+
+    fn value(&self, row: &ExportRow) -> Value {
+        let thread = match (self, &row.thread) {
+            (Self::Address, _) => return row.address.clone().into(),
+            (Self::Balance, _) => return row.balance.into(),
+            (_, None) => return Value::Null,
+            (_, Some(thread)) => thread,
+        };
+        match self {
+            Self::Address | Self::Balance => Value::Null,
+            Self::Id => thread.id.clone().into(),
+            Self::Domain => thread.domain.clone().into(),
+            Self::Trigger => thread.trigger.clone().into(),
+            Self::Paused => thread.paused.into(),
+            Self::Fee => thread.fee.into(),
+            Self::RateLimit => thread.rate_limit.into(),
+            Self::CreatedAt => rfc3339(Some(thread.created_at)),
+            Self::ExecIndex => thread.exec_index.into(),
+            Self::LastExecSlot => thread.last_exec_slot.into(),
+            Self::LastExecTime => rfc3339(thread.last_exec_time),
+        }
+    }
+}
+
+impl FromStr for ExportField {
+    type Err = CliError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|field| field.name() == value)
+            .ok_or(CliError::BadParameter(format!(
+                "fields: unknown field {}",
+                value
+            )))
+    }
+}
+
+fn rfc3339(unix_timestamp: Option<i64>) -> Value {
+    unix_timestamp
+        .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+        .map_or(Value::Null, |datetime| datetime.to_rfc3339().into())
+}
+
+/// A thread account of the export, only the address and the balance are known of the threads of an unknown version.
+#[derive(Debug, Default)]
+pub struct ExportRow {
+    pub address: String,
+    pub balance: u64,
+    pub thread: Option<ExportThread>,
+}
+
+#[derive(Debug, Default)]
+pub struct ExportThread {
+    pub id: String,
+    pub domain: Option<String>,
+    pub trigger: String,
+    pub paused: bool,
+    pub fee: u64,
+    pub rate_limit: u64,
+    pub created_at: i64,
+    pub exec_index: Option<u64>,
+    pub last_exec_slot: Option<u64>,
+    pub last_exec_time: Option<i64>,
+}
+
+/// Writes the exported threads one row at a time, so that the rows aren't held in memory.
+pub struct ExportWriter<W: Write> {
+    out: W,
+    format: ExportFormat,
+    fields: Vec<ExportField>,
+    rows: usize,
+}
+
+impl<W: Write> ExportWriter<W> {
+    /// Starts the export with the CSV header, or the opening of the JSON array.
+    pub fn new(
+        mut out: W,
+        format: ExportFormat,
+        fields: Vec<ExportField>,
+    ) -> std::io::Result<Self> {
+        match format {
+            ExportFormat::Csv => {
+                let header = fields.iter().map(ExportField::name).collect::<Vec<_>>();
+                writeln!(out, "{}", header.join(","))?;
+            }
+            ExportFormat::Json => write!(out, "[")?,
+        }
+        Ok(Self {
+            out,
+            format,
+            fields,
+            rows: 0,
+        })
+    }
+
+    pub fn write(&mut self, row: &ExportRow) -> std::io::Result<()> {
+        let values = self.fields.iter().map(|field| (field, field.value(row)));
+        match self.format {
+            ExportFormat::Csv => {
+                let cells = values
+                    .map(|(_, value)| csv_cell(&value))
+                    .collect::<Vec<_>>();
+                writeln!(self.out, "{}", cells.join(","))?;
+            }
+            ExportFormat::Json => {
+                let members = values
+                    .map(|(field, value)| format!("{:?}: {}", field.name(), value))
+                    .collect::<Vec<_>>();
+                let separator = if self.rows == 0 { "" } else { "," };
+                write!(self.out, "{}\n  {{{}}}", separator, members.join(", "))?;
+            }
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Ends the export and returns the number of written rows.
+    pub fn finish(mut self) -> std::io::Result<usize> {
+        if self.format == ExportFormat::Json {
+            writeln!(self.out, "{}]", if self.rows == 0 { "" } else { "\n" })?;
+        }
+        self.out.flush()?;
+        Ok(self.rows)
+    }
+}
+
+/// A CSV cell, quoted when it contains a separator, a quote or a line break.
+fn csv_cell(value: &Value) -> String {
+    let text = match value {
+        Value::Null => return String::new(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<ExportRow> {
+        vec![
+            ExportRow {
+                address: "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi".into(),
+                balance: 2_000_000,
+                thread: Some(ExportThread {
+                    id: "payouts, \"monthly\"".into(),
+                    trigger: "cron 0 0 1 * *".into(),
+                    fee: 1_000,
+                    rate_limit: 10,
+                    created_at: 1_700_000_000,
+                    exec_index: Some(3),
+                    last_exec_slot: Some(12),
+                    last_exec_time: Some(1_700_000_060),
+                    ..ExportThread::default()
+                }),
+            },
+            ExportRow {
+                address: "8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR".into(),
+                balance: 1,
+                thread: None,
+            },
+        ]
+    }
+
+    fn export(format: ExportFormat, fields: Vec<ExportField>) -> String {
+        let mut out = vec![];
+        let mut writer = ExportWriter::new(&mut out, format, fields).unwrap();
+        for row in rows() {
+            writer.write(&row).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap(), 2);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn csv_rows_follow_the_column_order() {
+        assert_eq!(
+            export(ExportFormat::Csv, ExportField::ALL.to_vec()),
+            "address,id,domain,trigger,paused,fee,rate_limit,balance,created_at,exec_index,last_exec_slot,last_exec_time\n\
+            4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi,\"payouts, \"\"monthly\"\"\",,cron 0 0 1 * *,false,1000,10,2000000,\
+            2023-11-14T22:13:20+00:00,3,12,2023-11-14T22:14:20+00:00\n\
+            8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR,,,,,,,1,,,,\n"
+        );
+    }
+
+    #[test]
+    fn json_rows_are_objects_with_the_selected_fields() {
+        let fields = vec![
+            ExportField::Balance,
+            ExportField::Address,
+            ExportField::Paused,
+        ];
+        let exported = export(ExportFormat::Json, fields);
+        assert_eq!(
+            serde_json::from_str::<Value>(&exported).unwrap(),
+            serde_json::json!([
+                {"balance": 2_000_000, "address": "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi", "paused": false},
+                {"balance": 1, "address": "8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR", "paused": null},
+            ])
+        );
+        assert!(exported.find("balance").unwrap() < exported.find("address").unwrap());
+
+        let mut out = vec![];
+        ExportWriter::new(&mut out, ExportFormat::Json, fields_of(&["id"]))
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Value>(&out).unwrap(),
+            serde_json::json!([])
+        );
+    }
+
+    fn fields_of(names: &[&str]) -> Vec<ExportField> {
+        names
+            .iter()
+            .map(|name| ExportField::from_str(name).unwrap())
+            .collect()
+    }
+}
@@ -0,0 +1,199 @@
+use std::{fs, str::FromStr};
+
+use anchor_lang::prelude::Pubkey;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sablier_thread_program::state::{SerializableAccount, SerializableInstruction};
+use sablier_utils::thread::PAYER_PUBKEY;
+use serde::{Deserialize, Serialize};
+use solana_sdk::bs58;
+
+use crate::errors::CliError;
+
+/// The spelling of the `PAYER_PUBKEY` sentinel in the instructions files, substituted with the worker's signatory.
+static PAYER_SENTINEL: &str = "PAYER";
+
+/// The hex encoded instruction data is prefixed, the data is base58 encoded otherwise.
+static HEX_PREFIX: &str = "0x";
+
+/// An instruction of an instructions file.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct InstructionEntry {
+    pub program_id: String,
+    #[serde(default)]
+    pub accounts: Vec<AccountEntry>,
+    /// The base58 encoded data, or the hex encoded data prefixed with `0x`.
+    #[serde(default)]
+    pub data: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AccountEntry {
+    /// The account address, or `PAYER` for the worker's signatory.
+    pub pubkey: String,
+    #[serde(default)]
+    pub is_signer: bool,
+    #[serde(default)]
+    pub is_writable: bool,
+}
+
+impl From<&SerializableInstruction> for InstructionEntry {
+    fn from(instruction: &SerializableInstruction) -> Self {
+        Self {
+            program_id: instruction.program_id.to_string(),
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|account| AccountEntry {
+                    pubkey: if account.pubkey == PAYER_PUBKEY {
+                        PAYER_SENTINEL.to_string()
+                    } else {
+                        account.pubkey.to_string()
+                    },
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: bs58::encode(&instruction.data).into_string(),
+        }
+    }
+}
+
+/// Reads the instructions of a thread from a file.
+pub fn read_instructions_file(path: &str) -> Result<Vec<SerializableInstruction>, CliError> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| CliError::InvalidInstructionsFile(format!("{}: {}", path, err)))?;
+    parse_instructions(&text)
+        .map_err(|err| CliError::InvalidInstructionsFile(format!("{}: {}", path, err)))
+}
+
+/// Parses a JSON instruction or list of instructions, or a base64 encoded bincode list of instructions.
+pub fn parse_instructions(text: &str) -> Result<Vec<SerializableInstruction>, String> {
+    let text = text.trim();
+    let entries = match text.chars().next() {
+        Some('[') => serde_json::from_str::<Vec<InstructionEntry>>(text),
+        Some('{') => serde_json::from_str::<InstructionEntry>(text).map(|entry| vec![entry]),
+        _ => {
+            let bytes = STANDARD
+                .decode(text)
+                .map_err(|err| format!("neither JSON nor base64: {}", err))?;
+            return bincode::deserialize(&bytes)
+                .map_err(|err| format!("invalid bincode instructions: {}", err));
+        }
+    }
+    .map_err(|err| err.to_string())?;
+    if entries.is_empty() {
+        return Err("no instructions".to_string());
+    }
+    entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            parse_instruction(entry).map_err(|err| format!("instructions[{}].{}", index, err))
+        })
+        .collect()
+}
+
+fn parse_instruction(entry: &InstructionEntry) -> Result<SerializableInstruction, String> {
+    let program_id = Pubkey::from_str(&entry.program_id)
+        .map_err(|err| format!("program_id: invalid pubkey `{}`: {}", entry.program_id, err))?;
+    let accounts = entry
+        .accounts
+        .iter()
+        .enumerate()
+        .map(|(index, account)| {
+            let pubkey = if account.pubkey == PAYER_SENTINEL {
+                PAYER_PUBKEY
+            } else {
+                Pubkey::from_str(&account.pubkey).map_err(|err| {
+                    format!(
+                        "accounts[{}].pubkey: invalid pubkey `{}`: {}",
+                        index, account.pubkey, err
+                    )
+                })?
+            };
+            Ok(SerializableAccount {
+                pubkey,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let data = match entry.data.strip_prefix(HEX_PREFIX) {
+        Some(hex) => decode_hex(hex),
+        None => bs58::decode(&entry.data)
+            .into_vec()
+            .map_err(|err| err.to_string()),
+    }
+    .map_err(|err| format!("data: invalid data `{}`: {}", entry.data, err))?;
+    Ok(SerializableInstruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+pub fn decode_hex(hex: &str) -> Result<Vec<u8>, String> {
+    if hex.len() % 2 != 0 {
+        return Err("odd number of hex digits".to_string());
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| {
+            hex.get(index..index + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| format!("invalid hex digits at {}", index))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static EXAMPLE: &str = include_str!("../tests/fixtures/instructions.json");
+
+    #[test]
+    fn example_file_round_trips_through_serde() {
+        let instructions = parse_instructions(EXAMPLE).unwrap();
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0].data, b"hello");
+        assert_eq!(instructions[1].accounts[0].pubkey, PAYER_PUBKEY);
+        assert_eq!(
+            instructions[1].data,
+            [2, 0, 0, 0, 0xe8, 0x03, 0, 0, 0, 0, 0, 0]
+        );
+
+        let entries: Vec<InstructionEntry> =
+            instructions.iter().map(InstructionEntry::from).collect();
+        let json = serde_json::to_string(&entries).unwrap();
+        assert_eq!(parse_instructions(&json).unwrap(), instructions);
+
+        let encoded = STANDARD.encode(bincode::serialize(&instructions).unwrap());
+        assert_eq!(parse_instructions(&encoded).unwrap(), instructions);
+    }
+
+    #[test]
+    fn malformed_entries_are_located() {
+        let err = parse_instructions(
+            r#"[{"program_id": "11111111111111111111111111111111", "accounts": [{"pubkey": "nope"}]}]"#,
+        )
+        .unwrap_err();
+        assert!(
+            err.starts_with("instructions[0].accounts[0].pubkey"),
+            "{}",
+            err
+        );
+
+        let err = parse_instructions("[\n  {\"program\": \"11111111111111111111111111111111\"}\n]")
+            .unwrap_err();
+        assert!(err.contains("line 2"), "{}", err);
+
+        let err = parse_instructions(
+            r#"{"program_id": "11111111111111111111111111111111", "data": "0xabc"}"#,
+        )
+        .unwrap_err();
+        assert!(err.starts_with("instructions[0].data"), "{}", err);
+    }
+}
@@ -1,11 +1,18 @@
+mod account_file;
 mod cli;
 mod client;
 mod config;
 mod deps;
 mod errors;
+mod export;
+mod instruction_file;
+mod manifest;
+mod output;
 mod parser;
 mod print;
 mod processor;
+mod signer;
+mod transaction;
 
 use {
     crate::{config::CliConfig, print::print_style},
@@ -14,9 +21,23 @@ use {
     processor::process,
 };
 
-fn main() -> Result<(), CliError> {
-    process(&app().get_matches()).map_err(|e| {
-        print_error!("{}", e);
-        e
-    })
+fn main() {
+    let matches = app().get_matches();
+    if let Err(err) = process(&matches) {
+        print_error(&err, matches.get_flag("verbose"));
+        std::process::exit(1);
+    }
+}
+
+/// Prints the error on a single line, followed by its causes and program logs with `--verbose`.
+fn print_error(err: &CliError, verbose: bool) {
+    print_error!("{}", err);
+    let details = err.details();
+    if verbose {
+        for detail in details {
+            eprintln!("  {}", detail);
+        }
+    } else if !details.is_empty() {
+        eprintln!("  run with --verbose for the details");
+    }
 }
@@ -0,0 +1,239 @@
+use std::{fs, str::FromStr};
+
+use anchor_lang::prelude::Pubkey;
+use serde::Deserialize;
+
+use crate::errors::CliError;
+
+/// The action applied to every thread of a manifest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchAction {
+    Pause,
+    Resume,
+    Delete,
+    Update,
+}
+
+impl BatchAction {
+    /// The action as it's named on the command line.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pause => "pause",
+            Self::Resume => "resume",
+            Self::Delete => "delete",
+            Self::Update => "update",
+        }
+    }
+}
+
+impl FromStr for BatchAction {
+    type Err = CliError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "pause" => Ok(Self::Pause),
+            "resume" => Ok(Self::Resume),
+            "delete" => Ok(Self::Delete),
+            "update" => Ok(Self::Update),
+            _ => Err(CliError::BadParameter("action".into())),
+        }
+    }
+}
+
+/// A manifest of threads, in YAML or JSON.
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct ManifestFile {
+    threads: Vec<ManifestEntry>,
+}
+
+/// A thread of a manifest, by its id or its address, with the settings it is updated with.
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct ManifestEntry {
+    id: Option<String>,
+    domain: Option<String>,
+    address: Option<String>,
+    fee: Option<u64>,
+    name: Option<String>,
+    rate_limit: Option<u64>,
+    /// The new cron schedule, only for the threads with a cron trigger.
+    schedule: Option<String>,
+    skippable: Option<bool>,
+}
+
+/// A thread of a batch, its address is derived from the id with the authority when it isn't set.
+#[derive(Debug, PartialEq)]
+pub struct BatchThread {
+    pub id: Option<String>,
+    pub domain: Option<String>,
+    pub address: Option<Pubkey>,
+    pub settings: BatchSettings,
+}
+
+/// The settings a batch updates a thread with.
+#[derive(Debug, Default, PartialEq)]
+pub struct BatchSettings {
+    pub fee: Option<u64>,
+    pub name: Option<String>,
+    pub rate_limit: Option<u64>,
+    pub schedule: Option<String>,
+    pub skippable: Option<bool>,
+}
+
+impl BatchSettings {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Reads the threads a batch action applies to from a manifest file.
+pub fn read_manifest_file(path: &str, action: BatchAction) -> Result<Vec<BatchThread>, CliError> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| CliError::InvalidManifest(format!("{}: {}", path, err)))?;
+    parse_manifest(&text, action)
+        .map_err(|err| CliError::InvalidManifest(format!("{}: {}", path, err)))
+}
+
+/// Parses a manifest, the settings are only allowed, and required, for an update.
+fn parse_manifest(text: &str, action: BatchAction) -> Result<Vec<BatchThread>, String> {
+    let manifest = serde_yaml::from_str::<ManifestFile>(text).map_err(|err| err.to_string())?;
+    if manifest.threads.is_empty() {
+        return Err("no threads".to_string());
+    }
+    manifest
+        .threads
+        .into_iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            parse_entry(entry, action).map_err(|err| format!("threads[{}]: {}", index, err))
+        })
+        .collect()
+}
+
+fn parse_entry(entry: ManifestEntry, action: BatchAction) -> Result<BatchThread, String> {
+    let address = match (&entry.id, &entry.address) {
+        (Some(_), Some(_)) => return Err("either the id or the address is set, not both".into()),
+        (None, None) => return Err("the id or the address is required".into()),
+        (Some(_), None) => None,
+        (None, Some(address)) => {
+            if entry.domain.is_some() {
+                return Err("the domain only applies to an id".into());
+            }
+            Some(
+                Pubkey::from_str(address)
+                    .map_err(|err| format!("address: invalid pubkey `{}`: {}", address, err))?,
+            )
+        }
+    };
+    let settings = BatchSettings {
+        fee: entry.fee,
+        name: entry.name,
+        rate_limit: entry.rate_limit,
+        schedule: entry.schedule,
+        skippable: entry.skippable,
+    };
+    match action {
+        BatchAction::Update if settings.is_empty() => {
+            return Err("no settings to update the thread with".into())
+        }
+        BatchAction::Pause | BatchAction::Resume | BatchAction::Delete if !settings.is_empty() => {
+            return Err("the settings only apply to an update".into())
+        }
+        _ => {}
+    }
+    Ok(BatchThread {
+        id: entry.id,
+        domain: entry.domain,
+        address,
+        settings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_threads_by_id_or_address() {
+        let threads = parse_manifest(
+            "threads:\n  - id: payouts\n    domain: acme\n  - address: 4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi\n",
+            BatchAction::Pause,
+        )
+        .unwrap();
+        assert_eq!(
+            threads,
+            vec![
+                BatchThread {
+                    id: Some("payouts".into()),
+                    domain: Some("acme".into()),
+                    address: None,
+                    settings: BatchSettings::default(),
+                },
+                BatchThread {
+                    id: None,
+                    domain: None,
+                    address: Some(Pubkey::new_from_array([1; 32])),
+                    settings: BatchSettings::default(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_the_settings_of_an_update() {
+        let threads = parse_manifest(
+            r#"{"threads": [{"id": "payouts", "rate_limit": 5, "schedule": "0 * * * * * *"}]}"#,
+            BatchAction::Update,
+        )
+        .unwrap();
+        assert_eq!(
+            threads[0].settings,
+            BatchSettings {
+                rate_limit: Some(5),
+                schedule: Some("0 * * * * * *".into()),
+                ..BatchSettings::default()
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_entries() {
+        let cases = [
+            ("threads: []", BatchAction::Pause, "no threads"),
+            (
+                "threads:\n  - id: a\n    address: 4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi",
+                BatchAction::Pause,
+                "threads[0]: either the id or the address is set, not both",
+            ),
+            (
+                "threads:\n  - id: a\n  - domain: b",
+                BatchAction::Resume,
+                "threads[1]: the id or the address is required",
+            ),
+            (
+                "threads:\n  - id: a\n    fee: 1",
+                BatchAction::Delete,
+                "threads[0]: the settings only apply to an update",
+            ),
+            (
+                "threads:\n  - id: a",
+                BatchAction::Update,
+                "threads[0]: no settings to update the thread with",
+            ),
+        ];
+        for (text, action, expected) in cases {
+            assert_eq!(
+                parse_manifest(text, action).unwrap_err(),
+                expected,
+                "{}",
+                text
+            );
+        }
+        assert!(
+            parse_manifest("threads:\n  - id: a\n    paused: true", BatchAction::Pause)
+                .unwrap_err()
+                .contains("unknown field `paused`")
+        );
+    }
+}
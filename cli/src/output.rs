@@ -0,0 +1,713 @@
+use std::{fmt::Write, str::FromStr};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sablier_network_program::state::{Config, Delegation, Pool, Registry, Snapshot, Worker};
+use sablier_thread_program::state::{
+    ClockData, Equality, ExecContext, SerializableInstruction, Trigger, TriggerContext,
+    VersionedThread,
+};
+use sablier_utils::CrateInfo;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::errors::CliError;
+
+/// The format the commands print the accounts in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// The human readable output, the default.
+    #[default]
+    Text,
+    /// A stable JSON output, for scripts.
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = CliError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(CliError::BadParameter("output".into())),
+        }
+    }
+}
+
+pub fn print_json<T: Serialize>(value: &T) -> Result<(), CliError> {
+    let output = serde_json::to_string_pretty(value)
+        .map_err(|err| CliError::BadParameter(err.to_string()))?;
+    println!("{}", output);
+    Ok(())
+}
+
+/// An output with the explorer link of its account, flattened into its fields.
+#[derive(Debug, Serialize)]
+pub struct Linked<T> {
+    #[serde(flatten)]
+    pub output: T,
+    pub explorer_url: String,
+}
+
+impl<T> Linked<T> {
+    pub fn new(output: T, explorer_url: String) -> Self {
+        Self {
+            output,
+            explorer_url,
+        }
+    }
+}
+
+/// A byte vector, as UTF-8 when it is valid and as base64 otherwise.
+pub fn bytes_to_string(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(value) => value.to_string(),
+        Err(_) => STANDARD.encode(bytes),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThreadOutput {
+    pub address: String,
+    pub authority: String,
+    pub id: String,
+    pub domain: Option<String>,
+    pub created_at: ClockOutput,
+    pub fee: u64,
+    pub rate_limit: u64,
+    pub paused: bool,
+    pub trigger: TriggerOutput,
+    pub exec_context: Option<ExecContextOutput>,
+    pub instructions: Vec<InstructionOutput>,
+    pub next_instruction: Option<InstructionOutput>,
+}
+
+impl ThreadOutput {
+    pub fn new(address: Pubkey, thread: &VersionedThread) -> Self {
+        Self {
+            address: address.to_string(),
+            authority: thread.authority().to_string(),
+            id: bytes_to_string(&thread.id()),
+            domain: thread.domain().as_deref().map(bytes_to_string),
+            created_at: thread.created_at().into(),
+            fee: thread.fee(),
+            rate_limit: thread.rate_limit(),
+            paused: thread.paused(),
+            trigger: thread.trigger().into(),
+            exec_context: thread.exec_context().map(ExecContextOutput::from),
+            instructions: thread
+                .instructions()
+                .iter()
+                .map(InstructionOutput::from)
+                .collect(),
+            next_instruction: thread
+                .next_instruction()
+                .as_ref()
+                .map(InstructionOutput::from),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClockOutput {
+    pub slot: u64,
+    pub epoch: u64,
+    pub unix_timestamp: i64,
+}
+
+impl From<ClockData> for ClockOutput {
+    fn from(clock: ClockData) -> Self {
+        Self {
+            slot: clock.slot,
+            epoch: clock.epoch,
+            unix_timestamp: clock.unix_timestamp,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TriggerOutput {
+    Account {
+        address: String,
+        offset: u64,
+        size: u64,
+    },
+    Cron {
+        schedule: String,
+        skippable: bool,
+    },
+    Now,
+    Slot {
+        slot: u64,
+    },
+    Epoch {
+        epoch: u64,
+    },
+    Timestamp {
+        unix_ts: i64,
+    },
+    Pyth {
+        /// The hex encoded price feed id.
+        feed_id: String,
+        equality: &'static str,
+        limit: i64,
+    },
+    Periodic {
+        delay: u64,
+    },
+    Interval {
+        interval_seconds: u64,
+        skippable: bool,
+    },
+}
+
+impl From<Trigger> for TriggerOutput {
+    fn from(trigger: Trigger) -> Self {
+        match trigger {
+            Trigger::Account {
+                address,
+                offset,
+                size,
+            } => Self::Account {
+                address: address.to_string(),
+                offset,
+                size,
+            },
+            Trigger::Cron {
+                schedule,
+                skippable,
+            } => Self::Cron {
+                schedule,
+                skippable,
+            },
+            Trigger::Now => Self::Now,
+            Trigger::Slot { slot } => Self::Slot { slot },
+            Trigger::Epoch { epoch } => Self::Epoch { epoch },
+            Trigger::Timestamp { unix_ts } => Self::Timestamp { unix_ts },
+            Trigger::Pyth {
+                feed_id,
+                equality,
+                limit,
+            } => Self::Pyth {
+                feed_id: feed_id.iter().fold(String::new(), |mut hex, byte| {
+                    let _ = write!(hex, "{:02x}", byte);
+                    hex
+                }),
+                equality: match equality {
+                    Equality::GreaterThanOrEqual => "gte",
+                    Equality::LessThanOrEqual => "lte",
+                },
+                limit,
+            },
+            Trigger::Periodic { delay } => Self::Periodic { delay },
+            Trigger::Interval {
+                interval_seconds,
+                skippable,
+            } => Self::Interval {
+                interval_seconds,
+                skippable,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExecContextOutput {
+    pub exec_index: u64,
+    pub execs_since_reimbursement: u64,
+    pub execs_since_slot: u64,
+    pub last_exec_at: u64,
+    pub trigger_context: TriggerContextOutput,
+}
+
+impl From<ExecContext> for ExecContextOutput {
+    fn from(exec_context: ExecContext) -> Self {
+        Self {
+            exec_index: exec_context.exec_index,
+            execs_since_reimbursement: exec_context.execs_since_reimbursement,
+            execs_since_slot: exec_context.execs_since_slot,
+            last_exec_at: exec_context.last_exec_at,
+            trigger_context: exec_context.trigger_context.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TriggerContextOutput {
+    Account { data_hash: u64 },
+    Cron { started_at: i64 },
+    Now,
+    Slot { started_at: u64 },
+    Epoch { started_at: u64 },
+    Timestamp { started_at: i64 },
+    Pyth { price: i64 },
+    Periodic { started_at: i64 },
+}
+
+impl From<TriggerContext> for TriggerContextOutput {
+    fn from(trigger_context: TriggerContext) -> Self {
+        match trigger_context {
+            TriggerContext::Account { data_hash } => Self::Account { data_hash },
+            TriggerContext::Cron { started_at } => Self::Cron { started_at },
+            TriggerContext::Now => Self::Now,
+            TriggerContext::Slot { started_at } => Self::Slot { started_at },
+            TriggerContext::Epoch { started_at } => Self::Epoch { started_at },
+            TriggerContext::Timestamp { started_at } => Self::Timestamp { started_at },
+            TriggerContext::Pyth { price } => Self::Pyth { price },
+            TriggerContext::Periodic { started_at } => Self::Periodic { started_at },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct InstructionOutput {
+    pub program_id: String,
+    pub accounts: Vec<AccountMetaOutput>,
+    /// The base64 encoded data.
+    pub data: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountMetaOutput {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl From<&SerializableInstruction> for InstructionOutput {
+    fn from(instruction: &SerializableInstruction) -> Self {
+        Self {
+            program_id: instruction.program_id.to_string(),
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|account| AccountMetaOutput {
+                    pubkey: account.pubkey.to_string(),
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: STANDARD.encode(&instruction.data),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigOutput {
+    pub address: String,
+    pub admin: String,
+    pub epoch_thread: String,
+    pub hasher_thread: String,
+    pub mint: String,
+}
+
+impl ConfigOutput {
+    pub fn new(address: Pubkey, config: &Config) -> Self {
+        Self {
+            address: address.to_string(),
+            admin: config.admin.to_string(),
+            epoch_thread: config.epoch_thread.to_string(),
+            hasher_thread: config.hasher_thread.to_string(),
+            mint: config.mint.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkerOutput {
+    pub address: String,
+    pub id: u64,
+    pub authority: String,
+    pub signatory: String,
+    pub commission_balance: u64,
+    pub commission_rate: u64,
+    pub total_delegations: u64,
+    pub fee: FeeOutput,
+    pub penalty: PenaltyOutput,
+}
+
+impl WorkerOutput {
+    pub fn new(address: Pubkey, worker: &Worker, fee: FeeOutput, penalty: PenaltyOutput) -> Self {
+        Self {
+            address: address.to_string(),
+            id: worker.id,
+            authority: worker.authority.to_string(),
+            signatory: worker.signatory.to_string(),
+            commission_balance: worker.commission_balance,
+            commission_rate: worker.commission_rate,
+            total_delegations: worker.total_delegations,
+            fee,
+            penalty,
+        }
+    }
+}
+
+/// The fee account of a worker, its balance is the lamports above the rent exemption.
+#[derive(Debug, Serialize)]
+pub struct FeeOutput {
+    pub address: String,
+    pub balance: u64,
+}
+
+/// The penalty account of a worker, its balance is the lamports above the rent exemption.
+#[derive(Debug, Serialize)]
+pub struct PenaltyOutput {
+    pub address: String,
+    pub balance: u64,
+}
+
+/// The balances of a worker, in lamports. The fees, penalty and commission are the lamports above the rent exemption.
+#[derive(Debug, Serialize)]
+pub struct WorkerBalanceOutput {
+    pub id: u64,
+    pub address: String,
+    pub fees: u64,
+    pub penalty: u64,
+    pub commission: u64,
+    pub signatory: String,
+    pub signatory_balance: u64,
+    /// Whether the signatory holds less than the minimum balance of the check.
+    pub low_signatory_balance: bool,
+}
+
+/// The balances of the workers of an authority, with their totals.
+#[derive(Debug, Serialize)]
+pub struct WorkerBalancesOutput {
+    pub authority: String,
+    pub workers: Vec<WorkerBalanceOutput>,
+    pub total_fees: u64,
+    pub total_penalty: u64,
+    pub total_commission: u64,
+    pub total_signatory_balance: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DelegationOutput {
+    pub address: String,
+    pub id: u64,
+    pub authority: String,
+    pub worker: String,
+    pub worker_id: u64,
+    /// The tokens locked with the worker, in base units of the mint.
+    pub stake_amount: u64,
+    pub decimals: u8,
+    /// The tokens deposited and not staked yet, with the decimals of the mint.
+    pub liquid_balance: String,
+    /// The lamports claimable as yield.
+    pub yield_balance: u64,
+}
+
+impl DelegationOutput {
+    pub fn new(
+        address: Pubkey,
+        delegation: &Delegation,
+        worker_id: u64,
+        decimals: u8,
+        liquid_balance: String,
+    ) -> Self {
+        Self {
+            address: address.to_string(),
+            id: delegation.id,
+            authority: delegation.authority.to_string(),
+            worker: delegation.worker.to_string(),
+            worker_id,
+            stake_amount: delegation.stake_amount,
+            decimals,
+            liquid_balance,
+            yield_balance: delegation.yield_balance,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PoolOutput {
+    pub address: String,
+    pub id: u64,
+    pub size: usize,
+    pub workers: Vec<String>,
+}
+
+impl PoolOutput {
+    pub fn new(address: Pubkey, pool: &Pool) -> Self {
+        Self {
+            address: address.to_string(),
+            id: pool.id,
+            size: pool.size,
+            workers: pool.workers.iter().map(Pubkey::to_string).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RegistryOutput {
+    pub address: String,
+    pub current_epoch: u64,
+    pub locked: bool,
+    pub nonce: u64,
+    pub total_pools: u64,
+    pub total_unstakes: u64,
+    pub total_workers: u64,
+    /// The snapshot of the current epoch, missing until the first one is taken.
+    pub snapshot: Option<SnapshotOutput>,
+}
+
+impl RegistryOutput {
+    pub fn new(address: Pubkey, registry: &Registry, snapshot: Option<SnapshotOutput>) -> Self {
+        Self {
+            address: address.to_string(),
+            current_epoch: registry.current_epoch,
+            locked: registry.locked,
+            nonce: registry.nonce,
+            total_pools: registry.total_pools,
+            total_unstakes: registry.total_unstakes,
+            total_workers: registry.total_workers,
+            snapshot,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SnapshotOutput {
+    pub address: String,
+    pub id: u64,
+    pub total_frames: u64,
+    pub total_stake: u64,
+}
+
+impl SnapshotOutput {
+    pub fn new(address: Pubkey, snapshot: &Snapshot) -> Self {
+        Self {
+            address: address.to_string(),
+            id: snapshot.id,
+            total_frames: snapshot.total_frames,
+            total_stake: snapshot.total_stake,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CrateInfoOutput {
+    pub spec: String,
+    pub blob: String,
+}
+
+impl From<CrateInfo> for CrateInfoOutput {
+    fn from(crate_info: CrateInfo) -> Self {
+        Self {
+            spec: crate_info.spec,
+            blob: crate_info.blob,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionOutput {
+    pub cli: String,
+    /// The Solana version the geyser plugin of this release is built for.
+    pub geyser_plugin_interface: String,
+    pub cluster: String,
+    /// The solana-core version of the RPC node.
+    pub node: String,
+    pub programs: Vec<ProgramVersionOutput>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProgramVersionOutput {
+    pub program: &'static str,
+    pub address: String,
+    /// The deployed version, unknown for the programs predating `GetCrateInfo`.
+    pub version: Option<String>,
+    pub compatible: Option<bool>,
+    /// Why the program is incompatible, or why its version is unknown.
+    pub note: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use sablier_thread_program::state::{SerializableAccount, Thread};
+
+    use super::*;
+    use crate::cli::app;
+
+    fn pubkey(byte: u8) -> Pubkey {
+        Pubkey::new_from_array([byte; 32])
+    }
+
+    fn snapshot(name: &str) -> serde_json::Value {
+        let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn output_is_a_global_flag() {
+        let matches = app()
+            .try_get_matches_from(["sablier", "thread", "get", "payouts", "-o", "json"])
+            .unwrap();
+        assert_eq!(
+            matches.get_one::<String>("output").map(String::as_str),
+            Some("json")
+        );
+    }
+
+    #[test]
+    fn thread_json_matches_the_snapshot() {
+        let thread = VersionedThread::V1(Thread {
+            authority: pubkey(1),
+            bump: 255,
+            created_at: ClockData {
+                slot: 10,
+                epoch: 1,
+                unix_timestamp: 1_700_000_000,
+            },
+            domain: Some(vec![0xff, 0x00]),
+            exec_context: Some(ExecContext {
+                exec_index: 1,
+                execs_since_reimbursement: 0,
+                execs_since_slot: 1,
+                last_exec_at: 12,
+                trigger_context: TriggerContext::Cron {
+                    started_at: 1_700_000_060,
+                },
+            }),
+            fee: 1_000,
+            id: b"payouts".to_vec(),
+            instructions: vec![SerializableInstruction {
+                program_id: pubkey(2),
+                accounts: vec![SerializableAccount::mutable(pubkey(3), false)],
+                data: b"hello".to_vec(),
+            }],
+            next_instruction: None,
+            paused: false,
+            rate_limit: 10,
+            trigger: Trigger::Cron {
+                schedule: "0 * * * * *".to_string(),
+                skippable: true,
+            },
+        });
+        assert_eq!(
+            serde_json::to_value(ThreadOutput::new(pubkey(4), &thread)).unwrap(),
+            snapshot("thread_output.json")
+        );
+    }
+
+    #[test]
+    fn worker_and_pool_json_match_the_snapshots() {
+        let worker = Worker {
+            authority: pubkey(1),
+            commission_balance: 5,
+            commission_rate: 10,
+            id: 0,
+            signatory: pubkey(2),
+            total_delegations: 3,
+            bump: 255,
+        };
+        let output = WorkerOutput::new(
+            pubkey(3),
+            &worker,
+            FeeOutput {
+                address: pubkey(4).to_string(),
+                balance: 100,
+            },
+            PenaltyOutput {
+                address: pubkey(5).to_string(),
+                balance: 0,
+            },
+        );
+        assert_eq!(
+            serde_json::to_value(output).unwrap(),
+            snapshot("worker_output.json")
+        );
+
+        let pool = Pool {
+            id: 0,
+            size: 1,
+            workers: vec![pubkey(2)].into(),
+        };
+        assert_eq!(
+            serde_json::to_value(PoolOutput::new(pubkey(6), &pool)).unwrap(),
+            snapshot("pool_output.json")
+        );
+
+        let delegation = Delegation {
+            authority: pubkey(1),
+            id: 2,
+            stake_amount: 150_000_000,
+            worker: pubkey(3),
+            yield_balance: 5_000,
+        };
+        assert_eq!(
+            serde_json::to_value(DelegationOutput::new(
+                pubkey(7),
+                &delegation,
+                0,
+                8,
+                "0.5".to_string()
+            ))
+            .unwrap(),
+            snapshot("delegation_output.json")
+        );
+    }
+
+    #[test]
+    fn config_json_matches_the_snapshot() {
+        let config = Config {
+            admin: pubkey(1),
+            epoch_thread: pubkey(2),
+            hasher_thread: pubkey(3),
+            mint: pubkey(4),
+        };
+        assert_eq!(
+            serde_json::to_value(ConfigOutput::new(pubkey(7), &config)).unwrap(),
+            snapshot("config_output.json")
+        );
+    }
+
+    #[test]
+    fn registry_json_matches_the_snapshot() {
+        let registry = Registry {
+            current_epoch: 4,
+            locked: true,
+            nonce: 9,
+            total_pools: 1,
+            total_unstakes: 0,
+            total_workers: 2,
+            bump: 255,
+        };
+        let epoch_snapshot = Snapshot {
+            id: 4,
+            total_frames: 2,
+            total_stake: 100,
+        };
+        assert_eq!(
+            serde_json::to_value(RegistryOutput::new(
+                pubkey(1),
+                &registry,
+                Some(SnapshotOutput::new(pubkey(3), &epoch_snapshot))
+            ))
+            .unwrap(),
+            snapshot("registry_output.json")
+        );
+    }
+
+    #[test]
+    fn linked_json_adds_the_explorer_url_to_the_fields() {
+        let config = Config {
+            admin: pubkey(1),
+            epoch_thread: pubkey(2),
+            hasher_thread: pubkey(3),
+            mint: pubkey(4),
+        };
+        let mut expected = snapshot("config_output.json");
+        expected["explorer_url"] = "https://explorer.solana.com/address/x".into();
+        assert_eq!(
+            serde_json::to_value(Linked::new(
+                ConfigOutput::new(pubkey(7), &config),
+                "https://explorer.solana.com/address/x".into()
+            ))
+            .unwrap(),
+            expected
+        );
+    }
+}
@@ -1,15 +1,30 @@
 use std::{convert::TryFrom, fs, path::PathBuf, str::FromStr};
 
+use chrono::DateTime;
+use chrono_tz::Tz;
 use clap::ArgMatches;
-use sablier_thread_program::state::{SerializableAccount, SerializableInstruction, Trigger};
+use sablier_network_program::state::Worker;
+use sablier_thread_program::{
+    constants::PERIODIC_MINIMUM_INTERVAL,
+    state::{Equality, SerializableAccount, SerializableInstruction, Trigger},
+};
+use sablier_utils::cron::parse_thread_schedule;
 use serde::{Deserialize as JsonDeserialize, Serialize as JsonSerialize};
 use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
     pubkey::Pubkey,
     signature::{read_keypair_file, Keypair},
     signer::Signer,
 };
 
-use crate::{cli::CliCommand, errors::CliError};
+use crate::{
+    account_file::DecodeSource,
+    cli::{CliCommand, TriggerArg},
+    errors::CliError,
+    export::{ExportField, ExportFormat},
+    instruction_file::{decode_hex, read_instructions_file},
+    manifest::{read_manifest_file, BatchAction},
+};
 
 impl TryFrom<&ArgMatches> for CliCommand {
     type Error = CliError;
@@ -25,9 +40,12 @@ impl TryFrom<&ArgMatches> for CliCommand {
             Some(("pool", matches)) => parse_pool_command(matches),
             Some(("thread", matches)) => parse_thread_command(matches),
             Some(("registry", matches)) => parse_registry_command(matches),
+            Some(("version", matches)) => Ok(CliCommand::Version {
+                cluster: parse_string("cluster", matches).ok(),
+            }),
             Some(("worker", matches)) => parse_worker_command(matches),
             _ => Err(CliError::CommandNotRecognized(
-                matches.subcommand().unwrap().0.into(),
+                matches.subcommand_name().unwrap_or_default().into(),
             )),
         }
     }
@@ -45,11 +63,8 @@ fn parse_bpf_command(matches: &ArgMatches) -> Result<CliCommand, CliError> {
                 [address, program] => {
                     let address = address
                         .parse::<Pubkey>()
-                        .or_else(|_| read_keypair_file(address).map(|keypair| keypair.pubkey()));
-
-                    if address.is_err() {
-                        return Err(CliError::InvalidAddress);
-                    }
+                        .or_else(|_| read_keypair_file(address).map(|keypair| keypair.pubkey()))
+                        .map_err(|_err| CliError::InvalidAddress)?;
 
                     let program_path = PathBuf::from(program);
 
@@ -58,7 +73,7 @@ fn parse_bpf_command(matches: &ArgMatches) -> Result<CliCommand, CliError> {
                     }
 
                     program_infos.push(ProgramInfo {
-                        program_id: address.unwrap(),
+                        program_id: address,
                         program_path,
                     });
                 }
@@ -67,20 +82,34 @@ fn parse_bpf_command(matches: &ArgMatches) -> Result<CliCommand, CliError> {
         }
     }
 
-    if let Some(values) = matches.get_many::<Pubkey>("clone") {
+    if let Some(values) = matches.get_many::<String>("clone") {
         for address in values {
-            clone_addresses.push(*address);
+            clone_addresses.push(
+                Pubkey::from_str(address)
+                    .map_err(|_err| CliError::BadParameter(format!("clone: {}", address)))?,
+            );
+        }
+    }
+
+    let mut clone_programs = Vec::<Pubkey>::new();
+    if let Some(values) = matches.get_many::<String>("clone_program") {
+        for program_id in values {
+            clone_programs.push(Pubkey::from_str(program_id).map_err(|_err| {
+                CliError::BadParameter(format!("clone-program: {}", program_id))
+            })?);
         }
     }
 
     Ok(CliCommand::Localnet {
         clone_addresses,
+        clone_programs,
         network_url: parse_string("url", matches).ok(),
         program_infos,
-        force_init: matches.contains_id("force_init"),
+        force_init: matches.get_flag("force_init"),
         solana_archive: parse_string("solana_archive", matches).ok(),
         sablier_archive: parse_string("sablier_archive", matches).ok(),
-        dev: matches.contains_id("dev"),
+        dev: matches.get_flag("dev"),
+        keep_ledger: matches.get_flag("keep_ledger"),
     })
 }
 
@@ -91,9 +120,10 @@ fn parse_config_command(matches: &ArgMatches) -> Result<CliCommand, CliError> {
             admin: parse_pubkey("admin", matches).ok(),
             epoch_thread: parse_pubkey("epoch_thread", matches).ok(),
             hasher_thread: parse_pubkey("hasher_thread", matches).ok(),
+            mint: parse_pubkey("mint", matches).ok(),
         }),
         _ => Err(CliError::CommandNotRecognized(
-            matches.subcommand().unwrap().0.into(),
+            matches.subcommand_name().unwrap_or_default().into(),
         )),
     }
 }
@@ -101,6 +131,16 @@ fn parse_config_command(matches: &ArgMatches) -> Result<CliCommand, CliError> {
 fn parse_crontab_command(matches: &ArgMatches) -> Result<CliCommand, CliError> {
     Ok(CliCommand::Crontab {
         schedule: parse_string("schedule", matches)?,
+        count: parse_usize("count", matches)?,
+        timezone: parse_string("timezone", matches)
+            .ok()
+            .map(|timezone| Tz::from_str(&timezone))
+            .transpose()
+            .map_err(|_err| CliError::BadParameter("timezone".into()))?,
+        after: matches
+            .contains_id("after")
+            .then(|| parse_timestamp("after", matches))
+            .transpose()?,
     })
 }
 
@@ -110,7 +150,7 @@ fn parse_delegation_command(matches: &ArgMatches) -> Result<CliCommand, CliError
             worker_id: parse_u64("worker_id", matches)?,
         }),
         Some(("deposit", matches)) => Ok(CliCommand::DelegationDeposit {
-            amount: parse_u64("amount", matches)?,
+            amount: parse_string("amount", matches)?,
             delegation_id: parse_u64("delegation_id", matches)?,
             worker_id: parse_u64("worker_id", matches)?,
         }),
@@ -119,12 +159,12 @@ fn parse_delegation_command(matches: &ArgMatches) -> Result<CliCommand, CliError
             worker_id: parse_u64("worker_id", matches)?,
         }),
         Some(("withdraw", matches)) => Ok(CliCommand::DelegationWithdraw {
-            amount: parse_u64("amount", matches)?,
+            amount: parse_string("amount", matches)?,
             delegation_id: parse_u64("delegation_id", matches)?,
             worker_id: parse_u64("worker_id", matches)?,
         }),
         _ => Err(CliError::CommandNotRecognized(
-            matches.subcommand().unwrap().0.into(),
+            matches.subcommand_name().unwrap_or_default().into(),
         )),
     }
 }
@@ -133,10 +173,11 @@ fn parse_explorer_command(matches: &ArgMatches) -> Result<CliCommand, CliError>
     match matches.subcommand() {
         Some(("get", matches)) => Ok(CliCommand::ExplorerGetThread {
             id: parse_string("id", matches).ok(),
+            domain: parse_string("domain", matches).ok(),
             address: parse_pubkey("address", matches).ok(),
         }),
         _ => Err(CliError::CommandNotRecognized(
-            matches.subcommand().unwrap().0.into(),
+            matches.subcommand_name().unwrap_or_default().into(),
         )),
     }
 }
@@ -158,43 +199,198 @@ fn parse_pool_command(matches: &ArgMatches) -> Result<CliCommand, CliError> {
         }),
         Some(("list", _)) => Ok(CliCommand::PoolList {}),
         _ => Err(CliError::CommandNotRecognized(
-            matches.subcommand().unwrap().0.into(),
+            matches.subcommand_name().unwrap_or_default().into(),
         )),
     }
 }
 
 fn parse_thread_command(matches: &ArgMatches) -> Result<CliCommand, CliError> {
     match matches.subcommand() {
+        Some(("batch", matches)) => {
+            let action = BatchAction::from_str(&parse_string("action", matches)?)?;
+            Ok(CliCommand::ThreadBatch {
+                action,
+                threads: read_manifest_file(&parse_string("file", matches)?, action)?,
+                continue_on_error: matches.get_flag("continue_on_error"),
+                dry_run: matches.get_flag("dry_run"),
+            })
+        }
+        Some((name @ ("pause-all" | "resume-all"), matches)) => Ok(CliCommand::ThreadPauseAll {
+            pause: name == "pause-all",
+            pattern: parse_string("match", matches).ok(),
+        }),
+        Some(("clone", matches)) => {
+            // The thread to clone is either the authority's thread with the id, or any thread by address.
+            let from = parse_string("from", matches)?;
+            let from_address = Pubkey::from_str(&from).ok();
+            if from_address.is_some() && matches.contains_id("from_domain") {
+                return Err(CliError::BadParameter(
+                    "from-domain: the domain only applies to an id".into(),
+                ));
+            }
+            Ok(CliCommand::ThreadClone {
+                from_id: from_address.is_none().then_some(from),
+                from_domain: parse_string("from_domain", matches).ok(),
+                from_address,
+                id: parse_string("to_id", matches)?,
+                domain: parse_string("to_domain", matches).ok(),
+                paused: matches.get_flag("paused"),
+            })
+        }
         Some(("crate-info", _)) => Ok(CliCommand::ThreadCrateInfo {}),
-        Some(("create", matches)) => Ok(CliCommand::ThreadCreate {
-            id: parse_string("id", matches)?,
-            domain: parse_string("domain", matches)?,
-            kickoff_instruction: parse_instruction_file("kickoff_instruction", matches)?,
-            trigger: parse_trigger(matches)?,
+        Some(("create", matches)) => {
+            let trigger =
+                parse_trigger_flags(matches)?.ok_or(CliError::BadParameter("trigger".into()))?;
+            if !matches!(
+                trigger,
+                TriggerArg::Trigger(Trigger::Cron { .. } | Trigger::Interval { .. })
+            ) && (matches.get_flag("skippable") || matches.get_flag("no_skippable"))
+            {
+                return Err(CliError::BadParameter(
+                    "skippable: only applies to a cron or periodic trigger".into(),
+                ));
+            }
+            Ok(CliCommand::ThreadCreate {
+                id: parse_string("id", matches)?,
+                domain: parse_string("domain", matches).ok(),
+                instructions: if matches.contains_id("instructions_file") {
+                    read_instructions_file(&parse_string("instructions_file", matches)?)?
+                } else {
+                    vec![parse_instruction_file("kickoff_instruction", matches)?]
+                },
+                trigger,
+            })
+        }
+        Some(("decode", matches)) => Ok(CliCommand::ThreadDecode {
+            source: if let Ok(path) = parse_string("file", matches) {
+                DecodeSource::File(path)
+            } else if let Ok(data) = parse_string("base64", matches) {
+                DecodeSource::Base64(data)
+            } else {
+                DecodeSource::Address(parse_pubkey("address", matches)?)
+            },
         }),
         Some(("delete", matches)) => Ok(CliCommand::ThreadDelete {
-            id: parse_string("id", matches)?,
+            id: parse_string("id", matches).ok(),
+            domain: parse_string("domain", matches).ok(),
+            address: parse_pubkey("address", matches).ok(),
+        }),
+        Some(("fund", matches)) => Ok(CliCommand::ThreadFund {
+            id: parse_string("id", matches).ok(),
+            domain: parse_string("domain", matches).ok(),
+            address: parse_pubkey("address", matches).ok(),
+            lamports: parse_amount(matches)?,
         }),
         Some(("get", matches)) => Ok(CliCommand::ThreadGet {
             id: parse_string("id", matches).ok(),
+            domain: parse_string("domain", matches).ok(),
             address: parse_pubkey("address", matches).ok(),
         }),
+        Some(("export", matches)) => Ok(CliCommand::ThreadExport {
+            format: ExportFormat::from_str(&parse_string("format", matches)?)?,
+            out: parse_string("out", matches).ok(),
+            fields: match matches.get_many::<String>("fields") {
+                None => ExportField::ALL.to_vec(),
+                Some(names) => names
+                    .map(|name| ExportField::from_str(name))
+                    .collect::<Result<_, _>>()?,
+            },
+        }),
+        Some(("list", matches)) => Ok(CliCommand::ThreadList {
+            limit: parse_usize("limit", matches).ok(),
+            json: matches.get_flag("json"),
+        }),
+        Some(("logs", matches)) => Ok(CliCommand::ThreadLogs {
+            id: parse_string("id", matches).ok(),
+            domain: parse_string("domain", matches).ok(),
+            address: parse_pubkey("address", matches).ok(),
+            limit: matches
+                .get_one::<u64>("limit")
+                .map(|limit| *limit as usize)
+                .ok_or(CliError::BadParameter("limit".into()))?,
+            json: matches.get_flag("json"),
+        }),
         Some(("pause", matches)) => Ok(CliCommand::ThreadPause {
-            id: parse_string("id", matches)?,
+            id: parse_string("id", matches).ok(),
+            domain: parse_string("domain", matches).ok(),
+            address: parse_pubkey("address", matches).ok(),
+        }),
+        Some(("pubkey", matches)) => Ok(CliCommand::ThreadPubkey {
+            id: match parse_string("raw_id_hex", matches) {
+                Ok(hex) => decode_hex(hex.trim_start_matches("0x"))
+                    .map_err(|err| CliError::BadParameter(format!("raw-id-hex: {}", err)))?,
+                Err(_err) => parse_string("id", matches)?.into_bytes(),
+            },
+            domain: parse_string("domain", matches).ok(),
         }),
         Some(("resume", matches)) => Ok(CliCommand::ThreadResume {
-            id: parse_string("id", matches)?,
+            id: parse_string("id", matches).ok(),
+            domain: parse_string("domain", matches).ok(),
+            address: parse_pubkey("address", matches).ok(),
         }),
         Some(("reset", matches)) => Ok(CliCommand::ThreadReset {
-            id: parse_string("id", matches)?,
+            id: parse_string("id", matches).ok(),
+            domain: parse_string("domain", matches).ok(),
+            address: parse_pubkey("address", matches).ok(),
+        }),
+        Some(("set-instruction", matches)) => {
+            let path = parse_string("instructions_file", matches)?;
+            let mut instructions = read_instructions_file(&path)?;
+            if instructions.len() != 1 {
+                return Err(CliError::InvalidInstructionsFile(format!(
+                    "{}: has {} instructions, set-instruction takes exactly one",
+                    path,
+                    instructions.len()
+                )));
+            }
+            Ok(CliCommand::ThreadSetInstruction {
+                id: parse_string("id", matches).ok(),
+                domain: parse_string("domain", matches).ok(),
+                address: parse_pubkey("address", matches).ok(),
+                index: matches.get_one::<usize>("index").copied(),
+                instruction: instructions.remove(0),
+            })
+        }
+        Some(("simulate", matches)) => Ok(CliCommand::ThreadSimulate {
+            id: parse_string("id", matches).ok(),
+            domain: parse_string("domain", matches).ok(),
+            address: parse_pubkey("address", matches).ok(),
+            worker_id: parse_u64("worker_id", matches)?,
+        }),
+        Some(("watch", matches)) => Ok(CliCommand::ThreadWatch {
+            id: parse_string("id", matches).ok(),
+            domain: parse_string("domain", matches).ok(),
+            address: parse_pubkey("address", matches).ok(),
+            interval: parse_u64("interval", matches).ok(),
+        }),
+        Some(("withdraw", matches)) => Ok(CliCommand::ThreadWithdraw {
+            id: parse_string("id", matches).ok(),
+            domain: parse_string("domain", matches).ok(),
+            address: parse_pubkey("address", matches).ok(),
+            lamports: parse_amount(matches)?,
+            to: parse_pubkey("to", matches).ok(),
         }),
         Some(("update", matches)) => Ok(CliCommand::ThreadUpdate {
             id: parse_string("id", matches)?,
+            domain: parse_string("domain", matches).ok(),
+            fee: parse_u64("fee", matches).ok(),
+            name: parse_string("name", matches).ok(),
             rate_limit: parse_u64("rate_limit", matches).ok(),
-            schedule: parse_string("schedule", matches).ok(),
+            instructions: parse_string("instructions_file", matches)
+                .ok()
+                .map(|path| read_instructions_file(&path))
+                .transpose()?,
+            trigger: parse_trigger_flags(matches)?,
+            skippable: if matches.get_flag("skippable") {
+                Some(true)
+            } else if matches.get_flag("no_skippable") {
+                Some(false)
+            } else {
+                None
+            },
         }),
         _ => Err(CliError::CommandNotRecognized(
-            matches.subcommand().unwrap().0.into(),
+            matches.subcommand_name().unwrap_or_default().into(),
         )),
     }
 }
@@ -204,7 +400,7 @@ fn parse_registry_command(matches: &ArgMatches) -> Result<CliCommand, CliError>
         Some(("get", _)) => Ok(CliCommand::RegistryGet {}),
         Some(("unlock", _)) => Ok(CliCommand::RegistryUnlock {}),
         _ => Err(CliError::CommandNotRecognized(
-            matches.subcommand().unwrap().0.into(),
+            matches.subcommand_name().unwrap_or_default().into(),
         )),
     }
 }
@@ -212,40 +408,270 @@ fn parse_registry_command(matches: &ArgMatches) -> Result<CliCommand, CliError>
 fn parse_worker_command(matches: &ArgMatches) -> Result<CliCommand, CliError> {
     match matches.subcommand() {
         Some(("create", matches)) => Ok(CliCommand::WorkerCreate {
-            signatory: parse_keypair_file("signatory_keypair", matches)?,
+            signatory: if matches.contains_id("signatory") {
+                parse_keypair_file("signatory", matches)?
+            } else {
+                parse_keypair_file("signatory_keypair", matches)?
+            },
+        }),
+        Some(("balances", matches)) => Ok(CliCommand::WorkerBalances {
+            min_signatory_balance: match parse_string("min_signatory_balance", matches) {
+                Ok(sol) => Some(
+                    sol_to_lamports(&sol)
+                        .ok_or(CliError::BadParameter("min_signatory_balance".into()))?,
+                ),
+                Err(_) => None,
+            },
+            json: matches.get_flag("json"),
+        }),
+        Some(("claim", matches)) => Ok(CliCommand::WorkerClaim {
+            address: parse_worker_address("id", matches)?,
+            lamports: if matches.contains_id("amount") {
+                Some(parse_amount(matches)?)
+            } else {
+                None
+            },
+            to: parse_pubkey("to", matches).ok(),
         }),
         Some(("get", matches)) => Ok(CliCommand::WorkerGet {
-            id: parse_u64("id", matches)?,
+            address: parse_worker_address("id", matches)?,
         }),
         Some(("update", matches)) => Ok(CliCommand::WorkerUpdate {
-            id: parse_u64("id", matches)?,
-            signatory: parse_keypair_file("signatory_keypair", matches).ok(),
+            address: parse_worker_address("id", matches)?,
+            commission_rate: matches.get_one::<u64>("commission_rate").copied(),
+            signatory: if matches.contains_id("signatory_keypair") {
+                Some(parse_keypair_file("signatory_keypair", matches)?.pubkey())
+            } else {
+                parse_pubkey("signatory", matches).ok()
+            },
         }),
         _ => Err(CliError::CommandNotRecognized(
-            matches.subcommand().unwrap().0.into(),
+            matches.subcommand_name().unwrap_or_default().into(),
         )),
     }
 }
 
 // Arg parsers
 
-fn parse_trigger(matches: &ArgMatches) -> Result<Trigger, CliError> {
-    if matches.contains_id("account") {
-        return Ok(Trigger::Account {
-            address: parse_pubkey("address", matches)?,
-            offset: 0, // TODO
-            size: 32,  // TODO
-        });
-    } else if matches.contains_id("cron") {
-        return Ok(Trigger::Cron {
-            schedule: parse_string("cron", matches)?,
-            skippable: true,
-        });
-    } else if matches.contains_id("now") {
-        return Ok(Trigger::Now);
+/// Parses the trigger flags, at most one trigger kind is set as enforced by clap.
+/// A new cron or periodic trigger is skippable unless `--no-skippable` is set, `thread update` resolves the flags against the
+/// current thread instead.
+fn parse_trigger_flags(matches: &ArgMatches) -> Result<Option<TriggerArg>, CliError> {
+    let trigger = if matches.contains_id("cron") {
+        Trigger::Cron {
+            schedule: parse_schedule("cron", matches)?,
+            skippable: !matches.get_flag("no_skippable"),
+        }
+    } else if matches.contains_id("account") {
+        let offset = parse_u64("offset", matches)?;
+        let size = parse_u64("size", matches)?;
+        if size == 0 {
+            return Err(CliError::BadParameter(
+                "size: the watched account data can't be empty".into(),
+            ));
+        }
+        if offset.checked_add(size).is_none() {
+            return Err(CliError::BadParameter(format!(
+                "offset: the watched account data would end past the largest offset, {} + {}",
+                offset, size
+            )));
+        }
+        Trigger::Account {
+            address: parse_pubkey("account", matches)?,
+            offset,
+            size,
+        }
+    } else if matches.get_flag("now") {
+        Trigger::Now
+    } else if matches.contains_id("slot") {
+        Trigger::Slot {
+            slot: parse_u64("slot", matches)?,
+        }
+    } else if matches.contains_id("epoch") {
+        Trigger::Epoch {
+            epoch: parse_u64("epoch", matches)?,
+        }
+    } else if matches.contains_id("timestamp") {
+        Trigger::Timestamp {
+            unix_ts: parse_timestamp("timestamp", matches)?,
+        }
+    } else if matches.contains_id("pyth") {
+        let (arg, equality) = if matches.contains_id("gte") {
+            ("gte", Equality::GreaterThanOrEqual)
+        } else {
+            ("lte", Equality::LessThanOrEqual)
+        };
+        let feed_id = parse_feed_id("pyth", matches)?;
+        let price = parse_string(arg, matches)?;
+        let exponent = matches.get_one::<i32>("pyth_exponent").copied();
+        // The price is scaled right away when the exponent is known, else once it is read from the feed.
+        match exponent {
+            Some(exponent) => Trigger::Pyth {
+                feed_id,
+                equality,
+                limit: scale_price(&price, exponent).ok_or_else(|| {
+                    CliError::BadParameter(format!(
+                        "{}: {} is not a price of the feed with the exponent {}",
+                        arg, price, exponent
+                    ))
+                })?,
+            },
+            None if is_decimal(&price) => {
+                return Ok(Some(TriggerArg::Pyth {
+                    feed_id,
+                    equality,
+                    price,
+                    exponent,
+                }))
+            }
+            None => {
+                return Err(CliError::BadParameter(format!(
+                    "{}: {} is not a decimal price",
+                    arg, price
+                )))
+            }
+        }
+    } else if matches.contains_id("periodic") {
+        let interval_seconds = parse_u64("periodic", matches)?;
+        if interval_seconds < PERIODIC_MINIMUM_INTERVAL {
+            return Err(CliError::BadParameter(format!(
+                "periodic: the interval must be at least {} seconds",
+                PERIODIC_MINIMUM_INTERVAL
+            )));
+        }
+        Trigger::Interval {
+            interval_seconds,
+            skippable: !matches.get_flag("no_skippable"),
+        }
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(TriggerArg::Trigger(trigger)))
+}
+
+/// Parses a unix timestamp, or a RFC 3339 date.
+fn parse_timestamp(arg: &str, matches: &ArgMatches) -> Result<i64, CliError> {
+    let value = parse_string(arg, matches)?;
+    value
+        .parse::<i64>()
+        .ok()
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(&value)
+                .ok()
+                .map(|datetime| datetime.timestamp())
+        })
+        .ok_or_else(|| {
+            CliError::BadParameter(format!(
+                "{}: {} is neither a unix timestamp nor a RFC 3339 date",
+                arg, value
+            ))
+        })
+}
+
+fn is_decimal(value: &str) -> bool {
+    let magnitude = value.strip_prefix('-').unwrap_or(value);
+    let (whole, fraction) = magnitude.split_once('.').unwrap_or((magnitude, ""));
+    !(whole.is_empty() && fraction.is_empty())
+        && whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+}
+
+/// Scales a decimal price to the integer prices of a pyth feed, e.g. 1.5 is 150000000 with the exponent -8.
+/// Fails when the price has more decimals than the exponent allows, or overflows.
+pub fn scale_price(price: &str, exponent: i32) -> Option<i64> {
+    let (negative, magnitude) = match price.strip_prefix('-') {
+        Some(magnitude) => (true, magnitude),
+        None => (false, price),
+    };
+    let scaled = if exponent <= 0 {
+        ui_amount_to_base_units(magnitude, exponent.unsigned_abs().try_into().ok()?)?
+    } else {
+        let units = ui_amount_to_base_units(magnitude, 0)?;
+        let factor = 10u64.checked_pow(exponent.unsigned_abs())?;
+        if units % factor != 0 {
+            return None;
+        }
+        units / factor
+    };
+    let scaled = i64::try_from(scaled).ok()?;
+    Some(if negative { -scaled } else { scaled })
+}
+
+/// Parses the schedule of a cron thread, rejecting the schedules the thread program could not evaluate.
+fn parse_schedule(arg: &str, matches: &ArgMatches) -> Result<String, CliError> {
+    let schedule = parse_string(arg, matches)?;
+    parse_thread_schedule(&schedule)
+        .map_err(|err| CliError::BadParameter(format!("{}: {}", arg, err)))?;
+    Ok(schedule)
+}
+
+/// Parses a hex encoded pyth price feed id, optionally prefixed with `0x`.
+fn parse_feed_id(arg: &str, matches: &ArgMatches) -> Result<[u8; 32], CliError> {
+    let value = parse_string(arg, matches)?;
+    let hex = value.strip_prefix("0x").unwrap_or(&value);
+    if hex.len() != 64 {
+        return Err(CliError::BadParameter(format!(
+            "{}: a feed id is 32 bytes, 64 hex digits, not {} digits",
+            arg,
+            hex.len()
+        )));
     }
+    let mut feed_id = [0; 32];
+    for (index, byte) in feed_id.iter_mut().enumerate() {
+        *byte = hex
+            .get(index * 2..index * 2 + 2)
+            .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| CliError::BadParameter(format!("{}: {} is not hex", arg, value)))?;
+    }
+    Ok(feed_id)
+}
+
+/// Parses the `--amount` of a transfer to lamports, it is in SOL unless `--lamports` is set.
+fn parse_amount(matches: &ArgMatches) -> Result<u64, CliError> {
+    if matches.get_flag("lamports") {
+        parse_u64("amount", matches)
+    } else {
+        sol_to_lamports(&parse_string("amount", matches)?)
+            .ok_or(CliError::BadParameter("amount".into()))
+    }
+}
+
+/// Converts a decimal amount of SOL to lamports, without the rounding of a float conversion.
+fn sol_to_lamports(amount: &str) -> Option<u64> {
+    ui_amount_to_base_units(amount, LAMPORTS_PER_SOL.ilog10() as u8)
+}
 
-    Err(CliError::BadParameter("trigger".into()))
+/// Converts a decimal amount of tokens to the base units of a mint with the decimals,
+/// without the rounding of a float conversion.
+pub fn ui_amount_to_base_units(amount: &str, decimals: u8) -> Option<u64> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let decimals = decimals as usize;
+    if (whole.is_empty() && fraction.is_empty())
+        || fraction.len() > decimals
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let whole = if whole.is_empty() {
+        0
+    } else {
+        whole.parse::<u64>().ok()?
+    };
+    let fraction = if decimals == 0 {
+        0
+    } else {
+        format!("{:0<width$}", fraction, width = decimals)
+            .parse::<u64>()
+            .ok()?
+    };
+    whole
+        .checked_mul(10u64.checked_pow(decimals as u32)?)?
+        .checked_add(fraction)
 }
 
 fn parse_instruction_file(
@@ -254,8 +680,8 @@ fn parse_instruction_file(
 ) -> Result<SerializableInstruction, CliError> {
     let filepath = parse_string(arg, matches)?;
     let text = fs::read_to_string(filepath).map_err(|_err| CliError::BadParameter(arg.into()))?;
-    let ix: JsonInstructionData =
-        serde_json::from_str(text.as_str()).expect("JSON was not well-formatted");
+    let ix: JsonInstructionData = serde_json::from_str(text.as_str())
+        .map_err(|err| CliError::BadParameter(format!("{}: {}", arg, err)))?;
     SerializableInstruction::try_from(&ix)
 }
 
@@ -264,6 +690,15 @@ fn parse_keypair_file(arg: &str, matches: &ArgMatches) -> Result<Keypair, CliErr
         .map_err(|_err| CliError::BadParameter(arg.into()))
 }
 
+/// Parses a worker id, or the address of a worker.
+fn parse_worker_address(arg: &str, matches: &ArgMatches) -> Result<Pubkey, CliError> {
+    let value = parse_string(arg, matches)?;
+    match value.parse::<u64>() {
+        Ok(id) => Ok(Worker::pubkey(id)),
+        Err(_err) => Pubkey::from_str(&value).map_err(|_err| CliError::BadParameter(arg.into())),
+    }
+}
+
 fn parse_pubkey(arg: &str, matches: &ArgMatches) -> Result<Pubkey, CliError> {
     Pubkey::from_str(parse_string(arg, matches)?.as_str())
         .map_err(|_err| CliError::BadParameter(arg.into()))
@@ -276,12 +711,6 @@ fn parse_string(arg: &str, matches: &ArgMatches) -> Result<String, CliError> {
         .cloned()
 }
 
-pub fn _parse_i64(arg: &str, matches: &ArgMatches) -> Result<i64, CliError> {
-    parse_string(arg, matches)?
-        .parse::<i64>()
-        .map_err(|_err| CliError::BadParameter(arg.into()))
-}
-
 pub fn parse_u64(arg: &str, matches: &ArgMatches) -> Result<u64, CliError> {
     parse_string(arg, matches)?
         .parse::<u64>()
@@ -313,8 +742,8 @@ impl TryFrom<&JsonInstructionData> for SerializableInstruction {
             accounts: value
                 .accounts
                 .iter()
-                .map(|acc| SerializableAccount::try_from(acc).unwrap())
-                .collect::<Vec<SerializableAccount>>(),
+                .map(SerializableAccount::try_from)
+                .collect::<Result<Vec<SerializableAccount>, CliError>>()?,
             data: value.data.clone(),
         })
     }
@@ -353,3 +782,258 @@ pub struct ProgramInfo {
     pub program_id: Pubkey,
     pub program_path: PathBuf,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sol_amounts_are_converted_to_lamports_exactly() {
+        assert_eq!(sol_to_lamports("1"), Some(LAMPORTS_PER_SOL));
+        assert_eq!(sol_to_lamports("0.1"), Some(100_000_000));
+        assert_eq!(sol_to_lamports(".000000001"), Some(1));
+        assert_eq!(sol_to_lamports("2.5"), Some(2_500_000_000));
+        assert_eq!(sol_to_lamports("0.0000000001"), None);
+        assert_eq!(sol_to_lamports("-1"), None);
+        assert_eq!(sol_to_lamports("."), None);
+        assert_eq!(sol_to_lamports("1e9"), None);
+    }
+
+    #[test]
+    fn token_amounts_respect_the_decimals_of_the_mint() {
+        assert_eq!(ui_amount_to_base_units("1.5", 8), Some(150_000_000));
+        assert_eq!(ui_amount_to_base_units("0.000000001", 8), None);
+        assert_eq!(ui_amount_to_base_units("42", 0), Some(42));
+        assert_eq!(ui_amount_to_base_units("4.2", 0), None);
+    }
+
+    #[test]
+    fn cloned_threads_are_looked_up_by_id_or_address() {
+        let parse = |args: &[&str]| {
+            let matches = crate::cli::app()
+                .try_get_matches_from([&["sablier", "thread", "clone"], args].concat())
+                .unwrap();
+            CliCommand::try_from(&matches)
+        };
+        assert_eq!(
+            parse(&[
+                "--from",
+                "payouts",
+                "--from-domain",
+                "acme",
+                "--to-id",
+                "copy",
+                "--paused"
+            ])
+            .unwrap(),
+            CliCommand::ThreadClone {
+                from_id: Some("payouts".into()),
+                from_domain: Some("acme".into()),
+                from_address: None,
+                id: "copy".into(),
+                domain: None,
+                paused: true,
+            }
+        );
+        assert_eq!(
+            parse(&[
+                "--from",
+                "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi",
+                "--to-id",
+                "copy",
+                "--to-domain",
+                "acme",
+            ])
+            .unwrap(),
+            CliCommand::ThreadClone {
+                from_id: None,
+                from_domain: None,
+                from_address: Some(Pubkey::new_from_array([1; 32])),
+                id: "copy".into(),
+                domain: Some("acme".into()),
+                paused: false,
+            }
+        );
+        assert!(parse(&[
+            "--from",
+            "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi",
+            "--from-domain",
+            "acme",
+            "--to-id",
+            "copy",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn every_trigger_kind_is_parsed_from_its_flags() {
+        let parse = |args: &[&str]| -> Result<Option<TriggerArg>, CliError> {
+            let matches = crate::cli::app()
+                .try_get_matches_from([&["sablier", "thread", "update", "payouts"], args].concat())
+                .map_err(|err| CliError::BadParameter(err.to_string()))?;
+            match CliCommand::try_from(&matches)? {
+                CliCommand::ThreadUpdate { trigger, .. } => Ok(trigger),
+                command => panic!("unexpected command {:?}", command),
+            }
+        };
+        let feed_id = "ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+        assert_eq!(
+            parse(&["--cron", "0 * * * * *", "--no-skippable"]).unwrap(),
+            Some(TriggerArg::Trigger(Trigger::Cron {
+                schedule: "0 * * * * *".into(),
+                skippable: false,
+            }))
+        );
+        assert_eq!(
+            parse(&["--timestamp", "2030-01-01T00:00:00Z"]).unwrap(),
+            Some(TriggerArg::Trigger(Trigger::Timestamp {
+                unix_ts: 1_893_456_000
+            }))
+        );
+        assert_eq!(
+            parse(&[
+                "--account",
+                "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi",
+                "--offset",
+                "8",
+                "--size",
+                "4"
+            ])
+            .unwrap(),
+            Some(TriggerArg::Trigger(Trigger::Account {
+                address: Pubkey::new_from_array([1; 32]),
+                offset: 8,
+                size: 4,
+            }))
+        );
+        assert_eq!(
+            parse(&["--pyth", feed_id, "--gte", "1.5", "--pyth-exponent", "-8"]).unwrap(),
+            Some(TriggerArg::Trigger(Trigger::Pyth {
+                feed_id: parse_feed_id_hex(feed_id),
+                equality: Equality::GreaterThanOrEqual,
+                limit: 150_000_000,
+            }))
+        );
+        assert_eq!(
+            parse(&["--pyth", feed_id, "--lte", "-2"]).unwrap(),
+            Some(TriggerArg::Pyth {
+                feed_id: parse_feed_id_hex(feed_id),
+                equality: Equality::LessThanOrEqual,
+                price: "-2".into(),
+                exponent: None,
+            })
+        );
+        assert_eq!(
+            parse(&["--periodic", "45", "--no-skippable"]).unwrap(),
+            Some(TriggerArg::Trigger(Trigger::Interval {
+                interval_seconds: 45,
+                skippable: false,
+            }))
+        );
+        assert_eq!(parse(&[]).unwrap(), None);
+        assert!(parse(&["--periodic", "0"]).is_err());
+        assert!(parse(&["--periodic", "5"]).is_err());
+        assert!(parse(&["--now", "--slot", "1"]).is_err());
+        assert!(parse(&["--pyth", feed_id]).is_err());
+        assert!(parse(&["--pyth", feed_id, "--gte", "1", "--lte", "2"]).is_err());
+        assert!(parse(&["--pyth", "0xef0d", "--gte", "1"]).is_err());
+        assert!(parse(&["--pyth", feed_id, "--gte", "1.2.3"]).is_err());
+        assert!(parse(&[
+            "--account",
+            "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi",
+            "--size",
+            "0"
+        ])
+        .is_err());
+        assert!(parse(&["--timestamp", "tomorrow"]).is_err());
+    }
+
+    fn parse_feed_id_hex(hex: &str) -> [u8; 32] {
+        decode_hex(hex).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn prices_are_scaled_by_the_exponent_of_their_feed() {
+        assert_eq!(scale_price("1.5", -8), Some(150_000_000));
+        assert_eq!(scale_price("-0.25", -2), Some(-25));
+        assert_eq!(scale_price("42", 0), Some(42));
+        assert_eq!(scale_price("4200", 2), Some(42));
+        assert_eq!(scale_price("4201", 2), None);
+        assert_eq!(scale_price("0.001", -2), None);
+        assert_eq!(scale_price("100000000000", -10), None);
+    }
+
+    #[test]
+    fn bulk_pauses_and_resumes_are_filtered_by_id() {
+        let parse = |args: &[&str]| {
+            let matches = crate::cli::app()
+                .try_get_matches_from([&["sablier", "thread"], args].concat())
+                .unwrap();
+            CliCommand::try_from(&matches)
+        };
+        assert_eq!(
+            parse(&["pause-all", "--match", "payouts-"]).unwrap(),
+            CliCommand::ThreadPauseAll {
+                pause: true,
+                pattern: Some("payouts-".into()),
+            }
+        );
+        assert_eq!(
+            parse(&["resume-all"]).unwrap(),
+            CliCommand::ThreadPauseAll {
+                pause: false,
+                pattern: None,
+            }
+        );
+    }
+
+    #[test]
+    fn worker_balance_thresholds_are_in_sol() {
+        let parse = |args: &[&str]| {
+            let matches = crate::cli::app()
+                .try_get_matches_from([&["sablier", "worker", "balances"], args].concat())
+                .unwrap();
+            CliCommand::try_from(&matches)
+        };
+        assert_eq!(
+            parse(&["--min-signatory-balance", "0.05", "--json"]).unwrap(),
+            CliCommand::WorkerBalances {
+                min_signatory_balance: Some(50_000_000),
+                json: true,
+            }
+        );
+        assert_eq!(
+            parse(&[]).unwrap(),
+            CliCommand::WorkerBalances {
+                min_signatory_balance: None,
+                json: false,
+            }
+        );
+        assert!(parse(&["--min-signatory-balance", "lots"]).is_err());
+    }
+
+    #[test]
+    fn thread_ids_are_parsed_as_bytes() {
+        let parse = |args: &[&str]| {
+            let matches = crate::cli::app()
+                .try_get_matches_from([&["sablier", "thread", "pubkey"], args].concat())
+                .unwrap();
+            CliCommand::try_from(&matches)
+        };
+        assert_eq!(
+            parse(&["--raw-id-hex", "0xff00", "-d", "acme"]).unwrap(),
+            CliCommand::ThreadPubkey {
+                id: vec![0xff, 0x00],
+                domain: Some("acme".into()),
+            }
+        );
+        assert_eq!(
+            parse(&["--id", "payouts"]).unwrap(),
+            CliCommand::ThreadPubkey {
+                id: b"payouts".to_vec(),
+                domain: None,
+            }
+        );
+        assert!(parse(&["--raw-id-hex", "f"]).is_err());
+    }
+}
@@ -35,17 +35,16 @@ macro_rules! print_error {
 
 /// Print a message with a colored title in the style of Cargo shell messages.
 pub fn print_style<S: AsRef<str> + Display>(status: S, message: S, color: Color, justified: bool) {
+    // The messages are best effort, a closed stderr isn't worth a panic.
     let mut output = StandardStream::stderr(ColorChoice::Auto);
-    output
-        .set_color(ColorSpec::new().set_fg(Some(color)).set_bold(true))
-        .unwrap();
+    let _ = output.set_color(ColorSpec::new().set_fg(Some(color)).set_bold(true));
     if justified {
-        write!(output, "{status:>12}").unwrap();
+        let _ = write!(output, "{status:>12}");
     } else {
-        write!(output, "{status}").unwrap();
-        output.set_color(ColorSpec::new().set_bold(true)).unwrap();
-        write!(output, ":").unwrap();
+        let _ = write!(output, "{status}");
+        let _ = output.set_color(ColorSpec::new().set_bold(true));
+        let _ = write!(output, ":");
     }
-    output.reset().unwrap();
-    writeln!(output, " {message}").unwrap();
+    let _ = output.reset();
+    let _ = writeln!(output, " {message}");
 }
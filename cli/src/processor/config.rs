@@ -1,16 +1,28 @@
 use anchor_lang::{
-    solana_program::{instruction::Instruction, pubkey::Pubkey},
+    solana_program::{instruction::Instruction, program_pack::Pack, pubkey::Pubkey},
     InstructionData, ToAccountMetas,
 };
 use sablier_network_program::state::{Config, ConfigSettings};
+use spl_token::state::Mint;
 
-use crate::{client::Client, errors::CliError};
+use crate::{
+    client::Client,
+    errors::CliError,
+    output::{print_json, ConfigOutput, Linked, OutputFormat},
+};
+
+use super::{confirm, fetch_account, fetch_config, Confirmation};
 
-pub fn get(client: &Client) -> Result<(), CliError> {
-    let config = client
-        .get::<Config>(&Config::pubkey())
-        .map_err(|_err| CliError::AccountNotFound(Config::pubkey().to_string()))?;
-    println!("{:#?}", config);
+pub fn get(client: &Client, output: OutputFormat) -> Result<(), CliError> {
+    let config = fetch_config(client)?;
+    let explorer_url = client.explorer().account_url(Config::pubkey());
+    match output {
+        OutputFormat::Text => println!("{:#?}\nExplorer: {}", config, explorer_url),
+        OutputFormat::Json => print_json(&Linked::new(
+            ConfigOutput::new(Config::pubkey(), &config),
+            explorer_url,
+        ))?,
+    }
     Ok(())
 }
 
@@ -19,20 +31,82 @@ pub fn set(
     admin: Option<Pubkey>,
     epoch_thread: Option<Pubkey>,
     hasher_thread: Option<Pubkey>,
+    mint: Option<Pubkey>,
+    yes: bool,
+    output: OutputFormat,
 ) -> Result<(), CliError> {
     // Get the current config.
-    let config = client
-        .get::<Config>(&Config::pubkey())
-        .map_err(|_err| CliError::AccountNotFound(Config::pubkey().to_string()))?;
+    let config = fetch_config(client)?;
+    if config.admin != client.payer_pubkey() {
+        return Err(CliError::BadParameter(format!(
+            "the config can only be set by its admin {}, not the payer {}",
+            config.admin,
+            client.payer_pubkey()
+        )));
+    }
+
+    // Validate the new settings before building the instruction.
+    if let Some(admin) = admin {
+        // The admin signs the config updates, so it must be a wallet rather than a program address.
+        if !admin.is_on_curve() {
+            return Err(CliError::BadParameter(format!(
+                "admin: {} is off curve, it could never sign",
+                admin
+            )));
+        }
+        eprintln!(
+            "Warning: transferring the admin to {} is irreversible, only it can transfer the admin back",
+            admin
+        );
+    }
+    for (arg, thread) in [
+        ("epoch_thread", epoch_thread),
+        ("hasher_thread", hasher_thread),
+    ] {
+        if let Some(thread) = thread {
+            validate_owner(client, arg, thread, &sablier_thread_program::ID)?;
+        }
+    }
+    if let Some(mint) = mint {
+        let data = validate_owner(client, "mint", mint, &spl_token::ID)?;
+        Mint::unpack(&data)
+            .map_err(|_err| CliError::BadParameter(format!("mint: {} is not a mint", mint)))?;
+    }
 
     // Build new config. settings
     let settings = ConfigSettings {
         admin: admin.unwrap_or(config.admin),
         epoch_thread: epoch_thread.unwrap_or(config.epoch_thread),
         hasher_thread: hasher_thread.unwrap_or(config.hasher_thread),
-        mint: config.mint,
+        mint: mint.unwrap_or(config.mint),
     };
 
+    confirm(
+        "update the config",
+        &format!(
+            "Address: {}\n{:#?}\nThe config is updated to:\n{}",
+            Config::pubkey(),
+            config,
+            [
+                ("admin", config.admin, settings.admin),
+                ("epoch_thread", config.epoch_thread, settings.epoch_thread),
+                (
+                    "hasher_thread",
+                    config.hasher_thread,
+                    settings.hasher_thread
+                ),
+                ("mint", config.mint, settings.mint),
+            ]
+            .iter()
+            .filter(|(_, current, new)| current != new)
+            .map(|(field, current, new)| format!("  {}: {} -> {}", field, current, new))
+            .collect::<Vec<_>>()
+            .join("\n")
+        ),
+        Confirmation::YesNo,
+        yes,
+    )?;
+
     // Submit tx
     let ix = Instruction {
         program_id: sablier_network_program::ID,
@@ -43,7 +117,24 @@ pub fn set(
         .to_account_metas(Some(false)),
         data: sablier_network_program::instruction::ConfigUpdate { settings }.data(),
     };
-    client.send_and_confirm(&[ix], &[client.payer()]).unwrap();
-    get(client)?;
+    client.send_and_confirm(&[ix], &[client.payer()])?;
+    get(client, output)?;
     Ok(())
 }
+
+/// Checks the account exists and is owned by the program, and returns its data.
+fn validate_owner(
+    client: &Client,
+    arg: &'static str,
+    address: Pubkey,
+    owner: &Pubkey,
+) -> Result<Vec<u8>, CliError> {
+    let account = fetch_account(client, address, arg)?;
+    if account.owner != *owner {
+        return Err(CliError::BadParameter(format!(
+            "{}: {} is owned by {}, not {}",
+            arg, address, account.owner, owner
+        )));
+    }
+    Ok(account.data)
+}
@@ -1,19 +1,38 @@
 use chrono::DateTime;
-use sablier_cron::Schedule;
+use chrono_tz::Tz;
+use sablier_utils::cron::TimezoneSchedule;
 use std::str::FromStr;
 
 use crate::{client::Client, errors::CliError};
 
-pub fn get(client: &Client, schedule: String) -> Result<(), CliError> {
-    let clock = client.get_clock().unwrap();
-    let schedule = Schedule::from_str(schedule.as_str()).unwrap();
+pub fn get(
+    client: &Client,
+    schedule: String,
+    count: usize,
+    timezone: Option<Tz>,
+    after: Option<i64>,
+) -> Result<(), CliError> {
+    let schedule = TimezoneSchedule::from_str(schedule.as_str())
+        .map_err(|err| CliError::BadParameter(format!("schedule: {}", err)))?;
+    let after = match after {
+        Some(after) => after,
+        None => client.get_clock()?.unix_timestamp,
+    };
 
-    let mut i = 0;
-    for t in schedule.after(&DateTime::from_timestamp(clock.unix_timestamp, 0).unwrap()) {
-        println!("{:#?}", t);
-        i += 1;
-        if i > 8 {
+    // The moments are printed in the timezone of the schedule, unless another one is requested.
+    let timezone = timezone.or(schedule.timezone);
+    for timestamp in schedule.upcoming(after, count) {
+        let Some(t) = DateTime::from_timestamp(timestamp, 0) else {
             break;
+        };
+        match timezone {
+            None => println!("{}", t.to_rfc3339()),
+            Some(timezone) => println!(
+                "{} ({} {})",
+                t.to_rfc3339(),
+                t.with_timezone(&timezone).to_rfc3339(),
+                timezone
+            ),
         }
     }
     Ok(())
@@ -1,28 +1,29 @@
 use anchor_lang::{
     solana_program::{instruction::Instruction, system_program},
-    AccountDeserialize, InstructionData, ToAccountMetas,
+    InstructionData, ToAccountMetas,
 };
 use sablier_network_program::state::{Config, Delegation, Worker};
-use spl_associated_token_account::get_associated_token_address;
+use solana_sdk::{native_token::lamports_to_sol, pubkey::Pubkey};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use spl_token::amount_to_ui_amount_string_trimmed;
+
+use crate::{
+    client::Client,
+    errors::CliError,
+    output::{print_json, DelegationOutput, Linked, OutputFormat},
+    parser::ui_amount_to_base_units,
+};
 
-use crate::{client::Client, errors::CliError};
+use super::{fetch_account, fetch_config, fetch_state, fetch_worker};
 
 pub fn create(client: &Client, worker_id: u64) -> Result<(), CliError> {
-    // Get config data
-    let config_pubkey = Config::pubkey();
-    let config_data = client
-        .get_account_data(&config_pubkey)
-        .map_err(|_err| CliError::AccountNotFound(config_pubkey.to_string()))?;
-    let config = Config::try_deserialize(&mut config_data.as_slice())
-        .map_err(|_err| CliError::AccountDataNotParsable(config_pubkey.to_string()))?;
+    let config = fetch_config(client)?;
 
     // Get worker
     let worker_pubkey = Worker::pubkey(worker_id);
-    let worker_data = client
-        .get_account_data(&worker_pubkey)
-        .map_err(|_err| CliError::AccountNotFound(worker_pubkey.to_string()))?;
-    let worker = Worker::try_deserialize(&mut worker_data.as_slice())
-        .map_err(|_err| CliError::AccountDataNotParsable(worker_pubkey.to_string()))?;
+    let worker = fetch_worker(client, worker_pubkey)?;
 
     // Build ix
     let delegation_pubkey = Delegation::pubkey(worker_pubkey, worker.total_delegations);
@@ -42,26 +43,34 @@ pub fn create(client: &Client, worker_id: u64) -> Result<(), CliError> {
         .to_account_metas(Some(false)),
         data: sablier_network_program::instruction::DelegationCreate {}.data(),
     };
-    client.send_and_confirm(&[ix], &[client.payer()]).unwrap();
+    client.send_and_confirm(&[ix], &[client.payer()])?;
+    println!(
+        "Delegation id: {}\nDelegation: {}\nExplorer: {}",
+        worker.total_delegations,
+        delegation_pubkey,
+        client.explorer().account_url(delegation_pubkey)
+    );
 
     Ok(())
 }
 
 pub fn deposit(
     client: &Client,
-    amount: u64,
+    amount: String,
     delegation_id: u64,
     worker_id: u64,
 ) -> Result<(), CliError> {
-    // Get config data
-    let config_pubkey = Config::pubkey();
-    let config_data = client
-        .get_account_data(&config_pubkey)
-        .map_err(|_err| CliError::AccountNotFound(config_pubkey.to_string()))?;
-    let config = Config::try_deserialize(&mut config_data.as_slice())
-        .map_err(|_err| CliError::AccountDataNotParsable(config_pubkey.to_string()))?;
-
-    // TODO Map the amount using the mint's decimals.
+    let config = fetch_config(client)?;
+    let amount = parse_token_amount(client, &amount, config.mint)?;
+
+    // The deposited tokens come from the payer's token account, which must hold them.
+    let authority_tokens = get_associated_token_address(&client.payer_pubkey(), &config.mint);
+    fetch_account(client, authority_tokens, "token").map_err(|err| {
+        err.with_hint(format!(
+            "the payer has no token account for the mint {}",
+            config.mint
+        ))
+    })?;
 
     // Build ix
     let worker_pubkey = Worker::pubkey(worker_id);
@@ -70,7 +79,7 @@ pub fn deposit(
         program_id: sablier_network_program::ID,
         accounts: sablier_network_program::accounts::DelegationDeposit {
             authority: client.payer_pubkey(),
-            authority_tokens: get_associated_token_address(&client.payer_pubkey(), &config.mint),
+            authority_tokens,
             config: Config::pubkey(),
             delegation: delegation_pubkey,
             delegation_tokens: get_associated_token_address(&delegation_pubkey, &config.mint),
@@ -79,35 +88,40 @@ pub fn deposit(
         .to_account_metas(Some(false)),
         data: sablier_network_program::instruction::DelegationDeposit { amount }.data(),
     };
-    client.send_and_confirm(&[ix], &[client.payer()]).unwrap();
+    client.send_and_confirm(&[ix], &[client.payer()])?;
 
     Ok(())
 }
 
 pub fn withdraw(
     client: &Client,
-    amount: u64,
+    amount: String,
     delegation_id: u64,
     worker_id: u64,
 ) -> Result<(), CliError> {
-    // Get config data
-    let config_pubkey = Config::pubkey();
-    let config_data = client
-        .get_account_data(&config_pubkey)
-        .map_err(|_err| CliError::AccountNotFound(config_pubkey.to_string()))?;
-    let config = Config::try_deserialize(&mut config_data.as_slice())
-        .map_err(|_err| CliError::AccountDataNotParsable(config_pubkey.to_string()))?;
-
-    // TODO Map the amount using the mint's decimals.
+    let config = fetch_config(client)?;
+    let amount = parse_token_amount(client, &amount, config.mint)?;
+
+    // The withdrawn tokens go to the payer's token account, created if it is missing.
+    let authority_tokens = get_associated_token_address(&client.payer_pubkey(), &config.mint);
+    let mut ixs = vec![];
+    if client.get_account(&authority_tokens).is_err() {
+        ixs.push(create_associated_token_account_idempotent(
+            &client.payer_pubkey(),
+            &client.payer_pubkey(),
+            &config.mint,
+            &anchor_spl::token::ID,
+        ));
+    }
 
     // Build ix
     let worker_pubkey = Worker::pubkey(worker_id);
     let delegation_pubkey = Delegation::pubkey(worker_pubkey, delegation_id);
-    let ix = Instruction {
+    ixs.push(Instruction {
         program_id: sablier_network_program::ID,
         accounts: sablier_network_program::accounts::DelegationWithdraw {
             authority: client.payer_pubkey(),
-            authority_tokens: get_associated_token_address(&client.payer_pubkey(), &config.mint),
+            authority_tokens,
             config: Config::pubkey(),
             delegation: delegation_pubkey,
             delegation_tokens: get_associated_token_address(&delegation_pubkey, &config.mint),
@@ -115,40 +129,63 @@ pub fn withdraw(
         }
         .to_account_metas(Some(false)),
         data: sablier_network_program::instruction::DelegationWithdraw { amount }.data(),
-    };
-    client.send_and_confirm(&[ix], &[client.payer()]).unwrap();
+    });
+    client.send_and_confirm(&ixs, &[client.payer()])?;
 
     Ok(())
 }
 
-pub fn get(client: &Client, delegation_id: u64, worker_id: u64) -> Result<(), CliError> {
-    // Get config account
-    let config_pubkey = Config::pubkey();
-    let config_data = client
-        .get_account_data(&config_pubkey)
-        .map_err(|_err| CliError::AccountNotFound(config_pubkey.to_string()))?;
-    let config = Config::try_deserialize(&mut config_data.as_slice())
-        .map_err(|_err| CliError::AccountDataNotParsable(config_pubkey.to_string()))?;
+pub fn get(
+    client: &Client,
+    delegation_id: u64,
+    worker_id: u64,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let config = fetch_config(client)?;
 
     // Get the delegation account.
     let worker_pubkey = Worker::pubkey(worker_id);
     let delegation_pubkey = Delegation::pubkey(worker_pubkey, delegation_id);
-    let delegation_data = client
-        .get_account_data(&delegation_pubkey)
-        .map_err(|_err| CliError::AccountNotFound(delegation_pubkey.to_string()))?;
-    let delegation = Delegation::try_deserialize(&mut delegation_data.as_slice())
-        .map_err(|_err| CliError::AccountDataNotParsable(delegation_pubkey.to_string()))?;
+    let delegation = fetch_state::<Delegation>(client, delegation_pubkey, "delegation")
+        .map_err(|err| err.with_hint("create it with `sablier delegation create`"))?;
 
     // Get the delegation's token account.
     let delegation_tokens_pubkey = get_associated_token_address(&delegation_pubkey, &config.mint);
-    let token_balance = client
-        .get_token_account_balance(&delegation_tokens_pubkey)
-        .map_err(|_err| CliError::AccountDataNotParsable(delegation_pubkey.to_string()))?;
-
+    let token_balance = client.get_token_account_balance(&delegation_tokens_pubkey)?;
+
+    let explorer_url = client.explorer().account_url(delegation_pubkey);
+    if output == OutputFormat::Json {
+        return print_json(&Linked::new(
+            DelegationOutput::new(
+                delegation_pubkey,
+                &delegation,
+                worker_id,
+                token_balance.decimals,
+                token_balance.ui_amount_string,
+            ),
+            explorer_url,
+        ));
+    }
     println!(
-        "Address: {}\n{:#?}\nLiquid balance: {}",
-        delegation_pubkey, delegation, token_balance.ui_amount_string
+        "Address: {}\nExplorer: {}\nWorker: {} ({})\nAuthority: {}\nStake: {}\nLiquid balance: {}\nYield: {} SOL",
+        delegation_pubkey,
+        explorer_url,
+        worker_id,
+        delegation.worker,
+        delegation.authority,
+        amount_to_ui_amount_string_trimmed(delegation.stake_amount, token_balance.decimals),
+        token_balance.ui_amount_string,
+        lamports_to_sol(delegation.yield_balance)
     );
 
     Ok(())
 }
+
+/// Converts an amount of tokens to the base units of the mint, with its decimals fetched from the cluster.
+fn parse_token_amount(client: &Client, amount: &str, mint: Pubkey) -> Result<u64, CliError> {
+    let decimals = client.get_token_supply(&mint)?.decimals;
+    ui_amount_to_base_units(amount, decimals).ok_or(CliError::BadParameter(format!(
+        "amount: {} is not an amount of the mint {} with {} decimals",
+        amount, mint, decimals
+    )))
+}
@@ -37,9 +37,7 @@ pub fn initialize(client: &Client, mint: Pubkey) -> Result<(), CliError> {
     };
 
     // Submit tx
-    client
-        .send_and_confirm(&[ix_a, ix_b], &[client.payer()])
-        .unwrap();
+    client.send_and_confirm(&[ix_a, ix_b], &[client.payer()])?;
 
     Ok(())
 }
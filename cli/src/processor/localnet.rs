@@ -1,22 +1,30 @@
 #[allow(deprecated)]
 use {
     crate::{
-        client::Client, config::CliConfig, deps, errors::CliError, parser::ProgramInfo,
-        print::print_style, print_status,
+        client::Client, config::CliConfig, deps, errors::CliError, output::OutputFormat,
+        parser::ProgramInfo, print::print_style, print_status,
     },
     anchor_lang::{
         solana_program::{instruction::Instruction, system_program},
         InstructionData, ToAccountMetas,
     },
-    anyhow::{Context, Result},
+    anyhow::{anyhow, bail, Context, Result},
     clap::crate_version,
-    sablier_network_program::state::{Config, ConfigSettings, Registry},
+    sablier_network_program::state::{
+        Config, ConfigSettings, Delegation, Fee, Pool, Registry, Worker,
+    },
     sablier_thread_program::state::{Thread, Trigger},
+    solana_account_decoder::{UiAccount, UiAccountEncoding},
+    solana_cli_config::ConfigInput,
+    solana_client::rpc_client::RpcClient,
     solana_sdk::{
+        account::Account,
+        bpf_loader, bpf_loader_deprecated,
+        bpf_loader_upgradeable::{self, UpgradeableLoaderState},
         native_token::LAMPORTS_PER_SOL,
         program_pack::Pack,
         pubkey::Pubkey,
-        signature::{read_keypair_file, Keypair, Signer},
+        signature::{read_keypair_file, write_keypair_file, Keypair, Signer},
         system_instruction,
     },
     spl_associated_token_account::{create_associated_token_account, get_associated_token_address},
@@ -25,32 +33,49 @@ use {
         state::Mint,
     },
     std::fs,
+    std::path::{Path, PathBuf},
     std::process::{Child, Command},
 };
 
+use super::fetch_optional_state;
+
+fn account_exists(client: &Client, pubkey: Pubkey) -> Result<bool, CliError> {
+    Ok(client
+        .get_account_with_commitment(&pubkey, client.commitment())?
+        .value
+        .is_some())
+}
+
 pub struct LocalnetStartArgs<'a> {
     pub config: &'a mut CliConfig,
     pub client: &'a Client,
     pub clone_addresses: Vec<Pubkey>,
+    pub clone_programs: Vec<Pubkey>,
     pub network_url: Option<String>,
     pub program_infos: Vec<ProgramInfo>,
     pub force_init: bool,
     pub solana_archive: Option<String>,
     pub sablier_archive: Option<String>,
     pub dev: bool,
+    pub keep_ledger: bool,
 }
 
+/// The id of the worker registered with the localnet signatory, the first one of the network.
+const LOCALNET_WORKER_ID: u64 = 0;
+
 pub fn start(
     LocalnetStartArgs {
         config,
         client,
         clone_addresses,
+        clone_programs,
         network_url,
-        program_infos,
+        mut program_infos,
         force_init,
         solana_archive,
         sablier_archive,
         dev,
+        keep_ledger,
     }: LocalnetStartArgs<'_>,
 ) -> Result<(), CliError> {
     config.dev = dev;
@@ -63,22 +88,39 @@ pub fn start(
     )
     .map_err(|err| CliError::FailedLocalnet(err.to_string()))?;
 
+    // Generate the signatory of the local worker, reused by the next runs
+    let signatory = load_or_create_signatory(config)
+        .map_err(|err| CliError::FailedLocalnet(err.to_string()))?;
+
     // Create Geyser Plugin Config file
     create_geyser_plugin_config(config).map_err(|err| CliError::FailedLocalnet(err.to_string()))?;
 
+    // Clone the accounts and programs from the cluster
+    let cloned_accounts = clone_from_cluster(
+        network_url,
+        clone_addresses,
+        clone_programs,
+        &mut program_infos,
+    )
+    .map_err(|err| CliError::FailedLocalnet(err.to_string()))?;
+
     // Start the validator
     let validator_process =
-        &mut start_test_validator(config, client, program_infos, network_url, clone_addresses)
+        &mut start_test_validator(config, client, program_infos, cloned_accounts, keep_ledger)
             .map_err(|err| CliError::FailedLocalnet(err.to_string()))?;
 
-    // Initialize Sablier
-    let mint_pubkey =
-        mint_sablier_token(client).map_err(|err| CliError::FailedTransaction(err.to_string()))?;
-    super::initialize::initialize(client, mint_pubkey)
-        .map_err(|err| CliError::FailedTransaction(err.to_string()))?;
-    register_worker(client, config).map_err(|err| CliError::FailedTransaction(err.to_string()))?;
-    create_threads(client, mint_pubkey)
-        .map_err(|err| CliError::FailedTransaction(err.to_string()))?;
+    // Initialize Sablier, skipping what a kept ledger was already bootstrapped with
+    let mint_pubkey = initialize_network(client).map_err(CliError::transaction_failed)?;
+    register_worker(client, &signatory).map_err(CliError::transaction_failed)?;
+    let (epoch_thread_pubkey, hasher_thread_pubkey) =
+        create_threads(client, mint_pubkey).map_err(CliError::transaction_failed)?;
+    print_summary(
+        config,
+        mint_pubkey,
+        &signatory,
+        epoch_thread_pubkey,
+        hasher_thread_pubkey,
+    );
 
     // Wait for process to be killed.
     _ = validator_process.wait();
@@ -86,6 +128,31 @@ pub fn start(
     Ok(())
 }
 
+fn load_or_create_signatory(config: &CliConfig) -> Result<Keypair> {
+    let path = config.signatory();
+    if Path::new(&path).exists() {
+        return read_keypair_file(&path)
+            .map_err(|err| anyhow!("Unable to read keypair {}: {}", path, err));
+    }
+    if let Some(dir) = Path::new(&path).parent() {
+        fs::create_dir_all(dir).context(format!("Unable to create {}", dir.display()))?;
+    }
+    let signatory = Keypair::new();
+    write_keypair_file(&signatory, &path)
+        .map_err(|err| anyhow!("Unable to write keypair {}: {}", path, err))?;
+    Ok(signatory)
+}
+
+/// Initializes the network with a new mint, or returns the mint of the network already initialized.
+fn initialize_network(client: &Client) -> Result<Pubkey> {
+    if let Some(config) = fetch_optional_state::<Config>(client, Config::pubkey(), "config")? {
+        return Ok(config.mint);
+    }
+    let mint_pubkey = mint_sablier_token(client)?;
+    super::initialize::initialize(client, mint_pubkey).context("initialize failed")?;
+    Ok(mint_pubkey)
+}
+
 fn mint_sablier_token(client: &Client) -> Result<Pubkey> {
     // Calculate rent and pubkeys
     let mint_keypair = Keypair::new();
@@ -140,28 +207,69 @@ fn mint_sablier_token(client: &Client) -> Result<Pubkey> {
     Ok(mint_keypair.pubkey())
 }
 
-fn register_worker(client: &Client, config: &CliConfig) -> Result<()> {
-    // Create the worker
-    let signatory = read_keypair_file(&config.signatory()).map_err(|err| {
-        CliError::FailedLocalnet(format!(
-            "Unable to read keypair {}: {}",
-            &config.signatory(),
-            err
-        ))
-    })?;
+fn register_worker(client: &Client, signatory: &Keypair) -> Result<()> {
+    // Fund the signatory, which pays for the transactions of the worker
+    let balance = client
+        .get_balance(&signatory.pubkey())
+        .context("Unable to get the balance of the signatory")?;
+    if balance < LAMPORTS_PER_SOL {
+        client
+            .airdrop(&signatory.pubkey(), LAMPORTS_PER_SOL)
+            .context("airdrop to signatory failed")?;
+    }
 
-    client
-        .airdrop(&signatory.pubkey(), LAMPORTS_PER_SOL)
-        .context("airdrop to signatory failed")?;
-    super::worker::create(client, signatory, true).context("worker::create failed")?;
+    // Create the worker
+    let worker_pubkey = Worker::pubkey(LOCALNET_WORKER_ID);
+    match fetch_optional_state::<Worker>(client, worker_pubkey, "worker")? {
+        Some(worker) if worker.signatory != signatory.pubkey() => bail!(
+            "worker {} is signed for by {}, not by the localnet signatory {}, reset the ledger",
+            LOCALNET_WORKER_ID,
+            worker.signatory,
+            signatory.pubkey()
+        ),
+        Some(_worker) => {}
+        None => {
+            super::worker::create(client, signatory.insecure_clone(), true, OutputFormat::Text)
+                .context("worker::create failed")?;
+        }
+    }
 
     // Delegate stake to the worker
-    super::delegation::create(client, 0).context("delegation::create failed")?;
-    super::delegation::deposit(client, 100000000, 0, 0).context("delegation::deposit failed")?;
+    if !account_exists(client, Delegation::pubkey(worker_pubkey, 0))? {
+        super::delegation::create(client, LOCALNET_WORKER_ID)
+            .context("delegation::create failed")?;
+        super::delegation::deposit(client, "1".to_string(), 0, LOCALNET_WORKER_ID)
+            .context("delegation::deposit failed")?;
+    }
     Ok(())
 }
 
-fn create_threads(client: &Client, mint_pubkey: Pubkey) -> Result<()> {
+fn print_summary(
+    config: &CliConfig,
+    mint_pubkey: Pubkey,
+    signatory: &Keypair,
+    epoch_thread_pubkey: Pubkey,
+    hasher_thread_pubkey: Pubkey,
+) {
+    let worker_pubkey = Worker::pubkey(LOCALNET_WORKER_ID);
+    print_status!("Ready", "Sablier localnet bootstrapped\n");
+    println!("Mint: {}", mint_pubkey);
+    println!("Config: {}", Config::pubkey());
+    println!("Registry: {}", Registry::pubkey());
+    println!("Pool: {}", Pool::pubkey(0));
+    println!("Worker {}: {}", LOCALNET_WORKER_ID, worker_pubkey);
+    println!("Fee account: {}", Fee::pubkey(worker_pubkey));
+    println!("Delegation: {}", Delegation::pubkey(worker_pubkey, 0));
+    println!("Signatory: {} ({})", signatory.pubkey(), config.signatory());
+    println!("Epoch thread: {}", epoch_thread_pubkey);
+    println!("Hasher thread: {}", hasher_thread_pubkey);
+    println!("Plugin config: {}", config.geyser_config());
+    println!("Plugin library: {}", config.geyser_lib());
+    println!("Ledger: {}", config.ledger());
+}
+
+/// Creates the network threads missing from the ledger, and returns the epoch and the hasher threads.
+fn create_threads(client: &Client, mint_pubkey: Pubkey) -> Result<(Pubkey, Pubkey)> {
     // Create epoch thread.
     let epoch_thread_id = "sablier.network.epoch";
     let epoch_thread_pubkey = Thread::pubkey(client.payer_pubkey(), epoch_thread_id.into(), None);
@@ -306,23 +414,31 @@ fn create_threads(client: &Client, mint_pubkey: Pubkey) -> Result<()> {
         data: sablier_network_program::instruction::ConfigUpdate { settings }.data(),
     };
 
+    if !account_exists(client, epoch_thread_pubkey)? {
+        client
+            .send_and_confirm(&[ix_a], &[client.payer()])
+            .context(format!(
+                "Failed to create thread: {} or update config",
+                epoch_thread_id,
+            ))?;
+    }
+    let mut ixs = vec![];
+    if !account_exists(client, hasher_thread_pubkey)? {
+        ixs.push(ix_b);
+    }
+    ixs.push(ix_c);
     client
-        .send_and_confirm(&[ix_a], &[client.payer()])
-        .context(format!(
-            "Failed to create thread: {} or update config",
-            epoch_thread_id,
-        ))?;
-    client
-        .send_and_confirm(&[ix_b, ix_c], &[client.payer()])
+        .send_and_confirm(&ixs, &[client.payer()])
         .context(format!("Failed to create thread: {}", hasher_thread_id))?;
 
-    Ok(())
+    Ok((epoch_thread_pubkey, hasher_thread_pubkey))
 }
 
 fn create_geyser_plugin_config(config: &CliConfig) -> Result<()> {
     let geyser_config = sablier_plugin_utils::PluginConfig {
         keypath: Some(config.signatory().to_owned()),
         libpath: Some(config.geyser_lib().to_owned()),
+        worker_id: LOCALNET_WORKER_ID,
         ..Default::default()
     };
 
@@ -333,20 +449,105 @@ fn create_geyser_plugin_config(config: &CliConfig) -> Result<()> {
     Ok(())
 }
 
+/// Fetches the accounts and programs from the cluster, and writes them to the clones directory.
+/// Returns the account files, the program files are added to the programs.
+fn clone_from_cluster(
+    network_url: Option<String>,
+    clone_addresses: Vec<Pubkey>,
+    clone_programs: Vec<Pubkey>,
+    program_infos: &mut Vec<ProgramInfo>,
+) -> Result<Vec<(Pubkey, PathBuf)>> {
+    if clone_addresses.is_empty() && clone_programs.is_empty() {
+        return Ok(vec![]);
+    }
+    let network_url = network_url.context("--url is required to clone accounts")?;
+    let (_, network_url) = ConfigInput::compute_json_rpc_url_setting(&network_url, "");
+    let rpc_client = RpcClient::new(network_url.clone());
+    let fetch = |address: &Pubkey| {
+        rpc_client
+            .get_account(address)
+            .context(format!("Failed to clone {} from {}", address, network_url))
+    };
+
+    let dir = CliConfig::clones_dir();
+    if dir.exists() {
+        fs::remove_dir_all(&dir).context(format!("Unable to clear {}", dir.display()))?;
+    }
+    fs::create_dir_all(&dir).context(format!("Unable to create {}", dir.display()))?;
+
+    let mut cloned_accounts = vec![];
+    for address in clone_addresses {
+        let account = fetch(&address)?;
+        let path = dir.join(format!("{}.json", address));
+        write_account_file(&path, &address, &account)?;
+        cloned_accounts.push((address, path));
+    }
+
+    for program_id in clone_programs {
+        let account = fetch(&program_id)?;
+        let program = if bpf_loader_upgradeable::check_id(&account.owner) {
+            // The executable of an upgradeable program is in its program data account.
+            let Ok(UpgradeableLoaderState::Program {
+                programdata_address,
+            }) = bincode::deserialize(&account.data)
+            else {
+                bail!("{} is not an upgradeable program", program_id);
+            };
+            let programdata = fetch(&programdata_address)?;
+            programdata
+                .data
+                .get(UpgradeableLoaderState::size_of_programdata_metadata()..)
+                .context(format!("{} has no program data", programdata_address))?
+                .to_vec()
+        } else if bpf_loader::check_id(&account.owner)
+            || bpf_loader_deprecated::check_id(&account.owner)
+        {
+            account.data
+        } else {
+            bail!(
+                "{} is not a program, it is owned by {}",
+                program_id,
+                account.owner
+            );
+        };
+        let path = dir.join(format!("{}.so", program_id));
+        fs::write(&path, program).context(format!("Unable to write {}", path.display()))?;
+        program_infos.push(ProgramInfo {
+            program_id,
+            program_path: path,
+        });
+    }
+
+    Ok(cloned_accounts)
+}
+
+/// Writes the account in the JSON format of `solana account --output json`, which the validator loads.
+fn write_account_file(path: &Path, address: &Pubkey, account: &Account) -> Result<()> {
+    let content = serde_json::to_string_pretty(&serde_json::json!({
+        "pubkey": address.to_string(),
+        "account": UiAccount::encode(address, account, UiAccountEncoding::Base64, None, None),
+    }))
+    .context(format!("Unable to serialize the account {}", address))?;
+    fs::write(path, content).context(format!("Unable to write {}", path.display()))
+}
+
 fn start_test_validator(
     config: &CliConfig,
     client: &Client,
     program_infos: Vec<ProgramInfo>,
-    network_url: Option<String>,
-    clone_addresses: Vec<Pubkey>,
+    cloned_accounts: Vec<(Pubkey, PathBuf)>,
+    keep_ledger: bool,
 ) -> Result<Child> {
     let path = config.active_runtime("solana-test-validator").to_owned();
     let cmd = &mut Command::new(path);
-    cmd.arg("-r")
+    if !keep_ledger {
+        cmd.arg("-r");
+    }
+    cmd.arg("--ledger")
+        .arg(config.ledger())
         .bpf_program(config, sablier_network_program::ID, "network")
         .bpf_program(config, sablier_thread_program::ID, "thread")
-        .network_url(network_url)
-        .clone_addresses(clone_addresses)
+        .accounts(cloned_accounts)
         .add_programs_with_path(program_infos)
         .geyser_plugin_config(config);
 
@@ -392,8 +593,7 @@ trait TestValidatorHelpers {
         program_name: &str,
     ) -> &mut Command;
     fn geyser_plugin_config(&mut self, config: &CliConfig) -> &mut Command;
-    fn network_url(&mut self, url: Option<String>) -> &mut Command;
-    fn clone_addresses(&mut self, clone_addresses: Vec<Pubkey>) -> &mut Command;
+    fn accounts(&mut self, accounts: Vec<(Pubkey, PathBuf)>) -> &mut Command;
 }
 
 impl TestValidatorHelpers for Command {
@@ -423,17 +623,37 @@ impl TestValidatorHelpers for Command {
             .arg(config.geyser_config())
     }
 
-    fn network_url(&mut self, url: Option<String>) -> &mut Command {
-        if let Some(url) = url {
-            self.arg("--url").arg(url);
+    fn accounts(&mut self, accounts: Vec<(Pubkey, PathBuf)>) -> &mut Command {
+        for (address, path) in accounts {
+            self.arg("--account").arg(address.to_string()).arg(path);
         }
         self
     }
+}
 
-    fn clone_addresses(&mut self, clone_addresses: Vec<Pubkey>) -> &mut Command {
-        for clone_address in clone_addresses {
-            self.arg("--clone").arg(clone_address.to_string());
-        }
-        self
+#[cfg(test)]
+mod tests {
+    use solana_client::rpc_response::RpcKeyedAccount;
+
+    use super::*;
+
+    #[test]
+    fn cloned_accounts_are_written_in_the_validator_format() {
+        let address = Pubkey::new_unique();
+        let account = Account {
+            lamports: 42,
+            data: vec![1, 2, 3],
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 7,
+        };
+        let path = std::env::temp_dir().join(format!("{}.json", address));
+        write_account_file(&path, &address, &account).unwrap();
+
+        let keyed_account: RpcKeyedAccount =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(keyed_account.pubkey, address.to_string());
+        assert_eq!(keyed_account.account.decode::<Account>(), Some(account));
     }
 }
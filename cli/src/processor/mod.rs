@@ -7,19 +7,158 @@ mod localnet;
 mod pool;
 mod registry;
 mod thread;
+mod version;
 mod worker;
 
-use anyhow::Result;
+use std::{
+    io::{self, BufRead, IsTerminal, Write},
+    str::FromStr,
+};
+
+use anchor_lang::AccountDeserialize;
+use anyhow::{Context, Result};
 use clap::ArgMatches;
-use solana_sdk::signature::read_keypair_file;
+use sablier_network_program::state::{Config, Registry, Worker};
+use sablier_thread_program::state::ThreadSettings;
+use sablier_utils::explorer::ExplorerSite;
+use solana_sdk::{
+    account::Account, commitment_config::CommitmentLevel, pubkey::Pubkey, signature::Signer,
+};
 
 use crate::{
-    cli::CliCommand, client::Client, config::CliConfig, errors::CliError,
+    account_file::DecodeSource,
+    cli::CliCommand,
+    client::{Client, ComputeBudget, PriorityFee},
+    config::{CliConfig, ClusterArgs, RPC_URL_ENV},
+    errors::CliError,
+    output::OutputFormat,
+    print::print_style,
+    print_note,
     processor::thread::parse_pubkey_from_id_or_address,
+    signer::{signer_from_path, LabeledSigner},
+    transaction::{DumpEncoding, Submitter},
 };
 
 use self::localnet::LocalnetStartArgs;
 
+/// Resolves the payer from the keypair path of the Solana config, a keypair file or a signer URI like `usb://ledger`.
+fn read_payer(config: &CliConfig) -> Result<LabeledSigner, CliError> {
+    signer_from_path(&config.keypair_path, "payer")
+}
+
+/// The hint of the missing accounts created with the network by `sablier initialize`.
+const NETWORK_HINT: &str =
+    "is the Sablier network initialized on this cluster? Check the cluster of --url, SABLIER_RPC_URL or the Solana config";
+
+/// Fetches an account, a missing account is told apart from a failed RPC request.
+fn fetch_account(
+    client: &Client,
+    pubkey: Pubkey,
+    account: &'static str,
+) -> Result<Account, CliError> {
+    client
+        .get_account_with_commitment(&pubkey, client.commitment())?
+        .value
+        .ok_or(CliError::account_not_found(account, pubkey))
+}
+
+/// Fetches and deserializes an account, the data of another type is named by its discriminator.
+fn fetch_state<T: AccountDeserialize>(
+    client: &Client,
+    pubkey: Pubkey,
+    account: &'static str,
+) -> Result<T, CliError> {
+    fetch_optional_state(client, pubkey, account)?
+        .ok_or(CliError::account_not_found(account, pubkey))
+}
+
+/// Fetches and deserializes an account which may not exist yet.
+fn fetch_optional_state<T: AccountDeserialize>(
+    client: &Client,
+    pubkey: Pubkey,
+    account: &'static str,
+) -> Result<Option<T>, CliError> {
+    let Some(data) = client
+        .get_account_with_commitment(&pubkey, client.commitment())?
+        .value
+        .map(|account| account.data)
+    else {
+        return Ok(None);
+    };
+    T::try_deserialize(&mut data.as_slice())
+        .map(Some)
+        .map_err(|_err| CliError::deserialization_failed(account, pubkey, &data))
+}
+
+fn fetch_config(client: &Client) -> Result<Config, CliError> {
+    fetch_state(client, Config::pubkey(), "config").map_err(|err| err.with_hint(NETWORK_HINT))
+}
+
+fn fetch_registry(client: &Client) -> Result<Registry, CliError> {
+    fetch_state(client, Registry::pubkey(), "registry").map_err(|err| err.with_hint(NETWORK_HINT))
+}
+
+/// Fetches a worker, by its address as the id of a worker isn't always known.
+fn fetch_worker(client: &Client, worker_pubkey: Pubkey) -> Result<Worker, CliError> {
+    fetch_state(client, worker_pubkey, "worker")
+        .map_err(|err| err.with_hint("the workers are registered with `sablier worker create`"))
+}
+
+/// How the operator confirms a destructive command.
+enum Confirmation<'a> {
+    /// Answering yes, for the commands which can be undone.
+    YesNo,
+    /// Typing the id of the account back, for the commands which can't.
+    Typed(&'a str),
+}
+
+/// Shows the account a destructive command operates on and what the command does to it, then asks the operator
+/// to confirm, unless `--yes` is passed. The summary goes to stderr, to keep the output of the command parsable.
+/// A declined command is cancelled, and without a terminal to answer on it is refused rather than hanging.
+fn confirm(
+    action: &str,
+    summary: &str,
+    confirmation: Confirmation,
+    yes: bool,
+) -> Result<(), CliError> {
+    if yes {
+        return Ok(());
+    }
+    if !io::stdin().is_terminal() {
+        return Err(CliError::ConfirmationRequired(action.to_string()));
+    }
+    if !summary.is_empty() {
+        eprintln!("{}", summary);
+    }
+    match confirmation {
+        Confirmation::YesNo => eprint!("{}? [y/N] ", capitalize(action)),
+        Confirmation::Typed(expected) => eprint!("Type {} to {}: ", expected, action),
+    }
+    let _ = io::stderr().flush();
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .map_err(|err| CliError::BadParameter(format!("confirmation: {}", err)))?;
+    let answer = answer.trim();
+    let confirmed = match confirmation {
+        Confirmation::YesNo => matches!(answer.to_lowercase().as_str(), "y" | "yes"),
+        Confirmation::Typed(expected) => answer == expected,
+    };
+    if confirmed {
+        Ok(())
+    } else {
+        Err(CliError::Cancelled)
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
 pub fn process(matches: &ArgMatches) -> Result<(), CliError> {
     // Parse command and config
     let command = CliCommand::try_from(matches)?;
@@ -30,23 +169,135 @@ pub fn process(matches: &ArgMatches) -> Result<(), CliError> {
         set_solana_config().map_err(|err| CliError::FailedLocalnet(err.to_string()))?
     }
 
-    let mut config = CliConfig::load();
+    let output = OutputFormat::from_str(
+        matches
+            .get_one::<String>("output")
+            .map_or("text", String::as_str),
+    )?;
+    // The thread commands act on the threads of the authority, the mutating ones sign as it or dump their transactions.
+    // The authority is either an address, or a signer co-signing with the payer, e.g. a hardware wallet.
+    let (authority, authority_signer) = match matches.get_one::<String>("authority") {
+        None => (None, None),
+        Some(authority) => match Pubkey::from_str(authority) {
+            Ok(address) => (Some(address), None),
+            Err(_err) => {
+                let signer = signer_from_path(authority, "authority")?;
+                (Some(signer.pubkey()), Some(signer))
+            }
+        },
+    };
+
+    // The localnet always runs on localhost, its `--url` is the cluster its accounts are cloned from.
+    let localnet = matches!(command, CliCommand::Localnet { .. });
+    let mut config = CliConfig::load().with_overrides(
+        &ClusterArgs {
+            url: matches
+                .get_one::<String>("url")
+                .filter(|_| !localnet)
+                .cloned(),
+            websocket_url: matches.get_one::<String>("ws").cloned(),
+            keypair_path: matches.get_one::<String>("keypair").cloned(),
+        },
+        std::env::var(RPC_URL_ENV).ok().filter(|_| !localnet),
+    );
+
+    // The thread addresses are derived without any RPC call, the payer is only read to be the default authority.
+    if let CliCommand::ThreadPubkey { id, domain } = command {
+        let authority = match authority {
+            Some(authority) => authority,
+            None => read_payer(&config)?.pubkey(),
+        };
+        return thread::pubkey(authority, id, domain, output);
+    }
+
+    // The thread data read from a file or from base64 is decoded offline.
+    if let CliCommand::ThreadDecode { source } = &command {
+        if !matches!(source, DecodeSource::Address(_)) {
+            return thread::decode(None, source.clone(), output);
+        }
+    }
 
     // Build the RPC client
-    let payer = read_keypair_file(&config.keypair_path)
-        .map_err(|_| CliError::KeypairNotFound(config.keypair_path.clone()))?;
+    let payer = read_payer(&config)?;
 
-    let client = Client::new(payer, config.json_rpc_url.clone());
+    let priority_fee = if matches.get_flag("auto_priority_fee") {
+        Some(PriorityFee::Auto)
+    } else {
+        matches
+            .get_one::<u64>("priority_fee")
+            .map(|price| PriorityFee::Fixed(*price))
+    };
+    let confirmation = matches
+        .get_one::<String>("commitment")
+        .map_or(Ok(CommitmentLevel::Confirmed), |level| {
+            CommitmentLevel::from_str(level)
+        })
+        .map_err(|_err| CliError::BadParameter("commitment".into()))?;
+    let explorer_site = matches
+        .get_one::<String>("explorer")
+        .map_or(Ok(ExplorerSite::default()), |site| {
+            ExplorerSite::from_str(site)
+        })
+        .map_err(|_err| CliError::BadParameter("explorer".into()))?;
+    if let CliCommand::Version { cluster: Some(url) } = &command {
+        config.json_rpc_url = url.clone();
+    }
+    if matches.get_flag("verbose") {
+        print_note!(
+            "cluster {} (from {})",
+            config.json_rpc_url,
+            config.cluster_source
+        );
+    }
+    let client = Client::new(payer, config.json_rpc_url.clone())
+        .with_compute_budget(ComputeBudget {
+            priority_fee,
+            compute_units: matches.get_one::<u32>("compute_units").copied(),
+        })
+        .with_confirmation(confirmation)
+        .with_explorer_site(explorer_site);
+
+    let authority = authority.unwrap_or_else(|| client.payer_pubkey());
+    let dump = matches
+        .get_one::<String>("dump_transaction")
+        .map(|encoding| DumpEncoding::from_str(encoding))
+        .transpose()?;
+    let submitter = Submitter::new(authority, dump).with_signer(authority_signer);
+    let yes = matches.get_flag("yes");
+
+    // A CLI of another release fails with baffling deserialization errors, it is told before the first RPC request.
+    if !matches.get_flag("skip_version_check")
+        && !matches!(
+            command,
+            CliCommand::Localnet { .. } | CliCommand::Version { .. }
+        )
+    {
+        version::warn_if_incompatible(&client, &config.json_rpc_url);
+    }
 
     // Process the command
     match command {
-        CliCommand::ConfigGet => config::get(&client),
+        CliCommand::ConfigGet => config::get(&client, output),
         CliCommand::ConfigSet {
             admin,
             epoch_thread,
             hasher_thread,
-        } => config::set(&client, admin, epoch_thread, hasher_thread),
-        CliCommand::Crontab { schedule } => crontab::get(&client, schedule),
+            mint,
+        } => config::set(
+            &client,
+            admin,
+            epoch_thread,
+            hasher_thread,
+            mint,
+            yes,
+            output,
+        ),
+        CliCommand::Crontab {
+            schedule,
+            count,
+            timezone,
+            after,
+        } => crontab::get(&client, schedule, count, timezone, after),
         CliCommand::DelegationCreate { worker_id } => delegation::create(&client, worker_id),
         CliCommand::DelegationDeposit {
             amount,
@@ -56,64 +307,259 @@ pub fn process(matches: &ArgMatches) -> Result<(), CliError> {
         CliCommand::DelegationGet {
             delegation_id,
             worker_id,
-        } => delegation::get(&client, delegation_id, worker_id),
+        } => delegation::get(&client, delegation_id, worker_id, output),
         CliCommand::DelegationWithdraw {
             amount,
             delegation_id,
             worker_id,
         } => delegation::withdraw(&client, amount, delegation_id, worker_id),
-        CliCommand::ExplorerGetThread { id, address } => {
-            let pubkey = parse_pubkey_from_id_or_address(client.payer_pubkey(), id, address)?;
-            explorer::thread_url(pubkey, config)
+        CliCommand::ExplorerGetThread {
+            id,
+            domain,
+            address,
+        } => {
+            let pubkey = parse_pubkey_from_id_or_address(authority, id, domain, address)?;
+            explorer::thread_url(pubkey, &client)
         }
         CliCommand::Initialize { mint } => initialize::initialize(&client, mint),
         CliCommand::Localnet {
             clone_addresses,
+            clone_programs,
             network_url,
             program_infos,
             force_init,
             solana_archive,
             sablier_archive,
             dev,
+            keep_ledger,
         } => localnet::start(LocalnetStartArgs {
             config: &mut config,
             client: &client,
             clone_addresses,
+            clone_programs,
             network_url,
             program_infos,
             force_init,
             solana_archive,
             sablier_archive,
             dev,
+            keep_ledger,
         }),
-        CliCommand::PoolGet { id } => pool::get(&client, id),
-        CliCommand::PoolList {} => pool::list(&client),
-        CliCommand::PoolUpdate { id, size } => pool::update(&client, id, size),
-        CliCommand::ThreadCrateInfo {} => thread::crate_info(&client),
+        CliCommand::PoolGet { id } => pool::get(&client, id, output),
+        CliCommand::PoolList {} => pool::list(&client, output),
+        CliCommand::PoolUpdate { id, size } => pool::update(&client, id, size, output),
+        CliCommand::ThreadBatch {
+            action,
+            threads,
+            continue_on_error,
+            dry_run,
+        } => thread::batch(
+            &client,
+            &submitter,
+            action,
+            threads,
+            continue_on_error,
+            dry_run,
+            yes,
+            output,
+        ),
+        CliCommand::ThreadCrateInfo {} => thread::crate_info(&client, output),
+        CliCommand::ThreadPauseAll { pause, pattern } => {
+            thread::pause_all(&client, &submitter, pause, pattern, yes, output)
+        }
+        CliCommand::ThreadClone {
+            from_id,
+            from_domain,
+            from_address,
+            id,
+            domain,
+            paused,
+        } => {
+            let source =
+                parse_pubkey_from_id_or_address(authority, from_id, from_domain, from_address)?;
+            thread::clone(&client, &submitter, source, id, domain, paused, output)
+        }
         CliCommand::ThreadCreate {
             id,
             domain,
-            kickoff_instruction,
+            instructions,
             trigger,
-        } => thread::create(&client, id, domain, vec![kickoff_instruction], trigger),
-        CliCommand::ThreadDelete { id } => thread::delete(&client, id),
-        CliCommand::ThreadPause { id } => thread::pause(&client, id),
-        CliCommand::ThreadResume { id } => thread::resume(&client, id),
-        CliCommand::ThreadReset { id } => thread::reset(&client, id),
-        CliCommand::ThreadGet { id, address } => {
-            let pubkey = parse_pubkey_from_id_or_address(client.payer_pubkey(), id, address)?;
-            thread::get(&client, pubkey)
+        } => thread::create(
+            &client,
+            &submitter,
+            id,
+            domain,
+            instructions,
+            thread::resolve_trigger(&client, trigger)?,
+            output,
+        ),
+        CliCommand::ThreadDecode { source } => thread::decode(Some(&client), source, output),
+        CliCommand::ThreadDelete {
+            id,
+            domain,
+            address,
+        } => {
+            let pubkey = parse_pubkey_from_id_or_address(authority, id, domain, address)?;
+            thread::delete(&client, &submitter, pubkey, yes)
+        }
+        CliCommand::ThreadLogs {
+            id,
+            domain,
+            address,
+            limit,
+            json,
+        } => {
+            let pubkey = parse_pubkey_from_id_or_address(authority, id, domain, address)?;
+            thread::logs(&client, pubkey, limit, json || output == OutputFormat::Json)
+        }
+        CliCommand::ThreadPause {
+            id,
+            domain,
+            address,
+        } => {
+            let pubkey = parse_pubkey_from_id_or_address(authority, id, domain, address)?;
+            thread::pause(&client, &submitter, pubkey, output)
+        }
+        CliCommand::ThreadPubkey { .. } => {
+            unreachable!("thread addresses are derived before the client is built")
+        }
+        CliCommand::ThreadResume {
+            id,
+            domain,
+            address,
+        } => {
+            let pubkey = parse_pubkey_from_id_or_address(authority, id, domain, address)?;
+            thread::resume(&client, &submitter, pubkey, output)
+        }
+        CliCommand::ThreadReset {
+            id,
+            domain,
+            address,
+        } => {
+            let pubkey = parse_pubkey_from_id_or_address(authority, id, domain, address)?;
+            thread::reset(&client, &submitter, pubkey, yes, output)
+        }
+        CliCommand::ThreadGet {
+            id,
+            domain,
+            address,
+        } => {
+            let pubkey = parse_pubkey_from_id_or_address(authority, id, domain, address)?;
+            thread::get(&client, pubkey, output)
+        }
+        CliCommand::ThreadFund {
+            id,
+            domain,
+            address,
+            lamports,
+        } => {
+            let pubkey = parse_pubkey_from_id_or_address(authority, id, domain, address)?;
+            thread::fund(&client, &submitter, pubkey, lamports)
+        }
+        CliCommand::ThreadExport {
+            format,
+            out,
+            fields,
+        } => thread::export(&client, authority, format, out, fields),
+        CliCommand::ThreadList { limit, json } => thread::list(
+            &client,
+            authority,
+            limit,
+            json || output == OutputFormat::Json,
+        ),
+        CliCommand::ThreadSimulate {
+            id,
+            domain,
+            address,
+            worker_id,
+        } => {
+            let pubkey = parse_pubkey_from_id_or_address(authority, id, domain, address)?;
+            thread::simulate(&client, pubkey, worker_id)
+        }
+        CliCommand::ThreadWatch {
+            id,
+            domain,
+            address,
+            interval,
+        } => {
+            let pubkey = parse_pubkey_from_id_or_address(authority, id, domain, address)?;
+            let websocket_url = if config.websocket_url.is_empty() {
+                solana_cli_config::Config::compute_websocket_url(&config.json_rpc_url)
+            } else {
+                config.websocket_url.clone()
+            };
+            thread::watch(&client, pubkey, &websocket_url, interval)
+        }
+        CliCommand::ThreadWithdraw {
+            id,
+            domain,
+            address,
+            lamports,
+            to,
+        } => {
+            let pubkey = parse_pubkey_from_id_or_address(authority, id, domain, address)?;
+            thread::withdraw(&client, &submitter, pubkey, lamports, to, yes)
+        }
+        CliCommand::ThreadSetInstruction {
+            id,
+            domain,
+            address,
+            index,
+            instruction,
+        } => {
+            let pubkey = parse_pubkey_from_id_or_address(authority, id, domain, address)?;
+            thread::set_instruction(&client, &submitter, pubkey, index, instruction, yes, output)
         }
         CliCommand::ThreadUpdate {
             id,
+            domain,
+            fee,
+            name,
             rate_limit,
-            schedule,
-        } => thread::update(&client, id, rate_limit, schedule),
-        CliCommand::RegistryGet => registry::get(&client),
-        CliCommand::RegistryUnlock => registry::unlock(&client),
-        CliCommand::WorkerCreate { signatory } => worker::create(&client, signatory, false),
-        CliCommand::WorkerGet { id } => worker::get(&client, id),
-        CliCommand::WorkerUpdate { id, signatory } => worker::update(&client, id, signatory),
+            instructions,
+            trigger,
+            skippable,
+        } => thread::update(
+            &client,
+            &submitter,
+            id,
+            domain,
+            ThreadSettings {
+                fee,
+                instructions,
+                name,
+                rate_limit,
+                trigger: trigger
+                    .map(|trigger| thread::resolve_trigger(&client, trigger))
+                    .transpose()?,
+            },
+            skippable,
+            output,
+        ),
+        CliCommand::RegistryGet => registry::get(&client, output),
+        CliCommand::RegistryUnlock => registry::unlock(&client, output),
+        CliCommand::Version { .. } => version::version(&client, &config.json_rpc_url, output),
+        CliCommand::WorkerCreate { signatory } => worker::create(&client, signatory, false, output),
+        CliCommand::WorkerClaim {
+            address,
+            lamports,
+            to,
+        } => worker::claim(&client, address, lamports, to, yes),
+        CliCommand::WorkerGet { address } => worker::get(&client, address, output),
+        CliCommand::WorkerBalances {
+            min_signatory_balance,
+            json,
+        } => worker::balances(
+            &client,
+            authority,
+            min_signatory_balance,
+            json || output == OutputFormat::Json,
+        ),
+        CliCommand::WorkerUpdate {
+            address,
+            commission_rate,
+            signatory,
+        } => worker::update(&client, address, commission_rate, signatory, yes, output),
     }
 }
 
@@ -121,7 +567,7 @@ fn set_solana_config() -> Result<()> {
     let mut process = std::process::Command::new("solana")
         .args(["config", "set", "--url", "l"])
         .spawn()
-        .expect("Failed to set solana config");
+        .context("failed to run `solana config set`, is the Solana CLI installed?")?;
     process.wait()?;
     std::thread::sleep(std::time::Duration::from_secs(1));
     Ok(())
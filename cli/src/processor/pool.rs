@@ -2,37 +2,72 @@ use anchor_lang::{
     solana_program::{instruction::Instruction, system_program},
     InstructionData, ToAccountMetas,
 };
-use sablier_network_program::state::{Config, Pool, PoolSettings, Registry};
+use sablier_network_program::state::{Config, Pool, PoolSettings};
+use solana_sdk::pubkey::Pubkey;
 
-use crate::{client::Client, errors::CliError};
+use crate::{
+    client::Client,
+    errors::CliError,
+    output::{print_json, Linked, OutputFormat, PoolOutput},
+};
+
+use super::{fetch_optional_state, fetch_state};
 
-pub fn get(client: &Client, id: u64) -> Result<(), CliError> {
+pub fn get(client: &Client, id: u64, output: OutputFormat) -> Result<(), CliError> {
     let pool_pubkey = Pool::pubkey(id);
-    let pool = client
-        .get::<Pool>(&pool_pubkey)
-        .map_err(|_err| CliError::AccountDataNotParsable(pool_pubkey.to_string()))?;
-    println!("{:#?}", pool);
+    let pool = fetch_state::<Pool>(client, pool_pubkey, "pool")
+        .map_err(|err| err.with_hint("list the pools with `sablier pool list`"))?;
+    let explorer_url = client.explorer().account_url(pool_pubkey);
+    match output {
+        OutputFormat::Text => print_pool(pool_pubkey, &pool, &explorer_url),
+        OutputFormat::Json => print_json(&Linked::new(
+            PoolOutput::new(pool_pubkey, &pool),
+            explorer_url,
+        ))?,
+    }
     Ok(())
 }
 
-pub fn list(client: &Client) -> Result<(), CliError> {
-    let registry_pubkey = Registry::pubkey();
-    let registry = client
-        .get::<Registry>(&registry_pubkey)
-        .map_err(|_err| CliError::AccountDataNotParsable(registry_pubkey.to_string()))?;
-
-    for pool_id in 0..registry.total_pools {
+/// Lists the pools by id, until the first missing one.
+pub fn list(client: &Client, output: OutputFormat) -> Result<(), CliError> {
+    let mut pools = vec![];
+    for pool_id in 0.. {
         let pool_pubkey = Pool::pubkey(pool_id);
-        let pool = client
-            .get::<Pool>(&pool_pubkey)
-            .map_err(|_err| CliError::AccountDataNotParsable(pool_pubkey.to_string()))?;
-        println!("{:#?}", pool);
+        let Some(pool) = fetch_optional_state::<Pool>(client, pool_pubkey, "pool")? else {
+            break;
+        };
+        let explorer_url = client.explorer().account_url(pool_pubkey);
+        match output {
+            OutputFormat::Text => print_pool(pool_pubkey, &pool, &explorer_url),
+            OutputFormat::Json => pools.push(Linked::new(
+                PoolOutput::new(pool_pubkey, &pool),
+                explorer_url,
+            )),
+        }
     }
 
+    if output == OutputFormat::Json {
+        print_json(&pools)?;
+    }
     Ok(())
 }
 
-pub fn update(client: &Client, id: u64, size: usize) -> Result<(), CliError> {
+/// Prints the workers of the pool in their rotation order, the first one is the next rotated out.
+fn print_pool(address: Pubkey, pool: &Pool, explorer_url: &str) {
+    println!(
+        "Address: {}\nExplorer: {}\nId: {}\nSize: {}\nWorkers: {}",
+        address,
+        explorer_url,
+        pool.id,
+        pool.size,
+        pool.workers.len()
+    );
+    for (index, worker) in pool.workers.iter().enumerate() {
+        println!("  {}: {}", index, worker);
+    }
+}
+
+pub fn update(client: &Client, id: u64, size: usize, output: OutputFormat) -> Result<(), CliError> {
     let pool_pubkey = Pool::pubkey(id);
     let settings = PoolSettings { size };
     let ix = Instruction {
@@ -47,7 +82,7 @@ pub fn update(client: &Client, id: u64, size: usize) -> Result<(), CliError> {
         .to_account_metas(Some(false)),
         data: sablier_network_program::instruction::PoolUpdate { settings }.data(),
     };
-    client.send_and_confirm(&[ix], &[client.payer()]).unwrap();
-    get(client, id)?;
+    client.send_and_confirm(&[ix], &[client.payer()])?;
+    get(client, id, output)?;
     Ok(())
 }
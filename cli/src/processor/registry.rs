@@ -1,25 +1,57 @@
 use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
 use sablier_network_program::state::{Config, Registry, Snapshot};
 
-use crate::{client::Client, errors::CliError};
+use crate::{
+    client::Client,
+    errors::CliError,
+    output::{print_json, Linked, OutputFormat, RegistryOutput, SnapshotOutput},
+};
 
-pub fn get(client: &Client) -> Result<(), CliError> {
-    let registry_pubkey = sablier_network_program::state::Registry::pubkey();
-    let registry = client
-        .get::<Registry>(&registry_pubkey)
-        .map_err(|_err| CliError::AccountDataNotParsable(registry_pubkey.to_string()))?;
+use super::{fetch_optional_state, fetch_registry};
 
+pub fn get(client: &Client, output: OutputFormat) -> Result<(), CliError> {
+    let registry_pubkey = Registry::pubkey();
+    let registry = fetch_registry(client)?;
+
+    // The snapshot of the current epoch is missing until the first one is taken.
     let snapshot_pubkey = Snapshot::pubkey(registry.current_epoch);
-    let snapshot = client
-        .get::<Snapshot>(&snapshot_pubkey)
-        .map_err(|_err| CliError::AccountDataNotParsable(snapshot_pubkey.to_string()))?;
+    let snapshot = fetch_optional_state::<Snapshot>(client, snapshot_pubkey, "snapshot")?;
 
-    println!("{}\n{:#?}", registry_pubkey, registry);
-    println!("{}\n{:#?}", snapshot_pubkey, snapshot);
+    let explorer_url = client.explorer().account_url(registry_pubkey);
+    if output == OutputFormat::Json {
+        return print_json(&Linked::new(
+            RegistryOutput::new(
+                registry_pubkey,
+                &registry,
+                snapshot
+                    .as_ref()
+                    .map(|snapshot| SnapshotOutput::new(snapshot_pubkey, snapshot)),
+            ),
+            explorer_url,
+        ));
+    }
+    println!(
+        "Address: {}\nExplorer: {}\nCurrent epoch: {}\nLocked: {}\nNonce: {}\nTotal pools: {}\nTotal workers: {}\nTotal unstakes: {}",
+        registry_pubkey,
+        explorer_url,
+        registry.current_epoch,
+        registry.locked,
+        registry.nonce,
+        registry.total_pools,
+        registry.total_workers,
+        registry.total_unstakes
+    );
+    match snapshot {
+        None => println!("Snapshot: none taken for epoch {}", registry.current_epoch),
+        Some(snapshot) => println!(
+            "Snapshot: {}\n  Frames: {}\n  Total stake: {}",
+            snapshot_pubkey, snapshot.total_frames, snapshot.total_stake
+        ),
+    }
     Ok(())
 }
 
-pub fn unlock(client: &Client) -> Result<(), CliError> {
+pub fn unlock(client: &Client, output: OutputFormat) -> Result<(), CliError> {
     let ix = Instruction {
         program_id: sablier_network_program::ID,
         accounts: sablier_network_program::accounts::RegistryUnlock {
@@ -30,7 +62,7 @@ pub fn unlock(client: &Client) -> Result<(), CliError> {
         .to_account_metas(Some(false)),
         data: sablier_network_program::instruction::RegistryUnlock {}.data(),
     };
-    client.send_and_confirm(&[ix], &[client.payer()]).unwrap();
-    get(client)?;
+    client.send_and_confirm(&[ix], &[client.payer()])?;
+    get(client, output)?;
     Ok(())
 }
@@ -1,16 +1,79 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
 use anchor_lang::{
     solana_program::{instruction::Instruction, system_program},
-    AccountDeserialize, InstructionData, ToAccountMetas,
+    AccountDeserialize, AnchorSerialize, Discriminator, InstructionData, ToAccountMetas,
+};
+use chrono::DateTime;
+use indicatif::{ProgressBar, ProgressStyle};
+use sablier_network_program::state::Worker;
+use sablier_thread_program::{
+    constants::THREAD_MINIMUM_FEE,
+    errors::SablierError,
+    instruction::{ThreadExec as ThreadExecData, ThreadKickoff as ThreadKickoffData},
+    state::{
+        Equality, ExecContext, SerializableInstruction, Thread, ThreadSettings, Trigger,
+        VersionedThread,
+    },
+};
+use sablier_utils::{
+    pyth::{get_oracle_key, PriceUpdateV2},
+    CrateInfo,
+};
+use serde::Serialize;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    pubsub_client::PubsubClient,
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::{
+        RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSimulateTransactionConfig,
+        RpcTransactionConfig,
+    },
+    rpc_filter::{Memcmp, RpcFilterType},
 };
-use sablier_thread_program::state::{
-    SerializableInstruction, Thread, ThreadSettings, Trigger, VersionedThread,
+use solana_sdk::{
+    account::Account, commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction, entrypoint::MAX_PERMITTED_DATA_INCREASE, hash::hash,
+    native_token::lamports_to_sol, pubkey::Pubkey, signature::Signature, system_instruction,
+    transaction::Transaction,
 };
-use sablier_utils::CrateInfo;
-use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+
+use crate::{
+    account_file::{decode_base64, read_account_file, DecodeSource},
+    cli::TriggerArg,
+    client::Client,
+    errors::CliError,
+    export::{ExportField, ExportFormat, ExportRow, ExportThread, ExportWriter},
+    manifest::{BatchAction, BatchSettings, BatchThread},
+    output::{bytes_to_string, print_json, CrateInfoOutput, Linked, OutputFormat, ThreadOutput},
+    parser::scale_price,
+    print::print_style,
+    print_warn,
+    transaction::Submitter,
+};
+
+use super::{confirm, fetch_account, fetch_state, Confirmation};
+
+/// The max compute units of a transaction, the simulated instruction is not held back by a lower limit.
+static MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
 
-use crate::{client::Client, errors::CliError};
+/// The max number of signatures returned by `getSignaturesForAddress`.
+static MAX_LOGS_LIMIT: usize = 1_000;
 
-pub fn crate_info(client: &Client) -> Result<(), CliError> {
+/// The delay between the transaction fetches of `thread logs`.
+static TRANSACTION_FETCH_DELAY: Duration = Duration::from_millis(100);
+
+/// The hint of a missing thread, whose address was likely derived from the wrong seeds.
+static THREAD_HINT: &str =
+    "a thread address is derived from its authority, id and domain, did you mean to pass --domain or --authority?";
+
+pub fn crate_info(client: &Client, output: OutputFormat) -> Result<(), CliError> {
     let ix = Instruction {
         program_id: sablier_thread_program::ID,
         accounts: sablier_thread_program::accounts::GetCrateInfo {
@@ -19,28 +82,124 @@ pub fn crate_info(client: &Client) -> Result<(), CliError> {
         .to_account_metas(Some(false)),
         data: sablier_thread_program::instruction::GetCrateInfo {}.data(),
     };
-    let crate_info: CrateInfo = client.get_return_data(ix).unwrap();
-    println!("{:#?}", crate_info);
+    let crate_info: CrateInfo = client.get_return_data(ix)?;
+    match output {
+        OutputFormat::Text => println!("{:#?}", crate_info),
+        OutputFormat::Json => print_json(&CrateInfoOutput::from(crate_info))?,
+    }
     Ok(())
 }
 
 pub fn create(
     client: &Client,
+    submitter: &Submitter,
     id: String,
-    domain: String,
+    domain: Option<String>,
     instructions: Vec<SerializableInstruction>,
     trigger: Trigger,
+    output: OutputFormat,
 ) -> Result<(), CliError> {
-    let thread_pubkey = Thread::pubkey(
-        client.payer_pubkey(),
-        id.clone().into_bytes(),
-        Some(domain.into_bytes()),
-    );
+    let (thread_pubkey, ix) = create_ix(submitter.authority(), id, domain, instructions, trigger);
+    if submitter.submit(client, &[ix])? {
+        get(client, thread_pubkey, output)?;
+    }
+    Ok(())
+}
+
+/// Resolves the trigger flags, a pyth price is scaled by the exponent of its feed, read from the price account of the
+/// feed unless `--pyth-exponent` is set. The trigger is checked before any instruction is built.
+pub fn resolve_trigger(client: &Client, trigger: TriggerArg) -> Result<Trigger, CliError> {
+    let trigger = match trigger {
+        TriggerArg::Trigger(trigger) => trigger,
+        TriggerArg::Pyth {
+            feed_id,
+            equality,
+            price,
+            exponent,
+        } => {
+            let exponent = match exponent {
+                Some(exponent) => exponent,
+                None => fetch_pyth_exponent(client, feed_id)?,
+            };
+            let limit = scale_price(&price, exponent).ok_or_else(|| {
+                CliError::BadParameter(format!(
+                    "price: {} is not a price of the feed with the exponent {}",
+                    price, exponent
+                ))
+            })?;
+            Trigger::Pyth {
+                feed_id,
+                equality,
+                limit,
+            }
+        }
+    };
+    check_trigger(client, &trigger)?;
+    Ok(trigger)
+}
+
+/// Reads the exponent of the prices of a pyth feed, from its price account of the default shard.
+fn fetch_pyth_exponent(client: &Client, feed_id: [u8; 32]) -> Result<i32, CliError> {
+    let price_update: PriceUpdateV2 = fetch_state(client, get_oracle_key(0, feed_id), "pyth price")
+        .map_err(|err| err.with_hint("set the exponent of the feed with --pyth-exponent"))?;
+    Ok(price_update.price_message.exponent)
+}
+
+/// Checks the trigger could fire, rather than the thread waiting forever on a mistyped trigger.
+fn check_trigger(client: &Client, trigger: &Trigger) -> Result<(), CliError> {
+    match trigger {
+        Trigger::Account {
+            address,
+            offset,
+            size,
+        } => match client
+            .get_account_with_commitment(address, client.commitment())?
+            .value
+        {
+            Some(account) if offset + size > account.data.len() as u64 => {
+                return Err(CliError::BadParameter(format!(
+                    "offset: the account {} has {} bytes of data, the watched bytes {}..{} are out of it",
+                    address,
+                    account.data.len(),
+                    offset,
+                    offset + size
+                )))
+            }
+            Some(_) => {}
+            None => print_warn!(
+                "The account {} does not exist, the thread can't trigger until it is created",
+                address
+            ),
+        },
+        Trigger::Timestamp { unix_ts } => {
+            let now = client.get_clock()?.unix_timestamp;
+            if *unix_ts <= now {
+                print_warn!(
+                    "The timestamp {} is in the past of the cluster clock {}, the thread triggers right away",
+                    unix_ts,
+                    now
+                );
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Builds the thread create instruction, the thread is created at the address derived from its id and domain.
+fn create_ix(
+    authority: Pubkey,
+    id: String,
+    domain: Option<String>,
+    instructions: Vec<SerializableInstruction>,
+    trigger: Trigger,
+) -> (Pubkey, Instruction) {
+    let thread_pubkey = thread_pubkey(authority, id.clone(), domain.clone());
     let ix = Instruction {
         program_id: sablier_thread_program::ID,
         accounts: sablier_thread_program::accounts::ThreadCreate {
-            authority: client.payer_pubkey(),
-            payer: client.payer_pubkey(),
+            authority,
+            payer: authority,
             system_program: system_program::ID,
             thread: thread_pubkey,
         }
@@ -48,127 +207,1874 @@ pub fn create(
         data: sablier_thread_program::instruction::ThreadCreate {
             amount: 0,
             id: id.into_bytes(),
-            domain: None,
+            domain: domain.map(String::into_bytes),
             instructions,
             trigger,
         }
         .data(),
     };
-    client.send_and_confirm(&[ix], &[client.payer()]).unwrap();
-    get(client, thread_pubkey)?;
+    (thread_pubkey, ix)
+}
+
+/// The addresses of a cloned thread and of its clone.
+#[derive(Serialize)]
+struct ThreadCloneOutput {
+    source: String,
+    clone: String,
+}
+
+/// Creates a thread with the instructions and trigger of another one, under a new id of the authority.
+/// The fee and rate limit are copied by an update, and the clone is paused, in its create transaction,
+/// so it never executes with the default settings.
+pub fn clone(
+    client: &Client,
+    submitter: &Submitter,
+    source_pubkey: Pubkey,
+    id: String,
+    domain: Option<String>,
+    paused: bool,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let source = fetch_thread(client, source_pubkey)?;
+    let (thread_pubkey, create) = create_ix(
+        submitter.authority(),
+        id.clone(),
+        domain,
+        source.instructions(),
+        source.trigger(),
+    );
+    if client
+        .get_account_with_commitment(&thread_pubkey, client.commitment())?
+        .value
+        .is_some()
+    {
+        return Err(CliError::BadParameter(format!(
+            "to-id: the thread {} already exists at {}",
+            id, thread_pubkey
+        )));
+    }
+
+    let mut ixs = vec![create];
+    let settings = ThreadSettings {
+        fee: Some(source.fee()).filter(|fee| *fee != THREAD_MINIMUM_FEE),
+        instructions: None,
+        name: None,
+        rate_limit: Some(source.rate_limit()).filter(|rate_limit| *rate_limit != u64::MAX),
+        trigger: None,
+    };
+    if settings.fee.is_some() || settings.rate_limit.is_some() {
+        ixs.push(update_ix(submitter.authority(), thread_pubkey, settings));
+    }
+    if paused {
+        ixs.push(pause_ix(submitter.authority(), thread_pubkey));
+    }
+    if !submitter.submit(client, &ixs)? {
+        return Ok(());
+    }
+    match output {
+        OutputFormat::Text => println!(
+            "Source: {}\nClone: {}{}",
+            source_pubkey,
+            thread_pubkey,
+            if paused { " (paused)" } else { "" }
+        ),
+        OutputFormat::Json => print_json(&ThreadCloneOutput {
+            source: source_pubkey.to_string(),
+            clone: thread_pubkey.to_string(),
+        })?,
+    }
     Ok(())
 }
 
-pub fn delete(client: &Client, id: String) -> Result<(), CliError> {
-    let thread_pubkey = Thread::pubkey(client.payer_pubkey(), id.into_bytes(), None);
-    let ix = Instruction {
+pub fn delete(
+    client: &Client,
+    submitter: &Submitter,
+    thread_pubkey: Pubkey,
+    yes: bool,
+) -> Result<(), CliError> {
+    let thread = fetch_authorized_thread(client, submitter, thread_pubkey)?;
+    // The deletion can't be undone, the id is typed back rather than a mistyped id confirmed with a y.
+    let id = match bytes_to_string(&thread.id()) {
+        id if id.is_empty() => thread_pubkey.to_string(),
+        id => id,
+    };
+    confirm(
+        "delete the thread",
+        &format!(
+            "{}\nDeleting closes the thread account, its lamports go to the authority {}",
+            thread_text(client, thread_pubkey, &thread),
+            submitter.authority()
+        ),
+        Confirmation::Typed(&id),
+        yes,
+    )?;
+    submitter.submit(client, &[delete_ix(submitter.authority(), thread_pubkey)])?;
+    Ok(())
+}
+
+/// Builds the thread delete instruction, the thread's lamports are returned to the authority.
+fn delete_ix(authority: Pubkey, thread_pubkey: Pubkey) -> Instruction {
+    Instruction {
         program_id: sablier_thread_program::ID,
         accounts: sablier_thread_program::accounts::ThreadDelete {
-            authority: client.payer_pubkey(),
-            close_to: client.payer_pubkey(),
+            authority,
+            close_to: authority,
             thread: thread_pubkey,
         }
         .to_account_metas(Some(false)),
         data: sablier_thread_program::instruction::ThreadDelete {}.data(),
+    }
+}
+
+pub fn fund(
+    client: &Client,
+    submitter: &Submitter,
+    address: Pubkey,
+    lamports: u64,
+) -> Result<(), CliError> {
+    let balance = thread_balance(client, address)?;
+    let ix = system_instruction::transfer(&submitter.authority(), &address, lamports);
+    if submitter.submit(client, &[ix])? {
+        println!("Balance before: {} SOL", lamports_to_sol(balance));
+        let balance = thread_balance(client, address)?;
+        println!("Balance after: {} SOL", lamports_to_sol(balance));
+    }
+    Ok(())
+}
+
+pub fn withdraw(
+    client: &Client,
+    submitter: &Submitter,
+    address: Pubkey,
+    lamports: u64,
+    to: Option<Pubkey>,
+    yes: bool,
+) -> Result<(), CliError> {
+    let account = fetch_thread_account(client, address)?;
+
+    // The program requires the thread to stay above its rent exemption, refuse early rather than failing the transaction.
+    let minimum_rent = client.get_minimum_balance_for_rent_exemption(account.data.len())?;
+    let withdrawable = account.lamports.saturating_sub(minimum_rent + 1);
+    if lamports > withdrawable {
+        return Err(CliError::BadParameter(format!(
+            "amount: the thread would fall below its rent exemption, at most {} SOL can be withdrawn",
+            lamports_to_sol(withdrawable)
+        )));
+    }
+
+    let pay_to = to.unwrap_or(submitter.authority());
+    let thread = decode_thread(&address.to_string(), &account.data)?;
+    confirm(
+        &format!("withdraw {} SOL from the thread", lamports_to_sol(lamports)),
+        &format!(
+            "{}\nWithdrawing sends {} SOL to {}, the thread keeps {} SOL to pay for its execs",
+            thread_text(client, address, &thread),
+            lamports_to_sol(lamports),
+            pay_to,
+            lamports_to_sol(account.lamports - lamports)
+        ),
+        Confirmation::YesNo,
+        yes,
+    )?;
+
+    let ix = Instruction {
+        program_id: sablier_thread_program::ID,
+        accounts: sablier_thread_program::accounts::ThreadWithdraw {
+            authority: submitter.authority(),
+            pay_to,
+            thread: address,
+        }
+        .to_account_metas(Some(false)),
+        data: sablier_thread_program::instruction::ThreadWithdraw { amount: lamports }.data(),
+    };
+    let sent = submitter.submit_with(client, &[ix], |err| {
+        if err.custom_error() == Some(SablierError::WithdrawalTooLarge.into()) {
+            CliError::transaction_failed(SablierError::WithdrawalTooLarge)
+        } else {
+            CliError::from(err)
+        }
+    })?;
+    if sent {
+        println!("Balance before: {} SOL", lamports_to_sol(account.lamports));
+        let balance = thread_balance(client, address)?;
+        println!("Balance after: {} SOL", lamports_to_sol(balance));
+    }
+    Ok(())
+}
+
+fn thread_balance(client: &Client, address: Pubkey) -> Result<u64, CliError> {
+    fetch_thread_account(client, address).map(|account| account.lamports)
+}
+
+fn fetch_thread_account(client: &Client, address: Pubkey) -> Result<Account, CliError> {
+    fetch_account(client, address, "thread").map_err(|err| err.with_hint(THREAD_HINT))
+}
+
+/// Fetches the thread at the address, the commands check it exists before sending anything.
+fn fetch_thread(client: &Client, address: Pubkey) -> Result<VersionedThread, CliError> {
+    fetch_state(client, address, "thread").map_err(|err| err.with_hint(THREAD_HINT))
+}
+
+/// Fetches the thread at the address, which must be owned by the authority the transaction is signed by.
+fn fetch_authorized_thread(
+    client: &Client,
+    submitter: &Submitter,
+    address: Pubkey,
+) -> Result<VersionedThread, CliError> {
+    let thread = fetch_thread(client, address)?;
+    if thread.authority() != submitter.authority() {
+        return Err(CliError::BadParameter(format!(
+            "the authority of the thread {} is {}, not {}, set it with --authority",
+            address,
+            thread.authority(),
+            submitter.authority()
+        )));
+    }
+    Ok(thread)
+}
+
+pub fn get(client: &Client, address: Pubkey, output: OutputFormat) -> Result<(), CliError> {
+    let thread = fetch_thread(client, address)?;
+    print_thread(
+        address,
+        &thread,
+        Some(client.explorer().account_url(address)),
+        output,
+    )
+}
+
+/// Prints a thread, with the explorer link of its account when there is a cluster to link to.
+fn print_thread(
+    address: Pubkey,
+    thread: &VersionedThread,
+    explorer_url: Option<String>,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    match (output, explorer_url) {
+        (OutputFormat::Text, explorer_url) => {
+            println!("{}", format_thread(address, thread, explorer_url))
+        }
+        (OutputFormat::Json, Some(explorer_url)) => print_json(&Linked::new(
+            ThreadOutput::new(address, thread),
+            explorer_url,
+        ))?,
+        (OutputFormat::Json, None) => print_json(&ThreadOutput::new(address, thread))?,
+    }
+    Ok(())
+}
+
+/// The text of a thread printed by `thread get`.
+fn format_thread(
+    address: Pubkey,
+    thread: &VersionedThread,
+    explorer_url: Option<String>,
+) -> String {
+    match explorer_url {
+        Some(explorer_url) => format!(
+            "Address: {}\nExplorer: {}\n{:#?}",
+            address, explorer_url, thread
+        ),
+        None => format!("Address: {}\n{:#?}", address, thread),
+    }
+}
+
+/// The thread as `thread get` prints it, shown before a destructive command so the operator sees what is changed.
+fn thread_text(client: &Client, address: Pubkey, thread: &VersionedThread) -> String {
+    format_thread(
+        address,
+        thread,
+        Some(client.explorer().account_url(address)),
+    )
+}
+
+/// Decodes the data of a thread account, and prints it like `thread get`.
+/// The data read from a file or from base64 is decoded offline, without the client, and its address is derived
+/// from the thread when the file doesn't have it.
+pub fn decode(
+    client: Option<&Client>,
+    source: DecodeSource,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let (label, address, data) = match source {
+        DecodeSource::File(path) => {
+            let account = read_account_file(&path)?;
+            (path, account.address, account.data)
+        }
+        DecodeSource::Base64(data) => ("the base64 data".to_string(), None, decode_base64(&data)?),
+        DecodeSource::Address(address) => {
+            let client = client.expect("the accounts are fetched with a client");
+            let data = fetch_thread_account(client, address)?.data;
+            (address.to_string(), Some(address), data)
+        }
     };
-    client.send_and_confirm(&[ix], &[client.payer()]).unwrap();
+    let thread = decode_thread(&label, &data)?;
+    let address = address
+        .unwrap_or_else(|| Thread::find_pubkey(thread.authority(), thread.id(), thread.domain()).0);
+    print_thread(
+        address,
+        &thread,
+        client.map(|client| client.explorer().account_url(address)),
+        output,
+    )
+}
+
+/// Deserializes thread data, the data of another account is named by its discriminator.
+fn decode_thread(label: &str, data: &[u8]) -> Result<VersionedThread, CliError> {
+    VersionedThread::try_deserialize(&mut &data[..])
+        .map_err(|_err| CliError::deserialization_failed("thread", label, data))
+}
+
+/// A row of the thread listing, only the address is known of the threads of an unknown version.
+#[derive(Serialize)]
+struct ThreadListEntry {
+    address: String,
+    #[serde(flatten)]
+    thread: Option<ThreadSummary>,
+    unknown_version: bool,
+}
+
+#[derive(Serialize)]
+struct ThreadSummary {
+    id: String,
+    domain: Option<String>,
+    trigger: String,
+    paused: bool,
+    next_instruction: bool,
+}
+
+pub fn list(
+    client: &Client,
+    authority: Pubkey,
+    limit: Option<usize>,
+    json: bool,
+) -> Result<(), CliError> {
+    let accounts = fetch_authority_threads(client, authority)?;
+    let mut entries: Vec<ThreadListEntry> = accounts
+        .into_iter()
+        .map(|(address, account)| {
+            let thread = VersionedThread::try_deserialize(&mut account.data.as_slice())
+                .ok()
+                .map(|thread| ThreadSummary {
+                    id: String::from_utf8_lossy(&thread.id()).into_owned(),
+                    domain: thread
+                        .domain()
+                        .map(|domain| String::from_utf8_lossy(&domain).into_owned()),
+                    trigger: trigger_summary(&thread.trigger()),
+                    paused: thread.paused(),
+                    next_instruction: thread.next_instruction().is_some(),
+                });
+            ThreadListEntry {
+                address: address.to_string(),
+                unknown_version: thread.is_none(),
+                thread,
+            }
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        let id = |entry: &ThreadListEntry| entry.thread.as_ref().map(|thread| thread.id.clone());
+        id(a).cmp(&id(b)).then_with(|| a.address.cmp(&b.address))
+    });
+    if let Some(limit) = limit {
+        entries.truncate(limit);
+    }
+
+    if json {
+        return print_json(&entries);
+    }
+    println!(
+        "{:<44}  {:<32}  {:<16}  {:<40}  {:<6}  NEXT_IX",
+        "ADDRESS", "ID", "DOMAIN", "TRIGGER", "PAUSED"
+    );
+    for entry in &entries {
+        match &entry.thread {
+            Some(thread) => println!(
+                "{:<44}  {:<32}  {:<16}  {:<40}  {:<6}  {}",
+                entry.address,
+                thread.id,
+                thread.domain.as_deref().unwrap_or("-"),
+                thread.trigger,
+                thread.paused,
+                thread.next_instruction
+            ),
+            None => println!("{:<44}  unknown version", entry.address),
+        }
+    }
     Ok(())
 }
 
-pub fn get(client: &Client, address: Pubkey) -> Result<(), CliError> {
-    let data = client.get_account_data(&address).unwrap();
-    let thread = VersionedThread::try_deserialize(&mut data.as_slice()).unwrap();
-    println!("Address: {}\n{:#?}", address, thread);
+/// Fetches the thread accounts of the authority.
+fn fetch_authority_threads(
+    client: &Client,
+    authority: Pubkey,
+) -> Result<Vec<(Pubkey, Account)>, CliError> {
+    // The authority is the first field of the thread accounts, right after the discriminator.
+    client
+        .get_program_accounts_with_config(
+            &sablier_thread_program::ID,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                    8,
+                    authority.to_bytes().to_vec(),
+                ))]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64Zstd),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            },
+        )
+        .map_err(CliError::from)
+}
+
+/// Writes the threads of the authority by address, one row at a time.
+/// The time of the last exec is the block time of its slot, fetched only when it is exported.
+pub fn export(
+    client: &Client,
+    authority: Pubkey,
+    format: ExportFormat,
+    out: Option<String>,
+    fields: Vec<ExportField>,
+) -> Result<(), CliError> {
+    let mut accounts = fetch_authority_threads(client, authority)?;
+    accounts.sort_by_key(|(address, _)| *address);
+
+    let writer: Box<dyn std::io::Write> = match &out {
+        None => Box::new(std::io::stdout().lock()),
+        Some(path) => Box::new(std::io::BufWriter::new(
+            std::fs::File::create(path)
+                .map_err(|err| CliError::BadParameter(format!("out: {}: {}", path, err)))?,
+        )),
+    };
+    let with_exec_time = fields.contains(&ExportField::LastExecTime);
+    let io_err = |err: std::io::Error| CliError::BadParameter(format!("out: {}", err));
+    let mut writer = ExportWriter::new(writer, format, fields).map_err(io_err)?;
+    let mut block_times = HashMap::new();
+    for (address, account) in accounts {
+        let thread = VersionedThread::try_deserialize(&mut account.data.as_slice())
+            .ok()
+            .map(|thread| {
+                let exec_context = thread.exec_context();
+                let last_exec_slot = exec_context.map(|context| context.last_exec_at);
+                ExportThread {
+                    id: String::from_utf8_lossy(&thread.id()).into_owned(),
+                    domain: thread
+                        .domain()
+                        .map(|domain| String::from_utf8_lossy(&domain).into_owned()),
+                    trigger: trigger_summary(&thread.trigger()),
+                    paused: thread.paused(),
+                    fee: thread.fee(),
+                    rate_limit: thread.rate_limit(),
+                    created_at: thread.created_at().unix_timestamp,
+                    exec_index: exec_context.map(|context| context.exec_index),
+                    last_exec_slot,
+                    last_exec_time: last_exec_slot.filter(|_| with_exec_time).and_then(|slot| {
+                        *block_times
+                            .entry(slot)
+                            .or_insert_with(|| client.get_block_time(slot).ok())
+                    }),
+                }
+            });
+        writer
+            .write(&ExportRow {
+                address: address.to_string(),
+                balance: account.lamports,
+                thread,
+            })
+            .map_err(io_err)?;
+    }
+    let rows = writer.finish().map_err(io_err)?;
+    if let Some(path) = out {
+        eprintln!("Exported {} threads to {}", rows, path);
+    }
     Ok(())
 }
 
-pub fn pause(client: &Client, id: String) -> Result<(), CliError> {
-    let thread_pubkey = Thread::pubkey(client.payer_pubkey(), id.into_bytes(), None);
-    let ix = Instruction {
+/// A one-line description of the trigger.
+fn trigger_summary(trigger: &Trigger) -> String {
+    match trigger {
+        Trigger::Account {
+            address,
+            offset,
+            size,
+        } => format!("account {} [{}..{}]", address, offset, offset + size),
+        Trigger::Cron { schedule, .. } => format!("cron {}", schedule),
+        Trigger::Now => "now".to_string(),
+        Trigger::Slot { slot } => format!("slot {}", slot),
+        Trigger::Epoch { epoch } => format!("epoch {}", epoch),
+        Trigger::Timestamp { unix_ts } => format!("timestamp {}", unix_ts),
+        Trigger::Pyth {
+            feed_id,
+            equality,
+            limit,
+        } => format!(
+            "pyth {}.. {} {}",
+            feed_id[..4].iter().fold(String::new(), |mut hex, byte| {
+                let _ = write!(hex, "{:02x}", byte);
+                hex
+            }),
+            match equality {
+                Equality::GreaterThanOrEqual => ">=",
+                Equality::LessThanOrEqual => "<=",
+            },
+            limit
+        ),
+        Trigger::Periodic {
+            delay: interval_seconds,
+        }
+        | Trigger::Interval {
+            interval_seconds, ..
+        } => format!("every {}s", interval_seconds),
+    }
+}
+
+/// Simulates the instruction the workers would send next for the thread, with the payer standing in for the signatory.
+pub fn simulate(client: &Client, address: Pubkey, worker_id: u64) -> Result<(), CliError> {
+    let thread = fetch_thread(client, address)?;
+    let worker_pubkey = Worker::pubkey(worker_id);
+    let ix = match thread.next_instruction() {
+        Some(next_instruction) => {
+            println!("Simulating the exec of the next instruction");
+            thread.exec_ix(
+                &next_instruction,
+                address,
+                client.payer_pubkey(),
+                worker_pubkey,
+            )
+        }
+        None => {
+            println!("Simulating the kickoff");
+            let trigger_account = match thread.trigger() {
+                Trigger::Account { address, .. } => Some(address),
+                // The price accounts of the default shard, like the plugin without a shard config.
+                Trigger::Pyth { feed_id, .. } => Some(get_oracle_key(0, feed_id)),
+                _ => None,
+            };
+            thread.kickoff_ix(
+                address,
+                client.payer_pubkey(),
+                worker_pubkey,
+                trigger_account,
+            )
+        }
+    };
+
+    let blockhash = client.get_latest_blockhash()?;
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT),
+            ix,
+        ],
+        Some(&client.payer_pubkey()),
+        &[client.payer()],
+        blockhash,
+    );
+    let result = client
+        .simulate_transaction_with_config(
+            &tx,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                commitment: Some(CommitmentConfig::processed()),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )?
+        .value;
+
+    println!("Worker: {} ({})", worker_id, worker_pubkey);
+    println!("Logs:");
+    for log in result.logs.unwrap_or_default() {
+        println!("  {}", log);
+    }
+    if let Some(units_consumed) = result.units_consumed {
+        println!("Compute units: {}", units_consumed);
+    }
+    match result.err {
+        Some(err) => Err(CliError::transaction_failed(format!(
+            "simulation failed: {}",
+            err
+        ))),
+        None => {
+            println!("Simulation succeeded");
+            Ok(())
+        }
+    }
+}
+
+/// An execution of a thread, decoded from one of its transactions.
+#[derive(Serialize)]
+struct ThreadLogEntry {
+    slot: u64,
+    block_time: Option<i64>,
+    signature: String,
+    /// Either `kickoff` or `exec`.
+    kind: &'static str,
+    worker: Option<String>,
+    compute_units: Option<u64>,
+    success: bool,
+    error: Option<String>,
+}
+
+pub fn logs(client: &Client, address: Pubkey, limit: usize, json: bool) -> Result<(), CliError> {
+    let signatures = client.get_signatures_for_address_with_config(
+        &address,
+        GetConfirmedSignaturesForAddress2Config {
+            limit: Some(limit.min(MAX_LOGS_LIMIT)),
+            commitment: Some(CommitmentConfig::confirmed()),
+            ..GetConfirmedSignaturesForAddress2Config::default()
+        },
+    )?;
+
+    let mut entries = vec![];
+    for (index, status) in signatures.iter().enumerate() {
+        // Space out the transaction fetches, the public RPC endpoints rate limit them.
+        if index > 0 {
+            std::thread::sleep(TRANSACTION_FETCH_DELAY);
+        }
+        let signature = Signature::from_str(&status.signature)
+            .map_err(|_err| CliError::BadParameter(status.signature.clone()))?;
+        let transaction = client.get_transaction_with_config(
+            &signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?;
+        if let Some(entry) = decode_thread_log(&status.signature, transaction) {
+            entries.push(entry);
+        }
+    }
+
+    if json {
+        return print_json(&entries);
+    }
+    println!(
+        "{:<10}  {:<25}  {:<88}  {:<7}  {:<44}  {:<7}  STATUS",
+        "SLOT", "TIME", "SIGNATURE", "KIND", "WORKER", "CU"
+    );
+    for entry in &entries {
+        println!(
+            "{:<10}  {:<25}  {:<88}  {:<7}  {:<44}  {:<7}  {}",
+            entry.slot,
+            entry
+                .block_time
+                .and_then(|block_time| DateTime::from_timestamp(block_time, 0))
+                .map_or("-".to_string(), |datetime| datetime.to_rfc3339()),
+            entry.signature,
+            entry.kind,
+            entry.worker.as_deref().unwrap_or("-"),
+            entry
+                .compute_units
+                .map_or("-".to_string(), |units| units.to_string()),
+            match &entry.error {
+                None if entry.success => "ok".to_string(),
+                None => "failed".to_string(),
+                Some(error) => format!("failed: {}", error),
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Decodes the execution of a transaction of the thread, the other transactions (e.g. create, update) are skipped.
+fn decode_thread_log(
+    signature: &str,
+    transaction: EncodedConfirmedTransactionWithStatusMeta,
+) -> Option<ThreadLogEntry> {
+    let versioned_transaction = transaction.transaction.transaction.decode()?;
+    let account_keys = versioned_transaction.message.static_account_keys();
+    let (kind, worker) = versioned_transaction
+        .message
+        .instructions()
+        .iter()
+        .find_map(|ix| {
+            if account_keys.get(ix.program_id_index as usize) != Some(&sablier_thread_program::ID) {
+                return None;
+            }
+            let (kind, worker_index) = if ix.data.starts_with(&ThreadExecData::DISCRIMINATOR) {
+                ("exec", exec_worker_index())
+            } else if ix.data.starts_with(&ThreadKickoffData::DISCRIMINATOR) {
+                ("kickoff", kickoff_worker_index())
+            } else {
+                return None;
+            };
+            let worker = ix
+                .accounts
+                .get(worker_index)
+                .and_then(|key_index| account_keys.get(*key_index as usize));
+            Some((kind, worker.map(Pubkey::to_string)))
+        })?;
+
+    let meta = transaction.transaction.meta;
+    let success = meta.as_ref().map_or(false, |meta| meta.err.is_none());
+    let compute_units = meta
+        .as_ref()
+        .and_then(|meta| Option::<u64>::from(meta.compute_units_consumed.clone()));
+    // The program logs the failure of the inner instruction before the runtime logs the error.
+    let error = meta.as_ref().filter(|_| !success).and_then(|meta| {
+        Option::<Vec<String>>::from(meta.log_messages.clone())?
+            .into_iter()
+            .find(|log| log.contains("Error") || log.contains("failed"))
+            .or_else(|| meta.err.as_ref().map(|err| err.to_string()))
+    });
+    Some(ThreadLogEntry {
+        slot: transaction.slot,
+        block_time: transaction.block_time,
+        signature: signature.to_string(),
+        kind,
+        worker,
+        compute_units,
+        success,
+        error,
+    })
+}
+
+/// The position of the worker in the accounts of the exec instruction.
+fn exec_worker_index() -> usize {
+    let worker = Pubkey::new_unique();
+    sablier_thread_program::accounts::ThreadExec {
+        fee: Pubkey::default(),
+        pool: Pubkey::default(),
+        signatory: Pubkey::default(),
+        thread: Pubkey::default(),
+        worker,
+    }
+    .to_account_metas(None)
+    .iter()
+    .position(|meta| meta.pubkey == worker)
+    .unwrap_or_default()
+}
+
+/// The position of the worker in the accounts of the kickoff instruction.
+fn kickoff_worker_index() -> usize {
+    let worker = Pubkey::new_unique();
+    sablier_thread_program::accounts::ThreadKickoff {
+        signatory: Pubkey::default(),
+        thread: Pubkey::default(),
+        worker,
+    }
+    .to_account_metas(None)
+    .iter()
+    .position(|meta| meta.pubkey == worker)
+    .unwrap_or_default()
+}
+
+pub fn pause(
+    client: &Client,
+    submitter: &Submitter,
+    thread_pubkey: Pubkey,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    fetch_authorized_thread(client, submitter, thread_pubkey)?;
+    if submitter.submit(client, &[pause_ix(submitter.authority(), thread_pubkey)])? {
+        get(client, thread_pubkey, output)?;
+    }
+    Ok(())
+}
+
+fn pause_ix(authority: Pubkey, thread_pubkey: Pubkey) -> Instruction {
+    Instruction {
         program_id: sablier_thread_program::ID,
         accounts: sablier_thread_program::accounts::ThreadPause {
-            authority: client.payer_pubkey(),
+            authority,
             thread: thread_pubkey,
         }
         .to_account_metas(Some(false)),
         data: sablier_thread_program::instruction::ThreadPause {}.data(),
-    };
-    client.send_and_confirm(&[ix], &[client.payer()]).unwrap();
-    get(client, thread_pubkey)?;
+    }
+}
+
+pub fn resume(
+    client: &Client,
+    submitter: &Submitter,
+    thread_pubkey: Pubkey,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    fetch_authorized_thread(client, submitter, thread_pubkey)?;
+    if submitter.submit(client, &[resume_ix(submitter.authority(), thread_pubkey)])? {
+        get(client, thread_pubkey, output)?;
+    }
     Ok(())
 }
 
-pub fn resume(client: &Client, id: String) -> Result<(), CliError> {
-    let thread_pubkey = Thread::pubkey(client.payer_pubkey(), id.into_bytes(), None);
-    let ix = Instruction {
+fn resume_ix(authority: Pubkey, thread_pubkey: Pubkey) -> Instruction {
+    Instruction {
         program_id: sablier_thread_program::ID,
         accounts: sablier_thread_program::accounts::ThreadResume {
-            authority: client.payer_pubkey(),
+            authority,
             thread: thread_pubkey,
         }
         .to_account_metas(Some(false)),
         data: sablier_thread_program::instruction::ThreadResume {}.data(),
-    };
-    client.send_and_confirm(&[ix], &[client.payer()]).unwrap();
-    get(client, thread_pubkey)?;
-    Ok(())
+    }
 }
 
-pub fn reset(client: &Client, id: String) -> Result<(), CliError> {
-    let thread_pubkey = Thread::pubkey(client.payer_pubkey(), id.into_bytes(), None);
+pub fn reset(
+    client: &Client,
+    submitter: &Submitter,
+    thread_pubkey: Pubkey,
+    yes: bool,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let thread = fetch_authorized_thread(client, submitter, thread_pubkey)?;
+    confirm(
+        "reset the thread",
+        &format!(
+            "{}\nResetting drops the exec context and the next instruction of the thread, \
+            it starts over from its trigger as if just created",
+            thread_text(client, thread_pubkey, &thread)
+        ),
+        Confirmation::YesNo,
+        yes,
+    )?;
     let ix = Instruction {
         program_id: sablier_thread_program::ID,
         accounts: sablier_thread_program::accounts::ThreadReset {
-            authority: client.payer_pubkey(),
+            authority: submitter.authority(),
             thread: thread_pubkey,
         }
         .to_account_metas(Some(false)),
         data: sablier_thread_program::instruction::ThreadReset {}.data(),
     };
-    client.send_and_confirm(&[ix], &[client.payer()]).unwrap();
-    get(client, thread_pubkey)?;
+    if submitter.submit(client, &[ix])? {
+        get(client, thread_pubkey, output)?;
+    }
     Ok(())
 }
 
 pub fn update(
     client: &Client,
+    submitter: &Submitter,
     id: String,
-    rate_limit: Option<u64>,
-    schedule: Option<String>,
+    domain: Option<String>,
+    mut settings: ThreadSettings,
+    skippable: Option<bool>,
+    output: OutputFormat,
 ) -> Result<(), CliError> {
-    let thread_pubkey = Thread::pubkey(client.payer_pubkey(), id.into_bytes(), None);
-    let trigger = schedule.map(|schedule| Trigger::Cron {
-        schedule,
-        skippable: true,
-    });
-    let settings = ThreadSettings {
-        fee: None,
-        instructions: None,
-        name: None,
-        rate_limit,
-        trigger,
+    let thread_pubkey = thread_pubkey(submitter.authority(), id, domain);
+    let thread = fetch_authorized_thread(client, submitter, thread_pubkey)?;
+    settings.trigger = updated_trigger(thread.trigger(), settings.trigger, skippable)?;
+    let ix = update_ix(submitter.authority(), thread_pubkey, settings);
+    if submitter.submit(client, &[ix])? {
+        get(client, thread_pubkey, output)?;
+    }
+    Ok(())
+}
+
+/// Replaces the instruction of the thread at the index, or appends it, keeping the other instructions as they are.
+/// The whole instruction list is sent in the update, so it must fit in a transaction,
+/// and the thread account can only grow by the max reallocation of an instruction.
+pub fn set_instruction(
+    client: &Client,
+    submitter: &Submitter,
+    thread_pubkey: Pubkey,
+    index: Option<usize>,
+    instruction: SerializableInstruction,
+    yes: bool,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let thread = fetch_authorized_thread(client, submitter, thread_pubkey)?;
+    let mut instructions = thread.instructions();
+    let (index, old) = match index {
+        Some(index) if index >= instructions.len() => {
+            return Err(CliError::BadParameter(format!(
+                "index: the thread {} has {} instructions, the index must be lower than {}, or pass --append",
+                thread_pubkey,
+                instructions.len(),
+                instructions.len()
+            )))
+        }
+        Some(index) => (
+            index,
+            Some(std::mem::replace(&mut instructions[index], instruction.clone())),
+        ),
+        None => {
+            instructions.push(instruction.clone());
+            (instructions.len() - 1, None)
+        }
     };
-    let ix = Instruction {
+    if old.as_ref() == Some(&instruction) {
+        println!(
+            "The instruction {} of the thread is unchanged, nothing was sent",
+            index
+        );
+        return Ok(());
+    }
+
+    // The thread program reallocates the thread to the length of its serialized state.
+    let account = fetch_account(client, thread_pubkey, "thread")?;
+    let data_len = match &thread {
+        VersionedThread::V1(thread) => {
+            let mut thread = thread.clone();
+            thread.instructions = instructions.clone();
+            8 + thread
+                .try_to_vec()
+                .map_err(|err| CliError::BadParameter(format!("instructions: {}", err)))?
+                .len()
+        }
+    };
+    if data_len > account.data.len() + MAX_PERMITTED_DATA_INCREASE {
+        return Err(CliError::BadParameter(format!(
+            "instructions-file: the thread would grow by {} bytes, more than the {} bytes it can be reallocated by at once",
+            data_len - account.data.len(),
+            MAX_PERMITTED_DATA_INCREASE
+        )));
+    }
+    let ix = update_ix(
+        submitter.authority(),
+        thread_pubkey,
+        ThreadSettings {
+            fee: None,
+            instructions: Some(instructions),
+            name: None,
+            rate_limit: None,
+            trigger: None,
+        },
+    );
+    if !client.fits_in_transaction(&[ix.clone()], &client.payer_pubkey()) {
+        return Err(CliError::BadParameter(
+            "instructions-file: the update carries every instruction of the thread, \
+            and with this one they don't fit in a transaction"
+                .into(),
+        ));
+    }
+
+    confirm(
+        &match old {
+            Some(_) => format!("replace the instruction {}", index),
+            None => "append the instruction".to_string(),
+        },
+        &format!(
+            "Thread: {}\n{}",
+            thread_pubkey,
+            instruction_diff(index, old.as_ref(), &instruction)
+        ),
+        Confirmation::YesNo,
+        yes,
+    )?;
+    if submitter.submit(client, &[ix])? {
+        get(client, thread_pubkey, output)?;
+    }
+    Ok(())
+}
+
+/// Describes the change of an instruction of a thread, the data by its length and hash.
+fn instruction_diff(
+    index: usize,
+    old: Option<&SerializableInstruction>,
+    new: &SerializableInstruction,
+) -> String {
+    let describe = |describe: fn(&SerializableInstruction) -> String| {
+        format!(
+            "{} -> {}",
+            old.map_or("none".to_string(), describe),
+            describe(new)
+        )
+    };
+    format!(
+        "Instruction {}{}:\n  program: {}\n  accounts: {}{}\n  data length: {}\n  data hash: {}",
+        index,
+        if old.is_none() { " (appended)" } else { "" },
+        describe(|ix| ix.program_id.to_string()),
+        describe(|ix| ix.accounts.len().to_string()),
+        match old {
+            Some(old) if old.accounts != new.accounts => " (changed)",
+            _ => "",
+        },
+        describe(|ix| ix.data.len().to_string()),
+        describe(|ix| hash(&ix.data).to_string()),
+    )
+}
+
+/// Builds the thread update instruction, the authority pays for the reallocation of the thread.
+fn update_ix(authority: Pubkey, thread_pubkey: Pubkey, settings: ThreadSettings) -> Instruction {
+    Instruction {
         program_id: sablier_thread_program::ID,
         accounts: sablier_thread_program::accounts::ThreadUpdate {
-            payer: client.payer_pubkey(),
-            authority: client.payer_pubkey(),
+            payer: authority,
+            authority,
             system_program: system_program::ID,
             thread: thread_pubkey,
         }
         .to_account_metas(Some(false)),
         data: sablier_thread_program::instruction::ThreadUpdate { settings }.data(),
+    }
+}
+
+/// The outcome of a thread of a batch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum BatchStatus {
+    /// Planned by a dry run.
+    Planned,
+    Succeeded,
+    /// Printed unsigned with `--dump-transaction`.
+    Dumped,
+    Failed,
+    /// Not sent after a failed transaction.
+    Skipped,
+}
+
+#[derive(Serialize)]
+struct BatchResult {
+    thread: String,
+    address: String,
+    /// The 1-based index of the transaction the thread's instruction is sent in.
+    transaction: Option<usize>,
+    status: BatchStatus,
+    error: Option<String>,
+}
+
+/// Applies the action to the threads in as few transactions as fit, sent one after the other.
+/// Stops at the first failure unless `continue_on_error` is set, and reports the outcome of every thread.
+pub fn batch(
+    client: &Client,
+    submitter: &Submitter,
+    action: BatchAction,
+    threads: Vec<BatchThread>,
+    continue_on_error: bool,
+    dry_run: bool,
+    yes: bool,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let authority = submitter.authority();
+    let mut results = vec![];
+    let mut entries = vec![];
+    for thread in threads {
+        let address = match thread.address {
+            Some(address) => address,
+            None => thread_pubkey(
+                authority,
+                thread.id.clone().unwrap_or_default(),
+                thread.domain,
+            ),
+        };
+        let mut result = BatchResult {
+            thread: thread.id.unwrap_or_else(|| address.to_string()),
+            address: address.to_string(),
+            transaction: None,
+            status: BatchStatus::Planned,
+            error: None,
+        };
+        match batch_ix(client, submitter, action, address, thread.settings) {
+            Ok(ix) => entries.push((result, ix)),
+            Err(err) if continue_on_error => {
+                result.status = BatchStatus::Failed;
+                result.error = Some(err.to_string());
+                results.push(result);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    // A dry run sends nothing, there is nothing to confirm.
+    if !dry_run && !entries.is_empty() {
+        confirm(
+            &format!(
+                "{} {} threads of {}",
+                action.as_str(),
+                entries.len(),
+                authority
+            ),
+            "",
+            Confirmation::YesNo,
+            yes,
+        )?;
+    }
+    send_batch(
+        client,
+        submitter,
+        entries,
+        results,
+        continue_on_error,
+        dry_run,
+        output,
+    )
+}
+
+/// Pauses, or resumes, every thread of the authority whose id contains the pattern, in as few transactions as fit.
+/// The threads already paused, or running, are left out, and the ones which couldn't be changed are reported.
+pub fn pause_all(
+    client: &Client,
+    submitter: &Submitter,
+    pause: bool,
+    pattern: Option<String>,
+    yes: bool,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let authority = submitter.authority();
+    let mut accounts = fetch_authority_threads(client, authority)?;
+    accounts.sort_by_key(|(address, _)| *address);
+
+    let mut results = vec![];
+    let mut entries = vec![];
+    let mut unchanged = 0;
+    for (address, account) in accounts {
+        let thread = VersionedThread::try_deserialize(&mut account.data.as_slice());
+        let id = match &thread {
+            Ok(thread) => bytes_to_string(&thread.id()),
+            Err(_) => address.to_string(),
+        };
+        // The threads which can't be read have no id to match, they are only reported when every thread is.
+        if pattern
+            .as_ref()
+            .map_or(false, |pattern| thread.is_err() || !id.contains(pattern))
+        {
+            continue;
+        }
+        let mut result = BatchResult {
+            thread: id,
+            address: address.to_string(),
+            transaction: None,
+            status: BatchStatus::Planned,
+            error: None,
+        };
+        match thread {
+            Ok(thread) if thread.paused() == pause => unchanged += 1,
+            Ok(_) if pause => entries.push((result, pause_ix(authority, address))),
+            Ok(_) => entries.push((result, resume_ix(authority, address))),
+            Err(_) => {
+                result.status = BatchStatus::Failed;
+                result.error = Some(
+                    CliError::deserialization_failed("thread", address, &account.data).to_string(),
+                );
+                results.push(result);
+            }
+        }
+    }
+
+    let (verb, state) = if pause {
+        ("pause", "paused")
+    } else {
+        ("resume", "running")
     };
-    client.send_and_confirm(&[ix], &[client.payer()]).unwrap();
-    get(client, thread_pubkey)?;
+    if unchanged > 0 {
+        eprintln!("{} threads are already {}", unchanged, state);
+    }
+    if !entries.is_empty() {
+        confirm(
+            &format!("{} {} threads of {}", verb, entries.len(), authority),
+            "",
+            Confirmation::YesNo,
+            yes,
+        )?;
+    }
+    send_batch(client, submitter, entries, results, true, false, output)
+}
+
+/// Sends the instructions in as few transactions as fit, with a progress bar, and reports the outcome of every thread.
+/// The results are those of the threads which failed before any transaction.
+fn send_batch(
+    client: &Client,
+    submitter: &Submitter,
+    entries: Vec<(BatchResult, Instruction)>,
+    mut results: Vec<BatchResult>,
+    continue_on_error: bool,
+    dry_run: bool,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let authority = submitter.authority();
+    let chunks = chunk_instructions(entries, |ixs| client.fits_in_transaction(ixs, &authority));
+    let total = chunks.len();
+    // The progress goes to stderr with the signatures, to keep the report parsable.
+    let progress = if dry_run {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(total as u64)
+    };
+    if let Ok(style) = ProgressStyle::with_template(
+        "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} transactions ({eta})",
+    ) {
+        progress.set_style(style.progress_chars("#>-"));
+    }
+    let mut failed = false;
+    for (index, chunk) in chunks.into_iter().enumerate() {
+        let (mut chunk_results, ixs): (Vec<_>, Vec<_>) = chunk.into_iter().unzip();
+        let status = if dry_run {
+            BatchStatus::Planned
+        } else if failed && !continue_on_error {
+            BatchStatus::Skipped
+        } else {
+            // The bar is hidden while sending, the signers may prompt for a confirmation.
+            match progress.suspend(|| submitter.submit(client, &ixs)) {
+                Ok(true) => BatchStatus::Succeeded,
+                Ok(false) => BatchStatus::Dumped,
+                Err(err) => {
+                    progress.suspend(|| eprintln!("[{}/{}] {}", index + 1, total, err));
+                    for result in &mut chunk_results {
+                        result.error = Some(err.to_string());
+                    }
+                    failed = true;
+                    BatchStatus::Failed
+                }
+            }
+        };
+        for mut result in chunk_results {
+            result.transaction = Some(index + 1);
+            result.status = status;
+            results.push(result);
+        }
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+
+    print_batch_results(&results, output)?;
+    let failures = results
+        .iter()
+        .filter(|result| result.status == BatchStatus::Failed)
+        .count();
+    if failures > 0 {
+        return Err(CliError::transaction_failed(format!(
+            "{} of {} threads failed",
+            failures,
+            results.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Builds the instruction applying the action to the thread, which must be the authority's.
+fn batch_ix(
+    client: &Client,
+    submitter: &Submitter,
+    action: BatchAction,
+    thread_pubkey: Pubkey,
+    settings: BatchSettings,
+) -> Result<Instruction, CliError> {
+    let thread = fetch_authorized_thread(client, submitter, thread_pubkey)?;
+    let authority = submitter.authority();
+    Ok(match action {
+        BatchAction::Pause => pause_ix(authority, thread_pubkey),
+        BatchAction::Resume => resume_ix(authority, thread_pubkey),
+        BatchAction::Delete => delete_ix(authority, thread_pubkey),
+        BatchAction::Update => {
+            let trigger = settings.schedule.map(|schedule| Trigger::Cron {
+                schedule,
+                skippable: true,
+            });
+            let settings = ThreadSettings {
+                fee: settings.fee,
+                instructions: None,
+                name: settings.name,
+                rate_limit: settings.rate_limit,
+                trigger: updated_trigger(thread.trigger(), trigger, settings.skippable)
+                    .map_err(|err| CliError::BadParameter(format!("{}: {}", thread_pubkey, err)))?,
+            };
+            update_ix(authority, thread_pubkey, settings)
+        }
+    })
+}
+
+/// Groups the instructions, in order, into as few transactions as fit.
+/// An instruction too large to share a transaction is sent alone.
+fn chunk_instructions<T>(
+    entries: Vec<(T, Instruction)>,
+    fits: impl Fn(&[Instruction]) -> bool,
+) -> Vec<Vec<(T, Instruction)>> {
+    let mut chunks: Vec<Vec<(T, Instruction)>> = vec![];
+    for entry in entries {
+        let fits_last = chunks.last().map_or(false, |chunk| {
+            let ixs = chunk
+                .iter()
+                .map(|(_, ix)| ix.clone())
+                .chain([entry.1.clone()])
+                .collect::<Vec<_>>();
+            fits(&ixs)
+        });
+        match chunks.last_mut() {
+            Some(chunk) if fits_last => chunk.push(entry),
+            _ => chunks.push(vec![entry]),
+        }
+    }
+    chunks
+}
+
+fn print_batch_results(results: &[BatchResult], output: OutputFormat) -> Result<(), CliError> {
+    if output == OutputFormat::Json {
+        return print_json(&results);
+    }
+    for status in [
+        BatchStatus::Planned,
+        BatchStatus::Succeeded,
+        BatchStatus::Dumped,
+        BatchStatus::Failed,
+        BatchStatus::Skipped,
+    ] {
+        let results = results
+            .iter()
+            .filter(|result| result.status == status)
+            .collect::<Vec<_>>();
+        if results.is_empty() {
+            continue;
+        }
+        println!("{:?}: {}", status, results.len());
+        for result in results {
+            let transaction = result
+                .transaction
+                .map_or_else(String::new, |index| format!(" in transaction {}", index));
+            match &result.error {
+                None => println!("  {} ({}){}", result.thread, result.address, transaction),
+                Some(error) => println!(
+                    "  {} ({}){}: {}",
+                    result.thread, result.address, transaction, error
+                ),
+            }
+        }
+    }
     Ok(())
 }
 
+pub fn watch(
+    client: &Client,
+    address: Pubkey,
+    websocket_url: &str,
+    interval: Option<u64>,
+) -> Result<(), CliError> {
+    let account = fetch_thread_account(client, address)?;
+    let mut fields = watched_fields(address, &account)?;
+    println!("[{}] Watching thread {}", now(), address);
+    for (name, value) in &fields {
+        println!("  {}: {}", name, value);
+    }
+
+    let mut print_changes = |account: &Account| -> Result<(), CliError> {
+        let next_fields = watched_fields(address, account)?;
+        for change in diff_fields(&fields, &next_fields) {
+            println!("[{}] {}", now(), change);
+        }
+        fields = next_fields;
+        Ok(())
+    };
+
+    match interval {
+        Some(interval) => loop {
+            std::thread::sleep(Duration::from_secs(interval));
+            match client
+                .get_account_with_commitment(&address, client.commitment())?
+                .value
+            {
+                Some(account) => print_changes(&account)?,
+                None => {
+                    println!("[{}] The thread was deleted", now());
+                    return Ok(());
+                }
+            }
+        },
+        None => {
+            let (_subscription, receiver) = PubsubClient::account_subscribe(
+                websocket_url,
+                &address,
+                Some(RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    ..RpcAccountInfoConfig::default()
+                }),
+            )
+            .map_err(|err| {
+                CliError::BadClient(format!(
+                    "failed to subscribe to {}, poll with --interval instead: {}",
+                    websocket_url, err
+                ))
+            })?;
+            for response in receiver {
+                match response.value.decode::<Account>() {
+                    Some(account) if account.lamports > 0 => print_changes(&account)?,
+                    _ => {
+                        println!("[{}] The thread was deleted", now());
+                        return Ok(());
+                    }
+                }
+            }
+            Err(CliError::BadClient(format!(
+                "the subscription to {} was closed",
+                websocket_url
+            )))
+        }
+    }
+}
+
+/// The fields of a thread watched for changes, by name.
+fn watched_fields(
+    address: Pubkey,
+    account: &Account,
+) -> Result<Vec<(&'static str, String)>, CliError> {
+    let thread = VersionedThread::try_deserialize(&mut account.data.as_slice())
+        .map_err(|_err| CliError::deserialization_failed("thread", address, &account.data))?;
+    let exec_context = thread.exec_context();
+    let exec_field =
+        |field: fn(&ExecContext) -> String| exec_context.as_ref().map_or("none".to_string(), field);
+    Ok(vec![
+        ("paused", thread.paused().to_string()),
+        ("trigger", trigger_summary(&thread.trigger())),
+        ("fee", thread.fee().to_string()),
+        ("rate_limit", thread.rate_limit().to_string()),
+        ("instructions", thread.instructions().len().to_string()),
+        (
+            "next_instruction",
+            thread
+                .next_instruction()
+                .map_or("none".to_string(), |ix| ix.program_id.to_string()),
+        ),
+        (
+            "exec_index",
+            exec_field(|context| context.exec_index.to_string()),
+        ),
+        (
+            "last_exec_at",
+            exec_field(|context| context.last_exec_at.to_string()),
+        ),
+        (
+            "execs_since_slot",
+            exec_field(|context| context.execs_since_slot.to_string()),
+        ),
+        (
+            "trigger_context",
+            exec_field(|context| format!("{:?}", context.trigger_context)),
+        ),
+        (
+            "balance",
+            format!("{} SOL", lamports_to_sol(account.lamports)),
+        ),
+    ])
+}
+
+/// The changed fields, as `field: old -> new`.
+fn diff_fields(old: &[(&'static str, String)], new: &[(&'static str, String)]) -> Vec<String> {
+    old.iter()
+        .zip(new)
+        .filter(|((_, old), (_, new))| old != new)
+        .map(|((name, old), (_, new))| format!("{}: {} -> {}", name, old, new))
+        .collect()
+}
+
+/// The current time, the changes are printed with.
+fn now() -> String {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .and_then(|elapsed| DateTime::from_timestamp(elapsed.as_secs() as i64, 0))
+        .map_or_else(String::new, |datetime| datetime.to_rfc3339())
+}
+
+/// Resolves the trigger to update the thread with, the program only accepts a trigger of the same kind.
+/// A cron or periodic trigger keeps its skippability unless it is overridden.
+/// A periodic trigger of the previous layout is migrated to an interval trigger when it is updated.
+fn updated_trigger(
+    current: Trigger,
+    trigger: Option<Trigger>,
+    skippable: Option<bool>,
+) -> Result<Option<Trigger>, CliError> {
+    let trigger = match (trigger, skippable) {
+        (None, None) => return Ok(None),
+        (Some(trigger), _) => trigger,
+        (None, Some(_)) => current.clone(),
+    };
+    if !current.is_same_kind(&trigger) {
+        return Err(CliError::BadParameter(format!(
+            "trigger: the {} trigger of the thread cannot be changed to a {} trigger, \
+            the thread program keeps the trigger kind of a thread, create a new thread instead",
+            trigger_kind(&current),
+            trigger_kind(&trigger)
+        )));
+    }
+    let current_skippable = match &current {
+        Trigger::Cron { skippable, .. } => *skippable,
+        current => current
+            .interval()
+            .map_or(true, |(_interval_seconds, skippable)| skippable),
+    };
+    match trigger {
+        Trigger::Cron { schedule, .. } => Ok(Some(Trigger::Cron {
+            schedule,
+            skippable: skippable.unwrap_or(current_skippable),
+        })),
+        Trigger::Periodic {
+            delay: interval_seconds,
+        }
+        | Trigger::Interval {
+            interval_seconds, ..
+        } => Ok(Some(Trigger::Interval {
+            interval_seconds,
+            skippable: skippable.unwrap_or(current_skippable),
+        })),
+        _ if skippable.is_some() => Err(CliError::BadParameter(format!(
+            "skippable: only applies to a cron or periodic trigger, not a {} trigger",
+            trigger_kind(&trigger)
+        ))),
+        trigger => Ok(Some(trigger)),
+    }
+}
+
+fn trigger_kind(trigger: &Trigger) -> &'static str {
+    match trigger {
+        Trigger::Account { .. } => "account",
+        Trigger::Cron { .. } => "cron",
+        Trigger::Now => "now",
+        Trigger::Slot { .. } => "slot",
+        Trigger::Epoch { .. } => "epoch",
+        Trigger::Timestamp { .. } => "timestamp",
+        Trigger::Pyth { .. } => "pyth",
+        Trigger::Periodic { .. } | Trigger::Interval { .. } => "periodic",
+    }
+}
+
 pub fn parse_pubkey_from_id_or_address(
     authority: Pubkey,
     id: Option<String>,
+    domain: Option<String>,
     address: Option<Pubkey>,
 ) -> Result<Pubkey, CliError> {
-    let address_from_id = id.map(|str| Thread::pubkey(authority, str.into(), None));
+    let address_from_id = id.map(|id| thread_pubkey(authority, id, domain));
     address.or(address_from_id).ok_or(CliError::InvalidAddress)
 }
+
+/// The address a thread is created at, derived offline.
+#[derive(Serialize)]
+struct ThreadPubkeyOutput {
+    address: String,
+    bump: u8,
+    authority: String,
+    id: String,
+    domain: Option<String>,
+}
+
+/// Prints the address of the authority's thread with the id, and its bump.
+pub fn pubkey(
+    authority: Pubkey,
+    id: Vec<u8>,
+    domain: Option<String>,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let (address, bump) = Thread::find_pubkey(
+        authority,
+        id.clone(),
+        domain.clone().map(String::into_bytes),
+    );
+    if output == OutputFormat::Json {
+        return print_json(&ThreadPubkeyOutput {
+            address: address.to_string(),
+            bump,
+            authority: authority.to_string(),
+            id: bytes_to_string(&id),
+            domain,
+        });
+    }
+    println!("Address: {}\nBump: {}", address, bump);
+    Ok(())
+}
+
+/// Derives the address of the authority's thread, the same way the program does on create.
+fn thread_pubkey(authority: Pubkey, id: String, domain: Option<String>) -> Pubkey {
+    Thread::pubkey(authority, id.into_bytes(), domain.map(String::into_bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{AccountSerialize, AnchorDeserialize};
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use sablier_thread_program::state::{ClockData, TriggerContext};
+    use solana_transaction_status::{
+        EncodedTransaction, EncodedTransactionWithStatusMeta, TransactionBinaryEncoding,
+    };
+
+    use super::*;
+
+    #[test]
+    fn batched_instructions_fill_the_transactions_in_order() {
+        let authority = Pubkey::new_unique();
+        let client = Client::new(
+            crate::signer::LabeledSigner::new(
+                "payer",
+                Box::new(solana_sdk::signature::Keypair::new()),
+            ),
+            "http://127.0.0.1:1".into(),
+        );
+        let entries = (0..80)
+            .map(|index| (index, pause_ix(authority, Pubkey::new_unique())))
+            .collect::<Vec<_>>();
+        let fits = |ixs: &[Instruction]| client.fits_in_transaction(ixs, &authority);
+
+        let chunks = chunk_instructions(entries, fits);
+        assert!(chunks.len() > 1);
+        let ixs = |chunk: &[(usize, Instruction)]| {
+            chunk.iter().map(|(_, ix)| ix.clone()).collect::<Vec<_>>()
+        };
+        for (index, chunk) in chunks.iter().enumerate() {
+            assert!(fits(&ixs(chunk)));
+            if let Some(next) = chunks.get(index + 1) {
+                let mut grown = ixs(chunk);
+                grown.push(next[0].1.clone());
+                assert!(!fits(&grown));
+            }
+        }
+        assert_eq!(
+            chunks
+                .iter()
+                .flatten()
+                .map(|(index, _)| *index)
+                .collect::<Vec<_>>(),
+            (0..80).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn domained_thread_is_created_at_the_address_it_is_looked_up_at() {
+        let authority = Pubkey::new_unique();
+        let (created_pubkey, ix) = create_ix(
+            authority,
+            "payouts".to_string(),
+            Some("treasury".to_string()),
+            vec![],
+            Trigger::Now,
+        );
+
+        // The program derives the thread address from the id and domain of the instruction data.
+        let data = sablier_thread_program::instruction::ThreadCreate::try_from_slice(&ix.data[8..])
+            .unwrap();
+        assert_eq!(data.domain, Some(b"treasury".to_vec()));
+        assert_eq!(
+            created_pubkey,
+            Thread::pubkey(authority, data.id.clone(), data.domain.clone())
+        );
+        assert!(ix.accounts.iter().any(|meta| meta.pubkey == created_pubkey));
+        assert_eq!(
+            parse_pubkey_from_id_or_address(
+                authority,
+                Some("payouts".to_string()),
+                Some("treasury".to_string()),
+                None
+            )
+            .unwrap(),
+            created_pubkey
+        );
+        assert_ne!(
+            created_pubkey,
+            thread_pubkey(authority, "payouts".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn updated_trigger_keeps_the_kind_and_skippability_of_the_thread() {
+        let cron = |schedule: &str, skippable| Trigger::Cron {
+            schedule: schedule.to_string(),
+            skippable,
+        };
+        assert_eq!(
+            updated_trigger(
+                cron("0 * * * * *", false),
+                Some(cron("1 * * * * *", true)),
+                None
+            )
+            .unwrap(),
+            Some(cron("1 * * * * *", false))
+        );
+        assert_eq!(
+            updated_trigger(cron("0 * * * * *", false), None, Some(true)).unwrap(),
+            Some(cron("0 * * * * *", true))
+        );
+        assert_eq!(updated_trigger(Trigger::Now, None, None).unwrap(), None);
+        assert!(updated_trigger(cron("0 * * * * *", true), Some(Trigger::Now), None).is_err());
+        assert!(updated_trigger(Trigger::Slot { slot: 1 }, None, Some(false)).is_err());
+        let interval = |interval_seconds, skippable| Trigger::Interval {
+            interval_seconds,
+            skippable,
+        };
+        assert_eq!(
+            updated_trigger(interval(45, false), Some(interval(60, true)), None).unwrap(),
+            Some(interval(60, false))
+        );
+        assert_eq!(
+            updated_trigger(interval(45, false), None, Some(true)).unwrap(),
+            Some(interval(45, true))
+        );
+        // A periodic trigger of the previous layout is migrated, it didn't skip missed moments.
+        let periodic = Trigger::Periodic { delay: 45 };
+        assert_eq!(
+            updated_trigger(periodic.clone(), Some(interval(60, true)), None).unwrap(),
+            Some(interval(60, false))
+        );
+        assert_eq!(
+            updated_trigger(periodic, None, Some(true)).unwrap(),
+            Some(interval(45, true))
+        );
+        assert!(updated_trigger(
+            interval(45, true),
+            Some(Trigger::Periodic { delay: 45 }),
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn decoded_threads_are_checked_by_discriminator() {
+        let thread = Thread {
+            authority: Pubkey::new_unique(),
+            bump: 0,
+            created_at: ClockData {
+                slot: 0,
+                epoch: 0,
+                unix_timestamp: 0,
+            },
+            domain: Some(b"acme".to_vec()),
+            exec_context: None,
+            fee: 1_000,
+            id: b"thread".to_vec(),
+            instructions: vec![],
+            next_instruction: None,
+            paused: false,
+            rate_limit: 1,
+            trigger: Trigger::Now,
+        };
+        let mut data = vec![];
+        thread.try_serialize(&mut data).unwrap();
+        let decoded = decode_thread("data", &data).unwrap();
+        assert_eq!(decoded.id(), b"thread".to_vec());
+        assert_eq!(decoded.domain(), Some(b"acme".to_vec()));
+
+        let mut worker = data.clone();
+        worker[..8].copy_from_slice(&Worker::DISCRIMINATOR);
+        let err = decode_thread("data", &worker).unwrap_err().to_string();
+        assert!(err.starts_with("data could not be deserialized as a thread account"));
+        assert!(err.ends_with("of a Worker account"));
+        assert!(matches!(
+            decode_thread("data", &data[..12]),
+            Err(CliError::DeserializationFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn watched_changes_are_printed_field_by_field() {
+        let account = |paused, exec_context| {
+            let thread = Thread {
+                authority: Pubkey::new_unique(),
+                bump: 0,
+                created_at: ClockData {
+                    slot: 0,
+                    epoch: 0,
+                    unix_timestamp: 0,
+                },
+                domain: None,
+                exec_context,
+                fee: 1_000,
+                id: b"thread".to_vec(),
+                instructions: vec![],
+                next_instruction: None,
+                paused,
+                rate_limit: 1,
+                trigger: Trigger::Now,
+            };
+            let mut data = vec![];
+            thread.try_serialize(&mut data).unwrap();
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner: sablier_thread_program::ID,
+                ..Account::default()
+            }
+        };
+        let address = Pubkey::new_unique();
+        let old = watched_fields(address, &account(false, None)).unwrap();
+        let new = watched_fields(
+            address,
+            &account(
+                true,
+                Some(ExecContext {
+                    exec_index: 0,
+                    execs_since_reimbursement: 0,
+                    execs_since_slot: 1,
+                    last_exec_at: 42,
+                    trigger_context: TriggerContext::Now,
+                }),
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            diff_fields(&old, &new),
+            vec![
+                "paused: false -> true",
+                "exec_index: none -> 0",
+                "last_exec_at: none -> 42",
+                "execs_since_slot: none -> 1",
+                "trigger_context: none -> Now",
+            ]
+        );
+        assert!(diff_fields(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn set_instructions_are_compared_by_program_accounts_and_data() {
+        let program_id = Pubkey::new_from_array([1; 32]);
+        let old = SerializableInstruction {
+            program_id,
+            accounts: vec![],
+            data: vec![1, 2, 3],
+        };
+        let new = SerializableInstruction {
+            data: vec![1, 2, 3, 4],
+            ..old.clone()
+        };
+        let diff = instruction_diff(2, Some(&old), &new);
+        assert!(diff.starts_with(&format!(
+            "Instruction 2:\n  program: {} -> {}\n  accounts: 0 -> 0\n  data length: 3 -> 4\n  data hash: ",
+            program_id, program_id
+        )));
+        assert!(instruction_diff(0, None, &new).starts_with(&format!(
+            "Instruction 0 (appended):\n  program: none -> {}\n  accounts: none -> 0\n",
+            program_id
+        )));
+    }
+
+    #[test]
+    fn failed_exec_is_decoded_with_its_worker_and_error() {
+        let (signatory, thread, worker) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let ix = Instruction {
+            program_id: sablier_thread_program::ID,
+            accounts: sablier_thread_program::accounts::ThreadExec {
+                fee: Pubkey::new_unique(),
+                pool: Pubkey::new_unique(),
+                signatory,
+                thread,
+                worker,
+            }
+            .to_account_metas(Some(true)),
+            data: ThreadExecData {}.data(),
+        };
+        let transaction = Transaction::new_with_payer(&[ix], Some(&signatory));
+        let encoded = STANDARD.encode(bincode::serialize(&transaction).unwrap());
+        let meta = serde_json::from_value(serde_json::json!({
+            "err": {"InstructionError": [0, {"Custom": 1}]},
+            "status": {"Err": {"InstructionError": [0, {"Custom": 1}]}},
+            "fee": 5000,
+            "preBalances": [],
+            "postBalances": [],
+            "logMessages": [
+                "Program log: Instruction: ThreadExec",
+                "Program log: AnchorError occurred. Error Code: InvalidThreadResponse.",
+            ],
+            "computeUnitsConsumed": 1234,
+        }))
+        .unwrap();
+        let transaction = EncodedConfirmedTransactionWithStatusMeta {
+            slot: 42,
+            block_time: Some(1_700_000_000),
+            transaction: EncodedTransactionWithStatusMeta {
+                transaction: EncodedTransaction::Binary(encoded, TransactionBinaryEncoding::Base64),
+                meta: Some(meta),
+                version: None,
+            },
+        };
+
+        let entry = decode_thread_log("signature", transaction).unwrap();
+        assert_eq!(entry.kind, "exec");
+        assert_eq!(entry.worker, Some(worker.to_string()));
+        assert_eq!(entry.compute_units, Some(1234));
+        assert!(!entry.success);
+        assert_eq!(
+            entry.error.as_deref(),
+            Some("Program log: AnchorError occurred. Error Code: InvalidThreadResponse.")
+        );
+    }
+}
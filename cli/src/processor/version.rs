@@ -0,0 +1,397 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anchor_lang::{
+    error::ErrorCode,
+    solana_program::{instruction::Instruction, system_program},
+    AnchorDeserialize, InstructionData, ToAccountMetas,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::crate_version;
+use sablier_utils::CrateInfo;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::InstructionError,
+    message::Message,
+    pubkey::Pubkey,
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::{
+    client::Client,
+    config::CliConfig,
+    errors::CliError,
+    output::{print_json, OutputFormat, ProgramVersionOutput, VersionOutput},
+    print::print_style,
+    print_warn,
+};
+
+/// How long the program versions of a cluster are trusted by the check of the other commands.
+const VERSION_CHECK_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Whether a program can be used by a CLI of another version.
+#[derive(Debug, PartialEq)]
+pub enum Compatibility {
+    Compatible,
+    /// Why the CLI may fail to build the instructions, or to read the accounts, of the program.
+    Incompatible(String),
+}
+
+/// The versions of the CLI and of a program are compatible when their majors match, and the CLI is no newer.
+/// Before 1.0 the minor versions are the breaking ones, and must match too.
+pub fn compatibility(cli: &Version, program: &Version) -> Compatibility {
+    if cli.major != program.major {
+        Compatibility::Incompatible("their major versions differ".into())
+    } else if cli.major == 0 && cli.minor != program.minor {
+        Compatibility::Incompatible("their minor versions differ, which breaks before 1.0".into())
+    } else if cli.minor > program.minor {
+        Compatibility::Incompatible(
+            "the CLI is newer, it may send instructions or read accounts the program doesn't have"
+                .into(),
+        )
+    } else {
+        Compatibility::Compatible
+    }
+}
+
+/// The programs whose versions the CLI depends on.
+#[derive(Clone, Copy)]
+enum SablierProgram {
+    Thread,
+    Network,
+}
+
+impl SablierProgram {
+    const ALL: [Self; 2] = [Self::Thread, Self::Network];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Thread => "thread",
+            Self::Network => "network",
+        }
+    }
+
+    fn id(self) -> Pubkey {
+        match self {
+            Self::Thread => sablier_thread_program::ID,
+            Self::Network => sablier_network_program::ID,
+        }
+    }
+
+    fn crate_info_ix(self) -> Instruction {
+        match self {
+            Self::Thread => Instruction {
+                program_id: sablier_thread_program::ID,
+                accounts: sablier_thread_program::accounts::GetCrateInfo {
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(Some(false)),
+                data: sablier_thread_program::instruction::GetCrateInfo {}.data(),
+            },
+            Self::Network => Instruction {
+                program_id: sablier_network_program::ID,
+                accounts: sablier_network_program::accounts::GetCrateInfo {
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(Some(false)),
+                data: sablier_network_program::instruction::GetCrateInfo {}.data(),
+            },
+        }
+    }
+}
+
+fn cli_version() -> Version {
+    Version::parse(crate_version!()).expect("the crate versions are semver")
+}
+
+/// The version of a program, read from the release tag in the spec link of its crate info.
+fn spec_version(spec: &str) -> Option<Version> {
+    let (_, tag) = spec.split_once("/blob/v")?;
+    Version::parse(tag.split('/').next()?).ok()
+}
+
+/// Reads the version of a deployed program by simulating its `GetCrateInfo` instruction, unsigned.
+/// The RPC failures are errors, the programs without a readable version are told apart by the reason.
+fn fetch_program_version(
+    client: &Client,
+    program: SablierProgram,
+) -> Result<Result<Version, String>, CliError> {
+    let message = Message::new(&[program.crate_info_ix()], Some(&client.payer_pubkey()));
+    let result = client
+        .simulate_transaction_with_config(
+            &Transaction::new_unsigned(message),
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                commitment: Some(CommitmentConfig::processed()),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )?
+        .value;
+    if let Some(err) = result.err {
+        return Ok(Err(match err {
+            TransactionError::InstructionError(_, InstructionError::Custom(code))
+                if code == ErrorCode::InstructionFallbackNotFound as u32 =>
+            {
+                "it predates the GetCrateInfo instruction".into()
+            }
+            TransactionError::ProgramAccountNotFound
+            | TransactionError::InvalidProgramForExecution => "it is not deployed".into(),
+            err => format!("its crate info could not be simulated: {}", err),
+        }));
+    }
+    Ok(result
+        .return_data
+        .filter(|return_data| return_data.program_id == program.id().to_string())
+        .and_then(|return_data| STANDARD.decode(return_data.data.0).ok())
+        .and_then(|data| CrateInfo::try_from_slice(&data).ok())
+        .and_then(|crate_info| spec_version(&crate_info.spec))
+        .ok_or_else(|| "its crate info has no version".to_string()))
+}
+
+/// The program versions last read from each cluster, by RPC URL.
+#[derive(Default, Deserialize, Serialize)]
+struct VersionCache {
+    clusters: HashMap<String, CachedVersions>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+struct CachedVersions {
+    /// The unix timestamp of the check.
+    checked_at: u64,
+    /// The versions by program name, the programs without a readable version are left out.
+    programs: HashMap<String, String>,
+}
+
+impl VersionCache {
+    fn path() -> PathBuf {
+        CliConfig::default_home().join("version-check.json")
+    }
+
+    fn load() -> Self {
+        fs::read(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Stores the versions read from the cluster, the cache is best effort and its failures are ignored.
+    fn store(url: &str, programs: HashMap<String, String>) {
+        let mut cache = Self::load();
+        cache.clusters.insert(
+            url.to_string(),
+            CachedVersions {
+                checked_at: now(),
+                programs,
+            },
+        );
+        let path = Self::path();
+        if let (Some(dir), Ok(json)) = (path.parent(), serde_json::to_vec_pretty(&cache)) {
+            let _ = fs::create_dir_all(dir).and_then(|_| fs::write(&path, json));
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Warns on one line when the programs of the cluster are incompatible with the CLI, before the command runs.
+/// The versions are cached for a day per cluster, the RPC failures are left for the command to report.
+pub fn warn_if_incompatible(client: &Client, url: &str) {
+    let programs = match VersionCache::load().clusters.remove(url) {
+        Some(cached) if now().saturating_sub(cached.checked_at) < VERSION_CHECK_TTL.as_secs() => {
+            cached.programs
+        }
+        _ => {
+            let mut programs = HashMap::new();
+            for program in SablierProgram::ALL {
+                match fetch_program_version(client, program) {
+                    Ok(Ok(version)) => {
+                        programs.insert(program.name().to_string(), version.to_string());
+                    }
+                    Ok(Err(_reason)) => {}
+                    Err(_err) => return,
+                }
+            }
+            VersionCache::store(url, programs.clone());
+            programs
+        }
+    };
+
+    let cli = cli_version();
+    let mismatches = SablierProgram::ALL
+        .iter()
+        .filter_map(|program| {
+            let version = Version::parse(programs.get(program.name())?).ok()?;
+            match compatibility(&cli, &version) {
+                Compatibility::Compatible => None,
+                Compatibility::Incompatible(reason) => Some(format!(
+                    "the {} program is {}, {}",
+                    program.name(),
+                    version,
+                    reason
+                )),
+            }
+        })
+        .collect::<Vec<_>>();
+    if !mismatches.is_empty() {
+        print_warn!(
+            "this CLI {} may not work with the cluster, {}; see `sablier version`, or pass --skip-version-check",
+            cli,
+            mismatches.join("; ")
+        );
+    }
+}
+
+/// Prints the versions of the CLI and of the programs deployed on the cluster, and warns on the incompatible ones.
+pub fn version(client: &Client, url: &str, output: OutputFormat) -> Result<(), CliError> {
+    let cli = cli_version();
+    let mut programs = vec![];
+    let mut cached = HashMap::new();
+    for program in SablierProgram::ALL {
+        let (version, compatible, note) = match fetch_program_version(client, program)? {
+            Ok(version) => {
+                cached.insert(program.name().to_string(), version.to_string());
+                match compatibility(&cli, &version) {
+                    Compatibility::Compatible => (Some(version), Some(true), None),
+                    Compatibility::Incompatible(reason) => {
+                        (Some(version), Some(false), Some(reason))
+                    }
+                }
+            }
+            Err(reason) => (None, None, Some(reason)),
+        };
+        programs.push(ProgramVersionOutput {
+            program: program.name(),
+            address: program.id().to_string(),
+            version: version.map(|version| version.to_string()),
+            compatible,
+            note,
+        });
+    }
+    VersionCache::store(url, cached);
+
+    let output_value = VersionOutput {
+        cli: cli.to_string(),
+        geyser_plugin_interface: env!("GEYSER_INTERFACE_VERSION").to_string(),
+        cluster: url.to_string(),
+        node: client.get_version()?.solana_core,
+        programs,
+    };
+    match output {
+        OutputFormat::Json => print_json(&output_value)?,
+        OutputFormat::Text => {
+            println!("CLI: {}", output_value.cli);
+            println!(
+                "Geyser plugin interface: {}",
+                output_value.geyser_plugin_interface
+            );
+            println!("Cluster: {}", output_value.cluster);
+            println!("Node: solana-core {}", output_value.node);
+            for program in &output_value.programs {
+                let status = match (&program.version, program.compatible) {
+                    (Some(version), Some(true)) => format!("{}, compatible", version),
+                    (Some(version), _) => format!("{}, incompatible", version),
+                    (None, _) => "unknown".to_string(),
+                };
+                match &program.note {
+                    None => println!(
+                        "Program {} ({}): {}",
+                        program.program, program.address, status
+                    ),
+                    Some(note) => println!(
+                        "Program {} ({}): {}, {}",
+                        program.program, program.address, status, note
+                    ),
+                }
+            }
+        }
+    }
+    for program in &output_value.programs {
+        if program.compatible == Some(false) {
+            print_warn!(
+                "The {} program {} is incompatible with this CLI {}, {}. \
+                Install the CLI of the program's release, its accounts and instructions may not decode",
+                program.program,
+                program.version.as_deref().unwrap_or_default(),
+                cli,
+                program.note.as_deref().unwrap_or_default()
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version: &str) -> Version {
+        Version::parse(version).unwrap()
+    }
+
+    #[test]
+    fn the_cli_is_compatible_with_programs_of_its_major_and_no_older_minor() {
+        assert_eq!(
+            compatibility(&version("1.1.0"), &version("1.1.3")),
+            Compatibility::Compatible
+        );
+        assert_eq!(
+            compatibility(&version("1.1.5"), &version("1.1.0")),
+            Compatibility::Compatible
+        );
+        assert_eq!(
+            compatibility(&version("1.1.0"), &version("1.2.0")),
+            Compatibility::Compatible
+        );
+        assert!(matches!(
+            compatibility(&version("1.2.0"), &version("1.1.0")),
+            Compatibility::Incompatible(_)
+        ));
+        assert!(matches!(
+            compatibility(&version("2.0.0"), &version("1.9.0")),
+            Compatibility::Incompatible(_)
+        ));
+        assert!(matches!(
+            compatibility(&version("1.0.0"), &version("2.0.0")),
+            Compatibility::Incompatible(_)
+        ));
+    }
+
+    #[test]
+    fn minor_versions_break_before_1_0() {
+        assert_eq!(
+            compatibility(&version("0.3.1"), &version("0.3.0")),
+            Compatibility::Compatible
+        );
+        assert!(matches!(
+            compatibility(&version("0.3.0"), &version("0.4.0")),
+            Compatibility::Incompatible(_)
+        ));
+    }
+
+    #[test]
+    fn program_versions_are_read_from_their_spec_link() {
+        assert_eq!(
+            spec_version(
+                "https://github.com/sablier-xyz/sablier/blob/v1.1.0/programs/thread/Cargo.toml"
+            ),
+            Some(version("1.1.0"))
+        );
+        assert_eq!(
+            spec_version("https://github.com/sablier-xyz/sablier/blob/main/Cargo.toml"),
+            None
+        );
+        assert_eq!(spec_version(""), None);
+    }
+}
@@ -1,89 +1,347 @@
+use std::collections::HashMap;
+
 use anchor_lang::{
     solana_program::{instruction::Instruction, system_program},
-    AccountDeserialize, InstructionData, ToAccountMetas,
+    AccountDeserialize, Discriminator, InstructionData, ToAccountMetas,
 };
 use anchor_spl::{associated_token, associated_token::get_associated_token_address, token};
 use sablier_network_program::state::{
     Config, Fee, Penalty, Registry, Snapshot, SnapshotFrame, Worker, WorkerSettings,
 };
-use solana_sdk::signature::{Keypair, Signer};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+    rpc_request::MAX_MULTIPLE_ACCOUNTS,
+};
+use solana_sdk::{
+    account::Account,
+    native_token::lamports_to_sol,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
 
-use crate::{client::Client, errors::CliError};
+use crate::{
+    client::Client,
+    errors::CliError,
+    output::{
+        print_json, FeeOutput, Linked, OutputFormat, PenaltyOutput, WorkerBalanceOutput,
+        WorkerBalancesOutput, WorkerOutput,
+    },
+};
 
-pub fn get(client: &Client, id: u64) -> Result<(), CliError> {
-    let worker_pubkey = Worker::pubkey(id);
-    let worker = client
-        .get::<Worker>(&worker_pubkey)
-        .map_err(|_err| CliError::AccountDataNotParsable(worker_pubkey.to_string()))?;
+use super::{
+    confirm, fetch_account, fetch_config, fetch_optional_state, fetch_registry, fetch_worker,
+    Confirmation,
+};
+
+pub fn get(client: &Client, worker_pubkey: Pubkey, output: OutputFormat) -> Result<(), CliError> {
+    let worker = fetch_worker(client, worker_pubkey)?;
 
     // Get fee balance
     let fee_pubkey = Fee::pubkey(worker_pubkey);
-    let fee_data = client
-        .get_account_data(&fee_pubkey)
-        .map_err(|_err| CliError::AccountNotFound(fee_pubkey.to_string()))?;
-    let fees_min_rent = client
-        .get_minimum_balance_for_rent_exemption(fee_data.len())
-        .unwrap();
-    let fees_balance = client.get_balance(&fee_pubkey).unwrap();
-    let fees_total = fees_balance - fees_min_rent;
+    let fee_account = fetch_account(client, fee_pubkey, "fee")?;
+    let fees_min_rent = client.get_minimum_balance_for_rent_exemption(fee_account.data.len())?;
+    let fees_total = fee_account.lamports.saturating_sub(fees_min_rent);
 
     // Get penalty balance
     let penalty_pubkey = Penalty::pubkey(worker_pubkey);
-    let penalty_data = client
-        .get_account_data(&penalty_pubkey)
-        .map_err(|_err| CliError::AccountNotFound(penalty_pubkey.to_string()))?;
-    let penalty_min_rent = client
-        .get_minimum_balance_for_rent_exemption(penalty_data.len())
-        .unwrap();
-    let penalty_balance = client.get_balance(&penalty_pubkey).unwrap();
-    let penalty_total = penalty_balance - penalty_min_rent;
+    let penalty_account = fetch_account(client, penalty_pubkey, "penalty")?;
+    let penalty_min_rent =
+        client.get_minimum_balance_for_rent_exemption(penalty_account.data.len())?;
+    let penalty_total = penalty_account.lamports.saturating_sub(penalty_min_rent);
 
+    let explorer_url = client.explorer().account_url(worker_pubkey);
+    if output == OutputFormat::Json {
+        return print_json(&Linked::new(
+            WorkerOutput::new(
+                worker_pubkey,
+                &worker,
+                FeeOutput {
+                    address: fee_pubkey.to_string(),
+                    balance: fees_total,
+                },
+                PenaltyOutput {
+                    address: penalty_pubkey.to_string(),
+                    balance: penalty_total,
+                },
+            ),
+            explorer_url,
+        ));
+    }
     println!(
-        "Address: {}\nFees: {}\nFee account: {}\nPenalty: {}\nPenalty account: {}\n{:#?}",
-        worker_pubkey, fees_total, fee_pubkey, penalty_total, penalty_pubkey, worker
+        "Address: {}\nExplorer: {}\nFees: {}\nFee account: {}\nPenalty: {}\nPenalty account: {}\n{:#?}",
+        worker_pubkey, explorer_url, fees_total, fee_pubkey, penalty_total, penalty_pubkey, worker
     );
 
     // Get registry
-    let registry_pubkey = Registry::pubkey();
-    let registry_data = client
-        .get_account_data(&registry_pubkey)
-        .map_err(|_err| CliError::AccountNotFound(registry_pubkey.to_string()))?;
-    let registry = Registry::try_deserialize(&mut registry_data.as_slice())
-        .map_err(|_err| CliError::AccountDataNotParsable(registry_pubkey.to_string()))?;
+    let registry = fetch_registry(client)?;
 
     // Get snapshot frame
     let snapshot_pubkey = Snapshot::pubkey(registry.current_epoch);
     let snapshot_frame_pubkey = SnapshotFrame::pubkey(snapshot_pubkey, worker.id);
-    match client.get_account_data(&snapshot_frame_pubkey) {
-        Err(_err) => {}
-        Ok(snapshot_frame_data) => {
-            let snapshot_frame = SnapshotFrame::try_deserialize(
-                &mut snapshot_frame_data.as_slice(),
-            )
-            .map_err(|_err| CliError::AccountDataNotParsable(registry_pubkey.to_string()))?;
-            println!("{:#?}", snapshot_frame);
-        }
+    if let Some(snapshot_frame) =
+        fetch_optional_state::<SnapshotFrame>(client, snapshot_frame_pubkey, "snapshot frame")?
+    {
+        println!("{:#?}", snapshot_frame);
+    }
+
+    Ok(())
+}
+
+/// Prints the claimable balances of the workers of the authority, with the balances of their signatories.
+/// Fails once printed when a signatory holds less than the minimum balance, to be run as a monitoring check.
+pub fn balances(
+    client: &Client,
+    authority: Pubkey,
+    min_signatory_balance: Option<u64>,
+    json: bool,
+) -> Result<(), CliError> {
+    let mut workers = fetch_authority_workers(client, authority)?;
+    workers.sort_by_key(|(_, _, worker)| worker.id);
+
+    let fee_accounts = fetch_multiple_accounts(
+        client,
+        &workers
+            .iter()
+            .map(|(address, _, _)| Fee::pubkey(*address))
+            .collect::<Vec<_>>(),
+    )?;
+    let penalty_accounts = fetch_multiple_accounts(
+        client,
+        &workers
+            .iter()
+            .map(|(address, _, _)| Penalty::pubkey(*address))
+            .collect::<Vec<_>>(),
+    )?;
+    let signatory_accounts = fetch_multiple_accounts(
+        client,
+        &workers
+            .iter()
+            .map(|(_, _, worker)| worker.signatory)
+            .collect::<Vec<_>>(),
+    )?;
+
+    // The rent exemptions are fetched once per data length, the fee and penalty accounts all have the same.
+    let mut rents = HashMap::new();
+    let mut above_rent = |account: Option<&Account>| -> Result<u64, CliError> {
+        let Some(account) = account else {
+            return Ok(0);
+        };
+        let min_rent = match rents.get(&account.data.len()) {
+            Some(min_rent) => *min_rent,
+            None => {
+                let min_rent = client.get_minimum_balance_for_rent_exemption(account.data.len())?;
+                rents.insert(account.data.len(), min_rent);
+                min_rent
+            }
+        };
+        Ok(account.lamports.saturating_sub(min_rent))
+    };
+
+    let mut entries = vec![];
+    for (i, (address, account, worker)) in workers.iter().enumerate() {
+        let signatory_balance = signatory_accounts[i]
+            .as_ref()
+            .map_or(0, |account| account.lamports);
+        entries.push(WorkerBalanceOutput {
+            id: worker.id,
+            address: address.to_string(),
+            fees: above_rent(fee_accounts[i].as_ref())?,
+            penalty: above_rent(penalty_accounts[i].as_ref())?,
+            // The commission is held by the worker account, which must stay rent exempt.
+            commission: worker.commission_balance.min(above_rent(Some(account))?),
+            signatory: worker.signatory.to_string(),
+            signatory_balance,
+            low_signatory_balance: min_signatory_balance.is_some_and(|min| signatory_balance < min),
+        });
+    }
+    let low: Vec<String> = entries
+        .iter()
+        .filter(|entry| entry.low_signatory_balance)
+        .map(|entry| entry.id.to_string())
+        .collect();
+    let balances = WorkerBalancesOutput {
+        authority: authority.to_string(),
+        total_fees: entries.iter().map(|entry| entry.fees).sum(),
+        total_penalty: entries.iter().map(|entry| entry.penalty).sum(),
+        total_commission: entries.iter().map(|entry| entry.commission).sum(),
+        total_signatory_balance: entries.iter().map(|entry| entry.signatory_balance).sum(),
+        workers: entries,
+    };
+
+    if json {
+        print_json(&balances)?;
+    } else if balances.workers.is_empty() {
+        println!("The authority {} has no workers", authority);
+    } else {
+        print_balances(&balances);
+    }
+
+    match min_signatory_balance {
+        Some(min) if !low.is_empty() => Err(CliError::LowSignatoryBalance(format!(
+            "the signatories of the workers {} hold less than {} SOL",
+            low.join(", "),
+            lamports_to_sol(min)
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn print_balances(balances: &WorkerBalancesOutput) {
+    println!(
+        "{:<6}  {:<44}  {:<14}  {:<14}  {:<14}  {:<44}  SIGNATORY_BALANCE",
+        "ID", "ADDRESS", "FEES", "PENALTY", "COMMISSION", "SIGNATORY"
+    );
+    for worker in &balances.workers {
+        println!(
+            "{:<6}  {:<44}  {:<14}  {:<14}  {:<14}  {:<44}  {}{}",
+            worker.id,
+            worker.address,
+            lamports_to_sol(worker.fees),
+            lamports_to_sol(worker.penalty),
+            lamports_to_sol(worker.commission),
+            worker.signatory,
+            lamports_to_sol(worker.signatory_balance),
+            if worker.low_signatory_balance {
+                " (low)"
+            } else {
+                ""
+            }
+        );
     }
+    println!(
+        "{:<6}  {:<44}  {:<14}  {:<14}  {:<14}  {:<44}  {}",
+        "TOTAL",
+        "",
+        lamports_to_sol(balances.total_fees),
+        lamports_to_sol(balances.total_penalty),
+        lamports_to_sol(balances.total_commission),
+        "",
+        lamports_to_sol(balances.total_signatory_balance)
+    );
+}
+
+/// Fetches the worker accounts of the authority, skipping the ones which can't be deserialized.
+fn fetch_authority_workers(
+    client: &Client,
+    authority: Pubkey,
+) -> Result<Vec<(Pubkey, Account, Worker)>, CliError> {
+    // The authority is the first field of the worker accounts, right after the discriminator.
+    let accounts = client.get_program_accounts_with_config(
+        &sablier_network_program::ID,
+        RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, Worker::DISCRIMINATOR.to_vec())),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(8, authority.to_bytes().to_vec())),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64Zstd),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        },
+    )?;
+    Ok(accounts
+        .into_iter()
+        .filter_map(|(address, account)| {
+            let worker = Worker::try_deserialize(&mut account.data.as_slice()).ok()?;
+            Some((address, account, worker))
+        })
+        .collect())
+}
 
+/// Fetches the accounts by batches of the max number of accounts of a request.
+fn fetch_multiple_accounts(
+    client: &Client,
+    pubkeys: &[Pubkey],
+) -> Result<Vec<Option<Account>>, CliError> {
+    let mut accounts = Vec::with_capacity(pubkeys.len());
+    for chunk in pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        accounts.extend(client.get_multiple_accounts(chunk)?);
+    }
+    Ok(accounts)
+}
+
+pub fn claim(
+    client: &Client,
+    worker_pubkey: Pubkey,
+    lamports: Option<u64>,
+    to: Option<Pubkey>,
+    yes: bool,
+) -> Result<(), CliError> {
+    let worker = fetch_worker(client, worker_pubkey)?;
+    if worker.authority != client.payer_pubkey() {
+        return Err(CliError::BadParameter(format!(
+            "the authority of the worker {} is {}, not the payer {}",
+            worker.id,
+            worker.authority,
+            client.payer_pubkey()
+        )));
+    }
+
+    // The commission is moved from the fee account to the worker when the fees are distributed,
+    // and the worker account must stay rent exempt.
+    let account = fetch_account(client, worker_pubkey, "worker")?;
+    let min_rent = client.get_minimum_balance_for_rent_exemption(account.data.len())?;
+    let claimable = worker
+        .commission_balance
+        .min(account.lamports.saturating_sub(min_rent));
+    println!("Claimable: {} SOL", lamports_to_sol(claimable));
+    if claimable == 0 {
+        return Err(CliError::BadParameter(format!(
+            "the worker {} has no commission to claim",
+            worker.id
+        )));
+    }
+    let lamports = lamports.unwrap_or(claimable);
+    if lamports > claimable {
+        return Err(CliError::BadParameter(format!(
+            "the amount {} SOL is more than the claimable {} SOL",
+            lamports_to_sol(lamports),
+            lamports_to_sol(claimable)
+        )));
+    }
+
+    let pay_to = to.unwrap_or(client.payer_pubkey());
+    confirm(
+        &format!("claim {} SOL of commission", lamports_to_sol(lamports)),
+        &format!(
+            "Address: {}\n{:#?}\nClaiming sends {} SOL of the commission to {}",
+            worker_pubkey,
+            worker,
+            lamports_to_sol(lamports),
+            pay_to
+        ),
+        Confirmation::YesNo,
+        yes,
+    )?;
+    let ix = Instruction {
+        program_id: sablier_network_program::ID,
+        accounts: sablier_network_program::accounts::WorkerClaim {
+            authority: client.payer_pubkey(),
+            pay_to,
+            worker: worker_pubkey,
+        }
+        .to_account_metas(Some(false)),
+        data: sablier_network_program::instruction::WorkerClaim { amount: lamports }.data(),
+    };
+    client.send_and_confirm(&[ix], &[client.payer()])?;
+    println!("Claimed {} SOL to {}", lamports_to_sol(lamports), pay_to);
     Ok(())
 }
 
-pub fn create(client: &Client, signatory: Keypair, silent: bool) -> Result<(), CliError> {
+pub fn create(
+    client: &Client,
+    signatory: Keypair,
+    silent: bool,
+    output: OutputFormat,
+) -> Result<(), CliError> {
     // Get config data
-    let config_pubkey = Config::pubkey();
-    let config_data = client
-        .get_account_data(&config_pubkey)
-        .map_err(|_err| CliError::AccountNotFound(config_pubkey.to_string()))?;
-    let config = Config::try_deserialize(&mut config_data.as_slice())
-        .map_err(|_err| CliError::AccountDataNotParsable(config_pubkey.to_string()))?;
+    let config = fetch_config(client)?;
 
     // Get registry
-    let registry_pubkey = Registry::pubkey();
-    let registry_data = client
-        .get_account_data(&registry_pubkey)
-        .map_err(|_err| CliError::AccountNotFound(registry_pubkey.to_string()))?;
-    let registry = Registry::try_deserialize(&mut registry_data.as_slice())
-        .map_err(|_err| CliError::AccountDataNotParsable(registry_pubkey.to_string()))?;
+    let registry = fetch_registry(client)?;
 
     // Build ix
     let worker_id = registry.total_workers;
@@ -120,27 +378,60 @@ pub fn create(client: &Client, signatory: Keypair, silent: bool) -> Result<(), C
         .to_account_metas(Some(false)),
         data: sablier_network_program::instruction::WorkerUtilsCreate {}.data(),
     };
-    client
-        .send_and_confirm(&[ix, ix_utils], &[client.payer(), &signatory])
-        .unwrap();
+    client.send_and_confirm(&[ix, ix_utils], &[client.payer(), &signatory])?;
     if !silent {
-        get(client, worker_id)?;
+        if output == OutputFormat::Text {
+            println!(
+                "Worker id: {}\nWorker: {}\nFee account: {}\nPenalty account: {}\n",
+                worker_id,
+                worker_pubkey,
+                Fee::pubkey(worker_pubkey),
+                Penalty::pubkey(worker_pubkey)
+            );
+        }
+        get(client, worker_pubkey, output)?;
     }
     Ok(())
 }
 
-pub fn update(client: &Client, id: u64, signatory: Option<Keypair>) -> Result<(), CliError> {
-    // Derive worker keypair.
-    let worker_pubkey = Worker::pubkey(id);
-    let worker = client
-        .get::<Worker>(&worker_pubkey)
-        .map_err(|_err| CliError::AccountDataNotParsable(worker_pubkey.to_string()))?;
+pub fn update(
+    client: &Client,
+    worker_pubkey: Pubkey,
+    commission_rate: Option<u64>,
+    signatory: Option<Pubkey>,
+    yes: bool,
+    output: OutputFormat,
+) -> Result<(), CliError> {
+    let worker = fetch_worker(client, worker_pubkey)?;
+
+    // The program rejects a zero commission rate, which new workers start with.
+    let commission_rate = commission_rate.unwrap_or(worker.commission_rate);
+    if commission_rate == 0 {
+        return Err(CliError::BadParameter(format!(
+            "the commission rate of the worker {} is unset, set it with --commission-rate",
+            worker.id
+        )));
+    }
 
     // Build and submit tx.
     let settings = WorkerSettings {
-        commission_rate: 0,
-        signatory: signatory.map_or(worker.signatory, |v| v.pubkey()),
+        commission_rate,
+        signatory: signatory.unwrap_or(worker.signatory),
     };
+    confirm(
+        "update the worker",
+        &format!(
+            "Address: {}\n{:#?}\nThe worker is updated to:\n  commission_rate: {} -> {}\n  signatory: {} -> {}",
+            worker_pubkey,
+            worker,
+            worker.commission_rate,
+            settings.commission_rate,
+            worker.signatory,
+            settings.signatory
+        ),
+        Confirmation::YesNo,
+        yes,
+    )?;
     let ix = Instruction {
         program_id: sablier_network_program::ID,
         accounts: sablier_network_program::accounts::WorkerUpdate {
@@ -151,7 +442,7 @@ pub fn update(client: &Client, id: u64, signatory: Option<Keypair>) -> Result<()
         .to_account_metas(Some(false)),
         data: sablier_network_program::instruction::WorkerUpdate { settings }.data(),
     };
-    client.send_and_confirm(&[ix], &[client.payer()]).unwrap();
-    get(client, worker.id)?;
+    client.send_and_confirm(&[ix], &[client.payer()])?;
+    get(client, worker_pubkey, output)?;
     Ok(())
 }
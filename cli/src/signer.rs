@@ -0,0 +1,122 @@
+use std::{fmt::Debug, path::Path};
+
+use solana_clap_utils::keypair::signer_from_path as resolve_signer;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Signature, Signer, SignerError},
+};
+
+use crate::errors::CliError;
+
+/// The prefix of the hardware wallet signer URIs, e.g. `usb://ledger?key=0`.
+static USB_PREFIX: &str = "usb://";
+
+/// A signer named after its role in the transactions, the name is printed before an interactive signer,
+/// e.g. a hardware wallet, asks for the confirmation of a signature.
+pub struct LabeledSigner {
+    label: &'static str,
+    signer: Box<dyn Signer>,
+}
+
+impl LabeledSigner {
+    pub fn new(label: &'static str, signer: Box<dyn Signer>) -> Self {
+        Self { label, signer }
+    }
+}
+
+impl Signer for LabeledSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        self.signer.try_pubkey()
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        if self.signer.is_interactive() {
+            eprintln!(
+                "Signing as the {} {}, confirm on the device",
+                self.label,
+                self.signer.pubkey()
+            );
+        }
+        self.signer.try_sign_message(message)
+    }
+
+    fn is_interactive(&self) -> bool {
+        self.signer.is_interactive()
+    }
+}
+
+impl Debug for LabeledSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.label, self.signer.pubkey())
+    }
+}
+
+/// Resolves the signer at a keypair path, or at a signer URI like the Solana CLI does:
+/// `usb://ledger`, `prompt://` for a seed phrase, `stdin://` or `file:PATH`.
+pub fn signer_from_path(path: &str, label: &'static str) -> Result<LabeledSigner, CliError> {
+    if cfg!(not(feature = "ledger")) && path.starts_with(USB_PREFIX) {
+        return Err(CliError::InvalidSigner(format!(
+            "{}: the hardware wallets need the CLI built with the `ledger` feature",
+            path
+        )));
+    }
+    let mut wallet_manager = None;
+    let signer = resolve_signer(
+        &clap_v2::ArgMatches::default(),
+        path,
+        label,
+        &mut wallet_manager,
+    )
+    .map_err(|err| {
+        if !path.contains(':') && !Path::new(path).exists() {
+            CliError::KeypairNotFound(path.to_string())
+        } else {
+            CliError::InvalidSigner(format!("{}: {}", path, err))
+        }
+    })?;
+    Ok(LabeledSigner::new(label, signer))
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signature::{write_keypair_file, Keypair};
+
+    use super::*;
+
+    #[test]
+    fn keypair_files_are_resolved_with_or_without_the_file_scheme() {
+        let keypair = Keypair::new();
+        let path = std::env::temp_dir().join(format!("sablier-signer-{}.json", keypair.pubkey()));
+        let path = path.to_str().unwrap();
+        write_keypair_file(&keypair, path).unwrap();
+
+        let signer = signer_from_path(path, "payer").unwrap();
+        assert_eq!(signer.pubkey(), keypair.pubkey());
+        assert_eq!(
+            signer.sign_message(b"message"),
+            keypair.sign_message(b"message")
+        );
+        assert!(!signer.is_interactive());
+        assert_eq!(
+            signer_from_path(&format!("file:{}", path), "authority")
+                .unwrap()
+                .pubkey(),
+            keypair.pubkey()
+        );
+        std::fs::remove_file(path).unwrap();
+
+        assert!(matches!(
+            signer_from_path(path, "payer"),
+            Err(CliError::KeypairNotFound(_))
+        ));
+    }
+
+    #[cfg(not(feature = "ledger"))]
+    #[test]
+    fn hardware_wallets_need_the_ledger_feature() {
+        assert!(matches!(
+            signer_from_path("usb://ledger", "payer"),
+            Err(CliError::InvalidSigner(_))
+        ));
+    }
+}
@@ -0,0 +1,139 @@
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_sdk::{
+    bs58, instruction::Instruction, message::Message, pubkey::Pubkey, signature::Signer,
+    transaction::Transaction,
+};
+
+use crate::{
+    client::{Client, ClientError},
+    errors::CliError,
+    signer::LabeledSigner,
+};
+
+/// The encoding of the dumped transactions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DumpEncoding {
+    /// The unsigned transaction, base64 encoded.
+    Base64,
+    /// The transaction message, base58 encoded, e.g. to import it into a Squads multisig.
+    Base58,
+}
+
+impl FromStr for DumpEncoding {
+    type Err = CliError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "base64" => Ok(Self::Base64),
+            "base58" => Ok(Self::Base58),
+            _ => Err(CliError::BadParameter("dump-transaction".into())),
+        }
+    }
+}
+
+/// Submits the transactions of the mutating commands, either signed by the payer and the authority's signer and sent,
+/// or printed unsigned for the authority to sign them offline, e.g. with a multisig.
+#[derive(Debug)]
+pub struct Submitter {
+    authority: Pubkey,
+    dump: Option<DumpEncoding>,
+    /// The signer of the authority when it isn't the payer, e.g. a hardware wallet.
+    signer: Option<LabeledSigner>,
+}
+
+impl Submitter {
+    pub fn new(authority: Pubkey, dump: Option<DumpEncoding>) -> Self {
+        Self {
+            authority,
+            dump,
+            signer: None,
+        }
+    }
+
+    pub fn with_signer(mut self, signer: Option<LabeledSigner>) -> Self {
+        self.signer = signer;
+        self
+    }
+
+    /// The authority the instructions are built for, the payer unless `--authority` is set.
+    pub fn authority(&self) -> Pubkey {
+        self.authority
+    }
+
+    /// Sends the instructions, or prints the unsigned transaction paid by the authority.
+    /// Returns whether the transaction was sent.
+    pub fn submit(&self, client: &Client, ixs: &[Instruction]) -> Result<bool, CliError> {
+        self.submit_with(client, ixs, CliError::from)
+    }
+
+    /// Submits the instructions like `submit`, with the errors of the sent transaction mapped by the command.
+    pub fn submit_with(
+        &self,
+        client: &Client,
+        ixs: &[Instruction],
+        map_err: impl FnOnce(ClientError) -> CliError,
+    ) -> Result<bool, CliError> {
+        let Some(encoding) = self.dump else {
+            let mut signers: Vec<&dyn Signer> = vec![client.payer()];
+            match &self.signer {
+                _ if self.authority == client.payer_pubkey() => {}
+                Some(signer) => signers.push(signer),
+                None => {
+                    return Err(CliError::BadParameter(format!(
+                        "authority: {} is not the payer, set it as a signer, e.g. usb://ledger, \
+                        or dump its transactions with --dump-transaction",
+                        self.authority
+                    )))
+                }
+            }
+            client.send_and_confirm(ixs, &signers).map_err(map_err)?;
+            return Ok(true);
+        };
+
+        let blockhash = client.get_latest_blockhash()?;
+        let ixs = client.with_compute_budget_instructions(ixs)?;
+        let message = Message::new_with_blockhash(&ixs, Some(&self.authority), &blockhash);
+        println!("{}", encode(message, encoding)?);
+        Ok(false)
+    }
+}
+
+fn encode(message: Message, encoding: DumpEncoding) -> Result<String, CliError> {
+    match encoding {
+        DumpEncoding::Base58 => Ok(bs58::encode(message.serialize()).into_string()),
+        DumpEncoding::Base64 => bincode::serialize(&Transaction::new_unsigned(message))
+            .map(|transaction| STANDARD.encode(transaction))
+            .map_err(|err| CliError::BadParameter(err.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{hash::Hash, system_instruction};
+
+    use super::*;
+
+    #[test]
+    fn dumped_transactions_are_paid_and_signed_by_the_authority() {
+        let authority = Pubkey::new_unique();
+        let ix = system_instruction::transfer(&authority, &Pubkey::new_unique(), 1);
+        let message = Message::new_with_blockhash(&[ix], Some(&authority), &Hash::new_unique());
+
+        let transaction: Transaction = bincode::deserialize(
+            &STANDARD
+                .decode(encode(message.clone(), DumpEncoding::Base64).unwrap())
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(transaction.message, message);
+        assert_eq!(transaction.message.account_keys[0], authority);
+        assert_eq!(transaction.signatures.len(), 1);
+
+        let serialized = bs58::decode(encode(message.clone(), DumpEncoding::Base58).unwrap())
+            .into_vec()
+            .unwrap();
+        assert_eq!(serialized, message.serialize());
+    }
+}
@@ -0,0 +1,46 @@
+//! Runs the CLI against a localnet with the programs deployed, e.g. the one started by `sablier localnet`.
+//! Ignored by default: `cargo test -p sablier-cli --test thread_domain -- --ignored`, `scripts/ci/localnet-e2e.sh` runs it.
+
+use std::process::{Command, Output};
+
+fn sablier(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sablier"))
+        .args(args)
+        .output()
+        .expect("failed to run the sablier CLI")
+}
+
+#[test]
+#[ignore = "requires a localnet with the sablier programs deployed"]
+fn domained_thread_is_created_looked_up_and_deleted() {
+    let id = format!("domain-test-{}", std::process::id());
+    let fixture = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/instructions.json"
+    );
+
+    let create = sablier(&[
+        "thread",
+        "create",
+        "--id",
+        &id,
+        "--domain",
+        "treasury",
+        "--instructions-file",
+        fixture,
+        "--cron",
+        "0 0 0 1 1 * 2100",
+    ]);
+    assert!(create.status.success(), "{:?}", create);
+
+    let get = sablier(&["thread", "get", &id, "--domain", "treasury"]);
+    assert!(get.status.success(), "{:?}", get);
+
+    // The thread is not found at the address derived without the domain.
+    let get_without_domain = sablier(&["thread", "get", &id]);
+    assert!(!get_without_domain.status.success());
+
+    // Without a terminal the deletion is only confirmed by the flag.
+    let delete = sablier(&["thread", "delete", &id, "--domain", "treasury", "--yes"]);
+    assert!(delete.status.success(), "{:?}", delete);
+}
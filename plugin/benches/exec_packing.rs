@@ -0,0 +1,84 @@
+use anchor_lang::AccountSerialize;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use sablier_plugin::bench::{build_exec_ix, decode_exec_state, decode_thread};
+use sablier_thread_program::state::{
+    ClockData, ExecContext, SerializableAccount, SerializableInstruction, Thread, Trigger,
+    TriggerContext, PAYER_PUBKEY,
+};
+use solana_sdk::pubkey::Pubkey;
+
+/// A thread of 10 instructions, each with a handful of accounts and some instruction data,
+/// in the middle of its execution.
+fn thread_account_data() -> Vec<u8> {
+    let instructions: Vec<SerializableInstruction> = (0..10)
+        .map(|_| SerializableInstruction {
+            program_id: Pubkey::new_unique(),
+            accounts: std::iter::once(SerializableAccount::mutable(PAYER_PUBKEY, true))
+                .chain((0..7).map(|_| SerializableAccount::readonly(Pubkey::new_unique(), false)))
+                .collect(),
+            data: vec![7; 128],
+        })
+        .collect();
+    let thread = Thread {
+        authority: Pubkey::new_unique(),
+        bump: 255,
+        created_at: ClockData {
+            slot: 0,
+            epoch: 0,
+            unix_timestamp: 0,
+        },
+        domain: None,
+        exec_context: Some(ExecContext {
+            exec_index: 0,
+            execs_since_reimbursement: 0,
+            execs_since_slot: 0,
+            last_exec_at: 0,
+            trigger_context: TriggerContext::Now,
+        }),
+        fee: 1_000,
+        id: b"bench".to_vec(),
+        next_instruction: Some(instructions[0].clone()),
+        instructions,
+        paused: false,
+        rate_limit: 10,
+        trigger: Trigger::Now,
+    };
+    let mut data = vec![];
+    thread.try_serialize(&mut data).unwrap();
+    data
+}
+
+/// One iteration of the packing loop: reads the simulated thread account and builds the next exec instruction.
+fn exec_packing(c: &mut Criterion) {
+    let data = thread_account_data();
+    let thread = decode_thread(&data).unwrap();
+    let (thread_pubkey, signatory_pubkey, worker_pubkey) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+
+    let mut group = c.benchmark_group("exec_packing");
+    group.bench_function("decode_thread", |b| {
+        b.iter(|| decode_thread(black_box(&data)).unwrap())
+    });
+    group.bench_function("decode_exec_state", |b| {
+        b.iter(|| decode_exec_state(black_box(&data)).unwrap())
+    });
+    group.bench_function("pack_iteration", |b| {
+        b.iter(|| {
+            let sim_state = decode_exec_state(black_box(&data)).unwrap();
+            build_exec_ix(
+                black_box(&thread),
+                sim_state.next_instruction.as_ref().unwrap(),
+                thread_pubkey,
+                signatory_pubkey,
+                worker_pubkey,
+            )
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, exec_packing);
+criterion_main!(benches);
@@ -0,0 +1,418 @@
+use std::{collections::HashMap, convert::Infallible, net::SocketAddr, str::FromStr, sync::Arc};
+
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use log::info;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    executors::{state::QuarantinedThread, tx::TxExecutor},
+    observers::thread::ThreadObserver,
+    reload::ConfigReloader,
+};
+
+/// The threads indexed by the observer, by trigger type.
+#[derive(Debug, Default, Serialize)]
+pub struct ObservedThreads {
+    /// The threads watching each account.
+    pub account: HashMap<String, Vec<String>>,
+    /// The timestamp each cron thread fires at next.
+    pub cron: HashMap<String, i64>,
+    pub now: Vec<String>,
+    /// The slot each slot thread fires at.
+    pub slot: HashMap<String, u64>,
+    /// The epoch each epoch thread fires at.
+    pub epoch: HashMap<String, u64>,
+    /// The threads watching each price feed.
+    pub pyth: HashMap<String, Vec<String>>,
+}
+
+/// The observer and executor state of a thread.
+#[derive(Debug, Serialize)]
+pub struct ThreadState {
+    pub thread: String,
+    pub indexed: bool,
+    pub watched_accounts: Vec<String>,
+    pub next_cron_timestamp: Option<i64>,
+    pub next_slot: Option<u64>,
+    pub next_epoch: Option<u64>,
+    pub price_feeds: Vec<String>,
+    pub awaiting_now: bool,
+    pub executable: Option<ExecutableThread>,
+    pub in_flight: Option<InFlightTransaction>,
+    pub backoff: Option<Backoff>,
+    pub quarantined: Option<QuarantinedThread>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExecutableThread {
+    pub thread: String,
+    pub due_slot: u64,
+    pub simulation_failures: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InFlightTransaction {
+    pub thread: String,
+    pub signature: String,
+    pub due_slot: u64,
+    pub sent_slot: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Backoff {
+    pub consecutive_failures: u32,
+    pub resume_slot: u64,
+    pub last_error: Option<String>,
+}
+
+/// The threads queued in the executor.
+#[derive(Debug, Serialize)]
+pub struct ExecutorQueues {
+    pub executable: Vec<ExecutableThread>,
+    pub in_flight: Vec<InFlightTransaction>,
+}
+
+impl ThreadObserver {
+    /// Clones the observer indexes.
+    pub async fn observed_threads(&self) -> ObservedThreads {
+        ObservedThreads {
+            account: self
+                .account_threads
+                .read()
+                .await
+                .iter()
+                .map(|(account, thread_pubkeys)| {
+                    (account.to_string(), to_strings(thread_pubkeys.iter()))
+                })
+                .collect(),
+            cron: targets(self.cron_threads.read().await.by_target()),
+            now: to_strings(self.now_threads.read().await.iter()),
+            slot: targets(self.slot_threads.read().await.by_target()),
+            epoch: targets(self.epoch_threads.read().await.by_target()),
+            pyth: self
+                .pyth_threads
+                .read()
+                .await
+                .iter()
+                .map(|(price_pubkey, pyth_threads)| {
+                    (
+                        price_pubkey.to_string(),
+                        to_strings(
+                            pyth_threads
+                                .iter()
+                                .map(|pyth_thread| &pyth_thread.thread_pubkey),
+                        ),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TxExecutor {
+    /// Clones the executable threads and the transactions awaiting confirmation.
+    pub async fn queues(&self) -> ExecutorQueues {
+        ExecutorQueues {
+            executable: self
+                .executable_threads
+                .entries()
+                .await
+                .into_iter()
+                .map(
+                    |(thread_pubkey, due_slot, simulation_failures)| ExecutableThread {
+                        thread: thread_pubkey.to_string(),
+                        due_slot,
+                        simulation_failures,
+                    },
+                )
+                .collect(),
+            in_flight: self
+                .transaction_history
+                .entries()
+                .await
+                .into_iter()
+                .map(|(thread_pubkey, metadata)| InFlightTransaction {
+                    thread: thread_pubkey.to_string(),
+                    signature: metadata.signature.to_string(),
+                    due_slot: metadata.due_slot,
+                    sent_slot: metadata.sent_slot,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Collects the state of a thread across the observer and the executor.
+pub async fn thread_state(
+    observer: &ThreadObserver,
+    executor: &TxExecutor,
+    thread_pubkey: &Pubkey,
+) -> ThreadState {
+    let thread = thread_pubkey.to_string();
+    let queues = executor.queues().await;
+    ThreadState {
+        indexed: observer.indexed_threads.contains(thread_pubkey),
+        watched_accounts: observer
+            .account_threads
+            .read()
+            .await
+            .iter()
+            .filter(|(_, thread_pubkeys)| thread_pubkeys.contains(thread_pubkey))
+            .map(|(account, _)| account.to_string())
+            .collect(),
+        next_cron_timestamp: observer.cron_threads.read().await.target(thread_pubkey),
+        next_slot: observer.slot_threads.read().await.target(thread_pubkey),
+        next_epoch: observer.epoch_threads.read().await.target(thread_pubkey),
+        price_feeds: observer
+            .pyth_threads
+            .read()
+            .await
+            .iter()
+            .filter(|(_, pyth_threads)| {
+                pyth_threads
+                    .iter()
+                    .any(|pyth_thread| pyth_thread.thread_pubkey == *thread_pubkey)
+            })
+            .map(|(price_pubkey, _)| price_pubkey.to_string())
+            .collect(),
+        awaiting_now: observer.now_threads.read().await.contains(thread_pubkey),
+        executable: queues
+            .executable
+            .into_iter()
+            .find(|executable_thread| executable_thread.thread == thread),
+        in_flight: queues
+            .in_flight
+            .into_iter()
+            .find(|in_flight| in_flight.thread == thread),
+        backoff: executor
+            .simulation_backoff
+            .get(thread_pubkey, &executor.backoff_config(thread_pubkey))
+            .await
+            .map(|(consecutive_failures, resume_slot, last_error)| Backoff {
+                consecutive_failures,
+                resume_slot,
+                last_error,
+            }),
+        quarantined: executor.quarantine.get(thread_pubkey).await,
+        thread,
+    }
+}
+
+/// The state read by the admin handlers.
+#[derive(Clone)]
+pub struct AdminState {
+    pub observer: Arc<ThreadObserver>,
+    pub executor: Arc<TxExecutor>,
+    pub reloader: Option<Arc<ConfigReloader>>,
+}
+
+/// Serves the admin endpoints on localhost only:
+/// `GET /threads`, `GET /threads/<pubkey>`, `GET /threads/<pubkey>/simulations` and `GET /executor`,
+/// `GET /quarantine` to list the quarantined threads, `DELETE /quarantine` and `DELETE /quarantine/<pubkey>` to release them,
+/// `POST /reload` to reload the config file and `POST /simulations/dump` to dump the recent simulations to a file.
+pub async fn serve(port: u16, state: AdminState) {
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let make_service = make_service_fn(move |_conn| {
+        let state = state.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(req, state.clone()))) }
+    });
+
+    match Server::try_bind(&addr) {
+        Err(err) => info!("Failed to bind the admin server on {}: {:?}", addr, err),
+        Ok(builder) => {
+            info!("Serving the admin interface on {}", addr);
+            if let Err(err) = builder.serve(make_service).await {
+                info!("Admin server error: {:?}", err);
+            }
+        }
+    }
+}
+
+async fn handle(req: Request<Body>, state: AdminState) -> Result<Response<Body>, Infallible> {
+    let path = req.uri().path().trim_end_matches('/');
+    if path == "/reload" {
+        return Ok(reload(req.method(), state.reloader.as_deref()).await);
+    }
+    if path == "/simulations/dump" {
+        return Ok(dump_simulations(req.method(), &state.executor).await);
+    }
+    if req.method() == Method::DELETE {
+        return Ok(release(path, &state.executor).await);
+    }
+    if req.method() != Method::GET {
+        return Ok(status(StatusCode::METHOD_NOT_ALLOWED));
+    }
+    let response = match path {
+        "/threads" => json(&state.observer.observed_threads().await),
+        "/executor" => json(&state.executor.queues().await),
+        "/quarantine" => json(&state.executor.quarantine.entries().await),
+        _ => match path.strip_prefix("/threads/") {
+            None => status(StatusCode::NOT_FOUND),
+            Some(thread) => match thread.strip_suffix("/simulations") {
+                Some(thread) => match Pubkey::from_str(thread) {
+                    Err(_) => status(StatusCode::BAD_REQUEST),
+                    Ok(thread_pubkey) => {
+                        json(&state.executor.simulation_logs.get(&thread_pubkey).await)
+                    }
+                },
+                None => match Pubkey::from_str(thread) {
+                    Err(_) => status(StatusCode::BAD_REQUEST),
+                    Ok(thread_pubkey) => {
+                        json(&thread_state(&state.observer, &state.executor, &thread_pubkey).await)
+                    }
+                },
+            },
+        },
+    };
+    Ok(response)
+}
+
+async fn reload(method: &Method, reloader: Option<&ConfigReloader>) -> Response<Body> {
+    if method != Method::POST {
+        return status(StatusCode::METHOD_NOT_ALLOWED);
+    }
+    match reloader {
+        None => status(StatusCode::NOT_FOUND),
+        Some(reloader) => match reloader.reload().await {
+            Ok(()) => status(StatusCode::NO_CONTENT),
+            Err(err) => Response::builder()
+                .status(StatusCode::UNPROCESSABLE_ENTITY)
+                .body(Body::from(err))
+                .unwrap(),
+        },
+    }
+}
+
+/// Releases a quarantined thread, or all of them.
+async fn release(path: &str, executor: &TxExecutor) -> Response<Body> {
+    if path == "/quarantine" {
+        let released = executor.quarantine.clear().await;
+        executor.metrics.quarantined_threads.set(0);
+        info!("Released {} threads from quarantine", released);
+        return status(StatusCode::NO_CONTENT);
+    }
+    match path.strip_prefix("/quarantine/").map(Pubkey::from_str) {
+        None => status(StatusCode::METHOD_NOT_ALLOWED),
+        Some(Err(_)) => status(StatusCode::BAD_REQUEST),
+        Some(Ok(thread_pubkey)) => match executor.release_thread(&thread_pubkey).await {
+            true => status(StatusCode::NO_CONTENT),
+            false => status(StatusCode::NOT_FOUND),
+        },
+    }
+}
+
+/// Writes the recent simulations of every thread to the configured dump file.
+async fn dump_simulations(method: &Method, executor: &TxExecutor) -> Response<Body> {
+    if method != Method::POST {
+        return status(StatusCode::METHOD_NOT_ALLOWED);
+    }
+    let Some(dump_path) = executor.config().simulation_logs.dump_path.clone() else {
+        return status(StatusCode::NOT_FOUND);
+    };
+    let simulations = executor.simulation_logs.entries().await;
+    match serde_json::to_vec_pretty(&simulations)
+        .map_err(std::io::Error::from)
+        .and_then(|data| std::fs::write(&dump_path, data))
+    {
+        Ok(()) => {
+            info!(
+                "Dumped the simulations of {} threads to {}",
+                simulations.len(),
+                dump_path
+            );
+            status(StatusCode::NO_CONTENT)
+        }
+        Err(err) => {
+            info!("Failed to dump the simulations to {}: {:?}", dump_path, err);
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from(err.to_string()))
+                .unwrap()
+        }
+    }
+}
+
+fn json<T: Serialize>(value: &T) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(serde_json::to_vec(value).unwrap()))
+        .unwrap()
+}
+
+fn status(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn to_strings<'a>(pubkeys: impl Iterator<Item = &'a Pubkey>) -> Vec<String> {
+    pubkeys.map(|pubkey| pubkey.to_string()).collect()
+}
+
+/// Inverts the scheduled threads grouped by target into the target of each thread.
+fn targets<T: Copy>(
+    by_target: HashMap<T, std::collections::HashSet<Pubkey>>,
+) -> HashMap<String, T> {
+    by_target
+        .into_iter()
+        .flat_map(|(target, thread_pubkeys)| {
+            thread_pubkeys
+                .into_iter()
+                .map(move |thread_pubkey| (thread_pubkey.to_string(), target))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use sablier_thread_program::state::{Thread, Trigger, VersionedThread};
+    use sablier_utils::thread::ClockData;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn observed_threads_are_listed_by_trigger() {
+        let observer = Arc::new(ThreadObserver::default());
+        let thread_pubkey = Pubkey::new_unique();
+        observer
+            .clone()
+            .observe_thread(
+                VersionedThread::V1(Thread {
+                    authority: Pubkey::new_unique(),
+                    bump: 0,
+                    created_at: ClockData {
+                        slot: 0,
+                        epoch: 0,
+                        unix_timestamp: 0,
+                    },
+                    domain: None,
+                    exec_context: None,
+                    fee: 0,
+                    id: vec![],
+                    instructions: vec![],
+                    next_instruction: None,
+                    paused: false,
+                    rate_limit: 0,
+                    trigger: Trigger::Slot { slot: 42 },
+                }),
+                thread_pubkey,
+                0,
+            )
+            .await
+            .unwrap();
+
+        let observed_threads = observer.observed_threads().await;
+        assert_eq!(
+            observed_threads.slot,
+            HashMap::from([(thread_pubkey.to_string(), 42)])
+        );
+        assert!(observed_threads.cron.is_empty());
+        assert!(observed_threads.now.is_empty());
+    }
+}
@@ -0,0 +1,78 @@
+use anchor_lang::Discriminator;
+use log::info;
+use sablier_thread_program::state::Thread;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+use crate::{error::PluginError, refresh::refresh_accounts};
+
+/// Selects the thread accounts among the thread program accounts.
+fn thread_accounts_filter() -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &Thread::discriminator()))
+}
+
+/// Fetches every thread account and passes it to `handle`, so the observer indexes are warm
+/// without waiting for the threads to be written again.
+///
+/// The thread program accounts are listed without their data first, then fetched by batches
+/// with getMultipleAccounts, to keep the responses small. Returns the number of threads fetched.
+pub async fn bootstrap_threads<F>(client: &RpcClient, handle: F) -> Result<usize, PluginError>
+where
+    F: Fn(Pubkey, Account, u64),
+{
+    let keys: Vec<Pubkey> = client
+        .get_program_accounts_with_config(
+            &sablier_thread_program::ID,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![thread_accounts_filter()]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    data_slice: Some(UiDataSliceConfig {
+                        offset: 0,
+                        length: 0,
+                    }),
+                    commitment: Some(CommitmentConfig::processed()),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            },
+        )
+        .await?
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+
+    let mut fetched = 0;
+    refresh_accounts(client, &keys, |key, account, slot| {
+        // A thread closed since it was listed is skipped, the observer never indexed it.
+        if let Some(account) = account {
+            handle(key, account, slot);
+            fetched += 1;
+        }
+    })
+    .await?;
+    info!("Bootstrapped {} threads", fetched);
+    Ok(fetched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_selects_thread_accounts() {
+        let filter = thread_accounts_filter();
+        let RpcFilterType::Memcmp(memcmp) = filter else {
+            panic!("expected a memcmp filter");
+        };
+        let mut data = Thread::discriminator().to_vec();
+        data.extend([0; 32]);
+        assert!(memcmp.bytes_match(&data));
+        assert!(!memcmp.bytes_match(&[0; 40]));
+    }
+}
@@ -0,0 +1,131 @@
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sablier_utils::thread::PAYER_PUBKEY;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+use crate::{
+    config::{InjectedInstruction, PluginConfig},
+    error::PluginError,
+};
+
+/// The instructions the config injects around the thread instructions of every exec transaction.
+#[derive(Debug, Default)]
+pub struct InjectedInstructions {
+    pub prefix: Vec<Instruction>,
+    pub suffix: Vec<Instruction>,
+}
+
+impl InjectedInstructions {
+    /// Parses the injected instructions of the config, with the `PAYER_PUBKEY` placeholder substituted with the signatory.
+    pub fn new(config: &PluginConfig, signatory_pubkey: Pubkey) -> Result<Self, PluginError> {
+        let parse = |instructions: &[InjectedInstruction]| {
+            instructions
+                .iter()
+                .map(|instruction| parse_instruction(instruction, signatory_pubkey))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(Self {
+            prefix: parse(&config.prefix_instructions)?,
+            suffix: parse(&config.suffix_instructions)?,
+        })
+    }
+
+    /// Checks the injected instructions of the config, so an invalid one is rejected when the config is loaded.
+    pub fn validate(config: &PluginConfig) -> Result<(), PluginError> {
+        Self::new(config, PAYER_PUBKEY).map(|_| ())
+    }
+}
+
+fn parse_instruction(
+    instruction: &InjectedInstruction,
+    signatory_pubkey: Pubkey,
+) -> Result<Instruction, PluginError> {
+    let invalid = |reason: String| {
+        PluginError::InvalidInjectedInstruction(instruction.program_id.clone(), reason)
+    };
+    let program_id = Pubkey::from_str(&instruction.program_id)?;
+    let accounts = instruction
+        .accounts
+        .iter()
+        .map(|account| {
+            let pubkey = Pubkey::from_str(&account.pubkey)?;
+            // The transactions are only signed by the signatory and the fee payer.
+            if account.is_signer && pubkey != PAYER_PUBKEY {
+                return Err(invalid(format!("account {} may not sign", pubkey)));
+            }
+            let pubkey = if pubkey == PAYER_PUBKEY {
+                signatory_pubkey
+            } else {
+                pubkey
+            };
+            Ok(AccountMeta {
+                pubkey,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+        })
+        .collect::<Result<Vec<_>, PluginError>>()?;
+    let data = STANDARD
+        .decode(&instruction.data)
+        .map_err(|err| invalid(format!("invalid base64 data: {}", err)))?;
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::InjectedAccountMeta;
+
+    use super::*;
+
+    fn memo(accounts: Vec<InjectedAccountMeta>) -> InjectedInstruction {
+        InjectedInstruction {
+            program_id: "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr".to_string(),
+            accounts,
+            data: STANDARD.encode("worker 0"),
+        }
+    }
+
+    #[test]
+    fn payer_placeholder_is_substituted_with_the_signatory() {
+        let signatory_pubkey = Pubkey::new_unique();
+        let config = PluginConfig {
+            prefix_instructions: vec![memo(vec![InjectedAccountMeta {
+                pubkey: PAYER_PUBKEY.to_string(),
+                is_signer: true,
+                is_writable: false,
+            }])],
+            ..PluginConfig::default()
+        };
+        let injected = InjectedInstructions::new(&config, signatory_pubkey).unwrap();
+        assert_eq!(
+            injected.prefix[0].accounts,
+            vec![AccountMeta::new_readonly(signatory_pubkey, true)]
+        );
+        assert_eq!(injected.prefix[0].data, b"worker 0");
+        assert!(injected.suffix.is_empty());
+    }
+
+    #[test]
+    fn additional_signers_are_rejected() {
+        let config = PluginConfig {
+            suffix_instructions: vec![memo(vec![InjectedAccountMeta {
+                pubkey: Pubkey::new_unique().to_string(),
+                is_signer: true,
+                is_writable: true,
+            }])],
+            ..PluginConfig::default()
+        };
+        assert!(matches!(
+            InjectedInstructions::validate(&config),
+            Err(PluginError::InvalidInjectedInstruction(..))
+        ));
+    }
+}
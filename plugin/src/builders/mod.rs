@@ -1,5 +1,7 @@
+mod injected;
 mod pool_rotation;
 mod thread_exec;
 
+pub use injected::*;
 pub use pool_rotation::*;
 pub use thread_exec::*;
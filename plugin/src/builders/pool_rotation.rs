@@ -1,15 +1,12 @@
-use std::sync::Arc;
-
 use anchor_lang::{solana_program::instruction::Instruction, InstructionData, ToAccountMetas};
 use log::info;
 use sablier_network_program::state::{Config, Pool, Registry, Snapshot, SnapshotFrame, Worker};
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{signature::Keypair, signer::Signer, transaction::Transaction};
+use solana_sdk::{hash::Hash, signature::Keypair, signer::Signer, transaction::Transaction};
 
 use crate::pool_position::PoolPosition;
 
 pub async fn build_pool_rotation_tx<'a>(
-    client: Arc<RpcClient>,
+    blockhash: Hash,
     keypair: &Keypair,
     pool_position: PoolPosition,
     registry: Registry,
@@ -80,6 +77,6 @@ pub async fn build_pool_rotation_tx<'a>(
 
     // Build and sign tx.
     let mut tx = Transaction::new_with_payer(&[ix.clone()], Some(&keypair.pubkey()));
-    tx.sign(&[keypair], client.get_latest_blockhash().await.unwrap());
+    tx.sign(&[keypair], blockhash);
     Some(tx)
 }
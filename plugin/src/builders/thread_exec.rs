@@ -1,165 +1,337 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
-use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_lang::AccountDeserialize;
 use log::info;
 use sablier_network_program::state::Worker;
-use sablier_thread_program::state::{Trigger, VersionedThread};
-use sablier_utils::{pyth::get_oracle_key, thread::PAYER_PUBKEY};
+use sablier_thread_program::{
+    constants::TRANSACTION_BASE_FEE_REIMBURSEMENT,
+    state::{Trigger, VersionedThread},
+};
+use sablier_utils::{pyth::PriceUpdateV2, thread::SerializableInstruction};
 use solana_account_decoder::UiAccountEncoding;
 use solana_client::{
+    client_error::{ClientErrorKind, Result as ClientResult},
     nonblocking::rpc_client::RpcClient,
     rpc_config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig},
-    rpc_custom_error::JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED,
+    rpc_response::{Response, RpcSimulateTransactionResult},
 };
 use solana_sdk::{
     account::Account,
+    address_lookup_table::AddressLookupTableAccount,
     commitment_config::CommitmentConfig,
     compute_budget::ComputeBudgetInstruction,
-    instruction::{AccountMeta, Instruction},
+    hash::{hash, Hash},
+    instruction::Instruction,
+    message::{v0, Message, VersionedMessage},
     pubkey::Pubkey,
     signature::Keypair,
     signer::Signer,
-    transaction::Transaction,
+    transaction::VersionedTransaction,
 };
+use tokio::sync::Semaphore;
 
-use crate::error::PluginError;
-
-/// Max byte size of a serialized transaction.
-static TRANSACTION_MESSAGE_SIZE_LIMIT: usize = 1_232;
+use crate::{
+    builders::InjectedInstructions,
+    config::{
+        LogFormat, PluginConfig, PriorityFeeConfig, PriorityFeeMode, PythConfig, StaticComputeUnits,
+    },
+    error::PluginError,
+    events::decode_exec_state,
+    executors::{
+        rpc_pool::{is_min_context_slot_not_reached_err, RpcPool},
+        state::{BlockhashCache, ComputeUnitCache, SimulationLogs, SimulationRecord},
+    },
+    metrics::Metrics,
+    observers::state::{hash_range, AccountTriggers},
+    utils::{commitment_config, get_price_account},
+};
 
-/// Max compute units that may be used by transaction.
-static TRANSACTION_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+/// The fee paid per transaction signature, in lamports.
+static LAMPORTS_PER_SIGNATURE: u64 = 5_000;
 
-/// The buffer amount to add to transactions' compute units in case on-chain PDA derivations take more CUs than used in simulation.
-static TRANSACTION_COMPUTE_UNIT_BUFFER: u32 = 1000;
+/// Max number of accounts that may be passed to getRecentPrioritizationFees.
+static PRIORITIZATION_FEES_ACCOUNTS_LIMIT: usize = 128;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn build_thread_exec_tx(
-    client: Arc<RpcClient>,
-    payer: &Keypair,
+    rpc_pool: Arc<RpcPool>,
+    signatory: &Keypair,
+    fee_payer: Option<&Keypair>,
+    worker_id: u64,
+    blockhash_cache: &BlockhashCache,
+    compute_unit_cache: &ComputeUnitCache,
+    account_triggers: &AccountTriggers,
+    simulation_logs: &SimulationLogs,
+    simulation_permits: &Semaphore,
     slot: u64,
     thread: VersionedThread,
     thread_pubkey: Pubkey,
-    worker_id: u64,
-) -> Result<Option<Transaction>, PluginError> {
+    config: &PluginConfig,
+    metrics: &Metrics,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<Option<VersionedTransaction>, PluginError> {
     // Grab the thread and relevant data.
     let now = std::time::Instant::now();
-    let blockhash = client.get_latest_blockhash().await?;
-    let signatory_pubkey = payer.pubkey();
+    let client = rpc_pool.client();
+    let blockhash = blockhash_cache
+        .get(&client, Duration::from_millis(config.blockhash_max_age_ms))
+        .await?;
+    let signatory_pubkey = signatory.pubkey();
     let worker_pubkey = Worker::pubkey(worker_id);
+    let priority_fee = &config.priority_fee;
+
+    // The buffer is added to the compute units consumed in simulation, in case on-chain PDA derivations
+    // take more compute units than simulated.
+    let (compute_unit_limit, compute_unit_buffer) =
+        config.executor.compute_units(&thread_pubkey.to_string());
+    let max_execs = config
+        .executor
+        .max_execs_per_transaction(&thread_pubkey.to_string());
 
     // Build the first instruction of the transaction.
-    let first_instruction = if thread.next_instruction().is_some() {
+    let first_instruction = if let Some(next_instruction) = thread.next_instruction() {
         build_exec_ix(
-            thread.clone(),
+            &thread,
+            &next_instruction,
             thread_pubkey,
             signatory_pubkey,
             worker_pubkey,
         )
     } else {
+        // Simulating against a missing price account fails with an opaque AccountNotFound error.
+        if let Trigger::Pyth { feed_id, .. } = thread.trigger() {
+            let price_pubkey = get_price_account(&config.pyth, feed_id);
+            let response = client
+                .get_account_with_commitment(&price_pubkey, CommitmentConfig::processed())
+                .await?;
+            if response.value.is_none() {
+                info!(
+                    "slot: {} thread: {} price account not found: {} shard_id: {}",
+                    slot,
+                    thread_pubkey,
+                    price_pubkey,
+                    config.pyth.shard_id(&feed_id)
+                );
+                return Ok(None);
+            }
+        }
         build_kickoff_ix(
-            thread.clone(),
+            &thread,
             thread_pubkey,
             signatory_pubkey,
             worker_pubkey,
+            &config.pyth,
         )
     };
 
     // Simulate the transaction and pack as many instructions as possible until we hit mem/cpu limits.
-    // TODO Migrate to versioned transactions.
-    let mut ixs: Vec<Instruction> = vec![
-        ComputeBudgetInstruction::set_compute_unit_limit(TRANSACTION_COMPUTE_UNIT_LIMIT),
-        first_instruction,
-    ];
+    // If priority fees are enabled, a placeholder compute unit price is packed right after the limit
+    // so the simulated size and compute units account for it.
+    let priority_fee_enabled = priority_fee.max_compute_unit_price > 0;
+    let mut ixs: Vec<Instruction> = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+        compute_unit_limit,
+    )];
+    if priority_fee_enabled {
+        ixs.push(ComputeBudgetInstruction::set_compute_unit_price(0));
+    }
+    // The injected instructions are simulated with the thread's, so they count against the size and compute unit limits.
+    let compute_budget_len = ixs.len();
+    let injected = InjectedInstructions::new(config, signatory_pubkey)?;
+    ixs.extend(injected.prefix);
+    let ixs_prefix_len = ixs.len();
+    ixs.push(first_instruction);
     let mut successful_ixs: Vec<Instruction> = vec![];
     let mut units_consumed: Option<u64> = None;
-    loop {
-        let mut sim_tx = Transaction::new_with_payer(&ixs, Some(&signatory_pubkey));
-        sim_tx.sign(&[payer], blockhash);
+    let mut thread_fee = thread.fee();
+    let mut simulation_error = None;
 
-        // Exit early if the transaction exceeds the size limit.
-        if sim_tx.message_data().len() > TRANSACTION_MESSAGE_SIZE_LIMIT {
-            info!("The transaction is too big to be send in one shot.");
-            break;
+    // The kickoff depends on the state of the trigger account, so it is requested back from the simulation too.
+    let trigger_pubkey = if thread.next_instruction().is_none() {
+        trigger_account(&thread.trigger(), &config.pyth)
+    } else {
+        None
+    };
+    let mut simulated_addresses = vec![thread_pubkey.to_string()];
+    if let Some(trigger_pubkey) = trigger_pubkey {
+        simulated_addresses.push(trigger_pubkey.to_string());
+    }
+
+    // Reuse the compute units of the last simulation if the thread's instruction, and the injected ones, have not changed since.
+    let instruction_hash = hash(&bincode::serialize(&(
+        &ixs[compute_budget_len..],
+        &injected.suffix,
+    ))?);
+    let cached_units = if config.executor.compute_unit_cache {
+        let cached_units = compute_unit_cache
+            .get(&thread_pubkey, &instruction_hash)
+            .await;
+        match cached_units {
+            Some(_) => metrics.compute_unit_cache_hits.inc(),
+            None => metrics.compute_unit_cache_misses.inc(),
         }
+        cached_units
+    } else {
+        None
+    };
 
-        // Run the simulation.
-        match client
-            .simulate_transaction_with_config(
+    if let Some(units) = cached_units {
+        successful_ixs = ixs.clone();
+        units_consumed = Some(units);
+    } else {
+        loop {
+            // Signed like the sent transaction, so the simulated account list is the real one.
+            let sim_tx = compile_tx(
+                &[ixs.as_slice(), &injected.suffix].concat(),
+                signatory,
+                fee_payer,
+                blockhash,
+                lookup_tables,
+            )?;
+
+            // Exit early if the transaction exceeds the size limit.
+            if sim_tx.message.serialize().len() > config.executor.message_size_limit {
+                info!("The transaction is too big to be send in one shot.");
+                break;
+            }
+
+            // Run the simulation.
+            metrics.simulations.inc();
+            match simulate_with_retry(
+                &rpc_pool,
+                simulation_permits,
                 &sim_tx,
-                RpcSimulateTransactionConfig {
-                    sig_verify: false,
-                    replace_recent_blockhash: true,
-                    commitment: Some(CommitmentConfig::processed()),
-                    accounts: Some(RpcSimulateTransactionAccountsConfig {
-                        encoding: Some(UiAccountEncoding::Base64Zstd),
-                        addresses: vec![thread_pubkey.to_string()],
-                    }),
-                    min_context_slot: Some(slot),
-                    ..RpcSimulateTransactionConfig::default()
-                },
+                &simulated_addresses,
+                slot,
+                config,
+                metrics,
             )
             .await
-        {
-            // If there was a simulation error, stop packing and exit now.
-            Err(err) => {
-                if let solana_client::client_error::ClientErrorKind::RpcError(
-                    solana_client::rpc_request::RpcError::RpcResponseError { code, .. },
-                ) = err.kind
-                {
-                    if code == JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED {
+            {
+                // If there was a simulation error, stop packing and exit now.
+                Err(err) => {
+                    if is_min_context_slot_not_reached_err(&err) {
+                        metrics
+                            .simulation_errors
+                            .with_label_values(&["MinContextSlotNotReached"])
+                            .inc();
                         return Err(PluginError::MinContextSlotNotReached);
                     }
+                    metrics
+                        .simulation_errors
+                        .with_label_values(&["RpcError"])
+                        .inc();
+                    simulation_logs
+                        .record(
+                            thread_pubkey,
+                            SimulationRecord {
+                                slot,
+                                instruction_count: ixs.len(),
+                                error: Some(err.to_string()),
+                                units_consumed: None,
+                                logs: vec![],
+                            },
+                            &config.simulation_logs,
+                        )
+                        .await;
+                    break;
                 }
-                break;
-            }
 
-            // If the simulation was successful, pack the ix into the tx.
-            Ok(response) => {
-                if response.value.err.is_some() {
-                    if successful_ixs.is_empty() {
-                        info!(
-                            "slot: {} thread: {} simulation_error: \"{}\" logs: {:?}",
-                            slot,
+                // If the simulation was successful, pack the ix into the tx.
+                Ok(response) => {
+                    simulation_logs
+                        .record(
                             thread_pubkey,
-                            response.value.err.unwrap(),
-                            response.value.logs.unwrap_or(vec![]),
-                        );
+                            SimulationRecord {
+                                slot,
+                                instruction_count: ixs.len(),
+                                error: response.value.err.as_ref().map(|err| err.to_string()),
+                                units_consumed: response.value.units_consumed,
+                                logs: response.value.logs.clone().unwrap_or_default(),
+                            },
+                            &config.simulation_logs,
+                        )
+                        .await;
+                    if let Some(err) = &response.value.err {
+                        metrics.record_simulation_error(err);
+                        if successful_ixs.is_empty() {
+                            info!(
+                                "slot: {} thread: {} simulation_error: \"{}\" logs: {:?}",
+                                slot,
+                                thread_pubkey,
+                                err,
+                                response.value.logs.clone().unwrap_or(vec![]),
+                            );
+                            simulation_error = Some((err.clone(), response.value.logs));
+                        }
+                        break;
                     }
-                    break;
-                }
 
-                // Update flag tracking if at least one instruction succeed.
-                successful_ixs = ixs.clone();
+                    // Record the trigger account state the kickoff succeeded with.
+                    if successful_ixs.is_empty() {
+                        if let Some(trigger_pubkey) = trigger_pubkey {
+                            let trigger_account = response
+                                .value
+                                .accounts
+                                .as_ref()
+                                .and_then(|ui_accounts| ui_accounts.get(1).cloned().flatten())
+                                .and_then(|ui_account| ui_account.decode::<Account>());
+                            match trigger_account {
+                                Some(account) => observe_simulated_trigger(
+                                    account_triggers,
+                                    &thread.trigger(),
+                                    &trigger_pubkey,
+                                    &account,
+                                    thread_pubkey,
+                                    slot,
+                                ),
+                                None => info!(
+                                    "slot: {} thread: {} trigger account missing from simulation: {}",
+                                    slot, thread_pubkey, trigger_pubkey
+                                ),
+                            }
+                        }
+                    }
 
-                // Record the compute units consumed by the simulation.
-                if response.value.units_consumed.is_some() {
-                    units_consumed = response.value.units_consumed;
-                }
+                    // Update flag tracking if at least one instruction succeed.
+                    successful_ixs = ixs.clone();
+
+                    // Record the compute units consumed by the simulation.
+                    if response.value.units_consumed.is_some() {
+                        units_consumed = response.value.units_consumed;
+                    }
 
-                // Parse the resulting thread account for the next instruction to simulate.
-                if let Some(ui_accounts) = response.value.accounts {
-                    if let Some(Some(ui_account)) = ui_accounts.first() {
-                        if let Some(account) = ui_account.decode::<Account>() {
-                            if let Ok(sim_thread) = VersionedThread::try_from(account.data) {
-                                if sim_thread.next_instruction().is_some() {
-                                    if let Some(exec_context) = sim_thread.exec_context() {
-                                        if exec_context
-                                            .execs_since_slot
-                                            .lt(&sim_thread.rate_limit())
-                                        {
-                                            ixs.push(build_exec_ix(
-                                                sim_thread,
-                                                thread_pubkey,
-                                                signatory_pubkey,
-                                                worker_pubkey,
-                                            ));
-                                        } else {
-                                            // Exit early if the thread has reached its rate limit.
-                                            break;
+                    // Parse the resulting thread account for the next instruction to simulate.
+                    // Only its exec state is read, the thread's instruction set is skipped over.
+                    if let Some(ui_accounts) = response.value.accounts {
+                        if let Some(Some(ui_account)) = ui_accounts.first() {
+                            if let Some(account) = ui_account.decode::<Account>() {
+                                if let Ok(sim_state) = decode_exec_state(&account.data) {
+                                    // Authorities can update the fee, so use the simulated one.
+                                    thread_fee = sim_state.fee;
+                                    if let Some(next_instruction) = &sim_state.next_instruction {
+                                        if let Some(exec_context) = sim_state.exec_context {
+                                            if may_pack_exec(
+                                                ixs.len() - ixs_prefix_len,
+                                                max_execs,
+                                                exec_context.execs_since_slot,
+                                                sim_state.rate_limit,
+                                            ) {
+                                                ixs.push(build_exec_ix(
+                                                    &thread,
+                                                    next_instruction,
+                                                    thread_pubkey,
+                                                    signatory_pubkey,
+                                                    worker_pubkey,
+                                                ));
+                                            } else {
+                                                // Exit early if the thread has reached its rate limit,
+                                                // or the transaction its max execs.
+                                                break;
+                                            }
                                         }
+                                    } else {
+                                        break;
                                     }
-                                } else {
-                                    break;
                                 }
                             }
                         }
@@ -167,132 +339,1012 @@ pub async fn build_thread_exec_tx(
                 }
             }
         }
-    }
 
+        // Only cache the units of transactions holding the first instruction alone, the following ones
+        // are only known from the simulated thread account.
+        if config.executor.compute_unit_cache {
+            match units_consumed {
+                Some(units) if successful_ixs.len() == ixs_prefix_len + 1 => {
+                    compute_unit_cache
+                        .insert(thread_pubkey, instruction_hash, units)
+                        .await
+                }
+                _ => compute_unit_cache.remove(&thread_pubkey).await,
+            }
+        }
+    }
     // If there were no successful instructions, then exit early. There is nothing to do.
     // Alternatively, exit early if only the kickoff instruction (and no execs) succeeded.
     if successful_ixs.is_empty() {
-        return Ok(None);
+        return match simulation_error {
+            Some((err, logs)) => Err(PluginError::FailedToSimulateTx(err, logs)),
+            None => Ok(None),
+        };
     }
 
     // Set the transaction's compute unit limit to be exactly the amount that was used in simulation.
     if let Some(units_consumed) = units_consumed {
         let units_committed = std::cmp::min(
-            (units_consumed as u32) + TRANSACTION_COMPUTE_UNIT_BUFFER,
-            TRANSACTION_COMPUTE_UNIT_LIMIT,
+            (units_consumed as u32).saturating_add(compute_unit_buffer),
+            compute_unit_limit,
         );
         _ = std::mem::replace(
             &mut successful_ixs[0],
             ComputeBudgetInstruction::set_compute_unit_limit(units_committed),
         );
     }
+    successful_ixs.extend(injected.suffix);
+
+    // Price the transaction's compute units from the recent fees paid for its writable accounts,
+    // or from the thread's fee. The configured minimum and maximum prices bound the derived one.
+    // The placeholder is dropped if the price is unavailable so the transaction is sent as before.
+    let mut compute_unit_price = None;
+    if priority_fee_enabled {
+        let price = match priority_fee.mode {
+            PriorityFeeMode::RecentFees => {
+                get_compute_unit_price(client.clone(), &successful_ixs, priority_fee).await
+            }
+            PriorityFeeMode::ThreadFee => units_consumed
+                .map(|units| thread_fee_compute_unit_price(thread_fee, units, priority_fee)),
+        }
+        .map(|price| {
+            price
+                .max(priority_fee.min_compute_unit_price)
+                .min(priority_fee.max_compute_unit_price)
+        });
+        match price {
+            Some(price) if price > 0 => {
+                compute_unit_price = Some(price);
+                _ = std::mem::replace(
+                    &mut successful_ixs[1],
+                    ComputeBudgetInstruction::set_compute_unit_price(price),
+                );
+            }
+            _ => {
+                successful_ixs.remove(1);
+            }
+        }
+    }
+
+    // Build the transaction and skip it if the thread's fee does not cover its cost.
+    // The priority fee is charged on the compute unit limit, not on the units consumed.
+    let tx = compile_tx(
+        &successful_ixs,
+        signatory,
+        fee_payer,
+        blockhash,
+        lookup_tables,
+    )?;
+    let compute_unit_limit = units_consumed
+        .map(|units| units + compute_unit_buffer as u64)
+        .unwrap_or(compute_unit_limit as u64)
+        .min(compute_unit_limit as u64);
+    if !fee_covers_cost(
+        config,
+        slot,
+        thread_pubkey,
+        thread_fee,
+        &tx,
+        compute_unit_price.unwrap_or_default(),
+        compute_unit_limit,
+    ) {
+        return Ok(None);
+    }
 
-    // Build and return the signed transaction.
-    let mut tx = Transaction::new_with_payer(&successful_ixs, Some(&signatory_pubkey));
-    tx.sign(&[payer], blockhash);
-    info!(
-        "slot: {:?} thread: {:?} sim_duration: {:?} instruction_count: {:?} compute_units: {:?} tx_sig: {:?}",
+    // Return the signed transaction.
+    metrics.transactions_built.inc();
+    metrics
+        .simulation_duration
+        .observe(now.elapsed().as_secs_f64());
+    if let Some(units_consumed) = units_consumed {
+        metrics.compute_units.observe(units_consumed as f64);
+    }
+    // In the json log format, the executor records the build instead.
+    if config.log_format == LogFormat::Text {
+        info!(
+        "slot: {:?} thread: {:?} sim_duration: {:?} instruction_count: {:?} compute_units: {:?} compute_unit_price: {:?} tx_sig: {:?}",
         slot,
         thread_pubkey,
         now.elapsed(),
         successful_ixs.len(),
         units_consumed,
+        compute_unit_price,
         tx.signatures[0]
     );
+    }
     Ok(Some(tx))
 }
 
-fn build_kickoff_ix(
+/// Builds the exec transaction of a latency-critical thread without simulating it.
+///
+/// The transaction packs the kickoff if the thread is not kicked off yet, then up to `exec_count` exec
+/// instructions predicted from the thread's instruction set, under the static compute unit limit and price.
+/// The kickoff counts towards the max execs per transaction.
+/// This trades the fees of failed transactions for latency: a transaction whose trigger condition is not met,
+/// whose instructions return a dynamic next instruction, or which needs more compute units than configured,
+/// still lands and pays its fees.
+#[allow(clippy::too_many_arguments)]
+pub async fn build_static_thread_exec_tx(
+    client: &RpcClient,
+    signatory: &Keypair,
+    fee_payer: Option<&Keypair>,
+    worker_id: u64,
+    blockhash_cache: &BlockhashCache,
+    static_compute_units: &StaticComputeUnits,
+    slot: u64,
     thread: VersionedThread,
     thread_pubkey: Pubkey,
+    config: &PluginConfig,
+    metrics: &Metrics,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<Option<VersionedTransaction>, PluginError> {
+    let blockhash = blockhash_cache
+        .get(client, Duration::from_millis(config.blockhash_max_age_ms))
+        .await?;
+    let signatory_pubkey = signatory.pubkey();
+    let worker_pubkey = Worker::pubkey(worker_id);
+
+    let mut ixs = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+        static_compute_units.compute_unit_limit,
+    )];
+    if static_compute_units.compute_unit_price > 0 {
+        ixs.push(ComputeBudgetInstruction::set_compute_unit_price(
+            static_compute_units.compute_unit_price,
+        ));
+    }
+    let injected = InjectedInstructions::new(config, signatory_pubkey)?;
+    ixs.extend(injected.prefix);
+    let ixs_prefix_len = ixs.len();
+    ixs.extend(static_thread_ixs(
+        &thread,
+        thread_pubkey,
+        signatory_pubkey,
+        worker_pubkey,
+        slot,
+        static_compute_units.exec_count,
+        config,
+    ));
+
+    // Drop the instructions which do not fit in the message size limit.
+    let mut tx = None;
+    while ixs.len() > ixs_prefix_len {
+        let built_tx = compile_tx(
+            &[ixs.as_slice(), &injected.suffix].concat(),
+            signatory,
+            fee_payer,
+            blockhash,
+            lookup_tables,
+        )?;
+        if built_tx.message.serialize().len() <= config.executor.message_size_limit {
+            tx = Some(built_tx);
+            break;
+        }
+        ixs.pop();
+    }
+    let Some(tx) = tx else {
+        info!(
+            "slot: {} thread: {} static build: the transaction is too big to be sent",
+            slot, thread_pubkey
+        );
+        return Ok(None);
+    };
+
+    if !fee_covers_cost(
+        config,
+        slot,
+        thread_pubkey,
+        thread.fee(),
+        &tx,
+        static_compute_units.compute_unit_price,
+        static_compute_units.compute_unit_limit as u64,
+    ) {
+        return Ok(None);
+    }
+
+    metrics.transactions_built.inc();
+    metrics
+        .static_transactions
+        .with_label_values(&["built"])
+        .inc();
+    if config.log_format == LogFormat::Text {
+        info!(
+            "slot: {:?} thread: {:?} static build instruction_count: {:?} compute_unit_limit: {:?} compute_unit_price: {:?} tx_sig: {:?}",
+            slot,
+            thread_pubkey,
+            ixs.len(),
+            static_compute_units.compute_unit_limit,
+            static_compute_units.compute_unit_price,
+            tx.signatures[0]
+        );
+    }
+    Ok(Some(tx))
+}
+
+/// The kickoff of the thread if it is not kicked off yet, then the exec instructions it is expected to execute,
+/// within the max execs per transaction.
+fn static_thread_ixs(
+    thread: &VersionedThread,
+    thread_pubkey: Pubkey,
     signatory_pubkey: Pubkey,
     worker_pubkey: Pubkey,
-) -> Instruction {
-    // Build the instruction.
-    let mut kickoff_ix = match thread {
-        VersionedThread::V1(_) => Instruction {
-            program_id: sablier_thread_program::ID,
-            accounts: sablier_thread_program::accounts::ThreadKickoff {
-                signatory: signatory_pubkey,
-                thread: thread_pubkey,
-                worker: worker_pubkey,
-            }
-            .to_account_metas(Some(false)),
-            data: sablier_thread_program::instruction::ThreadKickoff {}.data(),
-        },
+    slot: u64,
+    exec_count: usize,
+    config: &PluginConfig,
+) -> Vec<Instruction> {
+    let mut ixs = vec![];
+    if thread.next_instruction().is_none() {
+        ixs.push(build_kickoff_ix(
+            thread,
+            thread_pubkey,
+            signatory_pubkey,
+            worker_pubkey,
+            &config.pyth,
+        ));
+    }
+    let exec_count = match config
+        .executor
+        .max_execs_per_transaction(&thread_pubkey.to_string())
+    {
+        Some(max_execs) => exec_count.min(max_execs.saturating_sub(ixs.len())),
+        None => exec_count,
     };
+    for next_instruction in static_exec_instructions(thread, slot, exec_count) {
+        ixs.push(build_exec_ix(
+            thread,
+            &next_instruction,
+            thread_pubkey,
+            signatory_pubkey,
+            worker_pubkey,
+        ));
+    }
+    ixs
+}
 
-    // If the thread's trigger is account-based, inject the triggering account.
-    match thread.trigger() {
-        Trigger::Account {
-            address,
-            offset: _,
-            size: _,
-        } => kickoff_ix.accounts.push(AccountMeta {
-            pubkey: address,
-            is_signer: false,
-            is_writable: false,
-        }),
-        Trigger::Pyth {
-            feed_id,
-            equality: _,
-            limit: _,
-        } => {
-            let pubkey = get_oracle_key(0, feed_id);
-            kickoff_ix.accounts.push(AccountMeta {
-                pubkey,
-                is_signer: false,
-                is_writable: false,
-            })
+/// Returns true if an exec instruction may be packed after the `packed` thread instructions, the kickoff included.
+/// The thread's rate limit bounds its execs in the slot, and the max execs the instructions of the transaction.
+fn may_pack_exec(
+    packed: usize,
+    max_execs: Option<usize>,
+    execs_since_slot: u64,
+    rate_limit: u64,
+) -> bool {
+    execs_since_slot < rate_limit && max_execs.map_or(true, |max_execs| packed < max_execs)
+}
+
+/// The next instructions the thread is expected to execute, from its instruction set, bounded by its rate limit.
+/// Dynamic instructions returned by the executed instructions can't be predicted.
+fn static_exec_instructions(
+    thread: &VersionedThread,
+    slot: u64,
+    exec_count: usize,
+) -> Vec<SerializableInstruction> {
+    let instructions = thread.instructions();
+    let (next_instructions, remaining_execs) = match thread.next_instruction() {
+        // The kickoff starts over from the first instruction, and resets the execs of the slot.
+        None => (instructions, thread.rate_limit()),
+        Some(next_instruction) => {
+            let exec_context = thread.exec_context();
+            let exec_index = exec_context.map_or(0, |exec_context| exec_context.exec_index);
+            let remaining_execs = match exec_context {
+                Some(exec_context) if exec_context.last_exec_at == slot => thread
+                    .rate_limit()
+                    .saturating_sub(exec_context.execs_since_slot),
+                _ => thread.rate_limit(),
+            };
+            let following = instructions.into_iter().skip(exec_index as usize + 1);
+            (
+                std::iter::once(next_instruction).chain(following).collect(),
+                remaining_execs,
+            )
+        }
+    };
+    next_instructions
+        .into_iter()
+        .take(exec_count.min(remaining_execs as usize))
+        .collect()
+}
+
+/// Simulates the transaction at the slot, retrying while no RPC endpoint has reached the slot yet.
+/// Each attempt waits for a permit, so the simulations in flight are bounded across all the builds.
+async fn simulate_with_retry(
+    rpc_pool: &RpcPool,
+    simulation_permits: &Semaphore,
+    sim_tx: &VersionedTransaction,
+    addresses: &[String],
+    slot: u64,
+    config: &PluginConfig,
+    metrics: &Metrics,
+) -> ClientResult<Response<RpcSimulateTransactionResult>> {
+    let retry = &config.min_context_slot_retry;
+    let mut attempt = 0;
+    loop {
+        let permit = simulation_permits
+            .acquire()
+            .await
+            .map_err(|err| ClientErrorKind::Custom(err.to_string()))?;
+        let result = rpc_pool
+            .simulate_transaction_with_config(
+                sim_tx,
+                RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    replace_recent_blockhash: true,
+                    commitment: Some(commitment_config(config.simulation_commitment)),
+                    accounts: Some(RpcSimulateTransactionAccountsConfig {
+                        encoding: Some(UiAccountEncoding::Base64Zstd),
+                        addresses: addresses.to_vec(),
+                    }),
+                    min_context_slot: Some(slot),
+                    ..RpcSimulateTransactionConfig::default()
+                },
+            )
+            .await;
+        drop(permit);
+        match result {
+            Err(err) if is_min_context_slot_not_reached_err(&err) && attempt < retry.attempts => {
+                attempt += 1;
+                metrics.min_context_slot_retries.inc();
+                tokio::time::sleep(Duration::from_millis(retry.delay_ms)).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Returns the account an account or pyth trigger is evaluated against.
+fn trigger_account(trigger: &Trigger, pyth_config: &PythConfig) -> Option<Pubkey> {
+    match trigger {
+        Trigger::Account { address, .. } => Some(*address),
+        Trigger::Pyth { feed_id, .. } => Some(get_price_account(pyth_config, *feed_id)),
+        _ => None,
+    }
+}
+
+/// Updates the hash of the watched data range from the trigger account returned by the simulation,
+/// so the thread is not triggered again by an update carrying the same data. Logs the state that justified the kickoff.
+fn observe_simulated_trigger(
+    account_triggers: &AccountTriggers,
+    trigger: &Trigger,
+    trigger_pubkey: &Pubkey,
+    account: &Account,
+    thread_pubkey: Pubkey,
+    slot: u64,
+) {
+    match trigger {
+        Trigger::Account { offset, size, .. } => {
+            account_triggers.observe(trigger_pubkey, &account.owner, account.lamports, &account.data);
+            info!(
+                "slot: {} thread: {} trigger account: {} data_hash: {}",
+                slot,
+                thread_pubkey,
+                trigger_pubkey,
+                hash_range(&account.data, *offset, *size)
+            );
+        }
+        Trigger::Pyth { .. } => {
+            match PriceUpdateV2::try_deserialize(&mut account.data.as_slice()) {
+                Ok(price_update) => info!(
+                    "slot: {} thread: {} trigger price account: {} price: {} exponent: {} publish_time: {}",
+                    slot,
+                    thread_pubkey,
+                    trigger_pubkey,
+                    price_update.price_message.price,
+                    price_update.price_message.exponent,
+                    price_update.price_message.publish_time
+                ),
+                Err(err) => info!(
+                    "slot: {} thread: {} failed to parse trigger price account: {} err: {}",
+                    slot, thread_pubkey, trigger_pubkey, err
+                ),
+            }
         }
         _ => {}
     }
+}
 
-    kickoff_ix
+/// Builds a signed transaction paid by the fee payer if there is one, otherwise by the signatory.
+/// The transaction is compiled against the lookup tables if there are any, otherwise it is a legacy transaction.
+///
+/// With a fee payer, the lamports flow as follows:
+/// - the fee payer pays the signature fees, the priority fee and the jito tip.
+/// - the signatory pays for the lamports spent by the inner instruction, e.g. the rent of the accounts it creates,
+///   since `PAYER_PUBKEY` is substituted with the signatory, here and on-chain.
+/// - the thread program reimburses the signatory from the thread, measured on the signatory's balance.
+///
+/// The substitution must stay on the signatory: the program only reimburses the signatory, and only lets
+/// the signatory sign for the inner instruction.
+pub fn compile_tx(
+    ixs: &[Instruction],
+    signatory: &Keypair,
+    fee_payer: Option<&Keypair>,
+    blockhash: Hash,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<VersionedTransaction, PluginError> {
+    let payer = fee_payer.unwrap_or(signatory);
+    let message = if lookup_tables.is_empty() {
+        VersionedMessage::Legacy(Message::new_with_blockhash(
+            ixs,
+            Some(&payer.pubkey()),
+            &blockhash,
+        ))
+    } else {
+        VersionedMessage::V0(v0::Message::try_compile(
+            &payer.pubkey(),
+            ixs,
+            lookup_tables,
+            blockhash,
+        )?)
+    };
+    sign(message, signatory, fee_payer)
 }
 
-fn build_exec_ix(
-    thread: VersionedThread,
+/// Signs the message with the keypairs among the signatory and the fee payer which are required signers of it.
+pub fn sign(
+    message: VersionedMessage,
+    signatory: &Keypair,
+    fee_payer: Option<&Keypair>,
+) -> Result<VersionedTransaction, PluginError> {
+    let required_signers =
+        &message.static_account_keys()[..message.header().num_required_signatures as usize];
+    let mut signers = vec![];
+    for keypair in fee_payer.into_iter().chain([signatory]) {
+        if required_signers.contains(&keypair.pubkey())
+            && !signers
+                .iter()
+                .any(|signer: &&Keypair| signer.pubkey() == keypair.pubkey())
+        {
+            signers.push(keypair);
+        }
+    }
+    Ok(VersionedTransaction::try_new(message, &signers)?)
+}
+
+/// Returns false and logs the shortfall if the fee filter is enabled and the thread's fee does not cover
+/// the estimated cost of the transaction and its bundle tip, plus the configured margin.
+fn fee_covers_cost(
+    config: &PluginConfig,
+    slot: u64,
+    thread_pubkey: Pubkey,
+    thread_fee: u64,
+    tx: &VersionedTransaction,
+    compute_unit_price: u64,
+    compute_unit_limit: u64,
+) -> bool {
+    if !config.fee_filter.enabled {
+        return true;
+    }
+    let cost = estimate_cost(
+        tx.message.header().num_required_signatures as u64,
+        compute_unit_price,
+        compute_unit_limit,
+    )
+    .saturating_add(config.bundle_tip(thread_fee));
+    match fee_shortfall(thread_fee, cost, config.fee_filter.margin_lamports) {
+        None => true,
+        Some(shortfall) => {
+            info!(
+                "slot: {} thread: {} skipped: fee does not cover cost, fee: {} cost: {} margin: {} shortfall: {}",
+                slot,
+                thread_pubkey,
+                thread_fee,
+                cost,
+                config.fee_filter.margin_lamports,
+                shortfall
+            );
+            false
+        }
+    }
+}
+
+/// Returns the lamports the thread's fee falls short of the cost plus the margin by, if any.
+fn fee_shortfall(thread_fee: u64, cost: u64, margin_lamports: u64) -> Option<u64> {
+    let required = cost.saturating_add(margin_lamports);
+    (thread_fee < required).then(|| required - thread_fee)
+}
+
+/// Returns the estimated cost of a transaction in lamports: the signature fees plus the priority fee,
+/// minus the base fee the thread reimburses to the signatory.
+fn estimate_cost(signatures: u64, compute_unit_price: u64, compute_unit_limit: u64) -> u64 {
+    let priority_fee =
+        (compute_unit_price as u128 * compute_unit_limit as u128).div_ceil(1_000_000);
+    (signatures * LAMPORTS_PER_SIGNATURE)
+        .saturating_add(priority_fee as u64)
+        .saturating_sub(TRANSACTION_BASE_FEE_REIMBURSEMENT)
+}
+
+/// Returns the compute unit price spending the configured share of the thread's fee on the units consumed.
+fn thread_fee_compute_unit_price(
+    thread_fee: u64,
+    units_consumed: u64,
+    priority_fee: &PriorityFeeConfig,
+) -> u64 {
+    if units_consumed == 0 {
+        return 0;
+    }
+    let percentage = priority_fee.thread_fee_percentage.min(100) as u128;
+    let price = thread_fee as u128 * percentage * 1_000_000 / 100 / units_consumed as u128;
+    price.min(u64::MAX as u128) as u64
+}
+
+/// Returns the configured percentile of the recent prioritization fees paid for the writable accounts of
+/// the given instructions, capped by the configured maximum. Returns `None` if the RPC method is unavailable.
+async fn get_compute_unit_price(
+    client: Arc<RpcClient>,
+    ixs: &[Instruction],
+    priority_fee: &PriorityFeeConfig,
+) -> Option<u64> {
+    let mut writable_accounts: Vec<Pubkey> = vec![];
+    for acc in ixs.iter().flat_map(|ix| ix.accounts.iter()) {
+        if acc.is_writable && !writable_accounts.contains(&acc.pubkey) {
+            writable_accounts.push(acc.pubkey);
+        }
+    }
+    writable_accounts.truncate(PRIORITIZATION_FEES_ACCOUNTS_LIMIT);
+
+    let mut fees: Vec<u64> = client
+        .get_recent_prioritization_fees(&writable_accounts)
+        .await
+        .ok()?
+        .iter()
+        .map(|fee| fee.prioritization_fee)
+        .collect();
+    if fees.is_empty() {
+        return Some(0);
+    }
+    fees.sort_unstable();
+    let percentile = std::cmp::min(priority_fee.percentile, 100) as usize;
+    let index = (fees.len() - 1) * percentile / 100;
+    Some(std::cmp::min(
+        fees[index],
+        priority_fee.max_compute_unit_price,
+    ))
+}
+
+fn build_kickoff_ix(
+    thread: &VersionedThread,
     thread_pubkey: Pubkey,
     signatory_pubkey: Pubkey,
     worker_pubkey: Pubkey,
+    pyth_config: &PythConfig,
 ) -> Instruction {
-    // Build the instruction.
-    let mut exec_ix = match thread {
-        VersionedThread::V1(_) => Instruction {
-            program_id: sablier_thread_program::ID,
-            accounts: sablier_thread_program::accounts::ThreadExec {
-                fee: sablier_network_program::state::Fee::pubkey(worker_pubkey),
-                pool: sablier_network_program::state::Pool::pubkey(0),
-                signatory: signatory_pubkey,
-                thread: thread_pubkey,
-                worker: worker_pubkey,
-            }
-            .to_account_metas(Some(true)),
-            data: sablier_thread_program::instruction::ThreadExec {}.data(),
-        },
+    thread.kickoff_ix(
+        thread_pubkey,
+        signatory_pubkey,
+        worker_pubkey,
+        trigger_account(&thread.trigger(), pyth_config),
+    )
+}
+
+/// Builds the exec instruction of the thread running `next_instruction`.
+pub fn build_exec_ix(
+    thread: &VersionedThread,
+    next_instruction: &SerializableInstruction,
+    thread_pubkey: Pubkey,
+    signatory_pubkey: Pubkey,
+    worker_pubkey: Pubkey,
+) -> Instruction {
+    thread.exec_ix(
+        next_instruction,
+        thread_pubkey,
+        signatory_pubkey,
+        worker_pubkey,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{AccountSerialize, Discriminator, InstructionData};
+    use sablier_thread_program::state::{ExecContext, Thread, TriggerContext};
+    use sablier_utils::thread::{ClockData, SerializableAccount, PAYER_PUBKEY};
+    use solana_sdk::hash::hashv;
+
+    use super::*;
+    use std::collections::HashSet;
+
+    use crate::{
+        config::{JitoConfig, JitoTip, SubmissionMode},
+        events::{decode_thread, AccountUpdate},
+        observers::state::AccountTrigger,
     };
 
-    if let Some(next_instruction) = thread.next_instruction() {
-        // Inject the target program account.
-        exec_ix.accounts.push(AccountMeta::new_readonly(
-            next_instruction.program_id,
-            false,
-        ));
+    fn thread_account_data(next_instruction: Option<SerializableInstruction>) -> Vec<u8> {
+        let thread = Thread {
+            authority: Pubkey::new_unique(),
+            bump: 0,
+            created_at: ClockData {
+                slot: 0,
+                epoch: 0,
+                unix_timestamp: 0,
+            },
+            domain: None,
+            exec_context: None,
+            fee: 1_000,
+            id: b"thread".to_vec(),
+            instructions: vec![],
+            next_instruction,
+            paused: false,
+            rate_limit: 1,
+            trigger: Trigger::Now,
+        };
+        let mut data = vec![];
+        thread.try_serialize(&mut data).unwrap();
+        data
+    }
 
-        // Inject the worker pubkey as the dynamic "payer" account.
-        for acc in next_instruction.accounts {
-            let acc_pubkey = if acc.pubkey == PAYER_PUBKEY {
-                signatory_pubkey
-            } else {
-                acc.pubkey
-            };
-            exec_ix.accounts.push(match acc.is_writable {
-                true => AccountMeta::new(acc_pubkey, false),
-                false => AccountMeta::new_readonly(acc_pubkey, false),
+    #[test]
+    fn current_thread_layout_round_trips_through_the_builders() {
+        let (target_program, target_account) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let data = thread_account_data(Some(SerializableInstruction {
+            program_id: target_program,
+            accounts: vec![
+                SerializableAccount::mutable(PAYER_PUBKEY, true),
+                SerializableAccount::readonly(target_account, false),
+            ],
+            data: vec![],
+        }));
+        let thread = decode_thread(&data).unwrap();
+        assert!(matches!(thread, VersionedThread::V1(_)));
+
+        let (thread_pubkey, signatory_pubkey, worker_pubkey) =
+            (thread.pubkey(), Pubkey::new_unique(), Worker::pubkey(0));
+        let kickoff_ix = build_kickoff_ix(
+            &thread,
+            thread_pubkey,
+            signatory_pubkey,
+            worker_pubkey,
+            &PythConfig::default(),
+        );
+        assert_eq!(kickoff_ix.program_id, sablier_thread_program::ID);
+        assert_eq!(
+            kickoff_ix.data,
+            sablier_thread_program::instruction::ThreadKickoff {}.data()
+        );
+        assert_eq!(kickoff_ix.accounts.len(), 3);
+
+        let exec_ix = build_exec_ix(
+            &thread,
+            &thread.next_instruction().unwrap(),
+            thread_pubkey,
+            signatory_pubkey,
+            worker_pubkey,
+        );
+        assert_eq!(exec_ix.program_id, sablier_thread_program::ID);
+        assert_eq!(
+            exec_ix.data,
+            sablier_thread_program::instruction::ThreadExec {}.data()
+        );
+        // The exec accounts, then the target program, then the next instruction accounts with the payer injected.
+        let injected: Vec<Pubkey> = exec_ix.accounts[5..]
+            .iter()
+            .map(|account| account.pubkey)
+            .collect();
+        assert_eq!(injected, [target_program, signatory_pubkey, target_account]);
+    }
+
+    #[test]
+    fn simulated_trigger_account_updates_the_watched_hash() {
+        let account_triggers = AccountTriggers::default();
+        let (address, thread_pubkey) = (Pubkey::new_unique(), Pubkey::new_unique());
+        account_triggers.add(
+            address,
+            thread_pubkey,
+            AccountTrigger {
+                offset: 0,
+                size: 8,
+                data_hash: None,
+                observed_data: None,
+                balance: None,
+            },
+        );
+        let trigger = Trigger::Account {
+            address,
+            offset: 0,
+            size: 8,
+        };
+        assert_eq!(
+            trigger_account(&trigger, &PythConfig::default()),
+            Some(address)
+        );
+
+        let account = Account {
+            lamports: 1,
+            data: vec![7; 8],
+            ..Account::default()
+        };
+        observe_simulated_trigger(
+            &account_triggers,
+            &trigger,
+            &address,
+            &account,
+            thread_pubkey,
+            0,
+        );
+        // The update carrying the data the kickoff ran with doesn't trigger the thread again.
+        assert!(account_triggers
+            .observe(&address, &account.owner, account.lamports, &account.data)
+            .is_empty());
+        assert_eq!(
+            account_triggers.observe(&address, &Pubkey::default(), 1, &[8; 8]),
+            HashSet::from([thread_pubkey])
+        );
+    }
+
+    #[test]
+    fn fee_payer_pays_while_the_signatory_still_signs() {
+        let (signatory, fee_payer) = (Keypair::new(), Keypair::new());
+        let data = thread_account_data(Some(SerializableInstruction {
+            program_id: Pubkey::new_unique(),
+            accounts: vec![SerializableAccount::mutable(PAYER_PUBKEY, true)],
+            data: vec![],
+        }));
+        let thread = decode_thread(&data).unwrap();
+        let exec_ix = build_exec_ix(
+            &thread,
+            &thread.next_instruction().unwrap(),
+            thread.pubkey(),
+            signatory.pubkey(),
+            Worker::pubkey(0),
+        );
+        // The payer of the inner instruction is the signatory, which the thread program reimburses.
+        assert_eq!(exec_ix.accounts.last().unwrap().pubkey, signatory.pubkey());
+
+        let tx = compile_tx(
+            &[exec_ix],
+            &signatory,
+            Some(&fee_payer),
+            Hash::new_unique(),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(tx.message.static_account_keys()[0], fee_payer.pubkey());
+        assert_eq!(tx.signatures.len(), 2);
+        assert!(tx
+            .verify_with_results()
+            .into_iter()
+            .all(|verified| verified));
+    }
+
+    #[test]
+    fn estimated_cost_is_net_of_the_base_fee_reimbursement() {
+        // A single signature is paid back by the thread.
+        assert_eq!(estimate_cost(1, 0, 200_000), 0);
+        assert_eq!(estimate_cost(2, 0, 200_000), LAMPORTS_PER_SIGNATURE);
+        // The priority fee is charged on the compute unit limit, rounded up.
+        assert_eq!(estimate_cost(1, 1, 200_000), 1);
+        assert_eq!(estimate_cost(1, 1, 1), 1);
+        assert_eq!(estimate_cost(1, 10_000, 200_000), 2_000);
+    }
+
+    #[test]
+    fn fee_filter_skips_threads_whose_fee_does_not_cover_the_cost() {
+        assert_eq!(fee_shortfall(1_000, 0, 0), None);
+        assert_eq!(fee_shortfall(1_000, 1_000, 0), None);
+        assert_eq!(fee_shortfall(1_000, 1_000, 1), Some(1));
+        assert_eq!(fee_shortfall(1_000, 2_000, 500), Some(1_500));
+        assert_eq!(fee_shortfall(0, u64::MAX, u64::MAX), Some(u64::MAX));
+
+        let signatory = Keypair::new();
+        let tx = compile_tx(&[], &signatory, None, Hash::new_unique(), &[]).unwrap();
+        let covers = |config: &PluginConfig, thread_fee: u64, compute_unit_price: u64| {
+            fee_covers_cost(
+                config,
+                0,
+                Pubkey::new_unique(),
+                thread_fee,
+                &tx,
+                compute_unit_price,
+                200_000,
+            )
+        };
+        let mut config = PluginConfig::default();
+        // Disabled by default, even a thread without fee is kept.
+        assert!(covers(&config, 0, 1_000_000));
+
+        // A thread paying the program's default fee is kept without a priority fee.
+        config.fee_filter.enabled = true;
+        assert!(covers(&config, 1_000, 0));
+        assert!(covers(&config, 1_000, 5_000));
+        assert!(!covers(&config, 1_000, 5_001));
+
+        config.fee_filter.margin_lamports = 1_001;
+        assert!(!covers(&config, 1_000, 0));
+
+        // The bundle tip is part of the cost in the jito submission mode.
+        config.fee_filter.margin_lamports = 0;
+        config.submission_mode = SubmissionMode::Jito;
+        config.jito = Some(JitoConfig {
+            block_engine_url: "https://block-engine.example.com".to_string(),
+            tip_account: Pubkey::new_unique().to_string(),
+            tip: JitoTip::FeePercentage(100),
+        });
+        assert!(covers(&config, 1_000, 0));
+        config.jito.as_mut().unwrap().tip = JitoTip::Lamports(1_001);
+        assert!(!covers(&config, 1_000, 0));
+    }
+
+    #[test]
+    fn static_builds_follow_the_instruction_set_within_the_rate_limit() {
+        let instructions: Vec<SerializableInstruction> = (0..4)
+            .map(|_| SerializableInstruction {
+                program_id: Pubkey::new_unique(),
+                accounts: vec![],
+                data: vec![],
             })
-        }
+            .collect();
+        let VersionedThread::V1(mut thread) = decode_thread(&thread_account_data(None)).unwrap();
+        thread.instructions = instructions.clone();
+        thread.rate_limit = 2;
+
+        // The kickoff starts from the first instruction.
+        let thread = VersionedThread::V1(thread);
+        assert_eq!(
+            static_exec_instructions(&thread, 10, 3),
+            instructions[..2].to_vec()
+        );
+
+        // A kicked off thread continues from its next instruction, with the execs left in the slot.
+        let VersionedThread::V1(mut thread) = thread;
+        thread.next_instruction = Some(instructions[1].clone());
+        thread.exec_context = Some(ExecContext {
+            exec_index: 1,
+            execs_since_reimbursement: 0,
+            execs_since_slot: 1,
+            last_exec_at: 10,
+            trigger_context: TriggerContext::Now,
+        });
+        let thread = VersionedThread::V1(thread);
+        assert_eq!(
+            static_exec_instructions(&thread, 10, 3),
+            instructions[1..2].to_vec()
+        );
+        assert_eq!(
+            static_exec_instructions(&thread, 11, 3),
+            instructions[1..3].to_vec()
+        );
     }
 
-    exec_ix
+    #[test]
+    fn max_execs_per_transaction_caps_the_packed_instructions() {
+        let instructions: Vec<SerializableInstruction> = (0..4)
+            .map(|_| SerializableInstruction {
+                program_id: Pubkey::new_unique(),
+                accounts: vec![],
+                data: vec![],
+            })
+            .collect();
+        let VersionedThread::V1(mut thread) = decode_thread(&thread_account_data(None)).unwrap();
+        thread.instructions = instructions.clone();
+        thread.rate_limit = 4;
+        let mut config = PluginConfig::default();
+        config.executor.max_execs_per_transaction = 1;
+        let (signatory, worker_pubkey) = (Keypair::new(), Worker::pubkey(0));
+        let packed_tx = |thread: &VersionedThread, config: &PluginConfig| {
+            let mut ixs = vec![ComputeBudgetInstruction::set_compute_unit_limit(200_000)];
+            ixs.extend(static_thread_ixs(
+                thread,
+                thread.pubkey(),
+                signatory.pubkey(),
+                worker_pubkey,
+                10,
+                3,
+                config,
+            ));
+            compile_tx(&ixs, &signatory, None, Hash::new_unique(), &[]).unwrap()
+        };
+        let thread_ix_data = |tx: &VersionedTransaction| -> Vec<Vec<u8>> {
+            tx.message.instructions()[1..]
+                .iter()
+                .map(|ix| ix.data.clone())
+                .collect()
+        };
+
+        // A thread not kicked off yet only packs its kickoff.
+        let kickoff_thread = VersionedThread::V1(thread.clone());
+        let tx = packed_tx(&kickoff_thread, &config);
+        assert_eq!(
+            thread_ix_data(&tx),
+            [sablier_thread_program::instruction::ThreadKickoff {}.data()]
+        );
+
+        // A kicked off thread packs a single exec.
+        thread.next_instruction = Some(instructions[0].clone());
+        thread.exec_context = Some(ExecContext {
+            exec_index: 0,
+            execs_since_reimbursement: 0,
+            execs_since_slot: 0,
+            last_exec_at: 9,
+            trigger_context: TriggerContext::Now,
+        });
+        let exec_thread = VersionedThread::V1(thread);
+        let tx = packed_tx(&exec_thread, &config);
+        assert_eq!(
+            thread_ix_data(&tx),
+            [sablier_thread_program::instruction::ThreadExec {}.data()]
+        );
+
+        // Without a cap, the thread packs as many execs as its rate limit allows.
+        let tx = packed_tx(&exec_thread, &PluginConfig::default());
+        assert_eq!(thread_ix_data(&tx).len(), 3);
+
+        // The simulated packing stops at the lowest of the rate limit and the cap.
+        assert!(!may_pack_exec(1, Some(1), 0, 4));
+        assert!(!may_pack_exec(1, Some(4), 1, 1));
+        assert!(may_pack_exec(1, Some(2), 1, 4));
+        assert!(may_pack_exec(3, None, 1, 4));
+    }
+
+    #[test]
+    fn exec_state_is_read_without_the_instruction_set() {
+        let instruction = SerializableInstruction {
+            program_id: Pubkey::new_unique(),
+            accounts: vec![SerializableAccount::mutable(PAYER_PUBKEY, true)],
+            data: vec![1; 64],
+        };
+        let VersionedThread::V1(mut thread) =
+            decode_thread(&thread_account_data(Some(instruction.clone()))).unwrap();
+        thread.domain = Some(b"domain".to_vec());
+        thread.instructions = vec![instruction.clone(); 10];
+        thread.exec_context = Some(ExecContext {
+            exec_index: 3,
+            execs_since_reimbursement: 0,
+            execs_since_slot: 2,
+            last_exec_at: 10,
+            trigger_context: TriggerContext::Now,
+        });
+        thread.rate_limit = 5;
+        let mut data = vec![];
+        thread.try_serialize(&mut data).unwrap();
+
+        let thread = decode_thread(&data).unwrap();
+        assert_eq!(
+            decode_exec_state(&data).unwrap(),
+            crate::events::ThreadExecState {
+                exec_context: thread.exec_context(),
+                fee: thread.fee(),
+                next_instruction: thread.next_instruction(),
+                rate_limit: thread.rate_limit(),
+            }
+        );
+        assert!(decode_exec_state(&data[..data.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn periodic_thread_of_the_previous_layout_still_deserializes() {
+        // A periodic thread trimmed to its exact length by a kickoff, as written before interval triggers.
+        // The trigger is the last field, `Trigger::Now` is only its variant index.
+        let mut data = thread_account_data(None);
+        assert_eq!(data.pop(), Some(2));
+        data.push(7);
+        data.extend_from_slice(&60u64.to_le_bytes());
+
+        let thread = Thread::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(thread.trigger, Trigger::Periodic { delay: 60 });
+        assert_eq!(thread.trigger.interval(), Some((60, false)));
+        let mut serialized = vec![];
+        thread.try_serialize(&mut serialized).unwrap();
+        assert_eq!(serialized, data);
+        assert_eq!(
+            decode_thread(&data).unwrap().trigger(),
+            Trigger::Periodic { delay: 60 }
+        );
+    }
+
+    #[test]
+    fn future_thread_layout_is_unsupported() {
+        let mut data = thread_account_data(None);
+        let discriminator: [u8; 8] = hashv(&[b"account:ThreadV2"]).to_bytes()[..8]
+            .try_into()
+            .unwrap();
+        assert_ne!(discriminator, Thread::discriminator());
+        data[..8].copy_from_slice(&discriminator);
+
+        assert!(matches!(
+            decode_thread(&data),
+            Err(PluginError::UnsupportedThreadVersion(unsupported)) if unsupported == discriminator
+        ));
+        assert!(
+            AccountUpdate::new(Pubkey::new_unique(), sablier_thread_program::ID, &data)
+                .event
+                .is_none()
+        );
+    }
 }
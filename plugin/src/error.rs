@@ -1,6 +1,11 @@
-use solana_client::client_error::ClientError;
+use solana_client::{client_error::ClientError, nonblocking::pubsub_client::PubsubClientError};
 use solana_geyser_plugin_interface::geyser_plugin_interface::GeyserPluginError;
-use solana_sdk::transaction::TransactionError;
+use solana_sdk::{
+    message::CompileError,
+    pubkey::{ParsePubkeyError, Pubkey},
+    signer::SignerError,
+    transaction::TransactionError,
+};
 use std::array::TryFromSliceError;
 use thiserror::Error;
 
@@ -12,6 +17,8 @@ pub enum PluginError {
     AnchorError(#[from] anchor_lang::error::Error),
     #[error("Solana client error: {0}")]
     SolanaClientError(#[from] ClientError),
+    #[error("Websocket client error: {0}")]
+    PubsubClientError(#[from] PubsubClientError),
     #[error("The pubkey cannot be deserialized: {0}")]
     ParsingError(#[from] TryFromSliceError),
     #[error("RPC client has not reached min context slot")]
@@ -22,6 +29,24 @@ pub enum PluginError {
     FailedToSendTx,
     #[error("Tx failed simulation: {0} Logs: {1:#?}")]
     FailedToSimulateTx(TransactionError, Option<Vec<String>>),
+    #[error("The pubkey cannot be parsed: {0}")]
+    InvalidPubkey(#[from] ParsePubkeyError),
+    #[error("HTTP client error: {0}")]
+    HttpError(#[from] reqwest::Error),
+    #[error("Block engine error: {0}")]
+    BlockEngineError(String),
+    #[error("Failed to compile the message: {0}")]
+    CompileError(#[from] CompileError),
+    #[error("Failed to sign the transaction: {0}")]
+    SignerError(#[from] SignerError),
+    #[error("Invalid lookup table: {0}")]
+    InvalidLookupTable(Pubkey),
+    #[error("Unsupported thread account version, discriminator: {0:?}")]
+    UnsupportedThreadVersion([u8; 8]),
+    #[error("Invalid injected instruction of program {0}: {1}")]
+    InvalidInjectedInstruction(String, String),
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
 }
 
 impl From<PluginError> for GeyserPluginError {
@@ -1,5 +1,9 @@
-use anchor_lang::{AccountDeserialize, Discriminator};
-use sablier_thread_program::state::{Thread, VersionedThread};
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator};
+use log::info;
+use sablier_network_program::state::Pool;
+use sablier_thread_program::state::{
+    ExecContext, SerializableInstruction, Thread, VersionedThread,
+};
 use sablier_utils::pyth::{self, PriceFeedMessage, PriceUpdateV2};
 use solana_geyser_plugin_interface::geyser_plugin_interface::ReplicaAccountInfoVersions;
 use solana_sdk::{clock::Clock, pubkey::Pubkey, sysvar};
@@ -9,6 +13,7 @@ use crate::error::PluginError;
 #[derive(Debug)]
 pub struct AccountUpdate {
     pub key: Pubkey,
+    pub owner: Pubkey,
     pub event: Option<AccountUpdateEvent>,
 }
 
@@ -17,6 +22,7 @@ pub enum AccountUpdateEvent {
     Clock { clock: Clock },
     Thread { thread: Box<VersionedThread> },
     PriceFeed { price_feed: PriceFeedMessage },
+    Pool,
 }
 
 impl<'a> From<ReplicaAccountInfoVersions<'a>> for AccountUpdate {
@@ -31,9 +37,105 @@ impl<'a> From<ReplicaAccountInfoVersions<'a>> for AccountUpdate {
         let owner = Pubkey::try_from(owner).unwrap_or_default();
         let key = Pubkey::try_from(key).unwrap_or_default();
 
-        let event = parse_event(key, owner, data).unwrap_or_default();
+        AccountUpdate::new(key, owner, data)
+    }
+}
+
+impl AccountUpdate {
+    pub fn new(key: Pubkey, owner: Pubkey, data: &[u8]) -> Self {
+        let event = parse_event(key, owner, data).unwrap_or_else(|err| {
+            if let PluginError::UnsupportedThreadVersion(_) = err {
+                info!("thread: {} {}", key, err);
+            }
+            None
+        });
+
+        AccountUpdate { key, owner, event }
+    }
+}
+
+/// Deserializes a thread account into the thread version matching its discriminator.
+/// A layout this plugin doesn't know is reported rather than mis-parsed as a known one.
+pub fn decode_thread(mut data: &[u8]) -> Result<VersionedThread, PluginError> {
+    let discriminator: [u8; 8] = data
+        .get(..8)
+        .unwrap_or_default()
+        .try_into()
+        .map_err(|_| PluginError::UnsupportedThreadVersion([0; 8]))?;
+    if discriminator == Thread::discriminator() {
+        return Ok(VersionedThread::V1(Thread::try_deserialize(&mut data)?));
+    }
+    Err(PluginError::UnsupportedThreadVersion(discriminator))
+}
+
+/// The fields of a thread account the exec packing loop reads from each simulated account.
+#[derive(Debug, PartialEq)]
+pub struct ThreadExecState {
+    pub exec_context: Option<ExecContext>,
+    pub fee: u64,
+    pub next_instruction: Option<SerializableInstruction>,
+    pub rate_limit: u64,
+}
+
+/// Reads the exec state of a thread account, skipping over the rest of the layout without allocating it.
+/// The thread's instruction set in particular is never deserialized.
+pub fn decode_exec_state(data: &[u8]) -> Result<ThreadExecState, PluginError> {
+    let discriminator: [u8; 8] = data
+        .get(..8)
+        .unwrap_or_default()
+        .try_into()
+        .map_err(|_| PluginError::UnsupportedThreadVersion([0; 8]))?;
+    if discriminator != Thread::discriminator() {
+        return Err(PluginError::UnsupportedThreadVersion(discriminator));
+    }
+    let buf = &mut &data[8..];
+    skip(buf, 32 + 1 + 24)?; // authority, bump, created_at
+    if u8::deserialize(buf)? == 1 {
+        skip_bytes(buf)?; // domain
+    }
+    let exec_context = Option::<ExecContext>::deserialize(buf)?;
+    let fee = u64::deserialize(buf)?;
+    skip_bytes(buf)?; // id
+    for _ in 0..u32::deserialize(buf)? {
+        skip_instruction(buf)?;
+    }
+    let next_instruction = Option::<SerializableInstruction>::deserialize(buf)?;
+    skip(buf, 1)?; // paused
+    let rate_limit = u64::deserialize(buf)?;
+    Ok(ThreadExecState {
+        exec_context,
+        fee,
+        next_instruction,
+        rate_limit,
+    })
+}
 
-        AccountUpdate { key, event }
+fn skip(buf: &mut &[u8], len: usize) -> std::io::Result<()> {
+    if buf.len() < len {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    *buf = &buf[len..];
+    Ok(())
+}
+
+fn skip_bytes(buf: &mut &[u8]) -> std::io::Result<()> {
+    let len = u32::deserialize(buf)? as usize;
+    skip(buf, len)
+}
+
+fn skip_instruction(buf: &mut &[u8]) -> std::io::Result<()> {
+    skip(buf, 32)?; // program_id
+    let accounts = u32::deserialize(buf)? as usize;
+    skip(buf, accounts.saturating_mul(32 + 1 + 1))?;
+    skip_bytes(buf) // data
+}
+
+/// Returns the lamports and the data of the account.
+pub fn account_data<'a>(value: &ReplicaAccountInfoVersions<'a>) -> (u64, &'a [u8]) {
+    match value {
+        ReplicaAccountInfoVersions::V0_0_1(acc) => (acc.lamports, acc.data),
+        ReplicaAccountInfoVersions::V0_0_2(acc) => (acc.lamports, acc.data),
+        ReplicaAccountInfoVersions::V0_0_3(acc) => (acc.lamports, acc.data),
     }
 }
 
@@ -49,12 +151,13 @@ fn parse_event(
     }
 
     if owner == sablier_thread_program::ID && data.len() > 8 {
-        let d = &data[..8];
-        if d == Thread::discriminator() {
-            return Ok(Some(AccountUpdateEvent::Thread {
-                thread: Box::new(VersionedThread::V1(Thread::try_deserialize(&mut data)?)),
-            }));
-        }
+        return Ok(Some(AccountUpdateEvent::Thread {
+            thread: Box::new(decode_thread(data)?),
+        }));
+    }
+
+    if owner == sablier_network_program::ID && key == Pool::pubkey(0) {
+        return Ok(Some(AccountUpdateEvent::Pool));
     }
 
     if owner == pyth::ID {
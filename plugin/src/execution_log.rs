@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use log::info;
+use serde::Serialize;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+use crate::config::{LogFormat, PluginConfig};
+
+/// The lifecycle points of a thread execution.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ExecutionEvent {
+    Executable {
+        due_slot: u64,
+    },
+    Simulated {
+        sim_duration_ms: u64,
+        success: bool,
+        error: Option<String>,
+    },
+    Built {
+        signature: String,
+        instruction_count: usize,
+        compute_unit_limit: Option<u32>,
+        compute_unit_price: Option<u64>,
+    },
+    Submitted {
+        signature: String,
+    },
+    Confirmed {
+        signature: String,
+    },
+    Finalized {
+        signature: String,
+    },
+    Expired {
+        signature: String,
+    },
+    Failed {
+        signature: String,
+        error: String,
+    },
+}
+
+impl ExecutionEvent {
+    pub fn simulated(sim_duration: Duration, success: bool, error: Option<String>) -> Self {
+        Self::Simulated {
+            sim_duration_ms: sim_duration.as_millis() as u64,
+            success,
+            error,
+        }
+    }
+
+    pub fn built(
+        signature: &Signature,
+        instruction_count: usize,
+        compute_unit_limit: Option<u32>,
+        compute_unit_price: Option<u64>,
+    ) -> Self {
+        Self::Built {
+            signature: signature.to_string(),
+            instruction_count,
+            compute_unit_limit,
+            compute_unit_price,
+        }
+    }
+
+    pub fn submitted(signature: &Signature) -> Self {
+        Self::Submitted {
+            signature: signature.to_string(),
+        }
+    }
+
+    pub fn confirmed(signature: &Signature) -> Self {
+        Self::Confirmed {
+            signature: signature.to_string(),
+        }
+    }
+
+    pub fn finalized(signature: &Signature) -> Self {
+        Self::Finalized {
+            signature: signature.to_string(),
+        }
+    }
+
+    pub fn expired(signature: &Signature) -> Self {
+        Self::Expired {
+            signature: signature.to_string(),
+        }
+    }
+
+    pub fn failed(signature: &Signature, error: String) -> Self {
+        Self::Failed {
+            signature: signature.to_string(),
+            error,
+        }
+    }
+}
+
+/// An execution event of a thread, as seen by this worker at a slot.
+#[derive(Debug, Serialize)]
+pub struct ExecutionRecord<'a> {
+    pub slot: u64,
+    pub thread: String,
+    pub worker_id: u64,
+    #[serde(flatten)]
+    pub event: &'a ExecutionEvent,
+}
+
+impl<'a> ExecutionRecord<'a> {
+    pub fn new(slot: u64, thread: &Pubkey, worker_id: u64, event: &'a ExecutionEvent) -> Self {
+        Self {
+            slot,
+            thread: thread.to_string(),
+            worker_id,
+            event,
+        }
+    }
+}
+
+/// Receives the execution records.
+pub trait ExecutionSink: Send + Sync {
+    fn record(&self, record: &ExecutionRecord);
+}
+
+/// Writes each record as a line of JSON through the log crate.
+pub struct JsonLogSink;
+
+impl ExecutionSink for JsonLogSink {
+    fn record(&self, record: &ExecutionRecord) {
+        match serde_json::to_string(record) {
+            Ok(line) => info!("{}", line),
+            Err(err) => info!("Failed to serialize execution record: {:?}", err),
+        }
+    }
+}
+
+/// Returns the sink of the configured log format, the text format doesn't have any.
+pub fn build_execution_sink(config: &PluginConfig) -> Option<Box<dyn ExecutionSink>> {
+    match config.log_format {
+        LogFormat::Text => None,
+        LogFormat::Json => Some(Box::new(JsonLogSink)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_are_flat_json_objects() {
+        let thread = Pubkey::new_unique();
+        let event = ExecutionEvent::Submitted {
+            signature: "sig".to_string(),
+        };
+        let record = ExecutionRecord::new(42, &thread, 7, &event);
+        assert_eq!(
+            serde_json::to_value(record).unwrap(),
+            serde_json::json!({
+                "slot": 42,
+                "thread": thread.to_string(),
+                "worker_id": 7,
+                "event": "submitted",
+                "signature": "sig",
+            })
+        );
+    }
+}
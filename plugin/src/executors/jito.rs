@@ -0,0 +1,328 @@
+use std::{str::FromStr, time::Duration};
+
+use log::info;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_sdk::{
+    address_lookup_table::AddressLookupTableAccount,
+    instruction::{AccountMeta, Instruction},
+    message::{
+        v0::{LoadedAddresses, LoadedMessage},
+        VersionedMessage,
+    },
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::VersionedTransaction,
+};
+
+use crate::{
+    builders::compile_tx,
+    config::{JitoConfig, JitoTip},
+    error::PluginError,
+};
+
+static BUNDLE_STATUS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+static BUNDLE_STATUS_POLL_ATTEMPTS: usize = 15;
+
+/// Submits exec transactions as tipped bundles to a Jito block engine.
+pub struct JitoClient {
+    client: reqwest::Client,
+    bundles_url: String,
+    tip_account: Pubkey,
+    tip: JitoTip,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcResponse<T> {
+    result: Option<T>,
+    error: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BundleStatuses {
+    value: Vec<Option<BundleStatus>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BundleStatus {
+    pub slot: u64,
+    pub confirmation_status: Option<String>,
+    pub err: Value,
+}
+
+impl JitoClient {
+    pub fn new(config: &JitoConfig) -> Result<Self, PluginError> {
+        Ok(Self {
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(5))
+                .build()?,
+            bundles_url: format!(
+                "{}/api/v1/bundles",
+                config.block_engine_url.trim_end_matches('/')
+            ),
+            tip_account: Pubkey::from_str(&config.tip_account)?,
+            tip: config.tip,
+        })
+    }
+
+    /// Re-signs the transaction with a tip transfer appended to its instructions, paid by the fee payer if there is one.
+    /// Returns the reason the transaction could not be tipped otherwise, the block engine rejects untipped bundles.
+    pub fn append_tip(
+        &self,
+        tx: &VersionedTransaction,
+        keypair: &Keypair,
+        fee_payer: Option<&Keypair>,
+        thread_fee: u64,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<VersionedTransaction, &'static str> {
+        let lamports = self.tip.lamports(thread_fee);
+        if lamports == 0 {
+            return Err("zero tip");
+        }
+
+        let mut ixs =
+            decompile_instructions(&tx.message, lookup_tables).ok_or("missing lookup table")?;
+        ixs.push(system_instruction::transfer(
+            &fee_payer.unwrap_or(keypair).pubkey(),
+            &self.tip_account,
+            lamports,
+        ));
+        let tipped_tx = compile_tx(
+            &ixs,
+            keypair,
+            fee_payer,
+            *tx.message.recent_blockhash(),
+            lookup_tables,
+        )
+        .map_err(|_| "compile error")?;
+
+        match bincode::serialized_size(&tipped_tx) {
+            Ok(size) if size as usize <= PACKET_DATA_SIZE => Ok(tipped_tx),
+            _ => Err("packet size exceeded"),
+        }
+    }
+
+    /// Sends the transactions as a single bundle and returns the bundle id.
+    pub async fn send_bundle(&self, txs: &[VersionedTransaction]) -> Result<String, PluginError> {
+        let encoded_txs = txs
+            .iter()
+            .map(|tx| Ok(solana_sdk::bs58::encode(bincode::serialize(tx)?).into_string()))
+            .collect::<Result<Vec<String>, PluginError>>()?;
+
+        self.request("sendBundle", json!([encoded_txs])).await
+    }
+
+    pub async fn get_bundle_status(
+        &self,
+        bundle_id: &str,
+    ) -> Result<Option<BundleStatus>, PluginError> {
+        let statuses: BundleStatuses = self
+            .request("getBundleStatuses", json!([[bundle_id]]))
+            .await?;
+        Ok(statuses.value.into_iter().next().flatten())
+    }
+
+    /// Polls the block engine until the bundle lands or the poll attempts run out.
+    pub async fn poll_bundle_status(&self, bundle_id: String) {
+        for _ in 0..BUNDLE_STATUS_POLL_ATTEMPTS {
+            tokio::time::sleep(BUNDLE_STATUS_POLL_INTERVAL).await;
+            match self.get_bundle_status(&bundle_id).await {
+                Err(err) => {
+                    info!("Failed to get bundle status: {} err: {:?}", bundle_id, err);
+                    return;
+                }
+                Ok(None) => {}
+                Ok(Some(status)) => {
+                    info!(
+                        "Bundle landed: {} slot: {} confirmation_status: {:?} err: {}",
+                        bundle_id, status.slot, status.confirmation_status, status.err
+                    );
+                    return;
+                }
+            }
+        }
+        info!("Bundle not landed: {}", bundle_id);
+    }
+
+    async fn request<T: for<'de> Deserialize<'de>>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<T, PluginError> {
+        let response: JsonRpcResponse<T> = self
+            .client
+            .post(&self.bundles_url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        match (response.result, response.error) {
+            (Some(result), _) => Ok(result),
+            (None, err) => Err(PluginError::BlockEngineError(
+                err.map(|err| err.to_string()).unwrap_or_default(),
+            )),
+        }
+    }
+}
+
+/// Returns the instructions of the message, resolving the looked up accounts from the lookup tables.
+/// Returns None if a lookup table of the message is missing.
+fn decompile_instructions(
+    message: &VersionedMessage,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Option<Vec<Instruction>> {
+    let (account_keys, is_signer, is_writable): (Vec<Pubkey>, Vec<bool>, Vec<bool>) = match message
+    {
+        VersionedMessage::Legacy(message) => (
+            message.account_keys.clone(),
+            (0..message.account_keys.len())
+                .map(|i| message.is_signer(i))
+                .collect(),
+            (0..message.account_keys.len())
+                .map(|i| message.is_writable(i))
+                .collect(),
+        ),
+        VersionedMessage::V0(message) => {
+            let mut loaded_addresses = LoadedAddresses::default();
+            for lookup in &message.address_table_lookups {
+                let table = lookup_tables
+                    .iter()
+                    .find(|table| table.key == lookup.account_key)?;
+                for i in &lookup.writable_indexes {
+                    loaded_addresses
+                        .writable
+                        .push(*table.addresses.get(*i as usize)?);
+                }
+                for i in &lookup.readonly_indexes {
+                    loaded_addresses
+                        .readonly
+                        .push(*table.addresses.get(*i as usize)?);
+                }
+            }
+            let message = LoadedMessage::new_borrowed(message, &loaded_addresses);
+            let account_keys: Vec<Pubkey> = message.account_keys().iter().copied().collect();
+            (
+                account_keys.clone(),
+                (0..account_keys.len())
+                    .map(|i| message.is_signer(i))
+                    .collect(),
+                (0..account_keys.len())
+                    .map(|i| message.is_writable(i))
+                    .collect(),
+            )
+        }
+    };
+
+    Some(
+        message
+            .instructions()
+            .iter()
+            .map(|ix| Instruction {
+                program_id: account_keys[ix.program_id_index as usize],
+                accounts: ix
+                    .accounts
+                    .iter()
+                    .map(|i| {
+                        let i = *i as usize;
+                        AccountMeta {
+                            pubkey: account_keys[i],
+                            is_signer: is_signer[i],
+                            is_writable: is_writable[i],
+                        }
+                    })
+                    .collect(),
+                data: ix.data.clone(),
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::hash::Hash;
+
+    use super::*;
+
+    fn jito_client(tip: JitoTip) -> JitoClient {
+        JitoClient::new(&JitoConfig {
+            block_engine_url: "https://block-engine.example.com/".to_string(),
+            tip_account: Pubkey::new_unique().to_string(),
+            tip,
+        })
+        .unwrap()
+    }
+
+    fn exec_tx(keypair: &Keypair) -> VersionedTransaction {
+        let ix = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![AccountMeta::new(keypair.pubkey(), true)],
+        );
+        compile_tx(&[ix], keypair, None, Hash::new_unique(), &[]).unwrap()
+    }
+
+    #[test]
+    fn tip_is_a_capped_share_of_the_thread_fee() {
+        assert_eq!(JitoTip::Lamports(1_000).lamports(5_000), 1_000);
+        assert_eq!(JitoTip::FeePercentage(10).lamports(5_000), 500);
+        assert_eq!(JitoTip::FeePercentage(1_000).lamports(5_000), 5_000);
+        assert_eq!(JitoTip::FeePercentage(100).lamports(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn tip_is_transferred_to_the_tip_account() {
+        let keypair = Keypair::new();
+        let tx = exec_tx(&keypair);
+        let jito = jito_client(JitoTip::FeePercentage(150));
+
+        let tipped_tx = jito.append_tip(&tx, &keypair, None, 2_000, &[]).unwrap();
+        let ixs = decompile_instructions(&tipped_tx.message, &[]).unwrap();
+        assert_eq!(ixs.len(), 2);
+        assert_eq!(ixs[0], decompile_instructions(&tx.message, &[]).unwrap()[0]);
+        assert_eq!(
+            ixs[1],
+            system_instruction::transfer(&keypair.pubkey(), &jito.tip_account, 2_000)
+        );
+        assert!(tipped_tx
+            .verify_with_results()
+            .into_iter()
+            .all(|verified| verified));
+    }
+
+    #[test]
+    fn untippable_transactions_are_reported() {
+        let keypair = Keypair::new();
+        let tx = exec_tx(&keypair);
+
+        let jito = jito_client(JitoTip::Lamports(0));
+        assert_eq!(
+            jito.append_tip(&tx, &keypair, None, 2_000, &[]),
+            Err("zero tip")
+        );
+        let jito = jito_client(JitoTip::FeePercentage(10));
+        assert_eq!(
+            jito.append_tip(&tx, &keypair, None, 0, &[]),
+            Err("zero tip")
+        );
+
+        let ix = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[0; PACKET_DATA_SIZE],
+            vec![AccountMeta::new(keypair.pubkey(), true)],
+        );
+        let tx = compile_tx(&[ix], &keypair, None, Hash::new_unique(), &[]).unwrap();
+        assert_eq!(
+            jito.append_tip(&tx, &keypair, None, 2_000, &[]),
+            Err("packet size exceeded")
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use std::{collections::HashMap, str::FromStr};
+
+use log::info;
+use sablier_network_program::state::{Fee, Pool, Worker};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    address_lookup_table::{
+        instruction::{
+            close_lookup_table, create_lookup_table, deactivate_lookup_table, extend_lookup_table,
+        },
+        state::{AddressLookupTable, LOOKUP_TABLE_MAX_ADDRESSES},
+        AddressLookupTableAccount,
+    },
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::Transaction,
+};
+use tokio::sync::RwLock;
+
+use crate::{config::PluginConfig, error::PluginError};
+
+/// The number of times an account must be seen in exec transactions before it is added to the table.
+static HOT_ACCOUNT_THRESHOLD: u64 = 16;
+
+/// Max number of addresses added to the table in a single extend transaction.
+static EXTEND_BATCH_SIZE: usize = 20;
+
+/// A lookup table owned by the signatory, holding the accounts shared by the worker's exec transactions.
+#[derive(Default)]
+pub struct LookupTableManager {
+    address: RwLock<Option<Pubkey>>,
+    table: RwLock<Option<AddressLookupTableAccount>>,
+    hot_accounts: RwLock<HashMap<Pubkey, u64>>,
+}
+
+impl LookupTableManager {
+    /// Returns the lookup tables to compile exec transactions against.
+    /// Only the addresses which are already active are returned.
+    pub async fn tables(&self) -> Vec<AddressLookupTableAccount> {
+        self.table.read().await.iter().cloned().collect()
+    }
+
+    /// Records the accounts of a built exec transaction, the most frequent ones are added to the table.
+    pub async fn observe(&self, accounts: &[Pubkey]) {
+        let mut w_state = self.hot_accounts.write().await;
+        for account in accounts {
+            *w_state.entry(*account).or_default() += 1;
+        }
+    }
+
+    /// Creates or loads the lookup table, refreshes its active addresses and extends it with the
+    /// worker's static accounts and the hot accounts it does not hold yet.
+    pub async fn sync(
+        &self,
+        client: &RpcClient,
+        keypair: &Keypair,
+        config: &PluginConfig,
+    ) -> Result<(), PluginError> {
+        let address = self.address(client, keypair, config).await?;
+        let table = fetch_lookup_table(client, &address).await?;
+        if table.meta.authority != Some(keypair.pubkey())
+            || table.meta.deactivation_slot != u64::MAX
+        {
+            return Err(PluginError::InvalidLookupTable(address));
+        }
+
+        // Addresses extended in the current slot can only be looked up from the next one.
+        let slot = client.get_slot().await?;
+        let extension_pending = table.meta.last_extended_slot >= slot;
+        let active_addresses = if extension_pending {
+            table.addresses[..table.meta.last_extended_slot_start_index as usize].to_vec()
+        } else {
+            table.addresses.to_vec()
+        };
+        *self.table.write().await = Some(AddressLookupTableAccount {
+            key: address,
+            addresses: active_addresses,
+        });
+        if extension_pending {
+            return Ok(());
+        }
+
+        // Extend the table with the accounts it is missing, as long as it has room for them.
+        let capacity = LOOKUP_TABLE_MAX_ADDRESSES.saturating_sub(table.addresses.len());
+        let new_addresses: Vec<Pubkey> = self
+            .wanted_accounts(config)
+            .await
+            .into_iter()
+            .filter(|account| !table.addresses.contains(account))
+            .take(capacity.min(EXTEND_BATCH_SIZE))
+            .collect();
+        if new_addresses.is_empty() {
+            return Ok(());
+        }
+        let ix = extend_lookup_table(
+            address,
+            keypair.pubkey(),
+            Some(keypair.pubkey()),
+            new_addresses.clone(),
+        );
+        send_ix(client, keypair, ix).await?;
+        info!(
+            "Extended lookup table: {} new_addresses: {:?}",
+            address, new_addresses
+        );
+        Ok(())
+    }
+
+    /// Deactivates then closes the given tables, sending their rent back to the signatory.
+    /// Tables must cool down after being deactivated, so closing them takes several calls.
+    /// Returns the tables which are not closed yet.
+    pub async fn close_stale_tables(
+        &self,
+        client: &RpcClient,
+        keypair: &Keypair,
+        tables: Vec<Pubkey>,
+    ) -> Vec<Pubkey> {
+        let active_address = *self.address.read().await;
+        let mut remaining = vec![];
+        for address in tables {
+            if Some(address) == active_address {
+                info!("Skipping closing the active lookup table: {}", address);
+                continue;
+            }
+            let table = match client
+                .get_account_with_commitment(&address, CommitmentConfig::processed())
+                .await
+            {
+                Err(err) => {
+                    info!("Failed to fetch lookup table: {} err: {:?}", address, err);
+                    remaining.push(address);
+                    continue;
+                }
+                Ok(response) => match response.value {
+                    None => continue,
+                    Some(account) => account,
+                },
+            };
+            let Ok(table) = AddressLookupTable::deserialize(&table.data) else {
+                info!("Skipping closing invalid lookup table: {}", address);
+                continue;
+            };
+
+            let ix = if table.meta.deactivation_slot == u64::MAX {
+                deactivate_lookup_table(address, keypair.pubkey())
+            } else {
+                close_lookup_table(address, keypair.pubkey(), keypair.pubkey())
+            };
+            match send_ix(client, keypair, ix).await {
+                Ok(()) if table.meta.deactivation_slot != u64::MAX => {
+                    info!("Closed lookup table: {}", address);
+                }
+                Ok(()) => {
+                    info!("Deactivated lookup table: {}", address);
+                    remaining.push(address);
+                }
+                Err(err) => {
+                    info!("Failed to close lookup table: {} err: {:?}", address, err);
+                    remaining.push(address);
+                }
+            }
+        }
+        remaining
+    }
+
+    async fn address(
+        &self,
+        client: &RpcClient,
+        keypair: &Keypair,
+        config: &PluginConfig,
+    ) -> Result<Pubkey, PluginError> {
+        if let Some(address) = *self.address.read().await {
+            return Ok(address);
+        }
+
+        let address = match config.lookup_table.address.as_ref() {
+            Some(address) => Pubkey::from_str(address)?,
+            None => {
+                let recent_slot = client
+                    .get_slot_with_commitment(CommitmentConfig::finalized())
+                    .await?;
+                let (ix, address) =
+                    create_lookup_table(keypair.pubkey(), keypair.pubkey(), recent_slot);
+                send_ix(client, keypair, ix).await?;
+                info!(
+                    "Created lookup table: {}, set it in the plugin config to reuse it across restarts",
+                    address
+                );
+                address
+            }
+        };
+        *self.address.write().await = Some(address);
+        Ok(address)
+    }
+
+    /// Returns the local workers' static accounts followed by the hot accounts, the most frequent first.
+    /// The observation counts are halved so that accounts which are no longer used cool down.
+    async fn wanted_accounts(&self, config: &PluginConfig) -> Vec<Pubkey> {
+        let mut accounts = vec![];
+        for worker in config.workers() {
+            let worker_pubkey = Worker::pubkey(worker.worker_id);
+            accounts.extend([worker_pubkey, Fee::pubkey(worker_pubkey)]);
+        }
+        accounts.extend([Pool::pubkey(0), system_program::ID]);
+
+        let mut w_state = self.hot_accounts.write().await;
+        let mut hot_accounts: Vec<(Pubkey, u64)> = w_state
+            .iter()
+            .filter(|(_, count)| **count >= HOT_ACCOUNT_THRESHOLD)
+            .map(|(account, count)| (*account, *count))
+            .collect();
+        hot_accounts.sort_by(|a, b| b.1.cmp(&a.1));
+        for (account, _count) in hot_accounts {
+            if !accounts.contains(&account) {
+                accounts.push(account);
+            }
+        }
+        w_state.retain(|_, count| {
+            *count /= 2;
+            *count > 0
+        });
+        accounts
+    }
+}
+
+async fn fetch_lookup_table(
+    client: &RpcClient,
+    address: &Pubkey,
+) -> Result<AddressLookupTable<'static>, PluginError> {
+    let data = client.get_account_data(address).await?;
+    let table = AddressLookupTable::deserialize(&data)
+        .map_err(|_| PluginError::InvalidLookupTable(*address))?;
+    Ok(AddressLookupTable {
+        meta: table.meta,
+        addresses: table.addresses.into_owned().into(),
+    })
+}
+
+async fn send_ix(
+    client: &RpcClient,
+    keypair: &Keypair,
+    ix: Instruction,
+) -> Result<(), PluginError> {
+    let blockhash = client.get_latest_blockhash().await?;
+    let tx =
+        Transaction::new_signed_with_payer(&[ix], Some(&keypair.pubkey()), &[keypair], blockhash);
+    client.send_and_confirm_transaction(&tx).await?;
+    Ok(())
+}
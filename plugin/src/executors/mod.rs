@@ -1,8 +1,13 @@
+pub mod jito;
+pub mod lookup_table;
+pub mod rpc_pool;
 pub mod state;
+pub mod submission;
 pub mod tx;
+pub mod worker;
 
 use anchor_lang::{prelude::Pubkey, AccountDeserialize};
-use log::info;
+use log::{info, warn};
 use solana_client::{
     client_error::{ClientError, ClientErrorKind, Result as ClientResult},
     nonblocking::rpc_client::RpcClient,
@@ -10,35 +15,335 @@ use solana_client::{
 use solana_geyser_plugin_interface::geyser_plugin_interface::Result as PluginResult;
 use solana_sdk::commitment_config::CommitmentConfig;
 use std::{
+    collections::HashMap,
     fmt::Debug,
+    str::FromStr,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
+    time::{Duration, Instant},
 };
-use tokio::runtime::Runtime;
+use tokio::{runtime::Runtime, sync::Mutex};
 use tx::TxExecutor;
 
-use crate::{config::PluginConfig, observers::Observers};
+use crate::{
+    config::{PluginConfig, SlotLagConfig},
+    error::PluginError,
+    health::Health,
+    metrics::Metrics,
+    observers::{
+        state::{AccountTriggers, SubmittedThreads, ThreadFilter, ThreadPipelines},
+        Observers,
+    },
+    persistence::PersistedState,
+    shutdown::Shutdown,
+    snapshot::SnapshotStream,
+};
 
 static LOCAL_RPC_URL: &str = "http://127.0.0.1:8899";
 
+/// The interval at which the blockhash cache is refreshed.
+static BLOCKHASH_REFRESH_INTERVAL: Duration = Duration::from_millis(400);
+
+/// The interval at which the signatory balance is fetched.
+static SIGNATORY_BALANCE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The interval at which the lookup table is synced and the stale ones are closed.
+static LOOKUP_TABLE_SYNC_INTERVAL: Duration = Duration::from_secs(10);
+
 pub struct Executors {
     pub tx: Arc<TxExecutor>,
     pub client: Arc<RpcClient>,
     pub lock: AtomicBool,
+    // The state persisted by the previous run, restored before the first slot is processed.
+    pub pending_state: Mutex<Option<PersistedState>>,
+    pub shutdown: Arc<Shutdown>,
+    // Publishes the executable threads to the external schedulers, if configured.
+    pub snapshot_stream: Option<Arc<SnapshotStream>>,
 }
 
 impl Executors {
-    pub fn new(config: PluginConfig) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        config: PluginConfig,
+        metrics: Arc<Metrics>,
+        health: Arc<Health>,
+        shutdown: Arc<Shutdown>,
+        submitted_threads: Arc<SubmittedThreads>,
+        account_triggers: Arc<AccountTriggers>,
+        thread_pipelines: Arc<ThreadPipelines>,
+        thread_filter: Arc<ThreadFilter>,
+    ) -> Self {
+        let snapshot_stream = config
+            .snapshot_stream
+            .as_ref()
+            .map(|snapshot_config| Arc::new(SnapshotStream::new(snapshot_config, metrics.clone())));
         Executors {
-            tx: Arc::new(TxExecutor::new(config.clone())),
+            tx: Arc::new(TxExecutor::new(
+                config.clone(),
+                metrics,
+                health,
+                submitted_threads,
+                account_triggers,
+                thread_pipelines,
+                thread_filter,
+            )),
             client: Arc::new(RpcClient::new_with_commitment(
                 LOCAL_RPC_URL.into(),
                 CommitmentConfig::processed(),
             )),
             lock: AtomicBool::new(false),
+            pending_state: Mutex::new(
+                config
+                    .persistence
+                    .path
+                    .as_deref()
+                    .and_then(PersistedState::load),
+            ),
+            shutdown,
+            snapshot_stream,
+        }
+    }
+
+    /// Keeps the blockhash cache warm so thread exec builds don't fetch it inline.
+    pub async fn refresh_blockhash(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(BLOCKHASH_REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.tx.blockhash_cache.refresh(&self.client).await {
+                info!("Failed to refresh blockhash: {:?}", err);
+            }
+        }
+    }
+
+    /// Tracks the signatory balances so the executor can throttle the workers running low.
+    pub async fn monitor_signatory_balance(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(SIGNATORY_BALANCE_INTERVAL);
+        loop {
+            interval.tick().await;
+            for worker in self.tx.workers.iter() {
+                let signatory_pubkey = worker.signatory();
+                match self.client.get_balance(&signatory_pubkey).await {
+                    Err(err) => info!("Failed to fetch signatory balance: {:?}", err),
+                    Ok(lamports) => {
+                        self.tx
+                            .metrics
+                            .signatory_balance
+                            .with_label_values(&[&worker.id.to_string()])
+                            .set(lamports as i64);
+                        if self
+                            .tx
+                            .signatory_balance
+                            .set(signatory_pubkey, lamports)
+                            .await
+                            != Some(lamports)
+                        {
+                            info!(
+                                "worker: {} signatory: {} balance: {}",
+                                worker.id, signatory_pubkey, lamports
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Compares the latest slot notified by geyser against the cluster slot, as seen by the RPC endpoints.
+    /// The builds are paused while the validator lags behind, they would run against stale state and fail.
+    pub async fn monitor_slot_lag(self: Arc<Self>) {
+        loop {
+            let config = self.tx.config().slot_lag.clone();
+            tokio::time::sleep(Duration::from_secs(config.check_interval_secs.max(1))).await;
+            let observed_slot = self.tx.health.observed_slot();
+            if !config.enabled || observed_slot == 0 {
+                self.record_slot_lag(0, false, &config);
+                continue;
+            }
+            match self
+                .tx
+                .rpc_pool()
+                .client()
+                .get_slot_with_commitment(CommitmentConfig::processed())
+                .await
+            {
+                Err(err) => info!("Failed to fetch the cluster slot: {:?}", err),
+                Ok(cluster_slot) => {
+                    let slot_lag = cluster_slot.saturating_sub(observed_slot);
+                    self.record_slot_lag(slot_lag, slot_lag > config.max_lag_slots, &config);
+                }
+            }
+        }
+    }
+
+    fn record_slot_lag(&self, slot_lag: u64, lagging: bool, config: &SlotLagConfig) {
+        self.tx.metrics.slot_lag.set(slot_lag as i64);
+        self.tx.metrics.slot_lag_paused.set(lagging as i64);
+        let was_lagging = self.tx.health.record_slot_lag(slot_lag, lagging);
+        if lagging && !was_lagging {
+            warn!(
+                "Validator is {} slots behind the cluster, above the max of {}, pausing the builds",
+                slot_lag, config.max_lag_slots
+            );
+        } else if !lagging && was_lagging {
+            warn!(
+                "Validator caught up with the cluster, {} slots behind, resuming the builds",
+                slot_lag
+            );
+        }
+    }
+
+    /// Keeps the signatory's lookup table in sync with the accounts of the exec transactions,
+    /// and closes the stale lookup tables listed in the config.
+    pub async fn manage_lookup_table(self: Arc<Self>) {
+        let mut stale_tables: Vec<Pubkey> = self
+            .tx
+            .config()
+            .lookup_table
+            .close
+            .iter()
+            .filter_map(|address| match Pubkey::from_str(address) {
+                Ok(address) => Some(address),
+                Err(err) => {
+                    info!("Invalid stale lookup table: {} err: {:?}", address, err);
+                    None
+                }
+            })
+            .collect();
+        let mut interval = tokio::time::interval(LOOKUP_TABLE_SYNC_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(err) = self
+                .tx
+                .lookup_table
+                .sync(
+                    &self.client,
+                    &self.tx.workers.primary().keypair,
+                    &self.tx.config(),
+                )
+                .await
+            {
+                info!("Failed to sync lookup table: {:?}", err);
+            }
+            if !stale_tables.is_empty() {
+                stale_tables = self
+                    .tx
+                    .lookup_table
+                    .close_stale_tables(
+                        &self.client,
+                        &self.tx.workers.primary().keypair,
+                        stale_tables,
+                    )
+                    .await;
+            }
+        }
+    }
+
+    /// Periodically writes the observer state to disk.
+    pub async fn persist_state(self: Arc<Self>, observers: Arc<Observers>) {
+        let mut interval = tokio::time::interval(Duration::from_secs(
+            self.tx.config().persistence.interval_secs,
+        ));
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.save_state(&observers).await {
+                info!("Failed to persist state: {:?}", err);
+            }
+        }
+    }
+
+    /// Stops accepting new executions and waits for the in-flight ones, then persists the observer state.
+    pub async fn shutdown(&self, observers: &Observers) {
+        self.shutdown.signal();
+        let timeout = Duration::from_millis(self.tx.config().shutdown_timeout_ms);
+        if self.shutdown.drain(timeout).await {
+            info!("Drained in-flight executions");
+        } else {
+            info!(
+                "Timed out draining in-flight executions: {} after: {:?}",
+                self.shutdown.in_flight(),
+                timeout
+            );
+        }
+        if let Err(err) = self.save_state(observers).await {
+            info!("Failed to persist state: {:?}", err);
+        }
+    }
+
+    /// Writes the observer state to disk, unless the persisted state has not been restored yet.
+    pub async fn save_state(&self, observers: &Observers) -> Result<(), PluginError> {
+        let config = self.tx.config();
+        let Some(path) = config.persistence.path.as_deref() else {
+            return Ok(());
+        };
+        if self.pending_state.lock().await.is_some() {
+            return Ok(());
+        }
+        PersistedState::capture(&observers.thread, &self.tx.executable_threads)
+            .await
+            .save(path)
+    }
+
+    async fn restore_state(&self, observers: &Observers, state: PersistedState) {
+        match state.validate(&self.client).await {
+            Err(err) => info!(
+                "Failed to validate persisted state: {:?}, starting cold",
+                err
+            ),
+            Ok(state) => {
+                state
+                    .restore(&observers.thread, &self.tx.executable_threads)
+                    .await
+            }
+        }
+    }
+
+    /// Executes the threads just created or reset with a now trigger, without waiting for the next slot.
+    /// They are left to the next slot if one is being processed, or if they are only published.
+    pub async fn execute_immediately(
+        self: Arc<Self>,
+        observers: Arc<Observers>,
+        slot: u64,
+        runtime: Arc<Runtime>,
+    ) {
+        if self.tx.config().publish_only() {
+            return;
+        }
+        let Some(_in_flight) = self.shutdown.track() else {
+            return;
+        };
+        if self
+            .lock
+            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+        {
+            return;
+        }
+
+        // Take the threads out of the now threads, so the next slot doesn't build them again.
+        let observed_threads: HashMap<Pubkey, Instant> = {
+            let mut w_now_threads = observers.thread.now_threads.write().await;
+            let r_immediate_threads = observers.thread.immediate_threads.read().await;
+            r_immediate_threads
+                .iter()
+                .filter(|(thread_pubkey, _)| w_now_threads.remove(thread_pubkey))
+                .map(|(thread_pubkey, observed_at)| (*thread_pubkey, *observed_at))
+                .collect()
+        };
+        if !observed_threads.is_empty() {
+            info!(
+                "execute_immediately: {} slot: {}",
+                observed_threads.len(),
+                slot
+            );
+            self.tx
+                .clone()
+                .execute_immediate_txs(self.client.clone(), observed_threads, slot, runtime)
+                .await;
         }
+
+        self.lock.store(false, Ordering::Relaxed);
     }
 
     pub async fn process_slot(
@@ -49,9 +354,22 @@ impl Executors {
     ) -> PluginResult<()> {
         info!("process_slot: {}", slot,);
         let now = std::time::Instant::now();
+        self.tx.health.record_observed_slot(slot);
+
+        // Stop accepting new executions once the plugin is unloading.
+        let Some(_in_flight) = self.shutdown.track() else {
+            info!(
+                "processed_slot: {} duration: {:?} status: stopping",
+                slot,
+                now.elapsed()
+            );
+            return Ok(());
+        };
 
         // Return early if node is not healthy.
-        if self.client.get_health().await.is_err() {
+        let rpc_healthy = self.client.get_health().await.is_ok();
+        self.tx.health.record_rpc_health(rpc_healthy);
+        if !rpc_healthy {
             info!(
                 "processed_slot: {} duration: {:?} status: unhealthy",
                 slot,
@@ -74,22 +392,46 @@ impl Executors {
             return Ok(());
         }
 
+        // Restore the persisted state before the first slot is processed.
+        let pending_state = self.pending_state.lock().await.take();
+        if let Some(state) = pending_state {
+            self.restore_state(&observers, state).await;
+        }
+
         // Process the slot on the observers.
         let executable_threads = observers.thread.clone().process_slot(slot).await;
+        if let Some(snapshot_stream) = self.snapshot_stream.as_ref() {
+            snapshot_stream.publish(&observers.thread, slot, &executable_threads);
+        }
 
-        // Process the slot in the transaction executor.
-        self.tx
-            .clone()
-            .execute_txs(
-                self.client.clone(),
-                executable_threads,
-                slot,
-                runtime.clone(),
-            )
-            .await?;
+        // Process the slot in the transaction executor, unless the external schedulers execute the threads.
+        if !self.tx.config().publish_only() {
+            self.tx
+                .clone()
+                .execute_txs(
+                    self.client.clone(),
+                    executable_threads,
+                    slot,
+                    runtime.clone(),
+                )
+                .await?;
+        }
+
+        // Drop the threads which could not be fetched for too long, their close was likely missed.
+        for thread_pubkey in self
+            .tx
+            .unreachable_threads
+            .expired(slot, self.tx.config().thread_fetch_ttl_slots)
+            .await
+        {
+            info!("Purging unreachable thread: {}", thread_pubkey);
+            observers.thread.purge_thread(&thread_pubkey).await;
+            self.tx.forget_thread(&thread_pubkey).await;
+        }
 
         // Release the lock.
         self.lock.store(false, std::sync::atomic::Ordering::Relaxed);
+        self.tx.health.record_processed_slot(slot);
         info!(
             "processed_slot: {} duration: {:?} status: processed",
             slot,
@@ -0,0 +1,298 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use log::{info, warn};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::SerializableTransaction,
+    rpc_config::RpcSimulateTransactionConfig,
+    rpc_custom_error::JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED,
+    rpc_request::RpcError,
+    rpc_response::{Response, RpcSimulateTransactionResult},
+};
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
+
+use crate::{
+    config::CircuitBreakerConfig,
+    executors::state::{BreakerState, CircuitBreaker},
+    metrics::Metrics,
+};
+
+/// The number of most recent requests used to compute an endpoint's error rate.
+static HEALTH_WINDOW: usize = 100;
+
+/// The number of consecutive rate limited requests after which an endpoint is deprioritized.
+static RATE_LIMIT_THRESHOLD: u32 = 3;
+
+/// The weight of the latest request in an endpoint's average latency.
+static LATENCY_SMOOTHING: f64 = 0.2;
+
+/// A set of RPC endpoints, routing requests to the healthiest one and failing over on errors.
+pub struct RpcPool {
+    endpoints: Vec<RpcEndpoint>,
+    metrics: Arc<Metrics>,
+}
+
+pub struct RpcEndpoint {
+    pub url: String,
+    pub client: Arc<RpcClient>,
+    health: Mutex<EndpointHealth>,
+    breaker: Mutex<CircuitBreaker>,
+}
+
+#[derive(Default)]
+struct EndpointHealth {
+    outcomes: VecDeque<bool>,
+    latency_ms: f64,
+    consecutive_rate_limits: u32,
+}
+
+impl EndpointHealth {
+    fn error_rate(&self) -> f64 {
+        if self.outcomes.is_empty() {
+            return 0.0;
+        }
+        self.outcomes.iter().filter(|ok| !**ok).count() as f64 / self.outcomes.len() as f64
+    }
+
+    fn record(&mut self, ok: bool, latency: Duration) {
+        if self.outcomes.len() == HEALTH_WINDOW {
+            self.outcomes.pop_front();
+        }
+        self.outcomes.push_back(ok);
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        self.latency_ms = if self.latency_ms == 0.0 {
+            latency_ms
+        } else {
+            LATENCY_SMOOTHING * latency_ms + (1.0 - LATENCY_SMOOTHING) * self.latency_ms
+        };
+    }
+
+    fn score(&self) -> (bool, f64, f64) {
+        (
+            self.consecutive_rate_limits >= RATE_LIMIT_THRESHOLD,
+            self.error_rate(),
+            self.latency_ms,
+        )
+    }
+}
+
+enum Outcome {
+    Ok,
+    /// The endpoint failed in a way that another endpoint might not.
+    Failover,
+    /// The endpoint answered, the error would be the same anywhere.
+    Fatal,
+}
+
+impl RpcPool {
+    pub fn new(
+        urls: &[String],
+        commitment: CommitmentConfig,
+        timeout: Duration,
+        breaker: &CircuitBreakerConfig,
+        metrics: Arc<Metrics>,
+    ) -> Self {
+        assert!(!urls.is_empty(), "the rpc pool needs at least one url");
+        Self {
+            endpoints: urls
+                .iter()
+                .map(|url| RpcEndpoint {
+                    url: url.clone(),
+                    client: Arc::new(RpcClient::new_with_timeout_and_commitment(
+                        url.clone(),
+                        timeout,
+                        commitment,
+                    )),
+                    health: Mutex::new(EndpointHealth::default()),
+                    breaker: Mutex::new(CircuitBreaker::new(breaker)),
+                })
+                .collect(),
+            metrics,
+        }
+    }
+
+    /// Returns the client of the healthiest endpoint.
+    pub fn client(&self) -> Arc<RpcClient> {
+        self.ranked()[0].client.clone()
+    }
+
+    pub async fn simulate_transaction_with_config<T: SerializableTransaction + Sync>(
+        &self,
+        tx: &T,
+        config: RpcSimulateTransactionConfig,
+    ) -> ClientResult<Response<RpcSimulateTransactionResult>> {
+        self.request(|client| {
+            let config = config.clone();
+            async move { client.simulate_transaction_with_config(tx, config).await }
+        })
+        .await
+    }
+
+    pub async fn send_transaction<T: SerializableTransaction + Sync>(
+        &self,
+        tx: &T,
+    ) -> ClientResult<Signature> {
+        self.request(|client| async move { client.send_transaction(tx).await })
+            .await
+    }
+
+    /// Returns false while the breaker of every endpoint is open, no request would go through.
+    pub fn is_available(&self, now: Instant) -> bool {
+        self.endpoints.iter().any(|endpoint| {
+            !matches!(
+                endpoint.breaker.lock().unwrap().state(),
+                BreakerState::Open { until } if now < until
+            )
+        })
+    }
+
+    /// Logs the error rate and latency of each endpoint.
+    pub fn log_health(&self) {
+        for endpoint in &self.endpoints {
+            let health = endpoint.health.lock().unwrap();
+            info!(
+                "rpc_endpoint: {} error_rate: {:.2} latency_ms: {:.1} consecutive_rate_limits: {}",
+                endpoint.url,
+                health.error_rate(),
+                health.latency_ms,
+                health.consecutive_rate_limits
+            );
+        }
+    }
+
+    fn ranked(&self) -> Vec<&RpcEndpoint> {
+        let mut endpoints: Vec<(&RpcEndpoint, (bool, f64, f64))> = self
+            .endpoints
+            .iter()
+            .map(|endpoint| (endpoint, endpoint.health.lock().unwrap().score()))
+            .collect();
+        endpoints.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        endpoints
+            .into_iter()
+            .map(|(endpoint, _)| endpoint)
+            .collect()
+    }
+
+    /// Tries the endpoints from healthiest to least healthy until one of them answers.
+    /// An endpoint which has not reached the min context slot is skipped without being penalized,
+    /// the error is only returned if no other endpoint has caught up.
+    async fn request<T, F, Fut>(&self, f: F) -> ClientResult<T>
+    where
+        F: Fn(Arc<RpcClient>) -> Fut,
+        Fut: std::future::Future<Output = ClientResult<T>>,
+    {
+        let mut min_context_slot_err: Option<ClientError> = None;
+        let mut last_err: Option<ClientError> = None;
+        for endpoint in self.ranked() {
+            let now = Instant::now();
+            if endpoint.breaker.lock().unwrap().admit(1, now) == 0 {
+                continue;
+            }
+            let result = f(endpoint.client.clone()).await;
+            let latency = now.elapsed();
+
+            let outcome = match &result {
+                Ok(_) => Outcome::Ok,
+                Err(err) => classify(err),
+            };
+            {
+                let mut health = endpoint.health.lock().unwrap();
+                match &result {
+                    Err(err) if is_rate_limited(err) => health.consecutive_rate_limits += 1,
+                    _ => health.consecutive_rate_limits = 0,
+                }
+                if is_min_context_slot_not_reached(&result) {
+                    self.metrics
+                        .min_context_slot_not_reached
+                        .with_label_values(&[&endpoint.url])
+                        .inc();
+                } else {
+                    health.record(!matches!(outcome, Outcome::Failover), latency);
+                    let transition = endpoint
+                        .breaker
+                        .lock()
+                        .unwrap()
+                        .record(!matches!(outcome, Outcome::Failover), Instant::now());
+                    if let Some(state) = transition {
+                        self.record_breaker_transition(&endpoint.url, state);
+                    }
+                }
+            }
+
+            match outcome {
+                Outcome::Ok | Outcome::Fatal => return result,
+                Outcome::Failover => {
+                    let err = result.err().unwrap();
+                    if is_min_context_slot_not_reached_err(&err) {
+                        min_context_slot_err = Some(err);
+                    } else {
+                        last_err = Some(err);
+                    }
+                    if self.endpoints.len() > 1 {
+                        info!("Failing over from rpc_endpoint: {}", endpoint.url);
+                    }
+                }
+            }
+        }
+        Err(min_context_slot_err.or(last_err).unwrap_or_else(|| {
+            ClientError::from(ClientErrorKind::Custom(
+                "No RPC endpoint available, the circuit breakers are open".into(),
+            ))
+        }))
+    }
+
+    fn record_breaker_transition(&self, url: &str, state: BreakerState) {
+        self.metrics
+            .circuit_breaker_state
+            .with_label_values(&[url])
+            .set(state.gauge());
+        match state {
+            BreakerState::Open { .. } => {
+                self.metrics
+                    .circuit_breaker_trips
+                    .with_label_values(&[url])
+                    .inc();
+                warn!(
+                    "Circuit breaker opened for rpc_endpoint: {}, too many requests failed",
+                    url
+                );
+            }
+            _ => info!("Circuit breaker of rpc_endpoint: {} is {:?}", url, state),
+        }
+    }
+}
+
+fn classify(err: &ClientError) -> Outcome {
+    match &err.kind {
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => Outcome::Failover,
+        ClientErrorKind::RpcError(RpcError::RpcRequestError(_)) => Outcome::Failover,
+        _ if is_min_context_slot_not_reached_err(err) => Outcome::Failover,
+        _ => Outcome::Fatal,
+    }
+}
+
+fn is_rate_limited(err: &ClientError) -> bool {
+    match &err.kind {
+        ClientErrorKind::Reqwest(err) => {
+            err.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS)
+        }
+        _ => false,
+    }
+}
+
+fn is_min_context_slot_not_reached<T>(result: &ClientResult<T>) -> bool {
+    matches!(result, Err(err) if is_min_context_slot_not_reached_err(err))
+}
+
+pub fn is_min_context_slot_not_reached_err(err: &ClientError) -> bool {
+    matches!(
+        err.kind,
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. })
+            if code == JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED
+    )
+}
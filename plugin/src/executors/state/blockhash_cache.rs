@@ -0,0 +1,132 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use solana_client::{client_error::Result as ClientResult, nonblocking::rpc_client::RpcClient};
+use solana_sdk::{commitment_config::CommitmentConfig, hash::Hash};
+use tokio::sync::RwLock;
+
+/// The number of fetched blockhashes whose last valid block height is remembered.
+/// A blockhash is valid for 150 blocks, so this covers the lifetime of the transactions built with it.
+static RECENT_BLOCKHASHES_LIMIT: usize = 512;
+
+/// The latest blockhash and the instant it was fetched at, shared by all the transactions built in a slot.
+/// The last valid block heights of the recently fetched blockhashes are kept, to tell when a transaction expired.
+#[derive(Default)]
+pub struct BlockhashCache {
+    latest: RwLock<Option<(Hash, Instant)>>,
+    recent: RwLock<VecDeque<(Hash, u64)>>,
+    commitment: CommitmentConfig,
+}
+
+impl BlockhashCache {
+    /// Creates a cache fetching the blockhashes at the commitment.
+    pub fn new(commitment: CommitmentConfig) -> Self {
+        Self {
+            commitment,
+            ..Self::default()
+        }
+    }
+
+    /// Fetches the latest blockhash and stores it in the cache.
+    pub async fn refresh(&self, client: &RpcClient) -> ClientResult<Hash> {
+        let latest = client
+            .get_latest_blockhash_with_commitment(self.commitment)
+            .await?;
+        Ok(self.store(latest).await)
+    }
+
+    /// Returns the cached blockhash, or fetches it directly if it is older than `max_age`.
+    pub async fn get(&self, client: &RpcClient, max_age: Duration) -> ClientResult<Hash> {
+        self.get_or_fetch(max_age, || {
+            client.get_latest_blockhash_with_commitment(self.commitment)
+        })
+        .await
+    }
+
+    /// Returns the last block height at which a transaction with the blockhash can land.
+    pub async fn last_valid_block_height(&self, blockhash: &Hash) -> Option<u64> {
+        let r_recent = self.recent.read().await;
+        r_recent
+            .iter()
+            .rev()
+            .find(|(recent_blockhash, _)| recent_blockhash == blockhash)
+            .map(|(_, last_valid_block_height)| *last_valid_block_height)
+    }
+
+    async fn get_or_fetch<F, Fut>(&self, max_age: Duration, fetch: F) -> ClientResult<Hash>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = ClientResult<(Hash, u64)>>,
+    {
+        if let Some((blockhash, fetched_at)) = *self.latest.read().await {
+            if fetched_at.elapsed() < max_age {
+                return Ok(blockhash);
+            }
+        }
+
+        let latest = fetch().await?;
+        Ok(self.store(latest).await)
+    }
+
+    async fn store(&self, (blockhash, last_valid_block_height): (Hash, u64)) -> Hash {
+        *self.latest.write().await = Some((blockhash, Instant::now()));
+        let mut w_recent = self.recent.write().await;
+        if w_recent.back().map(|(recent, _)| *recent) != Some(blockhash) {
+            if w_recent.len() == RECENT_BLOCKHASHES_LIMIT {
+                w_recent.pop_front();
+            }
+            w_recent.push_back((blockhash, last_valid_block_height));
+        }
+        blockhash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    async fn build_many(cache: &BlockhashCache, max_age: Duration, count: usize) -> usize {
+        let rpc_calls = AtomicUsize::new(0);
+        for _ in 0..count {
+            cache
+                .get_or_fetch(max_age, || async {
+                    rpc_calls.fetch_add(1, Ordering::Relaxed);
+                    Ok((Hash::new_unique(), 150))
+                })
+                .await
+                .unwrap();
+        }
+        rpc_calls.load(Ordering::Relaxed)
+    }
+
+    #[tokio::test]
+    async fn fresh_blockhash_is_fetched_once_per_slot() {
+        let cache = BlockhashCache::default();
+        assert_eq!(build_many(&cache, Duration::from_secs(60), 500).await, 1);
+    }
+
+    #[tokio::test]
+    async fn stale_blockhash_is_fetched_directly() {
+        let cache = BlockhashCache::default();
+        assert_eq!(build_many(&cache, Duration::ZERO, 10).await, 10);
+    }
+
+    #[tokio::test]
+    async fn last_valid_block_height_is_kept_for_recent_blockhashes() {
+        let cache = BlockhashCache::default();
+        let first = cache.store((Hash::new_unique(), 150)).await;
+        for height in 151..151 + RECENT_BLOCKHASHES_LIMIT as u64 - 1 {
+            cache.store((Hash::new_unique(), height)).await;
+        }
+        assert_eq!(cache.last_valid_block_height(&first).await, Some(150));
+
+        // The oldest blockhash is forgotten once the limit is reached.
+        cache.store((Hash::new_unique(), 1_000)).await;
+        assert_eq!(cache.last_valid_block_height(&first).await, None);
+    }
+}
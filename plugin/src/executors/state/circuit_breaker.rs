@@ -0,0 +1,222 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crate::config::CircuitBreakerConfig;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreakerState {
+    /// Requests go through, their results are recorded in the window.
+    Closed,
+    /// Requests are rejected until the cool-down ends.
+    Open { until: Instant },
+    /// A few probes are let through, the breaker closes once they all succeed and opens again on a failure.
+    HalfOpen {
+        since: Instant,
+        admitted: u32,
+        succeeded: u32,
+    },
+}
+
+impl BreakerState {
+    /// The value of the state in the metrics.
+    pub fn gauge(&self) -> i64 {
+        match self {
+            Self::Closed => 0,
+            Self::HalfOpen { .. } => 1,
+            Self::Open { .. } => 2,
+        }
+    }
+}
+
+/// Tracks the results of a rolling window of requests and opens when too many of them failed.
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    outcomes: VecDeque<bool>,
+    state: BreakerState,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: &CircuitBreakerConfig) -> Self {
+        Self {
+            config: config.clone(),
+            outcomes: VecDeque::new(),
+            state: BreakerState::Closed,
+        }
+    }
+
+    pub fn state(&self) -> BreakerState {
+        self.state
+    }
+
+    fn cooldown(&self) -> Duration {
+        Duration::from_millis(self.config.cooldown_ms)
+    }
+
+    /// Returns how many of the `requested` requests may go through now.
+    /// A breaker whose cool-down ended turns half-open and admits up to `probes` requests,
+    /// and admits them again if their results did not come back within another cool-down.
+    pub fn admit(&mut self, requested: usize, now: Instant) -> usize {
+        if !self.config.enabled {
+            return requested;
+        }
+        if let BreakerState::Open { until } = self.state {
+            if now < until {
+                return 0;
+            }
+            self.state = BreakerState::HalfOpen {
+                since: now,
+                admitted: 0,
+                succeeded: 0,
+            };
+        }
+        let cooldown = self.cooldown();
+        match &mut self.state {
+            BreakerState::Closed => requested,
+            BreakerState::Open { .. } => 0,
+            BreakerState::HalfOpen {
+                since,
+                admitted,
+                succeeded,
+            } => {
+                if *admitted >= self.config.probes && now >= *since + cooldown {
+                    *since = now;
+                    *admitted = *succeeded;
+                }
+                let admissible = self.config.probes.saturating_sub(*admitted) as usize;
+                let admitted_now = requested.min(admissible);
+                *admitted += admitted_now as u32;
+                admitted_now
+            }
+        }
+    }
+
+    /// Records the result of a request. Returns the new state if the breaker changed state.
+    pub fn record(&mut self, ok: bool, now: Instant) -> Option<BreakerState> {
+        if !self.config.enabled {
+            return None;
+        }
+        let cooldown = self.cooldown();
+        let next_state = match &mut self.state {
+            // A late result of a request admitted before the breaker opened.
+            BreakerState::Open { .. } => None,
+            BreakerState::HalfOpen { succeeded, .. } => {
+                if !ok {
+                    Some(BreakerState::Open {
+                        until: now + cooldown,
+                    })
+                } else {
+                    *succeeded += 1;
+                    (*succeeded >= self.config.probes).then_some(BreakerState::Closed)
+                }
+            }
+            BreakerState::Closed => {
+                if self.outcomes.len() >= self.config.window.max(1) {
+                    self.outcomes.pop_front();
+                }
+                self.outcomes.push_back(ok);
+                (self.outcomes.len() >= self.config.min_samples
+                    && self.failure_ratio() > self.config.failure_ratio)
+                    .then_some(BreakerState::Open {
+                        until: now + cooldown,
+                    })
+            }
+        };
+        if let Some(state) = next_state {
+            self.state = state;
+            // The breaker starts over from a clean window once it closes.
+            self.outcomes.clear();
+        }
+        next_state
+    }
+
+    fn failure_ratio(&self) -> f64 {
+        if self.outcomes.is_empty() {
+            return 0.0;
+        }
+        self.outcomes.iter().filter(|ok| !**ok).count() as f64 / self.outcomes.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(&CircuitBreakerConfig {
+            enabled: true,
+            window: 10,
+            min_samples: 4,
+            failure_ratio: 0.5,
+            cooldown_ms: 1_000,
+            probes: 2,
+        })
+    }
+
+    #[test]
+    fn breaker_opens_then_half_opens_then_closes() {
+        let mut breaker = breaker();
+        let now = Instant::now();
+        assert_eq!(breaker.admit(5, now), 5);
+
+        // Not enough samples yet.
+        assert_eq!(breaker.record(false, now), None);
+        assert_eq!(breaker.record(false, now), None);
+        assert_eq!(breaker.record(true, now), None);
+        let until = now + Duration::from_millis(1_000);
+        assert_eq!(
+            breaker.record(false, now),
+            Some(BreakerState::Open { until })
+        );
+        assert_eq!(breaker.admit(5, now + Duration::from_millis(999)), 0);
+
+        // The cool-down ended, only the probes go through.
+        assert_eq!(breaker.admit(5, until), 2);
+        assert_eq!(breaker.admit(5, until), 0);
+        assert!(matches!(breaker.state(), BreakerState::HalfOpen { .. }));
+        assert_eq!(breaker.record(true, until), None);
+        assert_eq!(breaker.record(true, until), Some(BreakerState::Closed));
+        assert_eq!(breaker.admit(5, until), 5);
+    }
+
+    #[test]
+    fn failed_probe_opens_the_breaker_again() {
+        let mut breaker = breaker();
+        let now = Instant::now();
+        for _ in 0..4 {
+            breaker.record(false, now);
+        }
+        let until = now + Duration::from_millis(1_000);
+        assert_eq!(breaker.admit(1, until), 1);
+        let reopened_until = until + Duration::from_millis(1_000);
+        assert_eq!(
+            breaker.record(false, until),
+            Some(BreakerState::Open {
+                until: reopened_until
+            })
+        );
+        assert_eq!(breaker.admit(1, until), 0);
+
+        // Probes whose results never came back are admitted again after another cool-down.
+        assert_eq!(breaker.admit(2, reopened_until), 2);
+        assert_eq!(breaker.admit(2, reopened_until), 0);
+        assert_eq!(
+            breaker.admit(2, reopened_until + Duration::from_millis(1_000)),
+            2
+        );
+    }
+
+    #[test]
+    fn disabled_breaker_never_opens() {
+        let mut breaker = CircuitBreaker::new(&CircuitBreakerConfig {
+            enabled: false,
+            ..CircuitBreakerConfig::default()
+        });
+        let now = Instant::now();
+        for _ in 0..100 {
+            assert_eq!(breaker.record(false, now), None);
+        }
+        assert_eq!(breaker.admit(3, now), 3);
+    }
+}
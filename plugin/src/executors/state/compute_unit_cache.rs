@@ -0,0 +1,29 @@
+use std::collections::HashMap;
+
+use solana_sdk::{hash::Hash, pubkey::Pubkey};
+use tokio::sync::RwLock;
+
+/// The compute units consumed by the last simulation of each thread, with the hash of the simulated
+/// instruction so a changed instruction is simulated again.
+#[derive(Default)]
+pub struct ComputeUnitCache(RwLock<HashMap<Pubkey, (Hash, u64)>>);
+
+impl ComputeUnitCache {
+    pub async fn get(&self, thread_pubkey: &Pubkey, instruction_hash: &Hash) -> Option<u64> {
+        let r_state = self.0.read().await;
+        r_state
+            .get(thread_pubkey)
+            .filter(|(hash, _units)| hash.eq(instruction_hash))
+            .map(|(_hash, units)| *units)
+    }
+
+    pub async fn insert(&self, thread_pubkey: Pubkey, instruction_hash: Hash, units_consumed: u64) {
+        let mut w_state = self.0.write().await;
+        w_state.insert(thread_pubkey, (instruction_hash, units_consumed));
+    }
+
+    pub async fn remove(&self, thread_pubkey: &Pubkey) {
+        let mut w_state = self.0.write().await;
+        w_state.remove(thread_pubkey);
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+
+use log::info;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient, rpc_request::MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS,
+};
+use solana_sdk::{
+    clock::MAX_PROCESSING_AGE, commitment_config::CommitmentConfig, pubkey::Pubkey,
+    signature::Signature, transaction::TransactionError,
+};
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+use tokio::sync::RwLock;
+
+/// A sent transaction whose status is polled.
+#[derive(Clone, Debug)]
+pub struct TrackedSignature {
+    pub signature: Signature,
+    pub thread_pubkey: Pubkey,
+    pub due_slot: u64,
+    pub sent_slot: u64,
+    pub last_valid_block_height: Option<u64>,
+    /// The local worker which signed the transaction.
+    pub worker_id: u64,
+    /// Whether the transaction was built without simulation.
+    pub static_build: bool,
+    /// Whether the transaction already reached the confirmation commitment.
+    pub confirmed: bool,
+}
+
+/// What became of a tracked transaction.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Confirmation {
+    /// The transaction reached the confirmation commitment, it is tracked until finalized.
+    Confirmed,
+    Finalized,
+    /// The blockhash of the transaction expired before it landed.
+    Expired,
+    /// The transaction landed with an error.
+    Errored(TransactionError),
+}
+
+/// The sent transactions awaiting confirmation, by signature.
+/// A signature is tracked until it is finalized, errored or expired.
+#[derive(Default)]
+pub struct ConfirmationTracker(RwLock<HashMap<Signature, TrackedSignature>>);
+
+impl ConfirmationTracker {
+    pub async fn track(&self, tracked_signature: TrackedSignature) {
+        let mut w_state = self.0.write().await;
+        w_state.insert(tracked_signature.signature, tracked_signature);
+    }
+
+    /// Stops tracking the transactions of a thread.
+    pub async fn untrack_thread(&self, thread_pubkey: &Pubkey) {
+        let mut w_state = self.0.write().await;
+        w_state.retain(|_, tracked_signature| tracked_signature.thread_pubkey != *thread_pubkey);
+    }
+
+    pub async fn len(&self) -> usize {
+        self.0.read().await.len()
+    }
+
+    /// Fetches the statuses of the tracked signatures by batches of `MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS`,
+    /// and returns what became of them since the last poll. The block height is only fetched
+    /// if a transaction has no status, to tell whether it expired.
+    pub async fn poll(
+        &self,
+        client: &RpcClient,
+        slot: u64,
+        commitment: CommitmentConfig,
+    ) -> Vec<(TrackedSignature, Confirmation)> {
+        let signatures: Vec<Signature> = self.0.read().await.keys().copied().collect();
+        let mut statuses = HashMap::new();
+        for chunk in signatures.chunks(MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS) {
+            match client.get_signature_statuses(chunk).await {
+                Err(err) => info!("Failed to get signature statuses: {:?}", err),
+                Ok(response) => statuses.extend(chunk.iter().copied().zip(response.value)),
+            }
+        }
+        let block_height = if statuses.values().any(Option::is_none) {
+            client.get_block_height().await.ok()
+        } else {
+            None
+        };
+        self.update(&statuses, slot, block_height, commitment).await
+    }
+
+    /// Classifies the polled statuses, and stops tracking the signatures which settled.
+    pub async fn update(
+        &self,
+        statuses: &HashMap<Signature, Option<TransactionStatus>>,
+        slot: u64,
+        block_height: Option<u64>,
+        commitment: CommitmentConfig,
+    ) -> Vec<(TrackedSignature, Confirmation)> {
+        let mut w_state = self.0.write().await;
+        let mut confirmations = vec![];
+        for (signature, status) in statuses {
+            let Some(tracked_signature) = w_state.get_mut(signature) else {
+                continue;
+            };
+            let mut settled = false;
+            for confirmation in classify(
+                tracked_signature,
+                status.as_ref(),
+                slot,
+                block_height,
+                commitment,
+            ) {
+                match confirmation {
+                    Confirmation::Confirmed => tracked_signature.confirmed = true,
+                    _ => settled = true,
+                }
+                confirmations.push((tracked_signature.clone(), confirmation));
+            }
+            if settled {
+                w_state.remove(signature);
+            }
+        }
+        confirmations
+    }
+}
+
+/// A transaction finalized without having been seen confirmed is reported as both.
+fn classify(
+    tracked_signature: &TrackedSignature,
+    status: Option<&TransactionStatus>,
+    slot: u64,
+    block_height: Option<u64>,
+    commitment: CommitmentConfig,
+) -> Vec<Confirmation> {
+    let Some(status) = status else {
+        return match is_expired(tracked_signature, slot, block_height) {
+            true => vec![Confirmation::Expired],
+            false => vec![],
+        };
+    };
+    if !status.satisfies_commitment(commitment) {
+        return vec![];
+    }
+    if let Some(err) = &status.err {
+        return vec![Confirmation::Errored(err.clone())];
+    }
+    let mut confirmations = vec![];
+    if !tracked_signature.confirmed {
+        confirmations.push(Confirmation::Confirmed);
+    }
+    if status.confirmation_status() == TransactionConfirmationStatus::Finalized {
+        confirmations.push(Confirmation::Finalized);
+    }
+    confirmations
+}
+
+/// A transaction without a known last valid block height is given the maximum age of a blockhash in slots.
+fn is_expired(tracked_signature: &TrackedSignature, slot: u64, block_height: Option<u64>) -> bool {
+    match (tracked_signature.last_valid_block_height, block_height) {
+        (Some(last_valid_block_height), Some(block_height)) => {
+            block_height > last_valid_block_height
+        }
+        (Some(_), None) => false,
+        (None, _) => slot > tracked_signature.sent_slot + MAX_PROCESSING_AGE as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::instruction::InstructionError;
+
+    use super::*;
+
+    fn tracked_signature(last_valid_block_height: Option<u64>) -> TrackedSignature {
+        TrackedSignature {
+            signature: Signature::new_unique(),
+            thread_pubkey: Pubkey::new_unique(),
+            due_slot: 10,
+            sent_slot: 10,
+            last_valid_block_height,
+            worker_id: 0,
+            static_build: false,
+            confirmed: false,
+        }
+    }
+
+    fn status(
+        confirmation_status: TransactionConfirmationStatus,
+        err: Option<TransactionError>,
+    ) -> Option<TransactionStatus> {
+        Some(TransactionStatus {
+            slot: 11,
+            confirmations: None,
+            status: err.clone().map_or(Ok(()), Err),
+            err,
+            confirmation_status: Some(confirmation_status),
+        })
+    }
+
+    async fn update(
+        tracker: &ConfirmationTracker,
+        tracked_signature: &TrackedSignature,
+        status: Option<TransactionStatus>,
+        block_height: Option<u64>,
+    ) -> Vec<Confirmation> {
+        tracker
+            .update(
+                &HashMap::from([(tracked_signature.signature, status)]),
+                20,
+                block_height,
+                CommitmentConfig::confirmed(),
+            )
+            .await
+            .into_iter()
+            .map(|(_, confirmation)| confirmation)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn signatures_are_tracked_until_finalized() {
+        let tracker = ConfirmationTracker::default();
+        let tracked_signature = tracked_signature(Some(100));
+        tracker.track(tracked_signature.clone()).await;
+
+        let processed = status(TransactionConfirmationStatus::Processed, None);
+        assert!(update(&tracker, &tracked_signature, processed, None)
+            .await
+            .is_empty());
+        let confirmed = status(TransactionConfirmationStatus::Confirmed, None);
+        assert_eq!(
+            update(&tracker, &tracked_signature, confirmed.clone(), None).await,
+            vec![Confirmation::Confirmed]
+        );
+        // The confirmation is only reported once.
+        assert!(update(&tracker, &tracked_signature, confirmed, None)
+            .await
+            .is_empty());
+        let finalized = status(TransactionConfirmationStatus::Finalized, None);
+        assert_eq!(
+            update(&tracker, &tracked_signature, finalized, None).await,
+            vec![Confirmation::Finalized]
+        );
+        assert_eq!(tracker.len().await, 0);
+    }
+
+    #[tokio::test]
+    async fn errored_and_expired_signatures_stop_being_tracked() {
+        let tracker = ConfirmationTracker::default();
+        let (errored, expiring, unknown) = (
+            tracked_signature(Some(100)),
+            tracked_signature(Some(100)),
+            tracked_signature(None),
+        );
+        for tracked_signature in [&errored, &expiring, &unknown] {
+            tracker.track(tracked_signature.clone()).await;
+        }
+
+        let err = TransactionError::InstructionError(0, InstructionError::Custom(1));
+        assert_eq!(
+            update(
+                &tracker,
+                &errored,
+                status(TransactionConfirmationStatus::Confirmed, Some(err.clone())),
+                None
+            )
+            .await,
+            vec![Confirmation::Errored(err)]
+        );
+
+        // The transaction may still land until its blockhash expires.
+        assert!(update(&tracker, &expiring, None, Some(100))
+            .await
+            .is_empty());
+        assert!(update(&tracker, &expiring, None, None).await.is_empty());
+        assert_eq!(
+            update(&tracker, &expiring, None, Some(101)).await,
+            vec![Confirmation::Expired]
+        );
+
+        // Without a last valid block height, the transaction expires with the maximum age of a blockhash.
+        assert!(update(&tracker, &unknown, None, Some(101)).await.is_empty());
+        assert_eq!(tracker.len().await, 1);
+        assert!(is_expired(&unknown, 11 + MAX_PROCESSING_AGE as u64, None));
+    }
+}
@@ -7,7 +7,7 @@ use log::info;
 use solana_sdk::{pubkey::Pubkey, signature::Signature};
 use tokio::sync::RwLock;
 
-use crate::{executors::tx::ExecutableThreadMetadata, pool_position::PoolPosition};
+use crate::executors::tx::ExecutableThreadMetadata;
 
 /// Number of times to retry a thread simulation.
 static MAX_THREAD_SIMULATION_FAILURES: u32 = 10;
@@ -29,12 +29,13 @@ impl ExecutableThreads {
             .and_modify(|metadata| metadata.simulation_failures += 1);
     }
 
-    pub async fn get(&self, pool_position: &PoolPosition, slot: u64) -> Vec<(Pubkey, u64)> {
+    /// Returns the executable threads. Workers outside of the pool are not paid for executing threads,
+    /// so they only pick up the threads the pool failed to execute within the timeout window.
+    pub async fn get(&self, is_pool_member: bool, slot: u64) -> Vec<(Pubkey, u64)> {
         // Get the set of thread pubkeys that are executable.
-        // Note we parallelize using rayon because this work is CPU heavy.
         let r_state = self.0.read().await;
 
-        if pool_position.current_position.is_none() && !pool_position.workers.is_empty() {
+        if !is_pool_member {
             // This worker is not in the pool. Get pubkeys of threads that are beyond the timeout window.
             r_state
                 .iter()
@@ -99,6 +100,24 @@ impl ExecutableThreads {
         );
     }
 
+    /// Returns the executable threads and the slot they became due at.
+    pub async fn due_slots(&self) -> Vec<(Pubkey, u64)> {
+        let r_state = self.0.read().await;
+        r_state
+            .iter()
+            .map(|(pubkey, metadata)| (*pubkey, metadata.due_slot))
+            .collect()
+    }
+
+    /// Returns the executable threads with their due slot and number of simulation failures.
+    pub async fn entries(&self) -> Vec<(Pubkey, u64, u32)> {
+        let r_state = self.0.read().await;
+        r_state
+            .iter()
+            .map(|(pubkey, metadata)| (*pubkey, metadata.due_slot, metadata.simulation_failures))
+            .collect()
+    }
+
     pub async fn add(&self, retriable_threads: HashSet<(Pubkey, u64)>) {
         let mut w_state = self.0.write().await;
         for (pubkey, due_slot) in retriable_threads {
@@ -1,7 +1,31 @@
+mod blockhash_cache;
+mod circuit_breaker;
+mod compute_unit_cache;
+mod confirmation_tracker;
 mod executable_threads;
+mod pool_cache;
+mod quarantine;
+mod retry_queue;
 mod rotation_history;
+mod signatory_balance;
+mod simulation_backoff;
+mod simulation_logs;
+mod thread_fees;
 mod transaction_history;
+mod unreachable_threads;
 
+pub use blockhash_cache::*;
+pub use circuit_breaker::*;
+pub use compute_unit_cache::*;
+pub use confirmation_tracker::*;
 pub use executable_threads::*;
+pub use pool_cache::*;
+pub use quarantine::*;
+pub use retry_queue::*;
 pub use rotation_history::*;
+pub use signatory_balance::*;
+pub use simulation_backoff::*;
+pub use simulation_logs::*;
+pub use thread_fees::*;
 pub use transaction_history::*;
+pub use unreachable_threads::*;
@@ -0,0 +1,83 @@
+use std::future::Future;
+
+use sablier_network_program::state::Pool;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::RwLock;
+
+use crate::{error::PluginError, executors::AccountGet};
+
+/// Number of slots after which the cached pool is fetched again, in case an update of the pool account was missed.
+static POOL_CACHE_MAX_AGE: u64 = 150;
+
+/// The workers of the pool and the slot they were fetched at.
+/// The cache is invalidated when the geyser plugin receives an update of the pool account, i.e. on pool rotations.
+#[derive(Default)]
+pub struct PoolCache(RwLock<Option<(Vec<Pubkey>, u64)>>);
+
+impl PoolCache {
+    /// Returns the workers of the pool, fetching them if the cache was invalidated.
+    pub async fn get(&self, client: &RpcClient, slot: u64) -> Result<Vec<Pubkey>, PluginError> {
+        self.get_or_fetch(slot, || async {
+            let pool = client.get::<Pool>(&Pool::pubkey(0)).await?;
+            Ok(pool.workers.into())
+        })
+        .await
+    }
+
+    pub async fn invalidate(&self) {
+        *self.0.write().await = None;
+    }
+
+    async fn get_or_fetch<F, Fut>(&self, slot: u64, fetch: F) -> Result<Vec<Pubkey>, PluginError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Vec<Pubkey>, PluginError>>,
+    {
+        if let Some((workers, fetched_slot)) = self.0.read().await.as_ref() {
+            if slot < fetched_slot.saturating_add(POOL_CACHE_MAX_AGE) {
+                return Ok(workers.clone());
+            }
+        }
+
+        let workers = fetch().await?;
+        *self.0.write().await = Some((workers.clone(), slot));
+        Ok(workers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    async fn get_many(cache: &PoolCache, slots: std::ops::Range<u64>) -> usize {
+        let rpc_calls = AtomicUsize::new(0);
+        for slot in slots {
+            cache
+                .get_or_fetch(slot, || async {
+                    rpc_calls.fetch_add(1, Ordering::Relaxed);
+                    Ok(vec![Pubkey::new_unique()])
+                })
+                .await
+                .unwrap();
+        }
+        rpc_calls.load(Ordering::Relaxed)
+    }
+
+    #[tokio::test]
+    async fn pool_is_fetched_once_until_invalidated() {
+        let cache = PoolCache::default();
+        assert_eq!(get_many(&cache, 0..100).await, 1);
+
+        cache.invalidate().await;
+        assert_eq!(get_many(&cache, 100..110).await, 1);
+    }
+
+    #[tokio::test]
+    async fn stale_pool_is_fetched_again() {
+        let cache = PoolCache::default();
+        assert_eq!(get_many(&cache, 0..POOL_CACHE_MAX_AGE * 2).await, 2);
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use solana_sdk::{instruction::InstructionError, pubkey::Pubkey, transaction::TransactionError};
+use tokio::sync::RwLock;
+
+/// Matches a simulation error which will not go away until the thread is updated.
+type ErrorMatcher = fn(&TransactionError) -> bool;
+
+/// The simulation errors which quarantine a thread, by name. Add new permanent error shapes here.
+static NON_RETRYABLE_ERRORS: &[(&str, ErrorMatcher)] = &[
+    ("ProgramAccountNotFound", |err| {
+        matches!(err, TransactionError::ProgramAccountNotFound)
+            || matches!(
+                err,
+                TransactionError::InstructionError(_, InstructionError::UnsupportedProgramId)
+            )
+    }),
+    ("InvalidProgramForExecution", |err| {
+        matches!(err, TransactionError::InvalidProgramForExecution)
+            || matches!(
+                err,
+                TransactionError::InstructionError(_, InstructionError::AccountNotExecutable)
+            )
+    }),
+    ("IncorrectProgramId", |err| {
+        matches!(
+            err,
+            TransactionError::InstructionError(_, InstructionError::IncorrectProgramId)
+        )
+    }),
+    ("InvalidAccountOwner", |err| {
+        matches!(
+            err,
+            TransactionError::InstructionError(
+                _,
+                InstructionError::InvalidAccountOwner | InstructionError::IllegalOwner
+            )
+        )
+    }),
+    ("InvalidInstructionData", |err| {
+        matches!(
+            err,
+            TransactionError::InstructionError(_, InstructionError::InvalidInstructionData)
+        )
+    }),
+    // The anchor errors of a closed account and of malformed instruction data.
+    ("AccountNotInitialized", |err| {
+        matches!(
+            err,
+            TransactionError::InstructionError(_, InstructionError::Custom(3012))
+        )
+    }),
+    ("AccountOwnedByWrongProgram", |err| {
+        matches!(
+            err,
+            TransactionError::InstructionError(_, InstructionError::Custom(3007))
+        )
+    }),
+    ("InstructionDidNotDeserialize", |err| {
+        matches!(
+            err,
+            TransactionError::InstructionError(
+                _,
+                InstructionError::Custom(101) | InstructionError::Custom(102)
+            )
+        )
+    }),
+];
+
+/// Returns the name of the simulation error if retrying the simulation is pointless.
+pub fn non_retryable_error(err: &TransactionError) -> Option<&'static str> {
+    NON_RETRYABLE_ERRORS
+        .iter()
+        .find(|(_, matches)| matches(err))
+        .map(|(name, _)| *name)
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct QuarantinedThread {
+    pub slot: u64,
+    pub reason: &'static str,
+    pub error: String,
+}
+
+/// The threads whose simulation failed with a non-retryable error.
+/// They are not built again until their account changes or they are released through the admin interface.
+#[derive(Default)]
+pub struct Quarantine(RwLock<HashMap<Pubkey, QuarantinedThread>>);
+
+impl Quarantine {
+    pub async fn add(&self, thread_pubkey: Pubkey, quarantined_thread: QuarantinedThread) {
+        let mut w_state = self.0.write().await;
+        w_state.insert(thread_pubkey, quarantined_thread);
+    }
+
+    pub async fn get(&self, thread_pubkey: &Pubkey) -> Option<QuarantinedThread> {
+        let r_state = self.0.read().await;
+        r_state.get(thread_pubkey).cloned()
+    }
+
+    pub async fn contains(&self, thread_pubkey: &Pubkey) -> bool {
+        let r_state = self.0.read().await;
+        r_state.contains_key(thread_pubkey)
+    }
+
+    /// Returns true if the thread was quarantined.
+    pub async fn remove(&self, thread_pubkey: &Pubkey) -> bool {
+        let mut w_state = self.0.write().await;
+        w_state.remove(thread_pubkey).is_some()
+    }
+
+    /// Releases every thread, returns how many were quarantined.
+    pub async fn clear(&self) -> usize {
+        let mut w_state = self.0.write().await;
+        let len = w_state.len();
+        w_state.clear();
+        len
+    }
+
+    pub async fn len(&self) -> usize {
+        self.0.read().await.len()
+    }
+
+    pub async fn entries(&self) -> HashMap<String, QuarantinedThread> {
+        let r_state = self.0.read().await;
+        r_state
+            .iter()
+            .map(|(thread_pubkey, quarantined_thread)| {
+                (thread_pubkey.to_string(), quarantined_thread.clone())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_permanent_errors_are_non_retryable() {
+        assert_eq!(
+            non_retryable_error(&TransactionError::ProgramAccountNotFound),
+            Some("ProgramAccountNotFound")
+        );
+        assert_eq!(
+            non_retryable_error(&TransactionError::InstructionError(
+                2,
+                InstructionError::IllegalOwner
+            )),
+            Some("InvalidAccountOwner")
+        );
+        assert_eq!(
+            non_retryable_error(&TransactionError::InstructionError(
+                2,
+                InstructionError::Custom(3012)
+            )),
+            Some("AccountNotInitialized")
+        );
+        for err in [
+            TransactionError::BlockhashNotFound,
+            TransactionError::InstructionError(2, InstructionError::ComputationalBudgetExceeded),
+            TransactionError::InstructionError(2, InstructionError::Custom(6000)),
+        ] {
+            assert_eq!(non_retryable_error(&err), None);
+        }
+    }
+}
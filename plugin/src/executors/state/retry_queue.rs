@@ -0,0 +1,163 @@
+use std::collections::{HashMap, HashSet};
+
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::RwLock;
+
+/// A thread deferred for lack of capacity, and the slot it was first deferred at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct RetryEntry {
+    origin_slot: u64,
+    one_shot: bool,
+}
+
+/// The threads deferred by a build queue overflow or the circuit breaker, as opposed to simulation failures.
+/// They are retried first on the next slots, one-shot triggers ahead of recurring ones, then in the order they were deferred,
+/// until they are built or another worker executed them.
+#[derive(Default)]
+pub struct RetryQueue(RwLock<HashMap<Pubkey, RetryEntry>>);
+
+impl RetryQueue {
+    /// Queues the deferred threads, a thread already queued keeps its origin slot.
+    /// Once the queue is full, a one-shot thread takes the place of the last deferred recurring one, if any.
+    /// Returns the number of threads left out, they wait their turn with the other executable threads.
+    pub async fn defer(
+        &self,
+        threads: impl IntoIterator<Item = (Pubkey, bool)>,
+        slot: u64,
+        capacity: usize,
+    ) -> usize {
+        let mut w_state = self.0.write().await;
+        let mut overflow = 0;
+        for (thread_pubkey, one_shot) in threads {
+            if w_state.contains_key(&thread_pubkey) {
+                continue;
+            }
+            if w_state.len() >= capacity {
+                let evicted = one_shot
+                    .then(|| {
+                        w_state
+                            .iter()
+                            .filter(|(_, entry)| !entry.one_shot)
+                            .max_by_key(|(_, entry)| entry.origin_slot)
+                            .map(|(thread_pubkey, _)| *thread_pubkey)
+                    })
+                    .flatten();
+                overflow += 1;
+                match evicted {
+                    Some(evicted) => w_state.remove(&evicted),
+                    None => continue,
+                };
+            }
+            w_state.insert(
+                thread_pubkey,
+                RetryEntry {
+                    origin_slot: slot,
+                    one_shot,
+                },
+            );
+        }
+        overflow
+    }
+
+    /// Moves the queued threads to the front, one-shot triggers first, then in the order they were deferred.
+    /// The other threads keep their order behind them.
+    pub async fn promote(&self, threads: &mut [(Pubkey, u64)]) {
+        let r_state = self.0.read().await;
+        threads.sort_by_key(
+            |(thread_pubkey, _due_slot)| match r_state.get(thread_pubkey) {
+                Some(entry) => (0, !entry.one_shot, entry.origin_slot),
+                None => (1, false, 0),
+            },
+        );
+    }
+
+    /// Drops the threads which are no longer executable, e.g. executed or purged.
+    pub async fn retain(&self, executable_threads: &HashSet<Pubkey>) {
+        let mut w_state = self.0.write().await;
+        w_state.retain(|thread_pubkey, _| executable_threads.contains(thread_pubkey));
+    }
+
+    /// Drops the thread if it was executed since it was deferred, e.g. by another worker.
+    /// Returns true if it was dropped.
+    pub async fn observe_exec(&self, thread_pubkey: &Pubkey, last_exec_at: u64) -> bool {
+        let mut w_state = self.0.write().await;
+        match w_state.get(thread_pubkey) {
+            Some(entry) if last_exec_at >= entry.origin_slot => {
+                w_state.remove(thread_pubkey);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub async fn remove(&self, thread_pubkey: &Pubkey) {
+        let mut w_state = self.0.write().await;
+        w_state.remove(thread_pubkey);
+    }
+
+    pub async fn len(&self) -> usize {
+        self.0.read().await.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn deferred_threads_are_retried_first_in_order() {
+        let retry_queue = RetryQueue::default();
+        let threads: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        retry_queue.defer([(threads[2], false)], 10, 8).await;
+        retry_queue
+            .defer([(threads[1], false), (threads[3], true)], 11, 8)
+            .await;
+        // Deferred again, the thread keeps its place.
+        retry_queue.defer([(threads[2], false)], 12, 8).await;
+
+        let mut queue: Vec<(Pubkey, u64)> = threads.iter().map(|thread| (*thread, 9)).collect();
+        retry_queue.promote(&mut queue).await;
+        assert_eq!(
+            queue,
+            vec![
+                (threads[3], 9),
+                (threads[2], 9),
+                (threads[1], 9),
+                (threads[0], 9)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn one_shot_threads_take_the_place_of_recurring_ones() {
+        let retry_queue = RetryQueue::default();
+        let (recurring, one_shot, late) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        assert_eq!(retry_queue.defer([(recurring, false)], 10, 1).await, 0);
+        assert_eq!(retry_queue.defer([(late, false)], 11, 1).await, 1);
+        assert_eq!(retry_queue.defer([(one_shot, true)], 11, 1).await, 1);
+        assert_eq!(retry_queue.defer([(late, true)], 12, 1).await, 1);
+
+        let mut queue = vec![(late, 9), (recurring, 9), (one_shot, 9)];
+        retry_queue.promote(&mut queue).await;
+        assert_eq!(queue, vec![(one_shot, 9), (late, 9), (recurring, 9)]);
+        assert_eq!(retry_queue.len().await, 1);
+    }
+
+    #[tokio::test]
+    async fn threads_executed_elsewhere_leave_the_queue() {
+        let retry_queue = RetryQueue::default();
+        let (executed, pending) = (Pubkey::new_unique(), Pubkey::new_unique());
+        retry_queue
+            .defer([(executed, true), (pending, true)], 10, 8)
+            .await;
+        assert!(!retry_queue.observe_exec(&executed, 9).await);
+        assert!(retry_queue.observe_exec(&executed, 10).await);
+
+        retry_queue.retain(&HashSet::new()).await;
+        assert_eq!(retry_queue.len().await, 0);
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use solana_client::nonblocking::rpc_client::RpcClient;
 use tokio::sync::RwLock;
@@ -8,20 +8,21 @@ use crate::executors::tx::TransactionMetadata;
 /// The number of slots to wait since the last rotation attempt.
 static ROTATION_CONFIRMATION_PERIOD: u64 = 16;
 
+/// The last rotation attempt of each local worker.
 #[derive(Default)]
-pub struct RotationHistory(RwLock<Option<TransactionMetadata>>);
+pub struct RotationHistory(RwLock<HashMap<u64, TransactionMetadata>>);
 
 impl RotationHistory {
-    pub async fn add(&self, tx: TransactionMetadata) {
+    pub async fn add(&self, worker_id: u64, tx: TransactionMetadata) {
         let mut w_state = self.0.write().await;
-        *w_state = Some(tx);
+        w_state.insert(worker_id, tx);
     }
 
-    pub async fn should_attempt(&self, client: Arc<RpcClient>, slot: u64) -> bool {
+    pub async fn should_attempt(&self, client: Arc<RpcClient>, worker_id: u64, slot: u64) -> bool {
         let r_state = self.0.read().await;
-        log::info!("Rotation history {:?}", r_state);
+        log::info!("Rotation history {:?}", r_state.get(&worker_id));
 
-        let Some(rotation_history) = r_state.as_ref() else {
+        let Some(rotation_history) = r_state.get(&worker_id) else {
             return true;
         };
 
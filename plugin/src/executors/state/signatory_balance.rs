@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::RwLock;
+
+use crate::config::BalanceConfig;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum BalanceStatus {
+    /// The balance is above the thresholds, or has not been fetched yet.
+    Healthy,
+    /// The balance is below the minimum balance, transactions debiting the signatory are not built.
+    Low(u64),
+    /// The balance is below the hard stop balance, all execution is halted.
+    Depleted(u64),
+}
+
+/// The last fetched balance of each signatory.
+#[derive(Default)]
+pub struct SignatoryBalance(RwLock<HashMap<Pubkey, u64>>);
+
+impl SignatoryBalance {
+    /// Stores the balance and returns the previous one.
+    pub async fn set(&self, signatory: Pubkey, lamports: u64) -> Option<u64> {
+        let mut w_state = self.0.write().await;
+        w_state.insert(signatory, lamports)
+    }
+
+    pub async fn status(&self, signatory: &Pubkey, config: &BalanceConfig) -> BalanceStatus {
+        let r_state = self.0.read().await;
+        match r_state.get(signatory).copied() {
+            Some(lamports) if lamports < config.hard_stop_lamports => {
+                BalanceStatus::Depleted(lamports)
+            }
+            Some(lamports) if lamports < config.min_balance_lamports => {
+                BalanceStatus::Low(lamports)
+            }
+            _ => BalanceStatus::Healthy,
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use log::info;
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::RwLock;
+
+use crate::config::SimulationBackoffConfig;
+
+#[derive(Debug)]
+pub struct BackoffMetadata {
+    pub consecutive_failures: u32,
+    pub last_attempt_slot: u64,
+    pub last_error: Option<String>,
+}
+
+/// Consecutive simulation failures of threads, kept across slots so that
+/// threads which always fail are skipped for exponentially longer periods.
+#[derive(Default)]
+pub struct SimulationBackoff(RwLock<HashMap<Pubkey, BackoffMetadata>>);
+
+impl SimulationBackoff {
+    /// Returns the number of consecutive failures of the thread.
+    pub async fn record_failure(
+        &self,
+        thread_pubkey: Pubkey,
+        slot: u64,
+        error: Option<String>,
+        config: &SimulationBackoffConfig,
+    ) -> u32 {
+        let mut w_state = self.0.write().await;
+        let metadata = w_state.entry(thread_pubkey).or_insert(BackoffMetadata {
+            consecutive_failures: 0,
+            last_attempt_slot: slot,
+            last_error: None,
+        });
+        metadata.consecutive_failures = metadata.consecutive_failures.saturating_add(1);
+        metadata.last_attempt_slot = slot;
+        metadata.last_error = error;
+
+        let backoff_slots = backoff_slots(metadata.consecutive_failures, config);
+        if backoff_slots > 0 {
+            info!(
+                "Backing off thread: {} consecutive_failures: {} until_slot: {}",
+                thread_pubkey,
+                metadata.consecutive_failures,
+                slot.saturating_add(backoff_slots)
+            );
+        }
+        metadata.consecutive_failures
+    }
+
+    pub async fn reset(&self, thread_pubkey: &Pubkey) {
+        let mut w_state = self.0.write().await;
+        w_state.remove(thread_pubkey);
+    }
+
+    pub async fn is_throttled(
+        &self,
+        thread_pubkey: &Pubkey,
+        slot: u64,
+        config: &SimulationBackoffConfig,
+    ) -> bool {
+        let r_state = self.0.read().await;
+        r_state
+            .get(thread_pubkey)
+            .map(|metadata| slot < resume_slot(metadata, config))
+            .unwrap_or(false)
+    }
+
+    /// Returns the consecutive failures of the thread, the slot it will be retried at and its last simulation error.
+    pub async fn get(
+        &self,
+        thread_pubkey: &Pubkey,
+        config: &SimulationBackoffConfig,
+    ) -> Option<(u32, u64, Option<String>)> {
+        let r_state = self.0.read().await;
+        r_state.get(thread_pubkey).map(|metadata| {
+            (
+                metadata.consecutive_failures,
+                resume_slot(metadata, config),
+                metadata.last_error.clone(),
+            )
+        })
+    }
+}
+
+fn resume_slot(metadata: &BackoffMetadata, config: &SimulationBackoffConfig) -> u64 {
+    metadata
+        .last_attempt_slot
+        .saturating_add(backoff_slots(metadata.consecutive_failures, config))
+}
+
+fn backoff_slots(consecutive_failures: u32, config: &SimulationBackoffConfig) -> u64 {
+    if consecutive_failures < config.failure_threshold {
+        return 0;
+    }
+    2u64.checked_pow(consecutive_failures)
+        .unwrap_or(u64::MAX)
+        .min(config.max_backoff_slots)
+}
@@ -0,0 +1,148 @@
+use std::collections::{HashMap, VecDeque};
+
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::RwLock;
+
+use crate::config::SimulationLogsConfig;
+
+/// The outcome of a simulation of a thread exec transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SimulationRecord {
+    pub slot: u64,
+    /// The number of instructions simulated, the compute budget ones included.
+    pub instruction_count: usize,
+    pub error: Option<String>,
+    pub units_consumed: Option<u64>,
+    pub logs: Vec<String>,
+}
+
+#[derive(Default)]
+struct ThreadSimulations {
+    records: VecDeque<SimulationRecord>,
+    /// The sequence number of the last record, to drop the least recently simulated threads first.
+    last_recorded: u64,
+}
+
+#[derive(Default)]
+struct SimulationLogsState {
+    threads: HashMap<Pubkey, ThreadSimulations>,
+    sequence: u64,
+}
+
+/// The most recent simulations of each thread, successful or not, to tell why a thread stopped executing.
+#[derive(Default)]
+pub struct SimulationLogs(RwLock<SimulationLogsState>);
+
+impl SimulationLogs {
+    /// Keeps the last `per_thread` simulations of the thread, for the last `max_threads` threads simulated.
+    pub async fn record(
+        &self,
+        thread_pubkey: Pubkey,
+        record: SimulationRecord,
+        config: &SimulationLogsConfig,
+    ) {
+        if config.per_thread == 0 || config.max_threads == 0 {
+            return;
+        }
+        let mut w_state = self.0.write().await;
+        w_state.sequence += 1;
+        let sequence = w_state.sequence;
+        let thread_simulations = w_state.threads.entry(thread_pubkey).or_default();
+        thread_simulations.last_recorded = sequence;
+        while thread_simulations.records.len() >= config.per_thread {
+            thread_simulations.records.pop_front();
+        }
+        thread_simulations.records.push_back(record);
+
+        while w_state.threads.len() > config.max_threads {
+            let Some(least_recent) = w_state
+                .threads
+                .iter()
+                .min_by_key(|(_, thread_simulations)| thread_simulations.last_recorded)
+                .map(|(thread_pubkey, _)| *thread_pubkey)
+            else {
+                break;
+            };
+            w_state.threads.remove(&least_recent);
+        }
+    }
+
+    /// Returns the recent simulations of the thread, from the oldest to the newest.
+    pub async fn get(&self, thread_pubkey: &Pubkey) -> Vec<SimulationRecord> {
+        let r_state = self.0.read().await;
+        r_state
+            .threads
+            .get(thread_pubkey)
+            .map(|thread_simulations| thread_simulations.records.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the recent simulations of every thread.
+    pub async fn entries(&self) -> HashMap<String, Vec<SimulationRecord>> {
+        let r_state = self.0.read().await;
+        r_state
+            .threads
+            .iter()
+            .map(|(thread_pubkey, thread_simulations)| {
+                (
+                    thread_pubkey.to_string(),
+                    thread_simulations.records.iter().cloned().collect(),
+                )
+            })
+            .collect()
+    }
+
+    pub async fn remove(&self, thread_pubkey: &Pubkey) {
+        let mut w_state = self.0.write().await;
+        w_state.threads.remove(thread_pubkey);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(slot: u64) -> SimulationRecord {
+        SimulationRecord {
+            slot,
+            instruction_count: 2,
+            error: None,
+            units_consumed: Some(1_000),
+            logs: vec![format!("Program log: slot {}", slot)],
+        }
+    }
+
+    #[tokio::test]
+    async fn simulations_are_bounded_per_thread_and_overall() {
+        let simulation_logs = SimulationLogs::default();
+        let config = SimulationLogsConfig {
+            per_thread: 2,
+            max_threads: 2,
+            dump_path: None,
+        };
+        let (first, second, third) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        for slot in 0..3 {
+            simulation_logs.record(first, record(slot), &config).await;
+        }
+        assert_eq!(
+            simulation_logs.get(&first).await,
+            vec![record(1), record(2)]
+        );
+
+        // The least recently simulated thread is dropped first.
+        simulation_logs.record(second, record(3), &config).await;
+        simulation_logs.record(first, record(4), &config).await;
+        simulation_logs.record(third, record(5), &config).await;
+        assert!(simulation_logs.get(&second).await.is_empty());
+        assert_eq!(
+            simulation_logs.get(&first).await,
+            vec![record(2), record(4)]
+        );
+        assert_eq!(simulation_logs.get(&third).await, vec![record(5)]);
+    }
+}
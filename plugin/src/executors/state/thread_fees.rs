@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::RwLock;
+
+/// The fee of the threads, as last observed, used to order the builds.
+#[derive(Default)]
+pub struct ThreadFees(RwLock<HashMap<Pubkey, u64>>);
+
+impl ThreadFees {
+    pub async fn set(&self, thread_pubkey: Pubkey, fee: u64) {
+        let mut w_state = self.0.write().await;
+        w_state.insert(thread_pubkey, fee);
+    }
+
+    pub async fn get(&self, thread_pubkey: &Pubkey) -> Option<u64> {
+        let r_state = self.0.read().await;
+        r_state.get(thread_pubkey).copied()
+    }
+
+    pub async fn remove(&self, thread_pubkey: &Pubkey) {
+        let mut w_state = self.0.write().await;
+        w_state.remove(thread_pubkey);
+    }
+
+    /// Orders the threads from the highest to the lowest fee.
+    /// Threads with equal fees are ordered by the slot they became due at, and threads whose fee is unknown are kept last.
+    pub async fn order(&self, threads: &mut [(Pubkey, u64)]) {
+        let r_state = self.0.read().await;
+        threads.sort_by_cached_key(|(thread_pubkey, due_slot)| {
+            (
+                std::cmp::Reverse(r_state.get(thread_pubkey).copied()),
+                *due_slot,
+            )
+        });
+    }
+
+    /// Orders the threads from the highest to the lowest fee and keeps the first `queue_size` ones.
+    /// Returns the number of threads left out.
+    pub async fn prioritize(&self, threads: &mut Vec<(Pubkey, u64)>, queue_size: usize) -> usize {
+        self.order(threads).await;
+        let overflow = threads.len().saturating_sub(queue_size);
+        threads.truncate(queue_size);
+        overflow
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn lowest_fee_threads_overflow_first() {
+        let thread_fees = ThreadFees::default();
+        let threads: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        thread_fees.set(threads[0], 1_000).await;
+        thread_fees.set(threads[1], 10).await;
+        thread_fees.set(threads[2], 5_000).await;
+
+        let mut queue: Vec<(Pubkey, u64)> = threads.iter().map(|thread| (*thread, 1)).collect();
+        assert_eq!(thread_fees.prioritize(&mut queue, 4).await, 0);
+        assert_eq!(
+            queue,
+            vec![
+                (threads[2], 1),
+                (threads[0], 1),
+                (threads[1], 1),
+                (threads[3], 1)
+            ]
+        );
+        assert_eq!(thread_fees.prioritize(&mut queue, 2).await, 2);
+        assert_eq!(queue, vec![(threads[2], 1), (threads[0], 1)]);
+    }
+
+    #[tokio::test]
+    async fn equal_fee_threads_are_served_in_due_order() {
+        let thread_fees = ThreadFees::default();
+        let threads: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        for thread in &threads {
+            thread_fees.set(*thread, 1_000).await;
+        }
+
+        let mut queue = vec![(threads[0], 12), (threads[1], 10), (threads[2], 11)];
+        thread_fees.prioritize(&mut queue, 3).await;
+        assert_eq!(
+            queue,
+            vec![(threads[1], 10), (threads[2], 11), (threads[0], 12)]
+        );
+    }
+}
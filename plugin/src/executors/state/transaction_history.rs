@@ -1,20 +1,11 @@
 use std::collections::{HashMap, HashSet};
 
 use log::info;
-use solana_sdk::{pubkey::Pubkey, signature::Signature, transaction::Transaction};
+use solana_sdk::{pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction};
 use tokio::sync::RwLock;
 
 use crate::executors::tx::TransactionMetadata;
 
-/// Number of slots to wait before checking for a confirmed transaction.
-static TRANSACTION_CONFIRMATION_PERIOD: u64 = 24;
-
-pub struct CheckableTransaction {
-    pub due_slot: u64,
-    pub thread_pubkey: Pubkey,
-    pub signature: Signature,
-}
-
 #[derive(Default)]
 pub struct TransactionHistory(RwLock<HashMap<Pubkey, TransactionMetadata>>);
 
@@ -23,7 +14,7 @@ impl TransactionHistory {
         &self,
         slot: u64,
         thread_pubkey: Pubkey,
-        tx: &Transaction,
+        tx: &VersionedTransaction,
     ) -> bool {
         let r_state = self.0.read().await;
         if let Some(metadata) = r_state.get(&thread_pubkey) {
@@ -34,15 +25,20 @@ impl TransactionHistory {
         false
     }
 
-    pub async fn get_checkable_tx(&self, slot: u64) -> Vec<CheckableTransaction> {
+    /// Returns the threads with a transaction awaiting confirmation, and their transaction.
+    pub async fn entries(&self) -> Vec<(Pubkey, TransactionMetadata)> {
         let r_state = self.0.read().await;
         r_state
             .iter()
-            .filter(|(_, metadata)| slot > metadata.sent_slot + TRANSACTION_CONFIRMATION_PERIOD)
-            .map(|(pubkey, metadata)| CheckableTransaction {
-                due_slot: metadata.due_slot,
-                thread_pubkey: *pubkey,
-                signature: metadata.signature,
+            .map(|(pubkey, metadata)| {
+                (
+                    *pubkey,
+                    TransactionMetadata {
+                        due_slot: metadata.due_slot,
+                        sent_slot: metadata.sent_slot,
+                        signature: metadata.signature,
+                    },
+                )
             })
             .collect()
     }
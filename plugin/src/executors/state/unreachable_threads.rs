@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::RwLock;
+
+/// The threads which could not be fetched, with the first slot they failed to be.
+/// Threads found deleted are recorded without a slot, they are purged right away.
+#[derive(Default)]
+pub struct UnreachableThreads(RwLock<HashMap<Pubkey, Option<u64>>>);
+
+impl UnreachableThreads {
+    pub async fn record(&self, thread_pubkey: Pubkey, slot: u64) {
+        let mut w_state = self.0.write().await;
+        w_state.entry(thread_pubkey).or_insert(Some(slot));
+    }
+
+    pub async fn record_deleted(&self, thread_pubkey: Pubkey) {
+        let mut w_state = self.0.write().await;
+        w_state.insert(thread_pubkey, None);
+    }
+
+    pub async fn remove(&self, thread_pubkey: &Pubkey) {
+        let mut w_state = self.0.write().await;
+        w_state.remove(thread_pubkey);
+    }
+
+    /// Removes and returns the deleted threads, and the ones which could not be fetched for more than `ttl` slots.
+    pub async fn expired(&self, slot: u64, ttl: u64) -> Vec<Pubkey> {
+        let mut w_state = self.0.write().await;
+        let expired: Vec<Pubkey> = w_state
+            .iter()
+            .filter(|(_, first_slot)| {
+                first_slot.map_or(true, |first_slot| slot > first_slot.saturating_add(ttl))
+            })
+            .map(|(thread_pubkey, _)| *thread_pubkey)
+            .collect();
+        for thread_pubkey in &expired {
+            w_state.remove(thread_pubkey);
+        }
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn threads_expire_after_the_ttl() {
+        let unreachable_threads = UnreachableThreads::default();
+        let thread_pubkey = Pubkey::new_unique();
+        unreachable_threads.record(thread_pubkey, 10).await;
+        unreachable_threads.record(thread_pubkey, 15).await;
+
+        // The ttl counts from the first failure.
+        assert!(unreachable_threads.expired(20, 10).await.is_empty());
+        assert_eq!(
+            unreachable_threads.expired(21, 10).await,
+            vec![thread_pubkey]
+        );
+        assert!(unreachable_threads.expired(30, 10).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn deleted_threads_expire_right_away() {
+        let unreachable_threads = UnreachableThreads::default();
+        let thread_pubkey = Pubkey::new_unique();
+        unreachable_threads.record(thread_pubkey, 10).await;
+        unreachable_threads.record_deleted(thread_pubkey).await;
+        assert_eq!(
+            unreachable_threads.expired(11, 10).await,
+            vec![thread_pubkey]
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use std::{collections::HashMap, sync::Arc};
+
+use log::info;
+use sablier_thread_program::state::VersionedThread;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    hash::Hash,
+    message::VersionedMessage,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    transaction::VersionedTransaction,
+};
+use tokio::sync::RwLock;
+
+use crate::{builders::sign, config::SubmissionMode, error::PluginError};
+
+use super::{state::TrackedSignature, tx::TxExecutor, AccountGet};
+
+/// A sent exec transaction, kept to be signed again if its blockhash expires before it lands.
+pub struct Submission {
+    pub message: VersionedMessage,
+    pub resubmits: u32,
+    /// The local worker which signed the transaction.
+    pub worker_id: u64,
+}
+
+/// The sent exec transactions awaiting confirmation, by thread.
+#[derive(Default)]
+pub struct Submissions(RwLock<HashMap<Pubkey, Submission>>);
+
+impl Submissions {
+    pub async fn add(&self, thread_pubkey: Pubkey, submission: Submission) {
+        let mut w_state = self.0.write().await;
+        w_state.insert(thread_pubkey, submission);
+    }
+
+    pub async fn remove(&self, thread_pubkey: &Pubkey) -> Option<Submission> {
+        let mut w_state = self.0.write().await;
+        w_state.remove(thread_pubkey)
+    }
+}
+
+/// What became of an expired transaction.
+pub enum Resubmission {
+    /// The transaction was signed again with a fresh blockhash and sent.
+    Sent(Signature),
+    /// The thread was executed by another worker meanwhile, there is nothing left to submit.
+    Settled,
+    /// The transaction is given up on, the thread is built again from scratch.
+    Abandoned,
+}
+
+/// Signs the message again with a fresh blockhash.
+pub fn resign(
+    message: &VersionedMessage,
+    blockhash: Hash,
+    signatory: &Keypair,
+    fee_payer: Option<&Keypair>,
+) -> Result<VersionedTransaction, PluginError> {
+    let mut message = message.clone();
+    message.set_recent_blockhash(blockhash);
+    sign(message, signatory, fee_payer)
+}
+
+impl TxExecutor {
+    /// Keeps the sent transaction of the thread, to resubmit it if it expires,
+    /// and tracks its signature until it settles.
+    pub(super) async fn track_submission(
+        &self,
+        thread_pubkey: Pubkey,
+        tx: &VersionedTransaction,
+        worker_id: u64,
+        due_slot: u64,
+        sent_slot: u64,
+    ) {
+        let resubmits = self
+            .submissions
+            .remove(&thread_pubkey)
+            .await
+            .map(|submission| submission.resubmits)
+            .unwrap_or_default();
+        self.submissions
+            .add(
+                thread_pubkey,
+                Submission {
+                    message: tx.message.clone(),
+                    resubmits,
+                    worker_id,
+                },
+            )
+            .await;
+        self.track_signature(thread_pubkey, tx, worker_id, due_slot, sent_slot)
+            .await;
+    }
+
+    async fn track_signature(
+        &self,
+        thread_pubkey: Pubkey,
+        tx: &VersionedTransaction,
+        worker_id: u64,
+        due_slot: u64,
+        sent_slot: u64,
+    ) {
+        self.confirmation_tracker
+            .track(TrackedSignature {
+                signature: tx.signatures[0],
+                thread_pubkey,
+                due_slot,
+                sent_slot,
+                last_valid_block_height: self
+                    .blockhash_cache
+                    .last_valid_block_height(tx.message.recent_blockhash())
+                    .await,
+                worker_id,
+                static_build: self
+                    .config()
+                    .executor
+                    .static_compute_units
+                    .contains_key(&thread_pubkey.to_string()),
+                confirmed: false,
+            })
+            .await;
+    }
+
+    /// Counts the landed transaction of the thread by whether it was resubmitted.
+    /// Returns the local worker which signed it.
+    pub(super) async fn record_landed(&self, thread_pubkey: &Pubkey) -> Option<u64> {
+        let submission = self.submissions.remove(thread_pubkey).await?;
+        if submission.resubmits == 0 {
+            self.metrics.transactions_landed_first_try.inc();
+        } else {
+            self.metrics.transactions_landed_after_retry.inc();
+        }
+        Some(submission.worker_id)
+    }
+
+    /// Signs the expired transaction of the thread with a fresh blockhash and sends it again,
+    /// up to `max_resubmits` times. The thread is fetched first, so a thread executed by another
+    /// worker meanwhile is not executed twice.
+    pub(super) async fn resubmit_expired_tx(
+        self: &Arc<Self>,
+        client: &RpcClient,
+        data: &TrackedSignature,
+        slot: u64,
+    ) -> Resubmission {
+        let Some(submission) = self.submissions.remove(&data.thread_pubkey).await else {
+            return Resubmission::Abandoned;
+        };
+        if submission.resubmits >= self.config().max_resubmits {
+            return Resubmission::Abandoned;
+        }
+        let Some(worker) = self.workers.get(submission.worker_id) else {
+            return Resubmission::Abandoned;
+        };
+
+        // Check the thread is still due.
+        match client.get::<VersionedThread>(&data.thread_pubkey).await {
+            Err(_err) => return Resubmission::Abandoned,
+            Ok(thread) => {
+                if let Some(exec_context) = thread.exec_context() {
+                    if exec_context.last_exec_at.gt(&data.due_slot) {
+                        return Resubmission::Settled;
+                    }
+                }
+            }
+        }
+
+        let tx = match self.blockhash_cache.refresh(client).await {
+            Err(err) => {
+                info!("Failed to refresh blockhash: {:?}", err);
+                return Resubmission::Abandoned;
+            }
+            Ok(blockhash) => match resign(
+                &submission.message,
+                blockhash,
+                &worker.keypair,
+                self.fee_payer.as_ref(),
+            ) {
+                Err(err) => {
+                    info!("Failed to sign tx again: {:?}", err);
+                    return Resubmission::Abandoned;
+                }
+                Ok(tx) => tx,
+            },
+        };
+        let unsent = match self.config().submission_mode {
+            SubmissionMode::Rpc => self.send_rpc_txs(&[tx.clone()]).await,
+            SubmissionMode::Tpu | SubmissionMode::Jito => self.send_tpu_txs(&[tx.clone()]).await,
+        };
+        if !unsent.is_empty() {
+            return Resubmission::Abandoned;
+        }
+
+        info!(
+            "Resubmitted thread: {} expired_signature: {} signature: {} resubmits: {}",
+            data.thread_pubkey,
+            data.signature,
+            tx.signatures[0],
+            submission.resubmits + 1
+        );
+        self.metrics.transactions_resubmitted.inc();
+        self.submissions
+            .add(
+                data.thread_pubkey,
+                Submission {
+                    message: tx.message.clone(),
+                    resubmits: submission.resubmits + 1,
+                    worker_id: submission.worker_id,
+                },
+            )
+            .await;
+        self.track_signature(
+            data.thread_pubkey,
+            &tx,
+            submission.worker_id,
+            data.due_slot,
+            slot,
+        )
+        .await;
+        Resubmission::Sent(tx.signatures[0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{message::Message, signer::Signer, system_instruction};
+
+    use super::*;
+
+    #[test]
+    fn resigned_transaction_uses_the_fresh_blockhash() {
+        let keypair = Keypair::new();
+        let ix = system_instruction::transfer(&keypair.pubkey(), &Pubkey::new_unique(), 1);
+        let message = VersionedMessage::Legacy(Message::new_with_blockhash(
+            &[ix],
+            Some(&keypair.pubkey()),
+            &Hash::new_unique(),
+        ));
+        let blockhash = Hash::new_unique();
+        let tx = resign(&message, blockhash, &keypair, None).unwrap();
+        assert_eq!(*tx.message.recent_blockhash(), blockhash);
+        assert!(tx
+            .verify_with_results()
+            .into_iter()
+            .all(|verified| verified));
+    }
+}
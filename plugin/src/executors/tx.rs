@@ -2,11 +2,12 @@ use std::{
     collections::{HashMap, HashSet},
     fmt::Debug,
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 use bincode::serialize;
-use log::info;
-use sablier_network_program::state::{Pool, Registry, Snapshot, SnapshotFrame, Worker};
+use log::{error, info, warn};
+use sablier_network_program::state::{Registry, Snapshot, SnapshotFrame};
 use sablier_thread_program::state::VersionedThread;
 use solana_client::{
     nonblocking::{rpc_client::RpcClient, tpu_client::TpuClient},
@@ -16,30 +17,91 @@ use solana_client::{
 use solana_geyser_plugin_interface::geyser_plugin_interface::Result as PluginResult;
 use solana_quic_client::{QuicConfig, QuicConnectionManager, QuicPool};
 use solana_sdk::{
+    borsh1::try_from_slice_unchecked,
     commitment_config::CommitmentConfig,
+    compute_budget::{self, ComputeBudgetInstruction},
     pubkey::Pubkey,
     signature::{Keypair, Signature},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError, VersionedTransaction},
+};
+use tokio::{
+    runtime::Runtime,
+    sync::{OnceCell, Semaphore},
+    task::JoinSet,
 };
-use tokio::runtime::Runtime;
 
 use crate::{
-    config::PluginConfig, error::PluginError, pool_position::PoolPosition,
-    utils::read_or_new_keypair,
+    config::{Pipeline, PluginConfig, SimulationBackoffConfig, SubmissionMode},
+    error::PluginError,
+    execution_log::{build_execution_sink, ExecutionEvent, ExecutionRecord, ExecutionSink},
+    health::Health,
+    metrics::Metrics,
+    observers::state::{AccountTriggers, SubmittedThreads, ThreadFilter, ThreadPipelines},
+    pool_position::PoolPosition,
+    refresh::{refresh_threads, RefreshedThread},
+    utils::commitment_config,
+    webhook::{Webhook, WebhookEvent},
 };
 
 use super::{
-    state::{ExecutableThreads, RotationHistory, TransactionHistory},
+    jito::JitoClient,
+    lookup_table::LookupTableManager,
+    rpc_pool::RpcPool,
+    state::{
+        non_retryable_error, BalanceStatus, BlockhashCache, BreakerState, CircuitBreaker,
+        ComputeUnitCache, Confirmation, ConfirmationTracker, ExecutableThreads, PoolCache,
+        Quarantine, QuarantinedThread, RetryQueue, RotationHistory, SignatoryBalance,
+        SimulationBackoff, SimulationLogs, ThreadFees, TrackedSignature, TransactionHistory,
+        UnreachableThreads,
+    },
+    submission::{Resubmission, Submissions},
+    worker::{assign_worker, fee_payer, LocalWorker, LocalWorkers},
     AccountGet,
 };
 
 /// TxExecutor
 pub struct TxExecutor {
-    pub config: PluginConfig,
+    /// The config, swapped as a whole when the config file is reloaded.
+    config: std::sync::RwLock<Arc<PluginConfig>>,
     pub executable_threads: ExecutableThreads,
     pub transaction_history: TransactionHistory,
+    pub submissions: Submissions,
+    pub confirmation_tracker: ConfirmationTracker,
+    pub submitted_threads: Arc<SubmittedThreads>,
+    /// The data ranges watched by account triggers, shared with the observer.
+    pub account_triggers: Arc<AccountTriggers>,
+    /// The pipeline of each thread, shared with the observer.
+    pub thread_pipelines: Arc<ThreadPipelines>,
+    /// The allowlist and denylist of threads and authorities, shared with the observer and swapped by a reload.
+    pub thread_filter: Arc<ThreadFilter>,
     pub rotation_history: RotationHistory,
-    pub keypair: Keypair,
+    pub simulation_backoff: SimulationBackoff,
+    pub simulation_logs: SimulationLogs,
+    pub quarantine: Quarantine,
+    pub unreachable_threads: UnreachableThreads,
+    pub thread_fees: ThreadFees,
+    pub retry_queue: RetryQueue,
+    /// Opens when too many of the sent transactions fail on-chain, the failures are not tied to an RPC endpoint.
+    pub circuit_breaker: std::sync::Mutex<CircuitBreaker>,
+    pub blockhash_cache: BlockhashCache,
+    pub compute_unit_cache: ComputeUnitCache,
+    pub pool_cache: PoolCache,
+    /// Whether the localnet mode is active, set once the cluster is known not to be mainnet-beta.
+    localnet: OnceCell<bool>,
+    pub signatory_balance: SignatoryBalance,
+    pub lookup_table: LookupTableManager,
+    /// The build permits of each pipeline, so the builds of one trigger type don't hold up the others.
+    pub build_permits: HashMap<Pipeline, Semaphore>,
+    pub simulation_permits: Semaphore,
+    rpc_pool: std::sync::RwLock<Arc<RpcPool>>,
+    pub workers: LocalWorkers,
+    /// Pays the fees of the exec transactions instead of the worker signatories, if set.
+    pub fee_payer: Option<Keypair>,
+    pub jito: Option<JitoClient>,
+    pub webhook: Option<Arc<Webhook>>,
+    pub execution_sink: Option<Box<dyn ExecutionSink>>,
+    pub metrics: Arc<Metrics>,
+    pub health: Arc<Health>,
 }
 
 #[derive(Debug)]
@@ -56,13 +118,57 @@ pub struct TransactionMetadata {
 }
 
 impl TxExecutor {
-    pub fn new(config: PluginConfig) -> Self {
+    pub fn new(
+        config: PluginConfig,
+        metrics: Arc<Metrics>,
+        health: Arc<Health>,
+        submitted_threads: Arc<SubmittedThreads>,
+        account_triggers: Arc<AccountTriggers>,
+        thread_pipelines: Arc<ThreadPipelines>,
+        thread_filter: Arc<ThreadFilter>,
+    ) -> Self {
         Self {
-            config: config.clone(),
+            config: std::sync::RwLock::new(Arc::new(config.clone())),
             executable_threads: ExecutableThreads::default(),
             transaction_history: TransactionHistory::default(),
+            submissions: Submissions::default(),
+            confirmation_tracker: ConfirmationTracker::default(),
+            submitted_threads,
+            account_triggers,
+            thread_pipelines,
+            thread_filter,
             rotation_history: RotationHistory::default(),
-            keypair: read_or_new_keypair(config.keypath),
+            simulation_backoff: SimulationBackoff::default(),
+            simulation_logs: SimulationLogs::default(),
+            quarantine: Quarantine::default(),
+            unreachable_threads: UnreachableThreads::default(),
+            thread_fees: ThreadFees::default(),
+            retry_queue: RetryQueue::default(),
+            circuit_breaker: std::sync::Mutex::new(CircuitBreaker::new(&config.circuit_breaker)),
+            blockhash_cache: BlockhashCache::new(commitment_config(config.confirmation_commitment)),
+            compute_unit_cache: ComputeUnitCache::default(),
+            pool_cache: PoolCache::default(),
+            localnet: OnceCell::new(),
+            signatory_balance: SignatoryBalance::default(),
+            lookup_table: LookupTableManager::default(),
+            build_permits: Pipeline::ALL
+                .into_iter()
+                .map(|pipeline| {
+                    (
+                        pipeline,
+                        Semaphore::new(config.executor.build_concurrency(pipeline)),
+                    )
+                })
+                .collect(),
+            simulation_permits: Semaphore::new(config.executor.simulation_concurrency),
+            rpc_pool: std::sync::RwLock::new(Arc::new(build_rpc_pool(&config, metrics.clone()))),
+            jito: build_jito_client(&config),
+            webhook: build_webhook(&config, metrics.clone()),
+            execution_sink: build_execution_sink(&config),
+            workers: LocalWorkers::new(&config),
+            fee_payer: fee_payer(&config),
+            metrics,
+            health,
         }
     }
 
@@ -73,91 +179,336 @@ impl TxExecutor {
         slot: u64,
         runtime: Arc<Runtime>,
     ) -> PluginResult<()> {
+        for thread_pubkey in &thread_pubkeys {
+            self.log_execution(
+                slot,
+                thread_pubkey,
+                self.workers.primary().id,
+                ExecutionEvent::Executable { due_slot: slot },
+            );
+        }
         self.executable_threads
             .rebase_threads(slot, &thread_pubkeys)
             .await;
 
+        // Halt everything if no signatory can pay for anything, the threads stay queued until one is funded.
+        let mut balance_statuses = HashMap::new();
+        for worker in self.workers.iter() {
+            let balance_status = self
+                .signatory_balance
+                .status(&worker.signatory(), &self.config().balance)
+                .await;
+            if let BalanceStatus::Depleted(lamports) = balance_status {
+                warn!(
+                    "Worker: {} signatory balance {} is below the hard stop of {} lamports, halting its execution",
+                    worker.id, lamports, self.config().balance.hard_stop_lamports
+                );
+            }
+            balance_statuses.insert(worker.id, balance_status);
+        }
+        if balance_statuses
+            .values()
+            .all(|balance_status| matches!(balance_status, BalanceStatus::Depleted(_)))
+        {
+            return Ok(());
+        }
+
         // Process retries.
         self.clone().process_retries(client.clone(), slot).await;
 
-        // Get self worker's position in the delegate pool.
-        let worker_pubkey = Worker::pubkey(self.config.worker_id);
-        if let Ok(pool_position) = (client.get::<Pool>(&Pool::pubkey(0)).await).map(|pool| {
-            let workers = &mut pool.workers.clone();
-            PoolPosition {
-                current_position: pool
-                    .workers
-                    .iter()
-                    .position(|k| k.eq(&worker_pubkey))
-                    .map(|i| i as u64),
-                workers: workers.make_contiguous().to_vec().clone(),
+        // Skip the builds while the validator lags behind the cluster, the threads stay queued until it catches up.
+        if self.health.is_lagging() {
+            info!(
+                "Skipping the builds of slot: {} reason: the validator lags behind the cluster",
+                slot
+            );
+            return Ok(());
+        }
+
+        if slot % RPC_HEALTH_LOG_INTERVAL == 0 {
+            self.rpc_pool().log_health();
+        }
+
+        // Get the local workers' positions in the delegate pool.
+        let is_localnet = self.is_localnet(&client).await;
+        let pool_workers = match self.pool_cache.get(&client, slot).await {
+            Ok(pool_workers) => pool_workers,
+            Err(_) if is_localnet => vec![],
+            Err(_) => return Ok(()),
+        };
+        let mut pool_positions = vec![];
+        for worker in self.workers.iter() {
+            let pool_position = PoolPosition::new(pool_workers.clone(), &worker.pubkey);
+            info!("worker: {} pool_position: {:?}", worker.id, pool_position);
+
+            // Skip the transactions debiting the signatory while its balance is low.
+            match balance_statuses.get(&worker.id) {
+                Some(BalanceStatus::Healthy) => {}
+                Some(BalanceStatus::Low(lamports)) => {
+                    warn!(
+                        "Worker: {} signatory balance {} is below the minimum of {} lamports, skipping transactions",
+                        worker.id, lamports, self.config().balance.min_balance_lamports
+                    );
+                    continue;
+                }
+                _ => continue,
             }
-        }) {
-            info!("pool_position: {:?}", pool_position);
 
             // Rotate into the worker pool.
-            if pool_position.current_position.is_none() {
+            if pool_position.current_position.is_none() && !self.config().dry_run && !is_localnet {
                 self.clone()
-                    .execute_pool_rotate_txs(client.clone(), slot, pool_position.clone())
+                    .execute_pool_rotate_txs(client.clone(), slot, worker, pool_position.clone())
                     .await
                     .ok();
             }
-
-            // Execute thread transactions.
-            self.clone()
-                .execute_thread_exec_txs(client.clone(), slot, pool_position, runtime.clone())
-                .await
-                .ok();
+            pool_positions.push((worker.id, pool_position));
+        }
+        if pool_positions.is_empty() {
+            return Ok(());
         }
 
+        // Execute thread transactions.
+        self.clone()
+            .execute_thread_exec_txs(client.clone(), slot, pool_positions, runtime.clone())
+            .await
+            .ok();
+
         Ok(())
     }
 
+    /// Executes the threads just created or reset with a now trigger in the observed slot.
+    /// Threads which cannot be built right away stay queued for the next slot.
+    pub async fn execute_immediate_txs(
+        self: Arc<Self>,
+        client: Arc<RpcClient>,
+        observed_threads: HashMap<Pubkey, Instant>,
+        slot: u64,
+        runtime: Arc<Runtime>,
+    ) {
+        let thread_pubkeys: HashSet<Pubkey> = observed_threads.keys().copied().collect();
+        for thread_pubkey in &thread_pubkeys {
+            self.log_execution(
+                slot,
+                thread_pubkey,
+                self.workers.primary().id,
+                ExecutionEvent::Executable { due_slot: slot },
+            );
+        }
+        self.executable_threads
+            .rebase_threads(slot, &thread_pubkeys)
+            .await;
+        if self.health.is_lagging() {
+            return;
+        }
+
+        // Workers outside of the pool only execute overdue threads, which is left to the next slots.
+        let is_permissionless = self.is_permissionless(&client).await;
+        let pool_workers = match self.pool_cache.get(&client, slot).await {
+            Ok(pool_workers) => pool_workers,
+            Err(_) if is_permissionless => vec![],
+            Err(_) => return,
+        };
+        let mut worker_ids = vec![];
+        for worker in self.workers.iter() {
+            let pool_position = PoolPosition::new(pool_workers.clone(), &worker.pubkey);
+            if (pool_position.is_member() || is_permissionless)
+                && matches!(
+                    self.signatory_balance
+                        .status(&worker.signatory(), &self.config().balance)
+                        .await,
+                    BalanceStatus::Healthy
+                )
+            {
+                worker_ids.push(worker.id);
+            }
+        }
+        if worker_ids.is_empty() {
+            return;
+        }
+
+        let executable_threads = thread_pubkeys
+            .into_iter()
+            .map(|thread_pubkey| (thread_pubkey, slot))
+            .collect();
+        let sent_threads = self
+            .clone()
+            .build_thread_exec_txs(client, slot, executable_threads, worker_ids, runtime)
+            .await;
+        for thread_pubkey in sent_threads {
+            if let Some(observed_at) = observed_threads.get(&thread_pubkey) {
+                self.metrics
+                    .now_execution_latency
+                    .observe(observed_at.elapsed().as_secs_f64());
+            }
+        }
+    }
+
     async fn process_retries(self: Arc<Self>, client: Arc<RpcClient>, slot: u64) {
-        // Get transaction signatures and corresponding threads to check.
-        let checkable_transactions = self.transaction_history.get_checkable_tx(slot).await;
+        // Poll the statuses of the sent transactions.
+        let confirmations = self
+            .confirmation_tracker
+            .poll(
+                &client,
+                slot,
+                commitment_config(self.config().confirmation_commitment),
+            )
+            .await;
+        self.metrics
+            .tracked_signatures
+            .set(self.confirmation_tracker.len().await as i64);
 
-        // Lookup transaction statuses and track which threads are successful / retriable.
+        // Track which threads are successful / retriable.
         let mut failed_threads: HashSet<Pubkey> = HashSet::new();
         let mut retriable_threads: HashSet<(Pubkey, u64)> = HashSet::new();
         let mut successful_threads: HashSet<Pubkey> = HashSet::new();
-        for data in checkable_transactions {
-            match client
-                .get_signature_status_with_commitment(
-                    &data.signature,
-                    CommitmentConfig::processed(),
-                )
-                .await
-            {
-                Err(_err) => {}
-                Ok(status) => match status {
-                    None => {
+        let mut resubmitted_threads: HashMap<Pubkey, (Signature, u64)> = HashMap::new();
+        for (data, confirmation) in confirmations {
+            match confirmation {
+                Confirmation::Expired => {
+                    // The thread was already settled when its transaction was confirmed.
+                    if data.confirmed {
                         info!(
-                            "Retrying thread: {:?} missing_signature: {:?}",
-                            data.thread_pubkey, data.signature
+                            "Confirmed signature: {} of thread: {} expired before it was finalized",
+                            data.signature, data.thread_pubkey
                         );
-                        retriable_threads.insert((data.thread_pubkey, data.due_slot));
+                        continue;
                     }
-                    Some(status) => match status {
-                        Err(err) => {
-                            info!(
-                                "Thread failed: {:?} failed_signature: {:?} err: {:?}",
-                                data.thread_pubkey, data.signature, err
-                            );
-                            failed_threads.insert(data.thread_pubkey);
+                    self.metrics.transactions_dropped.inc();
+                    self.record_static_outcome(&data, "expired");
+                    self.log_execution(
+                        slot,
+                        &data.thread_pubkey,
+                        data.worker_id,
+                        ExecutionEvent::expired(&data.signature),
+                    );
+                    self.notify(WebhookEvent::expired(
+                        &data.thread_pubkey,
+                        slot,
+                        &data.signature,
+                    ));
+                    match self.resubmit_expired_tx(&client, &data, slot).await {
+                        Resubmission::Sent(signature) => {
+                            resubmitted_threads
+                                .insert(data.thread_pubkey, (signature, data.due_slot));
                         }
-                        Ok(()) => {
+                        Resubmission::Settled => {
                             successful_threads.insert(data.thread_pubkey);
                         }
-                    },
-                },
+                        Resubmission::Abandoned => {
+                            info!(
+                                "Retrying thread: {:?} missing_signature: {:?}",
+                                data.thread_pubkey, data.signature
+                            );
+                            self.metrics.transactions_abandoned.inc();
+                            retriable_threads.insert((data.thread_pubkey, data.due_slot));
+                        }
+                    }
+                }
+                Confirmation::Errored(err) => {
+                    info!(
+                        "Thread failed: {:?} failed_signature: {:?} err: {:?}",
+                        data.thread_pubkey, data.signature, err
+                    );
+                    self.record_landed(&data.thread_pubkey).await;
+                    self.metrics.transactions_failed.inc();
+                    self.record_static_outcome(&data, "failed");
+                    self.record_onchain_result(false);
+                    self.metrics
+                        .worker_transactions_failed
+                        .with_label_values(&[&data.worker_id.to_string()])
+                        .inc();
+                    self.log_execution(
+                        slot,
+                        &data.thread_pubkey,
+                        data.worker_id,
+                        ExecutionEvent::failed(&data.signature, err.to_string()),
+                    );
+                    self.notify(WebhookEvent::failed(
+                        &data.thread_pubkey,
+                        slot,
+                        &data.signature,
+                        err.to_string(),
+                    ));
+                    // A thread failing on-chain is backed off like a thread failing its simulation.
+                    self.simulation_backoff
+                        .record_failure(
+                            data.thread_pubkey,
+                            slot,
+                            Some(err.to_string()),
+                            &self.backoff_config(&data.thread_pubkey),
+                        )
+                        .await;
+                    failed_threads.insert(data.thread_pubkey);
+                }
+                Confirmation::Confirmed => {
+                    self.record_landed(&data.thread_pubkey).await;
+                    self.metrics.transactions_confirmed.inc();
+                    self.record_static_outcome(&data, "confirmed");
+                    self.record_onchain_result(true);
+                    self.metrics
+                        .worker_transactions_confirmed
+                        .with_label_values(&[&data.worker_id.to_string()])
+                        .inc();
+                    self.log_execution(
+                        slot,
+                        &data.thread_pubkey,
+                        data.worker_id,
+                        ExecutionEvent::confirmed(&data.signature),
+                    );
+                    self.notify(WebhookEvent::confirmed(
+                        &data.thread_pubkey,
+                        slot,
+                        &data.signature,
+                    ));
+                    successful_threads.insert(data.thread_pubkey);
+                }
+                Confirmation::Finalized => {
+                    self.metrics.transactions_finalized.inc();
+                    self.log_execution(
+                        slot,
+                        &data.thread_pubkey,
+                        data.worker_id,
+                        ExecutionEvent::finalized(&data.signature),
+                    );
+                    self.notify(WebhookEvent::finalized(
+                        &data.thread_pubkey,
+                        slot,
+                        &data.signature,
+                    ));
+                }
             }
         }
 
+        for thread_pubkey in &successful_threads {
+            self.simulation_backoff.reset(thread_pubkey).await;
+        }
+
+        // A thread failing on-chain may have used more compute units than cached.
+        for thread_pubkey in &failed_threads {
+            self.compute_unit_cache.remove(thread_pubkey).await;
+        }
+
         // Requeue retriable threads and drop transactions from history.
         self.transaction_history
             .clean(&failed_threads, &retriable_threads, &successful_threads)
             .await;
+        for thread_pubkey in failed_threads
+            .iter()
+            .chain(successful_threads.iter())
+            .chain(
+                retriable_threads
+                    .iter()
+                    .map(|(thread_pubkey, _due_slot)| thread_pubkey),
+            )
+        {
+            self.submitted_threads.remove(thread_pubkey).await;
+        }
+        for thread_pubkey in resubmitted_threads.keys() {
+            self.submitted_threads.add(*thread_pubkey, slot).await;
+        }
+        self.transaction_history
+            .add(slot, resubmitted_threads)
+            .await;
         self.executable_threads.add(retriable_threads).await;
     }
 
@@ -165,11 +516,12 @@ impl TxExecutor {
         self: Arc<Self>,
         client: Arc<RpcClient>,
         slot: u64,
+        worker: &LocalWorker,
         pool_position: PoolPosition,
     ) -> Result<(), PluginError> {
         let should_attempt = self
             .rotation_history
-            .should_attempt(client.clone(), slot)
+            .should_attempt(client.clone(), worker.id, slot)
             .await;
 
         if !should_attempt {
@@ -177,28 +529,48 @@ impl TxExecutor {
         }
         let registry = client.get::<Registry>(&Registry::pubkey()).await?;
         let snapshot_pubkey = Snapshot::pubkey(registry.current_epoch);
-        let snapshot_frame_pubkey = SnapshotFrame::pubkey(snapshot_pubkey, self.config.worker_id);
+        let snapshot_frame_pubkey = SnapshotFrame::pubkey(snapshot_pubkey, worker.id);
         if let Ok(snapshot) = client.get::<Snapshot>(&snapshot_pubkey).await {
             if let Ok(snapshot_frame) = client.get::<SnapshotFrame>(&snapshot_frame_pubkey).await {
-                if let Some(tx) = crate::builders::build_pool_rotation_tx(
-                    client.clone(),
-                    &self.keypair,
+                let blockhash = self
+                    .blockhash_cache
+                    .get(&client, self.blockhash_max_age())
+                    .await?;
+                if let Some(mut tx) = crate::builders::build_pool_rotation_tx(
+                    blockhash,
+                    &worker.keypair,
                     pool_position,
                     registry,
                     snapshot,
                     snapshot_frame,
-                    self.config.worker_id,
+                    worker.id,
                 )
                 .await
                 {
-                    self.clone().simulate_tx(&tx).await?;
+                    // Re-sign with a fresh blockhash if the cached one has expired, then simulate again.
+                    match self.clone().simulate_tx(&tx).await {
+                        Err(PluginError::FailedToSimulateTx(
+                            TransactionError::BlockhashNotFound,
+                            _,
+                        )) => {
+                            let blockhash = self.blockhash_cache.refresh(&client).await?;
+                            tx.sign(&[&worker.keypair], blockhash);
+                            self.clone().simulate_tx(&tx).await?;
+                        }
+                        result => {
+                            result?;
+                        }
+                    }
                     self.clone().submit_tx(&tx).await?;
                     self.rotation_history
-                        .add(TransactionMetadata {
-                            due_slot: slot,
-                            sent_slot: slot,
-                            signature: tx.signatures[0],
-                        })
+                        .add(
+                            worker.id,
+                            TransactionMetadata {
+                                due_slot: slot,
+                                sent_slot: slot,
+                                signature: tx.signatures[0],
+                            },
+                        )
                         .await;
                 }
             }
@@ -210,87 +582,478 @@ impl TxExecutor {
         self: Arc<Self>,
         client: Arc<RpcClient>,
         observed_slot: u64,
-        pool_position: PoolPosition,
+        pool_positions: Vec<(u64, PoolPosition)>,
         runtime: Arc<Runtime>,
     ) -> PluginResult<()> {
+        // Threads are routed to the local workers in the pool.
+        // They are not built while no local worker is in the pool, unless they are overdue.
+        let is_permissionless = self.is_permissionless(&client).await;
+        let member_ids: Vec<u64> = pool_positions
+            .iter()
+            .filter(|(_, pool_position)| pool_position.is_member() || is_permissionless)
+            .map(|(worker_id, _)| *worker_id)
+            .collect();
+        let is_pool_member = !member_ids.is_empty();
+        let worker_ids = if is_pool_member {
+            member_ids
+        } else {
+            pool_positions
+                .iter()
+                .map(|(worker_id, _)| *worker_id)
+                .collect()
+        };
         let executable_threads = self
             .executable_threads
-            .get(&pool_position, observed_slot)
+            .get(is_pool_member, observed_slot)
             .await;
+        let executable_pubkeys: HashSet<Pubkey> = self
+            .executable_threads
+            .due_slots()
+            .await
+            .into_iter()
+            .map(|(thread_pubkey, _due_slot)| thread_pubkey)
+            .collect();
+        self.retry_queue.retain(&executable_pubkeys).await;
+        if !is_pool_member {
+            info!(
+                "Workers: {:?} are not in the pool, executing {} overdue threads",
+                worker_ids,
+                executable_threads.len()
+            );
+        }
+
+        // Each trigger type goes through its own pipeline, with its own backoff, queue and build permits.
+        let mut pipeline_threads: HashMap<Pipeline, Vec<(Pubkey, u64)>> = HashMap::new();
+        for (thread_pubkey, due_slot) in executable_threads {
+            pipeline_threads
+                .entry(self.thread_pipelines.get(&thread_pubkey))
+                .or_default()
+                .push((thread_pubkey, due_slot));
+        }
+        let config = self.config();
+        let mut pipeline_queues = vec![];
+        for pipeline in Pipeline::ALL {
+            let mut pipeline_queue = pipeline_threads.remove(&pipeline).unwrap_or_default();
+
+            // Skip the threads backed off after repeated simulation failures.
+            let mut throttled_threads = HashSet::new();
+            for (thread_pubkey, _due_slot) in &pipeline_queue {
+                if self
+                    .simulation_backoff
+                    .is_throttled(
+                        thread_pubkey,
+                        observed_slot,
+                        config.pipeline_backoff(pipeline),
+                    )
+                    .await
+                {
+                    throttled_threads.insert(*thread_pubkey);
+                }
+            }
+            pipeline_queue
+                .retain(|(thread_pubkey, _due_slot)| !throttled_threads.contains(thread_pubkey));
+            self.metrics
+                .pipeline_executable_threads
+                .with_label_values(&[pipeline.name()])
+                .set(pipeline_queue.len() as i64);
+
+            // Threads wait their turn in a bounded queue ordered by fee, behind the threads deferred by the previous slots.
+            // The lowest fee ones are deferred to the next slots when it overflows.
+            self.thread_fees.order(&mut pipeline_queue).await;
+            self.retry_queue.promote(&mut pipeline_queue).await;
+            let deferred_threads = pipeline_queue.split_off(
+                config
+                    .executor
+                    .build_queue_size(pipeline)
+                    .min(pipeline_queue.len()),
+            );
+            let overflow = deferred_threads.len();
+            if overflow > 0 {
+                info!(
+                    "Build queue overflow of pipeline: {}, deferring {} threads to the next slot",
+                    pipeline.name(),
+                    overflow
+                );
+                self.defer_threads(deferred_threads, observed_slot).await;
+                self.metrics.build_queue_overflow.inc_by(overflow as u64);
+                self.metrics
+                    .pipeline_build_queue_overflow
+                    .with_label_values(&[pipeline.name()])
+                    .inc_by(overflow as u64);
+            }
+            pipeline_queues.push(pipeline_queue);
+        }
+
+        // The pipeline queues are interleaved, so a partial admission below does not starve any of them.
+        let mut executable_threads = interleave(pipeline_queues);
+        self.metrics
+            .executable_threads
+            .set(executable_threads.len() as i64);
         if executable_threads.is_empty() {
             return Ok(());
         }
 
-        // Build transactions in parallel.
-        // Note we parallelize using tokio because this work is IO heavy (RPC simulation calls).
-        let tasks: Vec<_> = executable_threads
+        // Skip the builds while the breakers are open, and only build a few probes while they are half-open.
+        let now = Instant::now();
+        if !self.rpc_pool().is_available(now) {
+            warn!(
+                "Circuit breakers of all rpc endpoints are open, skipping {} executable threads",
+                executable_threads.len()
+            );
+            return Ok(());
+        }
+        let admitted = self
+            .circuit_breaker
+            .lock()
+            .unwrap()
+            .admit(executable_threads.len(), now);
+        if admitted < executable_threads.len() {
+            warn!(
+                "Circuit breaker is {:?}, building {} of {} executable threads",
+                self.circuit_breaker.lock().unwrap().state(),
+                admitted,
+                executable_threads.len()
+            );
+            let deferred_threads = executable_threads.split_off(admitted);
+            self.defer_threads(deferred_threads, observed_slot).await;
+            if executable_threads.is_empty() {
+                return Ok(());
+            }
+        }
+
+        // The deferred threads got their turn, they are no longer retried ahead of the others.
+        for (thread_pubkey, _due_slot) in &executable_threads {
+            self.retry_queue.remove(thread_pubkey).await;
+        }
+        self.metrics
+            .retry_queue_depth
+            .set(self.retry_queue.len().await as i64);
+
+        let now = std::time::Instant::now();
+        self.clone()
+            .build_thread_exec_txs(
+                client,
+                observed_slot,
+                executable_threads,
+                worker_ids,
+                runtime,
+            )
+            .await;
+        self.metrics
+            .slot_build_duration
+            .observe(now.elapsed().as_secs_f64());
+
+        Ok(())
+    }
+
+    /// Builds the transactions of the executable threads with the local workers they are assigned to,
+    /// and submits them as they complete. Returns the threads whose transaction was sent.
+    async fn build_thread_exec_txs(
+        self: Arc<Self>,
+        client: Arc<RpcClient>,
+        observed_slot: u64,
+        executable_threads: Vec<(Pubkey, u64)>,
+        worker_ids: Vec<u64>,
+        runtime: Arc<Runtime>,
+    ) -> HashSet<Pubkey> {
+        // Drop the threads with a transaction in flight, another trigger marked them executable again.
+        let pending_threads = self.submitted_threads.pending(observed_slot).await;
+        let (executable_threads, duplicate_threads): (Vec<_>, Vec<_>) = executable_threads
+            .into_iter()
+            .partition(|(thread_pubkey, _due_slot)| !pending_threads.contains(thread_pubkey));
+        for (thread_pubkey, _due_slot) in duplicate_threads {
+            info!(
+                "Skipping thread: {} reason: a transaction is in flight",
+                thread_pubkey
+            );
+            self.executable_threads.remove(&thread_pubkey).await;
+        }
+
+        // Fetch the threads of the slot by batches rather than one by one in each build.
+        // A deleted thread is purged on the slot, one which could not be fetched is purged once it stays unreachable.
+        let thread_pubkeys: Vec<Pubkey> = executable_threads
             .iter()
-            .map(|(thread_pubkey, due_slot)| {
-                runtime.spawn(self.clone().try_build_thread_exec_tx(
-                    client.clone(),
-                    observed_slot,
-                    *due_slot,
-                    *thread_pubkey,
-                ))
-            })
+            .map(|(thread_pubkey, _due_slot)| *thread_pubkey)
             .collect();
-        let mut executed_threads: HashMap<Pubkey, (Signature, u64)> = HashMap::new();
+        let mut threads = refresh_threads(&client, &thread_pubkeys).await;
 
-        // Serialize to wire transactions.
-        let wire_txs = futures::future::join_all(tasks)
-            .await
-            .iter()
-            .filter_map(|res| match res {
-                Err(_err) => None,
-                Ok(res) => match res {
-                    None => None,
-                    Some((pubkey, tx, due_slot)) => {
-                        executed_threads.insert(*pubkey, (tx.signatures[0], *due_slot));
-                        Some(tx)
+        // Build transactions in parallel, started in the order of the queue.
+        // The build permits are handed out first come first served, so the highest fee threads are built first.
+        // Note we parallelize using tokio because this work is IO heavy (RPC simulation calls).
+        // The builds wait for a permit of their pipeline, so at most `build_concurrency` threads of a pipeline
+        // are built at once, and the built transactions are submitted in batches of that size as they complete.
+        // Their simulations also wait for a permit, bounding the simulations in flight across all the builds.
+        let concurrency = self.config().executor.build_concurrency;
+        let mut tasks = JoinSet::new();
+        let mut pipeline_depths: HashMap<Pipeline, i64> = HashMap::new();
+        for (thread_pubkey, due_slot) in executable_threads {
+            let Some(worker_id) = assign_worker(&worker_ids, &thread_pubkey, observed_slot) else {
+                continue;
+            };
+            let thread = match threads.remove(&thread_pubkey) {
+                None => {
+                    self.executable_threads
+                        .increment_simulation_failure(thread_pubkey)
+                        .await;
+                    self.unreachable_threads
+                        .record(thread_pubkey, observed_slot)
+                        .await;
+                    continue;
+                }
+                Some(RefreshedThread::Deleted) => {
+                    info!("Skipping thread: {} reason: deleted", thread_pubkey);
+                    self.executable_threads.remove(&thread_pubkey).await;
+                    self.unreachable_threads.record_deleted(thread_pubkey).await;
+                    continue;
+                }
+                Some(RefreshedThread::Live(thread)) => *thread,
+            };
+            let pipeline = self.thread_pipelines.get(&thread_pubkey);
+            *pipeline_depths.entry(pipeline).or_default() += 1;
+            let this = self.clone();
+            tasks.spawn_on(
+                async move {
+                    let built_tx = async {
+                        let _permit = this.build_permits[&pipeline].acquire().await.ok()?;
+                        this.metrics.build_queue_last_served_fee.set(
+                            this.thread_fees
+                                .get(&thread_pubkey)
+                                .await
+                                .unwrap_or_default() as i64,
+                        );
+                        // A hanging build is cancelled so it does not hold its permit any longer.
+                        let build_timeout =
+                            Duration::from_millis(this.config().executor.build_timeout_ms);
+                        let build = this.clone().try_build_thread_exec_tx(
+                            observed_slot,
+                            due_slot,
+                            thread,
+                            thread_pubkey,
+                            worker_id,
+                        );
+                        match tokio::time::timeout(build_timeout, build).await {
+                            Ok(built_tx) => built_tx,
+                            Err(_elapsed) => {
+                                this.record_build_timeout(
+                                    thread_pubkey,
+                                    observed_slot,
+                                    build_timeout,
+                                )
+                                .await;
+                                None
+                            }
+                        }
                     }
+                    .await;
+                    (pipeline, built_tx)
                 },
-            })
-            .map(|tx| serialize(tx).unwrap())
-            .collect::<Vec<Vec<u8>>>();
+                runtime.handle(),
+            );
+        }
+        self.metrics.build_queue_depth.set(tasks.len() as i64);
+        for pipeline in Pipeline::ALL {
+            self.set_pipeline_depth(pipeline, &pipeline_depths);
+        }
 
-        // Batch submit transactions to the leader.
-        match get_tpu_client()
-            .await
-            .try_send_wire_transaction_batch(wire_txs)
-            .await
-        {
-            Err(err) => {
-                info!("Failed to sent transaction batch: {:?}", err);
+        let mut built_txs = vec![];
+        let mut sent_threads = HashSet::new();
+        while let Some(res) = tasks.join_next().await {
+            self.metrics.build_queue_depth.set(tasks.len() as i64);
+            // A failed build only drops its own thread, the rest of the batch goes on.
+            if let Ok((pipeline, built_tx)) = res {
+                if let Some(depth) = pipeline_depths.get_mut(&pipeline) {
+                    *depth -= 1;
+                }
+                self.set_pipeline_depth(pipeline, &pipeline_depths);
+                if let Some(built_tx) = built_tx {
+                    self.metrics
+                        .pipeline_transactions_built
+                        .with_label_values(&[pipeline.name()])
+                        .inc();
+                    built_txs.push(built_tx);
+                }
             }
-            Ok(()) => {
-                self.executable_threads
-                    .remove_executed_threads(&executed_threads)
+            if built_txs.len() >= concurrency || (tasks.is_empty() && !built_txs.is_empty()) {
+                sent_threads.extend(
+                    self.clone()
+                        .submit_thread_exec_txs(
+                            observed_slot,
+                            std::mem::take(&mut built_txs),
+                            runtime.clone(),
+                        )
+                        .await,
+                );
+            }
+        }
+        sent_threads
+    }
+
+    /// Submits the built transactions and tracks them for the retry processing.
+    /// Returns the threads whose transaction was sent.
+    async fn submit_thread_exec_txs(
+        self: Arc<Self>,
+        observed_slot: u64,
+        built_txs: Vec<(Pubkey, VersionedTransaction, u64, u64, bool)>,
+        runtime: Arc<Runtime>,
+    ) -> Vec<Pubkey> {
+        let mut executed_threads: HashMap<Pubkey, (Signature, u64)> = HashMap::new();
+        let mut executed_txs: HashMap<Pubkey, (VersionedTransaction, u64)> = HashMap::new();
+        let mut bundle_txs = vec![];
+        let mut txs = vec![];
+        for (pubkey, tx, due_slot, worker_id, bundle) in built_txs {
+            executed_threads.insert(pubkey, (tx.signatures[0], due_slot));
+            executed_txs.insert(pubkey, (tx.clone(), worker_id));
+            if bundle {
+                bundle_txs.push(tx);
+            } else {
+                txs.push(tx);
+            }
+        }
+
+        // In dry run mode, log what would have been sent instead of submitting it.
+        if self.config().dry_run {
+            for (thread_pubkey, (signature, _due_slot)) in &executed_threads {
+                if let Some(tx) = bundle_txs
+                    .iter()
+                    .chain(&txs)
+                    .find(|tx| tx.signatures[0].eq(signature))
+                {
+                    log_dry_run_tx(observed_slot, thread_pubkey, tx);
+                }
+            }
+            self.executable_threads
+                .remove_executed_threads(&executed_threads)
+                .await;
+            return executed_threads.into_keys().collect();
+        }
+
+        // Submit bundles to the block engine, keeping the transactions it did not accept.
+        if !bundle_txs.is_empty() {
+            txs.extend(
+                self.clone()
+                    .submit_bundles(bundle_txs, runtime.clone())
+                    .await,
+            );
+        }
+
+        // Submit the remaining transactions. Whether they landed is checked by the retry processing.
+        if !txs.is_empty() {
+            let unsent = match self.config().submission_mode {
+                SubmissionMode::Rpc => self.send_rpc_txs(&txs).await,
+                SubmissionMode::Tpu | SubmissionMode::Jito => self.send_tpu_txs(&txs).await,
+            };
+            executed_threads.retain(|_, (signature, _)| !unsent.contains(signature));
+        }
+
+        let sent_threads = executed_threads.keys().copied().collect();
+        if !executed_threads.is_empty() {
+            self.metrics
+                .transactions_sent
+                .inc_by(executed_threads.len() as u64);
+            for (thread_pubkey, (signature, due_slot)) in &executed_threads {
+                let Some((tx, worker_id)) = executed_txs.get(thread_pubkey) else {
+                    continue;
+                };
+                self.metrics
+                    .worker_transactions_sent
+                    .with_label_values(&[&worker_id.to_string()])
+                    .inc();
+                self.log_execution(
+                    observed_slot,
+                    thread_pubkey,
+                    *worker_id,
+                    ExecutionEvent::submitted(signature),
+                );
+                self.notify(WebhookEvent::sent(thread_pubkey, observed_slot, signature));
+                self.track_submission(*thread_pubkey, tx, *worker_id, *due_slot, observed_slot)
                     .await;
-                self.transaction_history
-                    .add(observed_slot, executed_threads)
+                self.submitted_threads
+                    .add(*thread_pubkey, observed_slot)
                     .await;
             }
+            self.executable_threads
+                .remove_executed_threads(&executed_threads)
+                .await;
+            self.transaction_history
+                .add(observed_slot, executed_threads)
+                .await;
         }
+        sent_threads
+    }
 
-        Ok(())
+    /// Sends each transaction as its own bundle and spawns a task polling its status.
+    /// Returns the transactions which could not be bundled, to be sent through the regular path.
+    async fn submit_bundles(
+        self: Arc<Self>,
+        txs: Vec<VersionedTransaction>,
+        runtime: Arc<Runtime>,
+    ) -> Vec<VersionedTransaction> {
+        let Some(jito) = self.jito.as_ref() else {
+            return txs;
+        };
+
+        let results = futures::future::join_all(
+            txs.iter()
+                .map(|tx| jito.send_bundle(std::slice::from_ref(tx))),
+        )
+        .await;
+
+        let mut unbundled_txs = vec![];
+        for (tx, result) in txs.into_iter().zip(results) {
+            match result {
+                Err(err) => {
+                    info!(
+                        "Failed to send bundle for tx: {:?} err: {:?}, falling back to tpu",
+                        tx.signatures[0], err
+                    );
+                    unbundled_txs.push(tx);
+                }
+                Ok(bundle_id) => {
+                    info!("Sent bundle: {} tx: {:?}", bundle_id, tx.signatures[0]);
+                    let this = self.clone();
+                    runtime.spawn(async move {
+                        if let Some(jito) = this.jito.as_ref() {
+                            jito.poll_bundle_status(bundle_id).await;
+                        }
+                    });
+                }
+            }
+        }
+        unbundled_txs
     }
 
     pub async fn try_build_thread_exec_tx(
         self: Arc<Self>,
-        client: Arc<RpcClient>,
         observed_slot: u64,
         due_slot: u64,
+        thread: VersionedThread,
         thread_pubkey: Pubkey,
-    ) -> Option<(Pubkey, Transaction, u64)> {
-        let thread = match client.clone().get::<VersionedThread>(&thread_pubkey).await {
-            Err(_err) => {
-                self.executable_threads
-                    .increment_simulation_failure(thread_pubkey)
-                    .await;
-                return None;
-            }
-            Ok(thread) => thread,
-        };
+        worker_id: u64,
+    ) -> Option<(Pubkey, VersionedTransaction, u64, u64, bool)> {
+        // A thread denied by a reload of the thread filter stays in the observer indexes, it is dropped here.
+        if !self
+            .thread_filter
+            .is_allowed(&thread_pubkey, &thread.authority())
+            .await
+        {
+            info!(
+                "Dropping thread: {} reason: denied by the thread filter",
+                thread_pubkey
+            );
+            self.forget_thread(&thread_pubkey).await;
+            return None;
+        }
+
+        let worker = self.workers.get(worker_id)?;
+
+        // A quarantined thread is not built until its account changes.
+        if self.quarantine.contains(&thread_pubkey).await {
+            self.executable_threads.remove(&thread_pubkey).await;
+            return None;
+        }
+
+        self.unreachable_threads.remove(&thread_pubkey).await;
+        self.thread_fees.set(thread_pubkey, thread.fee()).await;
 
         // Exit early if the thread has been executed after it became due.
         if let Some(exec_context) = thread.exec_context() {
@@ -301,49 +1064,410 @@ impl TxExecutor {
             }
         }
 
-        if let Ok(tx) = crate::builders::build_thread_exec_tx(
-            client.clone(),
-            &self.keypair,
-            due_slot,
-            thread,
-            thread_pubkey,
-            self.config.worker_id,
-        )
-        .await
-        {
-            if let Some(tx) = tx {
+        // Skip the build if the thread hit its rate limit in this slot, its exec instruction would fail.
+        if is_rate_limited(&thread, observed_slot) {
+            info!(
+                "Requeuing thread: {} reason: rate limit reached in slot {}",
+                thread_pubkey, observed_slot
+            );
+            self.executable_threads
+                .add(HashSet::from([(thread_pubkey, due_slot)]))
+                .await;
+            return None;
+        }
+
+        let thread_fee = thread.fee();
+        let lookup_tables = self.lookup_table.tables().await;
+        let now = Instant::now();
+        let config = self.config();
+        let static_compute_units = config
+            .executor
+            .static_compute_units
+            .get(&thread_pubkey.to_string());
+        let result = match static_compute_units {
+            Some(static_compute_units) => {
+                crate::builders::build_static_thread_exec_tx(
+                    &self.rpc_pool().client(),
+                    &worker.keypair,
+                    self.fee_payer.as_ref(),
+                    worker.id,
+                    &self.blockhash_cache,
+                    static_compute_units,
+                    observed_slot,
+                    thread,
+                    thread_pubkey,
+                    &config,
+                    &self.metrics,
+                    &lookup_tables,
+                )
+                .await
+            }
+            None => {
+                crate::builders::build_thread_exec_tx(
+                    self.rpc_pool(),
+                    &worker.keypair,
+                    self.fee_payer.as_ref(),
+                    worker.id,
+                    &self.blockhash_cache,
+                    &self.compute_unit_cache,
+                    &self.account_triggers,
+                    &self.simulation_logs,
+                    &self.simulation_permits,
+                    due_slot,
+                    thread,
+                    thread_pubkey,
+                    &config,
+                    &self.metrics,
+                    &lookup_tables,
+                )
+                .await
+            }
+        };
+        let simulated =
+            match &result {
+                _ if static_compute_units.is_some() => None,
+                Ok(tx) => Some(ExecutionEvent::simulated(now.elapsed(), tx.is_some(), None)),
+                Err(PluginError::FailedToSimulateTx(err, _logs)) => Some(
+                    ExecutionEvent::simulated(now.elapsed(), false, Some(err.to_string())),
+                ),
+                Err(_err) => None,
+            };
+        if let Some(event) = simulated {
+            self.log_execution(observed_slot, &thread_pubkey, worker.id, event);
+        }
+        match result {
+            Ok(Some(tx)) => {
+                if self.config().lookup_table.enabled {
+                    let num_signers = tx.message.header().num_required_signatures as usize;
+                    self.lookup_table
+                        .observe(&tx.message.static_account_keys()[num_signers..])
+                        .await;
+                }
+                // The tip stays in the transaction if it falls back to the tpu,
+                // so the signature tracked for retries is the one that was sent.
+                // An untipped transaction is sent through the regular path, the block engine would reject it.
+                let (tx, bundle) = match self.jito.as_ref().map(|jito| {
+                    jito.append_tip(
+                        &tx,
+                        &worker.keypair,
+                        self.fee_payer.as_ref(),
+                        thread_fee,
+                        &lookup_tables,
+                    )
+                }) {
+                    Some(Ok(tipped_tx)) => (tipped_tx, true),
+                    Some(Err(reason)) => {
+                        info!(
+                            "Skipping bundle for tx: {:?} reason: {}, falling back to tpu",
+                            tx.signatures[0], reason
+                        );
+                        self.metrics
+                            .bundle_tips_skipped
+                            .with_label_values(&[reason])
+                            .inc();
+                        (tx, false)
+                    }
+                    None => (tx, false),
+                };
                 if !self
                     .transaction_history
                     .is_duplicate_tx(observed_slot, thread_pubkey, &tx)
                     .await
                 {
-                    Some((thread_pubkey, tx, due_slot))
+                    self.health.record_built_slot(observed_slot);
+                    let (instruction_count, compute_unit_limit, compute_unit_price) =
+                        compute_budget(&tx);
+                    self.log_execution(
+                        observed_slot,
+                        &thread_pubkey,
+                        worker.id,
+                        ExecutionEvent::built(
+                            &tx.signatures[0],
+                            instruction_count,
+                            compute_unit_limit,
+                            compute_unit_price,
+                        ),
+                    );
+                    self.notify(WebhookEvent::built(
+                        &thread_pubkey,
+                        observed_slot,
+                        &tx.signatures[0],
+                        compute_unit_limit,
+                    ));
+                    Some((thread_pubkey, tx, due_slot, worker.id, bundle))
                 } else {
                     None
                 }
-            } else {
+            }
+            Ok(None) => {
+                self.record_simulation_failure(thread_pubkey, observed_slot, None)
+                    .await;
+                None
+            }
+            Err(PluginError::FailedToSimulateTx(err, _logs)) => {
+                match non_retryable_error(&err) {
+                    Some(reason) => {
+                        self.quarantine_thread(thread_pubkey, observed_slot, reason, &err)
+                            .await
+                    }
+                    None => {
+                        self.record_simulation_failure(
+                            thread_pubkey,
+                            observed_slot,
+                            Some(err.to_string()),
+                        )
+                        .await
+                    }
+                }
+                None
+            }
+            // The rpc lags behind the validator, requeue the thread for the next observed slot.
+            Err(PluginError::MinContextSlotNotReached) => {
+                info!(
+                    "Requeuing thread: {} reason: rpc has not reached slot {}",
+                    thread_pubkey, due_slot
+                );
                 self.executable_threads
-                    .increment_simulation_failure(thread_pubkey)
+                    .add(HashSet::from([(thread_pubkey, due_slot)]))
                     .await;
                 None
             }
-        } else {
-            None
+            Err(_err) => None,
         }
     }
 
+    /// Drops the state of a thread which was closed.
+    pub async fn forget_thread(&self, thread_pubkey: &Pubkey) {
+        self.executable_threads.remove(thread_pubkey).await;
+        self.simulation_backoff.reset(thread_pubkey).await;
+        self.simulation_logs.remove(thread_pubkey).await;
+        self.release_thread(thread_pubkey).await;
+        self.compute_unit_cache.remove(thread_pubkey).await;
+        self.unreachable_threads.remove(thread_pubkey).await;
+        self.thread_fees.remove(thread_pubkey).await;
+        self.retry_queue.remove(thread_pubkey).await;
+        self.submissions.remove(thread_pubkey).await;
+        self.confirmation_tracker
+            .untrack_thread(thread_pubkey)
+            .await;
+        self.submitted_threads.remove(thread_pubkey).await;
+    }
+
+    /// Stops building the thread until its account changes, retrying its simulation is pointless.
+    async fn quarantine_thread(
+        &self,
+        thread_pubkey: Pubkey,
+        observed_slot: u64,
+        reason: &'static str,
+        err: &TransactionError,
+    ) {
+        warn!(
+            "Quarantining thread: {} reason: {} simulation_error: \"{}\"",
+            thread_pubkey, reason, err
+        );
+        self.quarantine
+            .add(
+                thread_pubkey,
+                QuarantinedThread {
+                    slot: observed_slot,
+                    reason,
+                    error: err.to_string(),
+                },
+            )
+            .await;
+        self.executable_threads.remove(&thread_pubkey).await;
+        self.simulation_backoff.reset(&thread_pubkey).await;
+        self.metrics
+            .quarantined_threads
+            .set(self.quarantine.len().await as i64);
+    }
+
+    /// Returns true if the workers execute the threads as if they were in the pool.
+    async fn is_permissionless(&self, client: &RpcClient) -> bool {
+        self.config().ignore_pool_membership || self.is_localnet(client).await
+    }
+
+    /// Returns true if the localnet mode is configured and active.
+    /// The mode is refused on mainnet-beta, the cluster is identified by its genesis hash the first time it is fetched.
+    pub async fn is_localnet(&self, client: &RpcClient) -> bool {
+        if !self.config().localnet {
+            return false;
+        }
+        let is_localnet = self
+            .localnet
+            .get_or_try_init(|| is_localnet_cluster(client))
+            .await;
+        matches!(is_localnet, Ok(true))
+    }
+
+    /// Queues the threads deferred for lack of capacity, so they are retried first on the next slots.
+    async fn defer_threads(&self, deferred_threads: Vec<(Pubkey, u64)>, observed_slot: u64) {
+        let overflow = self
+            .retry_queue
+            .defer(
+                deferred_threads
+                    .into_iter()
+                    .map(|(thread_pubkey, _due_slot)| {
+                        (
+                            thread_pubkey,
+                            self.thread_pipelines.is_one_shot(&thread_pubkey),
+                        )
+                    }),
+                observed_slot,
+                self.config().executor.retry_queue_size,
+            )
+            .await;
+        if overflow > 0 {
+            info!(
+                "Retry queue overflow, {} deferred threads wait their turn with the others",
+                overflow
+            );
+            self.metrics.retry_queue_overflow.inc_by(overflow as u64);
+        }
+        self.metrics
+            .retry_queue_depth
+            .set(self.retry_queue.len().await as i64);
+    }
+
+    /// Drops a deferred thread from the executable threads once it was executed since, e.g. by another worker.
+    pub async fn observe_thread_exec(&self, thread_pubkey: &Pubkey, last_exec_at: u64) {
+        if self
+            .retry_queue
+            .observe_exec(thread_pubkey, last_exec_at)
+            .await
+        {
+            info!(
+                "Dropping deferred thread: {} reason: executed at slot: {}",
+                thread_pubkey, last_exec_at
+            );
+            self.executable_threads.remove(thread_pubkey).await;
+            self.metrics
+                .retry_queue_depth
+                .set(self.retry_queue.len().await as i64);
+        }
+    }
+
+    /// Releases the thread from the quarantine, returns true if it was quarantined.
+    pub async fn release_thread(&self, thread_pubkey: &Pubkey) -> bool {
+        let released = self.quarantine.remove(thread_pubkey).await;
+        if released {
+            info!("Released thread: {} from quarantine", thread_pubkey);
+            self.metrics
+                .quarantined_threads
+                .set(self.quarantine.len().await as i64);
+        }
+        released
+    }
+
+    /// Backs off a thread whose build timed out like a thread failing its simulation.
+    /// Nothing was sent for it, so it is not left marked in flight and the next attempts go through.
+    async fn record_build_timeout(
+        &self,
+        thread_pubkey: Pubkey,
+        observed_slot: u64,
+        build_timeout: Duration,
+    ) {
+        info!(
+            "Cancelled the build of thread: {} reason: timed out after {:?}",
+            thread_pubkey, build_timeout
+        );
+        self.metrics.build_timeouts.inc();
+        self.submitted_threads.remove(&thread_pubkey).await;
+        self.record_simulation_failure(
+            thread_pubkey,
+            observed_slot,
+            Some(format!("build timed out after {:?}", build_timeout)),
+        )
+        .await;
+    }
+
+    async fn record_simulation_failure(
+        &self,
+        thread_pubkey: Pubkey,
+        observed_slot: u64,
+        error: Option<String>,
+    ) {
+        self.executable_threads
+            .increment_simulation_failure(thread_pubkey)
+            .await;
+        let backoff_config = self.backoff_config(&thread_pubkey);
+        let consecutive_failures = self
+            .simulation_backoff
+            .record_failure(thread_pubkey, observed_slot, error.clone(), &backoff_config)
+            .await;
+        if consecutive_failures >= backoff_config.failure_threshold {
+            self.notify(WebhookEvent::simulation_failed(
+                &thread_pubkey,
+                observed_slot,
+                consecutive_failures,
+                error,
+            ));
+        }
+    }
+
+    /// Returns the simulation backoff settings of the thread's pipeline.
+    pub fn backoff_config(&self, thread_pubkey: &Pubkey) -> SimulationBackoffConfig {
+        self.config()
+            .pipeline_backoff(self.thread_pipelines.get(thread_pubkey))
+            .clone()
+    }
+
+    fn set_pipeline_depth(&self, pipeline: Pipeline, pipeline_depths: &HashMap<Pipeline, i64>) {
+        self.metrics
+            .pipeline_build_queue_depth
+            .with_label_values(&[pipeline.name()])
+            .set(pipeline_depths.get(&pipeline).copied().unwrap_or_default());
+    }
+
+    /// Counts the outcomes of the transactions built without simulation apart, to weigh their failed fees.
+    fn record_static_outcome(&self, data: &TrackedSignature, outcome: &str) {
+        if data.static_build {
+            self.metrics
+                .static_transactions
+                .with_label_values(&[outcome])
+                .inc();
+        }
+    }
+
+    fn log_execution(
+        &self,
+        slot: u64,
+        thread_pubkey: &Pubkey,
+        worker_id: u64,
+        event: ExecutionEvent,
+    ) {
+        if let Some(sink) = self.execution_sink.as_ref() {
+            sink.record(&ExecutionRecord::new(
+                slot,
+                thread_pubkey,
+                worker_id,
+                &event,
+            ));
+        }
+    }
+
+    fn notify(&self, event: WebhookEvent) {
+        if let Some(webhook) = self.webhook.as_ref() {
+            webhook.notify(event);
+        }
+    }
+
+    pub fn blockhash_max_age(&self) -> Duration {
+        Duration::from_millis(self.config().blockhash_max_age_ms)
+    }
+
     async fn simulate_tx(self: Arc<Self>, tx: &Transaction) -> Result<Transaction, PluginError> {
-        let response =
-            RpcClient::new_with_commitment(LOCAL_RPC_URL.into(), CommitmentConfig::processed())
-                .simulate_transaction_with_config(
-                    tx,
-                    RpcSimulateTransactionConfig {
-                        replace_recent_blockhash: false,
-                        commitment: Some(CommitmentConfig::processed()),
-                        ..RpcSimulateTransactionConfig::default()
-                    },
-                )
-                .await?;
+        let response = self
+            .rpc_pool()
+            .simulate_transaction_with_config(
+                tx,
+                RpcSimulateTransactionConfig {
+                    replace_recent_blockhash: false,
+                    commitment: Some(commitment_config(self.config().simulation_commitment)),
+                    ..RpcSimulateTransactionConfig::default()
+                },
+            )
+            .await?;
 
         match response.value.err {
             Some(err) => Err(PluginError::FailedToSimulateTx(err, response.value.logs)),
@@ -351,8 +1475,56 @@ impl TxExecutor {
         }
     }
 
+    /// Batch sends the transactions to the current and upcoming leaders, falling back to the rpc.
+    /// Returns the signatures of the transactions which could not be sent.
+    pub(super) async fn send_tpu_txs(&self, txs: &[VersionedTransaction]) -> HashSet<Signature> {
+        let wire_txs = txs
+            .iter()
+            .map(|tx| serialize(tx).unwrap())
+            .collect::<Vec<Vec<u8>>>();
+        match get_tpu_client()
+            .await
+            .try_send_wire_transaction_batch(wire_txs)
+            .await
+        {
+            Ok(()) => HashSet::new(),
+            Err(err) => {
+                info!(
+                    "Failed to sent transaction batch: {:?}, falling back to rpc",
+                    err
+                );
+                self.send_rpc_txs(txs).await
+            }
+        }
+    }
+
+    /// Sends the transactions through the rpc pool.
+    /// Returns the signatures of the transactions which could not be sent.
+    pub(super) async fn send_rpc_txs(&self, txs: &[VersionedTransaction]) -> HashSet<Signature> {
+        let rpc_pool = self.rpc_pool();
+        let results =
+            futures::future::join_all(txs.iter().map(|tx| rpc_pool.send_transaction(tx))).await;
+        txs.iter()
+            .zip(results)
+            .filter_map(|(tx, result)| match result {
+                Ok(_) => None,
+                Err(err) => {
+                    info!("Failed to send tx: {:?} err: {:?}", tx.signatures[0], err);
+                    Some(tx.signatures[0])
+                }
+            })
+            .collect()
+    }
+
     async fn submit_tx(self: Arc<Self>, tx: &Transaction) -> Result<Transaction, PluginError> {
-        if !get_tpu_client().await.send_transaction(tx).await {
+        let sent = match self.config().submission_mode {
+            SubmissionMode::Rpc => self.rpc_pool().send_transaction(tx).await.is_ok(),
+            SubmissionMode::Tpu | SubmissionMode::Jito => {
+                get_tpu_client().await.send_transaction(tx).await
+                    || self.rpc_pool().send_transaction(tx).await.is_ok()
+            }
+        };
+        if !sent {
             return Err(PluginError::FailedToSendTx);
         }
         Ok(tx.clone())
@@ -365,6 +1537,211 @@ impl Debug for TxExecutor {
     }
 }
 
+fn log_dry_run_tx(slot: u64, thread_pubkey: &Pubkey, tx: &VersionedTransaction) {
+    let (instruction_count, compute_unit_limit, compute_unit_price) = compute_budget(tx);
+    info!(
+        "dry_run slot: {} thread: {} instruction_count: {} compute_unit_limit: {:?} compute_unit_price: {:?} tx_size: {}",
+        slot,
+        thread_pubkey,
+        instruction_count,
+        compute_unit_limit,
+        compute_unit_price,
+        serialize(tx).map(|data| data.len()).unwrap_or_default()
+    );
+}
+
+/// Returns the number of non compute budget instructions of the transaction, its compute unit limit and price.
+fn compute_budget(tx: &VersionedTransaction) -> (usize, Option<u32>, Option<u64>) {
+    let mut instruction_count = 0;
+    let mut compute_unit_limit = None;
+    let mut compute_unit_price = None;
+    for ix in tx.message.instructions() {
+        if tx.message.static_account_keys()[ix.program_id_index as usize] != compute_budget::id() {
+            instruction_count += 1;
+            continue;
+        }
+        match try_from_slice_unchecked::<ComputeBudgetInstruction>(&ix.data) {
+            Ok(ComputeBudgetInstruction::SetComputeUnitLimit(units)) => {
+                compute_unit_limit = Some(units)
+            }
+            Ok(ComputeBudgetInstruction::SetComputeUnitPrice(price)) => {
+                compute_unit_price = Some(price)
+            }
+            _ => {}
+        }
+    }
+    (instruction_count, compute_unit_limit, compute_unit_price)
+}
+
+fn build_jito_client(config: &PluginConfig) -> Option<JitoClient> {
+    if config.submission_mode != SubmissionMode::Jito {
+        return None;
+    }
+    match config.jito.as_ref().map(JitoClient::new) {
+        None => {
+            info!("Jito submission mode is enabled without a jito config, using tpu");
+            None
+        }
+        Some(Err(err)) => {
+            info!("Failed to build jito client: {:?}, using tpu", err);
+            None
+        }
+        Some(Ok(client)) => Some(client),
+    }
+}
+
+fn build_webhook(config: &PluginConfig, metrics: Arc<Metrics>) -> Option<Arc<Webhook>> {
+    match config
+        .webhook
+        .as_ref()
+        .map(|webhook| Webhook::new(webhook, metrics))
+    {
+        None => None,
+        Some(Err(err)) => {
+            info!("Failed to build webhook: {:?}", err);
+            None
+        }
+        Some(Ok(webhook)) => Some(Arc::new(webhook)),
+    }
+}
+
+impl TxExecutor {
+    /// Returns the current config. A reload swaps it as a whole, so the returned config is always consistent.
+    pub fn config(&self) -> Arc<PluginConfig> {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn rpc_pool(&self) -> Arc<RpcPool> {
+        self.rpc_pool.read().unwrap().clone()
+    }
+
+    /// Applies a reloaded config. Everything derived from it is built before anything is swapped.
+    pub fn apply_config(&self, config: PluginConfig) {
+        let current = self.config();
+        let rpc_pool = (config.rpc_urls != current.rpc_urls
+            || config.executor.build_timeout_ms != current.executor.build_timeout_ms)
+            .then(|| Arc::new(build_rpc_pool(&config, self.metrics.clone())));
+
+        for pipeline in Pipeline::ALL {
+            resize_permits(
+                &self.build_permits[&pipeline],
+                current.executor.build_concurrency(pipeline),
+                config.executor.build_concurrency(pipeline),
+            );
+        }
+        resize_permits(
+            &self.simulation_permits,
+            current.executor.simulation_concurrency,
+            config.executor.simulation_concurrency,
+        );
+        if let Some(rpc_pool) = rpc_pool {
+            *self.rpc_pool.write().unwrap() = rpc_pool;
+        }
+        *self.config.write().unwrap() = Arc::new(config);
+    }
+
+    /// Records whether a sent transaction succeeded on-chain in the circuit breaker.
+    fn record_onchain_result(&self, ok: bool) {
+        let transition = self
+            .circuit_breaker
+            .lock()
+            .unwrap()
+            .record(ok, Instant::now());
+        let Some(state) = transition else {
+            return;
+        };
+        self.metrics
+            .circuit_breaker_state
+            .with_label_values(&[ONCHAIN_BREAKER])
+            .set(state.gauge());
+        match state {
+            BreakerState::Open { .. } => {
+                self.metrics
+                    .circuit_breaker_trips
+                    .with_label_values(&[ONCHAIN_BREAKER])
+                    .inc();
+                warn!(
+                    "Circuit breaker opened, too many transactions failed on-chain. Pausing the builds for {} ms",
+                    self.config().circuit_breaker.cooldown_ms
+                );
+            }
+            _ => info!("Circuit breaker of the sent transactions is {:?}", state),
+        }
+    }
+}
+
+/// The label of the circuit breaker of the transactions failing on-chain.
+static ONCHAIN_BREAKER: &str = "onchain";
+
+/// Returns true if the thread was executed as many times as its rate limit allows in the slot.
+fn is_rate_limited(thread: &VersionedThread, slot: u64) -> bool {
+    thread.exec_context().is_some_and(|exec_context| {
+        exec_context.last_exec_at == slot && exec_context.execs_since_slot >= thread.rate_limit()
+    })
+}
+
+/// Returns false if the cluster is mainnet-beta, where the localnet mode is refused.
+async fn is_localnet_cluster(client: &RpcClient) -> Result<bool, PluginError> {
+    let genesis_hash = client.get_genesis_hash().await?;
+    if genesis_hash.to_string() == MAINNET_BETA_GENESIS_HASH {
+        error!("Refusing the localnet mode, the cluster is mainnet-beta");
+        return Ok(false);
+    }
+    info!("Localnet mode active, genesis hash: {}", genesis_hash);
+    Ok(true)
+}
+
+/// Merges the queues taking one item of each in turn, keeping the order within each queue.
+fn interleave<T>(queues: Vec<Vec<T>>) -> Vec<T> {
+    let mut queues: Vec<_> = queues.into_iter().map(|queue| queue.into_iter()).collect();
+    let mut merged = vec![];
+    loop {
+        let len = merged.len();
+        merged.extend(queues.iter_mut().filter_map(|queue| queue.next()));
+        if merged.len() == len {
+            return merged;
+        }
+    }
+}
+
+/// Adds or removes permits so the semaphore admits `size` holders at once.
+/// Permits held while the semaphore shrinks are not given back until they are released.
+fn resize_permits(semaphore: &Semaphore, current: usize, size: usize) {
+    match size.cmp(&current) {
+        std::cmp::Ordering::Greater => semaphore.add_permits(size - current),
+        std::cmp::Ordering::Less => {
+            let forgotten = semaphore.forget_permits(current - size);
+            if forgotten < current - size {
+                info!(
+                    "Shrinking the permits from {} to {}, {} permits in use could not be removed",
+                    current,
+                    size,
+                    current - size - forgotten
+                );
+            }
+        }
+        std::cmp::Ordering::Equal => {}
+    }
+}
+
+fn build_rpc_pool(config: &PluginConfig, metrics: Arc<Metrics>) -> RpcPool {
+    let mut urls = vec![LOCAL_RPC_URL.to_string()];
+    urls.extend(config.rpc_urls.iter().cloned());
+    RpcPool::new(
+        &urls,
+        CommitmentConfig::processed(),
+        Duration::from_millis(config.executor.build_timeout_ms),
+        &config.circuit_breaker,
+        metrics,
+    )
+}
+
+/// The number of slots between two rpc endpoint health reports.
+static RPC_HEALTH_LOG_INTERVAL: u64 = 100;
+
+/// The genesis hash of mainnet-beta, where the localnet mode is refused.
+static MAINNET_BETA_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+
 static LOCAL_RPC_URL: &str = "http://127.0.0.1:8899";
 static LOCAL_WEBSOCKET_URL: &str = "ws://127.0.0.1:8900";
 
@@ -385,3 +1762,76 @@ async fn get_tpu_client() -> TpuClient<QuicPool, QuicConnectionManager, QuicConf
     .await
     .unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use sablier_thread_program::state::{ExecContext, Thread, Trigger, TriggerContext};
+    use sablier_utils::thread::ClockData;
+
+    use super::*;
+
+    fn rate_limited_thread(
+        rate_limit: u64,
+        execs_since_slot: u64,
+        last_exec_at: u64,
+    ) -> VersionedThread {
+        VersionedThread::V1(Thread {
+            authority: Pubkey::new_unique(),
+            bump: 0,
+            created_at: ClockData {
+                slot: 0,
+                epoch: 0,
+                unix_timestamp: 0,
+            },
+            domain: None,
+            exec_context: Some(ExecContext {
+                exec_index: 0,
+                execs_since_reimbursement: 0,
+                execs_since_slot,
+                last_exec_at,
+                trigger_context: TriggerContext::Now,
+            }),
+            fee: 0,
+            id: vec![],
+            instructions: vec![],
+            next_instruction: None,
+            paused: false,
+            rate_limit,
+            trigger: Trigger::Now,
+        })
+    }
+
+    #[test]
+    fn thread_at_its_rate_limit_is_not_built() {
+        assert!(is_rate_limited(&rate_limited_thread(2, 2, 100), 100));
+        // The limit resets in the next slot.
+        assert!(!is_rate_limited(&rate_limited_thread(2, 2, 100), 101));
+        assert!(!is_rate_limited(&rate_limited_thread(2, 1, 100), 100));
+    }
+
+    #[test]
+    fn pipeline_queues_are_interleaved() {
+        assert_eq!(
+            interleave(vec![vec![1, 2, 3], vec![], vec![4], vec![5, 6]]),
+            vec![1, 4, 5, 2, 6, 3]
+        );
+    }
+
+    #[tokio::test]
+    async fn localnet_mode_is_refused_on_mainnet_beta() {
+        let cluster = |genesis_hash: String| {
+            RpcClient::new_mock_with_mocks(
+                "succeeds".to_string(),
+                HashMap::from([(
+                    solana_client::rpc_request::RpcRequest::GetGenesisHash,
+                    serde_json::json!(genesis_hash),
+                )]),
+            )
+        };
+        let mainnet_beta = cluster(MAINNET_BETA_GENESIS_HASH.to_string());
+        assert!(!is_localnet_cluster(&mainnet_beta).await.unwrap());
+
+        let localnet = cluster(solana_sdk::hash::Hash::new_unique().to_string());
+        assert!(is_localnet_cluster(&localnet).await.unwrap());
+    }
+}
@@ -0,0 +1,111 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use sablier_network_program::state::Worker;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use crate::{config::PluginConfig, utils::read_or_new_keypair};
+
+/// A worker identity operated by the plugin, and the keypair of its signatory.
+pub struct LocalWorker {
+    pub id: u64,
+    pub pubkey: Pubkey,
+    pub keypair: Keypair,
+}
+
+impl LocalWorker {
+    pub fn signatory(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+}
+
+/// The worker identities operated by the plugin, in the config order.
+/// The first one is the primary worker, which signs the transactions not tied to a thread, e.g. the lookup table ones.
+pub struct LocalWorkers(Vec<LocalWorker>);
+
+impl LocalWorkers {
+    pub fn new(config: &PluginConfig) -> Self {
+        Self(
+            config
+                .workers()
+                .into_iter()
+                .map(|worker| LocalWorker {
+                    id: worker.worker_id,
+                    pubkey: Worker::pubkey(worker.worker_id),
+                    // Simulations don't verify signatures, so a throwaway keypair is enough in dry run mode.
+                    keypair: if config.dry_run {
+                        Keypair::new()
+                    } else {
+                        read_or_new_keypair(worker.keypath)
+                    },
+                })
+                .collect(),
+        )
+    }
+
+    pub fn primary(&self) -> &LocalWorker {
+        &self.0[0]
+    }
+
+    pub fn get(&self, worker_id: u64) -> Option<&LocalWorker> {
+        self.0.iter().find(|worker| worker.id == worker_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &LocalWorker> {
+        self.0.iter()
+    }
+}
+
+/// Reads the keypair paying the exec transaction fees, if one is configured.
+pub fn fee_payer(config: &PluginConfig) -> Option<Keypair> {
+    config.fee_payer.as_ref().map(|keypath| {
+        if config.dry_run {
+            Keypair::new()
+        } else {
+            read_or_new_keypair(Some(keypath.clone()))
+        }
+    })
+}
+
+/// Assigns the thread to one of the workers. The assignment only depends on the thread and the slot,
+/// so two local workers never race on the same thread within a slot.
+pub fn assign_worker(worker_ids: &[u64], thread_pubkey: &Pubkey, slot: u64) -> Option<u64> {
+    if worker_ids.is_empty() {
+        return None;
+    }
+    let mut hasher = DefaultHasher::new();
+    thread_pubkey.hash(&mut hasher);
+    slot.hash(&mut hasher);
+    Some(worker_ids[(hasher.finish() % worker_ids.len() as u64) as usize])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn threads_are_assigned_deterministically() {
+        let worker_ids = [0, 4, 7];
+        let threads: Vec<Pubkey> = (0..32).map(|_| Pubkey::new_unique()).collect();
+        for thread_pubkey in &threads {
+            let worker_id = assign_worker(&worker_ids, thread_pubkey, 42).unwrap();
+            assert!(worker_ids.contains(&worker_id));
+            assert_eq!(
+                assign_worker(&worker_ids, thread_pubkey, 42),
+                Some(worker_id)
+            );
+        }
+        // The threads are spread across the workers.
+        let assigned: std::collections::HashSet<u64> = threads
+            .iter()
+            .filter_map(|thread_pubkey| assign_worker(&worker_ids, thread_pubkey, 42))
+            .collect();
+        assert!(assigned.len() > 1);
+        assert_eq!(assign_worker(&[], &threads[0], 42), None);
+    }
+}
@@ -0,0 +1,125 @@
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+use crate::config::HealthConfig;
+
+/// Liveness of the slot processing and of the RPC client.
+#[derive(Default)]
+pub struct Health {
+    last_processed_slot: AtomicU64,
+    last_processed_at: Mutex<Option<Instant>>,
+    last_built_slot: AtomicU64,
+    rpc_healthy: AtomicBool,
+    rpc_checked_at: Mutex<Option<Instant>>,
+    // The latest slot notified by geyser, compared against the cluster slot.
+    observed_slot: AtomicU64,
+    slot_lag: AtomicU64,
+    lagging: AtomicBool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    pub healthy: bool,
+    pub last_processed_slot: u64,
+    pub last_built_slot: u64,
+    pub tracked_threads: usize,
+    pub rpc_healthy: bool,
+    pub slot_lag: u64,
+    /// The builds are paused while the validator lags behind the cluster.
+    pub lagging: bool,
+}
+
+impl Health {
+    pub fn record_processed_slot(&self, slot: u64) {
+        self.last_processed_slot.fetch_max(slot, Ordering::Relaxed);
+        *self.last_processed_at.lock().unwrap() = Some(Instant::now());
+    }
+
+    pub fn record_observed_slot(&self, slot: u64) {
+        self.observed_slot.fetch_max(slot, Ordering::Relaxed);
+    }
+
+    pub fn observed_slot(&self) -> u64 {
+        self.observed_slot.load(Ordering::Relaxed)
+    }
+
+    /// Records the lag behind the cluster. Returns whether the validator was lagging before.
+    pub fn record_slot_lag(&self, slot_lag: u64, lagging: bool) -> bool {
+        self.slot_lag.store(slot_lag, Ordering::Relaxed);
+        self.lagging.swap(lagging, Ordering::Relaxed)
+    }
+
+    pub fn is_lagging(&self) -> bool {
+        self.lagging.load(Ordering::Relaxed)
+    }
+
+    pub fn record_built_slot(&self, slot: u64) {
+        self.last_built_slot.fetch_max(slot, Ordering::Relaxed);
+    }
+
+    pub fn record_rpc_health(&self, healthy: bool) {
+        self.rpc_healthy.store(healthy, Ordering::Relaxed);
+        *self.rpc_checked_at.lock().unwrap() = Some(Instant::now());
+    }
+
+    pub fn report(&self, tracked_threads: usize, config: &HealthConfig) -> HealthReport {
+        let rpc_healthy = self.rpc_healthy.load(Ordering::Relaxed);
+        let lagging = self.is_lagging();
+        let healthy = rpc_healthy
+            && !lagging
+            && is_fresh(&self.last_processed_at, config.max_processed_slot_age_secs)
+            && is_fresh(&self.rpc_checked_at, config.max_rpc_health_age_secs);
+        HealthReport {
+            healthy,
+            last_processed_slot: self.last_processed_slot.load(Ordering::Relaxed),
+            last_built_slot: self.last_built_slot.load(Ordering::Relaxed),
+            tracked_threads,
+            rpc_healthy,
+            slot_lag: self.slot_lag.load(Ordering::Relaxed),
+            lagging,
+        }
+    }
+}
+
+impl Debug for Health {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "health")
+    }
+}
+
+fn is_fresh(instant: &Mutex<Option<Instant>>, max_age_secs: u64) -> bool {
+    instant
+        .lock()
+        .unwrap()
+        .map(|instant| instant.elapsed() <= Duration::from_secs(max_age_secs))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lagging_validator_is_unhealthy() {
+        let health = Health::default();
+        health.record_processed_slot(100);
+        health.record_rpc_health(true);
+        assert!(health.report(0, &HealthConfig::default()).healthy);
+
+        assert!(!health.record_slot_lag(64, true));
+        let report = health.report(0, &HealthConfig::default());
+        assert!(!report.healthy);
+        assert_eq!(report.slot_lag, 64);
+
+        assert!(health.record_slot_lag(2, false));
+        assert!(health.report(0, &HealthConfig::default()).healthy);
+    }
+}
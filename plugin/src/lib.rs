@@ -1,20 +1,53 @@
 pub mod config {
-    pub use sablier_plugin_utils::PluginConfig;
+    pub use sablier_plugin_utils::{
+        BalanceConfig, BalanceDirection, CircuitBreakerConfig, Commitment, ComputeUnitOverride,
+        ExecutorConfig, FeeFilterConfig, HealthConfig, InjectedAccountMeta, InjectedInstruction,
+        JitoConfig, JitoTip, LogFormat, LookupTableConfig, MinContextSlotRetryConfig,
+        PersistenceConfig, Pipeline, PipelineConfig, PipelinesConfig, PluginConfig,
+        PriorityFeeConfig, PriorityFeeMode, PythConfig, SimulationBackoffConfig,
+        SimulationLogsConfig, SlotLagConfig, SnapshotStreamConfig, StaticComputeUnits,
+        SubmissionMode, ThreadFilterConfig, TokenBalanceTrigger, WebhookConfig,
+        WebsocketFallbackConfig, WorkerConfig,
+    };
 }
 
 use solana_geyser_plugin_interface::geyser_plugin_interface::GeyserPlugin;
 
+#[cfg(feature = "http-server")]
+mod admin;
+mod bootstrap;
 mod builders;
 mod error;
 mod events;
+mod execution_log;
 mod executors;
+mod health;
+mod metrics;
 mod observers;
+mod persistence;
 mod plugin;
 mod pool_position;
+mod refresh;
+mod reload;
+#[cfg(feature = "http-server")]
+mod server;
+mod shutdown;
+mod snapshot;
 mod utils;
+mod webhook;
+mod websocket;
 
 pub use plugin::SablierPlugin;
 
+/// The exec packing steps, exposed for the benchmarks only.
+#[doc(hidden)]
+pub mod bench {
+    pub use crate::{
+        builders::build_exec_ix,
+        events::{decode_exec_state, decode_thread},
+    };
+}
+
 #[no_mangle]
 #[allow(improper_ctypes_definitions)]
 /// # Safety
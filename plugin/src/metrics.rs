@@ -0,0 +1,493 @@
+use std::fmt::Debug;
+
+use prometheus::{
+    core::Collector, exponential_buckets, Encoder, Histogram, HistogramOpts, IntCounter,
+    IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+
+/// Prometheus metrics of the observers and executors.
+pub struct Metrics {
+    registry: Registry,
+    pub threads_observed: IntCounter,
+    pub simulations: IntCounter,
+    pub simulation_errors: IntCounterVec,
+    pub simulation_duration: Histogram,
+    pub min_context_slot_not_reached: IntCounterVec,
+    pub min_context_slot_retries: IntCounter,
+    pub circuit_breaker_state: IntGaugeVec,
+    pub circuit_breaker_trips: IntCounterVec,
+    pub compute_units: Histogram,
+    pub build_queue_depth: IntGauge,
+    pub build_queue_overflow: IntCounter,
+    pub build_queue_last_served_fee: IntGauge,
+    pub build_timeouts: IntCounter,
+    pub pipeline_executable_threads: IntGaugeVec,
+    pub pipeline_build_queue_depth: IntGaugeVec,
+    pub pipeline_build_queue_overflow: IntCounterVec,
+    pub pipeline_transactions_built: IntCounterVec,
+    pub compute_unit_cache_hits: IntCounter,
+    pub compute_unit_cache_misses: IntCounter,
+    pub static_transactions: IntCounterVec,
+    pub slot_build_duration: Histogram,
+    pub now_execution_latency: Histogram,
+    pub transactions_built: IntCounter,
+    pub transactions_sent: IntCounter,
+    pub transactions_confirmed: IntCounter,
+    pub transactions_finalized: IntCounter,
+    pub transactions_failed: IntCounter,
+    pub transactions_dropped: IntCounter,
+    pub transactions_resubmitted: IntCounter,
+    pub transactions_landed_first_try: IntCounter,
+    pub transactions_landed_after_retry: IntCounter,
+    pub transactions_abandoned: IntCounter,
+    pub worker_transactions_sent: IntCounterVec,
+    pub worker_transactions_confirmed: IntCounterVec,
+    pub worker_transactions_failed: IntCounterVec,
+    pub webhook_events_dropped: IntCounter,
+    pub webhook_events_failed: IntCounter,
+    pub snapshot_messages_published: IntCounter,
+    pub snapshot_messages_skipped: IntCounter,
+    pub snapshot_consumers: IntGauge,
+    pub pyth_updates_filtered: IntCounterVec,
+    pub retry_queue_depth: IntGauge,
+    pub retry_queue_overflow: IntCounter,
+    pub websocket_fallbacks: IntCounter,
+    pub websocket_fallback_updates: IntCounter,
+    pub bundle_tips_skipped: IntCounterVec,
+    pub executable_threads: IntGauge,
+    pub slot_lag: IntGauge,
+    pub slot_lag_paused: IntGauge,
+    pub tracked_signatures: IntGauge,
+    pub quarantined_threads: IntGauge,
+    pub signatory_balance: IntGaugeVec,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("sablier".into()), None).unwrap();
+        let this = Self {
+            threads_observed: IntCounter::new(
+                "threads_observed_total",
+                "Thread accounts indexed by the observer",
+            )
+            .unwrap(),
+            simulations: IntCounter::new(
+                "simulations_total",
+                "Simulations run while building thread exec transactions",
+            )
+            .unwrap(),
+            simulation_errors: IntCounterVec::new(
+                Opts::new(
+                    "simulation_errors_total",
+                    "Failed simulations by error type",
+                ),
+                &["error"],
+            )
+            .unwrap(),
+            simulation_duration: Histogram::with_opts(
+                HistogramOpts::new(
+                    "simulation_duration_seconds",
+                    "Time spent simulating a thread exec transaction",
+                )
+                .buckets(exponential_buckets(0.005, 2.0, 12).unwrap()),
+            )
+            .unwrap(),
+            min_context_slot_not_reached: IntCounterVec::new(
+                Opts::new(
+                    "min_context_slot_not_reached_total",
+                    "Requests rejected because the endpoint lags behind the min context slot",
+                ),
+                &["endpoint"],
+            )
+            .unwrap(),
+            circuit_breaker_state: IntGaugeVec::new(
+                Opts::new(
+                    "circuit_breaker_state",
+                    "State of the circuit breakers: 0 closed, 1 half-open, 2 open",
+                ),
+                &["breaker"],
+            )
+            .unwrap(),
+            circuit_breaker_trips: IntCounterVec::new(
+                Opts::new(
+                    "circuit_breaker_trips_total",
+                    "Times the circuit breakers opened",
+                ),
+                &["breaker"],
+            )
+            .unwrap(),
+            static_transactions: IntCounterVec::new(
+                Opts::new(
+                    "static_transactions_total",
+                    "Thread exec transactions built without simulation, by outcome",
+                ),
+                &["outcome"],
+            )
+            .unwrap(),
+            min_context_slot_retries: IntCounter::new(
+                "min_context_slot_retries_total",
+                "Simulations retried because no endpoint reached the min context slot",
+            )
+            .unwrap(),
+            compute_units: Histogram::with_opts(
+                HistogramOpts::new(
+                    "compute_units",
+                    "Compute units consumed by thread exec transactions",
+                )
+                .buckets(exponential_buckets(1_000.0, 2.0, 11).unwrap()),
+            )
+            .unwrap(),
+            build_queue_depth: IntGauge::new(
+                "build_queue_depth",
+                "Thread exec transactions waiting to be built in the current slot",
+            )
+            .unwrap(),
+            build_queue_last_served_fee: IntGauge::new(
+                "build_queue_last_served_fee",
+                "Fee of the last thread taken from the build queue, 0 if unknown",
+            )
+            .unwrap(),
+            build_queue_overflow: IntCounter::new(
+                "build_queue_overflow_total",
+                "Executable threads deferred to the next slot because the build queue was full",
+            )
+            .unwrap(),
+            pipeline_executable_threads: IntGaugeVec::new(
+                Opts::new(
+                    "pipeline_executable_threads",
+                    "Threads queued for execution, by pipeline",
+                ),
+                &["pipeline"],
+            )
+            .unwrap(),
+            pipeline_build_queue_depth: IntGaugeVec::new(
+                Opts::new(
+                    "pipeline_build_queue_depth",
+                    "Thread exec transactions waiting to be built in the current slot, by pipeline",
+                ),
+                &["pipeline"],
+            )
+            .unwrap(),
+            pipeline_build_queue_overflow: IntCounterVec::new(
+                Opts::new(
+                    "pipeline_build_queue_overflow_total",
+                    "Executable threads deferred to the next slot because their pipeline's build queue was full",
+                ),
+                &["pipeline"],
+            )
+            .unwrap(),
+            pipeline_transactions_built: IntCounterVec::new(
+                Opts::new(
+                    "pipeline_transactions_built_total",
+                    "Thread exec transactions built, by pipeline",
+                ),
+                &["pipeline"],
+            )
+            .unwrap(),
+            build_timeouts: IntCounter::new(
+                "build_timeouts_total",
+                "Thread exec transaction builds cancelled because they timed out",
+            )
+            .unwrap(),
+            compute_unit_cache_hits: IntCounter::new(
+                "compute_unit_cache_hits_total",
+                "Thread exec transactions built from cached compute units without simulation",
+            )
+            .unwrap(),
+            compute_unit_cache_misses: IntCounter::new(
+                "compute_unit_cache_misses_total",
+                "Thread exec transactions simulated because their compute units were not cached",
+            )
+            .unwrap(),
+            slot_build_duration: Histogram::with_opts(
+                HistogramOpts::new(
+                    "slot_build_duration_seconds",
+                    "Time spent building the thread exec transactions of a slot",
+                )
+                .buckets(exponential_buckets(0.01, 2.0, 12).unwrap()),
+            )
+            .unwrap(),
+            now_execution_latency: Histogram::with_opts(
+                HistogramOpts::new(
+                    "now_execution_latency_seconds",
+                    "Time from observing a thread with a now trigger to sending its transaction",
+                )
+                .buckets(exponential_buckets(0.01, 2.0, 12).unwrap()),
+            )
+            .unwrap(),
+            transactions_built: IntCounter::new(
+                "transactions_built_total",
+                "Thread exec transactions built",
+            )
+            .unwrap(),
+            transactions_sent: IntCounter::new(
+                "transactions_sent_total",
+                "Thread exec transactions sent",
+            )
+            .unwrap(),
+            transactions_confirmed: IntCounter::new(
+                "transactions_confirmed_total",
+                "Thread exec transactions confirmed",
+            )
+            .unwrap(),
+            transactions_finalized: IntCounter::new(
+                "transactions_finalized_total",
+                "Thread exec transactions finalized",
+            )
+            .unwrap(),
+            transactions_failed: IntCounter::new(
+                "transactions_failed_total",
+                "Thread exec transactions which landed with an error",
+            )
+            .unwrap(),
+            transactions_dropped: IntCounter::new(
+                "transactions_dropped_total",
+                "Thread exec transactions which never landed",
+            )
+            .unwrap(),
+            transactions_resubmitted: IntCounter::new(
+                "transactions_resubmitted_total",
+                "Thread exec transactions signed again with a fresh blockhash after theirs expired",
+            )
+            .unwrap(),
+            transactions_landed_first_try: IntCounter::new(
+                "transactions_landed_first_try_total",
+                "Thread exec transactions which landed without being resubmitted",
+            )
+            .unwrap(),
+            transactions_landed_after_retry: IntCounter::new(
+                "transactions_landed_after_retry_total",
+                "Thread exec transactions which landed after being resubmitted",
+            )
+            .unwrap(),
+            transactions_abandoned: IntCounter::new(
+                "transactions_abandoned_total",
+                "Expired thread exec transactions given up on, their thread is built again",
+            )
+            .unwrap(),
+            worker_transactions_sent: IntCounterVec::new(
+                Opts::new(
+                    "worker_transactions_sent_total",
+                    "Exec transactions sent by local worker",
+                ),
+                &["worker_id"],
+            )
+            .unwrap(),
+            worker_transactions_confirmed: IntCounterVec::new(
+                Opts::new(
+                    "worker_transactions_confirmed_total",
+                    "Exec transactions confirmed by local worker",
+                ),
+                &["worker_id"],
+            )
+            .unwrap(),
+            worker_transactions_failed: IntCounterVec::new(
+                Opts::new(
+                    "worker_transactions_failed_total",
+                    "Exec transactions failed on-chain by local worker",
+                ),
+                &["worker_id"],
+            )
+            .unwrap(),
+            webhook_events_dropped: IntCounter::new(
+                "webhook_events_dropped_total",
+                "Webhook events dropped because the delivery queue was full",
+            )
+            .unwrap(),
+            webhook_events_failed: IntCounter::new(
+                "webhook_events_failed_total",
+                "Webhook events which could not be delivered",
+            )
+            .unwrap(),
+            snapshot_messages_published: IntCounter::new(
+                "snapshot_messages_published_total",
+                "Executable threads published to the snapshot stream",
+            )
+            .unwrap(),
+            snapshot_messages_skipped: IntCounter::new(
+                "snapshot_messages_skipped_total",
+                "Snapshot messages skipped by consumers falling behind the stream",
+            )
+            .unwrap(),
+            snapshot_consumers: IntGauge::new(
+                "snapshot_consumers",
+                "Consumers connected to the snapshot stream",
+            )
+            .unwrap(),
+            pyth_updates_filtered: IntCounterVec::new(
+                Opts::new(
+                    "pyth_updates_filtered_total",
+                    "Price updates which did not enqueue a pyth thread, by reason",
+                ),
+                &["reason"],
+            )
+            .unwrap(),
+            retry_queue_depth: IntGauge::new(
+                "retry_queue_depth",
+                "Threads deferred for lack of capacity, retried first on the next slots",
+            )
+            .unwrap(),
+            retry_queue_overflow: IntCounter::new(
+                "retry_queue_overflow_total",
+                "Deferred threads left out of the full retry queue",
+            )
+            .unwrap(),
+            websocket_fallbacks: IntCounter::new(
+                "websocket_fallbacks_total",
+                "Websocket subscriptions opened after a gap in the geyser stream",
+            )
+            .unwrap(),
+            websocket_fallback_updates: IntCounter::new(
+                "websocket_fallback_updates_total",
+                "Account updates received over the websocket fallback",
+            )
+            .unwrap(),
+            bundle_tips_skipped: IntCounterVec::new(
+                Opts::new(
+                    "bundle_tips_skipped_total",
+                    "Transactions sent outside of a bundle because they could not be tipped, by reason",
+                ),
+                &["reason"],
+            )
+            .unwrap(),
+            executable_threads: IntGauge::new("executable_threads", "Threads queued for execution")
+                .unwrap(),
+            slot_lag: IntGauge::new(
+                "slot_lag",
+                "Slots the validator lags behind the cluster, as of the last check",
+            )
+            .unwrap(),
+            slot_lag_paused: IntGauge::new(
+                "slot_lag_paused",
+                "1 while the builds are paused because the validator lags behind the cluster",
+            )
+            .unwrap(),
+            tracked_signatures: IntGauge::new(
+                "tracked_signatures",
+                "Sent transactions whose status is polled until they settle",
+            )
+            .unwrap(),
+            quarantined_threads: IntGauge::new(
+                "quarantined_threads",
+                "Threads not built until their account changes, their simulation failed with a non-retryable error",
+            )
+            .unwrap(),
+            signatory_balance: IntGaugeVec::new(
+                Opts::new(
+                    "signatory_balance_lamports",
+                    "Signatory balance by local worker",
+                ),
+                &["worker_id"],
+            )
+            .unwrap(),
+            registry,
+        };
+
+        let collectors: Vec<Box<dyn Collector>> = vec![
+            Box::new(this.threads_observed.clone()),
+            Box::new(this.simulations.clone()),
+            Box::new(this.simulation_errors.clone()),
+            Box::new(this.simulation_duration.clone()),
+            Box::new(this.min_context_slot_not_reached.clone()),
+            Box::new(this.min_context_slot_retries.clone()),
+            Box::new(this.compute_units.clone()),
+            Box::new(this.build_queue_depth.clone()),
+            Box::new(this.build_queue_overflow.clone()),
+            Box::new(this.build_queue_last_served_fee.clone()),
+            Box::new(this.build_timeouts.clone()),
+            Box::new(this.pipeline_executable_threads.clone()),
+            Box::new(this.pipeline_build_queue_depth.clone()),
+            Box::new(this.pipeline_build_queue_overflow.clone()),
+            Box::new(this.pipeline_transactions_built.clone()),
+            Box::new(this.compute_unit_cache_hits.clone()),
+            Box::new(this.compute_unit_cache_misses.clone()),
+            Box::new(this.static_transactions.clone()),
+            Box::new(this.slot_build_duration.clone()),
+            Box::new(this.now_execution_latency.clone()),
+            Box::new(this.transactions_built.clone()),
+            Box::new(this.transactions_sent.clone()),
+            Box::new(this.transactions_confirmed.clone()),
+            Box::new(this.transactions_finalized.clone()),
+            Box::new(this.transactions_failed.clone()),
+            Box::new(this.transactions_dropped.clone()),
+            Box::new(this.transactions_resubmitted.clone()),
+            Box::new(this.transactions_landed_first_try.clone()),
+            Box::new(this.transactions_landed_after_retry.clone()),
+            Box::new(this.transactions_abandoned.clone()),
+            Box::new(this.worker_transactions_sent.clone()),
+            Box::new(this.circuit_breaker_state.clone()),
+            Box::new(this.circuit_breaker_trips.clone()),
+            Box::new(this.worker_transactions_confirmed.clone()),
+            Box::new(this.worker_transactions_failed.clone()),
+            Box::new(this.webhook_events_dropped.clone()),
+            Box::new(this.webhook_events_failed.clone()),
+            Box::new(this.snapshot_messages_published.clone()),
+            Box::new(this.snapshot_messages_skipped.clone()),
+            Box::new(this.snapshot_consumers.clone()),
+            Box::new(this.pyth_updates_filtered.clone()),
+            Box::new(this.retry_queue_depth.clone()),
+            Box::new(this.retry_queue_overflow.clone()),
+            Box::new(this.websocket_fallbacks.clone()),
+            Box::new(this.websocket_fallback_updates.clone()),
+            Box::new(this.bundle_tips_skipped.clone()),
+            Box::new(this.executable_threads.clone()),
+            Box::new(this.slot_lag.clone()),
+            Box::new(this.slot_lag_paused.clone()),
+            Box::new(this.tracked_signatures.clone()),
+            Box::new(this.quarantined_threads.clone()),
+            Box::new(this.signatory_balance.clone()),
+        ];
+        for collector in collectors {
+            this.registry.register(collector).unwrap();
+        }
+        this
+    }
+
+    pub fn record_simulation_error(&self, err: &TransactionError) {
+        self.simulation_errors
+            .with_label_values(&[&error_label(err)])
+            .inc();
+    }
+
+    /// Encodes the metrics in the Prometheus text format.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buffer = vec![];
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .unwrap();
+        buffer
+    }
+}
+
+impl Debug for Metrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "metrics")
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The error variant name, without the variable data to bound the label's cardinality.
+fn error_label(err: &TransactionError) -> String {
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            format!("InstructionError::Custom({})", code)
+        }
+        TransactionError::InstructionError(_, err) => {
+            format!("InstructionError::{}", variant_name(&format!("{:?}", err)))
+        }
+        err => variant_name(&format!("{:?}", err)).to_string(),
+    }
+}
+
+fn variant_name(debug: &str) -> &str {
+    debug
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or(debug)
+}
@@ -3,16 +3,36 @@ pub mod thread;
 
 use std::{fmt::Debug, sync::Arc};
 
+use state::{AccountTriggers, SubmittedThreads, ThreadFilter, ThreadPipelines};
 use thread::ThreadObserver;
 
-#[derive(Default)]
+use crate::{config::PluginConfig, metrics::Metrics, shutdown::Shutdown};
+
 pub struct Observers {
     pub thread: Arc<ThreadObserver>,
 }
 
 impl Observers {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(
+        config: &PluginConfig,
+        metrics: Arc<Metrics>,
+        shutdown: Arc<Shutdown>,
+        submitted_threads: Arc<SubmittedThreads>,
+        account_triggers: Arc<AccountTriggers>,
+        thread_pipelines: Arc<ThreadPipelines>,
+        thread_filter: Arc<ThreadFilter>,
+    ) -> Self {
+        Self {
+            thread: Arc::new(ThreadObserver::new(
+                config,
+                metrics,
+                shutdown,
+                submitted_threads,
+                account_triggers,
+                thread_pipelines,
+                thread_filter,
+            )),
+        }
     }
 }
 
@@ -1,11 +1,27 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::{Hash, Hasher},
     ops::Deref,
 };
 
-use solana_sdk::pubkey::Pubkey;
+use log::info;
+use solana_sdk::{pubkey, pubkey::Pubkey};
 use tokio::sync::RwLock;
 
+use crate::config::TokenBalanceTrigger;
+
+static TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+static TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EnJ4mYsE2NTj6xELsNQm");
+
+/// The byte offset and size of the amount of an SPL token account.
+pub static TOKEN_AMOUNT_OFFSET: u64 = 64;
+pub static TOKEN_AMOUNT_SIZE: u64 = 8;
+
+/// The byte size of an SPL token account, followed by the account type and the extensions for Token-2022.
+static TOKEN_ACCOUNT_LEN: usize = 165;
+static TOKEN_ACCOUNT_STATE_OFFSET: usize = 108;
+static TOKEN_2022_ACCOUNT_TYPE: u8 = 2;
+
 #[derive(Default)]
 pub struct AccountThreads(RwLock<HashMap<Pubkey, HashSet<Pubkey>>>);
 
@@ -35,21 +51,319 @@ impl Deref for AccountThreads {
     }
 }
 
+/// The data range watched by an account trigger, and the hash of the data it was last triggered with.
+#[derive(Debug)]
+pub struct AccountTrigger {
+    pub offset: u64,
+    pub size: u64,
+    pub data_hash: Option<u64>,
+    /// The watched data the thread was last triggered with, published to the snapshot stream.
+    pub observed_data: Option<Vec<u8>>,
+    /// Set for the threads watching the amount of a token account with a threshold.
+    pub balance: Option<BalanceCrossing>,
+}
+
+/// The threshold of a thread watching the amount of a token account, and the amount last observed.
+#[derive(Debug)]
+pub struct BalanceCrossing {
+    pub rule: TokenBalanceTrigger,
+    pub last_amount: Option<u64>,
+}
+
+impl BalanceCrossing {
+    /// Returns the crossing of the thread if its trigger watches the amount of a token account and it has a threshold.
+    pub fn new(offset: u64, size: u64, rule: Option<&TokenBalanceTrigger>) -> Option<Self> {
+        if offset != TOKEN_AMOUNT_OFFSET || size != TOKEN_AMOUNT_SIZE {
+            return None;
+        }
+        rule.map(|rule| Self {
+            rule: rule.clone(),
+            last_amount: None,
+        })
+    }
+
+    /// Records the amount and returns true if it crossed the threshold since the last one.
+    /// The first amount observed crosses it if it is past the threshold.
+    fn observe(&mut self, amount: u64) -> bool {
+        let TokenBalanceTrigger {
+            threshold,
+            direction,
+        } = self.rule;
+        let was_met = self.last_amount.map_or(false, |last_amount| {
+            direction.is_met(last_amount, threshold)
+        });
+        self.last_amount = Some(amount);
+        direction.is_met(amount, threshold) && !was_met
+    }
+}
+
+/// Parses the amount of an SPL token account, none if the account is not an initialized token account.
+fn token_amount(owner: &Pubkey, data: &[u8]) -> Option<u64> {
+    if *owner != TOKEN_PROGRAM_ID && *owner != TOKEN_2022_PROGRAM_ID {
+        return None;
+    }
+    let is_token_account = match data.len().cmp(&TOKEN_ACCOUNT_LEN) {
+        std::cmp::Ordering::Less => false,
+        std::cmp::Ordering::Equal => true,
+        std::cmp::Ordering::Greater => {
+            *owner == TOKEN_2022_PROGRAM_ID && data[TOKEN_ACCOUNT_LEN] == TOKEN_2022_ACCOUNT_TYPE
+        }
+    };
+    if !is_token_account || data[TOKEN_ACCOUNT_STATE_OFFSET] == 0 {
+        return None;
+    }
+    let offset = TOKEN_AMOUNT_OFFSET as usize;
+    data[offset..offset + TOKEN_AMOUNT_SIZE as usize]
+        .try_into()
+        .ok()
+        .map(u64::from_le_bytes)
+}
+
+/// The data ranges watched by the threads with an account trigger.
+/// Map from account pubkeys to the threads watching them.
+/// A std lock is used since the ranges are hashed synchronously, while the validator still holds the account data.
 #[derive(Default)]
-pub struct UpdatedAccounts(RwLock<HashSet<Pubkey>>);
+pub struct AccountTriggers(std::sync::RwLock<HashMap<Pubkey, HashMap<Pubkey, AccountTrigger>>>);
 
-impl UpdatedAccounts {
-    pub async fn add(&self, account: Pubkey) {
-        let mut w_state = self.0.write().await;
+impl AccountTriggers {
+    pub fn add(&self, address: Pubkey, thread_pubkey: Pubkey, trigger: AccountTrigger) {
+        let mut w_state = self.0.write().unwrap();
+        w_state
+            .entry(address)
+            .or_default()
+            .insert(thread_pubkey, trigger);
+    }
 
-        w_state.insert(account);
+    pub fn remove(&self, thread_pubkey: &Pubkey) {
+        let mut w_state = self.0.write().unwrap();
+        w_state.retain(|_, triggers| {
+            triggers.remove(thread_pubkey);
+            !triggers.is_empty()
+        });
+    }
+
+    pub fn contains(&self, address: &Pubkey, thread_pubkey: &Pubkey) -> bool {
+        let r_state = self.0.read().unwrap();
+        r_state
+            .get(address)
+            .map(|triggers| triggers.contains_key(thread_pubkey))
+            .unwrap_or(false)
+    }
+
+    /// Returns the watched data the thread was last triggered with, if an update triggered it since it was indexed.
+    pub fn observed_data(&self, address: &Pubkey, thread_pubkey: &Pubkey) -> Option<Vec<u8>> {
+        let r_state = self.0.read().unwrap();
+        r_state
+            .get(address)
+            .and_then(|triggers| triggers.get(thread_pubkey))
+            .and_then(|trigger| trigger.observed_data.clone())
+    }
+
+    /// Returns the accounts watched by at least one thread.
+    pub fn addresses(&self) -> Vec<Pubkey> {
+        let r_state = self.0.read().unwrap();
+        r_state.keys().copied().collect()
+    }
+
+    /// Hashes the data range watched by each thread and returns the threads whose range changed.
+    /// The threads watching the amount of a token account with a threshold are only returned once it crosses it,
+    /// those watching another account or malformed data fall back to the range changes.
+    /// A closed account does not trigger any thread.
+    pub fn observe(
+        &self,
+        address: &Pubkey,
+        owner: &Pubkey,
+        lamports: u64,
+        data: &[u8],
+    ) -> HashSet<Pubkey> {
+        let mut triggered_threads = HashSet::new();
+        if lamports == 0 {
+            return triggered_threads;
+        }
+        let mut w_state = self.0.write().unwrap();
+        let Some(triggers) = w_state.get_mut(address) else {
+            return triggered_threads;
+        };
+        for (thread_pubkey, trigger) in triggers.iter_mut() {
+            let range = watched_range(data, trigger.offset, trigger.size);
+            let data_hash = hash_data(range);
+            if trigger.offset.saturating_add(trigger.size) > data.len() as u64 {
+                info!(
+                    "Clamped data range of thread: {} offset: {} size: {} to account: {} len: {}",
+                    thread_pubkey,
+                    trigger.offset,
+                    trigger.size,
+                    address,
+                    data.len()
+                );
+            }
+            let changed = trigger.data_hash != Some(data_hash);
+            if changed {
+                trigger.data_hash = Some(data_hash);
+                trigger.observed_data = Some(range.to_vec());
+            }
+            let crossed = match trigger.balance.as_mut() {
+                Some(balance) => token_amount(owner, data).map(|amount| balance.observe(amount)),
+                None => None,
+            };
+            if crossed.unwrap_or(changed) {
+                triggered_threads.insert(*thread_pubkey);
+            }
+        }
+        triggered_threads
     }
 }
 
-impl Deref for UpdatedAccounts {
-    type Target = RwLock<HashSet<Pubkey>>;
+/// Hashes the data at [offset, offset + size) the way the thread program does on kickoff,
+/// the range is clamped to the data length.
+pub fn hash_range(data: &[u8], offset: u64, size: u64) -> u64 {
+    hash_data(watched_range(data, offset, size))
+}
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+fn watched_range(data: &[u8], offset: u64, size: u64) -> &[u8] {
+    let start = (offset as usize).min(data.len());
+    let end = start.saturating_add(size as usize).min(data.len());
+    &data[start..end]
+}
+
+fn hash_data(range: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    range.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triggers(address: Pubkey, thread_pubkey: Pubkey, data_hash: Option<u64>) -> AccountTriggers {
+        let triggers = AccountTriggers::default();
+        triggers.add(
+            address,
+            thread_pubkey,
+            AccountTrigger {
+                offset: 8,
+                size: 8,
+                data_hash,
+                observed_data: None,
+                balance: None,
+            },
+        );
+        triggers
+    }
+
+    #[test]
+    fn unchanged_range_does_not_trigger() {
+        let (address, thread_pubkey) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let triggers = triggers(address, thread_pubkey, None);
+        let mut data = vec![0; 24];
+        assert_eq!(
+            triggers.observe(&address, &Pubkey::default(), 1, &data),
+            HashSet::from([thread_pubkey])
+        );
+
+        // Bytes outside of the range changed.
+        data[0] = 1;
+        data[20] = 1;
+        assert!(triggers
+            .observe(&address, &Pubkey::default(), 1, &data)
+            .is_empty());
+
+        data[10] = 1;
+        assert_eq!(
+            triggers.observe(&address, &Pubkey::default(), 1, &data),
+            HashSet::from([thread_pubkey])
+        );
+        assert_eq!(
+            triggers.observed_data(&address, &thread_pubkey),
+            Some(data[8..16].to_vec())
+        );
+    }
+
+    #[test]
+    fn range_hash_of_the_last_execution_does_not_trigger() {
+        let (address, thread_pubkey) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let data = vec![7; 24];
+        let triggers = triggers(address, thread_pubkey, Some(hash_range(&data, 8, 8)));
+        assert!(triggers
+            .observe(&address, &Pubkey::default(), 1, &data)
+            .is_empty());
+    }
+
+    #[test]
+    fn closed_account_does_not_trigger() {
+        let (address, thread_pubkey) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let triggers = triggers(address, thread_pubkey, None);
+        assert!(triggers
+            .observe(&address, &Pubkey::default(), 0, &[])
+            .is_empty());
+    }
+
+    fn token_account(owner: Pubkey, amount: u64) -> (Pubkey, Vec<u8>) {
+        let mut data = vec![0; TOKEN_ACCOUNT_LEN];
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        data[TOKEN_ACCOUNT_STATE_OFFSET] = 1;
+        (owner, data)
+    }
+
+    #[test]
+    fn token_balance_triggers_on_threshold_crossings() {
+        let (address, thread_pubkey) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let triggers = AccountTriggers::default();
+        let rule = TokenBalanceTrigger {
+            threshold: 100,
+            direction: crate::config::BalanceDirection::Below,
+        };
+        triggers.add(
+            address,
+            thread_pubkey,
+            AccountTrigger {
+                offset: TOKEN_AMOUNT_OFFSET,
+                size: TOKEN_AMOUNT_SIZE,
+                data_hash: None,
+                observed_data: None,
+                balance: BalanceCrossing::new(TOKEN_AMOUNT_OFFSET, TOKEN_AMOUNT_SIZE, Some(&rule)),
+            },
+        );
+        let observe = |owner: Pubkey, amount: u64| {
+            let (owner, data) = token_account(owner, amount);
+            !triggers.observe(&address, &owner, 1, &data).is_empty()
+        };
+
+        // Balance changes above the threshold, then the crossing, then changes past it.
+        assert!(!observe(TOKEN_PROGRAM_ID, 500));
+        assert!(!observe(TOKEN_PROGRAM_ID, 400));
+        assert!(observe(TOKEN_PROGRAM_ID, 100));
+        assert!(!observe(TOKEN_PROGRAM_ID, 50));
+        assert!(!observe(TOKEN_PROGRAM_ID, 150));
+        assert!(observe(TOKEN_2022_PROGRAM_ID, 0));
+
+        // An account which is not a token account falls back to the range changes.
+        assert!(observe(Pubkey::new_unique(), 10));
+        assert!(observe(Pubkey::new_unique(), 20));
+        let mut malformed = token_account(TOKEN_PROGRAM_ID, 30).1;
+        malformed.truncate(TOKEN_ACCOUNT_LEN - 1);
+        assert!(!triggers
+            .observe(&address, &TOKEN_PROGRAM_ID, 1, &malformed)
+            .is_empty());
+    }
+
+    #[test]
+    fn balance_crossing_requires_the_amount_range() {
+        let rule = TokenBalanceTrigger {
+            threshold: 1,
+            direction: crate::config::BalanceDirection::Above,
+        };
+        assert!(
+            BalanceCrossing::new(TOKEN_AMOUNT_OFFSET, TOKEN_AMOUNT_SIZE, Some(&rule)).is_some()
+        );
+        assert!(BalanceCrossing::new(0, TOKEN_AMOUNT_SIZE, Some(&rule)).is_none());
+        assert!(BalanceCrossing::new(TOKEN_AMOUNT_OFFSET, TOKEN_AMOUNT_SIZE, None).is_none());
+    }
+
+    #[test]
+    fn range_is_clamped_to_the_data() {
+        assert_eq!(hash_range(&[1, 2, 3], 1, 8), hash_range(&[1, 2, 3], 1, 2));
+        assert_eq!(hash_range(&[1, 2, 3], 8, 8), hash_range(&[], 0, 0));
     }
 }
@@ -0,0 +1,50 @@
+use std::{collections::HashMap, ops::Deref, time::Instant};
+
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::RwLock;
+
+/// The threads with a now trigger awaiting their kickoff, with the instant they were first observed.
+/// The same update can be delivered several times, a thread is only enqueued once until its state changes.
+#[derive(Default)]
+pub struct ImmediateThreads(RwLock<HashMap<Pubkey, Instant>>);
+
+impl ImmediateThreads {
+    /// Returns false if the thread was already observed awaiting its kickoff.
+    pub async fn add(&self, thread_key: Pubkey) -> bool {
+        let mut w_state = self.0.write().await;
+        if w_state.contains_key(&thread_key) {
+            return false;
+        }
+        w_state.insert(thread_key, Instant::now());
+        true
+    }
+
+    pub async fn remove(&self, thread_key: &Pubkey) {
+        let mut w_state = self.0.write().await;
+        w_state.remove(thread_key);
+    }
+}
+
+impl Deref for ImmediateThreads {
+    type Target = RwLock<HashMap<Pubkey, Instant>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn redelivered_threads_are_added_once() {
+        let immediate_threads = ImmediateThreads::default();
+        let thread_key = Pubkey::new_unique();
+        assert!(immediate_threads.add(thread_key).await);
+        assert!(!immediate_threads.add(thread_key).await);
+
+        immediate_threads.remove(&thread_key).await;
+        assert!(immediate_threads.add(thread_key).await);
+    }
+}
@@ -0,0 +1,30 @@
+use std::collections::HashSet;
+
+use solana_sdk::pubkey::Pubkey;
+
+/// The threads indexed by the observer, to recognize the updates closing them.
+/// A std lock is used since every account update is checked synchronously.
+#[derive(Default)]
+pub struct IndexedThreads(std::sync::RwLock<HashSet<Pubkey>>);
+
+impl IndexedThreads {
+    pub fn add(&self, thread_key: Pubkey) {
+        let mut w_state = self.0.write().unwrap();
+        w_state.insert(thread_key);
+    }
+
+    pub fn remove(&self, thread_key: &Pubkey) {
+        let mut w_state = self.0.write().unwrap();
+        w_state.remove(thread_key);
+    }
+
+    pub fn contains(&self, thread_key: &Pubkey) -> bool {
+        let r_state = self.0.read().unwrap();
+        r_state.contains(thread_key)
+    }
+
+    pub fn keys(&self) -> Vec<Pubkey> {
+        let r_state = self.0.read().unwrap();
+        r_state.iter().copied().collect()
+    }
+}
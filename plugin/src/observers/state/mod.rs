@@ -2,14 +2,28 @@ mod account;
 mod clock;
 mod cron;
 mod epoch;
+mod immediate;
+mod indexed;
 mod now;
+mod pipelines;
 mod pyth;
+mod schedule;
 mod slot;
+mod submitted;
+mod thread_filter;
+mod triggers;
 
 pub use account::*;
 pub use clock::*;
 pub use cron::*;
 pub use epoch::*;
+pub use immediate::*;
+pub use indexed::*;
 pub use now::*;
+pub use pipelines::*;
 pub use pyth::*;
+pub use schedule::*;
 pub use slot::*;
+pub use submitted::*;
+pub use thread_filter::*;
+pub use triggers::*;
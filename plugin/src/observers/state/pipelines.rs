@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use sablier_thread_program::state::Trigger;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::config::Pipeline;
+
+/// Returns the pipeline the exec transactions of a thread with the trigger are built in.
+pub fn trigger_pipeline(trigger: &Trigger) -> Pipeline {
+    match trigger {
+        Trigger::Cron { .. }
+        | Trigger::Timestamp { .. }
+        | Trigger::Periodic { .. }
+        | Trigger::Interval { .. } => Pipeline::Cron,
+        Trigger::Account { .. } => Pipeline::Account,
+        Trigger::Pyth { .. } => Pipeline::Oracle,
+        Trigger::Slot { .. } | Trigger::Epoch { .. } => Pipeline::Scheduled,
+        Trigger::Now => Pipeline::Immediate,
+    }
+}
+
+/// Returns true if the trigger fires once, rather than on a schedule or on each update of what it watches.
+pub fn is_one_shot(trigger: &Trigger) -> bool {
+    matches!(
+        trigger,
+        Trigger::Now | Trigger::Timestamp { .. } | Trigger::Slot { .. } | Trigger::Epoch { .. }
+    )
+}
+
+/// The pipeline of each indexed thread, and whether its trigger is one-shot, set by the observer and read by the executor.
+/// A thread keeps its trigger's pipeline while it executes its instructions.
+#[derive(Default)]
+pub struct ThreadPipelines(std::sync::RwLock<HashMap<Pubkey, (Pipeline, bool)>>);
+
+impl ThreadPipelines {
+    pub fn set(&self, thread_pubkey: Pubkey, trigger: &Trigger) {
+        let mut w_state = self.0.write().unwrap();
+        w_state.insert(
+            thread_pubkey,
+            (trigger_pipeline(trigger), is_one_shot(trigger)),
+        );
+    }
+
+    pub fn remove(&self, thread_pubkey: &Pubkey) {
+        let mut w_state = self.0.write().unwrap();
+        w_state.remove(thread_pubkey);
+    }
+
+    /// Returns the pipeline of the thread, threads not indexed yet are built in the immediate pipeline.
+    pub fn get(&self, thread_pubkey: &Pubkey) -> Pipeline {
+        let r_state = self.0.read().unwrap();
+        r_state
+            .get(thread_pubkey)
+            .map(|(pipeline, _one_shot)| *pipeline)
+            .unwrap_or(Pipeline::Immediate)
+    }
+
+    /// Returns true if the trigger of the thread is one-shot, threads not indexed yet are taken for recurring ones.
+    pub fn is_one_shot(&self, thread_pubkey: &Pubkey) -> bool {
+        let r_state = self.0.read().unwrap();
+        r_state
+            .get(thread_pubkey)
+            .map_or(false, |(_pipeline, one_shot)| *one_shot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sablier_thread_program::state::Equality;
+
+    use super::*;
+
+    #[test]
+    fn threads_are_routed_by_trigger() {
+        let pipelines = ThreadPipelines::default();
+        let cron_thread = Pubkey::new_unique();
+        let pyth_thread = Pubkey::new_unique();
+        let timestamp_thread = Pubkey::new_unique();
+        pipelines.set(
+            cron_thread,
+            &Trigger::Interval {
+                interval_seconds: 60,
+                skippable: true,
+            },
+        );
+        pipelines.set(
+            pyth_thread,
+            &Trigger::Pyth {
+                feed_id: [0; 32],
+                equality: Equality::GreaterThanOrEqual,
+                limit: 0,
+            },
+        );
+        pipelines.set(timestamp_thread, &Trigger::Timestamp { unix_ts: 0 });
+        assert_eq!(pipelines.get(&cron_thread), Pipeline::Cron);
+        assert_eq!(pipelines.get(&pyth_thread), Pipeline::Oracle);
+        assert_eq!(pipelines.get(&timestamp_thread), Pipeline::Cron);
+        assert!(pipelines.is_one_shot(&timestamp_thread));
+        assert!(!pipelines.is_one_shot(&cron_thread));
+
+        pipelines.remove(&cron_thread);
+        assert_eq!(pipelines.get(&cron_thread), Pipeline::Immediate);
+    }
+}
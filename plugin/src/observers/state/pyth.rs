@@ -14,12 +14,33 @@ pub struct PythThread {
     pub limit: i64,
 }
 
+impl PythThread {
+    /// Returns true if the price satisfies the trigger. A price which did not satisfy it yet must be past the limit
+    /// by the hysteresis band, so a price flapping around the limit does not enqueue the thread on every update.
+    /// The on-chain check at kickoff remains authoritative.
+    pub fn is_satisfied(&self, price: i64, hysteresis_bps: u64, was_satisfied: bool) -> bool {
+        let band = if was_satisfied {
+            0
+        } else {
+            (self.limit as i128).abs() * hysteresis_bps as i128 / 10_000
+        };
+        match self.equality {
+            Equality::GreaterThanOrEqual => price as i128 >= self.limit as i128 + band,
+            Equality::LessThanOrEqual => price as i128 <= self.limit as i128 - band,
+        }
+    }
+}
+
 #[derive(Default)]
-pub struct PythThreads(RwLock<HashMap<Pubkey, HashSet<PythThread>>>);
+pub struct PythThreads {
+    threads: RwLock<HashMap<Pubkey, HashSet<PythThread>>>,
+    // The threads satisfied by the last price update of their feed.
+    satisfied: std::sync::Mutex<HashSet<Pubkey>>,
+}
 
 impl PythThreads {
     pub async fn add(&self, price_key: Pubkey, data: PythThread) {
-        let mut w_state = self.0.write().await;
+        let mut w_state = self.threads.write().await;
 
         w_state
             .entry(price_key)
@@ -28,12 +49,78 @@ impl PythThreads {
             })
             .or_insert(HashSet::from([data]));
     }
+
+    /// Evaluates the trigger of the thread against the price, a stale price satisfies none.
+    pub fn evaluate(
+        &self,
+        pyth_thread: &PythThread,
+        price: Option<i64>,
+        hysteresis_bps: u64,
+    ) -> bool {
+        let mut satisfied = self.satisfied.lock().unwrap();
+        let was_satisfied = satisfied.contains(&pyth_thread.thread_pubkey);
+        let is_satisfied = price.map_or(false, |price| {
+            pyth_thread.is_satisfied(price, hysteresis_bps, was_satisfied)
+        });
+        if is_satisfied {
+            satisfied.insert(pyth_thread.thread_pubkey);
+        } else {
+            satisfied.remove(&pyth_thread.thread_pubkey);
+        }
+        is_satisfied
+    }
+
+    pub fn forget(&self, thread_pubkey: &Pubkey) {
+        self.satisfied.lock().unwrap().remove(thread_pubkey);
+    }
 }
 
 impl Deref for PythThreads {
     type Target = RwLock<HashMap<Pubkey, HashSet<PythThread>>>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.threads
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn price_must_cross_the_band_until_satisfied() {
+        let pyth_threads = PythThreads::default();
+        let pyth_thread = PythThread {
+            thread_pubkey: Pubkey::new_unique(),
+            equality: Equality::GreaterThanOrEqual,
+            limit: 10_000,
+        };
+        // A 10 bps band above the limit of 10_000.
+        assert!(!pyth_threads.evaluate(&pyth_thread, Some(10_005), 10));
+        assert!(pyth_threads.evaluate(&pyth_thread, Some(10_010), 10));
+
+        // Once satisfied, the price only needs to stay at the limit.
+        assert!(pyth_threads.evaluate(&pyth_thread, Some(10_000), 10));
+        assert!(!pyth_threads.evaluate(&pyth_thread, Some(9_999), 10));
+        assert!(!pyth_threads.evaluate(&pyth_thread, Some(10_000), 10));
+
+        // A stale price resets the trigger.
+        assert!(pyth_threads.evaluate(&pyth_thread, Some(10_010), 10));
+        assert!(!pyth_threads.evaluate(&pyth_thread, None, 10));
+        assert!(!pyth_threads.evaluate(&pyth_thread, Some(10_000), 10));
+    }
+
+    #[test]
+    fn band_is_below_the_limit_of_lte_triggers() {
+        let pyth_thread = PythThread {
+            thread_pubkey: Pubkey::new_unique(),
+            equality: Equality::LessThanOrEqual,
+            limit: -10_000,
+        };
+        assert!(!pyth_thread.is_satisfied(-10_000, 10, false));
+        assert!(pyth_thread.is_satisfied(-10_010, 10, false));
+        assert!(pyth_thread.is_satisfied(-10_000, 10, true));
+        // A band wider than the price range does not overflow.
+        assert!(!pyth_thread.is_satisfied(i64::MIN, u64::MAX, false));
     }
 }
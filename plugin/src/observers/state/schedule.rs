@@ -0,0 +1,223 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    hash::Hash,
+};
+
+use solana_sdk::pubkey::Pubkey;
+
+/// Min number of stale entries before the heap is rebuilt from the live targets.
+static COMPACTION_THRESHOLD: usize = 1_024;
+
+/// The threads waiting for a target (timestamp, slot or epoch), ordered by target so that only the due ones are visited.
+/// Rescheduled and removed threads leave a stale entry in the heap, which is dropped when it is popped.
+pub struct Schedule<T> {
+    heap: BinaryHeap<Reverse<(T, Pubkey)>>,
+    targets: HashMap<Pubkey, T>,
+}
+
+impl<T: Ord> Default for Schedule<T> {
+    fn default() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            targets: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Copy + Ord + Hash> Schedule<T> {
+    /// Schedules the thread at the target, replacing its previous one.
+    pub fn insert(&mut self, target: T, thread_pubkey: Pubkey) {
+        if self.targets.insert(thread_pubkey, target) == Some(target) {
+            return;
+        }
+        self.heap.push(Reverse((target, thread_pubkey)));
+        self.compact();
+    }
+
+    pub fn remove(&mut self, thread_pubkey: &Pubkey) {
+        self.targets.remove(thread_pubkey);
+    }
+
+    /// Removes and returns the threads whose target is reached.
+    /// Targets are compared with `>=`, so a target which is never produced (e.g. a skipped slot) is still reached.
+    pub fn pop_due(&mut self, current: T) -> Vec<Pubkey> {
+        let mut due_threads = vec![];
+        while let Some(Reverse((target, thread_pubkey))) = self.heap.peek().copied() {
+            if target > current {
+                break;
+            }
+            self.heap.pop();
+            if self.targets.get(&thread_pubkey) == Some(&target) {
+                self.targets.remove(&thread_pubkey);
+                due_threads.push(thread_pubkey);
+            }
+        }
+        due_threads
+    }
+
+    /// Returns the target the thread is scheduled at.
+    pub fn target(&self, thread_pubkey: &Pubkey) -> Option<T> {
+        self.targets.get(thread_pubkey).copied()
+    }
+
+    pub fn threads(&self) -> impl Iterator<Item = &Pubkey> {
+        self.targets.keys()
+    }
+
+    /// Returns the scheduled threads grouped by target.
+    pub fn by_target(&self) -> HashMap<T, HashSet<Pubkey>> {
+        let mut targets: HashMap<T, HashSet<Pubkey>> = HashMap::new();
+        for (thread_pubkey, target) in &self.targets {
+            targets.entry(*target).or_default().insert(*thread_pubkey);
+        }
+        targets
+    }
+
+    pub fn len(&self) -> usize {
+        self.targets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    /// Rebuilds the heap once the stale entries outnumber the live ones.
+    fn compact(&mut self) {
+        if self.heap.len() < COMPACTION_THRESHOLD.max(self.targets.len() * 2) {
+            return;
+        }
+        self.heap = self
+            .targets
+            .iter()
+            .map(|(thread_pubkey, target)| Reverse((*target, *thread_pubkey)))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    #[test]
+    fn only_due_threads_are_popped() {
+        let mut schedule = Schedule::default();
+        let (a, b, c) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        schedule.insert(10, a);
+        schedule.insert(20, b);
+        schedule.insert(30, c);
+
+        assert!(schedule.pop_due(9).is_empty());
+        assert_eq!(schedule.pop_due(20), vec![a, b]);
+        assert_eq!(schedule.len(), 1);
+        assert_eq!(schedule.pop_due(100), vec![c]);
+        assert!(schedule.is_empty());
+    }
+
+    #[test]
+    fn skipped_targets_are_reached() {
+        let mut schedule = Schedule::default();
+        let thread_pubkey = Pubkey::new_unique();
+        schedule.insert(100u64, thread_pubkey);
+
+        // Slot 100 was skipped by the leader.
+        assert!(schedule.pop_due(99).is_empty());
+        assert_eq!(schedule.pop_due(101), vec![thread_pubkey]);
+    }
+
+    #[test]
+    fn rescheduled_threads_are_rekeyed() {
+        let mut schedule = Schedule::default();
+        let thread_pubkey = Pubkey::new_unique();
+        schedule.insert(10, thread_pubkey);
+        schedule.insert(50, thread_pubkey);
+
+        assert!(schedule.pop_due(20).is_empty());
+        assert_eq!(schedule.pop_due(50), vec![thread_pubkey]);
+        assert!(schedule.pop_due(100).is_empty());
+    }
+
+    #[test]
+    fn removed_threads_are_dropped_when_popped() {
+        let mut schedule = Schedule::default();
+        let thread_pubkey = Pubkey::new_unique();
+        schedule.insert(10, thread_pubkey);
+        schedule.remove(&thread_pubkey);
+
+        assert!(schedule.pop_due(20).is_empty());
+        assert!(schedule.heap.is_empty());
+    }
+
+    #[test]
+    fn stale_entries_are_compacted() {
+        let mut schedule = Schedule::default();
+        let thread_pubkey = Pubkey::new_unique();
+        for timestamp in 0..(COMPACTION_THRESHOLD as i64 * 4) {
+            schedule.insert(timestamp, thread_pubkey);
+        }
+        assert!(schedule.heap.len() <= COMPACTION_THRESHOLD);
+        assert_eq!(
+            schedule.by_target(),
+            HashMap::from([(
+                COMPACTION_THRESHOLD as i64 * 4 - 1,
+                HashSet::from([thread_pubkey])
+            )])
+        );
+    }
+
+    /// Compares the previous scan of every moment on each clock update with the heap.
+    /// Run with `cargo test -p sablier_plugin --release -- --ignored --nocapture schedule_benchmark`.
+    #[test]
+    #[ignore]
+    fn schedule_benchmark() {
+        const TICKS: i64 = 1_000;
+        for count in [10_000, 100_000] {
+            let threads: Vec<(i64, Pubkey)> = (0..count)
+                .map(|i| ((i as i64 * 7_919) % 86_400, Pubkey::new_unique()))
+                .collect();
+
+            let mut moments: HashMap<i64, HashSet<Pubkey>> = HashMap::new();
+            for (timestamp, thread_pubkey) in &threads {
+                moments
+                    .entry(*timestamp)
+                    .or_default()
+                    .insert(*thread_pubkey);
+            }
+            let now = Instant::now();
+            let mut scan_due = 0;
+            for unix_timestamp in 0..TICKS {
+                moments.retain(|timestamp, thread_pubkeys| {
+                    let is_due = unix_timestamp >= *timestamp;
+                    if is_due {
+                        scan_due += thread_pubkeys.drain().count();
+                    }
+                    !is_due
+                });
+            }
+            let scan_elapsed = now.elapsed();
+
+            let mut schedule = Schedule::default();
+            for (timestamp, thread_pubkey) in &threads {
+                schedule.insert(*timestamp, *thread_pubkey);
+            }
+            let now = Instant::now();
+            let mut heap_due = 0;
+            for unix_timestamp in 0..TICKS {
+                heap_due += schedule.pop_due(unix_timestamp).len();
+            }
+            let heap_elapsed = now.elapsed();
+
+            assert_eq!(scan_due, heap_due);
+            println!(
+                "threads: {} ticks: {} scan: {:?} heap: {:?}",
+                count, TICKS, scan_elapsed, heap_elapsed
+            );
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::{HashMap, HashSet};
+
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::RwLock;
+
+/// The threads with an exec transaction in flight, by the slot it was sent at.
+/// Shared by the observer and the executor, so a thread marked executable again by another trigger
+/// is not built twice before its execution completes, or the window of slots elapses.
+#[derive(Default)]
+pub struct SubmittedThreads {
+    window: u64,
+    slots: RwLock<HashMap<Pubkey, u64>>,
+}
+
+impl SubmittedThreads {
+    pub fn new(window: u64) -> Self {
+        Self {
+            window,
+            slots: RwLock::default(),
+        }
+    }
+
+    pub async fn add(&self, thread_pubkey: Pubkey, slot: u64) {
+        let mut w_slots = self.slots.write().await;
+        w_slots.insert(thread_pubkey, slot);
+    }
+
+    pub async fn remove(&self, thread_pubkey: &Pubkey) {
+        let mut w_slots = self.slots.write().await;
+        w_slots.remove(thread_pubkey);
+    }
+
+    /// Clears the thread once its exec context shows it executed since the transaction was sent.
+    pub async fn complete(&self, thread_pubkey: &Pubkey, last_exec_at: u64) {
+        let mut w_slots = self.slots.write().await;
+        if w_slots
+            .get(thread_pubkey)
+            .is_some_and(|sent_slot| last_exec_at >= *sent_slot)
+        {
+            w_slots.remove(thread_pubkey);
+        }
+    }
+
+    /// Returns the threads with a transaction in flight, dropping the ones sent before the window.
+    pub async fn pending(&self, slot: u64) -> HashSet<Pubkey> {
+        let mut w_slots = self.slots.write().await;
+        w_slots.retain(|_, sent_slot| slot <= sent_slot.saturating_add(self.window));
+        w_slots.keys().copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn threads_are_pending_until_executed_or_expired() {
+        let submitted_threads = SubmittedThreads::new(10);
+        let (executed, expired) = (Pubkey::new_unique(), Pubkey::new_unique());
+        submitted_threads.add(executed, 5).await;
+        submitted_threads.add(expired, 5).await;
+        assert_eq!(
+            submitted_threads.pending(6).await,
+            HashSet::from([executed, expired])
+        );
+
+        // An exec context older than the transaction doesn't complete it.
+        submitted_threads.complete(&executed, 4).await;
+        assert!(submitted_threads.pending(6).await.contains(&executed));
+        submitted_threads.complete(&executed, 6).await;
+        assert_eq!(
+            submitted_threads.pending(15).await,
+            HashSet::from([expired])
+        );
+        assert!(submitted_threads.pending(16).await.is_empty());
+    }
+}
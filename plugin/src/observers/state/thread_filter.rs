@@ -0,0 +1,63 @@
+use std::{collections::HashSet, str::FromStr};
+
+use log::info;
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::RwLock;
+
+use crate::config::ThreadFilterConfig;
+
+#[derive(Default)]
+struct Lists {
+    allowlist: HashSet<Pubkey>,
+    denylist: HashSet<Pubkey>,
+}
+
+/// The thread and authority pubkeys this worker is allowed to execute.
+#[derive(Default)]
+pub struct ThreadFilter(RwLock<Lists>);
+
+impl ThreadFilter {
+    pub fn new(config: &ThreadFilterConfig) -> Self {
+        Self(RwLock::new(Lists::from(config)))
+    }
+
+    pub async fn update(&self, config: &ThreadFilterConfig) {
+        let mut w_state = self.0.write().await;
+        *w_state = Lists::from(config);
+    }
+
+    /// Returns false if the thread or its authority is denied, or if an allowlist is set and matches neither.
+    pub async fn is_allowed(&self, thread_pubkey: &Pubkey, authority: &Pubkey) -> bool {
+        let r_state = self.0.read().await;
+        if r_state.denylist.contains(thread_pubkey) || r_state.denylist.contains(authority) {
+            return false;
+        }
+        r_state.allowlist.is_empty()
+            || r_state.allowlist.contains(thread_pubkey)
+            || r_state.allowlist.contains(authority)
+    }
+}
+
+impl From<&ThreadFilterConfig> for Lists {
+    fn from(config: &ThreadFilterConfig) -> Self {
+        Self {
+            allowlist: parse_pubkeys(&config.allowlist),
+            denylist: parse_pubkeys(&config.denylist),
+        }
+    }
+}
+
+fn parse_pubkeys(keys: &[String]) -> HashSet<Pubkey> {
+    keys.iter()
+        .filter_map(|key| match Pubkey::from_str(key) {
+            Ok(pubkey) => Some(pubkey),
+            Err(err) => {
+                info!(
+                    "Ignoring invalid thread filter pubkey: {} err: {:?}",
+                    key, err
+                );
+                None
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,26 @@
+use std::collections::HashMap;
+
+use sablier_thread_program::state::Trigger;
+use solana_sdk::pubkey::Pubkey;
+
+/// The trigger of each indexed thread, published with the thread once it is executable.
+#[derive(Default)]
+pub struct ThreadTriggers(std::sync::RwLock<HashMap<Pubkey, Trigger>>);
+
+impl ThreadTriggers {
+    pub fn set(&self, thread_pubkey: Pubkey, trigger: Trigger) {
+        let mut w_state = self.0.write().unwrap();
+        w_state.insert(thread_pubkey, trigger);
+    }
+
+    pub fn remove(&self, thread_pubkey: &Pubkey) {
+        let mut w_state = self.0.write().unwrap();
+        w_state.remove(thread_pubkey);
+    }
+
+    /// Returns the trigger of the thread, unknown for the threads restored from the persisted state until observed again.
+    pub fn get(&self, thread_pubkey: &Pubkey) -> Option<Trigger> {
+        let r_state = self.0.read().unwrap();
+        r_state.get(thread_pubkey).cloned()
+    }
+}
@@ -1,22 +1,31 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fmt::Debug,
     str::FromStr,
-    sync::{atomic::AtomicU64, Arc},
+    sync::{
+        atomic::{AtomicI64, AtomicU64, Ordering},
+        Arc,
+    },
 };
 
-use chrono::DateTime;
 use log::info;
-use sablier_cron::Schedule;
-use sablier_thread_program::state::{Equality, Trigger, TriggerContext, VersionedThread};
-use sablier_utils::pyth::{get_oracle_key, PriceFeedMessage};
+use sablier_thread_program::state::{Trigger, TriggerContext, VersionedThread};
+use sablier_utils::{cron::next_timestamp, pyth::PriceFeedMessage};
 use solana_sdk::{clock::Clock, pubkey::Pubkey};
 
-use crate::{error::PluginError, observers::state::PythThread};
+use crate::{
+    config::{PluginConfig, PythConfig, TokenBalanceTrigger},
+    error::PluginError,
+    metrics::Metrics,
+    observers::state::PythThread,
+    shutdown::Shutdown,
+    utils::get_price_account,
+};
 
 use super::state::{
-    AccountThreads, Clocks, CronThreads, EpochThreads, NowThreads, PythThreads, SlotThreads,
-    UpdatedAccounts,
+    AccountThreads, AccountTrigger, AccountTriggers, BalanceCrossing, Clocks, CronThreads,
+    EpochThreads, ImmediateThreads, IndexedThreads, NowThreads, PythThreads, SlotThreads,
+    SubmittedThreads, ThreadFilter, ThreadPipelines, ThreadTriggers,
 };
 
 #[derive(Default)]
@@ -27,6 +36,9 @@ pub struct ThreadObserver {
     // Integer tracking the current epoch.
     pub current_epoch: AtomicU64,
 
+    // The unix timestamp of the latest clock, price updates published too long before are stale.
+    pub current_unix_timestamp: AtomicI64,
+
     // The set of threads with an account trigger.
     // Map from account pubkeys to the set of threads listening for an account update.
     pub account_threads: AccountThreads,
@@ -38,6 +50,9 @@ pub struct ThreadObserver {
     // The set of threads with a now trigger.
     pub now_threads: NowThreads,
 
+    // The threads with a now trigger awaiting their kickoff, executed without waiting for the next slot.
+    pub immediate_threads: ImmediateThreads,
+
     // The set of threads with a slot trigger.
     pub slot_threads: SlotThreads,
 
@@ -47,17 +62,128 @@ pub struct ThreadObserver {
     // The set of threads with a pyth trigger.
     pub pyth_threads: PythThreads,
 
-    // The set of accounts that have updated.
-    pub updated_accounts: UpdatedAccounts,
+    // The data ranges watched by the threads with an account trigger, also updated from the kickoff simulations.
+    pub account_triggers: Arc<AccountTriggers>,
+
+    // The pipeline of each indexed thread, read by the executor.
+    pub thread_pipelines: Arc<ThreadPipelines>,
+
+    // The trigger of each indexed thread, published to the snapshot stream.
+    pub thread_triggers: ThreadTriggers,
+
+    // The threads indexed by the observer, purged from every index once closed.
+    pub indexed_threads: IndexedThreads,
+
+    // The threads with an exec transaction in flight, not enqueued again until it completes.
+    pub submitted_threads: Arc<SubmittedThreads>,
+
+    // The allowlist and denylist of threads and authorities, shared with the executor.
+    pub thread_filter: Arc<ThreadFilter>,
+
+    // The shards of the pyth price accounts.
+    pub pyth_config: PythConfig,
+
+    // The thresholds of the threads watching the amount of a token account.
+    pub token_balance_triggers: HashMap<Pubkey, TokenBalanceTrigger>,
+
+    pub metrics: Arc<Metrics>,
+
+    // Signaled when the plugin unloads, no thread is enqueued for execution afterwards.
+    pub shutdown: Arc<Shutdown>,
 }
 
 impl ThreadObserver {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(
+        config: &PluginConfig,
+        metrics: Arc<Metrics>,
+        shutdown: Arc<Shutdown>,
+        submitted_threads: Arc<SubmittedThreads>,
+        account_triggers: Arc<AccountTriggers>,
+        thread_pipelines: Arc<ThreadPipelines>,
+        thread_filter: Arc<ThreadFilter>,
+    ) -> Self {
+        Self {
+            thread_filter,
+            pyth_config: config.pyth.clone(),
+            token_balance_triggers: config
+                .token_balance_triggers
+                .iter()
+                .filter_map(|(thread, rule)| match Pubkey::from_str(thread) {
+                    Ok(thread_pubkey) => Some((thread_pubkey, rule.clone())),
+                    Err(err) => {
+                        info!(
+                            "Ignoring invalid token balance trigger thread: {} err: {:?}",
+                            thread, err
+                        );
+                        None
+                    }
+                })
+                .collect(),
+            metrics,
+            shutdown,
+            submitted_threads,
+            account_triggers,
+            thread_pipelines,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the number of distinct threads indexed by the observer.
+    pub async fn tracked_threads(&self) -> usize {
+        let mut threads: HashSet<Pubkey> = HashSet::new();
+        threads.extend(self.account_threads.read().await.values().flatten());
+        threads.extend(self.cron_threads.read().await.threads());
+        threads.extend(self.now_threads.read().await.iter());
+        threads.extend(self.slot_threads.read().await.threads());
+        threads.extend(self.epoch_threads.read().await.threads());
+        threads.extend(
+            self.pyth_threads
+                .read()
+                .await
+                .values()
+                .flatten()
+                .map(|pyth_thread| pyth_thread.thread_pubkey),
+        );
+        threads.len()
+    }
+
+    /// Returns true if the account update closes an indexed thread,
+    /// i.e. the account was emptied or reassigned away from the thread program.
+    pub fn is_closed_thread(&self, account_pubkey: &Pubkey, owner: &Pubkey, lamports: u64) -> bool {
+        (lamports == 0 || *owner != sablier_thread_program::ID)
+            && self.indexed_threads.contains(account_pubkey)
+    }
+
+    /// Removes the thread from every index, so it is never enqueued for execution again.
+    pub async fn purge_thread(&self, thread_pubkey: &Pubkey) {
+        self.indexed_threads.remove(thread_pubkey);
+        self.account_threads
+            .write()
+            .await
+            .retain(|_, thread_pubkeys| {
+                thread_pubkeys.remove(thread_pubkey);
+                !thread_pubkeys.is_empty()
+            });
+        self.account_triggers.remove(thread_pubkey);
+        self.thread_pipelines.remove(thread_pubkey);
+        self.thread_triggers.remove(thread_pubkey);
+        self.cron_threads.write().await.remove(thread_pubkey);
+        self.now_threads.write().await.remove(thread_pubkey);
+        self.immediate_threads.remove(thread_pubkey).await;
+        self.slot_threads.write().await.remove(thread_pubkey);
+        self.epoch_threads.write().await.remove(thread_pubkey);
+        self.pyth_threads.write().await.retain(|_, pyth_threads| {
+            pyth_threads.retain(|pyth_thread| pyth_thread.thread_pubkey != *thread_pubkey);
+            !pyth_threads.is_empty()
+        });
+        self.pyth_threads.forget(thread_pubkey);
     }
 
     pub async fn process_slot(self: Arc<Self>, slot: u64) -> HashSet<Pubkey> {
         let mut executable_threads = HashSet::new();
+        if self.shutdown.is_stopping() {
+            return executable_threads;
+        }
 
         // Drop old clocks.
         self.clocks.cleanup(slot).await;
@@ -65,122 +191,169 @@ impl ThreadObserver {
         // Get the set of threads that were triggered by the current clock.
         let r_clocks = self.clocks.read().await;
         if let Some(clock) = r_clocks.get(&slot) {
-            let mut w_cron_threads = self.cron_threads.write().await;
-            w_cron_threads.retain(|target_timestamp, thread_pubkeys| {
-                let is_due = clock.unix_timestamp >= *target_timestamp;
-                if is_due {
-                    self.current_epoch
-                        .fetch_max(clock.epoch, std::sync::atomic::Ordering::Relaxed);
-                    executable_threads.extend(thread_pubkeys.drain());
-                }
-                !is_due
-            });
-            drop(w_cron_threads);
+            let due_threads = self
+                .cron_threads
+                .write()
+                .await
+                .pop_due(clock.unix_timestamp);
+            executable_threads.extend(due_threads);
         }
         drop(r_clocks);
 
-        // Get the set of threads were triggered by an account update.
-        let r_account_threads = self.account_threads.read().await;
-        let mut w_updated_accounts = self.updated_accounts.write().await;
-        w_updated_accounts.iter().for_each(|account_pubkey| {
-            if let Some(thread_pubkeys) = r_account_threads.get(account_pubkey) {
-                executable_threads.extend(thread_pubkeys);
-            }
-        });
-        w_updated_accounts.clear();
-        drop(r_account_threads);
-        drop(w_updated_accounts);
-
         // Get the set of threads that were triggered by a slot update.
-        let mut w_slot_threads = self.slot_threads.write().await;
-        w_slot_threads.retain(|target_slot, thread_pubkeys| {
-            let is_due = slot >= *target_slot;
-            if is_due {
-                executable_threads.extend(thread_pubkeys.drain());
-            }
-            !is_due
-        });
-        drop(w_slot_threads);
+        // Target slots which were skipped are reached by the next processed slot.
+        executable_threads.extend(self.slot_threads.write().await.pop_due(slot));
 
         // Get the set of threads that were trigger by an epoch update.
-        let mut w_epoch_threads = self.epoch_threads.write().await;
-        let current_epoch = self
-            .current_epoch
-            .load(std::sync::atomic::Ordering::Relaxed);
-        w_epoch_threads.retain(|target_epoch, thread_pubkeys| {
-            let is_due = current_epoch >= *target_epoch;
-            if is_due {
-                executable_threads.extend(thread_pubkeys.drain());
-            }
-            !is_due
-        });
-        drop(w_epoch_threads);
+        let current_epoch = self.current_epoch.load(Ordering::Relaxed);
+        executable_threads.extend(self.epoch_threads.write().await.pop_due(current_epoch));
 
         // Get the set of immediate threads.
         let mut w_now_threads = self.now_threads.write().await;
         executable_threads.extend(w_now_threads.drain());
+        drop(w_now_threads);
+
+        // Skip the threads with a transaction in flight, whichever trigger marked them executable again.
+        let pending_threads = self.submitted_threads.pending(slot).await;
+        executable_threads.retain(|thread_pubkey| !pending_threads.contains(thread_pubkey));
 
         executable_threads
     }
 
     pub async fn observe_clock(self: Arc<Self>, clock: Clock) {
+        self.current_epoch.fetch_max(clock.epoch, Ordering::Relaxed);
+        self.current_unix_timestamp
+            .fetch_max(clock.unix_timestamp, Ordering::Relaxed);
         self.clocks.add(clock).await;
     }
 
-    /// Move all threads listening to this account into the executable set.
-    pub async fn observe_account(self: Arc<Self>, account_pubkey: Pubkey, _slot: u64) {
-        if self.account_threads.contains(&account_pubkey).await {
-            self.updated_accounts.add(account_pubkey).await;
+    /// Move the threads whose watched data range changed into the executable set.
+    /// The threads whose range is unknown, e.g. restored from the persisted state, are moved on any update.
+    pub async fn observe_account(
+        self: Arc<Self>,
+        account_pubkey: Pubkey,
+        lamports: u64,
+        triggered_threads: HashSet<Pubkey>,
+    ) {
+        if lamports == 0 {
+            return;
+        }
+        let r_account_threads = self.account_threads.read().await;
+        if let Some(thread_pubkeys) = r_account_threads.get(&account_pubkey) {
+            for thread_pubkey in thread_pubkeys {
+                if triggered_threads.contains(thread_pubkey)
+                    || !self
+                        .account_triggers
+                        .contains(&account_pubkey, thread_pubkey)
+                {
+                    self.now_threads.add(*thread_pubkey).await;
+                }
+            }
         }
     }
 
+    /// Move the threads whose trigger is satisfied by the price into the executable set.
+    /// This only spares the simulations of the kickoffs bound to fail, the trigger is checked again on-chain.
     pub async fn observe_price_feed(
         self: Arc<Self>,
         account_pubkey: Pubkey,
         price_feed: PriceFeedMessage,
     ) {
+        let current_unix_timestamp = self.current_unix_timestamp.load(Ordering::Relaxed);
+        let stale = current_unix_timestamp > 0
+            && current_unix_timestamp.saturating_sub(price_feed.publish_time)
+                > self.pyth_config.max_price_age_secs as i64;
+        let price = (!stale).then_some(price_feed.price);
         let r_pyth_threads = self.pyth_threads.read().await;
         if let Some(pyth_threads) = r_pyth_threads.get(&account_pubkey) {
             for pyth_thread in pyth_threads {
-                match pyth_thread.equality {
-                    Equality::GreaterThanOrEqual => {
-                        if price_feed.price.ge(&pyth_thread.limit) {
-                            self.now_threads.add(pyth_thread.thread_pubkey).await;
-                        }
-                    }
-                    Equality::LessThanOrEqual => {
-                        if price_feed.price.le(&pyth_thread.limit) {
-                            self.now_threads.add(pyth_thread.thread_pubkey).await;
-                        }
-                    }
+                if self
+                    .pyth_threads
+                    .evaluate(pyth_thread, price, self.pyth_config.hysteresis_bps)
+                {
+                    self.now_threads.add(pyth_thread.thread_pubkey).await;
+                } else {
+                    self.metrics
+                        .pyth_updates_filtered
+                        .with_label_values(&[if stale { "stale" } else { "not_satisfied" }])
+                        .inc();
                 }
             }
         }
         drop(r_pyth_threads);
     }
 
+    /// Indexes the thread according to its trigger.
+    /// Returns true if the thread was just created or reset with a now trigger, and should be executed right away.
     pub async fn observe_thread(
         self: Arc<Self>,
         thread: VersionedThread,
         thread_pubkey: Pubkey,
         slot: u64,
-    ) -> Result<(), PluginError> {
+    ) -> Result<bool, PluginError> {
         // If the thread is paused, just return without indexing
         if thread.paused() {
-            return Ok(());
+            return Ok(false);
+        }
+
+        // If the thread is filtered out, just return without indexing
+        if !self
+            .thread_filter
+            .is_allowed(&thread_pubkey, &thread.authority())
+            .await
+        {
+            return Ok(false);
         }
 
         info!("Indexing thread: {:?} slot: {}", thread_pubkey, slot);
+        self.indexed_threads.add(thread_pubkey);
+        self.thread_pipelines.set(thread_pubkey, &thread.trigger());
+        self.thread_triggers.set(thread_pubkey, thread.trigger());
+        if let Some(exec_context) = thread.exec_context() {
+            self.submitted_threads
+                .complete(&thread_pubkey, exec_context.last_exec_at)
+                .await;
+        }
+        self.metrics.threads_observed.inc();
         if thread.next_instruction().is_some() {
             // If the thread has a next instruction, index it as executable.
+            self.immediate_threads.remove(&thread_pubkey).await;
             self.now_threads.add(thread_pubkey).await;
         } else {
             // Otherwise, index the thread according to its trigger type.
             match thread.trigger() {
-                Trigger::Account { address, .. } => {
+                Trigger::Account {
+                    address,
+                    offset,
+                    size,
+                } => {
                     // Index the thread by its trigger's account pubkey.
                     self.account_threads.add(address, thread_pubkey).await;
 
+                    // Track the hash of the watched range, the thread only runs again once it changes.
+                    let data_hash = match thread.exec_context() {
+                        None => None,
+                        Some(exec_context) => match exec_context.trigger_context {
+                            TriggerContext::Account { data_hash } => Some(data_hash),
+                            _ => return Err(PluginError::InvalidExecContext),
+                        },
+                    };
+                    self.account_triggers.add(
+                        address,
+                        thread_pubkey,
+                        AccountTrigger {
+                            offset,
+                            size,
+                            data_hash,
+                            observed_data: None,
+                            balance: BalanceCrossing::new(
+                                offset,
+                                size,
+                                self.token_balance_triggers.get(&thread_pubkey),
+                            ),
+                        },
+                    );
+
                     // Threads with account triggers might be immediately executable,
                     // Thus, we should attempt to execute these threads right away without for an account update.
                     self.now_threads.add(thread_pubkey).await;
@@ -196,7 +369,8 @@ impl ThreadObserver {
                     };
 
                     // Index the thread to its target timestamp
-                    match next_moment(reference_timestamp, schedule) {
+                    // Schedules prefixed with a timezone are matched against its local wall clock.
+                    match next_timestamp(reference_timestamp, &schedule) {
                         None => {} // The thread does not have any upcoming scheduled target time
                         Some(target_timestamp) => {
                             self.cron_threads.add(target_timestamp, thread_pubkey).await
@@ -204,16 +378,49 @@ impl ThreadObserver {
                     }
                 }
                 Trigger::Timestamp { unix_ts } => {
-                    self.cron_threads.add(unix_ts, thread_pubkey).await
+                    // The thread fires once for its moment, the exec context records the moment it ran for.
+                    // A reset or an update to another moment re-arms it.
+                    let fired_at = match thread.exec_context() {
+                        None => None,
+                        Some(exec_context) => match exec_context.trigger_context {
+                            TriggerContext::Timestamp { started_at } => Some(started_at),
+                            _ => return Err(PluginError::InvalidExecContext),
+                        },
+                    };
+                    if fired_at != Some(unix_ts) {
+                        self.cron_threads.add(unix_ts, thread_pubkey).await
+                    }
                 }
                 Trigger::Now => {
+                    // Threads just created or reset are executed right away.
+                    // The same update may be delivered again, it is only enqueued once.
+                    if thread.exec_context().is_none() {
+                        if !self.immediate_threads.add(thread_pubkey).await {
+                            return Ok(false);
+                        }
+                        self.now_threads.add(thread_pubkey).await;
+                        return Ok(true);
+                    }
+                    self.immediate_threads.remove(&thread_pubkey).await;
                     self.now_threads.add(thread_pubkey).await;
                 }
                 Trigger::Slot { slot } => {
+                    // Threads rescheduled to another slot (e.g. by a thread response) are re-keyed.
                     self.slot_threads.add(slot, thread_pubkey).await;
                 }
                 Trigger::Epoch { epoch } => {
-                    self.epoch_threads.add(epoch, thread_pubkey).await;
+                    // The thread fires once per target epoch, the exec context records the last one it ran for.
+                    // A thread observed again in the same epoch, e.g. after a restart, is not indexed again.
+                    let last_epoch = match thread.exec_context() {
+                        None => None,
+                        Some(exec_context) => match exec_context.trigger_context {
+                            TriggerContext::Epoch { started_at } => Some(started_at),
+                            _ => return Err(PluginError::InvalidExecContext),
+                        },
+                    };
+                    if last_epoch.map_or(true, |last_epoch| last_epoch < epoch) {
+                        self.epoch_threads.add(epoch, thread_pubkey).await;
+                    }
                 }
                 Trigger::Pyth {
                     feed_id,
@@ -225,10 +432,15 @@ impl ThreadObserver {
                         equality,
                         limit,
                     };
-                    let price_pubkey = get_oracle_key(0, feed_id);
+                    let price_pubkey = get_price_account(&self.pyth_config, feed_id);
                     self.pyth_threads.add(price_pubkey, pyth_thread).await;
                 }
-                Trigger::Periodic { delay } => {
+                Trigger::Periodic {
+                    delay: interval_seconds,
+                }
+                | Trigger::Interval {
+                    interval_seconds, ..
+                } => {
                     // Find a reference timestamp for calculating the thread's upcoming target time.
                     let reference_timestamp = match thread.exec_context() {
                         None => thread.created_at().unix_timestamp,
@@ -238,13 +450,14 @@ impl ThreadObserver {
                         },
                     };
 
-                    let next_moment = reference_timestamp + delay as i64;
+                    // The missed moments of a skippable trigger are collapsed by the kickoff, the first one is due either way.
+                    let next_moment = reference_timestamp.saturating_add(interval_seconds as i64);
                     self.cron_threads.add(next_moment, thread_pubkey).await;
                 }
             }
         }
 
-        Ok(())
+        Ok(false)
     }
 }
 
@@ -254,12 +467,350 @@ impl Debug for ThreadObserver {
     }
 }
 
-fn next_moment(after: i64, schedule: String) -> Option<i64> {
-    match Schedule::from_str(&schedule) {
-        Err(_) => None,
-        Ok(schedule) => schedule
-            .next_after(&DateTime::from_timestamp(after, 0).unwrap())
-            .take()
-            .map(|datetime| datetime.timestamp()),
+#[cfg(test)]
+mod tests {
+    use sablier_thread_program::state::{ExecContext, Thread};
+    use sablier_utils::thread::ClockData;
+
+    use super::*;
+
+    fn test_thread(trigger: Trigger, trigger_context: Option<TriggerContext>) -> VersionedThread {
+        VersionedThread::V1(Thread {
+            authority: Pubkey::new_unique(),
+            bump: 0,
+            created_at: ClockData {
+                slot: 0,
+                epoch: 0,
+                unix_timestamp: 0,
+            },
+            domain: None,
+            exec_context: trigger_context.map(|trigger_context| ExecContext {
+                exec_index: 0,
+                execs_since_reimbursement: 0,
+                execs_since_slot: 0,
+                last_exec_at: 0,
+                trigger_context,
+            }),
+            fee: 0,
+            id: vec![],
+            instructions: vec![],
+            next_instruction: None,
+            paused: false,
+            rate_limit: 0,
+            trigger,
+        })
+    }
+
+    fn epoch_thread(epoch: u64, last_epoch: Option<u64>) -> VersionedThread {
+        test_thread(
+            Trigger::Epoch { epoch },
+            last_epoch.map(|started_at| TriggerContext::Epoch { started_at }),
+        )
+    }
+
+    async fn observe_epoch(observer: &Arc<ThreadObserver>, epoch: u64) {
+        observer
+            .clone()
+            .observe_clock(Clock {
+                epoch,
+                ..Clock::default()
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn stale_price_updates_do_not_enqueue_pyth_threads() {
+        let observer = Arc::new(ThreadObserver::default());
+        let thread_pubkey = Pubkey::new_unique();
+        let feed_id = [1; 32];
+        let thread = test_thread(
+            Trigger::Pyth {
+                feed_id,
+                equality: sablier_thread_program::state::Equality::GreaterThanOrEqual,
+                limit: 100,
+            },
+            None,
+        );
+        observer
+            .clone()
+            .observe_thread(thread, thread_pubkey, 0)
+            .await
+            .unwrap();
+        observer
+            .clone()
+            .observe_clock(Clock {
+                unix_timestamp: 1_000,
+                ..Clock::default()
+            })
+            .await;
+        let price_feed = |publish_time: i64| PriceFeedMessage {
+            feed_id,
+            price: 200,
+            conf: 0,
+            exponent: 0,
+            publish_time,
+            prev_publish_time: 0,
+            ema_price: 0,
+            ema_conf: 0,
+        };
+        let price_pubkey = get_price_account(&observer.pyth_config, feed_id);
+
+        observer
+            .clone()
+            .observe_price_feed(price_pubkey, price_feed(900))
+            .await;
+        assert!(observer.clone().process_slot(1).await.is_empty());
+        assert_eq!(
+            observer
+                .metrics
+                .pyth_updates_filtered
+                .with_label_values(&["stale"])
+                .get(),
+            1
+        );
+
+        observer
+            .clone()
+            .observe_price_feed(price_pubkey, price_feed(990))
+            .await;
+        assert_eq!(
+            observer.clone().process_slot(2).await,
+            HashSet::from([thread_pubkey])
+        );
+    }
+
+    #[tokio::test]
+    async fn epoch_thread_fires_once_at_the_boundary() {
+        let observer = Arc::new(ThreadObserver::default());
+        let thread_pubkey = Pubkey::new_unique();
+        observe_epoch(&observer, 10).await;
+        observer
+            .clone()
+            .observe_thread(epoch_thread(11, None), thread_pubkey, 0)
+            .await
+            .unwrap();
+        assert!(observer.clone().process_slot(1).await.is_empty());
+
+        observe_epoch(&observer, 11).await;
+        assert_eq!(
+            observer.clone().process_slot(2).await,
+            HashSet::from([thread_pubkey])
+        );
+        assert!(observer.clone().process_slot(3).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn epoch_thread_observed_after_the_boundary_fires() {
+        let observer = Arc::new(ThreadObserver::default());
+        let thread_pubkey = Pubkey::new_unique();
+        observe_epoch(&observer, 12).await;
+
+        // The clock of the new epoch was observed before the thread.
+        observer
+            .clone()
+            .observe_thread(epoch_thread(11, Some(10)), thread_pubkey, 0)
+            .await
+            .unwrap();
+        assert_eq!(
+            observer.clone().process_slot(1).await,
+            HashSet::from([thread_pubkey])
+        );
+    }
+
+    #[tokio::test]
+    async fn restart_in_the_same_epoch_does_not_fire_again() {
+        let observer = Arc::new(ThreadObserver::default());
+        let thread_pubkey = Pubkey::new_unique();
+        observe_epoch(&observer, 11).await;
+
+        // The thread already ran for epoch 11 before the restart.
+        observer
+            .clone()
+            .observe_thread(epoch_thread(11, Some(11)), thread_pubkey, 0)
+            .await
+            .unwrap();
+        assert!(observer.clone().process_slot(1).await.is_empty());
+        assert_eq!(observer.tracked_threads().await, 0);
+    }
+
+    #[tokio::test]
+    async fn timestamp_thread_fires_once_for_its_moment() {
+        let observer = Arc::new(ThreadObserver::default());
+        let thread_pubkey = Pubkey::new_unique();
+        let observe_clock = |slot, unix_timestamp| {
+            observer.clone().observe_clock(Clock {
+                slot,
+                unix_timestamp,
+                ..Clock::default()
+            })
+        };
+        observer
+            .clone()
+            .observe_thread(
+                test_thread(Trigger::Timestamp { unix_ts: 100 }, None),
+                thread_pubkey,
+                0,
+            )
+            .await
+            .unwrap();
+        observe_clock(1, 99).await;
+        assert!(observer.clone().process_slot(1).await.is_empty());
+        observe_clock(2, 100).await;
+        assert_eq!(
+            observer.clone().process_slot(2).await,
+            HashSet::from([thread_pubkey])
+        );
+
+        // Once it ran for the moment, the thread is not indexed again.
+        let fired = TriggerContext::Timestamp { started_at: 100 };
+        observer
+            .clone()
+            .observe_thread(
+                test_thread(Trigger::Timestamp { unix_ts: 100 }, Some(fired)),
+                thread_pubkey,
+                3,
+            )
+            .await
+            .unwrap();
+        observe_clock(4, 101).await;
+        assert!(observer.clone().process_slot(4).await.is_empty());
+
+        // An update to another moment re-arms it.
+        observer
+            .clone()
+            .observe_thread(
+                test_thread(Trigger::Timestamp { unix_ts: 101 }, Some(fired)),
+                thread_pubkey,
+                5,
+            )
+            .await
+            .unwrap();
+        observe_clock(6, 101).await;
+        assert_eq!(
+            observer.clone().process_slot(6).await,
+            HashSet::from([thread_pubkey])
+        );
+    }
+
+    #[tokio::test]
+    async fn closed_thread_is_not_executed() {
+        let observer = Arc::new(ThreadObserver::default());
+        let thread_pubkey = Pubkey::new_unique();
+        observer
+            .clone()
+            .observe_thread(
+                test_thread(Trigger::Timestamp { unix_ts: 100 }, None),
+                thread_pubkey,
+                1,
+            )
+            .await
+            .unwrap();
+        assert_eq!(observer.tracked_threads().await, 1);
+
+        // An update of another account does not close the thread.
+        assert!(!observer.is_closed_thread(&Pubkey::new_unique(), &Pubkey::default(), 0));
+
+        // The thread account is emptied and reassigned to the system program.
+        assert!(observer.is_closed_thread(&thread_pubkey, &Pubkey::default(), 0));
+        observer.purge_thread(&thread_pubkey).await;
+        assert_eq!(observer.tracked_threads().await, 0);
+        assert!(!observer.is_closed_thread(&thread_pubkey, &Pubkey::default(), 0));
+
+        observer
+            .clone()
+            .observe_clock(Clock {
+                slot: 2,
+                unix_timestamp: 100,
+                ..Clock::default()
+            })
+            .await;
+        assert!(observer.clone().process_slot(2).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn thread_marked_executable_again_is_not_built_twice() {
+        let observer = Arc::new(ThreadObserver {
+            submitted_threads: Arc::new(SubmittedThreads::new(150)),
+            ..ThreadObserver::default()
+        });
+        let (account_pubkey, thread_pubkey) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let trigger = Trigger::Account {
+            address: account_pubkey,
+            offset: 0,
+            size: 8,
+        };
+        observer
+            .clone()
+            .observe_thread(test_thread(trigger.clone(), None), thread_pubkey, 1)
+            .await
+            .unwrap();
+        assert_eq!(
+            observer.clone().process_slot(1).await,
+            HashSet::from([thread_pubkey])
+        );
+
+        // The executor sent the transaction, then an account update marks the thread executable again
+        // before it landed.
+        observer.submitted_threads.add(thread_pubkey, 1).await;
+        observer
+            .clone()
+            .observe_account(account_pubkey, 1, HashSet::from([thread_pubkey]))
+            .await;
+        assert!(observer.clone().process_slot(2).await.is_empty());
+
+        // The thread update shows the execution completed.
+        let mut executed_thread =
+            test_thread(trigger, Some(TriggerContext::Account { data_hash: 0 }));
+        let VersionedThread::V1(thread) = &mut executed_thread;
+        thread.exec_context.as_mut().unwrap().last_exec_at = 2;
+        observer
+            .clone()
+            .observe_thread(executed_thread, thread_pubkey, 3)
+            .await
+            .unwrap();
+        assert_eq!(
+            observer.clone().process_slot(3).await,
+            HashSet::from([thread_pubkey])
+        );
+    }
+
+    #[tokio::test]
+    async fn redelivered_now_thread_is_enqueued_once() {
+        let observer = Arc::new(ThreadObserver::default());
+        let thread_pubkey = Pubkey::new_unique();
+        assert!(observer
+            .clone()
+            .observe_thread(test_thread(Trigger::Now, None), thread_pubkey, 1)
+            .await
+            .unwrap());
+        assert_eq!(
+            observer.clone().process_slot(1).await,
+            HashSet::from([thread_pubkey])
+        );
+
+        // The same update is delivered again at another commitment level.
+        assert!(!observer
+            .clone()
+            .observe_thread(test_thread(Trigger::Now, None), thread_pubkey, 1)
+            .await
+            .unwrap());
+        assert!(observer.clone().process_slot(2).await.is_empty());
+
+        // Once kicked off, a reset thread is executed right away again.
+        observer
+            .clone()
+            .observe_thread(
+                test_thread(Trigger::Now, Some(TriggerContext::Now)),
+                thread_pubkey,
+                2,
+            )
+            .await
+            .unwrap();
+        observer.clone().process_slot(3).await;
+        assert!(observer
+            .clone()
+            .observe_thread(test_thread(Trigger::Now, None), thread_pubkey, 4)
+            .await
+            .unwrap());
     }
 }
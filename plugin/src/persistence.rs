@@ -0,0 +1,273 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    hash::Hash,
+    path::Path,
+    sync::atomic::Ordering,
+};
+
+use anchor_lang::prelude::{borsh, AnchorDeserialize, AnchorSerialize};
+use log::info;
+use sablier_thread_program::state::VersionedThread;
+use sablier_utils::thread::Equality;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    error::PluginError,
+    executors::state::ExecutableThreads,
+    observers::{state::PythThread, thread::ThreadObserver},
+    refresh::refresh_accounts,
+};
+
+/// The observer maps and the executable threads, written to disk so that a restarted validator
+/// does not wait for thread accounts to be rewritten before executing them.
+#[derive(AnchorSerialize, AnchorDeserialize, Default)]
+pub struct PersistedState {
+    pub current_epoch: u64,
+    pub account_threads: Vec<(Pubkey, Vec<Pubkey>)>,
+    pub cron_threads: Vec<(i64, Vec<Pubkey>)>,
+    pub now_threads: Vec<Pubkey>,
+    pub slot_threads: Vec<(u64, Vec<Pubkey>)>,
+    pub epoch_threads: Vec<(u64, Vec<Pubkey>)>,
+    pub pyth_threads: Vec<(Pubkey, Vec<PersistedPythThread>)>,
+    pub executable_threads: Vec<(Pubkey, u64)>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct PersistedPythThread {
+    pub thread_pubkey: Pubkey,
+    pub equality: Equality,
+    pub limit: i64,
+}
+
+impl PersistedState {
+    pub async fn capture(
+        observer: &ThreadObserver,
+        executable_threads: &ExecutableThreads,
+    ) -> Self {
+        Self {
+            current_epoch: observer.current_epoch.load(Ordering::Relaxed),
+            account_threads: to_entries(&*observer.account_threads.read().await),
+            cron_threads: to_entries(&observer.cron_threads.read().await.by_target()),
+            now_threads: observer.now_threads.read().await.iter().copied().collect(),
+            slot_threads: to_entries(&observer.slot_threads.read().await.by_target()),
+            epoch_threads: to_entries(&observer.epoch_threads.read().await.by_target()),
+            pyth_threads: observer
+                .pyth_threads
+                .read()
+                .await
+                .iter()
+                .map(|(price_pubkey, pyth_threads)| {
+                    (
+                        *price_pubkey,
+                        pyth_threads
+                            .iter()
+                            .map(|pyth_thread| PersistedPythThread {
+                                thread_pubkey: pyth_thread.thread_pubkey,
+                                equality: pyth_thread.equality.clone(),
+                                limit: pyth_thread.limit,
+                            })
+                            .collect(),
+                    )
+                })
+                .collect(),
+            executable_threads: executable_threads.due_slots().await,
+        }
+    }
+
+    /// Reads the state file. A missing or corrupted file is logged and treated as a cold start.
+    pub fn load(path: &str) -> Option<Self> {
+        let data = match fs::read(path) {
+            Err(err) => {
+                info!("No persisted state loaded from: {} err: {}", path, err);
+                return None;
+            }
+            Ok(data) => data,
+        };
+        match Self::try_from_slice(&data) {
+            Err(err) => {
+                info!("Ignoring corrupted persisted state: {} err: {}", path, err);
+                None
+            }
+            Ok(state) => Some(state),
+        }
+    }
+
+    /// Writes the state to a temporary file first, so a crash mid-write never corrupts the previous state.
+    pub fn save(&self, path: &str) -> Result<(), PluginError> {
+        let tmp_path = format!("{}.tmp", path);
+        fs::write(&tmp_path, self.try_to_vec()?)?;
+        fs::rename(&tmp_path, Path::new(path))?;
+        Ok(())
+    }
+
+    /// Drops the threads which were deleted or paused while the plugin was down.
+    pub async fn validate(mut self, client: &RpcClient) -> Result<Self, PluginError> {
+        let thread_pubkeys: Vec<Pubkey> = self.thread_pubkeys().into_iter().collect();
+        let mut live_threads: HashSet<Pubkey> = HashSet::new();
+        refresh_accounts(client, &thread_pubkeys, |thread_pubkey, account, _slot| {
+            let Some(account) = account else {
+                return;
+            };
+            if account.owner != sablier_thread_program::ID {
+                return;
+            }
+            if let Ok(thread) = VersionedThread::try_from(account.data) {
+                if !thread.paused() {
+                    live_threads.insert(thread_pubkey);
+                }
+            }
+        })
+        .await?;
+
+        let dropped_threads = thread_pubkeys.len() - live_threads.len();
+        if dropped_threads > 0 {
+            info!(
+                "Dropped {} persisted threads which no longer exist or are paused",
+                dropped_threads
+            );
+        }
+        let is_live = |thread_pubkey: &Pubkey| live_threads.contains(thread_pubkey);
+        retain_entries(&mut self.account_threads, is_live);
+        retain_entries(&mut self.cron_threads, is_live);
+        self.now_threads.retain(is_live);
+        retain_entries(&mut self.slot_threads, is_live);
+        retain_entries(&mut self.epoch_threads, is_live);
+        for (_price_pubkey, pyth_threads) in self.pyth_threads.iter_mut() {
+            pyth_threads.retain(|pyth_thread| is_live(&pyth_thread.thread_pubkey));
+        }
+        self.pyth_threads
+            .retain(|(_price_pubkey, pyth_threads)| !pyth_threads.is_empty());
+        self.executable_threads
+            .retain(|(thread_pubkey, _due_slot)| is_live(thread_pubkey));
+        Ok(self)
+    }
+
+    /// Indexes the persisted threads in the observer and the executor.
+    pub async fn restore(self, observer: &ThreadObserver, executable_threads: &ExecutableThreads) {
+        info!(
+            "Restoring {} persisted threads",
+            self.thread_pubkeys().len()
+        );
+        for thread_pubkey in self.thread_pubkeys() {
+            observer.indexed_threads.add(thread_pubkey);
+        }
+        observer
+            .current_epoch
+            .fetch_max(self.current_epoch, Ordering::Relaxed);
+        for (account_pubkey, thread_pubkeys) in self.account_threads {
+            for thread_pubkey in thread_pubkeys {
+                observer
+                    .account_threads
+                    .add(account_pubkey, thread_pubkey)
+                    .await;
+            }
+        }
+        for (timestamp, thread_pubkeys) in self.cron_threads {
+            for thread_pubkey in thread_pubkeys {
+                observer.cron_threads.add(timestamp, thread_pubkey).await;
+            }
+        }
+        for thread_pubkey in self.now_threads {
+            observer.now_threads.add(thread_pubkey).await;
+        }
+        for (slot, thread_pubkeys) in self.slot_threads {
+            for thread_pubkey in thread_pubkeys {
+                observer.slot_threads.add(slot, thread_pubkey).await;
+            }
+        }
+        for (epoch, thread_pubkeys) in self.epoch_threads {
+            for thread_pubkey in thread_pubkeys {
+                observer.epoch_threads.add(epoch, thread_pubkey).await;
+            }
+        }
+        for (price_pubkey, pyth_threads) in self.pyth_threads {
+            for pyth_thread in pyth_threads {
+                observer
+                    .pyth_threads
+                    .add(
+                        price_pubkey,
+                        PythThread {
+                            thread_pubkey: pyth_thread.thread_pubkey,
+                            equality: pyth_thread.equality,
+                            limit: pyth_thread.limit,
+                        },
+                    )
+                    .await;
+            }
+        }
+        executable_threads
+            .add(self.executable_threads.into_iter().collect())
+            .await;
+    }
+
+    fn thread_pubkeys(&self) -> HashSet<Pubkey> {
+        let mut thread_pubkeys: HashSet<Pubkey> = HashSet::new();
+        thread_pubkeys.extend(self.account_threads.iter().flat_map(|(_, v)| v));
+        thread_pubkeys.extend(self.cron_threads.iter().flat_map(|(_, v)| v));
+        thread_pubkeys.extend(self.now_threads.iter());
+        thread_pubkeys.extend(self.slot_threads.iter().flat_map(|(_, v)| v));
+        thread_pubkeys.extend(self.epoch_threads.iter().flat_map(|(_, v)| v));
+        thread_pubkeys.extend(
+            self.pyth_threads
+                .iter()
+                .flat_map(|(_, v)| v.iter().map(|pyth_thread| &pyth_thread.thread_pubkey)),
+        );
+        thread_pubkeys.extend(self.executable_threads.iter().map(|(v, _)| v));
+        thread_pubkeys
+    }
+}
+
+fn to_entries<K: Copy>(map: &HashMap<K, HashSet<Pubkey>>) -> Vec<(K, Vec<Pubkey>)> {
+    map.iter()
+        .map(|(key, thread_pubkeys)| (*key, thread_pubkeys.iter().copied().collect()))
+        .collect()
+}
+
+fn retain_entries<K: Eq + Hash>(
+    entries: &mut Vec<(K, Vec<Pubkey>)>,
+    is_live: impl Fn(&Pubkey) -> bool,
+) {
+    for (_key, thread_pubkeys) in entries.iter_mut() {
+        thread_pubkeys.retain(&is_live);
+    }
+    entries.retain(|(_key, thread_pubkeys)| !thread_pubkeys.is_empty());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corrupted_state_is_a_cold_start() {
+        let path = std::env::temp_dir().join(format!("sablier-state-{}", Pubkey::new_unique()));
+        let path = path.to_str().unwrap();
+        assert!(PersistedState::load(path).is_none());
+
+        fs::write(path, [1, 2, 3]).unwrap();
+        assert!(PersistedState::load(path).is_none());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn state_roundtrips_through_the_file() {
+        let path = std::env::temp_dir().join(format!("sablier-state-{}", Pubkey::new_unique()));
+        let path = path.to_str().unwrap();
+        let thread_pubkey = Pubkey::new_unique();
+        let state = PersistedState {
+            current_epoch: 7,
+            cron_threads: vec![(1_700_000_000, vec![thread_pubkey])],
+            executable_threads: vec![(thread_pubkey, 42)],
+            ..PersistedState::default()
+        };
+        state.save(path).unwrap();
+
+        let loaded = PersistedState::load(path).unwrap();
+        assert_eq!(loaded.current_epoch, 7);
+        assert_eq!(loaded.cron_threads, state.cron_threads);
+        assert_eq!(loaded.executable_threads, state.executable_threads);
+        assert_eq!(loaded.thread_pubkeys(), HashSet::from([thread_pubkey]));
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -1,16 +1,34 @@
-use std::{fmt::Debug, sync::Arc};
+use std::{
+    collections::HashSet,
+    fmt::Debug,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 use log::info;
 use solana_geyser_plugin_interface::geyser_plugin_interface::{
-    GeyserPlugin, ReplicaAccountInfoVersions, Result as PluginResult, SlotStatus,
+    GeyserPlugin, GeyserPluginError, ReplicaAccountInfoVersions, Result as PluginResult, SlotStatus,
 };
+use solana_sdk::{account::Account, pubkey::Pubkey};
 use tokio::runtime::{Builder, Runtime};
 
 use crate::{
+    builders::InjectedInstructions,
     config::PluginConfig,
-    events::{AccountUpdate, AccountUpdateEvent},
+    events::{account_data, AccountUpdate, AccountUpdateEvent},
     executors::Executors,
-    observers::Observers,
+    health::Health,
+    metrics::Metrics,
+    observers::{
+        state::{AccountTriggers, SubmittedThreads, ThreadFilter, ThreadPipelines},
+        Observers,
+    },
+    reload::ConfigReloader,
+    shutdown::Shutdown,
+    websocket::WebsocketFallback,
 };
 
 pub struct SablierPlugin {
@@ -28,6 +46,11 @@ pub struct Inner {
     pub config: PluginConfig,
     pub executors: Arc<Executors>,
     pub observers: Arc<Observers>,
+    pub metrics: Arc<Metrics>,
+    pub health: Arc<Health>,
+    pub websocket_fallback: Option<Arc<WebsocketFallback>>,
+    /// Set once the thread accounts bootstrap was started.
+    pub bootstrapped: AtomicBool,
     pub runtime: Arc<Runtime>,
 }
 
@@ -46,7 +69,12 @@ impl GeyserPlugin for SablierPlugin {
         );
         info!("Loading snapshot...");
         let config = PluginConfig::read_from(config_file)?;
-        *self = SablierPlugin::new_from_config(config);
+        InjectedInstructions::validate(&config).map_err(|err| {
+            GeyserPluginError::ConfigFileReadError {
+                msg: err.to_string(),
+            }
+        })?;
+        *self = SablierPlugin::new_from_config(config, Some(PathBuf::from(config_file)));
         Ok(())
     }
 
@@ -57,52 +85,28 @@ impl GeyserPlugin for SablierPlugin {
         is_startup: bool,
     ) -> PluginResult<()> {
         // Parse account info.
+        let (lamports, data) = account_data(&account);
         let account_update = AccountUpdate::from(account);
 
-        // Process event on tokio task.
-        self.inner.clone().spawn(|inner| async move {
-            // Send all account updates to the thread observer for account listeners.
-            // Only process account updates if we're past the startup phase.
-            if !is_startup {
-                inner
-                    .observers
-                    .thread
-                    .clone()
-                    .observe_account(account_update.key, slot)
-                    .await;
-            }
-
-            if let Some(event) = account_update.event {
-                // Process specific update events.
-                match event {
-                    AccountUpdateEvent::Clock { clock } => {
-                        inner.observers.thread.clone().observe_clock(clock).await;
-                    }
-                    AccountUpdateEvent::Thread { thread } => {
-                        inner
-                            .observers
-                            .thread
-                            .clone()
-                            .observe_thread(*thread, account_update.key, slot)
-                            .await
-                            .ok();
-                    }
-                    AccountUpdateEvent::PriceFeed { price_feed } => {
-                        inner
-                            .observers
-                            .thread
-                            .clone()
-                            .observe_price_feed(account_update.key, price_feed)
-                            .await;
-                    }
-                }
+        if !is_startup {
+            if let Some(websocket_fallback) = self.inner.websocket_fallback.as_ref() {
+                websocket_fallback.observe_account(slot);
             }
+        }
 
-            Ok(())
-        });
+        self.inner
+            .observe_account(account_update, lamports, data, slot, is_startup);
         Ok(())
     }
 
+    fn on_unload(&mut self) {
+        info!("Unloading plugin...");
+        let inner = self.inner.clone();
+        inner
+            .runtime
+            .block_on(inner.executors.shutdown(&inner.observers));
+    }
+
     fn notify_end_of_startup(&self) -> PluginResult<()> {
         info!("Snapshot loaded");
         Ok(())
@@ -116,6 +120,18 @@ impl GeyserPlugin for SablierPlugin {
     ) -> PluginResult<()> {
         self.inner.clone().spawn(|inner| async move {
             if let SlotStatus::Processed = status {
+                if inner.config.bootstrap_threads
+                    && !inner.bootstrapped.swap(true, Ordering::SeqCst)
+                {
+                    inner.clone().bootstrap_threads();
+                }
+                if let Some(gap_slot) = inner
+                    .websocket_fallback
+                    .as_ref()
+                    .and_then(|websocket_fallback| websocket_fallback.observe_slot(slot))
+                {
+                    inner.clone().open_websocket_fallback(gap_slot);
+                }
                 inner
                     .executors
                     .clone()
@@ -129,15 +145,109 @@ impl GeyserPlugin for SablierPlugin {
 }
 
 impl SablierPlugin {
-    fn new_from_config(config: PluginConfig) -> Self {
+    fn new_from_config(config: PluginConfig, config_path: Option<PathBuf>) -> Self {
         let runtime = build_runtime(config.clone());
-        let observers = Arc::new(Observers::new());
-        let executors = Arc::new(Executors::new(config.clone()));
+        let metrics = Arc::new(Metrics::new());
+        let health = Arc::new(Health::default());
+        let shutdown = Arc::new(Shutdown::default());
+        let submitted_threads = Arc::new(SubmittedThreads::new(config.dedupe_window_slots));
+        let account_triggers = Arc::new(AccountTriggers::default());
+        let thread_pipelines = Arc::new(ThreadPipelines::default());
+        let thread_filter = Arc::new(ThreadFilter::new(&config.thread_filter));
+        let observers = Arc::new(Observers::new(
+            &config,
+            metrics.clone(),
+            shutdown.clone(),
+            submitted_threads.clone(),
+            account_triggers.clone(),
+            thread_pipelines.clone(),
+            thread_filter.clone(),
+        ));
+        let executors = Arc::new(Executors::new(
+            config.clone(),
+            metrics.clone(),
+            health.clone(),
+            shutdown,
+            submitted_threads,
+            account_triggers,
+            thread_pipelines,
+            thread_filter,
+        ));
+        if let Some(snapshot_stream) = executors.snapshot_stream.clone() {
+            runtime.spawn(snapshot_stream.serve());
+        }
+        // The built-in executor is left idle when the executable threads are only published.
+        if !config.publish_only() {
+            runtime.spawn(executors.clone().refresh_blockhash());
+            runtime.spawn(executors.clone().monitor_signatory_balance());
+            runtime.spawn(executors.clone().monitor_slot_lag());
+        }
+        if let Some(webhook) = executors.tx.webhook.clone() {
+            runtime.spawn(webhook.deliver());
+        }
+        if config.persistence.path.is_some() {
+            runtime.spawn(executors.clone().persist_state(observers.clone()));
+        }
+        if config.lookup_table.enabled && !config.dry_run && !config.publish_only() {
+            runtime.spawn(executors.clone().manage_lookup_table());
+        }
+        if let Some(port) = config.metrics_port {
+            #[cfg(feature = "http-server")]
+            runtime.spawn(crate::server::serve(
+                config.metrics_bind_address,
+                port,
+                crate::server::ServerState {
+                    config: config.health.clone(),
+                    metrics: metrics.clone(),
+                    health: health.clone(),
+                    observers: observers.clone(),
+                },
+            ));
+            #[cfg(not(feature = "http-server"))]
+            info!(
+                "Ignoring metrics_port: {}, the plugin was built without the http-server feature",
+                port
+            );
+        }
+        let reloader = config_path.map(|path| {
+            Arc::new(ConfigReloader::new(
+                path,
+                observers.clone(),
+                executors.clone(),
+            ))
+        });
+        if let Some(reloader) = reloader.clone() {
+            runtime.spawn(reloader.watch());
+        }
+        if let Some(port) = config.admin_port {
+            #[cfg(feature = "http-server")]
+            runtime.spawn(crate::admin::serve(
+                port,
+                crate::admin::AdminState {
+                    observer: observers.thread.clone(),
+                    executor: executors.tx.clone(),
+                    reloader,
+                },
+            ));
+            #[cfg(not(feature = "http-server"))]
+            info!(
+                "Ignoring admin_port: {}, the plugin was built without the http-server feature",
+                port
+            );
+        }
+        let websocket_fallback = config
+            .websocket_fallback
+            .as_ref()
+            .map(|config| Arc::new(WebsocketFallback::new(config, metrics.clone())));
         Self {
             inner: Arc::new(Inner {
                 config,
                 executors,
                 observers,
+                metrics,
+                health,
+                websocket_fallback,
+                bootstrapped: AtomicBool::new(false),
                 runtime,
             }),
         }
@@ -146,11 +256,165 @@ impl SablierPlugin {
 
 impl Default for SablierPlugin {
     fn default() -> Self {
-        Self::new_from_config(PluginConfig::default())
+        Self::new_from_config(PluginConfig::default(), None)
     }
 }
 
 impl Inner {
+    /// Observes an account update, delivered by geyser or by the websocket fallback.
+    fn observe_account(
+        self: &Arc<Self>,
+        account_update: AccountUpdate,
+        lamports: u64,
+        data: &[u8],
+        slot: u64,
+        is_startup: bool,
+    ) {
+        // Hash the data ranges watched by account triggers while the account data is still borrowed.
+        let triggered_threads = if is_startup {
+            HashSet::new()
+        } else {
+            self.observers.thread.account_triggers.observe(
+                &account_update.key,
+                &account_update.owner,
+                lamports,
+                data,
+            )
+        };
+
+        // Check whether the update closes a thread while the owner is at hand.
+        let closed_thread = !is_startup
+            && self.observers.thread.is_closed_thread(
+                &account_update.key,
+                &account_update.owner,
+                lamports,
+            );
+
+        // Process event on tokio task.
+        self.clone().spawn(|inner| async move {
+            // Send all account updates to the thread observer for account listeners.
+            // Only process account updates if we're past the startup phase.
+            if !is_startup {
+                inner
+                    .observers
+                    .thread
+                    .clone()
+                    .observe_account(account_update.key, lamports, triggered_threads)
+                    .await;
+            }
+
+            // A closed thread is dropped from the observer and the executor, it can't be executed anymore.
+            if closed_thread {
+                info!("Purging closed thread: {}", account_update.key);
+                inner
+                    .observers
+                    .thread
+                    .purge_thread(&account_update.key)
+                    .await;
+                inner.executors.tx.forget_thread(&account_update.key).await;
+            }
+
+            if let Some(event) = account_update.event {
+                // Process specific update events.
+                match event {
+                    AccountUpdateEvent::Clock { clock } => {
+                        inner.observers.thread.clone().observe_clock(clock).await;
+                    }
+                    AccountUpdateEvent::Thread { thread } => {
+                        // An updated thread may no longer fail simulation.
+                        inner
+                            .executors
+                            .tx
+                            .simulation_backoff
+                            .reset(&account_update.key)
+                            .await;
+                        inner.executors.tx.release_thread(&account_update.key).await;
+                        if let Some(exec_context) = thread.exec_context() {
+                            inner
+                                .executors
+                                .tx
+                                .observe_thread_exec(&account_update.key, exec_context.last_exec_at)
+                                .await;
+                        }
+                        inner
+                            .executors
+                            .tx
+                            .thread_fees
+                            .set(account_update.key, thread.fee())
+                            .await;
+                        let execute_immediately = inner
+                            .observers
+                            .thread
+                            .clone()
+                            .observe_thread(*thread, account_update.key, slot)
+                            .await
+                            .unwrap_or(false);
+                        if execute_immediately && !is_startup {
+                            inner
+                                .executors
+                                .clone()
+                                .execute_immediately(
+                                    inner.observers.clone(),
+                                    slot,
+                                    inner.runtime.clone(),
+                                )
+                                .await;
+                        }
+                    }
+                    AccountUpdateEvent::Pool => {
+                        // The pool rotated, the workers are fetched again on the next slot.
+                        inner.executors.tx.pool_cache.invalidate().await;
+                    }
+                    AccountUpdateEvent::PriceFeed { price_feed } => {
+                        inner
+                            .observers
+                            .thread
+                            .clone()
+                            .observe_price_feed(account_update.key, price_feed)
+                            .await;
+                    }
+                }
+            }
+
+            Ok(())
+        });
+    }
+
+    /// Streams the accounts over the websocket until geyser resumes, through the same path as the geyser updates.
+    fn open_websocket_fallback(self: Arc<Self>, gap_slot: u64) {
+        let Some(websocket_fallback) = self.websocket_fallback.clone() else {
+            return;
+        };
+        let client = self.executors.client.clone();
+        let observers = self.observers.clone();
+        self.runtime.clone().spawn(websocket_fallback.run(
+            gap_slot,
+            client,
+            observers,
+            move |key, account, slot| self.observe_fetched_account(key, &account, slot),
+        ));
+    }
+
+    /// Fetches the thread accounts once, through the same path as the geyser updates, without holding up the slot processing.
+    fn bootstrap_threads(self: Arc<Self>) {
+        let client = self.executors.client.clone();
+        self.runtime.clone().spawn(async move {
+            if let Err(err) = crate::bootstrap::bootstrap_threads(&client, |key, account, slot| {
+                self.observe_fetched_account(key, &account, slot)
+            })
+            .await
+            {
+                info!("Failed to bootstrap the threads: {:?}", err);
+            }
+        });
+    }
+
+    /// Observes an account fetched over RPC rather than delivered by geyser.
+    fn observe_fetched_account(self: &Arc<Self>, key: Pubkey, account: &Account, slot: u64) {
+        let account_update = AccountUpdate::new(key, account.owner, &account.data);
+        self.observe_account(account_update, account.lamports, &account.data, slot, false);
+    }
+
     fn spawn<F: std::future::Future<Output = PluginResult<()>> + Send + 'static>(
         self: Arc<Self>,
         f: impl FnOnce(Arc<Self>) -> F,
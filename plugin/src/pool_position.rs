@@ -5,3 +5,20 @@ pub struct PoolPosition {
     pub current_position: Option<u64>,
     pub workers: Vec<Pubkey>,
 }
+
+impl PoolPosition {
+    pub fn new(workers: Vec<Pubkey>, worker_pubkey: &Pubkey) -> Self {
+        Self {
+            current_position: workers
+                .iter()
+                .position(|k| k.eq(worker_pubkey))
+                .map(|i| i as u64),
+            workers,
+        }
+    }
+
+    /// Returns true if the worker is paid for executing threads, i.e. it is in the pool or the pool is empty.
+    pub fn is_member(&self) -> bool {
+        self.current_position.is_some() || self.workers.is_empty()
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use log::info;
+use sablier_thread_program::state::VersionedThread;
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_request::MAX_MULTIPLE_ACCOUNTS};
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+use crate::error::PluginError;
+
+/// Fetches the accounts with getMultipleAccounts, by batches of `MAX_MULTIPLE_ACCOUNTS`, and passes
+/// each one to `handle` with the slot it was read at. A missing account is passed as `None`, it was deleted.
+///
+/// This is the single path for bulk account fetches: the startup bootstrap, the websocket reconciliation,
+/// the persisted state validation and the thread refreshes before the builds all go through it.
+pub async fn refresh_accounts<F>(
+    client: &RpcClient,
+    keys: &[Pubkey],
+    mut handle: F,
+) -> Result<(), PluginError>
+where
+    F: FnMut(Pubkey, Option<Account>, u64),
+{
+    for chunk in keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let response = client
+            .get_multiple_accounts_with_commitment(chunk, CommitmentConfig::processed())
+            .await?;
+        for (key, account) in chunk.iter().zip(response.value) {
+            handle(*key, account, response.context.slot);
+        }
+    }
+    Ok(())
+}
+
+/// A thread account fetched before its build.
+pub enum RefreshedThread {
+    Live(Box<VersionedThread>),
+    /// The account is gone or was reassigned away from the thread program.
+    Deleted,
+}
+
+/// Fetches the threads executable in a slot at once, instead of one getAccountInfo per build.
+/// The threads of a batch which failed to be fetched, or which don't deserialize, are left out.
+pub async fn refresh_threads(
+    client: &RpcClient,
+    thread_pubkeys: &[Pubkey],
+) -> HashMap<Pubkey, RefreshedThread> {
+    let mut threads = HashMap::new();
+    for chunk in thread_pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let result = refresh_accounts(client, chunk, |thread_pubkey, account, _slot| {
+            if let Some(thread) = refreshed_thread(account) {
+                threads.insert(thread_pubkey, thread);
+            }
+        })
+        .await;
+        if let Err(err) = result {
+            info!("Failed to refresh {} threads, err: {:?}", chunk.len(), err);
+        }
+    }
+    threads
+}
+
+fn refreshed_thread(account: Option<Account>) -> Option<RefreshedThread> {
+    let Some(account) = account else {
+        return Some(RefreshedThread::Deleted);
+    };
+    if account.lamports == 0 || account.owner != sablier_thread_program::ID {
+        return Some(RefreshedThread::Deleted);
+    }
+    VersionedThread::try_from(account.data)
+        .ok()
+        .map(|thread| RefreshedThread::Live(Box::new(thread)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_and_reassigned_threads_are_deleted() {
+        assert!(matches!(
+            refreshed_thread(None),
+            Some(RefreshedThread::Deleted)
+        ));
+        let reassigned = Account {
+            lamports: 1,
+            owner: Pubkey::new_unique(),
+            ..Account::default()
+        };
+        assert!(matches!(
+            refreshed_thread(Some(reassigned)),
+            Some(RefreshedThread::Deleted)
+        ));
+
+        // A thread account which does not deserialize is not taken for a deleted one.
+        let corrupted = Account {
+            lamports: 1,
+            owner: sablier_thread_program::ID,
+            data: vec![0; 8],
+            ..Account::default()
+        };
+        assert!(refreshed_thread(Some(corrupted)).is_none());
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    fmt::Debug,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use log::info;
+
+use crate::{
+    builders::InjectedInstructions, config::PluginConfig, executors::Executors,
+    observers::Observers,
+};
+
+/// The interval at which the config file is checked for changes.
+static RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Reloads the config file when it changes, or when asked to by the admin interface.
+/// Only the settings which are safe to change at runtime may differ, a new config changing any other
+/// setting is rejected as a whole.
+pub struct ConfigReloader {
+    path: PathBuf,
+    observers: Arc<Observers>,
+    executors: Arc<Executors>,
+    last_modified: Mutex<Option<SystemTime>>,
+}
+
+impl ConfigReloader {
+    pub fn new(path: PathBuf, observers: Arc<Observers>, executors: Arc<Executors>) -> Self {
+        let last_modified = modified_at(&path);
+        Self {
+            path,
+            observers,
+            executors,
+            last_modified: Mutex::new(last_modified),
+        }
+    }
+
+    /// Reads, validates and applies the config file. Nothing is applied if any of it is invalid.
+    pub async fn reload(&self) -> Result<(), String> {
+        let config = PluginConfig::read_from(&self.path).map_err(|err| err.to_string())?;
+        InjectedInstructions::validate(&config).map_err(|err| err.to_string())?;
+        let changes = self.executors.tx.config().restart_required_changes(&config);
+        if !changes.is_empty() {
+            return Err(format!(
+                "the config changes settings which require a restart: {}",
+                changes.join(", ")
+            ));
+        }
+
+        self.observers
+            .thread
+            .thread_filter
+            .update(&config.thread_filter)
+            .await;
+        self.executors.tx.apply_config(config);
+        Ok(())
+    }
+
+    /// Reloads the config file whenever its modification time changes.
+    pub async fn watch(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(RELOAD_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let last_modified = modified_at(&self.path);
+            {
+                let mut previous = self.last_modified.lock().unwrap();
+                if *previous == last_modified {
+                    continue;
+                }
+                *previous = last_modified;
+            }
+            self.log_reload().await;
+        }
+    }
+
+    /// Reloads the config file and logs the outcome.
+    async fn log_reload(&self) {
+        match self.reload().await {
+            Ok(()) => info!("Reloaded config: {}", self.path.display()),
+            Err(err) => info!(
+                "Rejected config reload: {}, keeping the current config. {}",
+                self.path.display(),
+                err
+            ),
+        }
+    }
+}
+
+fn modified_at(path: &PathBuf) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+impl Debug for ConfigReloader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "config reloader: {}", self.path.display())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use anchor_lang::AccountSerialize;
+    use sablier_thread_program::state::{Thread, Trigger};
+    use sablier_utils::thread::ClockData;
+    use solana_sdk::pubkey::Pubkey;
+
+    use super::*;
+    use crate::{
+        config::ThreadFilterConfig,
+        events::decode_thread,
+        health::Health,
+        metrics::Metrics,
+        observers::state::{AccountTriggers, SubmittedThreads, ThreadFilter, ThreadPipelines},
+        shutdown::Shutdown,
+    };
+
+    /// Returns a reloader of a config file in the temp dir, and the executors it applies to.
+    fn new_reloader(config: &PluginConfig) -> (ConfigReloader, Arc<Executors>, PathBuf) {
+        let metrics = Arc::new(Metrics::new());
+        let shutdown = Arc::new(Shutdown::default());
+        let submitted_threads = Arc::new(SubmittedThreads::new(config.dedupe_window_slots));
+        let account_triggers = Arc::new(AccountTriggers::default());
+        let thread_pipelines = Arc::new(ThreadPipelines::default());
+        let thread_filter = Arc::new(ThreadFilter::new(&config.thread_filter));
+        let observers = Arc::new(Observers::new(
+            config,
+            metrics.clone(),
+            shutdown.clone(),
+            submitted_threads.clone(),
+            account_triggers.clone(),
+            thread_pipelines.clone(),
+            thread_filter.clone(),
+        ));
+        let executors = Arc::new(Executors::new(
+            config.clone(),
+            metrics,
+            Arc::new(Health::default()),
+            shutdown,
+            submitted_threads,
+            account_triggers,
+            thread_pipelines,
+            thread_filter,
+        ));
+        let path = std::env::temp_dir().join(format!("sablier-config-{}", Pubkey::new_unique()));
+        let reloader = ConfigReloader::new(path.clone(), observers, executors.clone());
+        (reloader, executors, path)
+    }
+
+    #[tokio::test]
+    async fn reload_applies_runtime_settings_and_rejects_the_others() {
+        let config = PluginConfig::default();
+        let (reloader, executors, path) = new_reloader(&config);
+
+        let reloaded = PluginConfig {
+            rpc_urls: vec!["http://rpc.example.com".to_string()],
+            ..config.clone()
+        };
+        std::fs::write(&path, serde_json::to_vec(&reloaded).unwrap()).unwrap();
+        reloader.reload().await.unwrap();
+        assert_eq!(executors.tx.config().rpc_urls, reloaded.rpc_urls);
+
+        let restarted = PluginConfig {
+            keypath: Some("other.json".to_string()),
+            executor: crate::config::ExecutorConfig {
+                build_queue_size: 1,
+                ..reloaded.executor.clone()
+            },
+            ..reloaded.clone()
+        };
+        std::fs::write(&path, serde_json::to_vec(&restarted).unwrap()).unwrap();
+        let err = reloader.reload().await.unwrap_err();
+        assert!(err.contains("keypath"));
+        // Nothing of the rejected config is applied.
+        assert_eq!(
+            executors.tx.config().executor.build_queue_size,
+            reloaded.executor.build_queue_size
+        );
+
+        std::fs::write(&path, b"{").unwrap();
+        assert!(reloader.reload().await.is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn reloaded_denylist_stops_the_execution_of_indexed_threads() {
+        let config = PluginConfig::default();
+        let (reloader, executors, path) = new_reloader(&config);
+        let thread = Thread {
+            authority: Pubkey::new_unique(),
+            bump: 0,
+            created_at: ClockData {
+                slot: 0,
+                epoch: 0,
+                unix_timestamp: 0,
+            },
+            domain: None,
+            exec_context: None,
+            fee: 1_000,
+            id: b"thread".to_vec(),
+            instructions: vec![],
+            next_instruction: None,
+            paused: false,
+            rate_limit: 1,
+            trigger: Trigger::Now,
+        };
+        let mut data = vec![];
+        thread.try_serialize(&mut data).unwrap();
+        let thread = decode_thread(&data).unwrap();
+        let thread_pubkey = thread.pubkey();
+        executors
+            .tx
+            .executable_threads
+            .add(HashSet::from([(thread_pubkey, 10)]))
+            .await;
+
+        let reloaded = PluginConfig {
+            thread_filter: ThreadFilterConfig {
+                allowlist: vec![],
+                denylist: vec![thread_pubkey.to_string()],
+            },
+            ..config
+        };
+        std::fs::write(&path, serde_json::to_vec(&reloaded).unwrap()).unwrap();
+        reloader.reload().await.unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        // The thread was indexed before the reload, it is dropped rather than built once due.
+        assert!(executors
+            .tx
+            .clone()
+            .try_build_thread_exec_tx(11, 10, thread, thread_pubkey, 0)
+            .await
+            .is_none());
+        assert!(executors.tx.executable_threads.due_slots().await.is_empty());
+    }
+}
@@ -0,0 +1,67 @@
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use log::info;
+
+use crate::{config::HealthConfig, health::Health, metrics::Metrics, observers::Observers};
+
+/// The state shared by the request handlers.
+#[derive(Clone)]
+pub struct ServerState {
+    pub config: HealthConfig,
+    pub metrics: Arc<Metrics>,
+    pub health: Arc<Health>,
+    pub observers: Arc<Observers>,
+}
+
+/// Serves the metrics on `GET /metrics` and the health check on `GET /health`.
+pub async fn serve(bind_address: IpAddr, port: u16, state: ServerState) {
+    let addr = SocketAddr::new(bind_address, port);
+    let make_service = make_service_fn(move |_conn| {
+        let state = state.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(req, state.clone()))) }
+    });
+
+    match Server::try_bind(&addr) {
+        Err(err) => info!("Failed to bind the http server on {}: {:?}", addr, err),
+        Ok(builder) => {
+            info!("Serving metrics and health on {}", addr);
+            if let Err(err) = builder.serve(make_service).await {
+                info!("Http server error: {:?}", err);
+            }
+        }
+    }
+}
+
+async fn handle(req: Request<Body>, state: ServerState) -> Result<Response<Body>, Infallible> {
+    let response = match (req.method(), req.uri().path()) {
+        (&Method::GET, "/metrics") => Response::builder()
+            .status(StatusCode::OK)
+            .header(hyper::header::CONTENT_TYPE, prometheus::TEXT_FORMAT)
+            .body(Body::from(state.metrics.encode())),
+        (&Method::GET, "/health") => {
+            let tracked_threads = state.observers.thread.tracked_threads().await;
+            let report = state.health.report(tracked_threads, &state.config);
+            let status = if report.healthy {
+                StatusCode::OK
+            } else {
+                StatusCode::SERVICE_UNAVAILABLE
+            };
+            Response::builder()
+                .status(status)
+                .header(hyper::header::CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::to_vec(&report).unwrap()))
+        }
+        _ => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty()),
+    };
+    Ok(response.unwrap())
+}
@@ -0,0 +1,125 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use tokio::sync::Notify;
+
+/// Signals the plugin is unloading and tracks the in-flight executions, so they can be drained
+/// before the runtime is dropped.
+#[derive(Debug, Default)]
+pub struct Shutdown {
+    stopping: AtomicBool,
+    in_flight: AtomicUsize,
+    drained: Notify,
+}
+
+impl Shutdown {
+    pub fn is_stopping(&self) -> bool {
+        self.stopping.load(Ordering::SeqCst)
+    }
+
+    /// Stops accepting new executions.
+    pub fn signal(&self) {
+        self.stopping.store(true, Ordering::SeqCst);
+    }
+
+    /// Registers an execution, which is in-flight until the returned guard is dropped.
+    /// Returns None once the shutdown is signaled.
+    pub fn track(self: &Arc<Self>) -> Option<InFlight> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = InFlight(self.clone());
+        if self.is_stopping() {
+            return None;
+        }
+        Some(guard)
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Waits for the in-flight executions to complete, returns false if the timeout expired first.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, async {
+            loop {
+                let drained = self.drained.notified();
+                tokio::pin!(drained);
+                drained.as_mut().enable();
+                if self.in_flight() == 0 {
+                    return;
+                }
+                drained.await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+}
+
+pub struct InFlight(Arc<Shutdown>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if self.0.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.drained.notify_waiters();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Spawns an execution holding an in-flight guard, setting the flag once it ran to completion.
+    fn spawn_execution(shutdown: &Arc<Shutdown>, duration: Duration) -> Arc<AtomicBool> {
+        let completed = Arc::new(AtomicBool::new(false));
+        let guard = shutdown.track().unwrap();
+        tokio::spawn({
+            let completed = completed.clone();
+            async move {
+                let _guard = guard;
+                tokio::time::sleep(duration).await;
+                completed.store(true, Ordering::SeqCst);
+            }
+        });
+        completed
+    }
+
+    #[tokio::test]
+    async fn drain_waits_for_in_flight_executions() {
+        let shutdown = Arc::new(Shutdown::default());
+        let executions: Vec<Arc<AtomicBool>> = (1..=3)
+            .map(|i| spawn_execution(&shutdown, Duration::from_millis(20 * i)))
+            .collect();
+
+        shutdown.signal();
+        assert!(shutdown.track().is_none());
+        assert!(shutdown.drain(Duration::from_secs(5)).await);
+        assert!(executions
+            .iter()
+            .all(|completed| completed.load(Ordering::SeqCst)));
+        assert_eq!(shutdown.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn drain_gives_up_after_the_timeout() {
+        let shutdown = Arc::new(Shutdown::default());
+        let completed = spawn_execution(&shutdown, Duration::from_secs(5));
+
+        shutdown.signal();
+        assert!(!shutdown.drain(Duration::from_millis(20)).await);
+        assert!(!completed.load(Ordering::SeqCst));
+        assert_eq!(shutdown.in_flight(), 1);
+    }
+
+    #[tokio::test]
+    async fn drain_returns_immediately_when_idle() {
+        let shutdown = Arc::new(Shutdown::default());
+        shutdown.signal();
+        assert!(shutdown.drain(Duration::ZERO).await);
+    }
+}
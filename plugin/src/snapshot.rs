@@ -0,0 +1,339 @@
+use std::{collections::HashSet, fmt::Write, path::Path, sync::Arc, time::Duration};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use log::info;
+use sablier_thread_program::state::{Equality, Trigger};
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use tokio::{
+    io::AsyncWriteExt,
+    net::{UnixListener, UnixStream},
+    sync::broadcast::{self, error::RecvError, Receiver, Sender},
+};
+
+use crate::{config::SnapshotStreamConfig, metrics::Metrics, observers::thread::ThreadObserver};
+
+/// The delay before binding the socket again after a failure.
+static BIND_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Messages written to the snapshot stream consumers, one JSON object per line.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SnapshotMessage {
+    /// A thread which became executable in the slot.
+    Executable {
+        slot: u64,
+        thread: String,
+        /// Unknown for the threads restored from the persisted state, until they are observed again.
+        trigger: Option<TriggerSummary>,
+        /// The watched data of the account trigger the thread was last triggered with.
+        observed_account: Option<ObservedAccount>,
+    },
+    /// The consumer fell behind the stream and the older messages were skipped.
+    Skipped { count: u64 },
+}
+
+/// The trigger of an executable thread.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TriggerSummary {
+    Account {
+        address: String,
+        offset: u64,
+        size: u64,
+    },
+    Cron {
+        schedule: String,
+        skippable: bool,
+    },
+    Now,
+    Slot {
+        slot: u64,
+    },
+    Epoch {
+        epoch: u64,
+    },
+    Timestamp {
+        unix_ts: i64,
+    },
+    Pyth {
+        /// The hex encoded price feed id.
+        feed_id: String,
+        equality: &'static str,
+        limit: i64,
+    },
+    Periodic {
+        delay: u64,
+    },
+    Interval {
+        interval_seconds: u64,
+        skippable: bool,
+    },
+}
+
+impl From<Trigger> for TriggerSummary {
+    fn from(trigger: Trigger) -> Self {
+        match trigger {
+            Trigger::Account {
+                address,
+                offset,
+                size,
+            } => Self::Account {
+                address: address.to_string(),
+                offset,
+                size,
+            },
+            Trigger::Cron {
+                schedule,
+                skippable,
+            } => Self::Cron {
+                schedule,
+                skippable,
+            },
+            Trigger::Now => Self::Now,
+            Trigger::Slot { slot } => Self::Slot { slot },
+            Trigger::Epoch { epoch } => Self::Epoch { epoch },
+            Trigger::Timestamp { unix_ts } => Self::Timestamp { unix_ts },
+            Trigger::Pyth {
+                feed_id,
+                equality,
+                limit,
+            } => Self::Pyth {
+                feed_id: feed_id.iter().fold(String::new(), |mut hex, byte| {
+                    let _ = write!(hex, "{:02x}", byte);
+                    hex
+                }),
+                equality: match equality {
+                    Equality::GreaterThanOrEqual => "gte",
+                    Equality::LessThanOrEqual => "lte",
+                },
+                limit,
+            },
+            Trigger::Periodic { delay } => Self::Periodic { delay },
+            Trigger::Interval {
+                interval_seconds,
+                skippable,
+            } => Self::Interval {
+                interval_seconds,
+                skippable,
+            },
+        }
+    }
+}
+
+/// The watched range of an account trigger, base64 encoded.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ObservedAccount {
+    pub address: String,
+    pub offset: u64,
+    pub data: String,
+}
+
+/// Publishes the threads executable in each slot to the consumers connected to a unix socket,
+/// e.g. external schedulers reusing the plugin's observation but submitting the transactions themselves.
+/// Each consumer receives the messages published while it is connected, it is dropped on a write error and may reconnect.
+pub struct SnapshotStream {
+    config: SnapshotStreamConfig,
+    sender: Sender<Arc<str>>,
+    metrics: Arc<Metrics>,
+}
+
+impl SnapshotStream {
+    pub fn new(config: &SnapshotStreamConfig, metrics: Arc<Metrics>) -> Self {
+        let (sender, _) = broadcast::channel(config.capacity.max(1));
+        Self {
+            config: config.clone(),
+            sender,
+            metrics,
+        }
+    }
+
+    /// Publishes the threads which became executable in the slot, with their trigger and observed account data.
+    pub fn publish(
+        &self,
+        observer: &ThreadObserver,
+        slot: u64,
+        executable_threads: &HashSet<Pubkey>,
+    ) {
+        for thread_pubkey in executable_threads {
+            let trigger = observer.thread_triggers.get(thread_pubkey);
+            let observed_account = match trigger {
+                Some(Trigger::Account {
+                    address, offset, ..
+                }) => observer
+                    .account_triggers
+                    .observed_data(&address, thread_pubkey)
+                    .map(|data| ObservedAccount {
+                        address: address.to_string(),
+                        offset,
+                        data: STANDARD.encode(data),
+                    }),
+                _ => None,
+            };
+            self.send(&SnapshotMessage::Executable {
+                slot,
+                thread: thread_pubkey.to_string(),
+                trigger: trigger.map(TriggerSummary::from),
+                observed_account,
+            });
+        }
+    }
+
+    fn send(&self, message: &SnapshotMessage) {
+        // Messages published without any consumer connected are dropped.
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+        match serde_json::to_string(message) {
+            Err(err) => info!("Failed to serialize snapshot message: {:?}", err),
+            Ok(line) => {
+                self.metrics.snapshot_messages_published.inc();
+                let _ = self.sender.send(line.into());
+            }
+        }
+    }
+
+    /// Accepts the consumers on the unix socket, binding it again if it fails.
+    pub async fn serve(self: Arc<Self>) {
+        loop {
+            match bind(&self.config.socket_path) {
+                Err(err) => info!(
+                    "Failed to bind snapshot stream: {} err: {:?}",
+                    self.config.socket_path, err
+                ),
+                Ok(listener) => {
+                    info!("Snapshot stream listening on {}", self.config.socket_path);
+                    loop {
+                        match listener.accept().await {
+                            Err(err) => {
+                                info!("Failed to accept snapshot consumer: {:?}", err);
+                                break;
+                            }
+                            Ok((stream, _)) => {
+                                tokio::spawn(self.clone().stream(stream, self.sender.subscribe()));
+                            }
+                        }
+                    }
+                }
+            }
+            tokio::time::sleep(BIND_RETRY_DELAY).await;
+        }
+    }
+
+    /// Writes the published messages to the consumer until it disconnects.
+    async fn stream(self: Arc<Self>, mut stream: UnixStream, mut receiver: Receiver<Arc<str>>) {
+        info!("Snapshot consumer connected");
+        self.metrics.snapshot_consumers.inc();
+        loop {
+            let line = match receiver.recv().await {
+                Ok(line) => line,
+                Err(RecvError::Lagged(count)) => {
+                    self.metrics.snapshot_messages_skipped.inc_by(count);
+                    match serde_json::to_string(&SnapshotMessage::Skipped { count }) {
+                        Ok(line) => line.into(),
+                        Err(_) => continue,
+                    }
+                }
+                Err(RecvError::Closed) => break,
+            };
+            let written = async {
+                stream.write_all(line.as_bytes()).await?;
+                stream.write_all(b"\n").await
+            }
+            .await;
+            if let Err(err) = written {
+                info!("Snapshot consumer disconnected: {:?}", err);
+                break;
+            }
+        }
+        self.metrics.snapshot_consumers.dec();
+    }
+}
+
+/// Binds the unix socket, removing the one left over by a previous run.
+fn bind(socket_path: &str) -> std::io::Result<UnixListener> {
+    if Path::new(socket_path).exists() {
+        std::fs::remove_file(socket_path)?;
+    }
+    UnixListener::bind(socket_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    use super::*;
+    use crate::observers::state::AccountTrigger;
+
+    #[tokio::test]
+    async fn executable_threads_are_streamed_to_the_consumers() {
+        let socket_path =
+            std::env::temp_dir().join(format!("sablier-{}.sock", Pubkey::new_unique()));
+        let snapshot_stream = Arc::new(SnapshotStream::new(
+            &SnapshotStreamConfig {
+                socket_path: socket_path.to_string_lossy().into_owned(),
+                ..SnapshotStreamConfig::default()
+            },
+            Arc::new(Metrics::new()),
+        ));
+        tokio::spawn(snapshot_stream.clone().serve());
+
+        let observer = ThreadObserver::default();
+        let (address, thread_pubkey) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let trigger = Trigger::Account {
+            address,
+            offset: 0,
+            size: 4,
+        };
+        observer.thread_triggers.set(thread_pubkey, trigger);
+        observer.account_triggers.add(
+            address,
+            thread_pubkey,
+            AccountTrigger {
+                offset: 0,
+                size: 4,
+                data_hash: None,
+                observed_data: None,
+                balance: None,
+            },
+        );
+        observer
+            .account_triggers
+            .observe(&address, &Pubkey::default(), 1, &[1, 2, 3, 4, 5]);
+
+        let stream = loop {
+            match UnixStream::connect(&socket_path).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        };
+        while snapshot_stream.sender.receiver_count() == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        snapshot_stream.publish(&observer, 42, &HashSet::from([thread_pubkey]));
+
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).await.unwrap();
+        let message: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(
+            message,
+            serde_json::json!({
+                "type": "executable",
+                "slot": 42,
+                "thread": thread_pubkey.to_string(),
+                "trigger": {
+                    "kind": "account",
+                    "address": address.to_string(),
+                    "offset": 0,
+                    "size": 4,
+                },
+                "observed_account": {
+                    "address": address.to_string(),
+                    "offset": 0,
+                    "data": STANDARD.encode([1, 2, 3, 4]),
+                },
+            })
+        );
+        let _ = std::fs::remove_file(socket_path);
+    }
+}
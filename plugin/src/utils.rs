@@ -1,4 +1,11 @@
-use solana_sdk::signature::{read_keypair_file, Keypair};
+use sablier_utils::pyth::{get_oracle_key, FeedId};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair},
+};
+
+use crate::config::{Commitment, PythConfig};
 
 pub fn read_or_new_keypair(keypath: Option<String>) -> Keypair {
     match keypath {
@@ -6,3 +13,16 @@ pub fn read_or_new_keypair(keypath: Option<String>) -> Keypair {
         None => Keypair::new(),
     }
 }
+
+/// Returns the price account of the feed, on the shard configured for it.
+pub fn get_price_account(config: &PythConfig, feed_id: FeedId) -> Pubkey {
+    get_oracle_key(config.shard_id(&feed_id), feed_id)
+}
+
+pub fn commitment_config(commitment: Commitment) -> CommitmentConfig {
+    match commitment {
+        Commitment::Processed => CommitmentConfig::processed(),
+        Commitment::Confirmed => CommitmentConfig::confirmed(),
+        Commitment::Finalized => CommitmentConfig::finalized(),
+    }
+}
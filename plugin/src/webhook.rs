@@ -0,0 +1,224 @@
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use log::info;
+use serde::Serialize;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use tokio::sync::mpsc::{self, error::TrySendError, Receiver, Sender};
+
+use crate::{config::WebhookConfig, error::PluginError, metrics::Metrics};
+
+/// The delay before the first retry of a failed delivery, doubled on each retry.
+static RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+
+/// Execution outcomes posted to the webhook.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WebhookEvent {
+    Built {
+        thread: String,
+        slot: u64,
+        signature: String,
+        compute_units: Option<u32>,
+    },
+    Sent {
+        thread: String,
+        slot: u64,
+        signature: String,
+    },
+    Confirmed {
+        thread: String,
+        slot: u64,
+        signature: String,
+    },
+    Finalized {
+        thread: String,
+        slot: u64,
+        signature: String,
+    },
+    Expired {
+        thread: String,
+        slot: u64,
+        signature: String,
+    },
+    Failed {
+        thread: String,
+        slot: u64,
+        signature: String,
+        error: String,
+    },
+    SimulationFailed {
+        thread: String,
+        slot: u64,
+        consecutive_failures: u32,
+        error: Option<String>,
+    },
+}
+
+impl WebhookEvent {
+    pub fn built(
+        thread: &Pubkey,
+        slot: u64,
+        signature: &Signature,
+        compute_units: Option<u32>,
+    ) -> Self {
+        Self::Built {
+            thread: thread.to_string(),
+            slot,
+            signature: signature.to_string(),
+            compute_units,
+        }
+    }
+
+    pub fn sent(thread: &Pubkey, slot: u64, signature: &Signature) -> Self {
+        Self::Sent {
+            thread: thread.to_string(),
+            slot,
+            signature: signature.to_string(),
+        }
+    }
+
+    pub fn confirmed(thread: &Pubkey, slot: u64, signature: &Signature) -> Self {
+        Self::Confirmed {
+            thread: thread.to_string(),
+            slot,
+            signature: signature.to_string(),
+        }
+    }
+
+    pub fn finalized(thread: &Pubkey, slot: u64, signature: &Signature) -> Self {
+        Self::Finalized {
+            thread: thread.to_string(),
+            slot,
+            signature: signature.to_string(),
+        }
+    }
+
+    pub fn expired(thread: &Pubkey, slot: u64, signature: &Signature) -> Self {
+        Self::Expired {
+            thread: thread.to_string(),
+            slot,
+            signature: signature.to_string(),
+        }
+    }
+
+    pub fn failed(thread: &Pubkey, slot: u64, signature: &Signature, error: String) -> Self {
+        Self::Failed {
+            thread: thread.to_string(),
+            slot,
+            signature: signature.to_string(),
+            error,
+        }
+    }
+
+    pub fn simulation_failed(
+        thread: &Pubkey,
+        slot: u64,
+        consecutive_failures: u32,
+        error: Option<String>,
+    ) -> Self {
+        Self::SimulationFailed {
+            thread: thread.to_string(),
+            slot,
+            consecutive_failures,
+            error,
+        }
+    }
+}
+
+/// Posts execution events to the configured webhook from a dedicated task.
+/// Events are queued without waiting, and dropped when the queue is full.
+pub struct Webhook {
+    config: WebhookConfig,
+    client: reqwest::Client,
+    sender: Sender<WebhookEvent>,
+    receiver: Mutex<Option<Receiver<WebhookEvent>>>,
+    metrics: Arc<Metrics>,
+}
+
+impl Webhook {
+    pub fn new(config: &WebhookConfig, metrics: Arc<Metrics>) -> Result<Self, PluginError> {
+        let (sender, receiver) = mpsc::channel(config.queue_size.max(1));
+        Ok(Self {
+            config: config.clone(),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_millis(config.timeout_ms))
+                .build()?,
+            sender,
+            receiver: Mutex::new(Some(receiver)),
+            metrics,
+        })
+    }
+
+    pub fn notify(&self, event: WebhookEvent) {
+        match self.sender.try_send(event) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) | Err(TrySendError::Closed(_)) => {
+                self.metrics.webhook_events_dropped.inc();
+            }
+        }
+    }
+
+    /// Delivers the queued events one at a time until the plugin is dropped.
+    pub async fn deliver(self: Arc<Self>) {
+        let Some(mut receiver) = self.receiver.lock().unwrap().take() else {
+            return;
+        };
+        while let Some(event) = receiver.recv().await {
+            if let Err(err) = self.post(&event).await {
+                info!(
+                    "Failed to deliver webhook event: {:?} err: {:?}",
+                    event, err
+                );
+                self.metrics.webhook_events_failed.inc();
+            }
+        }
+    }
+
+    async fn post(&self, event: &WebhookEvent) -> Result<(), PluginError> {
+        let mut attempt = 0;
+        loop {
+            let mut request = self.client.post(&self.config.url).json(event);
+            if let Some(token) = self.config.bearer_token.as_ref() {
+                request = request.bearer_auth(token);
+            }
+            let result = request.send().await.and_then(|res| res.error_for_status());
+            match result {
+                Ok(_) => return Ok(()),
+                Err(err) if is_transient(&err) && attempt < self.config.max_retries => {
+                    attempt += 1;
+                    tokio::time::sleep(retry_delay(attempt)).await;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+}
+
+impl Debug for Webhook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "webhook")
+    }
+}
+
+fn is_transient(err: &reqwest::Error) -> bool {
+    match err.status() {
+        Some(status) => {
+            status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        }
+        None => err.is_timeout() || err.is_connect() || err.is_request(),
+    }
+}
+
+/// Returns an exponential delay with up to 50% of jitter, so failed deliveries don't retry in lockstep.
+fn retry_delay(attempt: u32) -> Duration {
+    let delay = RETRY_BASE_DELAY.saturating_mul(2u32.saturating_pow(attempt - 1));
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    delay + delay.mul_f64((nanos % 1_000) as f64 / 2_000.0)
+}
@@ -0,0 +1,224 @@
+use std::{
+    fmt::Debug,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use futures::{
+    stream::{select_all, BoxStream},
+    StreamExt,
+};
+use log::info;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient},
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+};
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+use crate::{
+    config::WebsocketFallbackConfig, error::PluginError, metrics::Metrics, observers::Observers,
+    refresh::refresh_accounts,
+};
+
+/// The interval at which the fallback checks whether the geyser stream resumed.
+static RESUME_CHECK_INTERVAL: Duration = Duration::from_millis(400);
+
+/// Streams the thread accounts and the accounts watched by account triggers over the RPC websocket
+/// after the slot notifications skipped slots, so the updates missed by geyser are not lost.
+/// The subscriptions are closed once geyser delivers account updates again and the missed ones are reconciled.
+pub struct WebsocketFallback {
+    config: WebsocketFallbackConfig,
+    last_slot: AtomicU64,
+    last_geyser_slot: AtomicU64,
+    active: AtomicBool,
+    metrics: Arc<Metrics>,
+}
+
+impl WebsocketFallback {
+    pub fn new(config: &WebsocketFallbackConfig, metrics: Arc<Metrics>) -> Self {
+        Self {
+            config: config.clone(),
+            last_slot: AtomicU64::new(0),
+            last_geyser_slot: AtomicU64::new(0),
+            active: AtomicBool::new(false),
+            metrics,
+        }
+    }
+
+    /// Records a processed slot. Returns the slot if it is more than `gap_slots` past the previous one
+    /// and the fallback is not running yet, the caller is then expected to run it.
+    pub fn observe_slot(&self, slot: u64) -> Option<u64> {
+        let last_slot = self.last_slot.fetch_max(slot, Ordering::SeqCst);
+        if last_slot == 0 || slot <= last_slot.saturating_add(self.config.gap_slots) {
+            return None;
+        }
+        if self.active.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+        Some(slot)
+    }
+
+    /// Records an account update delivered by geyser.
+    pub fn observe_account(&self, slot: u64) {
+        self.last_geyser_slot.fetch_max(slot, Ordering::SeqCst);
+    }
+
+    /// Returns true once geyser delivered an account update past the gap.
+    fn geyser_resumed(&self, gap_slot: u64) -> bool {
+        self.last_geyser_slot.load(Ordering::SeqCst) > gap_slot
+    }
+
+    /// Passes the account updates received over the websocket to `handle` until geyser resumes,
+    /// then fetches the indexed threads and the watched accounts once and closes the subscriptions.
+    pub async fn run<F>(
+        self: Arc<Self>,
+        gap_slot: u64,
+        client: Arc<RpcClient>,
+        observers: Arc<Observers>,
+        handle: F,
+    ) where
+        F: Fn(Pubkey, Account, u64),
+    {
+        self.metrics.websocket_fallbacks.inc();
+        info!(
+            "Slot notifications skipped to slot: {}, opening websocket fallback: {}",
+            gap_slot, self.config.url
+        );
+        if let Err(err) = self.stream(gap_slot, &client, &observers, &handle).await {
+            info!("Websocket fallback failed: {:?}", err);
+        }
+        self.active.store(false, Ordering::SeqCst);
+        info!("Closed websocket fallback");
+    }
+
+    async fn stream<F>(
+        &self,
+        gap_slot: u64,
+        client: &RpcClient,
+        observers: &Observers,
+        handle: &F,
+    ) -> Result<(), PluginError>
+    where
+        F: Fn(Pubkey, Account, u64),
+    {
+        let pubsub = PubsubClient::new(&self.config.url).await?;
+        let account_config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(CommitmentConfig::processed()),
+            ..RpcAccountInfoConfig::default()
+        };
+
+        let mut streams: Vec<BoxStream<'_, (Pubkey, Account, u64)>> = vec![];
+        let mut unsubscribes = vec![];
+        let (stream, unsubscribe) = pubsub
+            .program_subscribe(
+                &sablier_thread_program::ID,
+                Some(RpcProgramAccountsConfig {
+                    account_config: account_config.clone(),
+                    ..RpcProgramAccountsConfig::default()
+                }),
+            )
+            .await?;
+        streams.push(
+            stream
+                .filter_map(|response| async move {
+                    let key = Pubkey::from_str(&response.value.pubkey).ok()?;
+                    let account = response.value.account.decode::<Account>()?;
+                    Some((key, account, response.context.slot))
+                })
+                .boxed(),
+        );
+        unsubscribes.push(unsubscribe);
+        for address in observers.thread.account_triggers.addresses() {
+            let (stream, unsubscribe) = pubsub
+                .account_subscribe(&address, Some(account_config.clone()))
+                .await?;
+            streams.push(
+                stream
+                    .filter_map(move |response| async move {
+                        let account = response.value.decode::<Account>()?;
+                        Some((address, account, response.context.slot))
+                    })
+                    .boxed(),
+            );
+            unsubscribes.push(unsubscribe);
+        }
+
+        let mut updates = select_all(streams);
+        while !self.geyser_resumed(gap_slot) {
+            match tokio::time::timeout(RESUME_CHECK_INTERVAL, updates.next()).await {
+                Err(_) => continue,
+                // The connection closed, the missed updates are still reconciled below.
+                Ok(None) => break,
+                Ok(Some((key, account, slot))) => {
+                    self.metrics.websocket_fallback_updates.inc();
+                    handle(key, account, slot);
+                }
+            }
+        }
+
+        let reconciled = self.reconcile(client, observers, handle).await;
+        drop(updates);
+        for unsubscribe in unsubscribes {
+            unsubscribe().await;
+        }
+        pubsub.shutdown().await?;
+        reconciled
+    }
+
+    /// Fetches the indexed threads and the watched accounts, in case they changed before the subscriptions were opened.
+    /// A missing account is handled like a closed one.
+    async fn reconcile<F>(
+        &self,
+        client: &RpcClient,
+        observers: &Observers,
+        handle: &F,
+    ) -> Result<(), PluginError>
+    where
+        F: Fn(Pubkey, Account, u64),
+    {
+        let mut addresses = observers.thread.indexed_threads.keys();
+        addresses.extend(observers.thread.account_triggers.addresses());
+        refresh_accounts(client, &addresses, |address, account, slot| {
+            handle(address, account.unwrap_or_default(), slot)
+        })
+        .await
+    }
+}
+
+impl Debug for WebsocketFallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "websocket fallback")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gap_in_slot_notifications_opens_a_single_fallback() {
+        let fallback = WebsocketFallback::new(
+            &WebsocketFallbackConfig {
+                url: String::new(),
+                gap_slots: 10,
+            },
+            Arc::new(Metrics::new()),
+        );
+        assert_eq!(fallback.observe_slot(100), None);
+        assert_eq!(fallback.observe_slot(110), None);
+        assert_eq!(fallback.observe_slot(121), Some(121));
+        // Already running.
+        assert_eq!(fallback.observe_slot(200), None);
+
+        fallback.observe_account(121);
+        assert!(!fallback.geyser_resumed(121));
+        fallback.observe_account(122);
+        assert!(fallback.geyser_resumed(121));
+    }
+}
@@ -3,11 +3,77 @@ use {
     solana_geyser_plugin_interface::geyser_plugin_interface::{
         GeyserPluginError, Result as PluginResult,
     },
-    std::{fs::File, path::Path},
+    std::{
+        collections::HashMap,
+        fs::File,
+        net::{IpAddr, Ipv4Addr},
+        path::Path,
+    },
 };
 
 static DEFAULT_TRANSACTION_TIMEOUT_THRESHOLD: u64 = 150;
 static DEFAULT_THREAD_COUNT: usize = 10;
+static DEFAULT_PRIORITY_FEE_PERCENTILE: u8 = 75;
+static DEFAULT_PRIORITY_FEE_THREAD_FEE_PERCENTAGE: u64 = 50;
+static DEFAULT_BLOCKHASH_MAX_AGE_MS: u64 = 2_000;
+static DEFAULT_SHUTDOWN_TIMEOUT_MS: u64 = 10_000;
+static DEFAULT_THREAD_FETCH_TTL_SLOTS: u64 = 150;
+static DEFAULT_MAX_RESUBMITS: u32 = 3;
+static DEFAULT_DEDUPE_WINDOW_SLOTS: u64 = 150;
+static DEFAULT_BACKOFF_FAILURE_THRESHOLD: u32 = 3;
+static DEFAULT_BACKOFF_MAX_SLOTS: u64 = 1_024;
+static DEFAULT_MAX_PROCESSED_SLOT_AGE_SECS: u64 = 30;
+static DEFAULT_MAX_RPC_HEALTH_AGE_SECS: u64 = 30;
+static DEFAULT_PERSISTENCE_INTERVAL_SECS: u64 = 60;
+static DEFAULT_COMPUTE_UNIT_BUFFER: u32 = 1_000;
+static DEFAULT_STATIC_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+static DEFAULT_BUILD_CONCURRENCY: usize = 16;
+static DEFAULT_SIMULATION_CONCURRENCY: usize = 16;
+static DEFAULT_BUILD_QUEUE_SIZE: usize = 1_024;
+static DEFAULT_BUILD_TIMEOUT_MS: u64 = 2_000;
+static DEFAULT_RETRY_QUEUE_SIZE: usize = 4_096;
+static DEFAULT_WEBHOOK_QUEUE_SIZE: usize = 1_024;
+static DEFAULT_WEBHOOK_MAX_RETRIES: u32 = 3;
+static DEFAULT_WEBHOOK_TIMEOUT_MS: u64 = 5_000;
+static DEFAULT_SNAPSHOT_STREAM_CAPACITY: usize = 4_096;
+static DEFAULT_WEBSOCKET_FALLBACK_URL: &str = "ws://127.0.0.1:8900";
+static DEFAULT_WEBSOCKET_FALLBACK_GAP_SLOTS: u64 = 32;
+static DEFAULT_CIRCUIT_BREAKER_WINDOW: usize = 50;
+static DEFAULT_CIRCUIT_BREAKER_MIN_SAMPLES: usize = 20;
+static DEFAULT_CIRCUIT_BREAKER_FAILURE_RATIO: f64 = 0.5;
+static DEFAULT_CIRCUIT_BREAKER_COOLDOWN_MS: u64 = 30_000;
+static DEFAULT_CIRCUIT_BREAKER_PROBES: u32 = 3;
+static DEFAULT_SIMULATION_LOGS_PER_THREAD: usize = 50;
+static DEFAULT_SIMULATION_LOGS_MAX_THREADS: usize = 1_000;
+static DEFAULT_MIN_CONTEXT_SLOT_RETRY_ATTEMPTS: u32 = 3;
+static DEFAULT_MIN_CONTEXT_SLOT_RETRY_DELAY_MS: u64 = 200;
+static DEFAULT_PYTH_HYSTERESIS_BPS: u64 = 5;
+static DEFAULT_PYTH_MAX_PRICE_AGE_SECS: u64 = 60;
+static DEFAULT_SLOT_LAG_MAX_SLOTS: u64 = 32;
+static DEFAULT_SLOT_LAG_CHECK_INTERVAL_SECS: u64 = 5;
+
+/// The settings which may be changed by reloading the config file, the others require a restart.
+pub static RELOADABLE_SETTINGS: &[&str] = &[
+    "balance",
+    "blockhash_max_age_ms",
+    "executor",
+    "fee_filter",
+    "ignore_pool_membership",
+    "max_resubmits",
+    "prefix_instructions",
+    "priority_fee",
+    "rpc_urls",
+    "simulation_backoff",
+    "slot_lag",
+    "suffix_instructions",
+    "thread_filter",
+];
+
+/// Max compute units that may be used by a transaction.
+pub static MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Max byte size of a serialized transaction message.
+pub static MAX_MESSAGE_SIZE_LIMIT: usize = 1_232;
 
 /// Plugin config.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -17,6 +83,150 @@ pub struct PluginConfig {
     pub thread_count: usize,
     pub transaction_timeout_threshold: u64,
     pub worker_id: u64,
+    #[serde(default)]
+    pub priority_fee: PriorityFeeConfig,
+    #[serde(default)]
+    pub submission_mode: SubmissionMode,
+    #[serde(default)]
+    pub jito: Option<JitoConfig>,
+    /// The age in milliseconds after which the cached blockhash is considered stale and fetched again.
+    #[serde(default = "default_blockhash_max_age_ms")]
+    pub blockhash_max_age_ms: u64,
+    /// The RPC endpoints used to simulate and send transactions, in addition to the local validator.
+    #[serde(default)]
+    pub rpc_urls: Vec<String>,
+    #[serde(default)]
+    pub simulation_backoff: SimulationBackoffConfig,
+    #[serde(default)]
+    pub thread_filter: ThreadFilterConfig,
+    /// The port of the HTTP server exposing the Prometheus metrics and the health check. Disabled if not set.
+    #[serde(default)]
+    pub metrics_port: Option<u16>,
+    /// The address the HTTP server binds to. Localhost by default, the metrics expose the worker internals.
+    #[serde(default = "default_metrics_bind_address")]
+    pub metrics_bind_address: IpAddr,
+    #[serde(default)]
+    pub health: HealthConfig,
+    /// The port of the read-only admin interface exposing the observer and executor state, bound to localhost. Disabled if not set.
+    #[serde(default)]
+    pub admin_port: Option<u16>,
+    /// Build and simulate transactions without signing them with the keypair nor submitting them.
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default)]
+    pub balance: BalanceConfig,
+    #[serde(default)]
+    pub fee_filter: FeeFilterConfig,
+    #[serde(default)]
+    pub lookup_table: LookupTableConfig,
+    #[serde(default)]
+    pub persistence: PersistenceConfig,
+    #[serde(default)]
+    pub executor: ExecutorConfig,
+    #[serde(default)]
+    pub pyth: PythConfig,
+    #[serde(default)]
+    pub min_context_slot_retry: MinContextSlotRetryConfig,
+    /// The webhook execution events are posted to. Disabled if not set.
+    #[serde(default)]
+    pub webhook: Option<WebhookConfig>,
+    /// The time in milliseconds the in-flight executions are given to complete when the plugin is unloaded.
+    #[serde(default = "default_shutdown_timeout_ms")]
+    pub shutdown_timeout_ms: u64,
+    /// Execute threads as if the worker were in the pool, e.g. on a localnet without any delegation. Testing only.
+    #[serde(default)]
+    pub ignore_pool_membership: bool,
+    /// Execute threads on a localnet without a worker pool: the pool membership is not checked and the missing pool,
+    /// fee or worker accounts don't prevent the builds, so the program errors show up in the simulation logs.
+    /// Refused when the cluster is mainnet-beta. Testing only.
+    #[serde(default)]
+    pub localnet: bool,
+    /// The number of slots after which a thread which cannot be fetched is dropped, in case its close was missed.
+    #[serde(default = "default_thread_fetch_ttl_slots")]
+    pub thread_fetch_ttl_slots: u64,
+    #[serde(default)]
+    pub log_format: LogFormat,
+    /// The number of times an exec transaction whose blockhash expired is signed again and resubmitted.
+    #[serde(default = "default_max_resubmits")]
+    pub max_resubmits: u32,
+    /// The number of slots a thread with an exec transaction in flight is not built again, unless it completes first.
+    #[serde(default = "default_dedupe_window_slots")]
+    pub dedupe_window_slots: u64,
+    /// The commitment exec transactions are simulated at.
+    #[serde(default = "default_simulation_commitment")]
+    pub simulation_commitment: Commitment,
+    /// The commitment sent transactions are confirmed at, and blockhashes are fetched at.
+    #[serde(default = "default_confirmation_commitment")]
+    pub confirmation_commitment: Commitment,
+    /// Streams the accounts over the RPC websocket when the geyser stream skipped slots. Disabled if not set.
+    #[serde(default)]
+    pub websocket_fallback: Option<WebsocketFallbackConfig>,
+    /// The worker identities operated by the plugin. Defaults to `worker_id`, signed for with the `keypath` keypair.
+    #[serde(default)]
+    pub workers: Vec<WorkerConfig>,
+    /// Fetches every thread account on startup, so the threads are observed before their accounts are written again.
+    /// Operators relying on the snapshot accounts replayed by geyser may disable it.
+    #[serde(default = "default_bootstrap_threads")]
+    pub bootstrap_threads: bool,
+    /// The keypair paying the fees of the exec transactions, while the worker signatories still sign as signatories.
+    /// The signatories pay the fees if not set.
+    #[serde(default)]
+    pub fee_payer: Option<String>,
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    #[serde(default)]
+    pub simulation_logs: SimulationLogsConfig,
+    #[serde(default)]
+    pub slot_lag: SlotLagConfig,
+    /// Thresholds of the threads watching the amount of an SPL token account, keyed by thread pubkey.
+    /// These threads only run when the amount crosses their threshold, instead of on every write to the account.
+    #[serde(default)]
+    pub token_balance_triggers: HashMap<String, TokenBalanceTrigger>,
+    /// Publishes the threads executable in each slot to external schedulers over a unix socket. Disabled if not set.
+    #[serde(default)]
+    pub snapshot_stream: Option<SnapshotStreamConfig>,
+    /// Instructions spliced into every exec transaction after the compute budget instructions, e.g. a memo.
+    #[serde(default)]
+    pub prefix_instructions: Vec<InjectedInstruction>,
+    /// Instructions spliced at the end of every exec transaction, e.g. a tip transfer.
+    #[serde(default)]
+    pub suffix_instructions: Vec<InjectedInstruction>,
+}
+
+fn default_bootstrap_threads() -> bool {
+    true
+}
+
+fn default_blockhash_max_age_ms() -> u64 {
+    DEFAULT_BLOCKHASH_MAX_AGE_MS
+}
+
+fn default_shutdown_timeout_ms() -> u64 {
+    DEFAULT_SHUTDOWN_TIMEOUT_MS
+}
+
+fn default_thread_fetch_ttl_slots() -> u64 {
+    DEFAULT_THREAD_FETCH_TTL_SLOTS
+}
+
+fn default_max_resubmits() -> u32 {
+    DEFAULT_MAX_RESUBMITS
+}
+
+fn default_dedupe_window_slots() -> u64 {
+    DEFAULT_DEDUPE_WINDOW_SLOTS
+}
+
+fn default_metrics_bind_address() -> IpAddr {
+    IpAddr::V4(Ipv4Addr::LOCALHOST)
+}
+
+fn default_simulation_commitment() -> Commitment {
+    Commitment::Processed
+}
+
+fn default_confirmation_commitment() -> Commitment {
+    Commitment::Confirmed
 }
 
 impl Default for PluginConfig {
@@ -27,6 +237,46 @@ impl Default for PluginConfig {
             transaction_timeout_threshold: DEFAULT_TRANSACTION_TIMEOUT_THRESHOLD,
             thread_count: DEFAULT_THREAD_COUNT,
             worker_id: 0,
+            priority_fee: PriorityFeeConfig::default(),
+            submission_mode: SubmissionMode::default(),
+            jito: None,
+            blockhash_max_age_ms: DEFAULT_BLOCKHASH_MAX_AGE_MS,
+            rpc_urls: vec![],
+            simulation_backoff: SimulationBackoffConfig::default(),
+            thread_filter: ThreadFilterConfig::default(),
+            metrics_port: None,
+            metrics_bind_address: default_metrics_bind_address(),
+            health: HealthConfig::default(),
+            admin_port: None,
+            dry_run: false,
+            balance: BalanceConfig::default(),
+            fee_filter: FeeFilterConfig::default(),
+            lookup_table: LookupTableConfig::default(),
+            persistence: PersistenceConfig::default(),
+            executor: ExecutorConfig::default(),
+            pyth: PythConfig::default(),
+            min_context_slot_retry: MinContextSlotRetryConfig::default(),
+            webhook: None,
+            shutdown_timeout_ms: DEFAULT_SHUTDOWN_TIMEOUT_MS,
+            ignore_pool_membership: false,
+            localnet: false,
+            thread_fetch_ttl_slots: DEFAULT_THREAD_FETCH_TTL_SLOTS,
+            log_format: LogFormat::default(),
+            max_resubmits: DEFAULT_MAX_RESUBMITS,
+            dedupe_window_slots: DEFAULT_DEDUPE_WINDOW_SLOTS,
+            simulation_commitment: default_simulation_commitment(),
+            confirmation_commitment: default_confirmation_commitment(),
+            websocket_fallback: None,
+            workers: vec![],
+            bootstrap_threads: default_bootstrap_threads(),
+            fee_payer: None,
+            circuit_breaker: CircuitBreakerConfig::default(),
+            simulation_logs: SimulationLogsConfig::default(),
+            slot_lag: SlotLagConfig::default(),
+            token_balance_triggers: HashMap::new(),
+            snapshot_stream: None,
+            prefix_instructions: vec![],
+            suffix_instructions: vec![],
         }
     }
 }
@@ -37,6 +287,999 @@ impl PluginConfig {
         let file = File::open(config_path)?;
         let this: Self = serde_json::from_reader(file)
             .map_err(|e| GeyserPluginError::ConfigFileReadError { msg: e.to_string() })?;
+        this.executor
+            .validate()
+            .and_then(|()| this.validate_workers())
+            .map_err(|msg| GeyserPluginError::ConfigFileReadError { msg })?;
         Ok(this)
     }
+
+    /// Returns the worker identities operated by the plugin.
+    pub fn workers(&self) -> Vec<WorkerConfig> {
+        if self.workers.is_empty() {
+            return vec![WorkerConfig {
+                worker_id: self.worker_id,
+                keypath: self.keypath.clone(),
+            }];
+        }
+        self.workers.clone()
+    }
+
+    /// Returns true if the executable threads are only published to the snapshot stream, not executed.
+    pub fn publish_only(&self) -> bool {
+        self.snapshot_stream
+            .as_ref()
+            .map_or(false, |snapshot_stream| snapshot_stream.publish_only)
+    }
+
+    /// Returns the tip paid with the bundle of a thread paying `thread_fee`,
+    /// zero outside of the jito submission mode.
+    pub fn bundle_tip(&self, thread_fee: u64) -> u64 {
+        match (&self.submission_mode, &self.jito) {
+            (SubmissionMode::Jito, Some(jito)) => jito.tip.lamports(thread_fee),
+            _ => 0,
+        }
+    }
+
+    /// Returns the simulation backoff settings of the pipeline.
+    pub fn pipeline_backoff(&self, pipeline: Pipeline) -> &SimulationBackoffConfig {
+        self.executor
+            .pipelines
+            .get(pipeline)
+            .simulation_backoff
+            .as_ref()
+            .unwrap_or(&self.simulation_backoff)
+    }
+
+    /// Returns the settings changed by the new config which can't be applied without a restart.
+    pub fn restart_required_changes(&self, new: &PluginConfig) -> Vec<String> {
+        let (Ok(serde_json::Value::Object(current)), Ok(serde_json::Value::Object(new))) =
+            (serde_json::to_value(self), serde_json::to_value(new))
+        else {
+            return vec![];
+        };
+        current
+            .iter()
+            .filter(|(setting, _)| !RELOADABLE_SETTINGS.contains(&setting.as_str()))
+            .filter(|(setting, value)| new.get(*setting) != Some(*value))
+            .map(|(setting, _)| setting.clone())
+            .collect()
+    }
+
+    fn validate_workers(&self) -> Result<(), String> {
+        let mut worker_ids = std::collections::HashSet::new();
+        for worker in &self.workers {
+            if !worker_ids.insert(worker.worker_id) {
+                return Err(format!("workers: duplicate worker_id {}", worker.worker_id));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A worker identity and the keypair of its signatory.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WorkerConfig {
+    pub worker_id: u64,
+    pub keypath: Option<String>,
+}
+
+/// Compute unit and size limits of exec transactions.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecutorConfig {
+    /// The compute unit limit exec transactions are simulated with, and the most they may be committed to.
+    pub compute_unit_limit: u32,
+    /// The compute units added to the simulated usage, for threads using more on-chain than in simulation.
+    pub compute_unit_buffer: u32,
+    /// The max byte size of exec transaction messages.
+    pub message_size_limit: usize,
+    /// Compute unit and packing settings of specific threads, keyed by thread pubkey.
+    pub thread_overrides: HashMap<String, ComputeUnitOverride>,
+    /// The max number of thread instructions packed per exec transaction, the kickoff included.
+    /// The thread's rate limit still applies below it. Unlimited if 0.
+    pub max_execs_per_transaction: usize,
+    /// The max number of exec transactions built and simulated at once.
+    pub build_concurrency: usize,
+    /// The max number of simulations in flight at once, across all the threads being built.
+    pub simulation_concurrency: usize,
+    /// The max number of threads queued to be built in a slot. Beyond it, the lowest fee threads wait for the next slot.
+    pub build_queue_size: usize,
+    /// The max time a thread exec transaction may take to be built, simulations included.
+    /// A build running over it is cancelled and counted as a simulation failure.
+    /// Also the timeout of each request to the RPC endpoints.
+    pub build_timeout_ms: u64,
+    /// The max number of threads deferred by a build queue overflow or the circuit breaker which are retried first,
+    /// one-shot triggers ahead of recurring ones, then in the order they were deferred.
+    /// Past it, the deferred threads wait their turn with the others. Disabled if 0.
+    pub retry_queue_size: usize,
+    /// Build exec transactions from the compute units of the thread's last simulation, without simulating
+    /// them again while the thread's instruction is unchanged. Trades safety for latency.
+    pub compute_unit_cache: bool,
+    /// Threads built without any simulation, with static compute unit settings, keyed by thread pubkey.
+    /// Trades the fees of failed transactions for latency.
+    pub static_compute_units: HashMap<String, StaticComputeUnits>,
+    /// Settings of the pipelines each trigger type is built in, falling back to the ones above.
+    pub pipelines: PipelinesConfig,
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        Self {
+            compute_unit_limit: MAX_COMPUTE_UNIT_LIMIT,
+            compute_unit_buffer: DEFAULT_COMPUTE_UNIT_BUFFER,
+            message_size_limit: MAX_MESSAGE_SIZE_LIMIT,
+            thread_overrides: HashMap::new(),
+            max_execs_per_transaction: 0,
+            build_concurrency: DEFAULT_BUILD_CONCURRENCY,
+            simulation_concurrency: DEFAULT_SIMULATION_CONCURRENCY,
+            build_queue_size: DEFAULT_BUILD_QUEUE_SIZE,
+            build_timeout_ms: DEFAULT_BUILD_TIMEOUT_MS,
+            retry_queue_size: DEFAULT_RETRY_QUEUE_SIZE,
+            compute_unit_cache: false,
+            static_compute_units: HashMap::new(),
+            pipelines: PipelinesConfig::default(),
+        }
+    }
+}
+
+impl ExecutorConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.build_concurrency == 0 {
+            return Err("executor.build_concurrency must be at least 1".to_string());
+        }
+        if self.simulation_concurrency == 0 {
+            return Err("executor.simulation_concurrency must be at least 1".to_string());
+        }
+        if self.build_queue_size == 0 {
+            return Err("executor.build_queue_size must be at least 1".to_string());
+        }
+        if self.build_timeout_ms == 0 {
+            return Err("executor.build_timeout_ms must be at least 1".to_string());
+        }
+        if self.message_size_limit > MAX_MESSAGE_SIZE_LIMIT {
+            return Err(format!(
+                "executor.message_size_limit {} exceeds {}",
+                self.message_size_limit, MAX_MESSAGE_SIZE_LIMIT
+            ));
+        }
+        validate_compute_units(
+            "executor",
+            self.compute_unit_limit,
+            self.compute_unit_buffer,
+        )?;
+        for (thread, static_compute_units) in &self.static_compute_units {
+            validate_compute_units(
+                &format!("executor.static_compute_units.{}", thread),
+                static_compute_units.compute_unit_limit,
+                0,
+            )?;
+        }
+        for pipeline in Pipeline::ALL {
+            let pipeline_config = self.pipelines.get(pipeline);
+            if pipeline_config.build_queue_size == Some(0) {
+                return Err(format!(
+                    "executor.pipelines.{}.build_queue_size must be at least 1",
+                    pipeline.name()
+                ));
+            }
+            if pipeline_config.build_concurrency == Some(0) {
+                return Err(format!(
+                    "executor.pipelines.{}.build_concurrency must be at least 1",
+                    pipeline.name()
+                ));
+            }
+        }
+        for (thread, thread_override) in &self.thread_overrides {
+            let (compute_unit_limit, compute_unit_buffer) = self.merge(thread_override);
+            validate_compute_units(
+                &format!("executor.thread_overrides.{}", thread),
+                compute_unit_limit,
+                compute_unit_buffer,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Returns the compute unit limit and buffer of the thread.
+    pub fn compute_units(&self, thread: &str) -> (u32, u32) {
+        match self.thread_overrides.get(thread) {
+            Some(thread_override) => self.merge(thread_override),
+            None => (self.compute_unit_limit, self.compute_unit_buffer),
+        }
+    }
+
+    /// Returns the max number of thread instructions packed per exec transaction of the thread, if limited.
+    pub fn max_execs_per_transaction(&self, thread: &str) -> Option<usize> {
+        let max_execs = self
+            .thread_overrides
+            .get(thread)
+            .and_then(|thread_override| thread_override.max_execs_per_transaction)
+            .unwrap_or(self.max_execs_per_transaction);
+        (max_execs > 0).then_some(max_execs)
+    }
+
+    /// Returns the max number of threads queued to be built in a slot by the pipeline.
+    pub fn build_queue_size(&self, pipeline: Pipeline) -> usize {
+        self.pipelines
+            .get(pipeline)
+            .build_queue_size
+            .unwrap_or(self.build_queue_size)
+    }
+
+    /// Returns the max number of exec transactions built at once by the pipeline.
+    pub fn build_concurrency(&self, pipeline: Pipeline) -> usize {
+        self.pipelines
+            .get(pipeline)
+            .build_concurrency
+            .unwrap_or(self.build_concurrency)
+    }
+
+    fn merge(&self, thread_override: &ComputeUnitOverride) -> (u32, u32) {
+        (
+            thread_override
+                .compute_unit_limit
+                .unwrap_or(self.compute_unit_limit),
+            thread_override
+                .compute_unit_buffer
+                .unwrap_or(self.compute_unit_buffer),
+        )
+    }
+}
+
+/// The pipelines exec transactions are built in, one per trigger type, so a burst of one
+/// trigger type does not delay the threads of the others.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pipeline {
+    /// Cron, timestamp and periodic triggers.
+    Cron,
+    /// Account triggers.
+    Account,
+    /// Pyth price triggers.
+    Oracle,
+    /// Slot and epoch triggers.
+    Scheduled,
+    /// Immediate triggers.
+    Immediate,
+}
+
+impl Pipeline {
+    pub const ALL: [Pipeline; 5] = [
+        Pipeline::Cron,
+        Pipeline::Account,
+        Pipeline::Oracle,
+        Pipeline::Scheduled,
+        Pipeline::Immediate,
+    ];
+
+    /// The name of the pipeline in the config and the metrics.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pipeline::Cron => "cron",
+            Pipeline::Account => "account",
+            Pipeline::Oracle => "oracle",
+            Pipeline::Scheduled => "scheduled",
+            Pipeline::Immediate => "immediate",
+        }
+    }
+}
+
+/// Settings of each pipeline.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PipelinesConfig {
+    pub cron: PipelineConfig,
+    pub account: PipelineConfig,
+    pub oracle: PipelineConfig,
+    pub scheduled: PipelineConfig,
+    pub immediate: PipelineConfig,
+}
+
+impl PipelinesConfig {
+    pub fn get(&self, pipeline: Pipeline) -> &PipelineConfig {
+        match pipeline {
+            Pipeline::Cron => &self.cron,
+            Pipeline::Account => &self.account,
+            Pipeline::Oracle => &self.oracle,
+            Pipeline::Scheduled => &self.scheduled,
+            Pipeline::Immediate => &self.immediate,
+        }
+    }
+}
+
+/// Settings of a pipeline, falling back to the executor's and the simulation backoff's when not set.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PipelineConfig {
+    pub build_queue_size: Option<usize>,
+    pub build_concurrency: Option<usize>,
+    pub simulation_backoff: Option<SimulationBackoffConfig>,
+}
+
+/// Compute unit and packing settings of a thread, falling back to the executor's when not set.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComputeUnitOverride {
+    pub compute_unit_limit: Option<u32>,
+    pub compute_unit_buffer: Option<u32>,
+    /// Unlimited if 0, whatever the executor's setting.
+    pub max_execs_per_transaction: Option<usize>,
+}
+
+/// Compute unit settings of a thread whose exec transactions are sent without being simulated.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StaticComputeUnits {
+    pub compute_unit_limit: u32,
+    /// The compute unit price in micro-lamports, not set if zero.
+    pub compute_unit_price: u64,
+    /// The max number of exec instructions packed in a transaction, after the kickoff if the thread is not kicked off yet.
+    pub exec_count: usize,
+}
+
+impl Default for StaticComputeUnits {
+    fn default() -> Self {
+        Self {
+            compute_unit_limit: DEFAULT_STATIC_COMPUTE_UNIT_LIMIT,
+            compute_unit_price: 0,
+            exec_count: 1,
+        }
+    }
+}
+
+fn validate_compute_units(
+    name: &str,
+    compute_unit_limit: u32,
+    compute_unit_buffer: u32,
+) -> Result<(), String> {
+    if compute_unit_limit > MAX_COMPUTE_UNIT_LIMIT {
+        return Err(format!(
+            "{}.compute_unit_limit {} exceeds {}",
+            name, compute_unit_limit, MAX_COMPUTE_UNIT_LIMIT
+        ));
+    }
+    if compute_unit_buffer > compute_unit_limit {
+        return Err(format!(
+            "{}.compute_unit_buffer {} exceeds the compute unit limit {}",
+            name, compute_unit_buffer, compute_unit_limit
+        ));
+    }
+    Ok(())
+}
+
+/// Compute unit price settings for exec transactions.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PriorityFeeConfig {
+    /// How the compute unit price is derived.
+    pub mode: PriorityFeeMode,
+    /// The percentile of recent prioritization fees to bid (0-100), in `recent_fees` mode.
+    pub percentile: u8,
+    /// The percentage of the thread's fee spent on the priority fee (0-100), in `thread_fee` mode.
+    pub thread_fee_percentage: u64,
+    /// The minimum compute unit price to bid, in micro-lamports.
+    pub min_compute_unit_price: u64,
+    /// The maximum compute unit price to bid, in micro-lamports. Zero disables priority fees.
+    pub max_compute_unit_price: u64,
+}
+
+impl Default for PriorityFeeConfig {
+    fn default() -> Self {
+        Self {
+            mode: PriorityFeeMode::default(),
+            percentile: DEFAULT_PRIORITY_FEE_PERCENTILE,
+            thread_fee_percentage: DEFAULT_PRIORITY_FEE_THREAD_FEE_PERCENTAGE,
+            min_compute_unit_price: 0,
+            max_compute_unit_price: 0,
+        }
+    }
+}
+
+/// How the compute unit price of exec transactions is derived.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriorityFeeMode {
+    /// Bid a percentile of the recent prioritization fees paid for the transaction's writable accounts.
+    #[default]
+    RecentFees,
+    /// Spend a share of the thread's fee, so threads paying higher fees outbid the others.
+    ThreadFee,
+}
+
+/// Backoff settings for threads whose simulation keeps failing.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationBackoffConfig {
+    /// The number of consecutive simulation failures after which a thread is backed off.
+    pub failure_threshold: u32,
+    /// The maximum number of slots a thread can be skipped for.
+    pub max_backoff_slots: u64,
+}
+
+impl Default for SimulationBackoffConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: DEFAULT_BACKOFF_FAILURE_THRESHOLD,
+            max_backoff_slots: DEFAULT_BACKOFF_MAX_SLOTS,
+        }
+    }
+}
+
+/// Pauses the builds while the RPC endpoints or the sent transactions fail too often.
+/// Each RPC endpoint has its own breaker, the transactions failing on-chain share one.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    pub enabled: bool,
+    /// The number of most recent results the failure ratio is computed over.
+    pub window: usize,
+    /// The number of results needed in the window before the breaker may open.
+    pub min_samples: usize,
+    /// The failure ratio above which the breaker opens.
+    pub failure_ratio: f64,
+    /// The time the breaker stays open before letting probes through.
+    pub cooldown_ms: u64,
+    /// The number of successful probes which close the breaker again.
+    pub probes: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: DEFAULT_CIRCUIT_BREAKER_WINDOW,
+            min_samples: DEFAULT_CIRCUIT_BREAKER_MIN_SAMPLES,
+            failure_ratio: DEFAULT_CIRCUIT_BREAKER_FAILURE_RATIO,
+            cooldown_ms: DEFAULT_CIRCUIT_BREAKER_COOLDOWN_MS,
+            probes: DEFAULT_CIRCUIT_BREAKER_PROBES,
+        }
+    }
+}
+
+/// The recent simulation outcomes kept per thread, served by the admin interface.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationLogsConfig {
+    /// The number of most recent simulations kept per thread.
+    pub per_thread: usize,
+    /// The number of threads simulations are kept for, the least recently simulated ones are dropped first.
+    pub max_threads: usize,
+    /// The file the simulations are dumped to on demand. The dump is disabled if not set.
+    pub dump_path: Option<String>,
+}
+
+impl Default for SimulationLogsConfig {
+    fn default() -> Self {
+        Self {
+            per_thread: DEFAULT_SIMULATION_LOGS_PER_THREAD,
+            max_threads: DEFAULT_SIMULATION_LOGS_MAX_THREADS,
+            dump_path: None,
+        }
+    }
+}
+
+/// The threshold the amount of a watched SPL token account must cross for its thread to run.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenBalanceTrigger {
+    pub threshold: u64,
+    pub direction: BalanceDirection,
+}
+
+/// The direction the amount of a token account crosses its threshold in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceDirection {
+    /// The amount rises to the threshold or above.
+    Above,
+    /// The amount falls to the threshold or below.
+    Below,
+}
+
+impl BalanceDirection {
+    /// Returns true if the amount is on the threshold's side of the direction.
+    pub fn is_met(&self, amount: u64, threshold: u64) -> bool {
+        match self {
+            Self::Above => amount >= threshold,
+            Self::Below => amount <= threshold,
+        }
+    }
+}
+
+/// Restricts the threads executed by this worker. Entries are thread or authority pubkeys.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThreadFilterConfig {
+    /// If not empty, only the threads matching one of these pubkeys are executed.
+    pub allowlist: Vec<String>,
+    /// The threads matching one of these pubkeys are never executed.
+    pub denylist: Vec<String>,
+}
+
+/// Freshness thresholds of the health check.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    /// The maximum number of seconds since the last processed slot.
+    pub max_processed_slot_age_secs: u64,
+    /// The maximum number of seconds since the last successful RPC health check.
+    pub max_rpc_health_age_secs: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            max_processed_slot_age_secs: DEFAULT_MAX_PROCESSED_SLOT_AGE_SECS,
+            max_rpc_health_age_secs: DEFAULT_MAX_RPC_HEALTH_AGE_SECS,
+        }
+    }
+}
+
+/// Retries of simulations rejected because the RPC has not caught up with the validator yet.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MinContextSlotRetryConfig {
+    /// The number of retries. Zero disables retries.
+    pub attempts: u32,
+    /// The delay between two retries, in milliseconds.
+    pub delay_ms: u64,
+}
+
+impl Default for MinContextSlotRetryConfig {
+    fn default() -> Self {
+        Self {
+            attempts: DEFAULT_MIN_CONTEXT_SLOT_RETRY_ATTEMPTS,
+            delay_ms: DEFAULT_MIN_CONTEXT_SLOT_RETRY_DELAY_MS,
+        }
+    }
+}
+
+/// Pauses the builds while the local validator lags behind the cluster, as seen by the RPC endpoints.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlotLagConfig {
+    pub enabled: bool,
+    /// The number of slots the validator may lag behind before the builds are paused.
+    pub max_lag_slots: u64,
+    /// The interval at which the cluster slot is fetched, in seconds.
+    pub check_interval_secs: u64,
+}
+
+impl Default for SlotLagConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_lag_slots: DEFAULT_SLOT_LAG_MAX_SLOTS,
+            check_interval_secs: DEFAULT_SLOT_LAG_CHECK_INTERVAL_SECS,
+        }
+    }
+}
+
+/// Pyth price feed settings.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PythConfig {
+    /// The shard of the price accounts, keyed by hex encoded feed id. Feeds not listed use shard 0.
+    pub shards: HashMap<String, u16>,
+    /// The band past the limit, in basis points of the limit, the price must reach before a thread is enqueued.
+    /// Once reached, the thread is enqueued until the price falls back behind the limit itself.
+    pub hysteresis_bps: u64,
+    /// The age in seconds past which a price update is stale, and does not enqueue any thread.
+    pub max_price_age_secs: u64,
+}
+
+impl Default for PythConfig {
+    fn default() -> Self {
+        Self {
+            shards: HashMap::new(),
+            hysteresis_bps: DEFAULT_PYTH_HYSTERESIS_BPS,
+            max_price_age_secs: DEFAULT_PYTH_MAX_PRICE_AGE_SECS,
+        }
+    }
+}
+
+impl PythConfig {
+    pub fn shard_id(&self, feed_id: &[u8; 32]) -> u16 {
+        self.shards
+            .iter()
+            .find(|(key, _)| decode_hex(key.trim_start_matches("0x")).as_deref() == Some(feed_id))
+            .map(|(_, shard_id)| *shard_id)
+            .unwrap_or_default()
+    }
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Persistence of the observed threads across validator restarts.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersistenceConfig {
+    /// The file the observer state is written to. Persistence is disabled if not set.
+    pub path: Option<String>,
+    /// The number of seconds between two writes of the observer state.
+    pub interval_secs: u64,
+}
+
+impl Default for PersistenceConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            interval_secs: DEFAULT_PERSISTENCE_INTERVAL_SECS,
+        }
+    }
+}
+
+/// Signatory balance thresholds. Zero disables a threshold.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BalanceConfig {
+    /// Below this balance, transactions debiting the signatory are not built.
+    pub min_balance_lamports: u64,
+    /// Below this balance, all execution is halted, including the RPC calls of the executor.
+    pub hard_stop_lamports: u64,
+}
+
+/// Skips threads whose fee does not cover the estimated cost of their transaction.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeeFilterConfig {
+    /// Off by default, so threads paying the program's default fee keep being executed.
+    pub enabled: bool,
+    /// The lamports the fee must exceed the cost by.
+    pub margin_lamports: u64,
+}
+
+/// Address lookup table settings of exec transactions.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LookupTableConfig {
+    /// Compile exec transactions against a lookup table owned by the signatory.
+    pub enabled: bool,
+    /// The lookup table to use. A new one is created if not set.
+    pub address: Option<String>,
+    /// Stale lookup tables owned by the signatory, to deactivate and close to recover their rent.
+    pub close: Vec<String>,
+}
+
+/// How exec transactions are submitted to the cluster.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmissionMode {
+    /// Send transactions directly to the current and upcoming leaders' TPU ports.
+    #[default]
+    Tpu,
+    /// Send transactions through the configured RPC endpoints.
+    Rpc,
+    /// Send transactions as tipped bundles to a Jito block engine.
+    Jito,
+}
+
+/// The commitment level of RPC requests, trading freshness for safety from forks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Commitment {
+    /// The latest state of the node. The freshest, but it may belong to a fork which is later rolled back.
+    Processed,
+    /// Voted on by a supermajority of the cluster. Practically never rolled back, about a slot behind.
+    Confirmed,
+    /// Rooted by the cluster. Never rolled back, but about 32 slots behind.
+    Finalized,
+}
+
+/// How the execution lifecycle is logged.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// One JSON object per execution event, with the slot, the thread and the worker id.
+    Json,
+}
+
+/// Webhook execution events are posted to.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Sent as a bearer token in the authorization header, if set.
+    pub bearer_token: Option<String>,
+    /// The max number of events waiting to be delivered. Further events are dropped.
+    pub queue_size: usize,
+    /// The number of retries of deliveries failing with a transient error.
+    pub max_retries: u32,
+    /// The timeout of a delivery, in milliseconds.
+    pub timeout_ms: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            bearer_token: None,
+            queue_size: DEFAULT_WEBHOOK_QUEUE_SIZE,
+            max_retries: DEFAULT_WEBHOOK_MAX_RETRIES,
+            timeout_ms: DEFAULT_WEBHOOK_TIMEOUT_MS,
+        }
+    }
+}
+
+/// Unix socket the executable threads are streamed to, one JSON message per line.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotStreamConfig {
+    pub socket_path: String,
+    /// The max number of messages a consumer may fall behind by, it skips the older ones past it.
+    pub capacity: usize,
+    /// Only publishes the executable threads, the built-in executor neither builds nor submits their transactions.
+    pub publish_only: bool,
+}
+
+impl Default for SnapshotStreamConfig {
+    fn default() -> Self {
+        Self {
+            socket_path: String::new(),
+            capacity: DEFAULT_SNAPSHOT_STREAM_CAPACITY,
+            publish_only: false,
+        }
+    }
+}
+
+/// An instruction injected into the exec transactions.
+/// The accounts set to the `PAYER_PUBKEY` placeholder are substituted with the worker's signatory,
+/// which is the only signer they may require.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InjectedInstruction {
+    pub program_id: String,
+    #[serde(default)]
+    pub accounts: Vec<InjectedAccountMeta>,
+    /// The base64 encoded instruction data.
+    #[serde(default)]
+    pub data: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InjectedAccountMeta {
+    pub pubkey: String,
+    #[serde(default)]
+    pub is_signer: bool,
+    #[serde(default)]
+    pub is_writable: bool,
+}
+
+/// Websocket subscriptions reconciling the account updates missed by the geyser stream,
+/// e.g. while a snapshot is loaded or the plugin is reloaded.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebsocketFallbackConfig {
+    /// The RPC websocket url, the local validator by default.
+    pub url: String,
+    /// The number of slots the processed slot notifications must jump by to open the subscriptions.
+    pub gap_slots: u64,
+}
+
+impl Default for WebsocketFallbackConfig {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_WEBSOCKET_FALLBACK_URL.to_string(),
+            gap_slots: DEFAULT_WEBSOCKET_FALLBACK_GAP_SLOTS,
+        }
+    }
+}
+
+/// Block engine settings used by the `jito` submission mode.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JitoConfig {
+    /// The block engine url, e.g. `https://mainnet.block-engine.jito.wtf`.
+    pub block_engine_url: String,
+    /// The account receiving the bundle tip.
+    pub tip_account: String,
+    /// The tip paid with each bundle.
+    pub tip: JitoTip,
+}
+
+/// The tip paid with each bundle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JitoTip {
+    /// A fixed amount of lamports.
+    Lamports(u64),
+    /// A percentage of the thread fee, capped at 100.
+    FeePercentage(u64),
+}
+
+impl JitoTip {
+    /// Returns the tip in lamports for a thread paying `thread_fee`.
+    pub fn lamports(&self, thread_fee: u64) -> u64 {
+        match self {
+            Self::Lamports(lamports) => *lamports,
+            Self::FeePercentage(percentage) => {
+                (thread_fee as u128 * (*percentage).min(100) as u128 / 100) as u64
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pyth_feeds_default_to_shard_zero() {
+        let mut feed_id = [0u8; 32];
+        feed_id[0] = 0xef;
+        feed_id[31] = 0x0d;
+        let config = PythConfig {
+            shards: HashMap::from([(
+                "0xEF0000000000000000000000000000000000000000000000000000000000000D".to_string(),
+                1,
+            )]),
+            ..PythConfig::default()
+        };
+        assert_eq!(config.shard_id(&feed_id), 1);
+        assert_eq!(config.shard_id(&[0u8; 32]), 0);
+    }
+
+    #[test]
+    fn workers_default_to_the_single_worker() {
+        let config = PluginConfig {
+            worker_id: 3,
+            keypath: Some("worker.json".to_string()),
+            ..PluginConfig::default()
+        };
+        let workers = config.workers();
+        assert_eq!(workers.len(), 1);
+        assert_eq!(workers[0].worker_id, 3);
+        assert_eq!(workers[0].keypath.as_deref(), Some("worker.json"));
+
+        let worker = WorkerConfig {
+            worker_id: 1,
+            keypath: None,
+        };
+        let config = PluginConfig {
+            workers: vec![worker.clone(), worker],
+            ..PluginConfig::default()
+        };
+        assert!(config.validate_workers().is_err());
+    }
+
+    #[test]
+    fn only_runtime_settings_are_reloadable() {
+        let current = PluginConfig::default();
+        let reloaded = PluginConfig {
+            rpc_urls: vec!["http://rpc.example.com".to_string()],
+            thread_filter: ThreadFilterConfig {
+                allowlist: vec![],
+                denylist: vec!["11111111111111111111111111111111".to_string()],
+            },
+            fee_filter: FeeFilterConfig {
+                enabled: true,
+                margin_lamports: 5_000,
+            },
+            ..PluginConfig::default()
+        };
+        assert!(current.restart_required_changes(&reloaded).is_empty());
+
+        let reloaded = PluginConfig {
+            keypath: Some("other.json".to_string()),
+            libpath: Some("other.so".to_string()),
+            ..reloaded
+        };
+        let mut changes = current.restart_required_changes(&reloaded);
+        changes.sort();
+        assert_eq!(changes, vec!["keypath", "libpath"]);
+    }
+
+    #[test]
+    fn default_executor_config_is_valid() {
+        assert!(ExecutorConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn executor_config_rejects_invalid_compute_units() {
+        let config = ExecutorConfig {
+            compute_unit_limit: MAX_COMPUTE_UNIT_LIMIT + 1,
+            ..ExecutorConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = ExecutorConfig {
+            compute_unit_limit: 200_000,
+            compute_unit_buffer: 200_001,
+            ..ExecutorConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = ExecutorConfig {
+            compute_unit_limit: 200_000,
+            thread_overrides: HashMap::from([(
+                "thread".to_string(),
+                ComputeUnitOverride {
+                    compute_unit_limit: Some(10_000),
+                    compute_unit_buffer: Some(20_000),
+                    max_execs_per_transaction: None,
+                },
+            )]),
+            ..ExecutorConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn thread_overrides_fall_back_to_the_executor_config() {
+        let config = ExecutorConfig {
+            thread_overrides: HashMap::from([(
+                "thread".to_string(),
+                ComputeUnitOverride {
+                    compute_unit_limit: None,
+                    compute_unit_buffer: Some(50_000),
+                    max_execs_per_transaction: None,
+                },
+            )]),
+            ..ExecutorConfig::default()
+        };
+        assert_eq!(
+            config.compute_units("thread"),
+            (MAX_COMPUTE_UNIT_LIMIT, 50_000)
+        );
+        assert_eq!(
+            config.compute_units("other"),
+            (MAX_COMPUTE_UNIT_LIMIT, DEFAULT_COMPUTE_UNIT_BUFFER)
+        );
+    }
+
+    #[test]
+    fn max_execs_per_transaction_is_overridden_per_thread() {
+        let config: ExecutorConfig = serde_json::from_str(
+            r#"{"max_execs_per_transaction": 1, "thread_overrides": {
+                "unlimited": {"max_execs_per_transaction": 0},
+                "capped": {"max_execs_per_transaction": 4}}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.max_execs_per_transaction("other"), Some(1));
+        assert_eq!(config.max_execs_per_transaction("capped"), Some(4));
+        assert_eq!(config.max_execs_per_transaction("unlimited"), None);
+        assert_eq!(
+            ExecutorConfig::default().max_execs_per_transaction("other"),
+            None
+        );
+    }
+
+    #[test]
+    fn pipelines_fall_back_to_the_shared_settings() {
+        let config = PluginConfig {
+            executor: serde_json::from_str(
+                r#"{"build_concurrency": 4, "pipelines": {"account": {"build_concurrency": 32,
+                    "simulation_backoff": {"failure_threshold": 1, "max_backoff_slots": 8}}}}"#,
+            )
+            .unwrap(),
+            ..PluginConfig::default()
+        };
+        assert_eq!(config.executor.build_concurrency(Pipeline::Account), 32);
+        assert_eq!(config.executor.build_concurrency(Pipeline::Cron), 4);
+        assert_eq!(
+            config.executor.build_queue_size(Pipeline::Account),
+            DEFAULT_BUILD_QUEUE_SIZE
+        );
+        assert_eq!(
+            config.pipeline_backoff(Pipeline::Account).max_backoff_slots,
+            8
+        );
+        assert_eq!(
+            config.pipeline_backoff(Pipeline::Oracle).max_backoff_slots,
+            DEFAULT_BACKOFF_MAX_SLOTS
+        );
+
+        let mut invalid = config.executor.clone();
+        invalid.pipelines.oracle.build_queue_size = Some(0);
+        assert!(invalid.validate().is_err());
+    }
 }
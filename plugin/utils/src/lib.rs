@@ -1,3 +1,11 @@
 mod config;
 
-pub use crate::config::PluginConfig;
+pub use crate::config::{
+    BalanceConfig, BalanceDirection, CircuitBreakerConfig, Commitment, ComputeUnitOverride,
+    ExecutorConfig, FeeFilterConfig, HealthConfig, InjectedAccountMeta, InjectedInstruction,
+    JitoConfig, JitoTip, LogFormat, LookupTableConfig, MinContextSlotRetryConfig,
+    PersistenceConfig, Pipeline, PipelineConfig, PipelinesConfig, PluginConfig, PriorityFeeConfig,
+    PriorityFeeMode, PythConfig, SimulationBackoffConfig, SimulationLogsConfig, SlotLagConfig,
+    SnapshotStreamConfig, StaticComputeUnits, SubmissionMode, ThreadFilterConfig,
+    TokenBalanceTrigger, WebhookConfig, WebsocketFallbackConfig, WorkerConfig,
+};
@@ -3,6 +3,7 @@ pub mod delegation_claim;
 pub mod delegation_create;
 pub mod delegation_deposit;
 pub mod delegation_withdraw;
+pub mod get_crate_info;
 pub mod initialize;
 pub mod penalty_claim;
 pub mod pool_create;
@@ -21,6 +22,7 @@ pub use delegation_claim::*;
 pub use delegation_create::*;
 pub use delegation_deposit::*;
 pub use delegation_withdraw::*;
+pub use get_crate_info::*;
 pub use initialize::*;
 pub use penalty_claim::*;
 pub use pool_create::*;
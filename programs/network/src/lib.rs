@@ -2,6 +2,8 @@
 //! It implements a PoS protocol that allows workers to rotate into "pools" proportionately to
 //! the amount of stake delgated to them. It also provides accounts for workers to collect fees
 //! and distribute those fees to delegators.
+#[macro_use]
+extern crate version;
 
 pub mod constants;
 pub mod errors;
@@ -13,7 +15,7 @@ mod jobs;
 use anchor_lang::prelude::*;
 use instructions::*;
 use jobs::*;
-use sablier_utils::thread::*;
+use sablier_utils::{thread::*, CrateInfo};
 use state::*;
 
 declare_id!("H6CTDj7ewA6PT1jGt2WTnkhWb2RwwuoQkAsgzRuSsnaV");
@@ -42,6 +44,10 @@ pub mod network_program {
         delegation_withdraw::handler(ctx, amount)
     }
 
+    pub fn get_crate_info(ctx: Context<GetCrateInfo>) -> Result<CrateInfo> {
+        get_crate_info::handler(ctx)
+    }
+
     pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
         initialize::handler(ctx)
     }
@@ -7,6 +7,10 @@ pub const SEED_THREAD: &[u8] = b"thread";
 #[constant]
 pub const THREAD_MINIMUM_FEE: u64 = 1000;
 
+/// The minimum number of seconds between the triggering moments of a periodic trigger.
+#[constant]
+pub const PERIODIC_MINIMUM_INTERVAL: u64 = 10;
+
 /// The ID of the pool workers must be a member of to collect fees.
 #[constant]
 pub const POOL_ID: u64 = 0;
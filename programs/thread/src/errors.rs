@@ -43,4 +43,8 @@ pub enum SablierError {
     /// Thrown if the user attempts to withdraw SOL that would put a thread below it's minimum rent threshold.
     #[msg("Withdrawing this amount would leave the thread with less than the minimum required SOL for rent exemption")]
     WithdrawalTooLarge,
+
+    /// Thrown if the interval of a periodic trigger is below the minimum allowed value.
+    #[msg("The interval of a periodic trigger cannot be below the minimum allowed value")]
+    PeriodicIntervalTooSmall,
 }
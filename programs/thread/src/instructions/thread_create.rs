@@ -50,6 +50,8 @@ pub fn handler(
     let system_program = &ctx.accounts.system_program;
     let thread = &mut ctx.accounts.thread;
 
+    validate_trigger(&trigger)?;
+
     // Initialize the thread
     let bump = ctx.bumps.thread;
     thread.authority = authority.key();
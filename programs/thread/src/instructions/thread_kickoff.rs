@@ -182,10 +182,7 @@ pub fn handler(ctx: Context<ThreadKickoff>) -> Result<()> {
             })
         }
         Trigger::Timestamp { unix_ts } => {
-            require!(
-                clock.unix_timestamp.ge(unix_ts),
-                SablierError::TriggerConditionFailed
-            );
+            require_timestamp_reached(*unix_ts, clock.unix_timestamp, thread.exec_context)?;
             thread.exec_context = Some(ExecContext {
                 exec_index: 0,
                 execs_since_reimbursement: 0,
@@ -257,7 +254,12 @@ pub fn handler(ctx: Context<ThreadKickoff>) -> Result<()> {
                 }
             }
         }
-        Trigger::Periodic { delay } => {
+        Trigger::Periodic { .. } | Trigger::Interval { .. } => {
+            let (interval_seconds, skippable) = thread
+                .trigger
+                .interval()
+                .ok_or(SablierError::InvalidThreadState)?;
+
             // Get the reference timestamp for calculating the thread's scheduled target timestamp.
             let reference_timestamp = match thread.exec_context {
                 None => thread.created_at.unix_timestamp,
@@ -268,17 +270,19 @@ pub fn handler(ctx: Context<ThreadKickoff>) -> Result<()> {
             };
 
             // Verify the current timestamp is greater than or equal to the threshold timestamp.
-            let threshold_timestamp = reference_timestamp + *delay as i64;
-
             msg!(
-                "Threshold timestamp: {}, clock timestamp: {}",
-                threshold_timestamp,
+                "Reference timestamp: {}, interval: {}, clock timestamp: {}",
+                reference_timestamp,
+                interval_seconds,
                 clock.unix_timestamp
             );
-            require!(
-                clock.unix_timestamp.ge(&threshold_timestamp),
-                SablierError::TriggerConditionFailed
-            );
+            let started_at = periodic_moment(
+                reference_timestamp,
+                interval_seconds,
+                skippable,
+                clock.unix_timestamp,
+            )
+            .ok_or(SablierError::TriggerConditionFailed)?;
 
             // Set the exec context.
             thread.exec_context = Some(ExecContext {
@@ -286,9 +290,7 @@ pub fn handler(ctx: Context<ThreadKickoff>) -> Result<()> {
                 execs_since_reimbursement: 0,
                 execs_since_slot: 0,
                 last_exec_at: clock.slot,
-                trigger_context: TriggerContext::Periodic {
-                    started_at: threshold_timestamp,
-                },
+                trigger_context: TriggerContext::Periodic { started_at },
             });
         }
     }
@@ -308,6 +310,48 @@ pub fn handler(ctx: Context<ThreadKickoff>) -> Result<()> {
     Ok(())
 }
 
+/// Verifies a timestamp trigger can kick off: the clock has reached its moment, and the thread hasn't run for it yet.
+/// The trigger context records the moment the thread ran for, a reset clears it and an update to another moment re-arms it.
+fn require_timestamp_reached(
+    unix_ts: i64,
+    clock_timestamp: i64,
+    exec_context: Option<ExecContext>,
+) -> Result<()> {
+    require!(
+        clock_timestamp.ge(&unix_ts),
+        SablierError::TriggerConditionFailed
+    );
+    match exec_context.map(|exec_context| exec_context.trigger_context) {
+        None => Ok(()),
+        Some(TriggerContext::Timestamp { started_at }) => {
+            require!(started_at.ne(&unix_ts), SablierError::InvalidThreadState);
+            Ok(())
+        }
+        Some(_) => Err(SablierError::InvalidThreadState.into()),
+    }
+}
+
+/// The moment a periodic trigger fires for, or `None` before its next moment. The moments are anchored to the
+/// reference timestamp rather than to the executions, so a late execution doesn't delay the next ones.
+/// A skippable trigger collapses the missed moments into the latest one, otherwise they are fired for one by one.
+fn periodic_moment(
+    reference_timestamp: i64,
+    interval_seconds: u64,
+    skippable: bool,
+    clock_timestamp: i64,
+) -> Option<i64> {
+    let interval = i64::try_from(interval_seconds).ok()?;
+    let next_moment = reference_timestamp.checked_add(interval)?;
+    if clock_timestamp < next_moment {
+        return None;
+    }
+    if !skippable {
+        return Some(next_moment);
+    }
+    let missed = (clock_timestamp - next_moment).checked_div(interval)?;
+    next_moment.checked_add(missed.checked_mul(interval)?)
+}
+
 fn next_timestamp(after: i64, schedule: &str) -> Option<i64> {
     Schedule::from_str(schedule)
         .unwrap()
@@ -315,3 +359,85 @@ fn next_timestamp(after: i64, schedule: &str) -> Option<i64> {
         .take()
         .map(|datetime| datetime.timestamp())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fired_at(started_at: i64) -> Option<ExecContext> {
+        Some(ExecContext {
+            exec_index: 0,
+            execs_since_reimbursement: 0,
+            execs_since_slot: 0,
+            last_exec_at: 0,
+            trigger_context: TriggerContext::Timestamp { started_at },
+        })
+    }
+
+    #[test]
+    fn timestamp_trigger_fires_from_its_moment() {
+        assert_eq!(
+            require_timestamp_reached(1_000, 999, None),
+            Err(SablierError::TriggerConditionFailed.into())
+        );
+        assert!(require_timestamp_reached(1_000, 1_000, None).is_ok());
+        assert!(require_timestamp_reached(1_000, 5_000, None).is_ok());
+    }
+
+    #[test]
+    fn timestamp_trigger_fires_once_until_it_is_rearmed() {
+        // The thread already ran for the moment.
+        assert_eq!(
+            require_timestamp_reached(1_000, 2_000, fired_at(1_000)),
+            Err(SablierError::InvalidThreadState.into())
+        );
+        // A reset clears the exec context.
+        assert!(require_timestamp_reached(1_000, 2_000, None).is_ok());
+        // An update to another moment re-arms the trigger once the clock reaches it.
+        assert!(require_timestamp_reached(1_500, 2_000, fired_at(1_000)).is_ok());
+        assert_eq!(
+            require_timestamp_reached(3_000, 2_000, fired_at(1_000)),
+            Err(SablierError::TriggerConditionFailed.into())
+        );
+    }
+
+    #[test]
+    fn periodic_trigger_fires_from_the_end_of_its_interval() {
+        assert_eq!(periodic_moment(1_000, 45, false, 1_044), None);
+        assert_eq!(periodic_moment(1_000, 45, false, 1_045), Some(1_045));
+        assert_eq!(periodic_moment(1_000, 45, true, 1_045), Some(1_045));
+        assert_eq!(periodic_moment(1_000, 0, true, 5_000), None);
+    }
+
+    #[test]
+    fn periodic_moments_are_anchored_to_the_schedule_rather_than_the_executions() {
+        // Every execution lands up to 30 seconds late, the moments stay on the 45 seconds grid.
+        let mut reference = 1_000;
+        for period in 1..=1_000 {
+            let late = period % 31;
+            let moment = periodic_moment(reference, 45, false, reference + 45 + late).unwrap();
+            assert_eq!(moment, 1_000 + 45 * period);
+            reference = moment;
+        }
+    }
+
+    #[test]
+    fn missed_periodic_moments_are_collapsed_only_when_skippable() {
+        // The network was down for 10 intervals and a half.
+        let clock = 1_000 + 45 * 10 + 20;
+        assert_eq!(periodic_moment(1_000, 45, true, clock), Some(1_450));
+        assert_eq!(periodic_moment(1_450, 45, true, clock), None);
+
+        // Without skipping, the missed moments are fired for one after the other until the clock is caught up.
+        let mut reference = 1_000;
+        let mut moments = vec![];
+        while let Some(moment) = periodic_moment(reference, 45, false, clock) {
+            moments.push(moment);
+            reference = moment;
+        }
+        assert_eq!(
+            moments,
+            (1..=10).map(|i| 1_000 + 45 * i).collect::<Vec<_>>()
+        );
+    }
+}
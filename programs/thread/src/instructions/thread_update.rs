@@ -59,10 +59,12 @@ pub fn handler(ctx: Context<ThreadUpdate>, settings: ThreadSettings) -> Result<(
     // If provided, update the thread's trigger and reset the exec context.
     if let Some(trigger) = settings.trigger {
         // Require the thread is not in the middle of processing.
+        // A periodic trigger may be migrated to an interval trigger, the account is reallocated below.
         require!(
-            std::mem::discriminant(&thread.trigger) == std::mem::discriminant(&trigger),
+            thread.trigger.is_same_kind(&trigger),
             SablierError::InvalidTriggerVariant
         );
+        validate_trigger(&trigger)?;
         thread.trigger = trigger.clone();
 
         // If the user updates an account trigger, the trigger context is no longer valid.
@@ -5,7 +5,10 @@ use sablier_utils::{
     MinSpace, Space,
 };
 
-use crate::constants::SEED_THREAD;
+use crate::{
+    constants::{PERIODIC_MINIMUM_INTERVAL, SEED_THREAD},
+    errors::SablierError,
+};
 
 /// Tracks the current state of a transaction thread on Solana.
 #[account]
@@ -39,6 +42,11 @@ pub struct Thread {
 impl Thread {
     /// Derive the pubkey of a thread account.
     pub fn pubkey(authority: Pubkey, id: Vec<u8>, domain: Option<Vec<u8>>) -> Pubkey {
+        Self::find_pubkey(authority, id, domain).0
+    }
+
+    /// Derive the pubkey of a thread account, with its bump seed.
+    pub fn find_pubkey(authority: Pubkey, id: Vec<u8>, domain: Option<Vec<u8>>) -> (Pubkey, u8) {
         Pubkey::find_program_address(
             &[
                 SEED_THREAD,
@@ -48,7 +56,6 @@ impl Thread {
             ],
             &crate::ID,
         )
-        .0
     }
 }
 
@@ -172,6 +179,20 @@ pub enum TriggerContext {
     },
 }
 
+/// Verifies a trigger a thread is created or updated with.
+pub fn validate_trigger(trigger: &Trigger) -> Result<()> {
+    if let Trigger::Interval {
+        interval_seconds, ..
+    } = trigger
+    {
+        require!(
+            *interval_seconds >= PERIODIC_MINIMUM_INTERVAL,
+            SablierError::PeriodicIntervalTooSmall
+        );
+    }
+    Ok(())
+}
+
 /// The properties of threads which are updatable.
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct ThreadSettings {
@@ -1,5 +1,9 @@
-use crate::{ClockData, ExecContext, SerializableInstruction, Thread, Trigger};
-use anchor_lang::{prelude::*, AccountDeserialize};
+use crate::{constants::POOL_ID, ClockData, ExecContext, SerializableInstruction, Thread, Trigger};
+use anchor_lang::{
+    prelude::*, solana_program::instruction::Instruction, AccountDeserialize, InstructionData,
+};
+use sablier_network_program::state::{Fee, Pool};
+use sablier_utils::thread::PAYER_PUBKEY;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum VersionedThread {
@@ -25,6 +29,12 @@ impl VersionedThread {
         }
     }
 
+    pub fn fee(&self) -> u64 {
+        match self {
+            Self::V1(t) => t.fee,
+        }
+    }
+
     pub fn id(&self) -> Vec<u8> {
         match self {
             Self::V1(t) => t.id.clone(),
@@ -43,6 +53,12 @@ impl VersionedThread {
         }
     }
 
+    pub fn instructions(&self) -> Vec<SerializableInstruction> {
+        match self {
+            Self::V1(t) => t.instructions.clone(),
+        }
+    }
+
     pub fn paused(&self) -> bool {
         match self {
             Self::V1(t) => t.paused,
@@ -55,6 +71,103 @@ impl VersionedThread {
         }
     }
 
+    /// The accounts of the kickoff instruction, before the accounts injected for the trigger.
+    pub fn kickoff_accounts(
+        &self,
+        thread_pubkey: Pubkey,
+        signatory_pubkey: Pubkey,
+        worker_pubkey: Pubkey,
+    ) -> Vec<AccountMeta> {
+        match self {
+            Self::V1(_) => crate::accounts::ThreadKickoff {
+                signatory: signatory_pubkey,
+                thread: thread_pubkey,
+                worker: worker_pubkey,
+            }
+            .to_account_metas(Some(false)),
+        }
+    }
+
+    pub fn kickoff_data(&self) -> Vec<u8> {
+        match self {
+            Self::V1(_) => crate::instruction::ThreadKickoff {}.data(),
+        }
+    }
+
+    /// The kickoff instruction, with the account the trigger reads appended, e.g. the watched account or the price account.
+    pub fn kickoff_ix(
+        &self,
+        thread_pubkey: Pubkey,
+        signatory_pubkey: Pubkey,
+        worker_pubkey: Pubkey,
+        trigger_account: Option<Pubkey>,
+    ) -> Instruction {
+        let mut accounts = self.kickoff_accounts(thread_pubkey, signatory_pubkey, worker_pubkey);
+        accounts.extend(trigger_account.map(|pubkey| AccountMeta::new_readonly(pubkey, false)));
+        Instruction {
+            program_id: self.program_id(),
+            accounts,
+            data: self.kickoff_data(),
+        }
+    }
+
+    /// The accounts of the exec instruction, before the accounts of the next instruction.
+    pub fn exec_accounts(
+        &self,
+        thread_pubkey: Pubkey,
+        signatory_pubkey: Pubkey,
+        worker_pubkey: Pubkey,
+    ) -> Vec<AccountMeta> {
+        match self {
+            Self::V1(_) => crate::accounts::ThreadExec {
+                fee: Fee::pubkey(worker_pubkey),
+                pool: Pool::pubkey(POOL_ID),
+                signatory: signatory_pubkey,
+                thread: thread_pubkey,
+                worker: worker_pubkey,
+            }
+            .to_account_metas(Some(true)),
+        }
+    }
+
+    pub fn exec_data(&self) -> Vec<u8> {
+        match self {
+            Self::V1(_) => crate::instruction::ThreadExec {}.data(),
+        }
+    }
+
+    /// The exec instruction running `next_instruction`, whose accounts are appended after its program,
+    /// with the `PAYER_PUBKEY` placeholder substituted with the signatory.
+    pub fn exec_ix(
+        &self,
+        next_instruction: &SerializableInstruction,
+        thread_pubkey: Pubkey,
+        signatory_pubkey: Pubkey,
+        worker_pubkey: Pubkey,
+    ) -> Instruction {
+        let mut accounts = self.exec_accounts(thread_pubkey, signatory_pubkey, worker_pubkey);
+        accounts.push(AccountMeta::new_readonly(
+            next_instruction.program_id,
+            false,
+        ));
+        accounts.extend(next_instruction.accounts.iter().map(|acc| {
+            let pubkey = if acc.pubkey == PAYER_PUBKEY {
+                signatory_pubkey
+            } else {
+                acc.pubkey
+            };
+            match acc.is_writable {
+                true => AccountMeta::new(pubkey, false),
+                false => AccountMeta::new_readonly(pubkey, false),
+            }
+        }));
+        Instruction {
+            program_id: self.program_id(),
+            accounts,
+            data: self.exec_data(),
+        }
+    }
+
     pub fn pubkey(&self) -> Pubkey {
         match self {
             Self::V1(_) => Thread::pubkey(self.authority(), self.id(), self.domain()),
@@ -0,0 +1,127 @@
+#!/usr/bin/env bash
+
+# End-to-end check of the plugin on a localnet without a worker pool.
+# Starts a solana-test-validator with the programs and the plugin in localnet mode,
+# registers a worker and checks its account, creates a thread with the CLI and waits for the plugin to execute it,
+# then runs the CLI tests which need a localnet.
+#
+# Requires the programs built with `anchor build`, the plugin built with `cargo build`,
+# and the solana and spl-token CLIs in the PATH.
+
+set -e
+
+timeout_secs=${TIMEOUT_SECS:-60}
+root="$(cd "$(dirname "$0")/../.."; pwd)"
+workdir="$(mktemp -d)"
+ledger="$workdir/test-ledger"
+
+case "$(uname)" in
+  Darwin) libpath="$root/target/debug/libsablier_plugin.dylib" ;;
+  *) libpath="$root/target/debug/libsablier_plugin.so" ;;
+esac
+for file in "$libpath" "$root/target/deploy/sablier_network_program.so" "$root/target/deploy/sablier_thread_program.so"; do
+  if [[ ! -f "$file" ]]; then
+    echo "Error: $file not found, build the programs and the plugin first"
+    exit 1
+  fi
+done
+
+# Keep the solana and sablier CLI configs of the user untouched.
+export HOME="$workdir"
+solana config set --url http://127.0.0.1:8899 --commitment confirmed >/dev/null
+solana-keygen new --no-bip39-passphrase --silent --outfile "$HOME/.config/solana/id.json"
+solana-keygen new --no-bip39-passphrase --silent --outfile "$workdir/signatory.json"
+
+cat >"$workdir/geyser-plugin-config.json" <<EOF
+{
+  "libpath": "$libpath",
+  "keypath": "$workdir/signatory.json",
+  "localnet": true
+}
+EOF
+
+cleanup() {
+  if [[ -n "$validator_pid" ]]; then
+    kill "$validator_pid" 2>/dev/null || true
+  fi
+  rm -rf "$workdir"
+}
+trap cleanup EXIT
+
+solana-test-validator \
+  --reset \
+  --quiet \
+  --ledger "$ledger" \
+  --bpf-program "$(solana address -k "$root/target/deploy/sablier_network_program-keypair.json")" "$root/target/deploy/sablier_network_program.so" \
+  --bpf-program "$(solana address -k "$root/target/deploy/sablier_thread_program-keypair.json")" "$root/target/deploy/sablier_thread_program.so" \
+  --geyser-plugin-config "$workdir/geyser-plugin-config.json" &
+validator_pid=$!
+
+until solana cluster-version >/dev/null 2>&1; do
+  sleep 1
+done
+
+sablier() {
+  cargo run --quiet --manifest-path "$root/Cargo.toml" --bin sablier -- "$@"
+}
+
+# Initialize the network and register the worker, it is not delegated to so the pool stays empty.
+solana airdrop 100 >/dev/null
+solana airdrop 10 "$workdir/signatory.json" >/dev/null
+mint="$(spl-token create-token --decimals 8 | awk '/Creating token/ { print $3 }')"
+sablier initialize --mint "$mint"
+sablier worker create --signatory "$workdir/signatory.json" >/dev/null
+
+# The registered worker is the first one, owned by the payer and signed for by the signatory.
+expect_worker() {
+  local worker
+  worker="$(sablier worker get 0 --output json)"
+  for field in "$@"; do
+    if ! grep -qF "$field" <<<"$worker"; then
+      echo "Error: the worker has no $field field"
+      echo "$worker"
+      exit 1
+    fi
+  done
+}
+expect_worker '"id": 0' "\"authority\": \"$(solana address)\"" "\"signatory\": \"$(solana address -k "$workdir/signatory.json")\""
+sablier worker update 0 --commission-rate 10 >/dev/null
+expect_worker '"commission_rate": 10'
+
+# A memo instruction, which only needs the thread to execute it.
+cat >"$workdir/kickoff_instruction.json" <<EOF
+{
+  "program_id": "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",
+  "accounts": [],
+  "data": [115, 97, 98, 108, 105, 101, 114]
+}
+EOF
+sablier thread create --id e2e --kickoff_instruction "$workdir/kickoff_instruction.json" --cron "* * * * * *"
+
+# Only a thread which was not executed yet is waited for, a usage error of the CLI fails right away.
+elapsed=0
+while true; do
+  status=0
+  thread="$(sablier thread get e2e 2>&1)" || status=$?
+  # Clap exits with 2 when it rejects the arguments.
+  if ((status == 2)); then
+    echo "Error: sablier thread get was rejected by the CLI"
+    echo "$thread"
+    exit 1
+  fi
+  if ((status == 0)) && grep -q "last_exec_at" <<<"$thread"; then
+    break
+  fi
+  if ((elapsed >= timeout_secs)); then
+    echo "Error: the thread was not executed within ${timeout_secs}s, see $ledger/validator.log"
+    trap - EXIT
+    kill "$validator_pid" 2>/dev/null || true
+    exit 1
+  fi
+  sleep 1
+  elapsed=$((elapsed + 1))
+done
+echo "The thread was executed by the plugin in localnet mode"
+
+# The CLI tests ignored without a localnet, they share the config of this one through HOME.
+cargo test --quiet --manifest-path "$root/Cargo.toml" -p sablier-cli --test thread_domain -- --ignored
@@ -0,0 +1,299 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, LocalResult, NaiveDateTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use sablier_cron::{error::Error, Schedule};
+
+/// The prefix attaching an IANA timezone to a cron schedule, e.g. `TZ=America/New_York 0 0 9 * * * *`.
+pub const TIMEZONE_PREFIX: &str = "TZ=";
+
+/// The fields of a longhand cron expression, the year is optional.
+const FIELD_NAMES: [&str; 7] = [
+    "seconds",
+    "minutes",
+    "hours",
+    "day of month",
+    "month",
+    "day of week",
+    "year",
+];
+
+/// Max number of minutes to look ahead for the end of a skipped local time range.
+const MAX_GAP_MINUTES: i64 = 24 * 60;
+
+/// A cron schedule, evaluated in UTC unless it is prefixed with a timezone.
+#[derive(Clone, Debug)]
+pub struct TimezoneSchedule {
+    pub schedule: Schedule,
+    pub timezone: Option<Tz>,
+}
+
+#[derive(Debug)]
+pub enum TimezoneScheduleError {
+    InvalidSchedule(Error),
+    /// The field of the expression the parser rejected, by position.
+    InvalidField {
+        position: usize,
+        name: &'static str,
+        value: String,
+    },
+    InvalidFieldCount(usize),
+    InvalidTimezone(String),
+    /// The thread program evaluates the schedules in UTC and does not accept the timezone prefix.
+    TimezoneNotSupported,
+}
+
+impl std::fmt::Display for TimezoneScheduleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidSchedule(err) => write!(f, "invalid schedule: {}", err),
+            Self::InvalidField {
+                position,
+                name,
+                value,
+            } => write!(
+                f,
+                "invalid schedule: field {} ({}) `{}` is invalid",
+                position + 1,
+                name,
+                value
+            ),
+            Self::InvalidFieldCount(count) => write!(
+                f,
+                "invalid schedule: expected 6 or 7 fields (seconds minutes hours day-of-month month day-of-week [year]), found {}",
+                count
+            ),
+            Self::InvalidTimezone(timezone) => write!(f, "invalid timezone: {}", timezone),
+            Self::TimezoneNotSupported => write!(
+                f,
+                "the {} prefix is not supported by the thread program, which evaluates schedules in UTC",
+                TIMEZONE_PREFIX
+            ),
+        }
+    }
+}
+
+/// Parses a cron expression, locating the offending field when it is invalid.
+pub fn parse_schedule(expression: &str) -> Result<Schedule, TimezoneScheduleError> {
+    Schedule::from_str(expression).map_err(|err| invalid_schedule(expression, err))
+}
+
+/// Parses the schedule of a cron thread, the way the thread program evaluates it on kickoff.
+pub fn parse_thread_schedule(expression: &str) -> Result<Schedule, TimezoneScheduleError> {
+    if expression.trim().starts_with(TIMEZONE_PREFIX) {
+        return Err(TimezoneScheduleError::TimezoneNotSupported);
+    }
+    parse_schedule(expression)
+}
+
+/// Locates the field of an invalid longhand expression, by parsing each field alone.
+fn invalid_schedule(expression: &str, err: Error) -> TimezoneScheduleError {
+    let fields: Vec<&str> = expression.split_whitespace().collect();
+    if fields.first().map_or(true, |field| field.starts_with('@')) {
+        return TimezoneScheduleError::InvalidSchedule(err);
+    }
+    if fields.len() < 6 || fields.len() > FIELD_NAMES.len() {
+        return TimezoneScheduleError::InvalidFieldCount(fields.len());
+    }
+    for (position, value) in fields.iter().enumerate() {
+        let mut probe = ["*"; 6].to_vec();
+        if position < probe.len() {
+            probe[position] = value;
+        } else {
+            probe.push(value);
+        }
+        if Schedule::from_str(&probe.join(" ")).is_err() {
+            return TimezoneScheduleError::InvalidField {
+                position,
+                name: FIELD_NAMES[position],
+                value: value.to_string(),
+            };
+        }
+    }
+    TimezoneScheduleError::InvalidSchedule(err)
+}
+
+impl FromStr for TimezoneSchedule {
+    type Err = TimezoneScheduleError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let (timezone, expression) = match value.strip_prefix(TIMEZONE_PREFIX) {
+            None => (None, value),
+            Some(rest) => {
+                let (timezone, expression) =
+                    rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                let timezone = Tz::from_str(timezone)
+                    .map_err(|_| TimezoneScheduleError::InvalidTimezone(timezone.to_string()))?;
+                (Some(timezone), expression.trim_start())
+            }
+        };
+        Ok(Self {
+            schedule: parse_schedule(expression)?,
+            timezone,
+        })
+    }
+}
+
+impl TimezoneSchedule {
+    /// Returns the unix timestamp of the first moment strictly after `after`.
+    ///
+    /// With a timezone, the schedule is matched against the local wall clock:
+    /// - a local time repeated when clocks fall back fires once, at its first occurrence.
+    /// - a local time skipped when clocks spring forward fires at the first valid instant after the gap.
+    pub fn next_after(&self, after: i64) -> Option<i64> {
+        let after_utc = DateTime::from_timestamp(after, 0)?;
+        let Some(timezone) = self.timezone else {
+            return self
+                .schedule
+                .next_after(&after_utc)
+                .map(|datetime| datetime.timestamp());
+        };
+
+        // Walk the local wall clock, using UTC as a timezone without transitions.
+        let mut cursor = after_utc.with_timezone(&timezone).naive_local();
+        loop {
+            let candidate = self
+                .schedule
+                .next_after(&Utc.from_utc_datetime(&cursor))?
+                .naive_utc();
+            let timestamp = match timezone.from_local_datetime(&candidate) {
+                LocalResult::Single(datetime) => datetime.timestamp(),
+                LocalResult::Ambiguous(earliest, _latest) => earliest.timestamp(),
+                LocalResult::None => end_of_gap(&timezone, candidate)?,
+            };
+            if timestamp > after {
+                return Some(timestamp);
+            }
+            cursor = candidate;
+        }
+    }
+
+    /// Returns the unix timestamps of the next `count` moments after `after`.
+    pub fn upcoming(&self, after: i64, count: usize) -> Vec<i64> {
+        let mut moments = Vec::with_capacity(count);
+        let mut cursor = after;
+        while moments.len() < count {
+            match self.next_after(cursor) {
+                None => break,
+                Some(timestamp) => {
+                    moments.push(timestamp);
+                    cursor = timestamp;
+                }
+            }
+        }
+        moments
+    }
+}
+
+/// Returns the first valid instant after a skipped local time.
+/// Transitions happen on minute boundaries, so the gap is walked minute by minute.
+fn end_of_gap(timezone: &Tz, skipped: NaiveDateTime) -> Option<i64> {
+    let mut local = skipped.with_second(0)?;
+    for _ in 0..MAX_GAP_MINUTES {
+        local += Duration::minutes(1);
+        if let Some(datetime) = timezone.from_local_datetime(&local).earliest() {
+            return Some(datetime.timestamp());
+        }
+    }
+    None
+}
+
+/// Returns the unix timestamp of the first moment of the schedule strictly after `after`.
+pub fn next_timestamp(after: i64, schedule: &str) -> Option<i64> {
+    TimezoneSchedule::from_str(schedule).ok()?.next_after(after)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> i64 {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+            .and_utc()
+            .timestamp()
+    }
+
+    #[test]
+    fn schedules_without_timezone_are_utc() {
+        let after = utc(2024, 1, 1, 0, 0);
+        assert_eq!(
+            next_timestamp(after, "0 0 9 * * * *"),
+            Some(utc(2024, 1, 1, 9, 0))
+        );
+    }
+
+    #[test]
+    fn local_time_follows_dst() {
+        let schedule = TimezoneSchedule::from_str("TZ=America/New_York 0 0 9 * * * *").unwrap();
+        // 9am EST is 14:00 UTC, 9am EDT is 13:00 UTC.
+        assert_eq!(
+            schedule.next_after(utc(2024, 3, 9, 0, 0)),
+            Some(utc(2024, 3, 9, 14, 0))
+        );
+        assert_eq!(
+            schedule.next_after(utc(2024, 3, 10, 0, 0)),
+            Some(utc(2024, 3, 10, 13, 0))
+        );
+    }
+
+    #[test]
+    fn repeated_local_time_fires_once() {
+        // Clocks fall back from 2am EDT to 1am EST on 2024-11-03, so 1:30 happens twice.
+        let schedule = TimezoneSchedule::from_str("TZ=America/New_York 0 30 1 * * * *").unwrap();
+        let after = utc(2024, 11, 3, 0, 0);
+        assert_eq!(
+            schedule.upcoming(after, 2),
+            vec![utc(2024, 11, 3, 5, 30), utc(2024, 11, 4, 6, 30)]
+        );
+    }
+
+    #[test]
+    fn skipped_local_time_fires_at_the_end_of_the_gap() {
+        // Clocks spring forward from 2am EST to 3am EDT on 2024-03-10, so 2:30 never happens.
+        let schedule = TimezoneSchedule::from_str("TZ=America/New_York 0 30 2 * * * *").unwrap();
+        let after = utc(2024, 3, 10, 0, 0);
+        assert_eq!(
+            schedule.upcoming(after, 2),
+            vec![utc(2024, 3, 10, 7, 0), utc(2024, 3, 11, 6, 30)]
+        );
+    }
+
+    #[test]
+    fn invalid_timezone_is_rejected() {
+        assert!(matches!(
+            TimezoneSchedule::from_str("TZ=Mars/Olympus 0 0 9 * * * *"),
+            Err(TimezoneScheduleError::InvalidTimezone(_))
+        ));
+        assert!(matches!(
+            TimezoneSchedule::from_str("TZ=UTC not a schedule"),
+            Err(TimezoneScheduleError::InvalidFieldCount(3))
+        ));
+    }
+
+    #[test]
+    fn invalid_field_is_located() {
+        assert!(matches!(
+            parse_schedule("0 0 25 * * *"),
+            Err(TimezoneScheduleError::InvalidField {
+                position: 2,
+                name: "hours",
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse_schedule("0 0 * * *"),
+            Err(TimezoneScheduleError::InvalidFieldCount(5))
+        ));
+        assert!(matches!(
+            parse_thread_schedule("TZ=UTC 0 0 9 * * *"),
+            Err(TimezoneScheduleError::TimezoneNotSupported)
+        ));
+        assert!(parse_thread_schedule("@daily").is_ok());
+    }
+}
@@ -1,10 +1,52 @@
+use std::{fmt::Display, str::FromStr};
+
 const EXPLORER_URL: &str = "https://explorer.solana.com";
+const SOLSCAN_URL: &str = "https://solscan.io";
+const SOLANA_FM_URL: &str = "https://solana.fm";
 const CK_EXPLORER_URL: &str = "https://explorer.sablier.dev";
 
-#[derive(Default)]
+const MAINNET_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+const TESTNET_GENESIS_HASH: &str = "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY";
+const DEVNET_GENESIS_HASH: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+
+/// The block explorer the links point to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExplorerSite {
+    /// The official explorer, explorer.solana.com.
+    #[default]
+    Solana,
+    Solscan,
+    SolanaFm,
+}
+
+impl FromStr for ExplorerSite {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "solana" => Ok(Self::Solana),
+            "solscan" => Ok(Self::Solscan),
+            "solana.fm" => Ok(Self::SolanaFm),
+            _ => Err(format!("unknown explorer: {}", value)),
+        }
+    }
+}
+
+impl ExplorerSite {
+    fn url(&self) -> &'static str {
+        match self {
+            Self::Solana => EXPLORER_URL,
+            Self::Solscan => SOLSCAN_URL,
+            Self::SolanaFm => SOLANA_FM_URL,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct Explorer {
     cluster: String,
     custom_rpc: Option<String>,
+    site: ExplorerSite,
 }
 
 impl From<String> for Explorer {
@@ -44,29 +86,47 @@ impl Explorer {
         Self {
             cluster: "custom".into(),
             custom_rpc: Some(custom_rpc),
+            ..Default::default()
         }
     }
 
-    /// Ex: https://explorer.solana.com/tx/{tx}
+    /// The explorer of the public cluster with this genesis hash,
+    /// or of the custom cluster served at the RPC url, e.g. a localnet.
+    pub fn from_genesis_hash(genesis_hash: &str, json_rpc_url: String) -> Self {
+        match genesis_hash {
+            MAINNET_GENESIS_HASH => Explorer::mainnet(),
+            TESTNET_GENESIS_HASH => Explorer::testnet(),
+            DEVNET_GENESIS_HASH => Explorer::devnet(),
+            _ => Explorer::custom(json_rpc_url),
+        }
+    }
+
+    pub fn with_site(mut self, site: ExplorerSite) -> Self {
+        self.site = site;
+        self
+    }
+
+    /// Ex: https://explorer.solana.com/address/{address}
     ///     ?cluster=custom
     ///     &customUrl=http://localhost:8899
-    pub fn tx_url<T: std::fmt::Display>(&self, tx: T) -> String {
-        let url = format!("{}/tx/{}?cluster={}", EXPLORER_URL, tx, self.cluster);
-        if self.cluster == "custom" {
-            url + "&customUrl=" + self.custom_rpc.as_ref().unwrap()
-        } else {
-            url
+    pub fn account_url<T: Display>(&self, address: T) -> String {
+        match self.site() {
+            ExplorerSite::Solscan => self.url("account", address),
+            ExplorerSite::Solana | ExplorerSite::SolanaFm => self.url("address", address),
         }
     }
 
+    /// Ex: https://explorer.solana.com/tx/{tx}
+    ///     ?cluster=custom
+    ///     &customUrl=http://localhost:8899
+    pub fn tx_url<T: Display>(&self, tx: T) -> String {
+        self.url("tx", tx)
+    }
+
     /// Ex: https://explorer.sablier.dev/thread/{thread}
     ///     ?network=custom
     ///     &customRPC=http://localhost:8899
-    pub fn thread_url<T: std::fmt::Display, U: std::fmt::Display>(
-        &self,
-        thread: T,
-        program_id: U,
-    ) -> String {
+    pub fn thread_url<T: Display, U: Display>(&self, thread: T, program_id: U) -> String {
         let url = format!(
             "{}/address/{}?programID={}&network={}",
             CK_EXPLORER_URL, thread, program_id, self.cluster
@@ -77,4 +137,89 @@ impl Explorer {
             url
         }
     }
+
+    /// The custom clusters are only browsable with the official explorer, which queries their RPC.
+    fn site(&self) -> ExplorerSite {
+        match self.custom_rpc {
+            Some(_) => ExplorerSite::Solana,
+            None => self.site,
+        }
+    }
+
+    fn url<T: Display>(&self, path: &str, value: T) -> String {
+        let site = self.site();
+        let url = format!("{}/{}/{}", site.url(), path, value);
+        match (site, &self.custom_rpc) {
+            (_, Some(custom_rpc)) => format!("{}?cluster=custom&customUrl={}", url, custom_rpc),
+            (ExplorerSite::Solana, None) => format!("{}?cluster={}", url, self.cluster),
+            (_, None) if self.cluster == "mainnet-beta" => url,
+            (ExplorerSite::Solscan, None) => format!("{}?cluster={}", url, self.cluster),
+            (ExplorerSite::SolanaFm, None) => format!("{}?cluster={}-solana", url, self.cluster),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi";
+
+    #[test]
+    fn detects_the_cluster_from_the_genesis_hash() {
+        let url = "http://localhost:8899".to_string();
+        assert_eq!(
+            Explorer::from_genesis_hash(DEVNET_GENESIS_HASH, url.clone()).account_url(ADDRESS),
+            format!("{}/address/{}?cluster=devnet", EXPLORER_URL, ADDRESS)
+        );
+        assert_eq!(
+            Explorer::from_genesis_hash(MAINNET_GENESIS_HASH, url.clone()).tx_url(ADDRESS),
+            format!("{}/tx/{}?cluster=mainnet-beta", EXPLORER_URL, ADDRESS)
+        );
+        assert_eq!(
+            Explorer::from_genesis_hash(ADDRESS, url).account_url(ADDRESS),
+            format!(
+                "{}/address/{}?cluster=custom&customUrl=http://localhost:8899",
+                EXPLORER_URL, ADDRESS
+            )
+        );
+    }
+
+    #[test]
+    fn links_to_the_selected_site() {
+        assert_eq!(
+            Explorer::devnet()
+                .with_site(ExplorerSite::Solscan)
+                .account_url(ADDRESS),
+            format!("{}/account/{}?cluster=devnet", SOLSCAN_URL, ADDRESS)
+        );
+        assert_eq!(
+            Explorer::mainnet()
+                .with_site(ExplorerSite::Solscan)
+                .tx_url(ADDRESS),
+            format!("{}/tx/{}", SOLSCAN_URL, ADDRESS)
+        );
+        assert_eq!(
+            Explorer::testnet()
+                .with_site(ExplorerSite::SolanaFm)
+                .account_url(ADDRESS),
+            format!(
+                "{}/address/{}?cluster=testnet-solana",
+                SOLANA_FM_URL, ADDRESS
+            )
+        );
+    }
+
+    #[test]
+    fn links_custom_clusters_to_the_official_explorer() {
+        assert_eq!(
+            Explorer::custom("http://127.0.0.1:8899".into())
+                .with_site(ExplorerSite::Solscan)
+                .tx_url(ADDRESS),
+            format!(
+                "{}/tx/{}?cluster=custom&customUrl=http://127.0.0.1:8899",
+                EXPLORER_URL, ADDRESS
+            )
+        );
+    }
 }
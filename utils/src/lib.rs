@@ -1,4 +1,6 @@
 pub mod account;
+#[cfg(feature = "cron")]
+pub mod cron;
 pub mod explorer;
 pub mod pubkey;
 pub mod pyth;
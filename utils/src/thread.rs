@@ -95,7 +95,42 @@ pub enum Trigger {
     },
 
     /// Allows a thread to be kicked off according to a period seconds number.
+    /// Kept for the layout of existing threads, it behaves as an interval trigger which doesn't skip missed moments.
     Periodic { delay: u64 },
+
+    /// Allows a thread to be kicked off every interval of seconds, from its creation.
+    Interval {
+        /// The number of seconds between the triggering moments, at least the minimum of the thread program.
+        interval_seconds: u64,
+
+        /// Boolean value indicating whether triggering moments may be skipped if they are missed (e.g. due to network downtime).
+        /// If false, any "missed" triggering moments will simply be executed as soon as the network comes back online.
+        skippable: bool,
+    },
+}
+
+impl Trigger {
+    /// Returns the interval in seconds and the skippability of a periodic or interval trigger.
+    pub fn interval(&self) -> Option<(u64, bool)> {
+        match self {
+            Self::Periodic { delay } => Some((*delay, false)),
+            Self::Interval {
+                interval_seconds,
+                skippable,
+            } => Some((*interval_seconds, *skippable)),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the thread may be updated from this trigger to the other one.
+    /// A periodic trigger may be migrated to an interval trigger.
+    pub fn is_same_kind(&self, other: &Trigger) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+            || matches!(
+                (self, other),
+                (Self::Periodic { .. }, Self::Interval { .. })
+            )
+    }
 }
 
 /// Operators for describing how to compare two values to one another.  